@@ -0,0 +1,124 @@
+//! Derive macro for SoAKit schemas.
+//!
+//! `#[derive(SoaSchema)]` on a struct with named fields implements
+//! `soakit::SoaSchema` for it, registering one typed field per struct field, and
+//! adds typed `get_<field>` and `set_<field>` functions reading and writing the
+//! field's column in a bulk. Use it through the `derive` feature of `soakit`,
+//! which re-exports the macro.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Fields, parse_macro_input};
+
+/// Derive `soakit::SoaSchema` and typed column accessors for a struct.
+///
+/// Every field must have a type implementing `soakit::ScalarType` (`i64`, `f64`,
+/// `bool` or `String`). Fields are registered under their Rust names.
+#[proc_macro_derive(SoaSchema)]
+pub fn derive_soa_schema(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Generate the trait implementation and accessors for a struct.
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "SoaSchema cannot be derived for generic structs",
+        ));
+    }
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    "SoaSchema requires a struct with named fields",
+                ));
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "SoaSchema can only be derived for structs",
+            ));
+        }
+    };
+
+    let idents: Vec<_> = fields
+        .iter()
+        .filter_map(|field| field.ident.as_ref())
+        .collect();
+    let types: Vec<_> = fields.iter().map(|field| &field.ty).collect();
+    let names: Vec<_> = idents
+        .iter()
+        .map(|ident| ident.to_string().trim_start_matches("r#").to_string())
+        .collect();
+    let getters = idents.iter().map(|ident| format_ident!("get_{}", ident));
+    let setters = idents.iter().map(|ident| format_ident!("set_{}", ident));
+    let get_docs = names
+        .iter()
+        .map(|name| format!("Get the `{}` column of a bulk.", name));
+    let set_docs = names.iter().map(|name| {
+        format!(
+            "Set the `{}` column of a bulk, returning the updated bulk.",
+            name
+        )
+    });
+
+    Ok(quote! {
+        impl ::soakit::SoaSchema for #name {
+            fn field_names() -> ::std::vec::Vec<&'static str> {
+                ::std::vec![#(#names),*]
+            }
+
+            fn register_fields(registry: &mut ::soakit::Registry) -> ::soakit::Result<()> {
+                #(registry.register_typed::<#types>(#names)?;)*
+                ::std::result::Result::Ok(())
+            }
+
+            fn into_columns(
+                records: ::std::vec::Vec<Self>,
+            ) -> ::std::collections::BTreeMap<::std::string::String, ::soakit::Value> {
+                #(let mut #idents: ::std::vec::Vec<#types> =
+                    ::std::vec::Vec::with_capacity(records.len());)*
+                for record in records {
+                    #(#idents.push(record.#idents);)*
+                }
+                let mut columns = ::std::collections::BTreeMap::new();
+                #(let _ = columns.insert(
+                    ::std::string::ToString::to_string(#names),
+                    ::soakit::Value::from(#idents),
+                );)*
+                columns
+            }
+        }
+
+        impl #name {
+            #(
+                #[doc = #get_docs]
+                pub fn #getters(
+                    bulk: &::soakit::Bulk,
+                    registry: &::soakit::Registry,
+                ) -> ::soakit::Result<::std::vec::Vec<#types>> {
+                    ::std::convert::TryFrom::try_from(bulk.get(registry, #names)?)
+                }
+
+                #[doc = #set_docs]
+                pub fn #setters(
+                    bulk: &::soakit::Bulk,
+                    registry: &::soakit::Registry,
+                    values: ::std::vec::Vec<#types>,
+                ) -> ::soakit::Result<::soakit::Bulk> {
+                    let values = values.into_iter().map(::soakit::Value::from).collect();
+                    bulk.set(registry, #names, values)
+                }
+            )*
+        }
+    })
+}
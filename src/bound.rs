@@ -0,0 +1,313 @@
+/// Bulks bound to their registry.
+///
+/// Every [`Bulk`] method that reads or writes fields takes the [`Registry`]
+/// describing them, and passing the wrong one fails in confusing ways (unknown
+/// fields, or values checked by another field's validator).
+/// [`Bulk::with_registry`] binds a bulk to a shared registry once, giving a
+/// [`BoundBulk`] whose methods take no registry. The explicit-registry methods
+/// remain available on the bulk itself through [`BoundBulk::bulk`].
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::{Bulk, Registry, Value};
+/// use std::sync::Arc;
+///
+/// let mut registry = Registry::new();
+/// registry.register_int("age").unwrap();
+/// let registry = Arc::new(registry);
+///
+/// let people = Bulk::new(2).unwrap().with_registry(Arc::clone(&registry));
+/// let people = people.set("age", vec![Value::ScalarInt(30), Value::ScalarInt(40)]).unwrap();
+/// assert_eq!(people.get("age").unwrap(), Value::VectorInt(vec![30, 40]));
+/// assert_eq!(people.partition_by("age").unwrap().len(), 2);
+/// ```
+use crate::bulk::{Bulk, FieldSummary, SortOrder, Violation};
+use crate::error::Result;
+use crate::meta::Registry;
+use crate::row::Rows;
+use crate::value::Value;
+use crate::view::View;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// A bulk together with the registry describing its fields, created with
+/// [`Bulk::with_registry`].
+///
+/// Methods mirror those of [`Bulk`] without the registry argument; updates
+/// return a new bound bulk sharing the same registry.
+#[derive(Clone)]
+pub struct BoundBulk {
+    /// The bulk
+    bulk: Bulk,
+    /// The registry describing the bulk's fields
+    registry: Arc<Registry>,
+}
+
+impl Bulk {
+    /// Bind the bulk to the registry describing its fields.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry used by every method of the bound bulk
+    pub const fn with_registry(self, registry: Arc<Registry>) -> BoundBulk {
+        BoundBulk {
+            bulk: self,
+            registry,
+        }
+    }
+}
+
+impl BoundBulk {
+    /// Get the bulk, for the methods that take an explicit registry.
+    pub const fn bulk(&self) -> &Bulk {
+        &self.bulk
+    }
+
+    /// Get the registry the bulk is bound to.
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    /// Unbind the bulk, returning it and its registry.
+    pub fn into_parts(self) -> (Bulk, Arc<Registry>) {
+        (self.bulk, self.registry)
+    }
+
+    /// Get the number of elements, as by [`Bulk::count`].
+    pub const fn count(&self) -> usize {
+        self.bulk.count()
+    }
+
+    /// Bind an updated bulk to the same registry.
+    fn rebind(&self, bulk: Bulk) -> Self {
+        Self {
+            bulk,
+            registry: Arc::clone(&self.registry),
+        }
+    }
+
+    /// Get the values of a field, as by [`Bulk::get`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::get`].
+    pub fn get(&self, field: &str) -> Result<Value> {
+        self.bulk.get(&self.registry, field)
+    }
+
+    /// Set the values of a field, as by [`Bulk::set`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::set`].
+    pub fn set(&self, field: &str, values: Vec<Value>) -> Result<Self> {
+        self.bulk
+            .set(&self.registry, field, values)
+            .map(|bulk| self.rebind(bulk))
+    }
+
+    /// Set the values of several fields at once, as by [`Bulk::set_many`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::set_many`].
+    pub fn set_many(&self, columns: BTreeMap<&str, Vec<Value>>) -> Result<Self> {
+        self.bulk
+            .set_many(&self.registry, columns)
+            .map(|bulk| self.rebind(bulk))
+    }
+
+    /// Set every element of a field to one value, as by [`Bulk::set_broadcast`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::set_broadcast`].
+    pub fn set_broadcast(&self, field: &str, value: Value) -> Result<Self> {
+        self.bulk
+            .set_broadcast(&self.registry, field, value)
+            .map(|bulk| self.rebind(bulk))
+    }
+
+    /// Set one element of a field, as by [`Bulk::set_element`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::set_element`].
+    pub fn set_element(&self, field: &str, idx: usize, value: Value) -> Result<Self> {
+        self.bulk
+            .set_element(&self.registry, field, idx, value)
+            .map(|bulk| self.rebind(bulk))
+    }
+
+    /// Append one element, as by [`Bulk::push_row`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::push_row`].
+    pub fn push_row(&mut self, record: BTreeMap<String, Value>) -> Result<()> {
+        self.bulk.push_row(&self.registry, record)
+    }
+
+    /// Get the values of every field of a group, as by [`Bulk::get_group`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::get_group`].
+    pub fn get_group(&self, group: &str) -> Result<BTreeMap<String, Value>> {
+        self.bulk.get_group(&self.registry, group)
+    }
+
+    /// Set every field of a group at once, as by [`Bulk::set_group`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::set_group`].
+    pub fn set_group(&self, group: &str, columns: Vec<Vec<Value>>) -> Result<Self> {
+        self.bulk
+            .set_group(&self.registry, group, columns)
+            .map(|bulk| self.rebind(bulk))
+    }
+
+    /// Keep only the data of a group's fields, as by [`Bulk::select_group`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::select_group`].
+    pub fn select_group(&self, group: &str) -> Result<Self> {
+        self.bulk
+            .select_group(&self.registry, group)
+            .map(|bulk| self.rebind(bulk))
+    }
+
+    /// Split the bulk by the values of a field, as by [`Bulk::partition_by`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::partition_by`].
+    pub fn partition_by(&self, field: &str) -> Result<Vec<View>> {
+        self.bulk.partition_by(&self.registry, field)
+    }
+
+    /// Iterate over the elements as rows, as by [`Bulk::iter_rows`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::iter_rows`].
+    pub fn iter_rows(&self) -> Result<Rows<'_>> {
+        self.bulk.iter_rows(&self.registry)
+    }
+
+    /// Sort the elements by one or more fields, as by [`Bulk::sort_by`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::sort_by`].
+    pub fn sort_by(&self, keys: &[(&str, SortOrder)]) -> Result<Self> {
+        self.bulk
+            .sort_by(&self.registry, keys)
+            .map(|bulk| self.rebind(bulk))
+    }
+
+    /// Keep the elements whose value of a field satisfies a predicate, as by
+    /// [`Bulk::filter_field`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::filter_field`].
+    pub fn filter_field<F>(&self, field: &str, predicate: F) -> Result<Self>
+    where
+        F: Fn(&Value) -> bool,
+    {
+        self.bulk
+            .filter_field(&self.registry, field, predicate)
+            .map(|bulk| self.rebind(bulk))
+    }
+
+    /// Summarize every field, as by [`Bulk::describe`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::describe`].
+    pub fn describe(&self) -> Result<BTreeMap<String, FieldSummary>> {
+        self.bulk.describe(&self.registry)
+    }
+
+    /// Check every stored value against its field, as by [`Bulk::validate_all`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::validate_all`].
+    pub fn validate_all(&self) -> Result<Vec<Violation>> {
+        self.bulk.validate_all(&self.registry)
+    }
+
+    /// List the required fields without data, as by [`Bulk::validate_complete`].
+    pub fn validate_complete(&self) -> Vec<String> {
+        self.bulk.validate_complete(&self.registry)
+    }
+
+    /// Render the bulk as a text table, as by [`Bulk::to_table_string`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::to_table_string`].
+    pub fn to_table_string(&self, max_rows: usize) -> Result<String> {
+        self.bulk.to_table_string(&self.registry, max_rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bound_bulk_matches_explicit_registry() {
+        let mut registry = Registry::new();
+        registry.register_int("age").unwrap();
+        registry.register_string("name").unwrap();
+        registry.set_required("name", true).unwrap();
+        let registry = Arc::new(registry);
+
+        let bound = Bulk::new(3).unwrap().with_registry(Arc::clone(&registry));
+        assert_eq!(bound.validate_complete(), vec!["name"]);
+        let ages = [30, 20, 40].map(Value::ScalarInt).to_vec();
+        let bound = bound.set("age", ages).unwrap();
+        let bound = bound
+            .set_broadcast("name", Value::ScalarString("x".to_string()))
+            .unwrap();
+        let bound = bound
+            .set_element("name", 1, Value::ScalarString("y".to_string()))
+            .unwrap();
+        assert!(Arc::ptr_eq(&bound.registry, &registry));
+        assert_eq!(
+            bound.get("age").unwrap(),
+            bound.bulk().get(&registry, "age").unwrap()
+        );
+
+        let sorted = bound.sort_by(&[("age", SortOrder::Desc)]).unwrap();
+        assert_eq!(
+            sorted.get("age").unwrap(),
+            Value::VectorInt(vec![40, 30, 20])
+        );
+        let young = bound.filter_field("age", |v| matches!(v, Value::ScalarInt(a) if *a < 35));
+        assert_eq!(young.unwrap().count(), 2);
+        assert_eq!(bound.partition_by("name").unwrap().len(), 2);
+        assert_eq!(bound.iter_rows().unwrap().count(), 3);
+        assert!(bound.validate_all().unwrap().is_empty() && bound.validate_complete().is_empty());
+        assert!(bound.get("missing").is_err());
+
+        let mut bound = bound;
+        let record = BTreeMap::from([
+            ("age".to_string(), Value::ScalarInt(50)),
+            ("name".to_string(), Value::ScalarString("z".to_string())),
+        ]);
+        bound.push_row(record).unwrap();
+        let (bulk, shared) = bound.into_parts();
+        assert_eq!(bulk.count(), 4);
+        assert_eq!(
+            bulk.get(&shared, "age").unwrap(),
+            Value::VectorInt(vec![30, 20, 40, 50])
+        );
+    }
+}
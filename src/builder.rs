@@ -0,0 +1,327 @@
+/// Fluent builders for registries and bulks.
+///
+/// [`RegistryBuilder`] registers fields with type-checking validators in a
+/// chain of calls, and [`BulkBuilder`] collects typed columns (`Vec<i64>`,
+/// `Vec<f64>`, ...) for a new bulk. Neither builder fails part-way: every
+/// field and column is checked once, when `build` is called.
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::builder::{BulkBuilder, RegistryBuilder};
+/// use soakit::Value;
+///
+/// let registry = RegistryBuilder::new()
+///     .with_int_field("age")
+///     .with_string_field("name")
+///     .build()
+///     .unwrap();
+///
+/// let bulk = BulkBuilder::new()
+///     .with_int_field("age", vec![25, 30])
+///     .with_string_field("name", vec!["Alice", "Bob"])
+///     .build(&registry)
+///     .unwrap();
+/// assert_eq!(bulk.get(&registry, "age").unwrap(), Value::VectorInt(vec![25, 30]));
+/// ```
+use crate::bulk::{Bulk, ElementId};
+use crate::error::{Result, SoAKitError};
+use crate::meta::{DerivedFunc, FieldType, Registry};
+use crate::value::Value;
+use std::collections::BTreeMap;
+
+/// A field waiting to be registered by [`RegistryBuilder::build`].
+struct PendingField {
+    /// Name of the field
+    name: String,
+    /// Validator for the field's values
+    validator: Box<dyn Fn(&Value) -> bool + Send + Sync>,
+    /// Dependencies of a derived field
+    dependencies: Vec<String>,
+    /// Function computing a derived field
+    derived_func: Option<DerivedFunc>,
+    /// Declared type of a typed field
+    field_type: Option<FieldType>,
+}
+
+/// Builder for a [`Registry`].
+///
+/// Fields are registered in the order they were added when [`build`](Self::build)
+/// is called, so derived fields should be added after their dependencies.
+#[derive(Default)]
+pub struct RegistryBuilder {
+    /// Fields to register, in order
+    fields: Vec<PendingField>,
+}
+
+impl RegistryBuilder {
+    /// Create an empty registry builder.
+    pub const fn new() -> Self {
+        Self { fields: Vec::new() }
+    }
+
+    /// Add a field with a custom validator.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the field
+    /// * `validator` - Function that validates values for this field
+    pub fn with_field(
+        mut self,
+        name: &str,
+        validator: Box<dyn Fn(&Value) -> bool + Send + Sync>,
+    ) -> Self {
+        self.fields.push(PendingField {
+            name: name.to_string(),
+            validator,
+            dependencies: Vec::new(),
+            derived_func: None,
+            field_type: None,
+        });
+        self
+    }
+
+    /// Add a field holding scalars of a given type, recorded in its metadata.
+    pub fn with_typed_field(mut self, name: &str, field_type: FieldType) -> Self {
+        self.fields.push(PendingField {
+            name: name.to_string(),
+            validator: field_type.validator(),
+            dependencies: Vec::new(),
+            derived_func: None,
+            field_type: Some(field_type),
+        });
+        self
+    }
+
+    /// Add a field holding `ScalarInt` values.
+    pub fn with_int_field(self, name: &str) -> Self {
+        self.with_typed_field(name, FieldType::Int)
+    }
+
+    /// Add a field holding `ScalarFloat` values.
+    pub fn with_float_field(self, name: &str) -> Self {
+        self.with_typed_field(name, FieldType::Float)
+    }
+
+    /// Add a field holding `ScalarBool` values.
+    pub fn with_bool_field(self, name: &str) -> Self {
+        self.with_typed_field(name, FieldType::Bool)
+    }
+
+    /// Add a field holding `ScalarString` values.
+    pub fn with_string_field(self, name: &str) -> Self {
+        self.with_typed_field(name, FieldType::String)
+    }
+
+    /// Add a derived field computed from other fields.
+    ///
+    /// The field's computed values are not validated.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the field
+    /// * `dependencies` - The fields passed to `func`, in order
+    /// * `func` - Function computing the field from its dependencies
+    pub fn with_derived_field(
+        mut self,
+        name: &str,
+        dependencies: Vec<String>,
+        func: DerivedFunc,
+    ) -> Self {
+        self.fields.push(PendingField {
+            name: name.to_string(),
+            validator: Box::new(|_: &Value| true),
+            dependencies,
+            derived_func: Some(func),
+            field_type: None,
+        });
+        self
+    }
+
+    /// Register every added field in a new registry.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Registry)` with every field registered.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Registry::register`] for the first field that
+    /// cannot be registered, such as an invalid name, a duplicate field or a
+    /// derived field without dependencies.
+    pub fn build(self) -> Result<Registry> {
+        let mut registry = Registry::new();
+        for field in self.fields {
+            let is_derived = field.derived_func.is_some();
+            registry.register(
+                field.name.clone(),
+                field.validator,
+                is_derived,
+                field.dependencies,
+                field.derived_func,
+            )?;
+            if let Some(field_type) = field.field_type {
+                registry.set_field_type(&field.name, field_type);
+            }
+        }
+        Ok(registry)
+    }
+}
+
+/// Builder for a [`Bulk`] from typed columns.
+///
+/// The number of elements is taken from the columns, which must all have the
+/// same length. [`build`](Self::build) validates the columns against a registry
+/// in one pass and stores them with [`Bulk::from_columns`].
+#[derive(Debug, Default)]
+pub struct BulkBuilder {
+    /// Columns to store, in the order they were added
+    columns: Vec<(String, Value)>,
+    /// Custom element identifiers, if any
+    ids: Option<Vec<ElementId>>,
+}
+
+impl BulkBuilder {
+    /// Create an empty bulk builder.
+    pub const fn new() -> Self {
+        Self {
+            columns: Vec::new(),
+            ids: None,
+        }
+    }
+
+    /// Assign custom element identifiers, as by [`Bulk::set_ids`].
+    pub fn with_ids<I, T>(mut self, ids: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<ElementId>,
+    {
+        self.ids = Some(ids.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Add a column given as a vector value, such as `Value::VectorInt`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the field
+    /// * `column` - The field's values, one per element
+    pub fn with_column(mut self, name: &str, column: Value) -> Self {
+        self.columns.push((name.to_string(), column));
+        self
+    }
+
+    /// Add an integer column.
+    pub fn with_int_field(self, name: &str, values: Vec<i64>) -> Self {
+        self.with_column(name, Value::VectorInt(values))
+    }
+
+    /// Add a float column.
+    pub fn with_float_field(self, name: &str, values: Vec<f64>) -> Self {
+        self.with_column(name, Value::VectorFloat(values))
+    }
+
+    /// Add a boolean column.
+    pub fn with_bool_field(self, name: &str, values: Vec<bool>) -> Self {
+        self.with_column(name, Value::VectorBool(values))
+    }
+
+    /// Add a string column.
+    pub fn with_string_field<S: Into<String>>(self, name: &str, values: Vec<S>) -> Self {
+        let values = values.into_iter().map(Into::into).collect();
+        self.with_column(name, Value::VectorString(values))
+    }
+
+    /// Build the bulk, validating every column against the registry.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with one element per column element and every field set.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldAlreadyExists`] if a field was added twice
+    /// - The errors of [`Bulk::from_columns`], and of [`Bulk::set_ids`] for the
+    ///   identifiers
+    pub fn build(self, registry: &Registry) -> Result<Bulk> {
+        let mut columns = BTreeMap::new();
+        for (name, column) in self.columns {
+            if columns.contains_key(&name) {
+                return Err(SoAKitError::FieldAlreadyExists(name));
+            }
+            let _ = columns.insert(name, column);
+        }
+        let bulk = Bulk::from_columns(registry, columns)?;
+        match self.ids {
+            Some(ids) => bulk.set_ids(ids),
+            None => Ok(bulk),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builders_defer_errors_to_build() {
+        let registry = RegistryBuilder::new()
+            .with_int_field("a")
+            .with_float_field("x")
+            .with_bool_field("flag")
+            .with_derived_field(
+                "twice",
+                vec!["a".to_string()],
+                Box::new(|args: &[Value]| match &args[0] {
+                    Value::VectorInt(v) => Ok(Value::VectorInt(v.iter().map(|x| x * 2).collect())),
+                    _ => Err(SoAKitError::InvalidArgument("expected ints".to_string())),
+                }),
+            )
+            .build()
+            .unwrap();
+        assert_eq!(registry.len(), 4);
+        let field_type = |field: &str| registry.get_metadata(field).unwrap().field_type;
+        assert_eq!(field_type("x"), Some(FieldType::Float));
+        assert_eq!(field_type("twice"), None);
+
+        let bulk = BulkBuilder::new()
+            .with_int_field("a", vec![1, 2, 3])
+            .with_float_field("x", vec![0.5, 1.5, 2.5])
+            .with_bool_field("flag", vec![true, false, true])
+            .build(&registry)
+            .unwrap();
+        assert_eq!(bulk.count(), 3);
+        assert_eq!(
+            bulk.get(&registry, "twice").unwrap(),
+            Value::VectorInt(vec![2, 4, 6])
+        );
+
+        let duplicate = RegistryBuilder::new()
+            .with_int_field("a")
+            .with_float_field("a");
+        assert!(duplicate.build().is_err());
+        let repeated = BulkBuilder::new()
+            .with_int_field("a", vec![1])
+            .with_int_field("a", vec![2]);
+        assert!(matches!(
+            repeated.build(&registry),
+            Err(SoAKitError::FieldAlreadyExists(_))
+        ));
+        let mistyped = BulkBuilder::new().with_string_field("a", vec!["one"]);
+        assert!(matches!(
+            mistyped.build(&registry),
+            Err(SoAKitError::ValidationFailed(_))
+        ));
+        let uneven = BulkBuilder::new()
+            .with_int_field("a", vec![1, 2])
+            .with_float_field("x", vec![1.0]);
+        assert!(matches!(
+            uneven.build(&registry),
+            Err(SoAKitError::LengthMismatch { .. })
+        ));
+    }
+}
@@ -4,13 +4,25 @@
 /// in SoAKit. It implements the Structure-of-Arrays pattern, storing each field
 /// as a separate array for improved cache locality and performance.
 use crate::error::{Result, SoAKitError};
-use crate::meta::Registry;
-use crate::util::filter_system_fields;
-use crate::value::Value;
+use crate::index::{FieldIndex, IndexKind};
+use crate::intern::InternedStrings;
+use crate::meta::{DerivedContext, MultiOutput, Registry, chunk_offsets};
+use crate::observer::Observers;
+use crate::schema::SoaSchema;
+use crate::util::{filter_system_fields, is_valid_field_name};
+use crate::value::{
+    Agg, CumOp, HashableValue, NullPolicy, RollEdge, RollOp, RollingWindow, Value, ValueKind,
+    int_to_float,
+};
 use serde::{Deserialize, Serialize};
-use std::cell::RefCell;
-use std::collections::{BTreeMap, HashSet};
-use std::rc::Rc;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{Bound, RangeBounds};
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 /// Size of each data chunk (tile) in the AoSoA structure.
 ///
@@ -21,13 +33,26 @@ pub const CHUNK_SIZE: usize = 1024;
 /// A chunk of data in the AoSoA structure.
 ///
 /// Stores a fixed number of elements (up to [`CHUNK_SIZE`]) for all fields.
-/// Each field is stored as a Vector Value (e.g., `VectorInt`, `VectorFloat`).
+/// Each field is stored as a Vector Value (e.g., `VectorInt`, `VectorFloat`),
+/// except interned string fields, which are stored as pooled strings.
+///
+/// Columns are reference-counted and never modified in place: storing a column
+/// replaces it. Cloning a chunk (and therefore a [`Bulk`]) shares every column
+/// with the original, and an update only allocates the columns it replaces.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Chunk {
     /// Number of elements in this chunk
     pub len: usize,
-    /// Column data: maps field names to Vector Values
-    pub columns: BTreeMap<String, Value>,
+    /// Column data: maps field names to shared Vector Values
+    pub columns: BTreeMap<String, Arc<Value>>,
+    /// Interned string columns: maps field names to shared pooled strings
+    #[serde(default)]
+    pub interned: BTreeMap<String, Arc<InternedStrings>>,
+    /// Versions of columns updated apart from the rest of their field, such as by
+    /// [`Bulk::set_element`]; other columns are at their field's version in
+    /// [`Meta::versions`]. See [`Bulk::chunk_version`].
+    #[serde(default)]
+    pub versions: BTreeMap<String, u64>,
 }
 
 impl Default for Chunk {
@@ -42,10 +67,258 @@ impl Chunk {
         Self {
             len: 0,
             columns: BTreeMap::new(),
+            interned: BTreeMap::new(),
+            versions: BTreeMap::new(),
+        }
+    }
+
+    /// Get the column value of a field, whether it is stored plainly or interned.
+    ///
+    /// Interned columns are materialized into a `VectorString`.
+    pub fn column(&self, field: &str) -> Option<Cow<'_, Value>> {
+        if let Some(value) = self.columns.get(field) {
+            return Some(Cow::Borrowed(&**value));
+        }
+        self.interned
+            .get(field)
+            .map(|strings| Cow::Owned(Value::VectorString(strings.to_strings())))
+    }
+
+    /// Check whether the chunk stores a column for a field.
+    pub fn has_column(&self, field: &str) -> bool {
+        self.columns.contains_key(field) || self.interned.contains_key(field)
+    }
+
+    /// Store the column value of a field.
+    ///
+    /// If the field is interned in this chunk and `value` is a `VectorString`, the
+    /// strings are interned again so the field stays interned; otherwise the value
+    /// is stored plainly. The column's separate version, if any, is cleared.
+    pub fn insert_column(&mut self, field: &str, value: Value) {
+        let _ = self.versions.remove(field);
+        match value {
+            Value::VectorString(strings) if self.interned.contains_key(field) => {
+                let _ = self.interned.insert(
+                    field.to_string(),
+                    Arc::new(InternedStrings::from_strings(&strings)),
+                );
+            }
+            value => {
+                let _ = self.interned.remove(field);
+                let _ = self.columns.insert(field.to_string(), Arc::new(value));
+            }
+        }
+    }
+
+    /// Remove the column of a field, whether it is stored plainly or interned.
+    ///
+    /// Returns `true` if the field was stored in this chunk.
+    pub fn remove_column(&mut self, field: &str) -> bool {
+        let _ = self.versions.remove(field);
+        let plain = self.columns.remove(field).is_some();
+        let interned = self.interned.remove(field).is_some();
+        plain || interned
+    }
+
+    /// Get the names of all fields stored in this chunk, in sorted order.
+    pub fn field_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .columns
+            .keys()
+            .chain(self.interned.keys())
+            .cloned()
+            .collect();
+        names.sort();
+        names
+    }
+}
+
+/// Sort direction for [`Bulk::sort_by`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SortOrder {
+    /// Smallest values first
+    #[default]
+    Asc,
+    /// Largest values first
+    Desc,
+}
+
+impl SortOrder {
+    /// Compare two elements in this direction, with NaNs and nulls always last.
+    fn compare(self, a: &Value, b: &Value) -> Ordering {
+        match (sorts_last(a), sorts_last(b)) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => match self {
+                SortOrder::Asc => a.total_cmp(b),
+                SortOrder::Desc => b.total_cmp(a),
+            },
+        }
+    }
+}
+
+/// Check whether an element is a NaN float or missing, which always sorts last.
+const fn sorts_last(v: &Value) -> bool {
+    match v {
+        Value::ScalarFloat(x) => x.is_nan(),
+        Value::Null => true,
+        _ => false,
+    }
+}
+
+/// An element competing in [`Bulk::top_k_positions`], ordered so that better
+/// ranked elements compare as smaller.
+struct Ranked {
+    /// The element's value
+    value: Value,
+    /// The element's position, which breaks ties in favour of earlier elements
+    position: usize,
+    /// The direction values are ranked in
+    order: SortOrder,
+}
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.order
+            .compare(&self.value, &other.value)
+            .then(self.position.cmp(&other.position))
+    }
+}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Ranked {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Ranked {}
+
+/// Which elements [`Bulk::join`] keeps.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum JoinKind {
+    /// Only elements with a match on both sides
+    #[default]
+    Inner,
+    /// Every element of the left bulk, matched or not
+    Left,
+}
+
+/// Which element of a set of duplicates [`Bulk::dedup`] keeps.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Keep {
+    /// The element that comes first
+    #[default]
+    First,
+    /// The element that comes last
+    Last,
+}
+
+/// Summary statistics of one field, as computed by [`Bulk::describe`].
+///
+/// Statistics that don't apply to the field's type (the mean of a string field,
+/// for instance) or that have no elements to summarize are `None`, as are all
+/// statistics of a field with nulls under [`NullPolicy::Propagate`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldSummary {
+    /// Number of elements
+    pub count: usize,
+    /// Number of missing elements: NaN floats and unset option entries
+    pub nulls: usize,
+    /// Smallest non-missing element
+    pub min: Option<Value>,
+    /// Largest non-missing element
+    pub max: Option<Value>,
+    /// Mean of the non-missing elements
+    pub mean: Option<f64>,
+    /// Population standard deviation of the non-missing elements
+    pub std: Option<f64>,
+    /// Number of distinct non-missing elements
+    pub unique: usize,
+}
+
+impl FieldSummary {
+    /// Summarize the values of one field.
+    fn of(value: &Value, nulls: NullPolicy) -> Self {
+        let as_float = |result: Result<Value>| match result {
+            Ok(Value::ScalarFloat(x)) => Some(x),
+            _ => None,
+        };
+        let scalars = value.to_scalars();
+        let present: HashSet<HashableValue> = scalars
+            .into_iter()
+            .filter(|v| !v.is_null() && !matches!(v, Value::ScalarFloat(x) if x.is_nan()))
+            .map(HashableValue::new)
+            .collect();
+        let null_count = value.null_count().unwrap_or(0);
+        let mut summary = Self {
+            count: value.len(),
+            nulls: null_count,
+            min: value.min().ok(),
+            max: value.max().ok(),
+            mean: as_float(value.mean()),
+            std: as_float(value.std()),
+            unique: present.len(),
+        };
+        if nulls == NullPolicy::Propagate && null_count > 0 {
+            summary.min = None;
+            summary.max = None;
+            summary.mean = None;
+            summary.std = None;
         }
+        summary
     }
 }
 
+/// Estimated memory use of a bulk, as computed by [`Bulk::memory_usage`].
+///
+/// Sizes are heap bytes estimated by [`Value::heap_size`]. Columns shared with
+/// clones of the bulk are counted in full, and interned columns count only their
+/// string handles, since the strings live in the shared pool.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// Bytes of each stored field, over all chunks
+    pub fields: BTreeMap<String, usize>,
+    /// Bytes of each chunk, over all fields
+    pub chunks: Vec<usize>,
+    /// Bytes of each cached derived field
+    pub cache: BTreeMap<String, usize>,
+    /// Bytes of the element IDs
+    pub ids: usize,
+    /// Sum of the field, cache and ID bytes
+    pub total: usize,
+}
+
+/// A stored element that fails its field's validator, as reported by
+/// [`Bulk::validate_all`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Violation {
+    /// Name of the field
+    pub field: String,
+    /// Position of the element in the bulk
+    pub index: usize,
+    /// The element's value
+    pub value: Value,
+}
+
+/// A failure confined to one element or record, as reported by
+/// [`Bulk::apply_tolerant`] and [`Bulk::from_records_json_tolerant`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct RowError {
+    /// Position of the element in the bulk, or of the record in the input
+    pub index: usize,
+    /// Name of the field, if the failure concerns a single field
+    pub field: Option<String>,
+    /// The error that the strict operation would have returned
+    pub error: SoAKitError,
+}
+
 /// Metadata for a Bulk structure.
 ///
 /// Contains information about the bulk structure including the number of elements,
@@ -57,6 +330,10 @@ impl Chunk {
 /// * `count` - The number of elements in the bulk
 /// * `id` - Vector of element IDs (typically 0..count-1)
 /// * `versions` - Map from field names to version numbers, incremented when fields are updated
+/// * `sorted` - Fields known to be sorted, with their order and the version they were sorted at
+/// * `custom_ids` - Custom identifiers assigned with [`Bulk::set_ids`], keyed by entries of `id`
+/// * `deleted` - Entries of `id` marked as deleted with [`Bulk::mark_deleted`]
+/// * `schema_version` - The schema version of the registry the data was written with
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Meta {
     /// Number of elements in the bulk
@@ -65,6 +342,21 @@ pub struct Meta {
     pub id: Vec<usize>,
     /// Version numbers for each field, used for cache invalidation
     pub versions: BTreeMap<String, u64>,
+    /// Sort order of fields known to be sorted, with the field version at which
+    /// the order was established; any later update of the field voids it
+    #[serde(default)]
+    pub sorted: BTreeMap<String, (SortOrder, u64)>,
+    /// Custom identifiers of elements, keyed by their entry in `id`, so every
+    /// operation that carries `id` over also carries them
+    #[serde(default)]
+    pub custom_ids: BTreeMap<usize, ElementId>,
+    /// Elements marked as deleted, by their entry in `id`
+    #[serde(default)]
+    pub deleted: BTreeSet<usize>,
+    /// Schema version of the registry the first field was stored with (see
+    /// [`Registry::schema_version`]); data written before versioning has version 0
+    #[serde(default)]
+    pub schema_version: u32,
 }
 
 impl Meta {
@@ -101,8 +393,87 @@ impl Meta {
             count,
             id: (0..count).collect(),
             versions: BTreeMap::new(),
+            sorted: BTreeMap::new(),
+            custom_ids: BTreeMap::new(),
+            deleted: BTreeSet::new(),
+            schema_version: 0,
         })
     }
+
+    /// Get the identifier of the element with ID `id`.
+    ///
+    /// This is its custom identifier if it has one, and `id` itself otherwise.
+    pub fn element_id(&self, id: usize) -> ElementId {
+        self.custom_ids
+            .get(&id)
+            .cloned()
+            .unwrap_or_else(|| ElementId::Int(u64::try_from(id).unwrap_or(u64::MAX)))
+    }
+
+    /// Get the first ID free for a new element.
+    ///
+    /// This is one past both the largest element ID and the largest numeric
+    /// custom identifier, so the identifier of the new element (see
+    /// [`Meta::element_id`]) cannot be taken already.
+    pub(crate) fn next_id(&self) -> Result<usize> {
+        let overflow = || SoAKitError::InvalidArgument("Arithmetic overflow".to_string());
+        let mut largest = self.id.iter().max().copied();
+        for custom_id in self.custom_ids.values() {
+            if let ElementId::Int(n) = custom_id {
+                let n = usize::try_from(*n).ok().ok_or_else(overflow)?;
+                largest = largest.max(Some(n));
+            }
+        }
+        largest
+            .map_or(Some(0), |max| max.checked_add(1))
+            .ok_or_else(overflow)
+    }
+}
+
+/// A custom element identifier, assigned with [`Bulk::set_ids`].
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::bulk::ElementId;
+///
+/// assert_eq!(ElementId::from(7u64), ElementId::Int(7));
+/// assert_eq!(ElementId::from("alice"), ElementId::Str("alice".to_string()));
+/// assert_eq!(ElementId::from("alice").to_string(), "alice");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum ElementId {
+    /// Integer identifier
+    Int(u64),
+    /// String identifier
+    Str(String),
+}
+
+impl From<u64> for ElementId {
+    fn from(id: u64) -> Self {
+        Self::Int(id)
+    }
+}
+
+impl From<&str> for ElementId {
+    fn from(id: &str) -> Self {
+        Self::Str(id.to_string())
+    }
+}
+
+impl From<String> for ElementId {
+    fn from(id: String) -> Self {
+        Self::Str(id)
+    }
+}
+
+impl fmt::Display for ElementId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Int(id) => write!(f, "{}", id),
+            Self::Str(id) => write!(f, "{}", id),
+        }
+    }
 }
 
 /// Cache entry for derived fields.
@@ -115,12 +486,60 @@ impl Meta {
 ///
 /// * `value` - The cached computed value
 /// * `versions` - Version numbers of the dependencies when this value was computed
+/// * `chunk_versions` - For chunk-safe fields computed chunk by chunk, the
+///   dependency versions of each chunk, so only changed chunks are recomputed
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CacheEntry {
     /// Cached value
     pub value: Value,
     /// Versions of dependencies when this was cached
     pub versions: Vec<u64>,
+    /// Versions of dependencies in each chunk when this was cached; empty unless
+    /// every dependency is stored and the field was computed chunk by chunk
+    #[serde(default)]
+    pub chunk_versions: Vec<Vec<u64>>,
+}
+
+/// A thread-safe cell for a bulk's caches.
+///
+/// It offers the `borrow`/`borrow_mut` interface of a `RefCell` on top of an
+/// `RwLock`, so a [`Bulk`] is `Send + Sync` and can be shared between threads,
+/// for instance in an `Arc`. Threads reading the same missing derived field may
+/// each compute it; every cached value is tagged with the versions it was
+/// computed from, so the cache stays correct. A lock poisoned by a panicking
+/// thread is recovered, since a cache can always be recomputed.
+#[derive(Default)]
+pub struct CacheCell<T>(RwLock<T>);
+
+impl<T> CacheCell<T> {
+    /// Wrap a value.
+    pub const fn new(value: T) -> Self {
+        Self(RwLock::new(value))
+    }
+
+    /// Lock the value for reading.
+    ///
+    /// As with a `RefCell`, calling [`CacheCell::borrow_mut`] on the same thread
+    /// while the returned guard is alive is an error; here it deadlocks.
+    pub fn borrow(&self) -> RwLockReadGuard<'_, T> {
+        self.0.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Lock the value for writing.
+    pub fn borrow_mut(&self) -> RwLockWriteGuard<'_, T> {
+        self.0.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Get mutable access to the value without locking.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.0.get_mut().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for CacheCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CacheCell").field(&*self.borrow()).finish()
+    }
 }
 
 /// Main Bulk structure for Structure-of-Arrays operations.
@@ -133,9 +552,11 @@ pub struct CacheEntry {
 /// # Features
 ///
 /// - **Immutable Updates**: All update operations return a new `Bulk` instance
+///   that shares every untouched column with the original
 /// - **Field Versioning**: Tracks changes to fields for cache invalidation
 /// - **Derived Field Caching**: Automatically caches computed derived fields
 /// - **Multiple Access Patterns**: Supports bulk operations, single element access, and views
+/// - **Thread Safety**: A bulk is `Send + Sync`, so it can be shared with worker threads
 ///
 /// # Fields
 ///
@@ -169,9 +590,19 @@ pub struct Bulk {
     pub meta: Meta,
     /// Field data storage: vector of chunks
     pub chunks: Vec<Chunk>,
-    /// Cache for derived fields (using RefCell for interior mutability)
+    /// Cache for derived fields (using a lock for thread-safe interior mutability)
+    #[serde(skip)]
+    pub cache: CacheCell<BTreeMap<String, CacheEntry>>,
+    /// Secondary indices over stored fields, built on demand
+    #[serde(skip)]
+    pub(crate) indices: CacheCell<BTreeMap<String, FieldIndex>>,
+    /// Position of every element by its identifier, built by
+    /// [`Bulk::index_of_id`] on first use
+    #[serde(skip)]
+    pub(crate) id_index: CacheCell<Option<HashMap<ElementId, usize>>>,
+    /// Callbacks run when a field version is bumped, shared by clones
     #[serde(skip)]
-    pub cache: RefCell<BTreeMap<String, CacheEntry>>,
+    pub(crate) observers: Observers,
 }
 
 impl Bulk {
@@ -205,7 +636,10 @@ impl Bulk {
         Ok(Self {
             meta,
             chunks: Vec::new(),
-            cache: RefCell::new(BTreeMap::new()),
+            cache: CacheCell::new(BTreeMap::new()),
+            indices: CacheCell::new(BTreeMap::new()),
+            id_index: CacheCell::new(None),
+            observers: Observers::default(),
         })
     }
 
@@ -221,7 +655,7 @@ impl Bulk {
     /// # Arguments
     ///
     /// * `registry` - The registry containing field metadata
-    /// * `field` - The name of the field to set
+    /// * `field` - The name of the field to set, or an alias of it (see [`Registry::add_alias`])
     /// * `values` - A vector of values, one for each element in the bulk
     ///
     /// # Returns
@@ -236,6 +670,7 @@ impl Bulk {
     ///
     /// - [`SoAKitError::FieldNotFound`] if the field is not registered
     /// - [`SoAKitError::ValidationFailed`] if a value fails validation
+    /// - [`SoAKitError::ShapeMismatch`] if a value does not have the field's declared shape
     /// - [`SoAKitError::LengthMismatch`] if the number of values doesn't match the bulk count
     /// - [`SoAKitError::InvalidArgument`] if values have inconsistent lengths
     ///
@@ -257,483 +692,716 @@ impl Bulk {
     /// let bulk = bulk.set(&registry, "age", values).unwrap();
     /// ```
     pub fn set(&self, registry: &Registry, field: &str, values: Vec<Value>) -> Result<Self> {
-        // Validate field exists in registry
-        if !registry.has_field(field) {
-            return Err(SoAKitError::FieldNotFound(field.to_string()));
-        }
-
-        // Check length matches
-        if values.len() != self.meta.count {
-            return Err(SoAKitError::LengthMismatch {
-                expected: self.meta.count,
-                actual: values.len(),
-            });
-        }
-
-        // Validate values (check if not empty first)
-        let first_value = values
-            .first()
-            .ok_or_else(|| SoAKitError::InvalidArgument("Values cannot be empty".to_string()))?;
-        if !registry.validate(field, first_value) {
-            return Err(SoAKitError::ValidationFailed(format!(
-                "Value validation failed for field: {}",
-                field
-            )));
-        }
-
-        // Validate all values have the same type/length
-        let first_len = first_value.len();
-        for (idx, val) in values.iter().enumerate() {
-            if val.len() != first_len {
-                return Err(SoAKitError::InvalidArgument(format!(
-                    "Value at index {} has different length",
-                    idx
-                )));
-            }
-        }
-
-        // Create new bulk with updated field
+        let field = registry.resolve(field);
+        registry.notify_access(field);
+        self.check_values(registry, field, &values)?;
         let mut new_bulk = self.clone();
-
-        // If chunks are empty (first field being set), initialize them
-        if new_bulk.chunks.is_empty() {
-            let num_chunks = self.meta.count.div_ceil(CHUNK_SIZE);
-            new_bulk.chunks = Vec::with_capacity(num_chunks);
-            for i in 0..num_chunks {
-                let start = i.checked_mul(CHUNK_SIZE).ok_or_else(|| {
-                    SoAKitError::InvalidArgument("Arithmetic overflow".to_string())
-                })?;
-                let end = std::cmp::min(
-                    start.checked_add(CHUNK_SIZE).ok_or_else(|| {
-                        SoAKitError::InvalidArgument("Arithmetic overflow".to_string())
-                    })?,
-                    self.meta.count,
-                );
-                new_bulk.chunks.push(Chunk {
-                    len: end.checked_sub(start).ok_or_else(|| {
-                        SoAKitError::InvalidArgument("Arithmetic underflow".to_string())
-                    })?,
-                    columns: BTreeMap::new(),
-                });
-            }
-        }
-
-        // Distribute values into chunks
-        for (i, chunk) in new_bulk.chunks.iter_mut().enumerate() {
-            let start = i
-                .checked_mul(CHUNK_SIZE)
-                .ok_or_else(|| SoAKitError::InvalidArgument("Arithmetic overflow".to_string()))?;
-            let end = std::cmp::min(
-                start.checked_add(CHUNK_SIZE).ok_or_else(|| {
-                    SoAKitError::InvalidArgument("Arithmetic overflow".to_string())
-                })?,
-                self.meta.count,
-            );
-            let chunk_values = values
-                .get(start..end)
-                .ok_or_else(|| {
-                    SoAKitError::InvalidArgument("Slice index out of bounds".to_string())
-                })?
-                .to_vec();
-
-            // Convert chunk values (scalars) to a single Vector Value
-            let vector_value = Value::from_scalars(chunk_values)?;
-            let _ = chunk.columns.insert(field.to_string(), vector_value);
-        }
-
-        // Increment version
-        let current_ver = new_bulk.meta.versions.get(field).copied().unwrap_or(0);
-        let new_ver = current_ver
-            .checked_add(1)
-            .ok_or_else(|| SoAKitError::InvalidArgument("Version overflow".to_string()))?;
-        let _ = new_bulk.meta.versions.insert(field.to_string(), new_ver);
-
-        // Invalidate cache for any derived fields that depend on this field
-        new_bulk.invalidate_dependent_cache(registry, field);
-
+        new_bulk.set_chunks(registry, field, |start, end| chunk_of(&values, start, end))?;
         Ok(new_bulk)
     }
-}
-
-impl Clone for Bulk {
-    fn clone(&self) -> Self {
-        Self {
-            meta: self.meta.clone(),
-            chunks: self.chunks.clone(),
-            cache: RefCell::new(self.cache.borrow().clone()),
-        }
-    }
-}
 
-impl Bulk {
-    /// Serialize bulk to JSON string
+    /// Set field values in place.
     ///
-    /// # Returns
+    /// This is the mutating counterpart of [`Bulk::set`]: it performs the same
+    /// validation, version bump and cache invalidation, but updates `self` instead
+    /// of cloning every chunk into a new bulk. Use it when initializing many columns
+    /// or when no earlier version of the bulk needs to be kept.
     ///
-    /// Returns `Ok(String)` containing the JSON representation, or an error if serialization fails.
+    /// If an error is returned, `self` is left unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `field` - The name or an alias of the field to set
+    /// * `values` - A vector of values, one for each element in the bulk
     ///
     /// # Errors
     ///
-    /// - [`SoAKitError::InvalidArgument`] if serialization fails
-    pub fn to_json(&self) -> Result<String> {
-        serde_json::to_string(self).map_err(|e| SoAKitError::InvalidArgument(e.to_string()))
+    /// Returns the same errors as [`Bulk::set`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let mut bulk = Bulk::new(2).unwrap();
+    /// bulk.set_mut(&registry, "age", vec![Value::ScalarInt(25), Value::ScalarInt(30)]).unwrap();
+    /// assert_eq!(bulk.get(&registry, "age").unwrap(), Value::VectorInt(vec![25, 30]));
+    /// ```
+    pub fn set_mut(&mut self, registry: &Registry, field: &str, values: Vec<Value>) -> Result<()> {
+        let field = registry.resolve(field);
+        registry.notify_access(field);
+        self.check_values(registry, field, &values)?;
+        self.set_chunks(registry, field, |start, end| chunk_of(&values, start, end))
     }
 
-    /// Deserialize bulk from JSON string
+    /// Set several fields at once (immutable update).
+    ///
+    /// Every column is validated before anything is written, then all of them are
+    /// stored into a single copy of the bulk. This avoids the intermediate bulks of
+    /// chaining one [`Bulk::set`] call per field. Each field's version is
+    /// incremented and dependent derived caches are invalidated, as with `set`.
     ///
     /// # Arguments
     ///
-    /// * `json` - JSON string to deserialize
+    /// * `registry` - The registry containing field metadata
+    /// * `columns` - The values of each field, one per element in the bulk
     ///
     /// # Returns
     ///
-    /// Returns `Ok(Bulk)` if successful, or an error if deserialization fails.
+    /// Returns `Ok(Bulk)` with every field set.
     ///
     /// # Errors
     ///
-    /// - [`SoAKitError::InvalidArgument`] if deserialization fails
-    pub fn from_json(json: &str) -> Result<Self> {
-        serde_json::from_str(json).map_err(|e| SoAKitError::InvalidArgument(e.to_string()))
-    }
-
-    /// Serialize bulk to binary format using bincode
+    /// Returns the same errors as [`Bulk::set`] for the first column that fails.
     ///
-    /// # Returns
+    /// # Examples
     ///
-    /// Returns `Ok(Vec<u8>)` containing the binary representation, or an error if serialization fails.
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    /// use std::collections::BTreeMap;
     ///
-    /// # Errors
+    /// let mut registry = Registry::new();
+    /// let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("age".to_string(), ints, false, vec![], None).unwrap();
+    /// let floats = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
+    /// registry.register("height".to_string(), floats, false, vec![], None).unwrap();
     ///
-    /// - [`SoAKitError::InvalidArgument`] if serialization fails
-    pub fn to_binary(&self) -> Result<Vec<u8>> {
-        bincode::serialize(self).map_err(|e| SoAKitError::InvalidArgument(e.to_string()))
+    /// let mut columns = BTreeMap::new();
+    /// columns.insert("age", vec![Value::ScalarInt(25), Value::ScalarInt(30)]);
+    /// columns.insert("height", vec![Value::ScalarFloat(1.7), Value::ScalarFloat(1.8)]);
+    ///
+    /// let bulk = Bulk::new(2).unwrap().set_many(&registry, columns).unwrap();
+    /// assert_eq!(bulk.list_data_fields(), vec!["age", "height"]);
+    /// ```
+    pub fn set_many(
+        &self,
+        registry: &Registry,
+        columns: BTreeMap<&str, Vec<Value>>,
+    ) -> Result<Self> {
+        for (field, values) in &columns {
+            self.check_values(registry, registry.resolve(field), values)?;
+        }
+        let mut new_bulk = self.clone();
+        for (field, values) in columns {
+            let field = registry.resolve(field);
+            registry.notify_access(field);
+            new_bulk.set_chunks(registry, field, |start, end| chunk_of(&values, start, end))?;
+        }
+        Ok(new_bulk)
     }
 
-    /// Deserialize bulk from binary format
+    /// Create a bulk from whole columns.
+    ///
+    /// Each column is a vector value (`VectorInt`, `VectorFloat`, an option
+    /// vector, a tensor, ...) holding one element per element of the new bulk.
+    /// Columns are sliced straight into chunks, so no per-element `Value` is
+    /// built. As with [`Bulk::set`], each field must be registered and the first
+    /// element of each column must pass the field's validator.
     ///
     /// # Arguments
     ///
-    /// * `data` - Binary data to deserialize
+    /// * `registry` - The registry containing field metadata
+    /// * `columns` - The values of each field, keyed by field name
     ///
     /// # Returns
     ///
-    /// Returns `Ok(Bulk)` if successful, or an error if deserialization fails.
+    /// Returns `Ok(Bulk)` with one element per column element and every field set.
     ///
     /// # Errors
     ///
-    /// - [`SoAKitError::InvalidArgument`] if deserialization fails
-    pub fn from_binary(data: &[u8]) -> Result<Self> {
-        bincode::deserialize(data).map_err(|e| SoAKitError::InvalidArgument(e.to_string()))
+    /// - [`SoAKitError::InvalidArgument`] if there are no columns, a column is
+    ///   empty or a column is not a vector
+    /// - [`SoAKitError::LengthMismatch`] if the columns have different lengths
+    /// - [`SoAKitError::FieldNotFound`] if a field is not registered
+    /// - [`SoAKitError::ValidationFailed`] if a column's first element fails validation
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    /// use std::collections::BTreeMap;
+    ///
+    /// let mut registry = Registry::new();
+    /// let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("age".to_string(), ints, false, vec![], None).unwrap();
+    /// let floats = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
+    /// registry.register("height".to_string(), floats, false, vec![], None).unwrap();
+    ///
+    /// let mut columns = BTreeMap::new();
+    /// columns.insert("age".to_string(), Value::VectorInt(vec![25, 30, 35]));
+    /// columns.insert("height".to_string(), Value::VectorFloat(vec![1.7, 1.8, 1.6]));
+    ///
+    /// let bulk = Bulk::from_columns(&registry, columns).unwrap();
+    /// assert_eq!(bulk.count(), 3);
+    /// assert_eq!(bulk.get(&registry, "age").unwrap(), Value::VectorInt(vec![25, 30, 35]));
+    /// ```
+    pub fn from_columns(registry: &Registry, columns: BTreeMap<String, Value>) -> Result<Self> {
+        let count = columns
+            .values()
+            .next()
+            .map(Value::len)
+            .ok_or_else(|| SoAKitError::InvalidArgument("No columns given".to_string()))?;
+        for (field, column) in &columns {
+            if column.is_scalar() || column.is_null() {
+                return Err(SoAKitError::InvalidArgument(format!(
+                    "Column '{}' is not a vector",
+                    field
+                )));
+            }
+            if column.len() != count {
+                return Err(SoAKitError::LengthMismatch {
+                    expected: count,
+                    actual: column.len(),
+                });
+            }
+            if !registry.has_field(field) {
+                return Err(SoAKitError::FieldNotFound(field.clone()));
+            }
+            registry.check_value(field, &column.get_element(0)?)?;
+            if registry.has_constraints(field) {
+                registry.check_constraints(field, &column.to_scalars())?;
+            }
+        }
+
+        let mut bulk = Self::new(count)?;
+        for (field, column) in &columns {
+            bulk.set_chunks(registry, field, |start, end| column.slice(start..end))?;
+        }
+        Ok(bulk)
     }
 
-    /// Serialize bulk to TOML string
+    /// Create a bulk from records of a struct implementing [`SoaSchema`].
+    ///
+    /// Each struct field is stored as a column, as by [`Bulk::from_columns`]; the
+    /// fields must have been registered, typically with
+    /// [`SoaSchema::register_fields`].
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `records` - The records, one per element
     ///
     /// # Returns
     ///
-    /// Returns `Ok(String)` containing the TOML representation, or an error if serialization fails.
+    /// Returns `Ok(Bulk)` with one element per record.
     ///
     /// # Errors
     ///
-    /// - [`SoAKitError::InvalidArgument`] if serialization fails
-    pub fn to_toml(&self) -> Result<String> {
-        toml::to_string(self).map_err(|e| SoAKitError::InvalidArgument(e.to_string()))
+    /// Returns the errors of [`Bulk::from_columns`], including
+    /// [`SoAKitError::InvalidArgument`] if there are no records.
+    pub fn from_structs<T: SoaSchema>(registry: &Registry, records: Vec<T>) -> Result<Self> {
+        Self::from_columns(registry, T::into_columns(records))
     }
 
-    /// Deserialize bulk from TOML string
+    /// Check that `values` can be stored in `field`.
+    ///
+    /// The field must be registered, there must be one value per element, the
+    /// first value must pass validation, all values must have the same length and
+    /// all values must satisfy the field's constraints.
+    pub(crate) fn check_values(
+        &self,
+        registry: &Registry,
+        field: &str,
+        values: &[Value],
+    ) -> Result<()> {
+        check_column(registry, field, self.meta.count, values)
+    }
+
+    /// Set every element of a field to the same value (immutable update).
+    ///
+    /// This is equivalent to calling [`Bulk::set`] with `count` copies of `value`,
+    /// but the copies are built one chunk at a time, so the caller never has to
+    /// construct a `Vec<Value>` the size of the whole bulk.
     ///
     /// # Arguments
     ///
-    /// * `toml` - TOML string to deserialize
+    /// * `registry` - The registry containing field metadata
+    /// * `field` - The name or an alias of the field to set
+    /// * `value` - The per-element value to store in every element
     ///
     /// # Returns
     ///
-    /// Returns `Ok(Bulk)` if successful, or an error if deserialization fails.
+    /// Returns `Ok(Bulk)` with the field set.
     ///
     /// # Errors
     ///
-    /// - [`SoAKitError::InvalidArgument`] if deserialization fails
-    pub fn from_toml(toml: &str) -> Result<Self> {
-        toml::from_str(toml).map_err(|e| SoAKitError::InvalidArgument(e.to_string()))
-    }
+    /// - [`SoAKitError::FieldNotFound`] if the field is not registered
+    /// - [`SoAKitError::ValidationFailed`] if `value` fails validation
+    /// - [`SoAKitError::ShapeMismatch`] if `value` does not have the field's declared shape
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarBool(_)));
+    /// registry.register("flag".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(3).unwrap();
+    /// let bulk = bulk.set_broadcast(&registry, "flag", Value::ScalarBool(true)).unwrap();
+    /// assert_eq!(
+    ///     bulk.get(&registry, "flag").unwrap(),
+    ///     Value::VectorBool(vec![true, true, true])
+    /// );
+    /// ```
+    pub fn set_broadcast(&self, registry: &Registry, field: &str, value: Value) -> Result<Self> {
+        let field = registry.resolve(field);
+        registry.notify_access(field);
+        if !registry.has_field(field) {
+            return Err(SoAKitError::FieldNotFound(field.to_string()));
+        }
+        registry.check_value(field, &value)?;
 
-    /// Helper to convert bulk data to a vector of record maps containing Values.
-    fn to_records_values(&self) -> Vec<std::collections::BTreeMap<String, Value>> {
-        let mut records = Vec::with_capacity(self.meta.count);
+        let mut new_bulk = self.clone();
+        new_bulk.set_chunks(registry, field, |start, end| {
+            let len = end
+                .checked_sub(start)
+                .ok_or_else(|| SoAKitError::InvalidArgument("Arithmetic underflow".to_string()))?;
+            Value::from_scalars(vec![value.clone(); len])
+        })?;
+        Ok(new_bulk)
+    }
 
-        for (chunk_idx, chunk) in self.chunks.iter().enumerate() {
-            let chunk_start_id = chunk_idx.checked_mul(CHUNK_SIZE).unwrap_or(0); // Safe: chunk_idx is within bounds
+    /// Set the value of one element of a field (immutable update).
+    ///
+    /// Only the chunk containing the element is rewritten; every other chunk is
+    /// shared with `self`. The field's version is incremented and dependent derived
+    /// caches are invalidated, as with [`Bulk::set`].
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `field` - The name or an alias of the field to update
+    /// * `idx` - The index of the element (0-based)
+    /// * `value` - The new per-element value
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with the element updated.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field is not registered or not set in this bulk
+    /// - [`SoAKitError::IndexOutOfBounds`] if `idx >= bulk.count()`
+    /// - [`SoAKitError::ValidationFailed`] if `value` fails validation
+    /// - [`SoAKitError::ShapeMismatch`] if `value` does not have the field's declared shape
+    /// - [`SoAKitError::InvalidArgument`] if `value` has a different length than the
+    ///   field's other elements or cannot be stored in the field's column
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(3).unwrap();
+    /// let bulk = bulk.set(&registry, "age", vec![Value::ScalarInt(25); 3]).unwrap();
+    /// let bulk = bulk.set_element(&registry, "age", 1, Value::ScalarInt(40)).unwrap();
+    /// assert_eq!(bulk.get(&registry, "age").unwrap(), Value::VectorInt(vec![25, 40, 25]));
+    /// ```
+    pub fn set_element(
+        &self,
+        registry: &Registry,
+        field: &str,
+        idx: usize,
+        value: Value,
+    ) -> Result<Self> {
+        let mut new_bulk = self.clone();
+        new_bulk.set_element_mut(registry, field, idx, value)?;
+        Ok(new_bulk)
+    }
 
-            for i in 0..chunk.len {
-                let mut record = std::collections::BTreeMap::new();
-                // Add ID
-                let id_idx = chunk_start_id.checked_add(i).unwrap_or(0); // Safe: within chunk bounds
-                #[allow(clippy::cast_possible_wrap)]
-                let id_val = self.meta.id.get(id_idx).copied().unwrap_or(0) as i64; // Safe: we know the index exists
-                let _ = record.insert("id".to_string(), Value::ScalarInt(id_val));
+    /// Set the value of one element of a field in place.
+    ///
+    /// This is the mutating counterpart of [`Bulk::set_element`]. If an error is
+    /// returned, `self` is left unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::set_element`].
+    pub fn set_element_mut(
+        &mut self,
+        registry: &Registry,
+        field: &str,
+        idx: usize,
+        value: Value,
+    ) -> Result<()> {
+        let field = registry.resolve(field);
+        registry.notify_access(field);
+        if !registry.has_field(field) {
+            return Err(SoAKitError::FieldNotFound(field.to_string()));
+        }
+        if idx >= self.meta.count {
+            return Err(SoAKitError::IndexOutOfBounds {
+                index: idx,
+                max: self.meta.count,
+            });
+        }
+        registry.check_value(field, &value)?;
+
+        let chunk_idx = idx
+            .checked_div(CHUNK_SIZE)
+            .ok_or_else(|| SoAKitError::InvalidArgument("Division by zero".to_string()))?;
+        let offset = idx
+            .checked_rem(CHUNK_SIZE)
+            .ok_or_else(|| SoAKitError::InvalidArgument("Division by zero".to_string()))?;
+        let column = self
+            .chunks
+            .get(chunk_idx)
+            .and_then(|chunk| chunk.column(field))
+            .ok_or_else(|| SoAKitError::FieldNotFound(field.to_string()))?;
 
-                // Add fields
-                for (name, values) in &chunk.columns {
-                    // Skip system fields
-                    if name.starts_with('_') {
-                        continue;
-                    }
+        let mut scalars = column.to_scalars();
+        let slot = scalars
+            .get_mut(offset)
+            .ok_or(SoAKitError::IndexOutOfBounds {
+                index: idx,
+                max: self.meta.count,
+            })?;
+        if !matches!(slot, Value::Null)
+            && !matches!(value, Value::Null)
+            && slot.len() != value.len()
+        {
+            return Err(SoAKitError::InvalidArgument(format!(
+                "Value at index {} has different length",
+                idx
+            )));
+        }
+        *slot = value;
+        let new_column = Value::from_scalars(scalars)?;
 
-                    // Get value at index i from the vector value
-                    if let Ok(val) = values.get_element(i) {
-                        let _ = record.insert(name.clone(), val);
-                    }
-                }
-                records.push(record);
-            }
+        let old_ver = self.meta.versions.get(field).copied().unwrap_or(0);
+        let new_ver = old_ver
+            .checked_add(1)
+            .ok_or_else(|| SoAKitError::InvalidArgument("Version overflow".to_string()))?;
+        // Only this chunk moves to the new version; the others keep the old one
+        for chunk in &mut self.chunks {
+            let _ = chunk.versions.entry(field.to_string()).or_insert(old_ver);
         }
-        records
+        if let Some(chunk) = self.chunks.get_mut(chunk_idx) {
+            chunk.insert_column(field, new_column);
+            let _ = chunk.versions.insert(field.to_string(), new_ver);
+        }
+        self.store_version(field, new_ver);
+        self.invalidate_unchunked_cache(registry, field);
+        Ok(())
     }
 
-    /// Helper to create Bulk from intermediate Value records.
-    fn from_records_values(
-        records: Vec<std::collections::BTreeMap<String, Value>>,
-        registry: &crate::meta::Registry,
-    ) -> Result<Self> {
-        let count = records.len();
-        if count == 0 {
-            return Err(SoAKitError::InvalidArgument(
-                "Cannot create Bulk from empty records".to_string(),
-            ));
-        }
+    /// Append one element to the bulk, in place.
+    ///
+    /// This is [`Bulk::extend_rows`] with a single record.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `record` - The new element's value for each data field
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::extend_rows`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    /// use std::collections::BTreeMap;
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let mut bulk = Bulk::new(1).unwrap();
+    /// bulk.set_mut(&registry, "age", vec![Value::ScalarInt(25)]).unwrap();
+    ///
+    /// let record = BTreeMap::from([("age".to_string(), Value::ScalarInt(30))]);
+    /// bulk.push_row(&registry, record).unwrap();
+    /// assert_eq!(bulk.count(), 2);
+    /// assert_eq!(bulk.get(&registry, "age").unwrap(), Value::VectorInt(vec![25, 30]));
+    /// ```
+    pub fn push_row(&mut self, registry: &Registry, record: BTreeMap<String, Value>) -> Result<()> {
+        self.extend_rows(registry, vec![record])
+    }
 
-        let bulk = Bulk::new(count)?;
-        let mut current_bulk = bulk;
+    /// Append elements to the bulk, in place.
+    ///
+    /// Each record must hold one value for every data field of the bulk. A field
+    /// with a default (see [`Registry::set_default`]) may be omitted and takes its
+    /// default; so may a field whose validator accepts [`Value::Null`], in which
+    /// case the new element is missing. Records may name fields by an alias (see
+    /// [`Registry::add_alias`]). Every value is validated before anything is stored.
+    ///
+    /// The last chunk is filled up first and new chunks are allocated as needed.
+    /// New elements get consecutive IDs after the largest existing ID and numeric
+    /// custom identifier (see [`Bulk::set_ids`]), so they never take the
+    /// identifier of an existing element. The version
+    /// of every data field is incremented and dependent derived caches are
+    /// invalidated. If an error is returned, `self` is left unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `records` - The new elements, each mapping field names to values
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if a record has a field that is not a data
+    ///   field of the bulk, misses a field, or has a value of the wrong length or type
+    /// - [`SoAKitError::ValidationFailed`] if a value fails validation
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    /// use std::collections::BTreeMap;
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let mut bulk = Bulk::new(1).unwrap();
+    /// bulk.set_mut(&registry, "age", vec![Value::ScalarInt(25)]).unwrap();
+    ///
+    /// let records = (0..3)
+    ///     .map(|i| BTreeMap::from([("age".to_string(), Value::ScalarInt(i))]))
+    ///     .collect();
+    /// bulk.extend_rows(&registry, records).unwrap();
+    /// assert_eq!(bulk.meta.id, vec![0, 1, 2, 3]);
+    /// assert_eq!(bulk.get(&registry, "age").unwrap(), Value::VectorInt(vec![25, 0, 1, 2]));
+    /// ```
+    pub fn extend_rows(
+        &mut self,
+        registry: &Registry,
+        records: Vec<BTreeMap<String, Value>>,
+    ) -> Result<()> {
+        let added = records.len();
+        if added == 0 {
+            return Ok(());
+        }
+        let new_count = self
+            .meta
+            .count
+            .checked_add(added)
+            .ok_or_else(|| SoAKitError::InvalidArgument("Arithmetic overflow".to_string()))?;
+        let first_id = self.meta.next_id()?;
+        let end_id = first_id
+            .checked_add(added)
+            .ok_or_else(|| SoAKitError::InvalidArgument("Arithmetic overflow".to_string()))?;
+
+        // Fill up the last chunk first, then split the rest into new chunks
+        let last_len = self.chunks.last().map_or(CHUNK_SIZE, |chunk| chunk.len);
+        let head = std::cmp::min(CHUNK_SIZE.saturating_sub(last_len), added);
+
+        let mut updates = Vec::new();
+        for (field, mut values) in self.appended_columns(registry, records)? {
+            let tail = values.split_off(head);
+            let mut last_column = match self.chunks.last().and_then(|chunk| chunk.column(&field)) {
+                Some(column) => column.to_scalars(),
+                None => return Err(SoAKitError::FieldNotFound(field)),
+            };
+            last_column.extend(values);
+            let last_column = Value::from_scalars(last_column)?;
+            let tail_columns = tail
+                .chunks(CHUNK_SIZE)
+                .map(|chunk_values| Value::from_scalars(chunk_values.to_vec()))
+                .collect::<Result<Vec<_>>>()?;
+            let new_ver = self
+                .meta
+                .versions
+                .get(&field)
+                .copied()
+                .unwrap_or(0)
+                .checked_add(1)
+                .ok_or_else(|| SoAKitError::InvalidArgument("Version overflow".to_string()))?;
+            updates.push((field, last_column, tail_columns, new_ver));
+        }
 
-        for name in registry.list_fields() {
-            let meta = registry
-                .get_metadata(&name)
-                .ok_or_else(|| SoAKitError::FieldNotFound(name.clone()))?;
-            if meta.is_derived {
-                continue;
+        // A bulk without chunks has no data fields, so only the count grows
+        let first_new_chunk = self.chunks.len();
+        if let Some(last) = self.chunks.last_mut() {
+            last.len = last.len.saturating_add(head);
+            let mut remaining = added.saturating_sub(head);
+            while remaining > 0 {
+                let len = std::cmp::min(remaining, CHUNK_SIZE);
+                self.chunks.push(Chunk {
+                    len,
+                    ..Chunk::new()
+                });
+                remaining = remaining.saturating_sub(len);
+            }
+        }
+        for (field, last_column, tail_columns, new_ver) in updates {
+            let (old_chunks, new_chunks) = self.chunks.split_at_mut(first_new_chunk);
+            if let Some(last) = old_chunks.last_mut() {
+                last.insert_column(&field, last_column);
             }
+            for (chunk, column) in new_chunks.iter_mut().zip(tail_columns) {
+                chunk.insert_column(&field, column);
+            }
+            self.store_version(&field, new_ver);
+            self.invalidate_dependent_cache(registry, &field);
+        }
 
-            let mut values = Vec::with_capacity(count);
+        self.meta.count = new_count;
+        self.meta.id.extend(first_id..end_id);
+        *self.id_index.get_mut() = None;
+        Ok(())
+    }
 
+    /// Validate appended records and regroup them into one column per data field.
+    fn appended_columns(
+        &self,
+        registry: &Registry,
+        records: Vec<BTreeMap<String, Value>>,
+    ) -> Result<BTreeMap<String, Vec<Value>>> {
+        let records = records
+            .into_iter()
+            .enumerate()
+            .map(|(i, record)| Self::resolve_record(registry, i, record))
+            .collect::<Result<Vec<_>>>()?;
+        let fields = self.list_data_fields();
+        let mut columns: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+        for field in &fields {
+            // Appended values must have the same length as the existing elements
+            let element_len = match self.chunks.first().and_then(|chunk| chunk.column(field)) {
+                Some(column) => column_element(&column, 0)?.len(),
+                None => return Err(SoAKitError::FieldNotFound(field.clone())),
+            };
+            let _ = columns.insert(field.clone(), Vec::with_capacity(records.len()));
             for (i, record) in records.iter().enumerate() {
-                if let Some(val) = record.get(&name) {
-                    // Validate
-                    if !(meta.validator)(val) {
+                let default = registry
+                    .get_metadata(field)
+                    .and_then(|m| m.default.as_ref());
+                let value = match (record.get(field), default) {
+                    (Some(value), _) | (None, Some(value)) => value.clone(),
+                    (None, None) if registry.validate(field, &Value::Null) => Value::Null,
+                    (None, None) => {
                         return Err(SoAKitError::InvalidArgument(format!(
-                            "Invalid value for field '{}' at index {}: {:?}",
-                            name, i, val
+                            "Missing field '{}' at index {}",
+                            field, i
                         )));
                     }
-
-                    values.push(val.clone());
-                } else {
+                };
+                registry.check_value(field, &value)?;
+                if !value.is_null() && value.len() != element_len {
                     return Err(SoAKitError::InvalidArgument(format!(
-                        "Missing field '{}' at index {}",
-                        name, i
+                        "Value for field '{}' at index {} has different length",
+                        field, i
                     )));
                 }
+                if let Some(column) = columns.get_mut(field) {
+                    column.push(value);
+                }
             }
-
-            current_bulk = current_bulk.set(registry, &name, values)?;
         }
 
-        Ok(current_bulk)
+        for (i, record) in records.iter().enumerate() {
+            if let Some(unknown) = record.keys().find(|key| !fields.contains(key)) {
+                return Err(SoAKitError::InvalidArgument(format!(
+                    "Record at index {} has field '{}', which is not a data field of this bulk",
+                    i, unknown
+                )));
+            }
+        }
+        Ok(columns)
     }
 
-    /// Serialize bulk to a JSON string of records (AoS format).
+    /// Grow or truncate the bulk to `new_count` elements (immutable update).
     ///
-    /// # Errors
+    /// Growing appends elements as by [`Bulk::extend_rows`], each holding the
+    /// value given in `defaults` for every data field. A field not in `defaults`
+    /// takes its registered default (see [`Registry::set_default`]), or is left
+    /// missing in the new elements, which requires its validator to accept
+    /// [`Value::Null`]. Truncating keeps the first `new_count` elements as by
+    /// [`Bulk::head`]. Element IDs follow the same rules as in those operations.
     ///
-    /// Returns an error if JSON serialization fails.
-    pub fn to_records_json(&self) -> Result<String> {
-        let records_values = self.to_records_values();
-
-        // Convert Values to untagged JSON values
-        let records: Vec<serde_json::Map<String, serde_json::Value>> = records_values
-            .into_iter()
-            .map(|record| {
-                record
-                    .into_iter()
-                    .map(|(k, v)| (k, v.to_untagged_json_value()))
-                    .collect()
-            })
-            .collect();
-
-        serde_json::to_string(&records).map_err(|e| SoAKitError::InvalidArgument(e.to_string()))
-    }
-
-    /// Deserialize bulk from a JSON string of records.
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `new_count` - The number of elements of the resized bulk
+    /// * `defaults` - Value of each data field in new elements
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with `new_count` elements.
     ///
     /// # Errors
     ///
-    /// Returns an error if:
-    /// - JSON parsing fails
-    /// - A record is not a valid JSON object
-    /// - Field values cannot be converted to the expected types
-    /// - Required fields are missing
-    pub fn from_records_json(json: &str, registry: &crate::meta::Registry) -> Result<Self> {
-        let parsed: serde_json::Value =
-            serde_json::from_str(json).map_err(|e| SoAKitError::InvalidArgument(e.to_string()))?;
-
-        let records_json = match parsed {
-            serde_json::Value::Array(arr) => arr,
-            _ => {
-                return Err(SoAKitError::InvalidArgument(
-                    "Expected JSON array of objects".to_string(),
-                ));
-            }
-        };
-
-        let mut records_values = Vec::with_capacity(records_json.len());
-        for (i, item) in records_json.into_iter().enumerate() {
-            match item {
-                serde_json::Value::Object(obj) => {
-                    let mut record = std::collections::BTreeMap::new();
-                    for (k, v) in obj {
-                        let val = Value::from_untagged_json_value(v)?;
-                        let _ = record.insert(k, val);
-                    }
-                    records_values.push(record);
-                }
-                _ => {
-                    return Err(SoAKitError::InvalidArgument(format!(
-                        "Record {} is not an object",
-                        i
-                    )));
-                }
+    /// - [`SoAKitError::InvalidArgument`] if `new_count` is 0, or when growing, if
+    ///   `defaults` names a field that is not a data field of the bulk or misses a
+    ///   field without a registered default whose validator rejects [`Value::Null`]
+    /// - [`SoAKitError::ValidationFailed`] if a default fails validation
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    /// use std::collections::BTreeMap;
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(2).unwrap();
+    /// let bulk = bulk.set(&registry, "age", vec![Value::ScalarInt(25); 2]).unwrap();
+    ///
+    /// let defaults = BTreeMap::from([("age".to_string(), Value::ScalarInt(0))]);
+    /// let grown = bulk.resize(&registry, 4, &defaults).unwrap();
+    /// assert_eq!(grown.get(&registry, "age").unwrap(), Value::VectorInt(vec![25, 25, 0, 0]));
+    /// assert_eq!(grown.meta.id, vec![0, 1, 2, 3]);
+    ///
+    /// let shrunk = grown.resize(&registry, 1, &defaults).unwrap();
+    /// assert_eq!(shrunk.get(&registry, "age").unwrap(), Value::VectorInt(vec![25]));
+    /// ```
+    pub fn resize(
+        &self,
+        registry: &Registry,
+        new_count: usize,
+        defaults: &BTreeMap<String, Value>,
+    ) -> Result<Self> {
+        if new_count == 0 {
+            return Err(SoAKitError::InvalidArgument(
+                "Bulk count must be greater than 0".to_string(),
+            ));
+        }
+        match new_count.cmp(&self.meta.count) {
+            Ordering::Less => self.head(new_count),
+            Ordering::Equal => Ok(self.clone()),
+            Ordering::Greater => {
+                let records = vec![defaults.clone(); new_count.saturating_sub(self.meta.count)];
+                let mut new_bulk = self.clone();
+                new_bulk.extend_rows(registry, records)?;
+                Ok(new_bulk)
             }
         }
-
-        Self::from_records_values(records_values, registry)
     }
 
-    /// Serialize bulk to a TOML string of records.
+    /// Replace the missing elements of every field that has a default (immutable update).
     ///
-    /// # Errors
-    ///
-    /// Returns an error if TOML serialization fails.
-    pub fn to_records_toml(&self) -> Result<String> {
-        let records_values = self.to_records_values();
-
-        // Convert Values to untagged JSON values (TOML uses serde data model)
-        let records: Vec<serde_json::Map<String, serde_json::Value>> = records_values
-            .into_iter()
-            .map(|record| {
-                record
-                    .into_iter()
-                    .map(|(k, v)| (k, v.to_untagged_json_value()))
-                    .collect()
-            })
-            .collect();
-
-        let mut map = std::collections::BTreeMap::new();
-        let _ = map.insert("records".to_string(), records);
-
-        toml::to_string(&map).map_err(|e| SoAKitError::InvalidArgument(e.to_string()))
-    }
-
-    /// Deserialize bulk from a TOML string of records.
-    /// Expects `[[records]]` format.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if:
-    /// - TOML parsing fails
-    /// - The TOML structure is invalid (missing `records` key)
-    /// - A record is not a valid object
-    /// - Field values cannot be converted to the expected types
-    /// - Required fields are missing
-    pub fn from_records_toml(toml: &str, registry: &crate::meta::Registry) -> Result<Self> {
-        let parsed: serde_json::Value =
-            toml::from_str(toml).map_err(|e| SoAKitError::InvalidArgument(e.to_string()))?;
-
-        let records_json = match parsed {
-            serde_json::Value::Object(mut obj) => match obj.remove("records") {
-                Some(serde_json::Value::Array(arr)) => arr,
-                _ => {
-                    return Err(SoAKitError::InvalidArgument(
-                        "Expected 'records' array in TOML".to_string(),
-                    ));
-                }
-            },
-            _ => {
-                return Err(SoAKitError::InvalidArgument(
-                    "Expected TOML table with 'records' array".to_string(),
-                ));
-            }
-        };
-
-        let mut records_values = Vec::with_capacity(records_json.len());
-        for (i, item) in records_json.into_iter().enumerate() {
-            match item {
-                serde_json::Value::Object(obj) => {
-                    let mut record = std::collections::BTreeMap::new();
-                    for (k, v) in obj {
-                        let val = Value::from_untagged_json_value(v)?;
-                        let _ = record.insert(k, val);
-                    }
-                    records_values.push(record);
-                }
-                _ => {
-                    return Err(SoAKitError::InvalidArgument(format!(
-                        "Record {} is not an object",
-                        i
-                    )));
-                }
-            }
-        }
-
-        Self::from_records_values(records_values, registry)
-    }
-
-    /// Serialize bulk to a binary format of records.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if binary serialization fails.
-    pub fn to_records_binary(&self) -> Result<Vec<u8>> {
-        let records = self.to_records_values();
-        bincode::serialize(&records).map_err(|e| SoAKitError::InvalidArgument(e.to_string()))
-    }
-
-    /// Deserialize bulk from a binary format of records.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if:
-    /// - Binary deserialization fails
-    /// - Field values cannot be converted to the expected types
-    /// - Required fields are missing
-    pub fn from_records_binary(data: &[u8], registry: &crate::meta::Registry) -> Result<Self> {
-        let records: Vec<std::collections::BTreeMap<String, Value>> =
-            bincode::deserialize(data).map_err(|e| SoAKitError::InvalidArgument(e.to_string()))?;
-
-        Self::from_records_values(records, registry)
-    }
-
-    /// Get field values.
-    ///
-    /// Retrieves the values for a field. For regular fields, this returns the
-    /// it from cache if valid) and returns it.
-    ///
-    /// The returned value is always a vector type (`VectorInt`, `VectorFloat`, etc.)
-    /// representing all elements' values for that field.
+    /// Missing elements are those of option vectors (see [`Value::to_dense`]);
+    /// each is replaced by the field's default, set with [`Registry::set_default`].
+    /// Fields without a default or without missing elements are left untouched,
+    /// and keep their version.
     ///
     /// # Arguments
     ///
     /// * `registry` - The registry containing field metadata
-    /// * `field` - The name of the field to retrieve
     ///
     /// # Returns
     ///
-    /// Returns `Ok(Value)` containing the field values as a vector, or an error if:
-    /// - The field is not registered
-    /// - The field has no data (for regular fields)
-    /// - Derived field computation fails
+    /// Returns `Ok(Bulk)` with the missing elements filled.
     ///
     /// # Errors
     ///
-    /// - [`SoAKitError::FieldNotFound`] if the field is not registered or has no data
-    /// - [`SoAKitError::InvalidArgument`] if derived field computation fails
+    /// - [`SoAKitError::InvalidArgument`] if a default is not a scalar of the field's kind
     ///
     /// # Examples
     ///
@@ -741,467 +1409,883 @@ impl Bulk {
     /// use soakit::{Bulk, Registry, Value};
     ///
     /// let mut registry = Registry::new();
-    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_) | Value::Null));
     /// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+    /// registry.set_default("age", Value::ScalarInt(0)).unwrap();
     ///
     /// let bulk = Bulk::new(3).unwrap();
-    /// let values = vec![
-    ///     Value::ScalarInt(25),
-    ///     Value::ScalarInt(30),
-    ///     Value::ScalarInt(35),
-    /// ];
-    /// let bulk = bulk.set(&registry, "age", values).unwrap();
-    ///
-    /// if let Value::VectorInt(ages) = bulk.get(&registry, "age").unwrap() {
-    ///     assert_eq!(ages, vec![25, 30, 35]);
-    /// }
+    /// let bulk = bulk.set(&registry, "age", vec![Value::ScalarInt(5), Value::Null, Value::ScalarInt(7)]).unwrap();
+    /// let filled = bulk.fill_missing(&registry).unwrap();
+    /// assert_eq!(filled.get(&registry, "age").unwrap(), Value::VectorInt(vec![5, 0, 7]));
     /// ```
-    pub fn get(&self, registry: &Registry, field: &str) -> Result<Value> {
-        let metadata = registry
-            .get_metadata(field)
-            .ok_or_else(|| SoAKitError::FieldNotFound(field.to_string()))?;
-
-        if metadata.is_derived {
-            // Check cache
-            let cache_borrow = self.cache.borrow();
-            if let Some(cache_entry) = cache_borrow.get(field) {
-                // Check if dependency versions match
-                let current_dep_versions: Result<Vec<u64>> = metadata
-                    .dependencies
-                    .iter()
-                    .map(|dep| {
-                        self.meta
-                            .versions
-                            .get(dep)
-                            .copied()
-                            .ok_or_else(|| SoAKitError::FieldNotFound(dep.clone()))
-                    })
-                    .collect();
-
-                let current_dep_versions = current_dep_versions?;
-
-                if cache_entry.versions == current_dep_versions {
-                    return Ok(cache_entry.value.clone());
-                }
+    pub fn fill_missing(&self, registry: &Registry) -> Result<Self> {
+        let mut bulk = self.clone();
+        for field in self.list_data_fields() {
+            let Some(default) = registry
+                .get_metadata(&field)
+                .and_then(|m| m.default.clone())
+            else {
+                continue;
+            };
+            let has_missing = self.chunks.iter().any(|chunk| {
+                chunk
+                    .column(&field)
+                    .is_some_and(|column| column.is_sparse())
+            });
+            if has_missing {
+                bulk.apply_chunks_mut(registry, &field, |column| {
+                    *column = column.to_dense(&default)?;
+                    Ok(())
+                })?;
             }
-            drop(cache_borrow); // Release borrow before mutable borrow
+        }
+        Ok(bulk)
+    }
 
-            // Compute derived value
-            let derived_func = metadata.derived_func.as_ref().ok_or_else(|| {
-                SoAKitError::InvalidArgument("Derived field missing function".to_string())
-            })?;
+    /// Replace `field` chunk by chunk, in place.
+    ///
+    /// `make_chunk(start, end)` must return the stored column value for elements
+    /// `start..end`. Chunks are created if this is the first field being set, the
+    /// field's version is incremented, and dependent derived caches are invalidated.
+    /// Every chunk is built before any is stored, so an error leaves `self` unchanged.
+    pub(crate) fn set_chunks<F>(
+        &mut self,
+        registry: &Registry,
+        field: &str,
+        mut make_chunk: F,
+    ) -> Result<()>
+    where
+        F: FnMut(usize, usize) -> Result<Value>,
+    {
+        let num_chunks = self.meta.count.div_ceil(CHUNK_SIZE);
+        let mut columns = Vec::with_capacity(num_chunks);
+        for i in 0..num_chunks {
+            let (start, end) = self.chunk_bounds(i)?;
+            let len = end
+                .checked_sub(start)
+                .ok_or_else(|| SoAKitError::InvalidArgument("Arithmetic underflow".to_string()))?;
+            columns.push((len, make_chunk(start, end)?));
+        }
+
+        let current_ver = self.meta.versions.get(field).copied().unwrap_or(0);
+        let new_ver = current_ver
+            .checked_add(1)
+            .ok_or_else(|| SoAKitError::InvalidArgument("Version overflow".to_string()))?;
 
-            // Get dependency values
-            let dep_values: Result<Vec<Value>> = metadata
-                .dependencies
+        // If chunks are empty (first field being set), initialize them
+        if self.chunks.is_empty() {
+            self.chunks = columns
                 .iter()
-                .map(|dep| self.get(registry, dep))
+                .map(|&(len, _)| Chunk {
+                    len,
+                    ..Chunk::new()
+                })
                 .collect();
+            self.meta.schema_version = registry.schema_version();
+        }
 
-            let dep_values = dep_values?;
+        // Distribute values into chunks
+        for (chunk, (_, vector_value)) in self.chunks.iter_mut().zip(columns) {
+            chunk.insert_column(field, vector_value);
+        }
 
-            // Compute derived value
-            let computed_value = derived_func(&dep_values)?;
+        self.store_version(field, new_ver);
 
-            // Get current dependency versions for caching
-            let current_dep_versions: Result<Vec<u64>> = metadata
-                .dependencies
-                .iter()
-                .map(|dep| {
-                    if let Some(dep_meta) = registry.get_metadata(dep) {
-                        if dep_meta.is_derived {
-                            // Derived fields don't have versions in meta.versions.
-                            // We rely on recursive cache invalidation, so we can use a placeholder.
-                            Ok(0)
-                        } else {
-                            self.meta
-                                .versions
-                                .get(dep)
-                                .copied()
-                                .ok_or_else(|| SoAKitError::FieldNotFound(dep.clone()))
-                        }
-                    } else {
-                        Err(SoAKitError::FieldNotFound(dep.clone()))
-                    }
-                })
-                .collect();
+        // Invalidate cache for any derived fields that depend on this field
+        self.invalidate_dependent_cache(registry, field);
 
-            let current_dep_versions = current_dep_versions?;
+        Ok(())
+    }
 
-            // Update cache
-            let mut cache_mut = self.cache.borrow_mut();
-            let _ = cache_mut.insert(
-                field.to_string(),
-                CacheEntry {
-                    value: computed_value.clone(),
-                    versions: current_dep_versions,
-                },
-            );
+    /// Record a field's new version and notify the observers of the bump.
+    fn store_version(&mut self, field: &str, new_ver: u64) {
+        let old_ver = self
+            .meta
+            .versions
+            .insert(field.to_string(), new_ver)
+            .unwrap_or(0);
+        self.observers.notify(field, old_ver, new_ver);
+    }
 
-            Ok(computed_value)
+    /// Get the element range `start..end` covered by chunk `i`.
+    pub(crate) fn chunk_bounds(&self, i: usize) -> Result<(usize, usize)> {
+        let start = i
+            .checked_mul(CHUNK_SIZE)
+            .ok_or_else(|| SoAKitError::InvalidArgument("Arithmetic overflow".to_string()))?;
+        let end = std::cmp::min(
+            start
+                .checked_add(CHUNK_SIZE)
+                .ok_or_else(|| SoAKitError::InvalidArgument("Arithmetic overflow".to_string()))?,
+            self.meta.count,
+        );
+        Ok((start, end))
+    }
+}
+
+/// Match each left key against an index over the right key column.
+///
+/// Returns one `(left, Some(right))` pair per match, plus `(left, None)` for
+/// unmatched left elements with [`JoinKind::Left`]. Null keys never match.
+fn join_pairs(
+    left_keys: &[Value],
+    right: &FieldIndex,
+    kind: JoinKind,
+) -> Vec<(usize, Option<usize>)> {
+    let mut pairs = Vec::new();
+    for (idx, key) in left_keys.iter().enumerate() {
+        let matches = if key.is_null() {
+            &[][..]
         } else {
-            // Regular field - get from chunks
-            if self.meta.count == 0 {
-                return Ok(Value::VectorInt(Vec::new()));
-            }
+            right.get(key)
+        };
+        if !matches.is_empty() {
+            pairs.extend(matches.iter().map(|&r| (idx, Some(r))));
+        } else if kind == JoinKind::Left {
+            pairs.push((idx, None));
+        }
+    }
+    pairs
+}
 
-            let mut result_value: Option<Value> = None;
+/// Advance a SplitMix64 generator and return its next output.
+const fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
 
-            for chunk in &self.chunks {
-                if let Some(chunk_val) = chunk.columns.get(field) {
-                    if let Some(res) = &mut result_value {
-                        res.append(chunk_val.clone())?;
-                    } else {
-                        result_value = Some(chunk_val.clone());
-                    }
-                } else {
-                    return Err(SoAKitError::FieldNotFound(format!(
-                        "Field {} missing in chunk",
+/// Check that `values` can be stored in `field` of a bulk of `count` elements.
+///
+/// See [`Bulk::check_values`].
+pub(crate) fn check_column(
+    registry: &Registry,
+    field: &str,
+    count: usize,
+    values: &[Value],
+) -> Result<()> {
+    // Validate field exists in registry
+    let metadata = registry
+        .get_metadata(field)
+        .ok_or_else(|| SoAKitError::FieldNotFound(field.to_string()))?;
+    if metadata.is_virtual() {
+        return Err(SoAKitError::InvalidArgument(format!(
+            "Cannot store values of virtual field: {}",
+            field
+        )));
+    }
+
+    // Check length matches
+    if values.len() != count {
+        return Err(SoAKitError::LengthMismatch {
+            expected: count,
+            actual: values.len(),
+        });
+    }
+    for value in values {
+        registry.check_shape(field, value)?;
+    }
+
+    // Validate values (check if not empty first)
+    let first_value = values
+        .first()
+        .ok_or_else(|| SoAKitError::InvalidArgument("Values cannot be empty".to_string()))?;
+    registry.check_value(field, first_value)?;
+
+    // Validate all values have the same type/length
+    let first_len = first_value.len();
+    for (idx, val) in values.iter().enumerate() {
+        if val.len() != first_len {
+            return Err(SoAKitError::InvalidArgument(format!(
+                "Value at index {} has different length",
+                idx
+            )));
+        }
+    }
+    registry.check_constraints(field, values)
+}
+
+/// Convert the scalars `values[start..end]` into one stored chunk column.
+pub(crate) fn chunk_of(values: &[Value], start: usize, end: usize) -> Result<Value> {
+    let chunk_values = values
+        .get(start..end)
+        .ok_or_else(|| SoAKitError::InvalidArgument("Slice index out of bounds".to_string()))?
+        .to_vec();
+
+    // Convert chunk values (scalars) to a single Vector Value
+    Value::from_scalars(chunk_values)
+}
+
+/// Transform the masked values of one field and rechunk the result.
+///
+/// `mask` must already be normalized to one entry per element; `bounds` holds
+/// the element range of every chunk. With a registry, every transformed value
+/// must pass the field's validator.
+pub(crate) fn applied_columns<F>(
+    chunks: &[Chunk],
+    registry: Option<&Registry>,
+    field: &str,
+    mask: &[bool],
+    bounds: &[(usize, usize)],
+    func: &F,
+) -> Result<Vec<Value>>
+where
+    F: Fn(&[Value]) -> Result<Vec<Value>>,
+{
+    // Get old values (reconstruct from chunks)
+    let mut old_values = Vec::with_capacity(mask.len());
+    for chunk in chunks {
+        if let Some(chunk_val) = chunk.column(field) {
+            // We need to flatten the vector value into scalars
+            // This is inefficient but necessary for the current apply API which works on slices of Values
+            match chunk_val.as_ref() {
+                other if other.is_vector() => old_values.extend(other.to_scalars()),
+                _ => {
+                    return Err(SoAKitError::InvalidArgument(format!(
+                        "Field {} is not a vector",
                         field
                     )));
                 }
             }
-
-            result_value.ok_or_else(|| SoAKitError::FieldNotFound(field.to_string()))
         }
     }
 
-    /// When a field is updated, any derived fields that depend on it need to
-    /// have their cache invalidated so they will be recomputed on the next access.
-    ///
-    /// # Arguments
-    ///
-    /// * `registry` - The registry to check for dependent fields
-    /// * `field` - The name of the field that was updated
-    fn invalidate_dependent_cache(&mut self, registry: &Registry, field: &str) {
-        let fields_to_invalidate: Vec<String> = registry
-            .list_fields()
-            .into_iter()
-            .filter(|f| {
-                if let Some(meta) = registry.get_metadata(f) {
-                    meta.is_derived && meta.dependencies.contains(&field.to_string())
-                } else {
-                    false
-                }
-            })
-            .collect();
+    if old_values.len() != mask.len() {
+        return Err(SoAKitError::FieldNotFound(format!(
+            "Field {} data incomplete",
+            field
+        )));
+    }
 
-        let mut cache_mut = self.cache.borrow_mut();
-        for f in &fields_to_invalidate {
-            let _ = cache_mut.remove(f);
+    // Extract subset based on mask
+    let subset: Vec<Value> = old_values
+        .iter()
+        .zip(mask)
+        .filter(|&(_, &selected)| selected)
+        .map(|(val, _)| val.clone())
+        .collect();
+
+    // Apply function to subset
+    let new_subset = func(&subset)?;
+
+    // Validate new subset length matches mask count
+    if new_subset.len() != subset.len() {
+        return Err(SoAKitError::LengthMismatch {
+            expected: subset.len(),
+            actual: new_subset.len(),
+        });
+    }
+    if let Some(registry) = registry {
+        for val in &new_subset {
+            registry.check_value(field, val)?;
         }
-        drop(cache_mut); // Release the borrow before recursive calls
+    }
 
-        // Recursively invalidate fields that depend on the invalidated fields
-        for f in fields_to_invalidate {
-            self.invalidate_dependent_cache(registry, &f);
+    // Update values for masked positions
+    let mut new_values = old_values;
+    let mut new_subset = new_subset.into_iter();
+    for (old_val, _) in new_values
+        .iter_mut()
+        .zip(mask)
+        .filter(|&(_, &selected)| selected)
+    {
+        if let Some(new_val) = new_subset.next() {
+            *old_val = new_val;
         }
     }
 
-    /// Get the count of elements in this bulk.
-    ///
-    /// # Returns
-    ///
-    /// The number of elements in the bulk as a `usize`.
-    ///
-    /// # Examples
-    ///
-    /// ```rust
-    /// use soakit::Bulk;
-    ///
-    /// let bulk = Bulk::new(10).unwrap();
-    /// assert_eq!(bulk.count(), 10);
-    /// ```
-    pub const fn count(&self) -> usize {
-        self.meta.count
+    // Rechunk revised values
+    bounds
+        .iter()
+        .map(|&(start, end)| chunk_of(&new_values, start, end))
+        .collect()
+}
+
+/// Gather the values of one field kept by `mask` (where it is `false`) and
+/// chunk them according to `bounds`.
+pub(crate) fn kept_columns(
+    chunks: &[Chunk],
+    field: &str,
+    mask: &[bool],
+    bounds: &[(usize, usize)],
+) -> Result<Vec<Value>> {
+    let kept: Vec<Value> = chunks
+        .iter()
+        .filter_map(|chunk| chunk.column(field))
+        .flat_map(|column| column.to_scalars())
+        .zip(mask)
+        .filter(|&(_, &remove)| !remove)
+        .map(|(value, _)| value)
+        .collect();
+    bounds
+        .iter()
+        .map(|&(start, end)| chunk_of(&kept, start, end))
+        .collect()
+}
+
+/// Map a fallible function over tasks, in parallel with the `rayon` feature.
+fn map_tasks<T, R, F>(tasks: &[T], f: F) -> Result<Vec<R>>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> Result<R> + Sync + Send,
+{
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        tasks.par_iter().map(f).collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        tasks.iter().map(f).collect()
     }
+}
 
-    /// List all data fields (excluding system fields).
-    ///
-    /// Returns a vector of field names that have data in this bulk.
-    /// System fields (those starting with `_`) are excluded.
+/// Leading bytes of [`Bulk::to_binary`] output, followed by the layout version as
+/// a little-endian `u32` and the bincode encoding of the bulk.
+///
+/// Binaries written before the header start with the element count instead,
+/// which would have to exceed 10^16 to read as these bytes.
+const BINARY_MAGIC: [u8; 8] = *b"SOAKITB\0";
+
+/// Version of the layout written by [`Bulk::to_binary`].
+///
+/// bincode encodes structs positionally and ignores `#[serde(default)]`, so
+/// every field added to [`Meta`] or [`Chunk`] needs a new version, and
+/// [`Bulk::from_binary`] decodes each older version explicitly. Version 0 is the
+/// headerless layout of [`LegacyBulk`].
+pub(crate) const BINARY_VERSION: u32 = 1;
+
+/// A bulk in the headerless binary layout: element count, IDs and field
+/// versions, and chunks of plain columns.
+#[derive(Deserialize)]
+struct LegacyBulk {
+    /// `count`, `id` and `versions` of [`Meta`]
+    meta: LegacyMeta,
+    /// `len` and `columns` of each [`Chunk`]
+    chunks: Vec<LegacyChunk>,
+}
+
+/// [`Meta`] in the headerless binary layout.
+#[derive(Deserialize)]
+struct LegacyMeta {
+    /// Number of elements
+    count: usize,
+    /// Element IDs
+    id: Vec<usize>,
+    /// Field versions
+    versions: BTreeMap<String, u64>,
+}
+
+/// [`Chunk`] in the headerless binary layout.
+#[derive(Deserialize)]
+struct LegacyChunk {
+    /// Number of elements
+    len: usize,
+    /// Column data
+    columns: BTreeMap<String, Value>,
+}
+
+impl LegacyBulk {
+    /// Convert to a bulk, with every field added since at its default.
+    fn into_bulk(self) -> Result<Bulk> {
+        let mut bulk = Bulk::new(self.meta.count)?;
+        bulk.meta.id = self.meta.id;
+        bulk.meta.versions = self.meta.versions;
+        bulk.chunks = self
+            .chunks
+            .into_iter()
+            .map(|chunk| Chunk {
+                len: chunk.len,
+                columns: chunk
+                    .columns
+                    .into_iter()
+                    .map(|(field, column)| (field, Arc::new(column)))
+                    .collect(),
+                ..Chunk::new()
+            })
+            .collect();
+        Ok(bulk)
+    }
+}
+
+impl Clone for Bulk {
+    fn clone(&self) -> Self {
+        Self {
+            meta: self.meta.clone(),
+            chunks: self.chunks.clone(),
+            cache: CacheCell::new(self.cache.borrow().clone()),
+            indices: CacheCell::new(self.indices.borrow().clone()),
+            // Clones often get new IDs right away; the index is rebuilt on demand
+            id_index: CacheCell::new(None),
+            observers: self.observers.clone(),
+        }
+    }
+}
+
+/// Bulks are equal when they have the same number of elements, the same
+/// stored fields holding the same values, and the same tombstones (see
+/// [`Bulk::mark_deleted`]) and custom identifiers (see [`Bulk::set_ids`]) at
+/// each position.
+///
+/// Values are compared as by [`Value::total_cmp`], so `NaN` equals `NaN`.
+/// Numeric element IDs, versions, derived caches, indices, observers and the
+/// chunk layout are ignored.
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::{Bulk, Registry, Value};
+///
+/// let mut registry = Registry::new();
+/// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+/// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+///
+/// let expected = Bulk::new(2).unwrap();
+/// let expected = expected.set(&registry, "age", vec![Value::ScalarInt(30); 2]).unwrap();
+/// let actual = Bulk::new(2).unwrap();
+/// let actual = actual.set(&registry, "age", vec![Value::ScalarInt(25); 2]).unwrap();
+/// assert_ne!(expected, actual);
+///
+/// // Versions differ, but the data is the same
+/// let actual = actual.set(&registry, "age", vec![Value::ScalarInt(30); 2]).unwrap();
+/// assert_eq!(expected, actual);
+/// assert_eq!(expected.content_hash(), actual.content_hash());
+/// ```
+impl PartialEq for Bulk {
+    fn eq(&self, other: &Self) -> bool {
+        let fields = self.list_data_fields();
+        self.meta.count == other.meta.count
+            && self.element_marks().eq(other.element_marks())
+            && fields == other.list_data_fields()
+            && fields.iter().all(|field| {
+                self.stored_scalars(field)
+                    .zip(other.stored_scalars(field))
+                    .all(|(a, b)| a.total_cmp(&b) == Ordering::Equal)
+            })
+    }
+}
+
+impl Eq for Bulk {}
+
+/// Hashes the same data [`PartialEq`] compares, so equal bulks hash equally.
+impl Hash for Bulk {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.meta.count.hash(state);
+        for marks in self.element_marks() {
+            marks.hash(state);
+        }
+        for field in self.list_data_fields() {
+            field.hash(state);
+            for value in self.stored_scalars(&field) {
+                HashableValue::new(value).hash(state);
+            }
+        }
+    }
+}
+
+impl Bulk {
+    /// Compute a hash of the bulk's data.
+    ///
+    /// Equal bulks, as compared by [`PartialEq`], have equal hashes, so a pipeline
+    /// can keep the hash of its input and skip work when it has not changed. The
+    /// hash is stable within one build of the program, not across versions.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Iterate over whether each element is deleted and its custom identifier.
+    fn element_marks(&self) -> impl Iterator<Item = (bool, Option<&ElementId>)> + '_ {
+        self.meta
+            .id
+            .iter()
+            .map(|id| (self.meta.deleted.contains(id), self.meta.custom_ids.get(id)))
+    }
+
+    /// Iterate over the values of a stored field, one scalar per element.
+    fn stored_scalars<'a>(&'a self, field: &'a str) -> impl Iterator<Item = Value> + 'a {
+        self.chunks.iter().flat_map(move |chunk| {
+            chunk
+                .column(field)
+                .map(|column| column.to_scalars())
+                .unwrap_or_default()
+        })
+    }
+}
+
+impl Bulk {
+    /// Serialize bulk to JSON string
     ///
     /// # Returns
     ///
-    /// A vector of field names as strings.
-    ///
-    /// # Examples
-    ///
-    /// ```rust
-    /// use soakit::{Bulk, Registry, Value};
-    ///
-    /// let mut registry = Registry::new();
-    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
-    /// registry.register("age".to_string(), validator.clone(), false, vec![], None).unwrap();
-    /// registry.register("height".to_string(), validator, false, vec![], None).unwrap();
+    /// Returns `Ok(String)` containing the JSON representation, or an error if serialization fails.
     ///
-    /// let bulk = Bulk::new(3).unwrap();
-    /// let bulk = bulk.set(&registry, "age", vec![Value::ScalarInt(25); 3]).unwrap();
-    /// let bulk = bulk.set(&registry, "height", vec![Value::ScalarInt(175); 3]).unwrap();
+    /// # Errors
     ///
-    /// let fields = bulk.list_data_fields();
-    /// assert_eq!(fields.len(), 2);
-    /// ```
-    pub fn list_data_fields(&self) -> Vec<String> {
-        if let Some(chunk) = self.chunks.first() {
-            filter_system_fields(&chunk.columns.keys().cloned().collect::<Vec<_>>())
-        } else {
-            Vec::new()
-        }
+    /// - [`SoAKitError::InvalidArgument`] if serialization fails
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self).map_err(|e| SoAKitError::InvalidArgument(e.to_string()))
     }
 
-    /// Create a proxy for accessing a single element at the given index.
-    ///
-    /// A [`Proxy`] provides a convenient way to access and manipulate a single
-    /// element's field values without working with the entire bulk.
+    /// Deserialize bulk from JSON string
     ///
     /// # Arguments
     ///
-    /// * `idx` - The index of the element (0-based)
+    /// * `json` - JSON string to deserialize
     ///
     /// # Returns
     ///
-    /// Returns `Ok(Proxy)` if successful, or an error if the index is out of bounds.
+    /// Returns `Ok(Bulk)` if successful, or an error if deserialization fails.
     ///
     /// # Errors
     ///
-    /// - [`SoAKitError::IndexOutOfBounds`] if `idx >= bulk.count()`
+    /// - [`SoAKitError::InvalidArgument`] if deserialization fails
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|e| SoAKitError::InvalidArgument(e.to_string()))
+    }
+
+    /// Serialize bulk to binary format using bincode
     ///
-    /// # Examples
+    /// The bincode encoding is preceded by a magic number and the version of the
+    /// layout, so that [`Bulk::from_binary`] can read binaries written by older
+    /// versions of this crate.
     ///
-    /// ```rust
-    /// use soakit::{Bulk, Registry, Value};
+    /// # Returns
     ///
-    /// let mut registry = Registry::new();
-    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
-    /// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+    /// Returns `Ok(Vec<u8>)` containing the binary representation, or an error if serialization fails.
     ///
-    /// let bulk = Bulk::new(3).unwrap();
-    /// let bulk = bulk.set(&registry, "age", vec![
-    ///     Value::ScalarInt(25),
-    ///     Value::ScalarInt(30),
-    ///     Value::ScalarInt(35),
-    /// ]).unwrap();
+    /// # Errors
     ///
-    /// let proxy = bulk.at(1).unwrap();
-    /// assert_eq!(proxy.get_field(&registry, "age").unwrap(), Value::ScalarInt(30));
-    /// ```
-    pub fn at(&self, idx: usize) -> Result<crate::proxy::Proxy> {
-        if idx >= self.meta.count {
-            return Err(SoAKitError::IndexOutOfBounds {
-                index: idx,
-                max: self.meta.count,
-            });
-        }
-        crate::proxy::Proxy::new(Rc::new(self.clone()), idx)
+    /// - [`SoAKitError::InvalidArgument`] if serialization fails
+    pub fn to_binary(&self) -> Result<Vec<u8>> {
+        let mut data = BINARY_MAGIC.to_vec();
+        data.extend_from_slice(&BINARY_VERSION.to_le_bytes());
+        bincode::serialize_into(&mut data, self)
+            .map_err(|e| SoAKitError::InvalidArgument(e.to_string()))?;
+        Ok(data)
     }
 
-    /// Apply a function to masked subset of data.
-    ///
-    /// This method applies a transformation function to the values at positions
-    /// where the mask is `true`, returning a new bulk with the updated values.
-    /// The function receives only the masked subset of values and must return
-    /// the same number of transformed values.
+    /// Deserialize bulk from binary format
     ///
-    /// If the mask is empty, it is treated as all `true` (applying to all elements).
+    /// Binaries written by [`Bulk::to_binary`] of any earlier version are read,
+    /// including those without a header; fields their layout lacks start out
+    /// at their defaults.
     ///
     /// # Arguments
     ///
-    /// * `mask` - Boolean array indicating which elements to transform (empty = all true)
-    /// * `func` - Function that takes a slice of values and returns transformed values
+    /// * `data` - Binary data to deserialize
     ///
     /// # Returns
     ///
-    /// Returns `Ok(Bulk)` with updated values, or an error if:
-    /// - The mask length doesn't match the bulk count (when mask is not empty)
-    /// - The function returns a different number of values than masked elements
-    /// - The function returns an error
+    /// Returns `Ok(Bulk)` if successful, or an error if deserialization fails.
     ///
     /// # Errors
     ///
-    /// - [`SoAKitError::LengthMismatch`] if mask length doesn't match or function returns wrong count
-    /// - [`SoAKitError::FieldNotFound`] if a field is missing
+    /// - [`SoAKitError::InvalidArgument`] if deserialization fails or the layout
+    ///   version is newer than this crate supports
+    pub fn from_binary(data: &[u8]) -> Result<Self> {
+        let decode_error = |e: bincode::Error| SoAKitError::InvalidArgument(e.to_string());
+        let Some(rest) = data.strip_prefix(&BINARY_MAGIC) else {
+            let legacy: LegacyBulk = bincode::deserialize(data).map_err(decode_error)?;
+            return legacy.into_bulk();
+        };
+        let (version, payload) = rest.split_first_chunk::<4>().ok_or_else(|| {
+            SoAKitError::InvalidArgument("Binary data ends inside its header".to_string())
+        })?;
+        match u32::from_le_bytes(*version) {
+            BINARY_VERSION => bincode::deserialize(payload).map_err(decode_error),
+            version => Err(SoAKitError::InvalidArgument(format!(
+                "Unsupported binary layout version {} (latest is {})",
+                version, BINARY_VERSION
+            ))),
+        }
+    }
+
+    /// Serialize bulk to TOML string
     ///
-    /// # Examples
+    /// # Returns
     ///
-    /// ```rust
-    /// use soakit::{Bulk, Registry, Value};
+    /// Returns `Ok(String)` containing the TOML representation, or an error if serialization fails.
     ///
-    /// let mut registry = Registry::new();
-    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
-    /// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+    /// # Errors
     ///
-    /// let bulk = Bulk::new(5).unwrap();
-    /// let bulk = bulk.set(&registry, "age", vec![
-    ///     Value::ScalarInt(10),
-    ///     Value::ScalarInt(20),
-    ///     Value::ScalarInt(30),
-    ///     Value::ScalarInt(40),
-    ///     Value::ScalarInt(50),
-    /// ]).unwrap();
+    /// - [`SoAKitError::InvalidArgument`] if serialization fails
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string(self).map_err(|e| SoAKitError::InvalidArgument(e.to_string()))
+    }
+
+    /// Deserialize bulk from TOML string
     ///
-    /// // Increment ages at positions 0, 2, 4
-    /// let mask = vec![true, false, true, false, true];
-    /// let new_bulk = bulk.apply(&mask, |subset| {
-    ///     Ok(subset.iter().map(|v| {
-    ///         if let Value::ScalarInt(i) = v {
-    ///             Value::ScalarInt(i + 1)
-    ///         } else {
-    ///             v.clone()
-    ///         }
-    ///     }).collect())
-    /// }).unwrap();
-    /// ```
-    pub fn apply<F>(&self, mask: &[bool], func: F) -> Result<Self>
-    where
-        F: Fn(&[Value]) -> Result<Vec<Value>>,
-    {
-        // Normalize mask: if empty, treat as all true
-        let normalized_mask = if mask.is_empty() {
-            vec![true; self.meta.count]
-        } else {
-            mask.to_vec()
-        };
+    /// # Arguments
+    ///
+    /// * `toml` - TOML string to deserialize
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` if successful, or an error if deserialization fails.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if deserialization fails
+    pub fn from_toml(toml: &str) -> Result<Self> {
+        toml::from_str(toml).map_err(|e| SoAKitError::InvalidArgument(e.to_string()))
+    }
 
-        // Validate mask length
-        if normalized_mask.len() != self.meta.count {
-            return Err(SoAKitError::LengthMismatch {
-                expected: self.meta.count,
-                actual: normalized_mask.len(),
-            });
-        }
+    /// Helper to convert bulk data to a vector of record maps containing Values.
+    fn to_records_values(&self) -> Vec<std::collections::BTreeMap<String, Value>> {
+        let mut records = Vec::with_capacity(self.meta.count);
 
-        // Create new bulk
-        let mut new_bulk = self.clone();
+        for (chunk_idx, chunk) in self.chunks.iter().enumerate() {
+            let chunk_start_id = chunk_idx.checked_mul(CHUNK_SIZE).unwrap_or(0); // Safe: chunk_idx is within bounds
 
-        // Get all data fields
-        let fields = self.list_data_fields();
+            for i in 0..chunk.len {
+                let mut record = std::collections::BTreeMap::new();
+                // Add ID
+                let id_idx = chunk_start_id.checked_add(i).unwrap_or(0); // Safe: within chunk bounds
+                #[allow(clippy::cast_possible_wrap)]
+                let id_val = self.meta.id.get(id_idx).copied().unwrap_or(0) as i64; // Safe: we know the index exists
+                let _ = record.insert("id".to_string(), Value::ScalarInt(id_val));
 
-        // Update each field
-        for field in fields {
-            // Get old values (reconstruct from chunks)
-            let mut old_values = Vec::with_capacity(self.meta.count);
-            for chunk in &self.chunks {
-                if let Some(chunk_val) = chunk.columns.get(&field) {
-                    // We need to flatten the vector value into scalars
-                    // This is inefficient but necessary for the current apply API which works on slices of Values
-                    match chunk_val {
-                        Value::VectorInt(v) => {
-                            old_values.extend(v.iter().map(|&x| Value::ScalarInt(x)))
-                        }
-                        Value::VectorFloat(v) => {
-                            old_values.extend(v.iter().map(|&x| Value::ScalarFloat(x)))
-                        }
-                        Value::VectorBool(v) => {
-                            old_values.extend(v.iter().map(|&x| Value::ScalarBool(x)))
-                        }
-                        Value::VectorString(v) => {
-                            old_values.extend(v.iter().map(|x| Value::ScalarString(x.clone())))
-                        }
-                        _ => {
-                            return Err(SoAKitError::InvalidArgument(format!(
-                                "Field {} is not a vector",
-                                field
-                            )));
-                        }
+                // Add fields
+                for name in chunk.field_names() {
+                    // Skip system fields
+                    if name.starts_with('_') {
+                        continue;
+                    }
+
+                    // Get value at index i from the vector value
+                    let Some(values) = chunk.column(&name) else {
+                        continue;
+                    };
+                    if let Ok(val) = column_element(&values, i) {
+                        let _ = record.insert(name.clone(), val);
                     }
                 }
+                records.push(record);
             }
-
-            if old_values.len() != self.meta.count {
-                return Err(SoAKitError::FieldNotFound(format!(
-                    "Field {} data incomplete",
-                    field
-                )));
+        }
+        records
+    }
+
+    /// Helper to create Bulk from intermediate Value records.
+    fn from_records_values(
+        records: Vec<std::collections::BTreeMap<String, Value>>,
+        registry: &crate::meta::Registry,
+    ) -> Result<Self> {
+        let count = records.len();
+        if count == 0 {
+            return Err(SoAKitError::InvalidArgument(
+                "Cannot create Bulk from empty records".to_string(),
+            ));
+        }
+
+        let records = records
+            .into_iter()
+            .enumerate()
+            .map(|(i, record)| Self::resolve_record(registry, i, record))
+            .collect::<Result<Vec<_>>>()?;
+        let bulk = Bulk::new(count)?;
+        let mut current_bulk = bulk;
+
+        for (name, meta) in registry.iter() {
+            if meta.is_derived || meta.is_virtual() {
+                continue;
             }
 
-            // Extract subset based on mask
-            let subset: Vec<Value> = old_values
-                .iter()
-                .enumerate()
-                .filter_map(|(idx, val)| {
-                    if normalized_mask.get(idx).copied().unwrap_or(false) {
-                        Some(val.clone())
-                    } else {
-                        None
-                    }
-                })
-                .collect();
+            let mut values = Vec::with_capacity(count);
+
+            for (i, record) in records.iter().enumerate() {
+                values.push(Self::record_value(meta, name, i, record)?);
+            }
 
-            // Apply function to subset
-            let new_subset = func(&subset)?;
+            current_bulk = current_bulk.set(registry, name, values)?;
+        }
 
-            // Validate new subset length matches mask count
-            let mask_count = normalized_mask.iter().filter(|&&b| b).count();
-            if new_subset.len() != mask_count {
-                return Err(SoAKitError::LengthMismatch {
-                    expected: mask_count,
-                    actual: new_subset.len(),
-                });
+        Ok(current_bulk)
+    }
+
+    /// Helper to rename the alias keys of record `i` to their field names.
+    fn resolve_record(
+        registry: &Registry,
+        i: usize,
+        record: BTreeMap<String, Value>,
+    ) -> Result<BTreeMap<String, Value>> {
+        let mut resolved = BTreeMap::new();
+        for (key, value) in record {
+            let field = registry.resolve(&key).to_string();
+            if resolved.insert(field.clone(), value).is_some() {
+                return Err(SoAKitError::InvalidArgument(format!(
+                    "Record at index {} gives field '{}' more than once",
+                    i, field
+                )));
             }
+        }
+        Ok(resolved)
+    }
 
-            // Update values for masked positions
-            let mut new_values = old_values;
-            let mut subset_idx = 0;
-            for (idx, mask_val) in normalized_mask.iter().enumerate() {
-                if *mask_val && let Some(new_val) = new_subset.get(subset_idx) {
-                    if let Some(old_val) = new_values.get_mut(idx) {
-                        *old_val = new_val.clone();
-                    }
-                    subset_idx = subset_idx.checked_add(1).unwrap_or(subset_idx); // Safe: iterating sequentially
-                }
+    /// Helper to get the value to store for a field from record `i`.
+    fn record_value(
+        meta: &crate::meta::FieldMetadata,
+        name: &str,
+        i: usize,
+        record: &std::collections::BTreeMap<String, Value>,
+    ) -> Result<Value> {
+        if let Some(val) = record.get(name) {
+            // Validate
+            if (meta.validator)(val) {
+                return Ok(val.clone());
             }
 
-            // Rechunk revised values
-            for (i, chunk) in new_bulk.chunks.iter_mut().enumerate() {
-                let start = i.checked_mul(CHUNK_SIZE).unwrap_or(0); // Safe: chunk index is valid
-                let end = std::cmp::min(
-                    start.checked_add(CHUNK_SIZE).unwrap_or(start), // Safe: adding constant
-                    self.meta.count,
-                );
-                let chunk_values = new_values
-                    .get(start..end)
-                    .ok_or_else(|| SoAKitError::InvalidArgument("Slice out of bounds".to_string()))?
-                    .to_vec();
+            // Untagged records lose the exact numeric type, e.g. a float written
+            // as `1`, so cast to the field's declared type where nothing is lost
+            if let Some(cast) = meta
+                .field_type
+                .and_then(|field_type| field_type.coerce(val))
+                .filter(|cast| (meta.validator)(cast))
+            {
+                return Ok(cast);
+            }
 
-                let vector_value = Value::from_scalars(chunk_values)?;
-                let _ = chunk.columns.insert(field.clone(), vector_value);
+            // Untagged records lose the JSON cell wrapper, so retry as a JSON cell
+            let json = Value::ScalarJson(val.to_untagged_json_value());
+            if !(meta.validator)(&json) {
+                let mismatch = meta
+                    .field_type
+                    .and_then(|field_type| field_type.mismatch(val));
+                return Err(SoAKitError::InvalidArgument(match mismatch {
+                    Some(mismatch) => format!(
+                        "Invalid value for field '{}' at index {}: {:?} ({})",
+                        name, i, val, mismatch
+                    ),
+                    None => format!(
+                        "Invalid value for field '{}' at index {}: {:?}",
+                        name, i, val
+                    ),
+                }));
             }
+            Ok(json)
+        } else if let Some(default) = &meta.default {
+            Ok(default.clone())
+        } else if (meta.validator)(&Value::Null) {
+            // Fields that accept Null may be omitted, e.g. in TOML
+            Ok(Value::Null)
+        } else {
+            Err(SoAKitError::InvalidArgument(format!(
+                "Missing field '{}' at index {}",
+                name, i
+            )))
+        }
+    }
 
-            // Increment version
-            let current_ver = new_bulk.meta.versions.get(&field).copied().unwrap_or(0);
-            let new_ver = current_ver
-                .checked_add(1)
-                .ok_or_else(|| SoAKitError::InvalidArgument("Version overflow".to_string()))?;
-            let _ = new_bulk.meta.versions.insert(field, new_ver);
+    /// Helper to convert parsed JSON records into Value records.
+    ///
+    /// `convert` turns each JSON field value into a [`Value`].
+    fn records_from_json_array(
+        records_json: Vec<serde_json::Value>,
+        convert: impl Fn(serde_json::Value) -> Result<Value>,
+    ) -> Result<Vec<std::collections::BTreeMap<String, Value>>> {
+        records_json
+            .into_iter()
+            .enumerate()
+            .map(|(i, item)| Self::record_from_json(i, item, &convert))
+            .collect()
+    }
+
+    /// Helper to convert parsed JSON record `i` into a Value record.
+    fn record_from_json(
+        i: usize,
+        item: serde_json::Value,
+        convert: &impl Fn(serde_json::Value) -> Result<Value>,
+    ) -> Result<std::collections::BTreeMap<String, Value>> {
+        let serde_json::Value::Object(obj) = item else {
+            return Err(SoAKitError::InvalidArgument(format!(
+                "Record {} is not an object",
+                i
+            )));
+        };
+        obj.into_iter().map(|(k, v)| Ok((k, convert(v)?))).collect()
+    }
+
+    /// Helper to parse a JSON string into an array of records.
+    fn parse_json_records(json: &str) -> Result<Vec<serde_json::Value>> {
+        let parsed: serde_json::Value =
+            serde_json::from_str(json).map_err(|e| SoAKitError::InvalidArgument(e.to_string()))?;
+
+        match parsed {
+            serde_json::Value::Array(arr) => Ok(arr),
+            _ => Err(SoAKitError::InvalidArgument(
+                "Expected JSON array of objects".to_string(),
+            )),
         }
+    }
 
-        Ok(new_bulk)
+    /// Serialize bulk to a JSON string of records (AoS format).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if JSON serialization fails.
+    pub fn to_records_json(&self) -> Result<String> {
+        let records_values = self.to_records_values();
+
+        // Convert Values to untagged JSON values
+        let records: Vec<serde_json::Map<String, serde_json::Value>> = records_values
+            .into_iter()
+            .map(|record| {
+                record
+                    .into_iter()
+                    .map(|(k, v)| (k, v.to_untagged_json_value()))
+                    .collect()
+            })
+            .collect();
+
+        serde_json::to_string(&records).map_err(|e| SoAKitError::InvalidArgument(e.to_string()))
     }
 
-    /// Partition the bulk by a field's values.
+    /// Deserialize bulk from a JSON string of records.
     ///
-    /// Creates a [`View`] for each unique value in the specified field. Each view
-    /// represents a partition containing all elements that have that particular value.
+    /// Records may name fields by an alias (see [`Registry::add_alias`]). Values
+    /// of fields with a [`FieldType`](crate::FieldType) are cast to it where
+    /// nothing is lost (see [`FieldType::coerce`](crate::FieldType::coerce)), so
+    /// a float written as `1` is read back as a float.
     ///
-    /// This is useful for grouping data by categorical values or performing
-    /// operations on subsets of the data.
+    /// # Errors
     ///
-    /// # Arguments
+    /// Returns an error if:
+    /// - JSON parsing fails
+    /// - A record is not a valid JSON object
+    /// - Field values cannot be converted to the expected types
+    /// - Required fields are missing
+    pub fn from_records_json(json: &str, registry: &crate::meta::Registry) -> Result<Self> {
+        let records_json = Self::parse_json_records(json)?;
+        let records_values =
+            Self::records_from_json_array(records_json, Value::from_untagged_json_value)?;
+
+        Self::from_records_values(records_values, registry)
+    }
+
+    /// Deserialize bulk from a JSON string of records, skipping bad records.
     ///
-    /// * `registry` - The registry containing field metadata
-    /// * `field` - The name of the field to partition by
+    /// This is [`Bulk::from_records_json`] for imports where a few malformed
+    /// records should not abort the whole load. A record that is not an object,
+    /// has a value that cannot be converted, or fails a field's validation is left
+    /// out and reported instead; a record failing several fields is reported once
+    /// per field. Each kept record's position in the input becomes its element ID
+    /// (see [`Meta::id`]), so elements can be traced back to their records.
     ///
     /// # Returns
     ///
-    /// Returns `Ok(Vec<View>)` with one view per unique value, or an error if:
-    /// - The field is not registered or has no data
-    /// - The field is not a vector type
+    /// Returns `Ok((Bulk, Vec<RowError>))` with the kept records and the errors of
+    /// the skipped ones, whose indices are positions in the input.
     ///
     /// # Errors
     ///
-    /// - [`SoAKitError::FieldNotFound`] if the field doesn't exist or has no data
-    /// - [`SoAKitError::InvalidArgument`] if the field is not a vector
+    /// Returns an error if:
+    /// - JSON parsing fails or the JSON is not an array
+    /// - No record is kept
+    /// - The kept records cannot be stored together, e.g. vectors of different lengths
     ///
     /// # Examples
     ///
@@ -1210,967 +2294,9457 @@ impl Bulk {
     ///
     /// let mut registry = Registry::new();
     /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
-    /// registry.register("category".to_string(), validator, false, vec![], None).unwrap();
-    ///
-    /// let bulk = Bulk::new(6).unwrap();
-    /// let bulk = bulk.set(&registry, "category", vec![
-    ///     Value::ScalarInt(1),
-    ///     Value::ScalarInt(2),
-    ///     Value::ScalarInt(1),
-    ///     Value::ScalarInt(3),
-    ///     Value::ScalarInt(2),
-    ///     Value::ScalarInt(1),
-    /// ]).unwrap();
+    /// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
     ///
-    /// let views = bulk.partition_by(&registry, "category").unwrap();
-    /// assert_eq!(views.len(), 3); // Three unique categories
+    /// let json = r#"[{"age": 30}, {"age": "old"}, 7, {"age": 40}]"#;
+    /// let (bulk, errors) = Bulk::from_records_json_tolerant(json, &registry).unwrap();
+    /// assert_eq!(bulk.get(&registry, "age").unwrap(), Value::VectorInt(vec![30, 40]));
+    /// assert_eq!(bulk.meta.id, vec![0, 3]);
+    /// assert_eq!(errors.iter().map(|e| e.index).collect::<Vec<_>>(), vec![1, 2]);
+    /// assert_eq!(errors[0].field.as_deref(), Some("age"));
     /// ```
-    pub fn partition_by(&self, registry: &Registry, field: &str) -> Result<Vec<crate::view::View>> {
-        // Check if field exists in data
-        if !self.list_data_fields().contains(&field.to_string()) {
-            return Err(SoAKitError::FieldNotFound(field.to_string()));
+    pub fn from_records_json_tolerant(
+        json: &str,
+        registry: &Registry,
+    ) -> Result<(Self, Vec<RowError>)> {
+        let mut stored = Vec::new();
+        for name in registry.list_fields() {
+            let meta = registry
+                .get_metadata(&name)
+                .ok_or_else(|| SoAKitError::FieldNotFound(name.clone()))?;
+            if !meta.is_derived && !meta.is_virtual() {
+                stored.push((name, meta));
+            }
         }
 
-        // Get field values
-        let field_value = self.get(registry, field)?;
-
-        // Extract unique values and create masks
-        let (unique_values, masks) = match field_value {
-            Value::VectorInt(v) => {
-                let unique: Vec<i64> = v
-                    .iter()
-                    .cloned()
-                    .collect::<HashSet<_>>()
-                    .into_iter()
-                    .collect();
-                let mut unique_sorted = unique;
-                unique_sorted.sort();
-                let masks: Vec<Vec<bool>> = unique_sorted
-                    .iter()
-                    .map(|&val| v.iter().map(|&x| x == val).collect())
-                    .collect();
-                let unique_values: Vec<Value> =
-                    unique_sorted.into_iter().map(Value::ScalarInt).collect();
-                (unique_values, masks)
-            }
-            Value::VectorFloat(v) => {
-                // For floats, we need to handle NaN and comparison carefully
-                // Use a hash set with bit representation for NaN-safe comparison
-                let mut seen = HashSet::new();
-                let mut unique = Vec::new();
-                for &val in &v {
-                    // Use bit representation for NaN-safe comparison
-                    let bits = f64::to_bits(val);
-                    if seen.insert(bits) {
-                        unique.push(val);
-                    }
+        let mut records = Vec::new();
+        let mut kept = Vec::new();
+        let mut errors = Vec::new();
+        for (i, item) in Self::parse_json_records(json)?.into_iter().enumerate() {
+            let record = Self::record_from_json(i, item, &Value::from_untagged_json_value)
+                .and_then(|record| Self::resolve_record(registry, i, record));
+            let record = match record {
+                Ok(record) => record,
+                Err(error) => {
+                    errors.push(RowError {
+                        index: i,
+                        field: None,
+                        error,
+                    });
+                    continue;
                 }
-                unique.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
-                let masks: Vec<Vec<bool>> = unique
-                    .iter()
-                    .map(|&val| {
-                        v.iter()
-                            .map(|&x| {
-                                if val.is_nan() && x.is_nan() {
-                                    true
-                                } else {
-                                    x == val
-                                }
-                            })
-                            .collect()
+            };
+            let failures: Vec<RowError> = stored
+                .iter()
+                .filter_map(|(name, meta)| {
+                    let error = Self::record_value(meta, name, i, &record).err()?;
+                    Some(RowError {
+                        index: i,
+                        field: Some(name.clone()),
+                        error,
                     })
-                    .collect();
-                let unique_values: Vec<Value> =
-                    unique.into_iter().map(Value::ScalarFloat).collect();
-                (unique_values, masks)
-            }
-            Value::VectorBool(v) => {
-                let unique = vec![true, false];
-                let masks: Vec<Vec<bool>> = unique
-                    .iter()
-                    .map(|&val| v.iter().map(|&x| x == val).collect())
-                    .collect();
-                let unique_values: Vec<Value> = unique.into_iter().map(Value::ScalarBool).collect();
-                (unique_values, masks)
+                })
+                .collect();
+            if failures.is_empty() {
+                records.push(record);
+                kept.push(i);
+            } else {
+                errors.extend(failures);
             }
-            Value::VectorString(v) => {
-                let unique: Vec<String> = v
-                    .iter()
-                    .cloned()
-                    .collect::<HashSet<_>>()
+        }
+
+        let mut bulk = Self::from_records_values(records, registry)?;
+        bulk.meta.id = kept;
+        Ok((bulk, errors))
+    }
+
+    /// Serialize bulk to a JSON string of records, tagging every value with its variant.
+    ///
+    /// Unlike [`Bulk::to_records_json`], each field value is written in the tagged
+    /// form used by `Value`'s serde implementation (e.g. `{"ScalarFloat": 1.0}`), so
+    /// [`Bulk::from_records_json_tagged`] reconstructs the exact variants. The
+    /// untagged format has to infer types on import, so it cannot tell a float
+    /// written as `1` from an integer, an empty float vector from an empty integer
+    /// vector, or a tensor from nested vectors.
+    ///
+    /// Non-finite floats cannot be represented in JSON and are written as `null`,
+    /// which fails on import.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(String)` containing the tagged JSON records.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if JSON serialization fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
+    /// registry.register("weight".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(1).unwrap();
+    /// let bulk = bulk.set(&registry, "weight", vec![Value::ScalarFloat(1.0)]).unwrap();
+    ///
+    /// let json = bulk.to_records_json_tagged().unwrap();
+    /// assert_eq!(json, r#"[{"id":{"ScalarInt":0},"weight":{"ScalarFloat":1.0}}]"#);
+    ///
+    /// let restored = Bulk::from_records_json_tagged(&json, &registry).unwrap();
+    /// assert_eq!(
+    ///     restored.get(&registry, "weight").unwrap(),
+    ///     Value::VectorFloat(vec![1.0])
+    /// );
+    /// ```
+    pub fn to_records_json_tagged(&self) -> Result<String> {
+        serde_json::to_string(&self.to_records_values())
+            .map_err(|e| SoAKitError::InvalidArgument(e.to_string()))
+    }
+
+    /// Deserialize bulk from a JSON string of tagged records.
+    ///
+    /// This is the inverse of [`Bulk::to_records_json_tagged`]: every field value
+    /// must be in the tagged form (e.g. `{"VectorInt": [1, 2]}`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - JSON parsing fails
+    /// - A record is not a valid JSON object
+    /// - A field value is not a valid tagged Value
+    /// - Field values fail validation or required fields are missing
+    pub fn from_records_json_tagged(json: &str, registry: &crate::meta::Registry) -> Result<Self> {
+        let records_json = Self::parse_json_records(json)?;
+        let records_values = Self::records_from_json_array(records_json, |v| {
+            serde_json::from_value(v).map_err(|e| SoAKitError::InvalidArgument(e.to_string()))
+        })?;
+
+        Self::from_records_values(records_values, registry)
+    }
+
+    /// Serialize bulk to a TOML string of records.
+    ///
+    /// TOML has no null, so missing elements of option vectors are left out of
+    /// their records. [`Bulk::from_records_toml`] reads them back as
+    /// [`Value::Null`] if the field's validator accepts `Null`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if TOML serialization fails.
+    pub fn to_records_toml(&self) -> Result<String> {
+        let records_values = self.to_records_values();
+
+        // Convert Values to untagged JSON values (TOML uses serde data model)
+        let records: Vec<serde_json::Map<String, serde_json::Value>> = records_values
+            .into_iter()
+            .map(|record| {
+                record
                     .into_iter()
-                    .collect();
-                let mut unique_sorted = unique;
-                unique_sorted.sort();
-                let masks: Vec<Vec<bool>> = unique_sorted
-                    .iter()
-                    .map(|val| v.iter().map(|x| x == val).collect())
-                    .collect();
-                let unique_values: Vec<Value> =
-                    unique_sorted.into_iter().map(Value::ScalarString).collect();
-                (unique_values, masks)
-            }
+                    .filter(|(_, v)| !v.is_null())
+                    .map(|(k, v)| (k, v.to_untagged_json_value()))
+                    .collect()
+            })
+            .collect();
+
+        let mut map = std::collections::BTreeMap::new();
+        let _ = map.insert("records".to_string(), records);
+
+        toml::to_string(&map).map_err(|e| SoAKitError::InvalidArgument(e.to_string()))
+    }
+
+    /// Deserialize bulk from a TOML string of records.
+    /// Expects `[[records]]` format.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - TOML parsing fails
+    /// - The TOML structure is invalid (missing `records` key)
+    /// - A record is not a valid object
+    /// - Field values cannot be converted to the expected types
+    /// - Required fields are missing
+    pub fn from_records_toml(toml: &str, registry: &crate::meta::Registry) -> Result<Self> {
+        let parsed: serde_json::Value =
+            toml::from_str(toml).map_err(|e| SoAKitError::InvalidArgument(e.to_string()))?;
+
+        let records_json = match parsed {
+            serde_json::Value::Object(mut obj) => match obj.remove("records") {
+                Some(serde_json::Value::Array(arr)) => arr,
+                _ => {
+                    return Err(SoAKitError::InvalidArgument(
+                        "Expected 'records' array in TOML".to_string(),
+                    ));
+                }
+            },
             _ => {
                 return Err(SoAKitError::InvalidArgument(
-                    "Partition field must be a vector".to_string(),
+                    "Expected TOML table with 'records' array".to_string(),
                 ));
             }
         };
 
-        // Create views
-        let bulk_rc = Rc::new(self.clone());
-        let views: Result<Vec<crate::view::View>> = unique_values
-            .into_iter()
-            .zip(masks)
-            .map(|(key, mask)| crate::view::View::new(key, mask, bulk_rc.clone()))
-            .collect();
+        let records_values =
+            Self::records_from_json_array(records_json, Value::from_untagged_json_value)?;
+
+        Self::from_records_values(records_values, registry)
+    }
+
+    /// Serialize bulk to a binary format of records.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if binary serialization fails.
+    pub fn to_records_binary(&self) -> Result<Vec<u8>> {
+        let records = self.to_records_values();
+        bincode::serialize(&records).map_err(|e| SoAKitError::InvalidArgument(e.to_string()))
+    }
+
+    /// Deserialize bulk from a binary format of records.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Binary deserialization fails
+    /// - Field values cannot be converted to the expected types
+    /// - Required fields are missing
+    pub fn from_records_binary(data: &[u8], registry: &crate::meta::Registry) -> Result<Self> {
+        let records: Vec<std::collections::BTreeMap<String, Value>> =
+            bincode::deserialize(data).map_err(|e| SoAKitError::InvalidArgument(e.to_string()))?;
+
+        Self::from_records_values(records, registry)
+    }
+
+    /// Get field values.
+    ///
+    /// Retrieves the values for a field. For regular fields, this returns the
+    /// it from cache if valid) and returns it.
+    ///
+    /// The returned value is always a vector type (`VectorInt`, `VectorFloat`, etc.)
+    /// representing all elements' values for that field.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `field` - The name of the field to retrieve, or an alias of it (see [`Registry::add_alias`])
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Value)` containing the field values as a vector, or an error if:
+    /// - The field is not registered
+    /// - The field has no data (for regular fields)
+    /// - Derived field computation fails
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field is not registered or has no data
+    /// - [`SoAKitError::InvalidArgument`] if derived field computation fails
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(3).unwrap();
+    /// let values = vec![
+    ///     Value::ScalarInt(25),
+    ///     Value::ScalarInt(30),
+    ///     Value::ScalarInt(35),
+    /// ];
+    /// let bulk = bulk.set(&registry, "age", values).unwrap();
+    ///
+    /// if let Value::VectorInt(ages) = bulk.get(&registry, "age").unwrap() {
+    ///     assert_eq!(ages, vec![25, 30, 35]);
+    /// }
+    /// ```
+    pub fn get(&self, registry: &Registry, field: &str) -> Result<Value> {
+        let field = registry.resolve(field);
+        registry.notify_access(field);
+        self.get_resolved(registry, field)
+    }
+
+    /// Get the values of a field named by its field name, not an alias, without
+    /// running the deprecation handler.
+    pub(crate) fn get_resolved(&self, registry: &Registry, field: &str) -> Result<Value> {
+        let metadata = registry
+            .get_metadata(field)
+            .ok_or_else(|| SoAKitError::FieldNotFound(field.to_string()))?;
+
+        if let Some(resolver) = &metadata.resolver {
+            // Virtual fields are resolved on every access, never cached
+            let chunk_offsets = chunk_offsets(self.chunks.iter().map(|chunk| chunk.len));
+            let context = DerivedContext {
+                ids: &self.meta.id,
+                count: self.meta.count,
+                chunk_offsets: &chunk_offsets,
+            };
+            let value = resolver(&context)?;
+            metadata.check_output(field, &value, self.meta.count)?;
+            return Ok(value);
+        }
+
+        if metadata.is_derived {
+            let current_dep_versions =
+                self.dependency_versions(registry, &metadata.dependencies)?;
+            // Values read from virtual fields may change without a version bump
+            let cacheable = !metadata.reads_virtual;
+
+            // Check cache
+            let cache_borrow = self.cache.borrow();
+            if let Some(cache_entry) = cache_borrow.get(field)
+                && cacheable
+                && cache_entry.versions == current_dep_versions
+            {
+                return Ok(cache_entry.value.clone());
+            }
+            drop(cache_borrow); // Release borrow before mutable borrow
+            // A stale entry still holds the chunks whose dependencies are unchanged
+            let stale = self.cache.borrow_mut().remove(field);
+
+            let derived_func = metadata.derived_func.as_ref().ok_or_else(|| {
+                SoAKitError::InvalidArgument("Derived field missing function".to_string())
+            })?;
+
+            if let Some(multi_output) = &metadata.multi_output {
+                return self.derive_multi(
+                    registry,
+                    field,
+                    &metadata.dependencies,
+                    multi_output,
+                    cacheable.then_some(current_dep_versions),
+                );
+            }
+
+            // Compute derived value
+            let (computed_value, chunk_versions) =
+                if metadata.chunk_safe && cacheable && !self.chunks.is_empty() {
+                    let chunk_versions = self.chunk_dependency_versions(&metadata.dependencies);
+                    let value = self.derive_by_chunk(
+                        registry,
+                        &metadata.dependencies,
+                        derived_func.as_ref(),
+                        stale.as_ref().zip(chunk_versions.as_deref()),
+                    )?;
+                    (value, chunk_versions.unwrap_or_default())
+                } else {
+                    let dep_values: Result<Vec<Value>> = metadata
+                        .dependencies
+                        .iter()
+                        .map(|dep| self.get_resolved(registry, dep))
+                        .collect();
+                    let chunk_offsets = chunk_offsets(self.chunks.iter().map(|chunk| chunk.len));
+                    let context = DerivedContext {
+                        ids: &self.meta.id,
+                        count: self.meta.count,
+                        chunk_offsets: &chunk_offsets,
+                    };
+                    (metadata.derive(&context, &dep_values?)?, Vec::new())
+                };
+
+            metadata.check_output(field, &computed_value, self.meta.count)?;
+            if !cacheable {
+                return Ok(computed_value);
+            }
+
+            // Update cache
+            let mut cache_mut = self.cache.borrow_mut();
+            let _ = cache_mut.insert(
+                field.to_string(),
+                CacheEntry {
+                    value: computed_value.clone(),
+                    versions: current_dep_versions,
+                    chunk_versions,
+                },
+            );
+
+            Ok(computed_value)
+        } else {
+            // Regular field - get from chunks
+            if self.meta.count == 0 {
+                return Ok(Value::VectorInt(Vec::new()));
+            }
+
+            let mut result_value: Option<Value> = None;
+
+            for chunk in &self.chunks {
+                if let Some(chunk_val) = chunk.column(field) {
+                    if let Some(res) = &mut result_value {
+                        res.append(chunk_val.into_owned())?;
+                    } else {
+                        result_value = Some(chunk_val.into_owned());
+                    }
+                } else {
+                    return Err(SoAKitError::FieldNotFound(format!(
+                        "Field {} missing in chunk",
+                        field
+                    )));
+                }
+            }
+
+            result_value.ok_or_else(|| SoAKitError::FieldNotFound(field.to_string()))
+        }
+    }
+
+    /// Compute every output of a multi-output derived field in one call and cache
+    /// them together, returning the value of `field`.
+    ///
+    /// The outputs share their dependencies, so each cache entry records the same
+    /// dependency versions. Without versions, nothing is cached.
+    fn derive_multi(
+        &self,
+        registry: &Registry,
+        field: &str,
+        dependencies: &[String],
+        multi_output: &MultiOutput,
+        versions: Option<Vec<u64>>,
+    ) -> Result<Value> {
+        let dep_values = dependencies
+            .iter()
+            .map(|dep| self.get_resolved(registry, dep))
+            .collect::<Result<Vec<Value>>>()?;
+        let values = multi_output.compute(&dep_values)?;
+        for (name, value) in multi_output.names.iter().zip(&values) {
+            if let Some(metadata) = registry.get_metadata(name) {
+                metadata.check_output(name, value, self.meta.count)?;
+            }
+        }
+
+        let Some(versions) = versions else {
+            let position = multi_output.names.iter().position(|name| name == field);
+            return position
+                .and_then(|i| values.into_iter().nth(i))
+                .ok_or_else(|| SoAKitError::FieldNotFound(field.to_string()));
+        };
+        let mut result = None;
+        let mut cache_mut = self.cache.borrow_mut();
+        for (name, value) in multi_output.names.iter().zip(values) {
+            if name == field {
+                result = Some(value.clone());
+            }
+            let _ = cache_mut.insert(
+                name.clone(),
+                CacheEntry {
+                    value,
+                    versions: versions.clone(),
+                    chunk_versions: Vec::new(),
+                },
+            );
+        }
+        result.ok_or_else(|| SoAKitError::FieldNotFound(field.to_string()))
+    }
+
+    /// When a field is updated, any derived fields that depend on it need to
+    /// have their cache invalidated so they will be recomputed on the next access.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry to check for dependent fields
+    /// * `field` - The name of the field that was updated
+    fn invalidate_dependent_cache(&mut self, registry: &Registry, field: &str) {
+        let fields_to_invalidate: Vec<&str> = registry
+            .iter()
+            .filter(|(_, metadata)| metadata.dependencies.iter().any(|dep| dep == field))
+            .map(|(name, _)| name)
+            .collect();
+
+        let mut cache_mut = self.cache.borrow_mut();
+        for f in &fields_to_invalidate {
+            let _ = cache_mut.remove(*f);
+        }
+        drop(cache_mut); // Release the borrow before recursive calls
+
+        // Recursively invalidate fields that depend on the invalidated fields
+        for f in fields_to_invalidate {
+            self.invalidate_dependent_cache(registry, f);
+        }
+    }
+
+    /// Invalidate the derived caches that depend on a field after an update of
+    /// some of its chunks.
+    ///
+    /// Entries tracked chunk by chunk are kept, since [`Bulk::get`] recomputes only
+    /// the chunks whose dependencies changed. Other dependents are invalidated as
+    /// by [`Bulk::invalidate_dependent_cache`], and so is everything depending on
+    /// any dependent.
+    fn invalidate_unchunked_cache(&mut self, registry: &Registry, field: &str) {
+        let dependents = registry.dependents_of(field);
+
+        let mut cache_mut = self.cache.borrow_mut();
+        for f in &dependents {
+            if cache_mut
+                .get(f)
+                .is_some_and(|entry| entry.chunk_versions.is_empty())
+            {
+                let _ = cache_mut.remove(f);
+            }
+        }
+        drop(cache_mut);
+
+        for f in dependents {
+            self.invalidate_dependent_cache(registry, &f);
+        }
+    }
+
+    /// Get the versions of a derived field's dependencies, as kept in its cache entry.
+    ///
+    /// Derived dependencies have no version of their own and count as `0`; their
+    /// cache entries are invalidated along with the fields they depend on.
+    fn dependency_versions(
+        &self,
+        registry: &Registry,
+        dependencies: &[String],
+    ) -> Result<Vec<u64>> {
+        dependencies
+            .iter()
+            .map(|dep| match registry.get_metadata(dep) {
+                Some(meta) if meta.is_derived || meta.is_virtual() => Ok(0),
+                Some(_) => self
+                    .meta
+                    .versions
+                    .get(dep)
+                    .copied()
+                    .ok_or_else(|| SoAKitError::FieldNotFound(dep.clone())),
+                None => Err(SoAKitError::FieldNotFound(dep.clone())),
+            })
+            .collect()
+    }
+
+    /// Get the version of each dependency in each chunk, or `None` unless every
+    /// dependency is stored in the chunks.
+    fn chunk_dependency_versions(&self, dependencies: &[String]) -> Option<Vec<Vec<u64>>> {
+        (0..self.chunks.len())
+            .map(|i| {
+                dependencies
+                    .iter()
+                    .map(|dep| self.chunk_version(dep, i))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Get the version of one chunk's column of a stored field.
+    ///
+    /// A column's version is its field's version in [`Meta::versions`], unless the
+    /// column was updated apart from the rest of the field, as by
+    /// [`Bulk::set_element`]: then only that chunk's column moves to the field's new
+    /// version, and the other columns keep their previous versions. Derived fields
+    /// cached chunk by chunk use these versions to recompute only the chunks whose
+    /// dependencies changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `field` - The name of a stored field
+    /// * `chunk` - The index of the chunk
+    ///
+    /// # Returns
+    ///
+    /// The column's version, or `None` if the chunk does not exist or does not
+    /// store the field.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::bulk::CHUNK_SIZE;
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("n".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(2 * CHUNK_SIZE).unwrap();
+    /// let bulk = bulk.set(&registry, "n", vec![Value::ScalarInt(0); 2 * CHUNK_SIZE]).unwrap();
+    /// let bulk = bulk.set_element(&registry, "n", CHUNK_SIZE, Value::ScalarInt(1)).unwrap();
+    /// assert_eq!(bulk.meta.versions["n"], 2);
+    /// assert_eq!(bulk.chunk_version("n", 0), Some(1));
+    /// assert_eq!(bulk.chunk_version("n", 1), Some(2));
+    /// ```
+    pub fn chunk_version(&self, field: &str, chunk: usize) -> Option<u64> {
+        let chunk = self.chunks.get(chunk).filter(|c| c.has_column(field))?;
+        chunk
+            .versions
+            .get(field)
+            .or_else(|| self.meta.versions.get(field))
+            .copied()
+    }
+
+    /// Compute a chunk-safe derived field one chunk at a time.
+    ///
+    /// Stored dependencies are read from each chunk's columns; derived
+    /// dependencies are computed whole and sliced. The per-chunk results are
+    /// concatenated, and computed in parallel with the `rayon` feature.
+    ///
+    /// `stale` pairs a previous cache entry with the current dependency versions of
+    /// each chunk; chunks whose versions match the entry's are sliced from it
+    /// instead of being recomputed.
+    fn derive_by_chunk(
+        &self,
+        registry: &Registry,
+        dependencies: &[String],
+        derived_func: &(dyn Fn(&[Value]) -> Result<Value> + Send + Sync),
+        stale: Option<(&CacheEntry, &[Vec<u64>])>,
+    ) -> Result<Value> {
+        let stale = stale.filter(|(entry, _)| {
+            entry.value.len() == self.meta.count && entry.chunk_versions.len() == self.chunks.len()
+        });
+        // None for a dependency stored in the chunks, Some(whole value) otherwise
+        let whole = dependencies
+            .iter()
+            .map(|dep| {
+                let stored = self.chunks.iter().all(|chunk| chunk.has_column(dep));
+                if stored {
+                    Ok(None)
+                } else {
+                    self.get_resolved(registry, dep).map(Some)
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let bounds = self.all_chunk_bounds()?;
+        let tasks: Vec<_> = self.chunks.iter().zip(bounds).enumerate().collect();
+
+        let parts = map_tasks(&tasks, |&(i, (chunk, (start, end)))| {
+            if let Some((entry, current)) = stale
+                && entry
+                    .chunk_versions
+                    .get(i)
+                    .is_some_and(|cached| current.get(i) == Some(cached))
+            {
+                return entry.value.slice(start..end);
+            }
+            let args = dependencies
+                .iter()
+                .zip(&whole)
+                .map(|(dep, whole)| match whole {
+                    Some(value) => value.slice(start..end),
+                    None => chunk
+                        .column(dep)
+                        .map(std::borrow::Cow::into_owned)
+                        .ok_or_else(|| SoAKitError::FieldNotFound(dep.clone())),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let part = derived_func(&args)?;
+            if part.len() != chunk.len {
+                return Err(SoAKitError::LengthMismatch {
+                    expected: chunk.len,
+                    actual: part.len(),
+                });
+            }
+            Ok(part)
+        })?;
+        Value::concat(&parts)
+    }
+
+    /// Get the count of elements in this bulk.
+    ///
+    /// # Returns
+    ///
+    /// The number of elements in the bulk as a `usize`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Bulk;
+    ///
+    /// let bulk = Bulk::new(10).unwrap();
+    /// assert_eq!(bulk.count(), 10);
+    /// ```
+    pub const fn count(&self) -> usize {
+        self.meta.count
+    }
+
+    /// Create a bulk with one element per custom identifier.
+    ///
+    /// # Arguments
+    ///
+    /// * `ids` - The identifiers of the elements, as for [`Bulk::set_ids`]
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with no field data and the given identifiers.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if `ids` is empty or holds a duplicate
+    pub fn from_ids<I, T>(ids: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<ElementId>,
+    {
+        let ids: Vec<ElementId> = ids.into_iter().map(Into::into).collect();
+        Self::new(ids.len())?.set_ids(ids)
+    }
+
+    /// Assign custom identifiers to the elements (immutable update).
+    ///
+    /// Identifiers may be integers or strings and must be distinct. They follow
+    /// their elements through operations that keep element IDs, such as
+    /// [`Bulk::remove_where`], [`Bulk::head`], [`Bulk::sort_by`] and the merges;
+    /// elements added by [`Bulk::extend_rows`] have none. An element without a
+    /// custom identifier is identified by its entry in [`Meta::id`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ids` - One identifier per element, in element order
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with the identifiers assigned.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::LengthMismatch`] if there is not one identifier per element
+    /// - [`SoAKitError::InvalidArgument`] if an identifier is repeated, or if the
+    ///   bulk's element IDs are not distinct
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, ElementId, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::from_ids(["ann", "bob", "cy"]).unwrap();
+    /// let bulk = bulk.set(&registry, "age", vec![
+    ///     Value::ScalarInt(40),
+    ///     Value::ScalarInt(17),
+    ///     Value::ScalarInt(30),
+    /// ]).unwrap();
+    ///
+    /// let adults = bulk.remove_where(&[false, true, false]).unwrap();
+    /// assert_eq!(adults.ids(), vec![ElementId::from("ann"), ElementId::from("cy")]);
+    /// assert_eq!(adults.index_of_id("cy"), Some(1));
+    /// assert_eq!(adults.index_of_id("bob"), None);
+    /// ```
+    pub fn set_ids<I, T>(&self, ids: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<ElementId>,
+    {
+        let ids: Vec<ElementId> = ids.into_iter().map(Into::into).collect();
+        if ids.len() != self.meta.count {
+            return Err(SoAKitError::LengthMismatch {
+                expected: self.meta.count,
+                actual: ids.len(),
+            });
+        }
+        let mut seen = HashSet::with_capacity(ids.len());
+        if let Some(duplicate) = ids.iter().find(|&id| !seen.insert(id)) {
+            return Err(SoAKitError::InvalidArgument(format!(
+                "Duplicate element id: {}",
+                duplicate
+            )));
+        }
+        let custom_ids: BTreeMap<usize, ElementId> =
+            self.meta.id.iter().copied().zip(ids).collect();
+        if custom_ids.len() != self.meta.count {
+            return Err(SoAKitError::InvalidArgument(
+                "Element IDs are not distinct".to_string(),
+            ));
+        }
+
+        let mut new_bulk = self.clone();
+        new_bulk.meta.custom_ids = custom_ids;
+        Ok(new_bulk)
+    }
+
+    /// Get the identifier of every element, in element order.
+    ///
+    /// See [`Meta::element_id`].
+    pub fn ids(&self) -> Vec<ElementId> {
+        self.meta
+            .id
+            .iter()
+            .map(|&id| self.meta.element_id(id))
+            .collect()
+    }
+
+    /// Find the position of the element with an identifier.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - A custom identifier, or the ID of an element without one
+    ///
+    /// The first lookup builds a map from every identifier to its position,
+    /// which later lookups on the same bulk reuse; operations returning a new
+    /// bulk start without it.
+    ///
+    /// # Returns
+    ///
+    /// The element's index, or `None` if no element has the identifier.
+    pub fn index_of_id(&self, id: impl Into<ElementId>) -> Option<usize> {
+        let id = id.into();
+        if let Some(positions) = &*self.id_index.borrow() {
+            return positions.get(&id).copied();
+        }
+        let mut positions = HashMap::with_capacity(self.meta.count);
+        for (idx, &element) in self.meta.id.iter().enumerate() {
+            let _ = positions
+                .entry(self.meta.element_id(element))
+                .or_insert(idx);
+        }
+        let found = positions.get(&id).copied();
+        *self.id_index.borrow_mut() = Some(positions);
+        found
+    }
+
+    /// List all data fields (excluding system fields).
+    ///
+    /// Returns a vector of field names that have data in this bulk.
+    /// System fields (those starting with `_`) are excluded.
+    ///
+    /// # Returns
+    ///
+    /// A vector of field names as strings.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("age".to_string(), validator.clone(), false, vec![], None).unwrap();
+    /// registry.register("height".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(3).unwrap();
+    /// let bulk = bulk.set(&registry, "age", vec![Value::ScalarInt(25); 3]).unwrap();
+    /// let bulk = bulk.set(&registry, "height", vec![Value::ScalarInt(175); 3]).unwrap();
+    ///
+    /// let fields = bulk.list_data_fields();
+    /// assert_eq!(fields.len(), 2);
+    /// ```
+    pub fn list_data_fields(&self) -> Vec<String> {
+        if let Some(chunk) = self.chunks.first() {
+            filter_system_fields(&chunk.field_names())
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Iterate over the values of an integer field without copying it.
+    ///
+    /// The values are read from each chunk's stored column in turn, so no vector
+    /// holding the whole field is built. Use [`Bulk::get`] for derived fields.
+    ///
+    /// # Arguments
+    ///
+    /// * `field` - The name of a stored `VectorInt` field
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(iterator)` over the field's values, one per element.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field is not stored in this bulk
+    /// - [`SoAKitError::InvalidArgument`] if the field does not hold integers
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(3).unwrap();
+    /// let bulk = bulk.set(&registry, "age", vec![
+    ///     Value::ScalarInt(25),
+    ///     Value::ScalarInt(30),
+    ///     Value::ScalarInt(35),
+    /// ]).unwrap();
+    ///
+    /// let total: i64 = bulk.iter_field_i64("age").unwrap().sum();
+    /// assert_eq!(total, 90);
+    /// assert!(bulk.iter_field_f64("age").is_err());
+    /// ```
+    pub fn iter_field_i64(&self, field: &str) -> Result<impl Iterator<Item = i64> + '_> {
+        let slices = self.column_slices(field, "integers", Value::as_int_slice)?;
+        Ok(slices.into_iter().flatten().copied())
+    }
+
+    /// Iterate over the values of a float field without copying it.
+    ///
+    /// See [`Bulk::iter_field_i64`].
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field is not stored in this bulk
+    /// - [`SoAKitError::InvalidArgument`] if the field does not hold floats
+    pub fn iter_field_f64(&self, field: &str) -> Result<impl Iterator<Item = f64> + '_> {
+        let slices = self.column_slices(field, "floats", Value::as_float_slice)?;
+        Ok(slices.into_iter().flatten().copied())
+    }
+
+    /// Iterate over the values of a boolean field without copying it.
+    ///
+    /// See [`Bulk::iter_field_i64`].
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field is not stored in this bulk
+    /// - [`SoAKitError::InvalidArgument`] if the field does not hold booleans
+    pub fn iter_field_bool(&self, field: &str) -> Result<impl Iterator<Item = bool> + '_> {
+        let slices = self.column_slices(field, "booleans", Value::as_bool_slice)?;
+        Ok(slices.into_iter().flatten().copied())
+    }
+
+    /// Iterate over the values of a string field without copying it.
+    ///
+    /// Works for plain and interned string fields alike. See
+    /// [`Bulk::iter_field_i64`].
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field is not stored in this bulk
+    /// - [`SoAKitError::InvalidArgument`] if the field does not hold strings
+    pub fn iter_field_str(&self, field: &str) -> Result<impl Iterator<Item = &str> + '_> {
+        if self.chunks.is_empty() {
+            return Err(SoAKitError::FieldNotFound(field.to_string()));
+        }
+        let mut parts: Vec<Box<dyn Iterator<Item = &str> + '_>> =
+            Vec::with_capacity(self.chunks.len());
+        for chunk in &self.chunks {
+            if let Some(strings) = chunk.interned.get(field) {
+                parts.push(Box::new(strings.0.iter().map(|s| &**s)));
+                continue;
+            }
+            let column = chunk
+                .columns
+                .get(field)
+                .ok_or_else(|| SoAKitError::FieldNotFound(field.to_string()))?;
+            let strings = column.as_str_slice().ok_or_else(|| {
+                SoAKitError::InvalidArgument(format!("Field '{field}' does not hold strings"))
+            })?;
+            parts.push(Box::new(strings.iter().map(String::as_str)));
+        }
+        Ok(parts.into_iter().flatten())
+    }
+
+    /// Borrow one typed slice per chunk of a stored field.
+    fn column_slices<'a, T>(
+        &'a self,
+        field: &str,
+        kind: &str,
+        as_slice: fn(&Value) -> Option<&[T]>,
+    ) -> Result<Vec<&'a [T]>> {
+        if self.chunks.is_empty() {
+            return Err(SoAKitError::FieldNotFound(field.to_string()));
+        }
+        self.chunks
+            .iter()
+            .map(|chunk| {
+                if chunk.interned.contains_key(field) {
+                    return Err(SoAKitError::InvalidArgument(format!(
+                        "Field '{field}' does not hold {kind}"
+                    )));
+                }
+                let column = chunk
+                    .columns
+                    .get(field)
+                    .ok_or_else(|| SoAKitError::FieldNotFound(field.to_string()))?;
+                as_slice(column).ok_or_else(|| {
+                    SoAKitError::InvalidArgument(format!("Field '{field}' does not hold {kind}"))
+                })
+            })
+            .collect()
+    }
+
+    /// Fold every value of a field into an accumulator.
+    ///
+    /// Stored fields are read one chunk at a time, so the field is never
+    /// concatenated into a single vector; derived fields are computed with
+    /// [`Bulk::get`] first. Values are passed to `func` as scalars, in order.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `field` - The name of the field to fold
+    /// * `init` - The initial accumulator
+    /// * `func` - Function combining the accumulator with the next value
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(T)` with the final accumulator.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field doesn't exist
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("amount".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(3).unwrap();
+    /// let bulk = bulk.set(&registry, "amount", vec![
+    ///     Value::ScalarInt(5),
+    ///     Value::ScalarInt(50),
+    ///     Value::ScalarInt(500),
+    /// ]).unwrap();
+    ///
+    /// let large = bulk.reduce(&registry, "amount", 0, |acc, v| match v {
+    ///     Value::ScalarInt(x) if *x >= 50 => acc + 1,
+    ///     _ => acc,
+    /// }).unwrap();
+    /// assert_eq!(large, 2);
+    /// assert_eq!(bulk.sum_field(&registry, "amount").unwrap(), Value::ScalarInt(555));
+    /// ```
+    pub fn reduce<T, F>(&self, registry: &Registry, field: &str, init: T, mut func: F) -> Result<T>
+    where
+        F: FnMut(T, &Value) -> T,
+    {
+        let mut acc = init;
+        for part in self.field_parts(registry, field)? {
+            for value in &part.to_scalars() {
+                acc = func(acc, value);
+            }
+        }
+        Ok(acc)
+    }
+
+    /// Sum the values of a field, one chunk at a time.
+    ///
+    /// The result is the same as `bulk.get(registry, field)?.sum()`; see
+    /// [`Value::sum`].
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field doesn't exist
+    /// - The errors of [`Value::sum`]
+    pub fn sum_field(&self, registry: &Registry, field: &str) -> Result<Value> {
+        let partials = self
+            .field_parts(registry, field)?
+            .iter()
+            .map(|part| part.sum())
+            .collect::<Result<Vec<_>>>()?;
+        Value::from_scalars(partials)?.sum()
+    }
+
+    /// Arithmetic mean of the values of a field, one chunk at a time.
+    ///
+    /// The result is the same as `bulk.get(registry, field)?.mean()`, up to
+    /// floating-point rounding; see [`Value::mean`].
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field doesn't exist
+    /// - The errors of [`Value::mean`]
+    pub fn mean_field(&self, registry: &Registry, field: &str) -> Result<Value> {
+        let mut total = 0.0;
+        let mut count = 0.0;
+        for part in self.field_parts(registry, field)? {
+            let Value::ScalarFloat(n) = part.count_nonnull()?.cast(ValueKind::Float)? else {
+                continue;
+            };
+            if n > 0.0
+                && let Value::ScalarFloat(mean) = part.mean()?
+            {
+                total += mean * n;
+                count += n;
+            }
+        }
+        if count > 0.0 {
+            Ok(Value::ScalarFloat(total / count))
+        } else {
+            Value::VectorFloat(Vec::new()).mean()
+        }
+    }
+
+    /// Smallest value of a field, one chunk at a time.
+    ///
+    /// The result is the same as `bulk.get(registry, field)?.min()`; see
+    /// [`Value::min`].
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field doesn't exist
+    /// - The errors of [`Value::min`]
+    pub fn min_field(&self, registry: &Registry, field: &str) -> Result<Value> {
+        self.extreme_field(registry, field, Value::min)
+    }
+
+    /// Largest value of a field, one chunk at a time.
+    ///
+    /// The result is the same as `bulk.get(registry, field)?.max()`; see
+    /// [`Value::max`].
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field doesn't exist
+    /// - The errors of [`Value::max`]
+    pub fn max_field(&self, registry: &Registry, field: &str) -> Result<Value> {
+        self.extreme_field(registry, field, Value::max)
+    }
+
+    /// Count the null elements of a field, one chunk at a time.
+    ///
+    /// Nulls are NaN floats and missing elements of option vectors; see
+    /// [`Value::count_null`].
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field doesn't exist
+    /// - The errors of [`Value::count_null`]
+    pub fn null_count_field(&self, registry: &Registry, field: &str) -> Result<usize> {
+        self.field_parts(registry, field)?
+            .iter()
+            .try_fold(0usize, |total, part| {
+                Ok(total.saturating_add(part.null_count()?))
+            })
+    }
+
+    /// Aggregate the values of a field, with a policy for null elements.
+    ///
+    /// This is [`Value::aggregate_with`] applied to the field, computed one chunk
+    /// at a time where the aggregation allows it. With [`NullPolicy::Propagate`],
+    /// the result is [`Value::Null`] if the field has any null element.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `field` - The field to aggregate; derived fields are allowed
+    /// * `agg` - The aggregation to apply
+    /// * `nulls` - What to do with null elements
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field doesn't exist
+    /// - The errors of the chosen aggregation
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Agg, Bulk, NullPolicy, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_) | Value::Null));
+    /// registry.register("visits".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(3).unwrap();
+    /// let bulk = bulk.set(&registry, "visits", vec![
+    ///     Value::ScalarInt(4),
+    ///     Value::Null,
+    ///     Value::ScalarInt(6),
+    /// ]).unwrap();
+    ///
+    /// let mean = bulk.aggregate_field(&registry, "visits", Agg::Mean, NullPolicy::Skip).unwrap();
+    /// assert_eq!(mean, Value::ScalarFloat(5.0));
+    /// let strict = bulk.aggregate_field(&registry, "visits", Agg::Mean, NullPolicy::Propagate);
+    /// assert_eq!(strict.unwrap(), Value::Null);
+    /// assert_eq!(bulk.null_count_field(&registry, "visits").unwrap(), 1);
+    /// ```
+    pub fn aggregate_field(
+        &self,
+        registry: &Registry,
+        field: &str,
+        agg: Agg,
+        nulls: NullPolicy,
+    ) -> Result<Value> {
+        if nulls == NullPolicy::Propagate
+            && agg.skips_nulls()
+            && self.null_count_field(registry, field)? > 0
+        {
+            return Ok(Value::Null);
+        }
+        match agg {
+            Agg::Sum => self.sum_field(registry, field),
+            Agg::Mean => self.mean_field(registry, field),
+            Agg::Min => self.min_field(registry, field),
+            Agg::Max => self.max_field(registry, field),
+            Agg::Count | Agg::NullCount => {
+                let partials = self
+                    .field_parts(registry, field)?
+                    .iter()
+                    .map(|part| part.aggregate(agg))
+                    .collect::<Result<Vec<_>>>()?;
+                Value::from_scalars(partials)?.sum()
+            }
+            Agg::Std => self.get(registry, field)?.std(),
+        }
+    }
+
+    /// Compute a running sum, running maximum or difference over a field.
+    ///
+    /// This is [`Value::cumulative`] applied to the field's values in element
+    /// order, so rows are treated as a time series. The result is one value per
+    /// element and can be stored back with [`Bulk::set`] under a new field.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `field` - The name of the field
+    /// * `op` - The running computation to apply
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Value)` with one element per element of the bulk.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field doesn't exist
+    /// - The errors of [`Value::cumulative`]
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, CumOp, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
+    /// registry.register("price".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(3).unwrap();
+    /// let bulk = bulk.set(&registry, "price", vec![
+    ///     Value::ScalarFloat(10.0),
+    ///     Value::ScalarFloat(12.5),
+    ///     Value::ScalarFloat(11.0),
+    /// ]).unwrap();
+    ///
+    /// let high = bulk.cumulative(&registry, "price", CumOp::Max).unwrap();
+    /// assert_eq!(high, Value::VectorFloat(vec![10.0, 12.5, 12.5]));
+    /// ```
+    pub fn cumulative(&self, registry: &Registry, field: &str, op: CumOp) -> Result<Value> {
+        self.get(registry, field)?.cumulative(op)
+    }
+
+    /// Aggregate each sliding window of a field.
+    ///
+    /// This is [`Bulk::rolling_with`] with the default [`RollEdge::Nan`] policy.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Bulk::rolling_with`].
+    pub fn rolling(
+        &self,
+        registry: &Registry,
+        field: &str,
+        window: usize,
+        op: RollOp,
+    ) -> Result<Value> {
+        self.rolling_with(registry, field, window, op, RollEdge::default())
+    }
+
+    /// Aggregate each sliding window of a field, with an edge policy.
+    ///
+    /// The result is the same as [`Value::rolling_with`] on the field's values,
+    /// but stored fields are read one chunk at a time: the window carries over
+    /// chunk boundaries, so the field is never concatenated.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `field` - The name of the field
+    /// * `window` - Number of elements in each window
+    /// * `op` - The aggregation applied to each window
+    /// * `edge` - What to yield before the first full window
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Value::VectorFloat)` with one element per element of the bulk.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field doesn't exist
+    /// - The errors of [`Value::rolling_with`]
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, RollEdge, RollOp, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
+    /// registry.register("temp".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(4).unwrap();
+    /// let bulk = bulk.set(&registry, "temp", vec![
+    ///     Value::ScalarFloat(20.0),
+    ///     Value::ScalarFloat(22.0),
+    ///     Value::ScalarFloat(21.0),
+    ///     Value::ScalarFloat(25.0),
+    /// ]).unwrap();
+    ///
+    /// let smooth = bulk
+    ///     .rolling_with(&registry, "temp", 2, RollOp::Mean, RollEdge::Partial)
+    ///     .unwrap();
+    /// assert_eq!(smooth, Value::VectorFloat(vec![20.0, 21.0, 21.5, 23.0]));
+    /// ```
+    pub fn rolling_with(
+        &self,
+        registry: &Registry,
+        field: &str,
+        window: usize,
+        op: RollOp,
+        edge: RollEdge,
+    ) -> Result<Value> {
+        let mut state = RollingWindow::new(window, op, edge, self.meta.count)?;
+        let mut out = Vec::with_capacity(self.meta.count);
+        for part in self.field_parts(registry, field)? {
+            out.extend(part.rolling_inputs()?.into_iter().map(|x| state.push(x)));
+        }
+        Ok(Value::VectorFloat(out))
+    }
+
+    /// Shared implementation of [`Bulk::min_field`] and [`Bulk::max_field`].
+    ///
+    /// Chunks without a non-NaN value have no extreme and are skipped; if every
+    /// chunk fails, the first chunk's error is returned.
+    fn extreme_field(
+        &self,
+        registry: &Registry,
+        field: &str,
+        extreme: fn(&Value) -> Result<Value>,
+    ) -> Result<Value> {
+        let mut partials = Vec::new();
+        let mut first_error = None;
+        for part in self.field_parts(registry, field)? {
+            match extreme(&part) {
+                Ok(value) => partials.push(value),
+                Err(e) => {
+                    let _ = first_error.get_or_insert(e);
+                }
+            }
+        }
+        match first_error {
+            Some(e) if partials.is_empty() => Err(e),
+            _ => extreme(&Value::from_scalars(partials)?),
+        }
+    }
+
+    /// Get the values of a field as one vector per chunk.
+    ///
+    /// Stored fields yield their chunk columns; derived and virtual fields are computed and
+    /// yielded whole.
+    fn field_parts(&self, registry: &Registry, field: &str) -> Result<Vec<Cow<'_, Value>>> {
+        let metadata = registry
+            .get_metadata(field)
+            .ok_or_else(|| SoAKitError::FieldNotFound(field.to_string()))?;
+        if metadata.is_derived || metadata.is_virtual() || self.chunks.is_empty() {
+            return Ok(vec![Cow::Owned(self.get(registry, field)?)]);
+        }
+        self.chunks
+            .iter()
+            .map(|chunk| {
+                chunk
+                    .column(field)
+                    .ok_or_else(|| SoAKitError::FieldNotFound(field.to_string()))
+            })
+            .collect()
+    }
+
+    /// Store a string field as interned strings.
+    ///
+    /// Each element of an interned field is a shared `Arc<str>` from the global
+    /// pool in [`crate::intern`], so repeated strings are stored only once. The
+    /// field's values are unchanged: [`get`](Self::get), [`partition_by`](Self::partition_by)
+    /// and serialization see a regular `VectorString`. The field stays interned when
+    /// it is later overwritten with string values.
+    ///
+    /// # Arguments
+    ///
+    /// * `field` - The name of the string field to intern
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with the field interned.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field has not been set
+    /// - [`SoAKitError::InvalidArgument`] if the field does not hold strings
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarString(_)));
+    /// registry.register("color".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(3).unwrap();
+    /// let bulk = bulk.set_broadcast(&registry, "color", Value::from("red")).unwrap();
+    /// let bulk = bulk.intern_field("color").unwrap();
+    ///
+    /// assert!(bulk.is_interned("color"));
+    /// assert_eq!(
+    ///     bulk.get(&registry, "color").unwrap(),
+    ///     Value::from(vec!["red", "red", "red"])
+    /// );
+    /// ```
+    pub fn intern_field(&self, field: &str) -> Result<Self> {
+        if !self
+            .chunks
+            .iter()
+            .any(|chunk| chunk.column(field).is_some())
+        {
+            return Err(SoAKitError::FieldNotFound(field.to_string()));
+        }
+
+        let mut new_bulk = self.clone();
+        for chunk in &mut new_bulk.chunks {
+            let Some(value) = chunk.columns.remove(field) else {
+                continue;
+            };
+            let Value::VectorString(strings) = &*value else {
+                return Err(SoAKitError::InvalidArgument(format!(
+                    "Cannot intern field '{}': not a string field",
+                    field
+                )));
+            };
+            let _ = chunk.interned.insert(
+                field.to_string(),
+                Arc::new(InternedStrings::from_strings(strings)),
+            );
+        }
+        Ok(new_bulk)
+    }
+
+    /// Check whether a field is stored as interned strings.
+    ///
+    /// See [`intern_field`](Self::intern_field).
+    ///
+    /// # Arguments
+    ///
+    /// * `field` - The name of the field
+    ///
+    /// # Returns
+    ///
+    /// `true` if the field is interned, `false` otherwise.
+    pub fn is_interned(&self, field: &str) -> bool {
+        self.chunks
+            .first()
+            .is_some_and(|chunk| chunk.interned.contains_key(field))
+    }
+
+    /// Remove a field from the bulk.
+    ///
+    /// Returns a new bulk without the field's column in any chunk and without its
+    /// version entry. Cached derived fields that depend on the field, directly or
+    /// through other derived fields, are invalidated. The field stays registered.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `field` - The name of the data field to remove
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` without the field.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field has not been set
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("temp_col".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(2).unwrap();
+    /// let bulk = bulk.set_broadcast(&registry, "temp_col", Value::ScalarInt(0)).unwrap();
+    /// let bulk = bulk.drop_field(&registry, "temp_col").unwrap();
+    /// assert!(bulk.list_data_fields().is_empty());
+    /// assert!(bulk.get(&registry, "temp_col").is_err());
+    /// ```
+    pub fn drop_field(&self, registry: &Registry, field: &str) -> Result<Self> {
+        if !self
+            .chunks
+            .iter()
+            .any(|chunk| chunk.column(field).is_some())
+        {
+            return Err(SoAKitError::FieldNotFound(field.to_string()));
+        }
+
+        let mut new_bulk = self.clone();
+        new_bulk.remove_field_data(registry, field);
+        Ok(new_bulk)
+    }
+
+    /// Remove a field's column, version, sort order and index, invalidating the
+    /// derived caches that depend on it.
+    fn remove_field_data(&mut self, registry: &Registry, field: &str) {
+        for chunk in &mut self.chunks {
+            let _ = chunk.remove_column(field);
+        }
+        let _ = self.meta.versions.remove(field);
+        let _ = self.meta.sorted.remove(field);
+        let _ = self.indices.get_mut().remove(field);
+        self.invalidate_dependent_cache(registry, field);
+    }
+
+    /// Get the values of every field of a group.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry defining the group (see [`Registry::define_group`])
+    /// * `group` - The name of the group
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(BTreeMap)` with the values of each field of the group, as by
+    /// [`Bulk::get`], keyed by field name.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if the group is not defined
+    /// - The errors of [`Bulk::get`] for the first field that fails
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// for field in ["x", "y", "mass"] {
+    ///     registry.register_float(field).unwrap();
+    /// }
+    /// registry.define_group("position", &["x", "y"]).unwrap();
+    ///
+    /// let bulk = Bulk::new(2).unwrap();
+    /// let columns = vec![
+    ///     vec![Value::ScalarFloat(1.0), Value::ScalarFloat(2.0)],
+    ///     vec![Value::ScalarFloat(3.0), Value::ScalarFloat(4.0)],
+    /// ];
+    /// let bulk = bulk.set_group(&registry, "position", columns).unwrap();
+    /// let bulk = bulk.set_broadcast(&registry, "mass", Value::ScalarFloat(9.5)).unwrap();
+    ///
+    /// let position = bulk.get_group(&registry, "position").unwrap();
+    /// assert_eq!(position["y"], Value::VectorFloat(vec![3.0, 4.0]));
+    /// let selected = bulk.select_group(&registry, "position").unwrap();
+    /// assert_eq!(selected.list_data_fields(), vec!["x", "y"]);
+    /// ```
+    pub fn get_group(&self, registry: &Registry, group: &str) -> Result<BTreeMap<String, Value>> {
+        registry
+            .group_fields(group)?
+            .iter()
+            .map(|field| Ok((field.clone(), self.get(registry, field)?)))
+            .collect()
+    }
+
+    /// Set every field of a group at once.
+    ///
+    /// The fields are checked before any is set, as by [`Bulk::set_many`].
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry defining the group (see [`Registry::define_group`])
+    /// * `group` - The name of the group
+    /// * `columns` - The values of each field, in the order of the group's fields
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with every field of the group set.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if the group is not defined or the number
+    ///   of columns differs from its number of fields
+    /// - The errors of [`Bulk::set_many`]
+    pub fn set_group(
+        &self,
+        registry: &Registry,
+        group: &str,
+        columns: Vec<Vec<Value>>,
+    ) -> Result<Self> {
+        let fields = registry.group_fields(group)?;
+        if columns.len() != fields.len() {
+            return Err(SoAKitError::InvalidArgument(format!(
+                "Group {} has {} fields but {} columns were given",
+                group,
+                fields.len(),
+                columns.len()
+            )));
+        }
+        let columns = fields.iter().map(String::as_str).zip(columns).collect();
+        self.set_many(registry, columns)
+    }
+
+    /// Keep only the data of a group's fields.
+    ///
+    /// Returns a new bulk with the same elements and ids, holding the stored
+    /// fields of the group; every other field is dropped as by
+    /// [`Bulk::drop_field`]. Derived fields of the group can still be computed if
+    /// their dependencies are in the group too.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry defining the group (see [`Registry::define_group`])
+    /// * `group` - The name of the group
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if the group is not defined
+    pub fn select_group(&self, registry: &Registry, group: &str) -> Result<Self> {
+        let fields = registry.group_fields(group)?;
+        let mut new_bulk = self.clone();
+        for field in self.list_data_fields() {
+            if !fields.contains(&field) {
+                new_bulk.remove_field_data(registry, &field);
+            }
+        }
+        Ok(new_bulk)
+    }
+
+    /// Rename a field's data.
+    ///
+    /// Returns a new bulk with the field's column in every chunk, its version entry
+    /// and its cached derived value (if any) moved to the new name. Pair this with
+    /// [`Registry::rename_field`] so that the registry and the bulk agree.
+    ///
+    /// # Arguments
+    ///
+    /// * `old` - The current name of the field
+    /// * `new` - The new name of the field (must be valid and unused in this bulk)
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with the field renamed.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if the new name is invalid
+    /// - [`SoAKitError::FieldNotFound`] if the bulk holds no data or cached value for `old`
+    /// - [`SoAKitError::FieldAlreadyExists`] if the bulk already holds data for `new`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(2).unwrap();
+    /// let bulk = bulk.set_broadcast(&registry, "age", Value::ScalarInt(30)).unwrap();
+    ///
+    /// registry.rename_field("age", "years").unwrap();
+    /// let bulk = bulk.rename_field("age", "years").unwrap();
+    /// assert_eq!(bulk.get(&registry, "years").unwrap(), Value::VectorInt(vec![30, 30]));
+    /// ```
+    pub fn rename_field(&self, old: &str, new: &str) -> Result<Self> {
+        if !is_valid_field_name(new) {
+            return Err(SoAKitError::InvalidArgument(format!(
+                "Invalid field name: {}",
+                new
+            )));
+        }
+        let has_data = |field: &str| {
+            self.chunks
+                .iter()
+                .any(|chunk| chunk.column(field).is_some())
+                || self.meta.versions.contains_key(field)
+        };
+        if has_data(new) || self.cache.borrow().contains_key(new) {
+            return Err(SoAKitError::FieldAlreadyExists(new.to_string()));
+        }
+        if !has_data(old) && !self.cache.borrow().contains_key(old) {
+            return Err(SoAKitError::FieldNotFound(old.to_string()));
+        }
+
+        let mut new_bulk = self.clone();
+        for chunk in &mut new_bulk.chunks {
+            if let Some(column) = chunk.columns.remove(old) {
+                let _ = chunk.columns.insert(new.to_string(), column);
+            }
+            if let Some(strings) = chunk.interned.remove(old) {
+                let _ = chunk.interned.insert(new.to_string(), strings);
+            }
+            if let Some(version) = chunk.versions.remove(old) {
+                let _ = chunk.versions.insert(new.to_string(), version);
+            }
+        }
+        if let Some(version) = new_bulk.meta.versions.remove(old) {
+            let _ = new_bulk.meta.versions.insert(new.to_string(), version);
+        }
+        if let Some(sorted) = new_bulk.meta.sorted.remove(old) {
+            let _ = new_bulk.meta.sorted.insert(new.to_string(), sorted);
+        }
+        let mut cache = new_bulk.cache.borrow_mut();
+        if let Some(entry) = cache.remove(old) {
+            let _ = cache.insert(new.to_string(), entry);
+        }
+        drop(cache);
+        let indices = new_bulk.indices.get_mut();
+        if let Some(index) = indices.remove(old) {
+            let _ = indices.insert(new.to_string(), index);
+        }
+        Ok(new_bulk)
+    }
+
+    /// Create a proxy for accessing a single element at the given index.
+    ///
+    /// A [`Proxy`] provides a convenient way to access and manipulate a single
+    /// element's field values without working with the entire bulk.
+    ///
+    /// # Arguments
+    ///
+    /// * `idx` - The index of the element (0-based)
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Proxy)` if successful, or an error if the index is out of bounds.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::IndexOutOfBounds`] if `idx >= bulk.count()`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(3).unwrap();
+    /// let bulk = bulk.set(&registry, "age", vec![
+    ///     Value::ScalarInt(25),
+    ///     Value::ScalarInt(30),
+    ///     Value::ScalarInt(35),
+    /// ]).unwrap();
+    ///
+    /// let proxy = bulk.at(1).unwrap();
+    /// assert_eq!(proxy.get_field(&registry, "age").unwrap(), Value::ScalarInt(30));
+    /// ```
+    pub fn at(&self, idx: usize) -> Result<crate::proxy::Proxy> {
+        if idx >= self.meta.count {
+            return Err(SoAKitError::IndexOutOfBounds {
+                index: idx,
+                max: self.meta.count,
+            });
+        }
+        crate::proxy::Proxy::new(Arc::new(self.clone()), idx)
+    }
+
+    /// Iterate over the elements as rows.
+    ///
+    /// Each [`Row`](crate::row::Row) holds the scalar values of one element for
+    /// every registered field stored in the bulk, plus every registered derived
+    /// field whose dependencies are all available. Stored columns are read one
+    /// chunk at a time without cloning the bulk, so this is much cheaper than
+    /// calling [`Bulk::at`] in a loop. Derived fields are computed (or read from
+    /// the cache) once, when the iterator is created.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Rows)` yielding one row per element, in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a derived field fails to compute.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(3).unwrap();
+    /// let bulk = bulk.set(&registry, "age", vec![
+    ///     Value::ScalarInt(25),
+    ///     Value::ScalarInt(30),
+    ///     Value::ScalarInt(35),
+    /// ]).unwrap();
+    ///
+    /// let adults = bulk
+    ///     .iter_rows(&registry)
+    ///     .unwrap()
+    ///     .filter(|row| matches!(row.get("age"), Some(Value::ScalarInt(a)) if *a >= 30))
+    ///     .count();
+    /// assert_eq!(adults, 2);
+    /// ```
+    pub fn iter_rows(&self, registry: &Registry) -> Result<crate::row::Rows<'_>> {
+        let stored = self.list_data_fields();
+        let mut fields = Vec::new();
+        let mut derived = Vec::new();
+        for field in registry.list_fields() {
+            if stored.contains(&field) {
+                fields.push(field);
+            } else if self.derived_available(registry, &field) {
+                let value = self.get(registry, &field)?;
+                derived.push((field, value));
+            }
+        }
+        Ok(crate::row::Rows::new(self, fields, derived))
+    }
+
+    /// Check whether `field` is a registered virtual field, or a derived field
+    /// that can be computed from the fields stored in this bulk.
+    fn derived_available(&self, registry: &Registry, field: &str) -> bool {
+        registry.get_metadata(field).is_some_and(|metadata| {
+            metadata.is_virtual()
+                || metadata.is_derived
+                    && metadata.dependencies.iter().all(|dep| {
+                        self.chunks
+                            .first()
+                            .is_some_and(|chunk| chunk.column(dep).is_some())
+                            || self.derived_available(registry, dep)
+                    })
+        })
+    }
+
+    /// Apply a function to masked subset of data.
+    ///
+    /// This method applies a transformation function to the values at positions
+    /// where the mask is `true`, returning a new bulk with the updated values.
+    /// The function receives only the masked subset of values and must return
+    /// the same number of transformed values.
+    ///
+    /// If the mask is empty, it is treated as all `true` (applying to all elements).
+    /// Every data field is transformed; use [`Bulk::apply_fields`] to transform
+    /// only some of them.
+    ///
+    /// # Arguments
+    ///
+    /// * `mask` - Boolean array indicating which elements to transform (empty = all true)
+    /// * `func` - Function that takes a slice of values and returns transformed values
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with updated values, or an error if:
+    /// - The mask length doesn't match the bulk count (when mask is not empty)
+    /// - The function returns a different number of values than masked elements
+    /// - The function returns an error
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::LengthMismatch`] if mask length doesn't match or function returns wrong count
+    /// - [`SoAKitError::FieldNotFound`] if a field is missing
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(5).unwrap();
+    /// let bulk = bulk.set(&registry, "age", vec![
+    ///     Value::ScalarInt(10),
+    ///     Value::ScalarInt(20),
+    ///     Value::ScalarInt(30),
+    ///     Value::ScalarInt(40),
+    ///     Value::ScalarInt(50),
+    /// ]).unwrap();
+    ///
+    /// // Increment ages at positions 0, 2, 4
+    /// let mask = vec![true, false, true, false, true];
+    /// let new_bulk = bulk.apply(&mask, |subset| {
+    ///     Ok(subset.iter().map(|v| {
+    ///         if let Value::ScalarInt(i) = v {
+    ///             Value::ScalarInt(i + 1)
+    ///         } else {
+    ///             v.clone()
+    ///         }
+    ///     }).collect())
+    /// }).unwrap();
+    /// ```
+    pub fn apply<F>(&self, mask: &[bool], func: F) -> Result<Self>
+    where
+        F: Fn(&[Value]) -> Result<Vec<Value>>,
+    {
+        let mut new_bulk = self.clone();
+        new_bulk.apply_mut(mask, func)?;
+        Ok(new_bulk)
+    }
+
+    /// Apply a function to a masked subset of data, in place.
+    ///
+    /// This is the mutating counterpart of [`Bulk::apply`]: every data field is
+    /// transformed and has its version bumped exactly as [`Bulk::apply`] would, but
+    /// `self` is updated instead of a cloned bulk.
+    ///
+    /// If an error is returned, `self` is left unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `mask` - Boolean array indicating which elements to transform (empty = all true)
+    /// * `func` - Function that takes a slice of values and returns transformed values
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::apply`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let mut bulk = Bulk::new(3).unwrap();
+    /// bulk.set_mut(&registry, "age", vec![Value::ScalarInt(10), Value::ScalarInt(20), Value::ScalarInt(30)]).unwrap();
+    /// bulk.apply_mut(&[false, true, false], |subset| {
+    ///     Ok(subset.iter().map(|_| Value::ScalarInt(0)).collect())
+    /// }).unwrap();
+    /// assert_eq!(bulk.get(&registry, "age").unwrap(), Value::VectorInt(vec![10, 0, 30]));
+    /// ```
+    pub fn apply_mut<F>(&mut self, mask: &[bool], func: F) -> Result<()>
+    where
+        F: Fn(&[Value]) -> Result<Vec<Value>>,
+    {
+        let fields = self.list_data_fields();
+        self.apply_to_fields(None, fields, mask, func)
+    }
+
+    /// Apply a function to a masked subset of chosen fields (immutable update).
+    ///
+    /// Unlike [`Bulk::apply`], which transforms every data field with the same
+    /// function, only the listed fields are transformed, so a bulk can mix field
+    /// types. Every transformed value must pass its field's validator. Each listed
+    /// field has its version bumped and its dependent derived caches invalidated.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `fields` - The names of the fields to transform
+    /// * `mask` - Boolean array indicating which elements to transform (empty = all true)
+    /// * `func` - Function called once per field with the masked values of that field
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with the listed fields updated.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if a field is not registered or not set in this bulk
+    /// - [`SoAKitError::ValidationFailed`] if a transformed value fails validation
+    /// - [`SoAKitError::LengthMismatch`] if the mask length doesn't match or the function
+    ///   returns the wrong number of values
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let floats = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
+    /// registry.register("score".to_string(), floats, false, vec![], None).unwrap();
+    /// let strings = Box::new(|v: &Value| matches!(v, Value::ScalarString(_)));
+    /// registry.register("name".to_string(), strings, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(2).unwrap();
+    /// let bulk = bulk.set(&registry, "score", vec![Value::ScalarFloat(1.0), Value::ScalarFloat(2.0)]).unwrap();
+    /// let bulk = bulk.set(&registry, "name", vec![Value::ScalarString("a".to_string()); 2]).unwrap();
+    ///
+    /// // Only "score" is passed to the closure, so it can assume floats
+    /// let bulk = bulk.apply_fields(&registry, &["score"], &[], |subset| {
+    ///     Ok(subset.iter().map(|v| match v {
+    ///         Value::ScalarFloat(x) => Value::ScalarFloat(x * 10.0),
+    ///         other => other.clone(),
+    ///     }).collect())
+    /// }).unwrap();
+    /// assert_eq!(bulk.get(&registry, "score").unwrap(), Value::VectorFloat(vec![10.0, 20.0]));
+    /// ```
+    pub fn apply_fields<F>(
+        &self,
+        registry: &Registry,
+        fields: &[&str],
+        mask: &[bool],
+        func: F,
+    ) -> Result<Self>
+    where
+        F: Fn(&[Value]) -> Result<Vec<Value>>,
+    {
+        let mut new_bulk = self.clone();
+        new_bulk.apply_fields_mut(registry, fields, mask, func)?;
+        Ok(new_bulk)
+    }
+
+    /// Apply a function to a masked subset of chosen fields, in place.
+    ///
+    /// This is the mutating counterpart of [`Bulk::apply_fields`]. If an error is
+    /// returned, `self` is left unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::apply_fields`].
+    pub fn apply_fields_mut<F>(
+        &mut self,
+        registry: &Registry,
+        fields: &[&str],
+        mask: &[bool],
+        func: F,
+    ) -> Result<()>
+    where
+        F: Fn(&[Value]) -> Result<Vec<Value>>,
+    {
+        let stored = self.list_data_fields();
+        for field in fields {
+            if !registry.has_field(field) || !stored.iter().any(|name| name == field) {
+                return Err(SoAKitError::FieldNotFound((*field).to_string()));
+            }
+        }
+        let fields = fields.iter().map(|field| (*field).to_string()).collect();
+        self.apply_to_fields(Some(registry), fields, mask, func)
+    }
+
+    /// Apply a function to each masked element of chosen fields, passing its ID.
+    ///
+    /// This is [`Bulk::apply_fields`] for position-dependent updates: `func` is
+    /// called once per masked element and field with the element's ID (see
+    /// [`Meta::id`]) and its current value, and returns the new value.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `fields` - The names of the fields to transform
+    /// * `mask` - Boolean array indicating which elements to transform (empty = all true)
+    /// * `func` - Function taking an element ID and value and returning the new value
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with the listed fields updated.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::apply_fields`], or the first error returned
+    /// by `func`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("rank".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(3).unwrap();
+    /// let bulk = bulk.set(&registry, "rank", vec![Value::ScalarInt(0); 3]).unwrap();
+    /// let bulk = bulk.apply_with_ids(&registry, &["rank"], &[true, false, true], |id, _| {
+    ///     Ok(Value::ScalarInt(id as i64 * 10))
+    /// }).unwrap();
+    /// assert_eq!(bulk.get(&registry, "rank").unwrap(), Value::VectorInt(vec![0, 0, 20]));
+    /// ```
+    pub fn apply_with_ids<F>(
+        &self,
+        registry: &Registry,
+        fields: &[&str],
+        mask: &[bool],
+        func: F,
+    ) -> Result<Self>
+    where
+        F: Fn(usize, &Value) -> Result<Value>,
+    {
+        let ids: Vec<usize> = if mask.is_empty() {
+            self.meta.id.clone()
+        } else {
+            self.meta
+                .id
+                .iter()
+                .zip(mask)
+                .filter(|&(_, &selected)| selected)
+                .map(|(&id, _)| id)
+                .collect()
+        };
+        self.apply_fields(registry, fields, mask, |subset| {
+            subset
+                .iter()
+                .zip(&ids)
+                .map(|(value, &id)| func(id, value))
+                .collect()
+        })
+    }
+
+    /// Apply a function to each masked element of chosen fields, skipping failures.
+    ///
+    /// This is [`Bulk::apply_with_ids`] for transforms that may fail on a few
+    /// elements: when `func` returns an error for an element, or returns a value
+    /// that fails the field's validator, the element keeps its old value and the
+    /// failure is reported instead of aborting the whole update.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `fields` - The names of the fields to transform
+    /// * `mask` - Boolean array indicating which elements to transform (empty = all true)
+    /// * `func` - Function taking an element ID and value and returning the new value
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok((Bulk, Vec<RowError>))` with the listed fields updated and one
+    /// error per failed element and field, whose indices are positions in the bulk.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if a field is not registered or not set in this bulk
+    /// - [`SoAKitError::LengthMismatch`] if the mask length doesn't match
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, SoAKitError, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("n".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(3).unwrap();
+    /// let bulk = bulk.set(&registry, "n", vec![Value::ScalarInt(10); 3]).unwrap();
+    /// let (bulk, errors) = bulk.apply_tolerant(&registry, &["n"], &[], |id, v| match (id, v) {
+    ///     (1, _) => Err(SoAKitError::InvalidArgument("bad element".to_string())),
+    ///     (_, Value::ScalarInt(n)) => Ok(Value::ScalarInt(n + id as i64)),
+    ///     _ => Ok(v.clone()),
+    /// }).unwrap();
+    /// assert_eq!(bulk.get(&registry, "n").unwrap(), Value::VectorInt(vec![10, 10, 12]));
+    /// assert_eq!(errors.len(), 1);
+    /// assert_eq!(errors[0].index, 1);
+    /// ```
+    pub fn apply_tolerant<F>(
+        &self,
+        registry: &Registry,
+        fields: &[&str],
+        mask: &[bool],
+        func: F,
+    ) -> Result<(Self, Vec<RowError>)>
+    where
+        F: Fn(usize, &Value) -> Result<Value>,
+    {
+        let mask = self.normalize_mask(mask)?;
+        let mut new_bulk = self.clone();
+        let mut errors = Vec::new();
+        for &field in fields {
+            let meta = registry
+                .get_metadata(field)
+                .ok_or_else(|| SoAKitError::FieldNotFound(field.to_string()))?;
+            let selected = self
+                .meta
+                .id
+                .iter()
+                .zip(self.stored_scalars(field))
+                .zip(&mask)
+                .enumerate()
+                .filter(|(_, (_, selected))| **selected);
+
+            let mut new_values = Vec::new();
+            for (index, ((&id, old), _)) in selected {
+                let result = func(id, &old).and_then(|new| {
+                    if (meta.validator)(&new) {
+                        Ok(new)
+                    } else {
+                        Err(SoAKitError::ValidationFailed(format!(
+                            "Value validation failed for field: {}",
+                            field
+                        )))
+                    }
+                });
+                match result {
+                    Ok(new) => new_values.push(new),
+                    Err(error) => {
+                        errors.push(RowError {
+                            index,
+                            field: Some(field.to_string()),
+                            error,
+                        });
+                        new_values.push(old);
+                    }
+                }
+            }
+
+            // `func` has already run, so hand the results over to the masked update
+            let new_values = std::cell::Cell::new(new_values);
+            new_bulk.apply_fields_mut(registry, &[field], &mask, |_| Ok(new_values.take()))?;
+        }
+        Ok((new_bulk, errors))
+    }
+
+    /// Transform a field one chunk column at a time (immutable update).
+    ///
+    /// `func` receives each chunk's column as a mutable vector value (e.g. a
+    /// [`Value::VectorFloat`] holding up to [`CHUNK_SIZE`] elements) and may modify
+    /// it in place, so transforms can work on plain slices instead of one scalar
+    /// `Value` per element. The column must keep its length, and its first element
+    /// must still pass validation. The field's version is incremented and dependent
+    /// derived caches are invalidated.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `field` - The name of the field to transform
+    /// * `func` - Function called once per chunk with that chunk's column
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with the field transformed.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field is not registered or not set in this bulk
+    /// - [`SoAKitError::LengthMismatch`] if `func` changes the length of a column
+    /// - [`SoAKitError::ShapeMismatch`] if an element of a transformed column does
+    ///   not have the field's declared shape
+    /// - [`SoAKitError::ValidationFailed`] if a transformed column fails validation
+    /// - Any error returned by `func`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, SoAKitError, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
+    /// registry.register("x".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(3).unwrap();
+    /// let bulk = bulk.set(&registry, "x", vec![Value::ScalarFloat(1.0); 3]).unwrap();
+    /// let bulk = bulk.apply_chunks(&registry, "x", |column| match column {
+    ///     Value::VectorFloat(xs) => {
+    ///         xs.iter_mut().for_each(|x| *x *= 2.0);
+    ///         Ok(())
+    ///     }
+    ///     _ => Err(SoAKitError::InvalidArgument("expected floats".to_string())),
+    /// }).unwrap();
+    /// assert_eq!(bulk.get(&registry, "x").unwrap(), Value::VectorFloat(vec![2.0; 3]));
+    /// ```
+    pub fn apply_chunks<F>(&self, registry: &Registry, field: &str, func: F) -> Result<Self>
+    where
+        F: FnMut(&mut Value) -> Result<()>,
+    {
+        let mut new_bulk = self.clone();
+        new_bulk.apply_chunks_mut(registry, field, func)?;
+        Ok(new_bulk)
+    }
+
+    /// Transform a field one chunk column at a time, in place.
+    ///
+    /// This is the mutating counterpart of [`Bulk::apply_chunks`]. If an error is
+    /// returned, `self` is left unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::apply_chunks`].
+    pub fn apply_chunks_mut<F>(
+        &mut self,
+        registry: &Registry,
+        field: &str,
+        mut func: F,
+    ) -> Result<()>
+    where
+        F: FnMut(&mut Value) -> Result<()>,
+    {
+        let shaped = registry
+            .get_metadata(field)
+            .ok_or_else(|| SoAKitError::FieldNotFound(field.to_string()))?
+            .shape
+            .is_some();
+
+        let mut columns = Vec::with_capacity(self.chunks.len());
+        for chunk in &self.chunks {
+            let mut column = chunk
+                .column(field)
+                .ok_or_else(|| SoAKitError::FieldNotFound(field.to_string()))?
+                .into_owned();
+            func(&mut column)?;
+            if column.len() != chunk.len {
+                return Err(SoAKitError::LengthMismatch {
+                    expected: chunk.len,
+                    actual: column.len(),
+                });
+            }
+            if shaped {
+                for value in column.to_scalars() {
+                    registry.check_shape(field, &value)?;
+                }
+            }
+            if chunk.len > 0 {
+                registry.check_value(field, &column_element(&column, 0)?)?;
+            }
+            if registry.has_constraints(field) {
+                registry.check_constraints(field, &column.to_scalars())?;
+            }
+            columns.push(column);
+        }
+        if columns.is_empty() {
+            return Err(SoAKitError::FieldNotFound(field.to_string()));
+        }
+
+        let new_ver = self
+            .meta
+            .versions
+            .get(field)
+            .copied()
+            .unwrap_or(0)
+            .checked_add(1)
+            .ok_or_else(|| SoAKitError::InvalidArgument("Version overflow".to_string()))?;
+        for (chunk, column) in self.chunks.iter_mut().zip(columns) {
+            chunk.insert_column(field, column);
+        }
+        self.store_version(field, new_ver);
+        self.invalidate_dependent_cache(registry, field);
+        Ok(())
+    }
+
+    /// Transform the masked values of `fields` with `func` and store the results.
+    ///
+    /// With a registry, transformed values are validated and dependent derived
+    /// caches are invalidated. Every column is built before any is stored.
+    fn apply_to_fields<F>(
+        &mut self,
+        registry: Option<&Registry>,
+        fields: Vec<String>,
+        mask: &[bool],
+        func: F,
+    ) -> Result<()>
+    where
+        F: Fn(&[Value]) -> Result<Vec<Value>>,
+    {
+        let mask = self.normalize_mask(mask)?;
+        let bounds = self.all_chunk_bounds()?;
+
+        // Build every updated column before storing any
+        let updates = fields
+            .into_iter()
+            .map(|field| {
+                let columns =
+                    applied_columns(&self.chunks, registry, &field, &mask, &bounds, &func)?;
+                Ok((field, columns))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.store_columns(registry, updates)
+    }
+
+    /// Expand an empty mask to all `true` and check the mask length.
+    pub(crate) fn normalize_mask(&self, mask: &[bool]) -> Result<Vec<bool>> {
+        let normalized_mask = if mask.is_empty() {
+            vec![true; self.meta.count]
+        } else {
+            mask.to_vec()
+        };
+        if normalized_mask.len() != self.meta.count {
+            return Err(SoAKitError::LengthMismatch {
+                expected: self.meta.count,
+                actual: normalized_mask.len(),
+            });
+        }
+        Ok(normalized_mask)
+    }
+
+    /// Get the element range of every chunk of this bulk.
+    pub(crate) fn all_chunk_bounds(&self) -> Result<Vec<(usize, usize)>> {
+        (0..self.chunks.len())
+            .map(|i| self.chunk_bounds(i))
+            .collect()
+    }
+
+    /// Store whole-field chunk columns, bumping each field's version.
+    ///
+    /// With a registry, dependent derived caches are invalidated too. Every new
+    /// version is computed before anything is stored.
+    pub(crate) fn store_columns(
+        &mut self,
+        registry: Option<&Registry>,
+        updates: Vec<(String, Vec<Value>)>,
+    ) -> Result<()> {
+        let versions = updates
+            .iter()
+            .map(|(field, _)| {
+                let current_ver = self.meta.versions.get(field).copied().unwrap_or(0);
+                current_ver
+                    .checked_add(1)
+                    .ok_or_else(|| SoAKitError::InvalidArgument("Version overflow".to_string()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        for ((field, columns), new_ver) in updates.into_iter().zip(versions) {
+            for (chunk, vector_value) in self.chunks.iter_mut().zip(columns) {
+                chunk.insert_column(&field, vector_value);
+            }
+            if let Some(registry) = registry {
+                self.invalidate_dependent_cache(registry, &field);
+            }
+            self.store_version(&field, new_ver);
+        }
+
+        Ok(())
+    }
+
+    /// Remove the elements where the mask is `true`.
+    ///
+    /// Returns a new bulk with the remaining elements, in their original order,
+    /// packed into fresh chunks. Element IDs and field versions are kept, interned
+    /// fields stay interned, and the derived field cache starts out empty.
+    ///
+    /// # Arguments
+    ///
+    /// * `mask` - Boolean array with one entry per element; `true` removes the element
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` without the removed elements.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::LengthMismatch`] if the mask length doesn't match the bulk count
+    /// - [`SoAKitError::InvalidArgument`] if every element would be removed
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(3).unwrap();
+    /// let bulk = bulk.set(&registry, "age", vec![
+    ///     Value::ScalarInt(10),
+    ///     Value::ScalarInt(20),
+    ///     Value::ScalarInt(30),
+    /// ]).unwrap();
+    ///
+    /// let bulk = bulk.remove_where(&[false, true, false]).unwrap();
+    /// assert_eq!(bulk.meta.id, vec![0, 2]);
+    /// assert_eq!(bulk.get(&registry, "age").unwrap(), Value::VectorInt(vec![10, 30]));
+    /// ```
+    pub fn remove_where(&self, mask: &[bool]) -> Result<Self> {
+        let (mut new_bulk, bounds) = self.kept_skeleton(mask)?;
+        for field in self.list_data_fields() {
+            let columns = kept_columns(&self.chunks, &field, mask, &bounds)?;
+            new_bulk.insert_kept(&field, columns, self.is_interned(&field));
+        }
+        Ok(new_bulk)
+    }
+
+    /// Build the bulk of the elements kept by `mask`, with IDs and versions but
+    /// no field data, and the element range of each of its chunks.
+    pub(crate) fn kept_skeleton(&self, mask: &[bool]) -> Result<(Self, Vec<(usize, usize)>)> {
+        if mask.len() != self.meta.count {
+            return Err(SoAKitError::LengthMismatch {
+                expected: self.meta.count,
+                actual: mask.len(),
+            });
+        }
+        let count = mask.iter().filter(|&&remove| !remove).count();
+        if count == 0 {
+            return Err(SoAKitError::InvalidArgument(
+                "Cannot remove every element of a bulk".to_string(),
+            ));
+        }
+
+        let mut new_bulk = Bulk::new(count)?;
+        new_bulk.meta.id = self
+            .meta
+            .id
+            .iter()
+            .zip(mask)
+            .filter(|&(_, &remove)| !remove)
+            .map(|(&id, _)| id)
+            .collect();
+        new_bulk.meta.custom_ids = new_bulk
+            .meta
+            .id
+            .iter()
+            .filter_map(|id| Some((*id, self.meta.custom_ids.get(id)?.clone())))
+            .collect();
+        new_bulk.meta.deleted = new_bulk
+            .meta
+            .id
+            .iter()
+            .filter(|id| self.meta.deleted.contains(id))
+            .copied()
+            .collect();
+        new_bulk.meta.versions = self.meta.versions.clone();
+        // Removing elements keeps the remaining ones in order
+        new_bulk.meta.sorted = self.meta.sorted.clone();
+
+        if self.list_data_fields().is_empty() {
+            return Ok((new_bulk, Vec::new()));
+        }
+        let bounds = (0..count.div_ceil(CHUNK_SIZE))
+            .map(|i| new_bulk.chunk_bounds(i))
+            .collect::<Result<Vec<_>>>()?;
+        new_bulk.chunks = bounds
+            .iter()
+            .map(|&(start, end)| Chunk {
+                len: end.saturating_sub(start),
+                ..Chunk::new()
+            })
+            .collect();
+        Ok((new_bulk, bounds))
+    }
+
+    /// Store the chunk columns of a field built by [`kept_columns`].
+    pub(crate) fn insert_kept(&mut self, field: &str, columns: Vec<Value>, interned: bool) {
+        for (chunk, column) in self.chunks.iter_mut().zip(columns) {
+            match column {
+                Value::VectorString(strings) if interned => {
+                    let _ = chunk.interned.insert(
+                        field.to_string(),
+                        Arc::new(InternedStrings::from_strings(&strings)),
+                    );
+                }
+                column => chunk.insert_column(field, column),
+            }
+        }
+    }
+
+    /// Rebuild the bulk's storage after heavy mutation.
+    ///
+    /// Elements marked with [`Bulk::mark_deleted`] are dropped, and the rest are
+    /// packed into evenly filled chunks whose columns hold no spare capacity.
+    /// Element IDs, custom identifiers, field versions and observers are kept.
+    ///
+    /// If no element was dropped, derived cache entries that are still current
+    /// and current indices are kept, and stale ones are cleared. Otherwise the
+    /// positions have changed, and the new bulk starts without caches or indices.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with the live elements in fresh chunks.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if every element is marked as deleted
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    /// use std::collections::BTreeMap;
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("n".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let mut bulk = Bulk::new(1).unwrap();
+    /// bulk.set_mut(&registry, "n", vec![Value::ScalarInt(0)]).unwrap();
+    /// for i in 1..5 {
+    ///     bulk.push_row(&registry, BTreeMap::from([("n".to_string(), Value::ScalarInt(i))])).unwrap();
+    /// }
+    /// bulk.mark_deleted_mut(&[false, true, false, true, false]).unwrap();
+    ///
+    /// let compacted = bulk.compact(&registry).unwrap();
+    /// assert_eq!(compacted.get(&registry, "n").unwrap(), Value::VectorInt(vec![0, 2, 4]));
+    /// assert_eq!(compacted.meta.id, vec![0, 2, 4]);
+    /// ```
+    pub fn compact(&self, registry: &Registry) -> Result<Self> {
+        let live = self.live_mask();
+        let dropped: Vec<bool> = live.iter().map(|&live| !live).collect();
+        let mut new_bulk = self.remove_where(&dropped)?;
+        new_bulk.observers = self.observers.clone();
+        new_bulk.meta.id.shrink_to_fit();
+        if !live.contains(&false) {
+            let cache = self.current_cache(registry);
+            *new_bulk.cache.borrow_mut() = cache;
+            *new_bulk.indices.borrow_mut() = self.current_indices();
+        }
+        Ok(new_bulk)
+    }
+
+    /// Get the derived cache entries that are still current, for new chunks.
+    ///
+    /// Per-chunk versions are dropped, since they describe the old chunks.
+    fn current_cache(&self, registry: &Registry) -> BTreeMap<String, CacheEntry> {
+        self.cache
+            .borrow()
+            .iter()
+            .filter(|(field, entry)| {
+                registry.get_metadata(field).is_some_and(|metadata| {
+                    metadata.is_derived
+                        && self
+                            .dependency_versions(registry, &metadata.dependencies)
+                            .is_ok_and(|versions| versions == entry.versions)
+                })
+            })
+            .map(|(field, entry)| {
+                let entry = CacheEntry {
+                    value: entry.value.clone(),
+                    versions: entry.versions.clone(),
+                    chunk_versions: Vec::new(),
+                };
+                (field.clone(), entry)
+            })
+            .collect()
+    }
+
+    /// Remove the elements at the given positions.
+    ///
+    /// Positions are indices into the bulk, not element IDs. Repeated positions
+    /// are removed once. See [`Bulk::remove_where`] for what the new bulk keeps.
+    ///
+    /// # Arguments
+    ///
+    /// * `indices` - Positions of the elements to remove, in any order
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` without the removed elements.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::IndexOutOfBounds`] if a position is out of bounds
+    /// - [`SoAKitError::InvalidArgument`] if every element would be removed
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(4).unwrap();
+    /// let bulk = bulk.set(&registry, "age", vec![
+    ///     Value::ScalarInt(10),
+    ///     Value::ScalarInt(20),
+    ///     Value::ScalarInt(30),
+    ///     Value::ScalarInt(40),
+    /// ]).unwrap();
+    ///
+    /// let bulk = bulk.remove_indices(&[3, 0]).unwrap();
+    /// assert_eq!(bulk.get(&registry, "age").unwrap(), Value::VectorInt(vec![20, 30]));
+    /// ```
+    pub fn remove_indices(&self, indices: &[usize]) -> Result<Self> {
+        let mut mask = vec![false; self.meta.count];
+        for &idx in indices {
+            let remove = mask.get_mut(idx).ok_or(SoAKitError::IndexOutOfBounds {
+                index: idx,
+                max: self.meta.count,
+            })?;
+            *remove = true;
+        }
+        self.remove_where(&mask)
+    }
+
+    /// Keep only the elements that match a predicate.
+    ///
+    /// The predicate is called once per element with a [`Proxy`](crate::proxy::Proxy)
+    /// for that element, so it can read any field, including derived ones. The
+    /// matching elements are packed into a new bulk as by [`Bulk::remove_where`].
+    ///
+    /// # Arguments
+    ///
+    /// * `predicate` - Function that returns `true` for elements to keep
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with the matching elements.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if no element matches
+    /// - Any error returned by `predicate`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(3).unwrap();
+    /// let bulk = bulk.set(&registry, "age", vec![
+    ///     Value::ScalarInt(15),
+    ///     Value::ScalarInt(30),
+    ///     Value::ScalarInt(45),
+    /// ]).unwrap();
+    ///
+    /// let adults = bulk
+    ///     .filter(|row| Ok(matches!(row.get_field(&registry, "age")?, Value::ScalarInt(a) if a >= 18)))
+    ///     .unwrap();
+    /// assert_eq!(adults.get(&registry, "age").unwrap(), Value::VectorInt(vec![30, 45]));
+    /// ```
+    pub fn filter<F>(&self, predicate: F) -> Result<Self>
+    where
+        F: Fn(&crate::proxy::Proxy) -> Result<bool>,
+    {
+        let shared = Arc::new(self.clone());
+        let mut remove = Vec::with_capacity(self.meta.count);
+        for idx in 0..self.meta.count {
+            let proxy = crate::proxy::Proxy::new(Arc::clone(&shared), idx)?;
+            remove.push(!predicate(&proxy)?);
+        }
+        self.remove_where(&remove)
+    }
+
+    /// Keep only the elements whose value of one field matches a predicate.
+    ///
+    /// This is a vectorized form of [`Bulk::filter`]: the field is read once and the
+    /// predicate is called with each element's value. Missing elements of sparse
+    /// fields are passed as [`Value::Null`].
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `field` - The field to test; derived fields are allowed
+    /// * `predicate` - Function that returns `true` for values whose elements are kept
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with the matching elements.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field doesn't exist
+    /// - [`SoAKitError::InvalidArgument`] if no element matches
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(3).unwrap();
+    /// let bulk = bulk.set(&registry, "age", vec![
+    ///     Value::ScalarInt(15),
+    ///     Value::ScalarInt(30),
+    ///     Value::ScalarInt(45),
+    /// ]).unwrap();
+    ///
+    /// let adults = bulk
+    ///     .filter_field(&registry, "age", |v| matches!(v, Value::ScalarInt(a) if *a >= 18))
+    ///     .unwrap();
+    /// assert_eq!(adults.meta.id, vec![1, 2]);
+    /// ```
+    pub fn filter_field<F>(&self, registry: &Registry, field: &str, predicate: F) -> Result<Self>
+    where
+        F: Fn(&Value) -> bool,
+    {
+        let remove: Vec<bool> = self
+            .get(registry, field)?
+            .to_scalars()
+            .iter()
+            .map(|value| !predicate(value))
+            .collect();
+        self.remove_where(&remove)
+    }
+
+    /// Remove duplicate elements.
+    ///
+    /// Two elements are duplicates if they have equal values for every field in
+    /// `subset`, or for every data field if `subset` is empty. Values are compared
+    /// as [`HashableValue`]s, so NaNs equal each other and `-0.0` equals `0.0`. Of
+    /// each set of duplicates, the first or last element is kept, as chosen by
+    /// `keep`; the kept elements are packed into a new bulk as by
+    /// [`Bulk::remove_where`].
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `subset` - The fields to compare; empty means all data fields
+    /// * `keep` - Which element of each set of duplicates to keep
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` without the duplicates.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if a field of `subset` doesn't exist
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Keep, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("user".to_string(), validator.clone(), false, vec![], None).unwrap();
+    /// registry.register("visit".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(3).unwrap()
+    ///     .set(&registry, "user", vec![Value::ScalarInt(1), Value::ScalarInt(2), Value::ScalarInt(1)]).unwrap()
+    ///     .set(&registry, "visit", vec![Value::ScalarInt(10), Value::ScalarInt(20), Value::ScalarInt(30)]).unwrap();
+    ///
+    /// let latest = bulk.dedup(&registry, &["user"], Keep::Last).unwrap();
+    /// assert_eq!(latest.get(&registry, "visit").unwrap(), Value::VectorInt(vec![20, 30]));
+    /// ```
+    pub fn dedup(&self, registry: &Registry, subset: &[&str], keep: Keep) -> Result<Self> {
+        let fields: Vec<String> = if subset.is_empty() {
+            self.list_data_fields()
+        } else {
+            subset.iter().map(|field| field.to_string()).collect()
+        };
+        let columns = fields
+            .iter()
+            .map(|field| Ok(self.get(registry, field)?.to_scalars()))
+            .collect::<Result<Vec<_>>>()?;
+
+        let row_key = |idx: usize| -> Vec<HashableValue> {
+            columns
+                .iter()
+                .map(|column| HashableValue::new(column.get(idx).cloned().unwrap_or(Value::Null)))
+                .collect()
+        };
+        let mut seen = HashSet::new();
+        let mut remove = vec![false; self.meta.count];
+        let order: Box<dyn Iterator<Item = usize>> = match keep {
+            Keep::First => Box::new(0..self.meta.count),
+            Keep::Last => Box::new((0..self.meta.count).rev()),
+        };
+        for idx in order {
+            if !seen.insert(row_key(idx))
+                && let Some(slot) = remove.get_mut(idx)
+            {
+                *slot = true;
+            }
+        }
+        self.remove_where(&remove)
+    }
+
+    /// Keep the first `n` elements.
+    ///
+    /// If the bulk has `n` or fewer elements, all of them are kept. The kept
+    /// elements are packed into a new bulk as by [`Bulk::remove_where`].
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of elements to keep
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with the first `n` elements.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if `n` is 0
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Bulk;
+    ///
+    /// let bulk = Bulk::new(5).unwrap();
+    /// assert_eq!(bulk.head(2).unwrap().meta.id, vec![0, 1]);
+    /// assert_eq!(bulk.tail(2).unwrap().meta.id, vec![3, 4]);
+    /// ```
+    pub fn head(&self, n: usize) -> Result<Self> {
+        let remove: Vec<bool> = (0..self.meta.count).map(|idx| idx >= n).collect();
+        self.remove_where(&remove)
+    }
+
+    /// Keep the last `n` elements.
+    ///
+    /// If the bulk has `n` or fewer elements, all of them are kept. See
+    /// [`Bulk::head`].
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of elements to keep
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with the last `n` elements.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if `n` is 0
+    pub fn tail(&self, n: usize) -> Result<Self> {
+        let first = self.meta.count.saturating_sub(n);
+        let remove: Vec<bool> = (0..self.meta.count).map(|idx| idx < first).collect();
+        self.remove_where(&remove)
+    }
+
+    /// Keep a random sample of `n` elements.
+    ///
+    /// Elements are chosen uniformly without replacement by a pseudo-random
+    /// generator seeded with `seed`, so the same seed always picks the same
+    /// elements. The sampled elements keep their original order. If the bulk has
+    /// `n` or fewer elements, all of them are kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of elements to keep
+    /// * `seed` - The seed of the pseudo-random generator
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with the sampled elements.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if `n` is 0
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Bulk;
+    ///
+    /// let bulk = Bulk::new(100).unwrap();
+    /// let sample = bulk.sample(10, 42).unwrap();
+    /// assert_eq!(sample.count(), 10);
+    /// assert_eq!(sample.meta.id, bulk.sample(10, 42).unwrap().meta.id);
+    /// ```
+    pub fn sample(&self, n: usize, seed: u64) -> Result<Self> {
+        let count = self.meta.count;
+        let n = n.min(count);
+
+        // Partial Fisher-Yates shuffle: the first `n` positions end up sampled
+        let mut positions: Vec<usize> = (0..count).collect();
+        let mut state = seed;
+        for i in 0..n {
+            let remaining = u64::try_from(count.saturating_sub(i)).unwrap_or(u64::MAX);
+            let offset = splitmix64(&mut state).checked_rem(remaining).unwrap_or(0);
+            let j = i.saturating_add(usize::try_from(offset).unwrap_or(0));
+            positions.swap(i, j);
+        }
+
+        let mut remove = vec![true; count];
+        for &idx in positions.iter().take(n) {
+            if let Some(slot) = remove.get_mut(idx) {
+                *slot = false;
+            }
+        }
+        self.remove_where(&remove)
+    }
+
+    /// Sort the elements by one or more fields.
+    ///
+    /// Elements are ordered by the first key, ties are broken by the next key, and
+    /// so on; elements that tie on every key keep their original relative order.
+    /// Values are compared with [`Value::total_cmp`], except that NaN floats and
+    /// missing elements ([`Value::Null`]) always sort last, in either direction.
+    ///
+    /// Every data field is reordered with the same permutation, as are the element
+    /// IDs. Field versions are incremented and derived caches are invalidated. If
+    /// the first key is a stored field, its order is recorded for
+    /// [`Bulk::binary_search`] and [`Bulk::range_lookup`].
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `keys` - The fields to sort by, each with its direction; derived fields are allowed
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with the elements reordered.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if `keys` is empty
+    /// - [`SoAKitError::FieldNotFound`] if a key field doesn't exist
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, SortOrder, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("score".to_string(), validator, false, vec![], None).unwrap();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarString(_)));
+    /// registry.register("name".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(3).unwrap();
+    /// let bulk = bulk.set(&registry, "score", vec![
+    ///     Value::ScalarInt(1),
+    ///     Value::ScalarInt(2),
+    ///     Value::ScalarInt(2),
+    /// ]).unwrap();
+    /// let bulk = bulk.set(&registry, "name", vec![
+    ///     Value::from("c"),
+    ///     Value::from("b"),
+    ///     Value::from("a"),
+    /// ]).unwrap();
+    ///
+    /// let sorted = bulk
+    ///     .sort_by(&registry, &[("score", SortOrder::Desc), ("name", SortOrder::Asc)])
+    ///     .unwrap();
+    /// assert_eq!(sorted.meta.id, vec![2, 1, 0]);
+    /// assert_eq!(sorted.get(&registry, "name").unwrap(), Value::from(vec!["a", "b", "c"]));
+    /// ```
+    pub fn sort_by(&self, registry: &Registry, keys: &[(&str, SortOrder)]) -> Result<Self> {
+        if keys.is_empty() {
+            return Err(SoAKitError::InvalidArgument(
+                "sort_by needs at least one key".to_string(),
+            ));
+        }
+        let key_columns = keys
+            .iter()
+            .map(|&(field, order)| Ok((self.get(registry, field)?.to_scalars(), order)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut permutation: Vec<usize> = (0..self.meta.count).collect();
+        // `sort_by` is stable, so full ties keep their original order
+        permutation.sort_by(|&a, &b| {
+            key_columns
+                .iter()
+                .map(|(column, order)| match (column.get(a), column.get(b)) {
+                    (Some(x), Some(y)) => order.compare(x, y),
+                    _ => Ordering::Equal,
+                })
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal)
+        });
+
+        let mut new_bulk = self.clone();
+        new_bulk.meta.id = permutation
+            .iter()
+            .map(|&idx| self.meta.id.get(idx).copied().unwrap_or(idx))
+            .collect();
+        for field in self.list_data_fields() {
+            let column = self.get(registry, &field)?.to_scalars();
+            let permuted = permutation
+                .iter()
+                .map(|&idx| {
+                    column
+                        .get(idx)
+                        .cloned()
+                        .ok_or(SoAKitError::IndexOutOfBounds {
+                            index: idx,
+                            max: column.len(),
+                        })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            new_bulk.set_chunks(registry, &field, |start, end| {
+                chunk_of(&permuted, start, end)
+            })?;
+        }
+        if let Some(&(first, order)) = keys.first()
+            && let Some(&version) = new_bulk.meta.versions.get(first)
+        {
+            let _ = new_bulk
+                .meta
+                .sorted
+                .insert(first.to_string(), (order, version));
+        }
+        Ok(new_bulk)
+    }
+
+    /// Keep the `k` elements ranked first by one field.
+    ///
+    /// This gives the same elements as [`Bulk::sort_by`] on `field` followed by
+    /// [`Bulk::head`], but only the top `k` values are kept while scanning the
+    /// field, so the whole bulk is never sorted. The kept elements are returned in
+    /// rank order; ties keep their original order, and NaN floats and missing
+    /// elements rank last in either direction. If the bulk has `k` or fewer
+    /// elements, all of them are kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `field` - The field to rank by; derived fields are allowed
+    /// * `k` - The number of elements to keep
+    /// * `order` - [`SortOrder::Desc`] for the largest values, [`SortOrder::Asc`] for the smallest
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with the top elements, best first.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if `k` is 0
+    /// - [`SoAKitError::FieldNotFound`] if the field doesn't exist
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, SortOrder, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("score".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let scores = [30, 90, 10, 70, 50].map(Value::ScalarInt).to_vec();
+    /// let bulk = Bulk::new(5).unwrap().set(&registry, "score", scores).unwrap();
+    ///
+    /// let top = bulk.top_k(&registry, "score", 2, SortOrder::Desc).unwrap();
+    /// assert_eq!(top.get(&registry, "score").unwrap(), Value::VectorInt(vec![90, 70]));
+    /// assert_eq!(top.meta.id, vec![1, 3]);
+    /// ```
+    pub fn top_k(
+        &self,
+        registry: &Registry,
+        field: &str,
+        k: usize,
+        order: SortOrder,
+    ) -> Result<Self> {
+        let positions = self.top_k_positions(registry, field, k, order)?;
+        let mut remove = vec![true; self.meta.count];
+        for &idx in &positions {
+            if let Some(slot) = remove.get_mut(idx) {
+                *slot = false;
+            }
+        }
+        // Sorting the kept elements is cheap, and stable like the ranking
+        self.remove_where(&remove)?
+            .sort_by(registry, &[(field, order)])
+    }
+
+    /// Get the positions of the `k` elements ranked first by one field.
+    ///
+    /// This is [`Bulk::top_k`] without building a bulk: the positions are returned
+    /// in rank order, for reading the elements with [`Bulk::at`] or a mask.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::top_k`].
+    pub fn top_k_positions(
+        &self,
+        registry: &Registry,
+        field: &str,
+        k: usize,
+        order: SortOrder,
+    ) -> Result<Vec<usize>> {
+        if k == 0 {
+            return Err(SoAKitError::InvalidArgument(
+                "top_k needs k of at least 1".to_string(),
+            ));
+        }
+        // A max-heap of the best elements so far, with the worst of them on top
+        let mut heap = BinaryHeap::with_capacity(k.saturating_add(1));
+        let mut position = 0usize;
+        for part in self.field_parts(registry, field)? {
+            for value in part.to_scalars() {
+                heap.push(Ranked {
+                    value,
+                    position,
+                    order,
+                });
+                if heap.len() > k {
+                    let _ = heap.pop();
+                }
+                position = position.saturating_add(1);
+            }
+        }
+        Ok(heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|ranked| ranked.position)
+            .collect())
+    }
+
+    /// Record that a stored field is sorted, after checking it.
+    ///
+    /// [`Bulk::sort_by`] records the order of its first key automatically; use
+    /// this for data that arrives already sorted. NaN floats and missing elements
+    /// must come last, as `sort_by` leaves them. The record lasts until the field
+    /// is next updated.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `field` - The name of a stored field
+    /// * `order` - The order the field is sorted in
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field is not stored in the bulk
+    /// - [`SoAKitError::InvalidArgument`] if the field is not sorted in `order`
+    pub fn declare_sorted(
+        &mut self,
+        registry: &Registry,
+        field: &str,
+        order: SortOrder,
+    ) -> Result<()> {
+        let version = self
+            .meta
+            .versions
+            .get(field)
+            .copied()
+            .ok_or_else(|| SoAKitError::FieldNotFound(field.to_string()))?;
+        let mut previous: Option<Value> = None;
+        for part in self.field_parts(registry, field)? {
+            for value in part.to_scalars() {
+                if let Some(prev) = &previous
+                    && order.compare(prev, &value) == Ordering::Greater
+                {
+                    return Err(SoAKitError::InvalidArgument(format!(
+                        "Field '{}' is not sorted in {:?} order",
+                        field, order
+                    )));
+                }
+                previous = Some(value);
+            }
+        }
+        let _ = self.meta.sorted.insert(field.to_string(), (order, version));
+        Ok(())
+    }
+
+    /// Get the order a field is known to be sorted in.
+    ///
+    /// # Returns
+    ///
+    /// The order recorded by [`Bulk::sort_by`] or [`Bulk::declare_sorted`], or
+    /// `None` if the field is not known to be sorted or has changed since.
+    pub fn sorted_order(&self, field: &str) -> Option<SortOrder> {
+        self.meta
+            .sorted
+            .get(field)
+            .filter(|&&(_, version)| self.meta.versions.get(field) == Some(&version))
+            .map(|&(order, _)| order)
+    }
+
+    /// Find an element with the given value in a sorted field.
+    ///
+    /// The field must be known to be sorted (see [`Bulk::sorted_order`]), so the
+    /// search takes O(log n) element reads instead of a scan.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `field` - The name of a sorted field
+    /// * `value` - The scalar value to look for
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Some(index))` with the position of the first element equal to
+    /// `value`, or `Ok(None)` if there is none.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field is not registered
+    /// - [`SoAKitError::InvalidArgument`] if the field is not known to be sorted
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, SortOrder, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("ts".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(4).unwrap();
+    /// let bulk = bulk.set(&registry, "ts", vec![
+    ///     Value::ScalarInt(40),
+    ///     Value::ScalarInt(10),
+    ///     Value::ScalarInt(30),
+    ///     Value::ScalarInt(20),
+    /// ]).unwrap();
+    /// let bulk = bulk.sort_by(&registry, &[("ts", SortOrder::Asc)]).unwrap();
+    ///
+    /// assert_eq!(bulk.binary_search(&registry, "ts", &Value::ScalarInt(30)).unwrap(), Some(2));
+    /// assert_eq!(bulk.binary_search(&registry, "ts", &Value::ScalarInt(35)).unwrap(), None);
+    /// assert_eq!(
+    ///     bulk.range_lookup("ts", Value::ScalarInt(15)..Value::ScalarInt(40)).unwrap(),
+    ///     1..3
+    /// );
+    /// ```
+    pub fn binary_search(
+        &self,
+        registry: &Registry,
+        field: &str,
+        value: &Value,
+    ) -> Result<Option<usize>> {
+        if !registry.has_field(field) {
+            return Err(SoAKitError::FieldNotFound(field.to_string()));
+        }
+        let found = self.range_lookup(field, value.clone()..=value.clone())?;
+        Ok((!found.is_empty()).then_some(found.start))
+    }
+
+    /// Find the elements of a sorted field whose values fall in a range.
+    ///
+    /// The field must be known to be sorted (see [`Bulk::sorted_order`]). Since it
+    /// is sorted, the matching elements are contiguous; their positions are found
+    /// with two binary searches. NaN and missing elements never match.
+    ///
+    /// # Arguments
+    ///
+    /// * `field` - The name of a sorted field
+    /// * `range` - The range of values to look for, such as `lo..hi` or `lo..=hi`
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Range<usize>)` with the positions of the matching elements,
+    /// which is empty if none match.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if the field is not known to be sorted
+    pub fn range_lookup(
+        &self,
+        field: &str,
+        range: impl RangeBounds<Value>,
+    ) -> Result<std::ops::Range<usize>> {
+        let order = self.sorted_order(field).ok_or_else(|| {
+            SoAKitError::InvalidArgument(format!(
+                "Field '{}' is not known to be sorted; use sort_by or declare_sorted",
+                field
+            ))
+        })?;
+        let below = |v: &Value| match range.start_bound() {
+            Bound::Included(lo) => v.total_cmp(lo) == Ordering::Less,
+            Bound::Excluded(lo) => v.total_cmp(lo) != Ordering::Greater,
+            Bound::Unbounded => false,
+        };
+        let above = |v: &Value| match range.end_bound() {
+            Bound::Included(hi) => v.total_cmp(hi) == Ordering::Greater,
+            Bound::Excluded(hi) => v.total_cmp(hi) != Ordering::Less,
+            Bound::Unbounded => false,
+        };
+        // Elements run [below][in range][above][missing] ascending, and
+        // [above][in range][below][missing] descending
+        let (start, end) = match order {
+            SortOrder::Asc => (
+                self.sorted_partition(field, below)?,
+                self.sorted_partition(field, |v| !above(v) && !sorts_last(v))?,
+            ),
+            SortOrder::Desc => (
+                self.sorted_partition(field, |v| above(v) && !sorts_last(v))?,
+                self.sorted_partition(field, |v| !below(v) && !sorts_last(v))?,
+            ),
+        };
+        Ok(start..end.max(start))
+    }
+
+    /// Find the first position of a sorted field whose element fails `pred`.
+    ///
+    /// `pred` must hold for a prefix of the field and fail for the rest.
+    fn sorted_partition<P>(&self, field: &str, pred: P) -> Result<usize>
+    where
+        P: Fn(&Value) -> bool,
+    {
+        let (mut lo, mut hi) = (0, self.meta.count);
+        while lo < hi {
+            let mid = lo.saturating_add(hi.saturating_sub(lo).checked_div(2).unwrap_or(0));
+            if pred(&self.element_at(field, mid)?) {
+                lo = mid.saturating_add(1);
+            } else {
+                hi = mid;
+            }
+        }
+        Ok(lo)
+    }
+
+    /// Read one element of a stored field.
+    fn element_at(&self, field: &str, idx: usize) -> Result<Value> {
+        let chunk_idx = idx
+            .checked_div(CHUNK_SIZE)
+            .ok_or_else(|| SoAKitError::InvalidArgument("Division by zero".to_string()))?;
+        let offset = idx
+            .checked_rem(CHUNK_SIZE)
+            .ok_or_else(|| SoAKitError::InvalidArgument("Division by zero".to_string()))?;
+        let column = self
+            .chunks
+            .get(chunk_idx)
+            .and_then(|chunk| chunk.column(field))
+            .ok_or_else(|| SoAKitError::FieldNotFound(field.to_string()))?;
+        column_element(&column, offset)
+    }
+
+    /// Join this bulk with another bulk on a key field.
+    ///
+    /// Builds a hash table over `other`'s key column and probes it with each element
+    /// of this bulk, in order. Each matching pair of elements becomes one element of
+    /// the result; an element of this bulk that matches several elements of `other`
+    /// appears once per match. With [`JoinKind::Left`], elements of this bulk without
+    /// a match are kept too, and `other`'s fields are missing ([`Value::Null`]) for
+    /// them. Null keys never match.
+    ///
+    /// The result has every data field of this bulk plus every data field of
+    /// `other` except the key, with fresh element IDs. Values are copied without
+    /// re-validation, so fields that receive missing elements become sparse even if
+    /// their validators reject `Null`.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The bulk to join with
+    /// * `registry` - The registry containing field metadata for both bulks
+    /// * `on` - The key field, which must be a data field of both bulks
+    /// * `kind` - Whether to keep only matches or all elements of this bulk
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with the joined elements.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if either bulk lacks the key field
+    /// - [`SoAKitError::FieldAlreadyExists`] if both bulks have the same non-key field
+    /// - [`SoAKitError::InvalidArgument`] if the join produces no elements
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, JoinKind, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("user_id".to_string(), validator.clone(), false, vec![], None).unwrap();
+    /// registry.register("amount".to_string(), validator, false, vec![], None).unwrap();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarString(_)));
+    /// registry.register("name".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let orders = Bulk::new(3).unwrap()
+    ///     .set(&registry, "user_id", vec![Value::ScalarInt(1), Value::ScalarInt(2), Value::ScalarInt(1)]).unwrap()
+    ///     .set(&registry, "amount", vec![Value::ScalarInt(10), Value::ScalarInt(20), Value::ScalarInt(30)]).unwrap();
+    /// let users = Bulk::new(1).unwrap()
+    ///     .set(&registry, "user_id", vec![Value::ScalarInt(1)]).unwrap()
+    ///     .set(&registry, "name", vec![Value::from("alice")]).unwrap();
+    ///
+    /// let joined = orders.join(&users, &registry, "user_id", JoinKind::Inner).unwrap();
+    /// assert_eq!(joined.get(&registry, "amount").unwrap(), Value::VectorInt(vec![10, 30]));
+    /// assert_eq!(joined.get(&registry, "name").unwrap(), Value::from(vec!["alice", "alice"]));
+    ///
+    /// let left = orders.join(&users, &registry, "user_id", JoinKind::Left).unwrap();
+    /// assert_eq!(left.count(), 3);
+    /// assert_eq!(left.at(1).unwrap().get_field(&registry, "name").unwrap(), Value::Null);
+    /// ```
+    pub fn join(
+        &self,
+        other: &Bulk,
+        registry: &Registry,
+        on: &str,
+        kind: JoinKind,
+    ) -> Result<Self> {
+        let left_fields = self.list_data_fields();
+        let mut right_fields = other.list_data_fields();
+        if !left_fields.iter().any(|field| field == on)
+            || !right_fields.iter().any(|field| field == on)
+        {
+            return Err(SoAKitError::FieldNotFound(on.to_string()));
+        }
+        right_fields.retain(|field| field != on);
+        if let Some(clash) = right_fields
+            .iter()
+            .find(|field| left_fields.contains(field))
+        {
+            return Err(SoAKitError::FieldAlreadyExists(clash.clone()));
+        }
+
+        // Probe the right key column's index with the left key column, hashing
+        // the right key column first if it has no current index
+        let left_keys = self.get(registry, on)?.to_scalars();
+        let indexed = other.with_current_index(on, |index| join_pairs(&left_keys, index, kind));
+        let pairs = match indexed {
+            Some(pairs) => pairs,
+            None => {
+                let right_keys = other.get(registry, on)?.to_scalars();
+                join_pairs(
+                    &left_keys,
+                    &FieldIndex::new(right_keys, 0, IndexKind::Hash),
+                    kind,
+                )
+            }
+        };
+        if pairs.is_empty() {
+            return Err(SoAKitError::InvalidArgument(
+                "Join produced no elements".to_string(),
+            ));
+        }
+
+        let mut joined = Bulk::new(pairs.len())?;
+        for field in &left_fields {
+            let column = self.get(registry, field)?.to_scalars();
+            let values: Vec<Value> = pairs
+                .iter()
+                .map(|&(left, _)| column.get(left).cloned().unwrap_or(Value::Null))
+                .collect();
+            joined.set_chunks(registry, field, |start, end| chunk_of(&values, start, end))?;
+        }
+        for field in &right_fields {
+            let column = other.get(registry, field)?.to_scalars();
+            let values: Vec<Value> = pairs
+                .iter()
+                .map(|&(_, right)| {
+                    right
+                        .and_then(|r| column.get(r).cloned())
+                        .unwrap_or(Value::Null)
+                })
+                .collect();
+            joined.set_chunks(registry, field, |start, end| chunk_of(&values, start, end))?;
+        }
+        Ok(joined)
+    }
+
+    /// Merge elements from another bulk, matching them by element ID.
+    ///
+    /// Elements of `other` whose ID is also in this bulk overwrite that element's
+    /// values; the others are appended with their IDs. `other` may have fewer
+    /// fields than this bulk: overwritten elements keep their values for the
+    /// missing fields, and appended elements get [`Value::Null`] if the field's
+    /// validator accepts it.
+    ///
+    /// Only fields whose values actually change get a new version and have their
+    /// dependent derived caches invalidated; when elements are appended, every
+    /// field changes.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The bulk to merge into this one
+    /// * `registry` - The registry containing field metadata
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with the merged elements.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if `other` has a field this bulk lacks, or
+    ///   an appended element has the identifier of another element (see
+    ///   [`Bulk::ids`])
+    /// - [`SoAKitError::ValidationFailed`] if a merged value fails validation,
+    ///   including a missing value of an appended element
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("score".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(2).unwrap()
+    ///     .set(&registry, "score", vec![Value::ScalarInt(1), Value::ScalarInt(2)]).unwrap();
+    /// let mut update = Bulk::new(2).unwrap()
+    ///     .set(&registry, "score", vec![Value::ScalarInt(20), Value::ScalarInt(30)]).unwrap();
+    /// update.meta.id = vec![1, 5];
+    ///
+    /// let merged = bulk.merge_by_id(&update, &registry).unwrap();
+    /// assert_eq!(merged.meta.id, vec![0, 1, 5]);
+    /// assert_eq!(merged.get(&registry, "score").unwrap(), Value::VectorInt(vec![1, 20, 30]));
+    /// ```
+    pub fn merge_by_id(&self, other: &Bulk, registry: &Registry) -> Result<Self> {
+        self.merge_rows(other, registry, &self.meta.id, &other.meta.id, true)
+    }
+
+    /// Merge elements from another bulk, matching them by a key field.
+    ///
+    /// Works like [`Bulk::merge_by_id`], except that elements match when their
+    /// values of `key` are equal (NaN-safely, as in [`Bulk::partition_by`]), and
+    /// appended elements get new IDs after the largest existing ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The bulk to merge into this one
+    /// * `registry` - The registry containing field metadata
+    /// * `key` - The field identifying elements, which must be set in both bulks
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with the merged elements.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if either bulk lacks the key field
+    /// - The errors of [`Bulk::merge_by_id`]
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarString(_)));
+    /// registry.register("sku".to_string(), validator, false, vec![], None).unwrap();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("qty".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let stock = Bulk::new(2).unwrap()
+    ///     .set(&registry, "sku", vec![Value::from("a"), Value::from("b")]).unwrap()
+    ///     .set(&registry, "qty", vec![Value::ScalarInt(1), Value::ScalarInt(2)]).unwrap();
+    /// let delivery = Bulk::new(2).unwrap()
+    ///     .set(&registry, "sku", vec![Value::from("b"), Value::from("c")]).unwrap()
+    ///     .set(&registry, "qty", vec![Value::ScalarInt(5), Value::ScalarInt(7)]).unwrap();
+    ///
+    /// let merged = stock.merge_by_key(&delivery, &registry, "sku").unwrap();
+    /// assert_eq!(merged.get(&registry, "qty").unwrap(), Value::VectorInt(vec![1, 5, 7]));
+    /// ```
+    pub fn merge_by_key(&self, other: &Bulk, registry: &Registry, key: &str) -> Result<Self> {
+        let keys = |bulk: &Bulk| -> Result<Vec<HashableValue>> {
+            if !bulk.list_data_fields().iter().any(|field| field == key) {
+                return Err(SoAKitError::FieldNotFound(key.to_string()));
+            }
+            Ok(bulk
+                .get(registry, key)?
+                .to_scalars()
+                .into_iter()
+                .map(HashableValue::new)
+                .collect())
+        };
+        self.merge_rows(other, registry, &keys(self)?, &keys(other)?, false)
+    }
+
+    /// Merge `other` into this bulk, matching elements with equal keys.
+    ///
+    /// Appended elements keep `other`'s IDs if `keep_other_ids` is set, and get new
+    /// IDs otherwise.
+    fn merge_rows<K: std::hash::Hash + Eq>(
+        &self,
+        other: &Bulk,
+        registry: &Registry,
+        keys: &[K],
+        other_keys: &[K],
+        keep_other_ids: bool,
+    ) -> Result<Self> {
+        let fields = self.list_data_fields();
+        let other_fields = other.list_data_fields();
+        if let Some(unknown) = other_fields.iter().find(|field| !fields.contains(field)) {
+            return Err(SoAKitError::InvalidArgument(format!(
+                "Field '{}' of the merged bulk is not a data field of this bulk",
+                unknown
+            )));
+        }
+
+        let mut positions: HashMap<&K, Vec<usize>> = HashMap::new();
+        for (idx, key) in keys.iter().enumerate() {
+            positions.entry(key).or_default().push(idx);
+        }
+        let mut overwrites = Vec::new();
+        let mut appended = Vec::new();
+        for (other_idx, key) in other_keys.iter().enumerate() {
+            match positions.get(key) {
+                Some(targets) => overwrites.extend(targets.iter().map(|&idx| (idx, other_idx))),
+                None => appended.push(other_idx),
+            }
+        }
+
+        let mut columns = Vec::with_capacity(fields.len());
+        for field in fields {
+            let old = self.get(registry, &field)?.to_scalars();
+            let incoming = if other_fields.contains(&field) {
+                Some(other.get(registry, &field)?.to_scalars())
+            } else {
+                None
+            };
+            let mut new = old.clone();
+            if let Some(incoming) = &incoming {
+                for &(idx, other_idx) in &overwrites {
+                    if let (Some(slot), Some(value)) = (new.get_mut(idx), incoming.get(other_idx)) {
+                        slot.clone_from(value);
+                    }
+                }
+            }
+            for &other_idx in &appended {
+                let value = incoming.as_ref().and_then(|column| column.get(other_idx));
+                new.push(value.cloned().unwrap_or(Value::Null));
+            }
+
+            // Validate every value that differs from the current one
+            let mut changed = false;
+            for (idx, value) in new.iter().enumerate() {
+                if old.get(idx).is_some_and(|cur| cur.equals_nan_aware(value)) {
+                    continue;
+                }
+                changed = true;
+                registry.check_value(&field, value)?;
+            }
+            columns.push((field, new, changed));
+        }
+
+        let mut new_bulk = self.clone();
+        let mut interned = Vec::new();
+        if !appended.is_empty() {
+            new_bulk.append_ids(other, &appended, keep_other_ids)?;
+            new_bulk.meta.count = new_bulk.meta.id.len();
+
+            // Chunks are rebuilt for the new count; interning is restored afterwards
+            interned = columns
+                .iter()
+                .filter(|(field, _, _)| self.is_interned(field))
+                .map(|(field, _, _)| field.clone())
+                .collect();
+            new_bulk.chunks = Vec::new();
+        }
+        for (field, values, changed) in columns {
+            if changed {
+                new_bulk
+                    .set_chunks(registry, &field, |start, end| chunk_of(&values, start, end))?;
+            }
+        }
+        for field in interned {
+            // A field that no longer holds plain strings, e.g. now sparse, stays plain
+            if let Ok(reinterned) = new_bulk.intern_field(&field) {
+                new_bulk = reinterned;
+            }
+        }
+        Ok(new_bulk)
+    }
+
+    /// Append the IDs of the elements of `other` appended by a merge.
+    ///
+    /// Their IDs are kept if `keep_other_ids` is set, and otherwise numbered after
+    /// the largest ID and numeric custom identifier of `self`. Custom identifiers and tombstones are carried over.
+    ///
+    /// Fails if an appended element would have the same identifier (see
+    /// [`Meta::element_id`]) as another element.
+    fn append_ids(&mut self, other: &Bulk, appended: &[usize], keep_other_ids: bool) -> Result<()> {
+        let mut taken: HashSet<ElementId> = self.ids().into_iter().collect();
+        let mut next_id = self.meta.next_id()?;
+        for &other_idx in appended {
+            let other_id = other.meta.id.get(other_idx);
+            let id = match other_id {
+                Some(&id) if keep_other_ids => id,
+                _ => {
+                    let id = next_id;
+                    next_id = next_id.checked_add(1).ok_or_else(|| {
+                        SoAKitError::InvalidArgument("Arithmetic overflow".to_string())
+                    })?;
+                    id
+                }
+            };
+            if let Some(custom_id) = other_id.and_then(|i| other.meta.custom_ids.get(i)) {
+                let _ = self.meta.custom_ids.insert(id, custom_id.clone());
+            }
+            let identifier = self.meta.element_id(id);
+            if !taken.insert(identifier.clone()) {
+                return Err(SoAKitError::InvalidArgument(format!(
+                    "Duplicate element id: {}",
+                    identifier
+                )));
+            }
+            if other_id.is_some_and(|i| other.meta.deleted.contains(i)) {
+                let _ = self.meta.deleted.insert(id);
+            }
+            self.meta.id.push(id);
+        }
+        *self.id_index.get_mut() = None;
+        Ok(())
+    }
+
+    /// Partition the bulk by a field's values.
+    ///
+    /// Creates a [`View`] for each unique value in the specified field. Each view
+    /// represents a partition containing all elements that have that particular value.
+    ///
+    /// This is useful for grouping data by categorical values or performing
+    /// operations on subsets of the data.
+    ///
+    /// Views are returned sorted by key according to [`Value::total_cmp`]. Float
+    /// keys are grouped NaN-safely: all NaNs form a single partition and `-0.0`
+    /// falls in the same partition as `0.0`.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `field` - The name of the field to partition by
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Vec<View>)` with one view per unique value, or an error if:
+    /// - The field is not registered or has no data
+    /// - The field is not a vector type
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field doesn't exist or has no data
+    /// - [`SoAKitError::InvalidArgument`] if the field is not a vector
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("category".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(6).unwrap();
+    /// let bulk = bulk.set(&registry, "category", vec![
+    ///     Value::ScalarInt(1),
+    ///     Value::ScalarInt(2),
+    ///     Value::ScalarInt(1),
+    ///     Value::ScalarInt(3),
+    ///     Value::ScalarInt(2),
+    ///     Value::ScalarInt(1),
+    /// ]).unwrap();
+    ///
+    /// let views = bulk.partition_by(&registry, "category").unwrap();
+    /// assert_eq!(views.len(), 3); // Three unique categories
+    /// ```
+    pub fn partition_by(&self, registry: &Registry, field: &str) -> Result<Vec<crate::view::View>> {
+        // Group element indices by value. `HashableValue` gives a total order with
+        // NaN-safe float equality, so keys come out sorted and NaNs share one group.
+        let positions = match self.with_current_index(field, FieldIndex::sorted_groups) {
+            Some(groups) => groups,
+            None => self.value_positions(registry, field)?,
+        };
+        self.views_of_groups(
+            positions
+                .into_iter()
+                .map(|(key, group)| (key.into_inner(), group)),
+        )
+    }
+
+    /// Count how often each value of a field occurs.
+    ///
+    /// This summarizes the partitions [`Bulk::partition_by`] would create, with
+    /// values grouped the same NaN-safe way, but only counts the elements instead
+    /// of building a mask per value. Stored fields are read chunk by chunk.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `field` - The field to count; derived fields are allowed
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Vec<(Value, usize)>)` with each distinct value and its count,
+    /// most frequent first; equally frequent values are in key order.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field doesn't exist
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarString(_)));
+    /// registry.register("color".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let colors = ["red", "blue", "red", "green", "blue", "red"];
+    /// let bulk = Bulk::new(6).unwrap();
+    /// let bulk = bulk.set(&registry, "color", colors.map(Value::from).to_vec()).unwrap();
+    ///
+    /// let counts = bulk.value_counts(&registry, "color").unwrap();
+    /// assert_eq!(counts, vec![
+    ///     (Value::from("red"), 3),
+    ///     (Value::from("blue"), 2),
+    ///     (Value::from("green"), 1),
+    /// ]);
+    /// ```
+    pub fn value_counts(&self, registry: &Registry, field: &str) -> Result<Vec<(Value, usize)>> {
+        let mut counts: BTreeMap<HashableValue, usize> = BTreeMap::new();
+        for part in self.field_parts(registry, field)? {
+            for value in part.to_scalars() {
+                let count = counts.entry(HashableValue::new(value)).or_default();
+                *count = count.saturating_add(1);
+            }
+        }
+        let mut counts: Vec<(Value, usize)> = counts
+            .into_iter()
+            .map(|(value, count)| (value.into_inner(), count))
+            .collect();
+        // Stable, so equal counts stay in key order
+        counts.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+        Ok(counts)
+    }
+
+    /// Partition the bulk by the combined values of several fields.
+    ///
+    /// This is [`Bulk::partition_by`] with a composite key: elements fall in the
+    /// same [`View`] when they agree on every listed field. Each view's key is a
+    /// [`Value::Matrix`] holding the key values in field order, e.g.
+    /// `Matrix([ScalarString("eu"), ScalarInt(1)])`. Views are returned sorted by
+    /// key, comparing the first field first, and floats are grouped NaN-safely as
+    /// in [`Bulk::partition_by`].
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `fields` - The fields making up the key; derived fields are allowed
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Vec<View>)` with one view per distinct combination of values.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if `fields` is empty or a field is not a vector
+    /// - [`SoAKitError::FieldNotFound`] if a field doesn't exist or has no data
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let strings = Box::new(|v: &Value| matches!(v, Value::ScalarString(_)));
+    /// registry.register("region".to_string(), strings, false, vec![], None).unwrap();
+    /// let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("tier".to_string(), ints, false, vec![], None).unwrap();
+    ///
+    /// let region = |s: &str| Value::ScalarString(s.to_string());
+    /// let bulk = Bulk::new(4).unwrap();
+    /// let bulk = bulk.set(&registry, "region", vec![region("eu"), region("us"), region("eu"), region("eu")]).unwrap();
+    /// let bulk = bulk.set(&registry, "tier", vec![Value::ScalarInt(1), Value::ScalarInt(1), Value::ScalarInt(2), Value::ScalarInt(1)]).unwrap();
+    ///
+    /// let views = bulk.partition_by_multi(&registry, &["region", "tier"]).unwrap();
+    /// assert_eq!(views.len(), 3);
+    /// assert_eq!(views[0].key(), &Value::Matrix(vec![region("eu"), Value::ScalarInt(1)]));
+    /// assert_eq!(views[0].count(), 2);
+    /// ```
+    pub fn partition_by_multi(
+        &self,
+        registry: &Registry,
+        fields: &[&str],
+    ) -> Result<Vec<crate::view::View>> {
+        if fields.is_empty() {
+            return Err(SoAKitError::InvalidArgument(
+                "Partition needs at least one field".to_string(),
+            ));
+        }
+        let stored = self.list_data_fields();
+        let mut columns = Vec::with_capacity(fields.len());
+        for &field in fields {
+            if !stored.iter().any(|name| name == field) {
+                return Err(SoAKitError::FieldNotFound(field.to_string()));
+            }
+            let column = self.get(registry, field)?;
+            if !column.is_vector() {
+                return Err(SoAKitError::InvalidArgument(
+                    "Partition field must be a vector".to_string(),
+                ));
+            }
+            columns.push(column);
+        }
+
+        let mut groups: BTreeMap<Vec<HashableValue>, Vec<usize>> = BTreeMap::new();
+        for idx in 0..self.meta.count {
+            let key = columns
+                .iter()
+                .map(|column| column.get_element(idx).map(HashableValue::new))
+                .collect::<Result<Vec<_>>>()?;
+            groups.entry(key).or_default().push(idx);
+        }
+        self.views_of_groups(groups.into_iter().map(|(key, group)| {
+            let key = key.into_iter().map(HashableValue::into_inner).collect();
+            (Value::Matrix(key), group)
+        }))
+    }
+
+    /// Partition the bulk by a key computed from each element.
+    ///
+    /// `classifier` is called once per element with a [`Proxy`](crate::proxy::Proxy)
+    /// for that element, as in [`Bulk::filter`], and returns the element's key, so
+    /// elements can be grouped by a prefix, a remainder or any other computed value
+    /// without storing it as a field first. One [`View`] is returned per distinct
+    /// key, sorted and grouped NaN-safely as in [`Bulk::partition_by`].
+    ///
+    /// # Arguments
+    ///
+    /// * `classifier` - Function returning the key of an element
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Vec<View>)` with one view per distinct key.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by `classifier`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarString(_)));
+    /// registry.register("sku".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let skus = ["AB-1", "CD-7", "AB-3"].map(|s| Value::ScalarString(s.to_string())).to_vec();
+    /// let bulk = Bulk::new(3).unwrap().set(&registry, "sku", skus).unwrap();
+    ///
+    /// let views = bulk
+    ///     .partition_by_fn(|row| match row.get_field(&registry, "sku")? {
+    ///         Value::ScalarString(sku) => Ok(Value::ScalarString(sku.chars().take(2).collect())),
+    ///         other => Ok(other),
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(views[0].key(), &Value::ScalarString("AB".to_string()));
+    /// assert_eq!(views[0].mask(), &[true, false, true]);
+    /// ```
+    pub fn partition_by_fn<F>(&self, classifier: F) -> Result<Vec<crate::view::View>>
+    where
+        F: Fn(&crate::proxy::Proxy) -> Result<Value>,
+    {
+        let shared = Arc::new(self.clone());
+        let mut groups: BTreeMap<HashableValue, Vec<usize>> = BTreeMap::new();
+        for idx in 0..self.meta.count {
+            let proxy = crate::proxy::Proxy::new(Arc::clone(&shared), idx)?;
+            let key = HashableValue::new(classifier(&proxy)?);
+            groups.entry(key).or_default().push(idx);
+        }
+        self.views_of_groups(
+            groups
+                .into_iter()
+                .map(|(key, group)| (key.into_inner(), group)),
+        )
+    }
+
+    /// Partition a numeric field into ranges of values.
+    ///
+    /// `edges` delimit the bins: with edges `[0, 18, 35, 65]` the bins are
+    /// `[0, 18)`, `[18, 35)` and `[35, 65]`, the last one including its upper edge.
+    /// One [`View`] is returned per bin, in order and even if empty, keyed by a
+    /// [`Value::VectorFloat`] of the bin's lower and upper edge. A final view keyed
+    /// by [`Value::Null`] holds the out-of-range elements: values below the first
+    /// edge or above the last, NaNs and missing elements.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `field` - The integer or float field to bin; derived fields are allowed
+    /// * `edges` - At least two finite, strictly increasing bin edges
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Vec<View>)` with `edges.len() - 1` bin views and the out-of-range view.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if the edges are invalid or the field is
+    ///   not a numeric vector
+    /// - [`SoAKitError::FieldNotFound`] if the field doesn't exist or has no data
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let ages = [4, 20, 40, 65, 90].map(Value::ScalarInt).to_vec();
+    /// let bulk = Bulk::new(5).unwrap().set(&registry, "age", ages).unwrap();
+    ///
+    /// let views = bulk.partition_by_bins(&registry, "age", &[0.0, 18.0, 35.0, 65.0]).unwrap();
+    /// let counts: Vec<usize> = views.iter().map(|v| v.count()).collect();
+    /// assert_eq!(counts, vec![1, 1, 2, 1]);
+    /// assert_eq!(views[0].key(), &Value::VectorFloat(vec![0.0, 18.0]));
+    /// assert_eq!(views[3].key(), &Value::Null);
+    /// ```
+    pub fn partition_by_bins(
+        &self,
+        registry: &Registry,
+        field: &str,
+        edges: &[f64],
+    ) -> Result<Vec<crate::view::View>> {
+        let increasing = edges.windows(2).all(|pair| matches!(pair, [a, b] if a < b));
+        if edges.len() < 2 || !increasing || !edges.iter().all(|edge| edge.is_finite()) {
+            return Err(SoAKitError::InvalidArgument(
+                "Bin edges must be at least two finite, strictly increasing values".to_string(),
+            ));
+        }
+        let values = self.numeric_values(registry, field)?;
+        self.views_of_bins(&values, edges)
+    }
+
+    /// Partition a numeric field into bins holding about equally many elements.
+    ///
+    /// The bin edges are the quantiles of the field's values (ignoring NaNs and
+    /// missing elements), interpolated linearly, and are then used as by
+    /// [`Bulk::partition_by_bins`]. Repeated values can make quantiles coincide;
+    /// coinciding edges are merged, so there may be fewer than `bins` bins.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `field` - The integer or float field to bin; derived fields are allowed
+    /// * `bins` - The number of bins, e.g. 4 for quartiles
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Vec<View>)` with the bin views followed by the out-of-range view,
+    /// which holds only NaNs and missing elements.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if `bins` is 0, the field is not a numeric
+    ///   vector, or it has no values to take quantiles of
+    /// - [`SoAKitError::FieldNotFound`] if the field doesn't exist or has no data
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
+    /// registry.register("score".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let scores = [1.0, 2.0, 3.0, 4.0, 5.0].map(Value::ScalarFloat).to_vec();
+    /// let bulk = Bulk::new(5).unwrap().set(&registry, "score", scores).unwrap();
+    ///
+    /// let views = bulk.partition_by_quantiles(&registry, "score", 2).unwrap();
+    /// assert_eq!(views[0].key(), &Value::VectorFloat(vec![1.0, 3.0]));
+    /// assert_eq!(views[1].key(), &Value::VectorFloat(vec![3.0, 5.0]));
+    /// assert_eq!((views[0].count(), views[1].count(), views[2].count()), (2, 3, 0));
+    /// ```
+    pub fn partition_by_quantiles(
+        &self,
+        registry: &Registry,
+        field: &str,
+        bins: usize,
+    ) -> Result<Vec<crate::view::View>> {
+        if bins == 0 {
+            return Err(SoAKitError::InvalidArgument(
+                "Number of bins must be positive".to_string(),
+            ));
+        }
+        let values = self.numeric_values(registry, field)?;
+        let mut sorted: Vec<f64> = values
+            .iter()
+            .flatten()
+            .copied()
+            .filter(|x| !x.is_nan())
+            .collect();
+        if sorted.is_empty() {
+            return Err(SoAKitError::InvalidArgument(format!(
+                "Field '{}' has no values to take quantiles of",
+                field
+            )));
+        }
+        sorted.sort_by(f64::total_cmp);
+
+        let mut edges: Vec<f64> = (0..=bins).map(|k| quantile(&sorted, k, bins)).collect();
+        edges.dedup();
+        if let [only] = edges[..] {
+            // Every value is equal: a single bin holding exactly that value
+            edges.push(only);
+        }
+        self.views_of_bins(&values, &edges)
+    }
+
+    /// Get a numeric field's values as floats, with `None` for missing elements.
+    fn numeric_values(&self, registry: &Registry, field: &str) -> Result<Vec<Option<f64>>> {
+        if !self.list_data_fields().iter().any(|name| name == field) {
+            return Err(SoAKitError::FieldNotFound(field.to_string()));
+        }
+        let column = self.get(registry, field)?;
+        if !column.is_vector() {
+            return Err(SoAKitError::InvalidArgument(
+                "Partition field must be a vector".to_string(),
+            ));
+        }
+        column
+            .to_scalars()
+            .into_iter()
+            .map(|value| match value {
+                Value::ScalarInt(x) => Ok(Some(int_to_float(x))),
+                Value::ScalarFloat(x) => Ok(Some(x)),
+                Value::Null => Ok(None),
+                _ => Err(SoAKitError::InvalidArgument(format!(
+                    "Field '{}' is not numeric",
+                    field
+                ))),
+            })
+            .collect()
+    }
+
+    /// Create one view per bin of `edges` and a last view of out-of-range values.
+    ///
+    /// `edges` must have at least two elements and never decrease.
+    fn views_of_bins(
+        &self,
+        values: &[Option<f64>],
+        edges: &[f64],
+    ) -> Result<Vec<crate::view::View>> {
+        let (Some(&first), Some(&last)) = (edges.first(), edges.last()) else {
+            return Err(SoAKitError::InvalidArgument(
+                "Missing bin edges".to_string(),
+            ));
+        };
+        let bins = edges.len().saturating_sub(1);
+        let mut groups = vec![Vec::new(); bins.saturating_add(1)];
+        for (idx, value) in values.iter().enumerate() {
+            let bin = match *value {
+                Some(x) if x >= first && x <= last => {
+                    // The last bin includes its upper edge
+                    let above = edges.partition_point(|&edge| edge <= x);
+                    above.min(bins).saturating_sub(1)
+                }
+                _ => bins,
+            };
+            if let Some(group) = groups.get_mut(bin) {
+                group.push(idx);
+            }
+        }
+
+        let keys = edges
+            .windows(2)
+            .map(|pair| Value::VectorFloat(pair.to_vec()))
+            .chain(std::iter::once(Value::Null));
+        self.views_of_groups(keys.zip(groups))
+    }
+
+    /// Create one view per group of element positions, keyed by the group's key.
+    fn views_of_groups(
+        &self,
+        groups: impl IntoIterator<Item = (Value, Vec<usize>)>,
+    ) -> Result<Vec<crate::view::View>> {
+        let len = self.meta.count;
+        let shared = Arc::new(self.clone());
+        groups
+            .into_iter()
+            .map(|(key, positions)| {
+                let mut mask = vec![false; len];
+                for idx in positions {
+                    if let Some(slot) = mask.get_mut(idx) {
+                        *slot = true;
+                    }
+                }
+                crate::view::View::new(key, mask, shared.clone())
+            })
+            .collect()
+    }
+
+    /// Group the positions of the elements of a stored field by value.
+    fn value_positions(
+        &self,
+        registry: &Registry,
+        field: &str,
+    ) -> Result<BTreeMap<HashableValue, Vec<usize>>> {
+        // Check if field exists in data
+        if !self.list_data_fields().contains(&field.to_string()) {
+            return Err(SoAKitError::FieldNotFound(field.to_string()));
+        }
+
+        // Get field values
+        let field_value = self.get(registry, field)?;
+
+        if !field_value.is_vector() {
+            return Err(SoAKitError::InvalidArgument(
+                "Partition field must be a vector".to_string(),
+            ));
+        }
+
+        let mut groups: BTreeMap<HashableValue, Vec<usize>> = BTreeMap::new();
+        for idx in 0..field_value.len() {
+            let key = HashableValue::new(field_value.get_element(idx)?);
+            groups.entry(key).or_default().push(idx);
+        }
+        Ok(groups)
+    }
+
+    /// Aggregate fields per group of equal key values.
+    ///
+    /// Groups the elements by `key` as [`Bulk::partition_by`] does and returns a
+    /// summary bulk with one element per group, in key order. The summary has the
+    /// key field, holding each group's key, and one field per aggregation, holding
+    /// the aggregate of that field over the group. Aggregated fields keep their
+    /// source field's name, so the summary can be read with the same registry; the
+    /// values are stored without re-validation, since e.g. the mean of an integer
+    /// field is a float.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `key` - The field to group by
+    /// * `aggs` - The fields to aggregate, each with its aggregation; derived fields are allowed
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with one element per group.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldAlreadyExists`] if a field is aggregated twice or is the key
+    /// - [`SoAKitError::FieldNotFound`] if a field doesn't exist
+    /// - [`SoAKitError::InvalidArgument`] if an aggregation fails for a group, e.g.
+    ///   the mean of a group whose values are all NaN
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Agg, Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarString(_)));
+    /// registry.register("category".to_string(), validator, false, vec![], None).unwrap();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("price".to_string(), validator.clone(), false, vec![], None).unwrap();
+    /// registry.register("qty".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(3).unwrap()
+    ///     .set(&registry, "category", vec![Value::from("b"), Value::from("a"), Value::from("b")]).unwrap()
+    ///     .set(&registry, "price", vec![Value::ScalarInt(1), Value::ScalarInt(5), Value::ScalarInt(2)]).unwrap()
+    ///     .set(&registry, "qty", vec![Value::ScalarInt(10), Value::ScalarInt(20), Value::ScalarInt(30)]).unwrap();
+    ///
+    /// let summary = bulk
+    ///     .group_agg(&registry, "category", &[("price", Agg::Mean), ("qty", Agg::Sum)])
+    ///     .unwrap();
+    /// assert_eq!(summary.get(&registry, "category").unwrap(), Value::from(vec!["a", "b"]));
+    /// assert_eq!(summary.get(&registry, "price").unwrap(), Value::VectorFloat(vec![5.0, 1.5]));
+    /// assert_eq!(summary.get(&registry, "qty").unwrap(), Value::VectorInt(vec![20, 40]));
+    /// ```
+    pub fn group_agg(&self, registry: &Registry, key: &str, aggs: &[(&str, Agg)]) -> Result<Self> {
+        self.group_agg_with(registry, key, aggs, NullPolicy::Skip)
+    }
+
+    /// Aggregate fields per group of equal key values, with a policy for null elements.
+    ///
+    /// This is [`Bulk::group_agg`] with each aggregation applied by
+    /// [`Value::aggregate_with`]: under [`NullPolicy::Propagate`], a group with a
+    /// null element gets a missing aggregate. Use [`Agg::NullCount`] to report the
+    /// number of nulls per group.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::group_agg`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Agg, Bulk, NullPolicy, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarString(_)));
+    /// registry.register("team".to_string(), validator, false, vec![], None).unwrap();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
+    /// registry.register("time".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(3).unwrap()
+    ///     .set(&registry, "team", vec![Value::from("a"), Value::from("b"), Value::from("a")]).unwrap()
+    ///     .set(&registry, "time", vec![Value::ScalarFloat(1.0), Value::ScalarFloat(2.0), Value::ScalarFloat(f64::NAN)]).unwrap();
+    ///
+    /// let summary = bulk
+    ///     .group_agg_with(&registry, "team", &[("time", Agg::Max)], NullPolicy::Propagate)
+    ///     .unwrap();
+    /// let times = summary.get(&registry, "time").unwrap();
+    /// assert_eq!(times.get_element(0).unwrap(), Value::Null);
+    /// assert_eq!(times.get_element(1).unwrap(), Value::ScalarFloat(2.0));
+    /// ```
+    pub fn group_agg_with(
+        &self,
+        registry: &Registry,
+        key: &str,
+        aggs: &[(&str, Agg)],
+        nulls: NullPolicy,
+    ) -> Result<Self> {
+        for (i, &(field, _)) in aggs.iter().enumerate() {
+            let repeated = aggs
+                .get(..i)
+                .is_some_and(|earlier| earlier.iter().any(|&(f, _)| f == field));
+            if field == key || repeated {
+                return Err(SoAKitError::FieldAlreadyExists(field.to_string()));
+            }
+        }
+
+        let views = self.partition_by(registry, key)?;
+        let mut keys = Vec::with_capacity(views.len());
+        let mut columns: Vec<Vec<Value>> = vec![Vec::with_capacity(views.len()); aggs.len()];
+        for view in &views {
+            keys.push(view.key().clone());
+            for (&(field, agg), column) in aggs.iter().zip(&mut columns) {
+                column.push(
+                    view.get_field(registry, field)?
+                        .aggregate_with(agg, nulls)?,
+                );
+            }
+        }
+
+        let mut summary = Bulk::new(views.len())?;
+        summary.set_chunks(registry, key, |start, end| chunk_of(&keys, start, end))?;
+        for (&(field, _), values) in aggs.iter().zip(&columns) {
+            summary.set_chunks(registry, field, |start, end| chunk_of(values, start, end))?;
+        }
+        Ok(summary)
+    }
+
+    /// Compute summary statistics for every field, for quick profiling.
+    ///
+    /// Every stored data field and every registered derived field whose
+    /// dependencies are available is summarized; see [`FieldSummary`].
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(BTreeMap)` mapping each field name to its summary.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if a stored field is not registered
+    /// - Any error returned while computing a derived field
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
+    /// registry.register("score".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(4).unwrap();
+    /// let bulk = bulk.set(&registry, "score", vec![
+    ///     Value::ScalarFloat(1.0),
+    ///     Value::ScalarFloat(f64::NAN),
+    ///     Value::ScalarFloat(3.0),
+    ///     Value::ScalarFloat(3.0),
+    /// ]).unwrap();
+    ///
+    /// let summary = &bulk.describe(&registry).unwrap()["score"];
+    /// assert_eq!(summary.count, 4);
+    /// assert_eq!(summary.nulls, 1);
+    /// assert_eq!(summary.max, Some(Value::ScalarFloat(3.0)));
+    /// assert_eq!(summary.unique, 2);
+    /// ```
+    pub fn describe(&self, registry: &Registry) -> Result<BTreeMap<String, FieldSummary>> {
+        self.describe_with(registry, NullPolicy::Skip)
+    }
+
+    /// Compute summary statistics for every field, with a policy for null elements.
+    ///
+    /// This is [`Bulk::describe`], except that under [`NullPolicy::Propagate`] the
+    /// statistics of a field with nulls are `None`; its counts are still reported.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::describe`].
+    pub fn describe_with(
+        &self,
+        registry: &Registry,
+        nulls: NullPolicy,
+    ) -> Result<BTreeMap<String, FieldSummary>> {
+        let mut summaries = BTreeMap::new();
+        for field in self.list_data_fields() {
+            let summary = FieldSummary::of(&self.get(registry, &field)?, nulls);
+            let _ = summaries.insert(field, summary);
+        }
+        for field in registry.list_fields() {
+            if self.derived_available(registry, &field) {
+                let summary = FieldSummary::of(&self.get(registry, &field)?, nulls);
+                let _ = summaries.insert(field, summary);
+            }
+        }
+        Ok(summaries)
+    }
+
+    /// Estimate the memory used by the bulk's columns, cache and IDs.
+    ///
+    /// See [`MemoryUsage`] for what the estimates count.
+    ///
+    /// # Returns
+    ///
+    /// A [`MemoryUsage`] with the bytes of each field, chunk and cached derived field.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(100).unwrap();
+    /// let bulk = bulk.set(&registry, "age", vec![Value::ScalarInt(30); 100]).unwrap();
+    ///
+    /// let usage = bulk.memory_usage();
+    /// assert_eq!(usage.fields["age"], 800);
+    /// assert_eq!(usage.chunks, vec![800]);
+    /// assert_eq!(usage.total, 800 + usage.ids);
+    /// ```
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let mut usage = MemoryUsage {
+            ids: size_of_val(self.meta.id.as_slice()),
+            ..MemoryUsage::default()
+        };
+        for chunk in &self.chunks {
+            let plain = chunk
+                .columns
+                .iter()
+                .map(|(field, column)| (field, column.heap_size()));
+            let interned = chunk
+                .interned
+                .iter()
+                .map(|(field, strings)| (field, strings.heap_size()));
+            let mut chunk_bytes: usize = 0;
+            for (field, bytes) in plain.chain(interned) {
+                let field_bytes = usage.fields.entry(field.clone()).or_insert(0);
+                *field_bytes = field_bytes.saturating_add(bytes);
+                chunk_bytes = chunk_bytes.saturating_add(bytes);
+            }
+            usage.chunks.push(chunk_bytes);
+        }
+        usage.cache = self
+            .cache
+            .borrow()
+            .iter()
+            .map(|(field, entry)| (field.clone(), entry.value.heap_size()))
+            .collect();
+
+        usage.total = usage
+            .fields
+            .values()
+            .chain(usage.cache.values())
+            .fold(usage.ids, |sum, &bytes| sum.saturating_add(bytes));
+        usage
+    }
+
+    /// Check every stored element against its field's current validator.
+    ///
+    /// Setting a field only validates its first element, and bulks deserialized
+    /// from older data or kept across registry changes are not validated at all.
+    /// This checks every element of every stored field and reports all failures.
+    /// Missing elements of sparse fields are not checked.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Vec<Violation>)` with every failing element, ordered by field
+    /// and then position; the vector is empty if the bulk is valid.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if a stored field is not registered
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+    /// let bulk = Bulk::new(3).unwrap();
+    /// let bulk = bulk.set(&registry, "age", vec![
+    ///     Value::ScalarInt(25),
+    ///     Value::ScalarInt(-4),
+    ///     Value::ScalarInt(35),
+    /// ]).unwrap();
+    /// assert!(bulk.validate_all(&registry).unwrap().is_empty());
+    ///
+    /// // A stricter registry rejects the negative age
+    /// let mut strict = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(a) if *a >= 0));
+    /// strict.register("age".to_string(), validator, false, vec![], None).unwrap();
+    /// let violations = bulk.validate_all(&strict).unwrap();
+    /// assert_eq!(violations.len(), 1);
+    /// assert_eq!(violations[0].index, 1);
+    /// assert_eq!(violations[0].value, Value::ScalarInt(-4));
+    /// ```
+    pub fn validate_all(&self, registry: &Registry) -> Result<Vec<Violation>> {
+        let bounds = self.all_chunk_bounds()?;
+        let mut violations = Vec::new();
+        for field in self.list_data_fields() {
+            if !registry.has_field(&field) {
+                return Err(SoAKitError::FieldNotFound(field));
+            }
+            for (chunk, &(start, _)) in self.chunks.iter().zip(&bounds) {
+                let Some(column) = chunk.column(&field) else {
+                    continue;
+                };
+                for (offset, value) in column.to_scalars().into_iter().enumerate() {
+                    if !value.is_null() && !registry.validate(&field, &value) {
+                        violations.push(Violation {
+                            field: field.clone(),
+                            index: start.saturating_add(offset),
+                            value,
+                        });
+                    }
+                }
+            }
+        }
+        Ok(violations)
+    }
+
+    /// List the required fields that have no data in the bulk.
+    ///
+    /// A required field (see [`Registry::set_required`]) is complete when every
+    /// chunk holds a column for it; every field of an empty bulk is complete.
+    /// Missing elements inside a column do not make it incomplete; use
+    /// [`Bulk::null_count_field`] to find them.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    ///
+    /// # Returns
+    ///
+    /// The names of the incomplete required fields, in sorted order; the vector is
+    /// empty if the bulk is complete.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// registry.register_int("age").unwrap();
+    /// registry.set_required("age", true).unwrap();
+    ///
+    /// let bulk = Bulk::new(2).unwrap();
+    /// assert_eq!(bulk.validate_complete(&registry), vec!["age"]);
+    ///
+    /// let bulk = bulk.set(&registry, "age", vec![Value::ScalarInt(1), Value::ScalarInt(2)]).unwrap();
+    /// assert!(bulk.validate_complete(&registry).is_empty());
+    /// ```
+    pub fn validate_complete(&self, registry: &Registry) -> Vec<String> {
+        registry
+            .required_fields()
+            .into_iter()
+            .filter(|field| {
+                // Chunks are allocated by the first stored field
+                let stored = !self.chunks.is_empty()
+                    && self.chunks.iter().all(|chunk| chunk.has_column(field));
+                self.meta.count != 0 && !stored
+            })
+            .collect()
+    }
+
+    /// Render the bulk as an aligned text table.
+    ///
+    /// The table has an `id` column followed by every stored data field and every
+    /// registered derived field whose dependencies are available. If the bulk has
+    /// more than `max_rows` elements, only the first and last rows are shown
+    /// (about half each) separated by a `...` row. A footer reports the full size.
+    ///
+    /// Numeric columns are right-aligned; all other columns are left-aligned.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `max_rows` - Maximum number of data rows to render
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(String)` with the rendered table.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if a stored field is not registered
+    /// - Any error returned while computing a derived field
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarString(_)));
+    /// registry.register("name".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(2).unwrap();
+    /// let bulk = bulk.set(&registry, "name", vec![
+    ///     Value::ScalarString("Alice".to_string()),
+    ///     Value::ScalarString("Bob".to_string()),
+    /// ]).unwrap();
+    ///
+    /// let table = bulk.to_table_string(&registry, 10).unwrap();
+    /// assert_eq!(table, "id | name \n---+------\n 0 | Alice\n 1 | Bob  \n[2 rows x 1 columns]");
+    /// ```
+    pub fn to_table_string(&self, registry: &Registry, max_rows: usize) -> Result<String> {
+        let mut headers = vec!["id".to_string()];
+        let mut columns = Vec::new();
+        for field in self.list_data_fields() {
+            columns.push(self.get(registry, &field)?);
+            headers.push(field);
+        }
+        for (field, meta) in registry.iter() {
+            if !meta.is_derived && !meta.is_virtual() {
+                continue;
+            }
+            match self.get(registry, field) {
+                Ok(value) => {
+                    columns.push(value);
+                    headers.push(field.to_string());
+                }
+                // Dependencies not present in this bulk: nothing to show
+                Err(SoAKitError::FieldNotFound(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        let mut right_align = vec![true];
+        right_align.extend(
+            columns
+                .iter()
+                .map(|c| matches!(c, Value::VectorInt(_) | Value::VectorFloat(_))),
+        );
+
+        // Pick which rows to show
+        let count = self.meta.count;
+        let (head, tail) = if count <= max_rows {
+            (count, 0)
+        } else {
+            let head = max_rows.div_ceil(2);
+            (head, max_rows.saturating_sub(head))
+        };
+        let shown = (0..head).chain(count.saturating_sub(tail)..count);
+
+        let mut rows: Vec<Vec<String>> = Vec::new();
+        for idx in shown {
+            let id = self.meta.id.get(idx).copied().unwrap_or(idx);
+            let mut row = vec![id.to_string()];
+            for column in &columns {
+                row.push(column_element(column, idx)?.to_string());
+            }
+            rows.push(row);
+        }
+        let truncated = head.saturating_add(tail) < count;
+        if truncated {
+            rows.insert(head, vec!["...".to_string(); headers.len()]);
+        }
+
+        // Column widths
+        let widths: Vec<usize> = headers
+            .iter()
+            .enumerate()
+            .map(|(col, header)| {
+                rows.iter()
+                    .filter_map(|row| row.get(col))
+                    .map(|cell| cell.chars().count())
+                    .chain(std::iter::once(header.chars().count()))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let render_row = |cells: &[String]| -> String {
+            cells
+                .iter()
+                .zip(widths.iter())
+                .zip(right_align.iter())
+                .map(|((cell, &width), &right)| {
+                    if right {
+                        format!("{:>width$}", cell, width = width)
+                    } else {
+                        format!("{:<width$}", cell, width = width)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" | ")
+        };
+
+        let mut lines = vec![render_row(&headers)];
+        lines.push(
+            widths
+                .iter()
+                .map(|&w| "-".repeat(w))
+                .collect::<Vec<_>>()
+                .join("-+-"),
+        );
+        for row in &rows {
+            lines.push(render_row(row));
+        }
+        lines.push(format!("[{} rows x {} columns]", count, columns.len()));
+
+        Ok(lines.join("\n"))
+    }
+}
+
+/// Extract the element at `idx` from a stored column value.
+///
+/// Unlike [`Value::get_element`], this also accepts matrix columns (one row per
+/// element), which is how per-element vector fields are stored.
+pub(crate) fn column_element(column: &Value, idx: usize) -> Result<Value> {
+    match column {
+        Value::Matrix(rows) => rows.get(idx).cloned().ok_or(SoAKitError::IndexOutOfBounds {
+            index: idx,
+            max: rows.len(),
+        }),
+        _ => column.get_element(idx),
+    }
+}
+
+/// Get the `k`-th of `bins` quantiles of sorted values, interpolating linearly.
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn quantile(sorted: &[f64], k: usize, bins: usize) -> f64 {
+    let position = (k as f64 / bins as f64) * sorted.len().saturating_sub(1) as f64;
+    let below = position.floor() as usize;
+    let fraction = position - position.floor();
+    match (sorted.get(below), sorted.get(below.saturating_add(1))) {
+        (Some(&low), Some(&high)) if fraction > 0.0 => low + (high - low) * fraction,
+        (Some(&low), _) => low,
+        _ => f64::NAN,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meta::DerivedFunc;
+    use crate::value::Value;
+
+    #[test]
+    fn test_new_bulk() {
+        let bulk = Bulk::new(5).unwrap();
+        assert_eq!(bulk.count(), 5);
+        assert_eq!(bulk.meta.id, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_new_bulk_zero_count() {
+        let result = Bulk::new(0);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_set_and_get() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("age".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let bulk = Bulk::new(3).unwrap();
+        let values = vec![
+            Value::ScalarInt(10),
+            Value::ScalarInt(20),
+            Value::ScalarInt(30),
+        ];
+        let bulk = bulk.set(&registry, "age", values).unwrap();
+
+        let result = bulk.get(&registry, "age").unwrap();
+        if let Value::VectorInt(v) = result {
+            assert_eq!(v, vec![10, 20, 30]);
+        } else {
+            panic!("Expected VectorInt");
+        }
+    }
+
+    #[test]
+    fn test_set_length_mismatch() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("age".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let bulk = Bulk::new(3).unwrap();
+        let values = vec![Value::ScalarInt(10), Value::ScalarInt(20)];
+        let result = bulk.set(&registry, "age", values);
+        assert!(result.is_err());
+        assert!(matches!(
+            result.unwrap_err(),
+            SoAKitError::LengthMismatch { .. }
+        ));
+    }
+
+    #[test]
+    fn test_set_all_value_types() {
+        let mut registry = Registry::new();
+
+        // Int field
+        let int_validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("age".to_string(), int_validator, false, vec![], None)
+            .unwrap();
+
+        // Float field
+        let float_validator = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
+        registry
+            .register("height".to_string(), float_validator, false, vec![], None)
+            .unwrap();
+
+        // Bool field
+        let bool_validator = Box::new(|v: &Value| matches!(v, Value::ScalarBool(_)));
+        registry
+            .register("active".to_string(), bool_validator, false, vec![], None)
+            .unwrap();
+
+        // String field
+        let str_validator = Box::new(|v: &Value| matches!(v, Value::ScalarString(_)));
+        registry
+            .register("name".to_string(), str_validator, false, vec![], None)
+            .unwrap();
+
+        let bulk = Bulk::new(2).unwrap();
+
+        let bulk = bulk
+            .set(
+                &registry,
+                "age",
+                vec![Value::ScalarInt(25), Value::ScalarInt(30)],
+            )
+            .unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "height",
+                vec![Value::ScalarFloat(1.75), Value::ScalarFloat(1.80)],
+            )
+            .unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "active",
+                vec![Value::ScalarBool(true), Value::ScalarBool(false)],
+            )
+            .unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "name",
+                vec![
+                    Value::ScalarString("Alice".to_string()),
+                    Value::ScalarString("Bob".to_string()),
+                ],
+            )
+            .unwrap();
+
+        // Verify all fields
+        if let Value::VectorInt(v) = bulk.get(&registry, "age").unwrap() {
+            assert_eq!(v, vec![25, 30]);
+        } else {
+            panic!("Expected VectorInt");
+        }
+
+        if let Value::VectorFloat(v) = bulk.get(&registry, "height").unwrap() {
+            assert_eq!(v, vec![1.75, 1.80]);
+        } else {
+            panic!("Expected VectorFloat");
+        }
+
+        if let Value::VectorBool(v) = bulk.get(&registry, "active").unwrap() {
+            assert_eq!(v, vec![true, false]);
+        } else {
+            panic!("Expected VectorBool");
+        }
+
+        if let Value::VectorString(v) = bulk.get(&registry, "name").unwrap() {
+            assert_eq!(v, vec!["Alice".to_string(), "Bob".to_string()]);
+        } else {
+            panic!("Expected VectorString");
+        }
+    }
+
+    #[test]
+    fn test_version_tracking() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("age".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let bulk = Bulk::new(3).unwrap();
+        assert_eq!(bulk.meta.versions.get("age"), None);
+
+        let bulk = bulk
+            .set(
+                &registry,
+                "age",
+                vec![
+                    Value::ScalarInt(10),
+                    Value::ScalarInt(20),
+                    Value::ScalarInt(30),
+                ],
+            )
+            .unwrap();
+        assert_eq!(bulk.meta.versions.get("age"), Some(&1));
+
+        let bulk = bulk
+            .set(
+                &registry,
+                "age",
+                vec![
+                    Value::ScalarInt(11),
+                    Value::ScalarInt(21),
+                    Value::ScalarInt(31),
+                ],
+            )
+            .unwrap();
+        assert_eq!(bulk.meta.versions.get("age"), Some(&2));
+
+        let bulk = bulk
+            .set(
+                &registry,
+                "age",
+                vec![
+                    Value::ScalarInt(12),
+                    Value::ScalarInt(22),
+                    Value::ScalarInt(32),
+                ],
+            )
+            .unwrap();
+        assert_eq!(bulk.meta.versions.get("age"), Some(&3));
+    }
+
+    #[test]
+    fn test_version_tracking_multiple_fields() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("a".to_string(), validator.clone(), false, vec![], None)
+            .unwrap();
+        registry
+            .register("b".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let bulk = Bulk::new(2).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "a",
+                vec![Value::ScalarInt(1), Value::ScalarInt(2)],
+            )
+            .unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "b",
+                vec![Value::ScalarInt(3), Value::ScalarInt(4)],
+            )
+            .unwrap();
+
+        assert_eq!(bulk.meta.versions.get("a"), Some(&1));
+        assert_eq!(bulk.meta.versions.get("b"), Some(&1));
+
+        let bulk = bulk
+            .set(
+                &registry,
+                "a",
+                vec![Value::ScalarInt(10), Value::ScalarInt(20)],
+            )
+            .unwrap();
+
+        assert_eq!(bulk.meta.versions.get("a"), Some(&2));
+        assert_eq!(bulk.meta.versions.get("b"), Some(&1));
+    }
+
+    #[test]
+    fn test_derived_field_caching() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+
+        registry
+            .register("a".to_string(), validator.clone(), false, vec![], None)
+            .unwrap();
+        registry
+            .register("b".to_string(), validator.clone(), false, vec![], None)
+            .unwrap();
+
+        let derived_func = Box::new(|args: &[Value]| {
+            if let (Value::VectorInt(a), Value::VectorInt(b)) = (&args[0], &args[1]) {
+                let sum: Vec<i64> = a.iter().zip(b.iter()).map(|(x, y)| x + y).collect();
+                Ok(Value::VectorInt(sum))
+            } else {
+                Err(SoAKitError::InvalidArgument(
+                    "Invalid arguments".to_string(),
+                ))
+            }
+        });
+        registry
+            .register(
+                "sum".to_string(),
+                validator,
+                true,
+                vec!["a".to_string(), "b".to_string()],
+                Some(derived_func),
+            )
+            .unwrap();
+
+        let bulk = Bulk::new(3).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "a",
+                vec![
+                    Value::ScalarInt(10),
+                    Value::ScalarInt(20),
+                    Value::ScalarInt(30),
+                ],
+            )
+            .unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "b",
+                vec![
+                    Value::ScalarInt(5),
+                    Value::ScalarInt(15),
+                    Value::ScalarInt(25),
+                ],
+            )
+            .unwrap();
+
+        // First get should compute
+        let sum1 = bulk.get(&registry, "sum").unwrap();
+        if let Value::VectorInt(v) = sum1 {
+            assert_eq!(v, vec![15, 35, 55]);
+        } else {
+            panic!("Expected VectorInt");
+        }
+
+        // Second get should use cache
+        let sum2 = bulk.get(&registry, "sum").unwrap();
+        if let Value::VectorInt(v) = sum2 {
+            assert_eq!(v, vec![15, 35, 55]);
+        } else {
+            panic!("Expected VectorInt");
+        }
+    }
+
+    #[test]
+    fn test_cache_invalidation() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+
+        registry
+            .register("a".to_string(), validator.clone(), false, vec![], None)
+            .unwrap();
+        registry
+            .register("b".to_string(), validator.clone(), false, vec![], None)
+            .unwrap();
+
+        let derived_func = Box::new(|args: &[Value]| {
+            if let (Value::VectorInt(a), Value::VectorInt(b)) = (&args[0], &args[1]) {
+                let sum: Vec<i64> = a.iter().zip(b.iter()).map(|(x, y)| x + y).collect();
+                Ok(Value::VectorInt(sum))
+            } else {
+                Err(SoAKitError::InvalidArgument(
+                    "Invalid arguments".to_string(),
+                ))
+            }
+        });
+        registry
+            .register(
+                "sum".to_string(),
+                validator,
+                true,
+                vec!["a".to_string(), "b".to_string()],
+                Some(derived_func),
+            )
+            .unwrap();
+
+        let bulk = Bulk::new(2).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "a",
+                vec![Value::ScalarInt(10), Value::ScalarInt(20)],
+            )
+            .unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "b",
+                vec![Value::ScalarInt(5), Value::ScalarInt(15)],
+            )
+            .unwrap();
+
+        // Get sum (should compute and cache)
+        let _sum1 = bulk.get(&registry, "sum").unwrap();
+
+        // Update dependency 'a'
+        let bulk = bulk
+            .set(
+                &registry,
+                "a",
+                vec![Value::ScalarInt(100), Value::ScalarInt(200)],
+            )
+            .unwrap();
+
+        // Get sum again (should recompute due to cache invalidation)
+        let sum2 = bulk.get(&registry, "sum").unwrap();
+        if let Value::VectorInt(v) = sum2 {
+            assert_eq!(v, vec![105, 215]);
+        } else {
+            panic!("Expected VectorInt");
+        }
+    }
+
+    #[test]
+    fn test_get_nonexistent_field() {
+        let registry = Registry::new();
+        let bulk = Bulk::new(3).unwrap();
+        let result = bulk.get(&registry, "nonexistent");
+        assert!(result.is_err());
+        assert!(matches!(result.unwrap_err(), SoAKitError::FieldNotFound(_)));
+    }
+
+    #[test]
+    fn test_set_nonexistent_field() {
+        let registry = Registry::new();
+        let bulk = Bulk::new(3).unwrap();
+        let values = vec![
+            Value::ScalarInt(10),
+            Value::ScalarInt(20),
+            Value::ScalarInt(30),
+        ];
+        let result = bulk.set(&registry, "nonexistent", values);
+        assert!(result.is_err());
+        assert!(matches!(result.unwrap_err(), SoAKitError::FieldNotFound(_)));
+    }
+
+    #[test]
+    fn test_set_validation_failure() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("age".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let bulk = Bulk::new(3).unwrap();
+        let values = vec![
+            Value::ScalarFloat(10.0),
+            Value::ScalarFloat(20.0),
+            Value::ScalarFloat(30.0),
+        ];
+        let result = bulk.set(&registry, "age", values);
+        assert!(result.is_err());
+        assert!(matches!(
+            result.unwrap_err(),
+            SoAKitError::ValidationFailed(_)
+        ));
+    }
+
+    #[test]
+    fn test_apply_operation() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("age".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let bulk = Bulk::new(5).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "age",
+                vec![
+                    Value::ScalarInt(10),
+                    Value::ScalarInt(20),
+                    Value::ScalarInt(30),
+                    Value::ScalarInt(40),
+                    Value::ScalarInt(50),
+                ],
+            )
+            .unwrap();
+
+        let mask = vec![true, false, true, false, true];
+        let new_bulk = bulk
+            .apply(&mask, |subset| {
+                let new_vals: Vec<Value> = subset
+                    .iter()
+                    .map(|v| {
+                        if let Value::ScalarInt(i) = v {
+                            Value::ScalarInt(i + 1)
+                        } else {
+                            v.clone()
+                        }
+                    })
+                    .collect();
+                Ok(new_vals)
+            })
+            .unwrap();
+
+        if let Value::VectorInt(v) = new_bulk.get(&registry, "age").unwrap() {
+            assert_eq!(v, vec![11, 20, 31, 40, 51]);
+        } else {
+            panic!("Expected VectorInt");
+        }
+    }
+
+    #[test]
+    fn test_apply_empty_mask() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("age".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let bulk = Bulk::new(3).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "age",
+                vec![
+                    Value::ScalarInt(10),
+                    Value::ScalarInt(20),
+                    Value::ScalarInt(30),
+                ],
+            )
+            .unwrap();
+
+        // Empty mask should be treated as all true
+        let new_bulk = bulk
+            .apply(&[], |subset| {
+                let new_vals: Vec<Value> = subset
+                    .iter()
+                    .map(|v| {
+                        if let Value::ScalarInt(i) = v {
+                            Value::ScalarInt(i + 1)
+                        } else {
+                            v.clone()
+                        }
+                    })
+                    .collect();
+                Ok(new_vals)
+            })
+            .unwrap();
+
+        if let Value::VectorInt(v) = new_bulk.get(&registry, "age").unwrap() {
+            assert_eq!(v, vec![11, 21, 31]);
+        } else {
+            panic!("Expected VectorInt");
+        }
+    }
+
+    #[test]
+    fn test_apply_mask_length_mismatch() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("age".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let bulk = Bulk::new(3).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "age",
+                vec![
+                    Value::ScalarInt(10),
+                    Value::ScalarInt(20),
+                    Value::ScalarInt(30),
+                ],
+            )
+            .unwrap();
+
+        let mask = vec![true, false]; // Wrong length
+        let result = bulk.apply(&mask, |subset| Ok(subset.to_vec()));
+        assert!(result.is_err());
+        assert!(matches!(
+            result.unwrap_err(),
+            SoAKitError::LengthMismatch { .. }
+        ));
+    }
+
+    #[test]
+    fn test_partition_by_int() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("category".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let bulk = Bulk::new(6).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "category",
+                vec![
+                    Value::ScalarInt(1),
+                    Value::ScalarInt(2),
+                    Value::ScalarInt(1),
+                    Value::ScalarInt(3),
+                    Value::ScalarInt(2),
+                    Value::ScalarInt(1),
+                ],
+            )
+            .unwrap();
+
+        let views = bulk.partition_by(&registry, "category").unwrap();
+        assert_eq!(views.len(), 3);
+
+        // Find view for category 1
+        let view_1 = views
+            .iter()
+            .find(|v| {
+                if let Value::ScalarInt(i) = v.key() {
+                    *i == 1
+                } else {
+                    false
+                }
+            })
+            .unwrap();
+        assert_eq!(view_1.count(), 3);
+    }
+
+    #[test]
+    fn test_partition_by_string() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarString(_)));
+        registry
+            .register("category".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let bulk = Bulk::new(4).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "category",
+                vec![
+                    Value::ScalarString("A".to_string()),
+                    Value::ScalarString("B".to_string()),
+                    Value::ScalarString("A".to_string()),
+                    Value::ScalarString("C".to_string()),
+                ],
+            )
+            .unwrap();
+
+        let views = bulk.partition_by(&registry, "category").unwrap();
+        assert_eq!(views.len(), 3);
+    }
+
+    #[test]
+    fn test_partition_by_float() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
+        registry
+            .register("value".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let bulk = Bulk::new(4).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "value",
+                vec![
+                    Value::ScalarFloat(1.0),
+                    Value::ScalarFloat(2.0),
+                    Value::ScalarFloat(1.0),
+                    Value::ScalarFloat(3.0),
+                ],
+            )
+            .unwrap();
+
+        let views = bulk.partition_by(&registry, "value").unwrap();
+        assert_eq!(views.len(), 3);
+    }
+
+    #[test]
+    fn test_partition_by_float_nan_and_signed_zero() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
+        registry
+            .register("value".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let bulk = Bulk::new(5).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "value",
+                vec![
+                    Value::ScalarFloat(f64::NAN),
+                    Value::ScalarFloat(0.0),
+                    Value::ScalarFloat(-0.0),
+                    Value::ScalarFloat(f64::NAN),
+                    Value::ScalarFloat(-1.0),
+                ],
+            )
+            .unwrap();
+
+        let views = bulk.partition_by(&registry, "value").unwrap();
+        assert_eq!(views.len(), 3);
+        assert_eq!(views[0].key(), &Value::ScalarFloat(-1.0));
+        assert_eq!(views[1].mask(), &[false, true, true, false, false]);
+        assert_eq!(views[2].mask(), &[true, false, false, true, false]);
+    }
+
+    #[test]
+    fn test_partition_by_bool() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarBool(_)));
+        registry
+            .register("flag".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let bulk = Bulk::new(4).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "flag",
+                vec![
+                    Value::ScalarBool(true),
+                    Value::ScalarBool(false),
+                    Value::ScalarBool(true),
+                    Value::ScalarBool(false),
+                ],
+            )
+            .unwrap();
+
+        let views = bulk.partition_by(&registry, "flag").unwrap();
+        assert_eq!(views.len(), 2);
+    }
+
+    #[test]
+    fn test_partition_nonexistent_field() {
+        let registry = Registry::new();
+        let bulk = Bulk::new(3).unwrap();
+        let result = bulk.partition_by(&registry, "nonexistent");
+        assert!(result.is_err());
+        assert!(matches!(result.unwrap_err(), SoAKitError::FieldNotFound(_)));
+    }
+
+    #[test]
+    fn test_list_data_fields() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("age".to_string(), validator.clone(), false, vec![], None)
+            .unwrap();
+        registry
+            .register("height".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let bulk = Bulk::new(3).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "age",
+                vec![
+                    Value::ScalarInt(10),
+                    Value::ScalarInt(20),
+                    Value::ScalarInt(30),
+                ],
+            )
+            .unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "height",
+                vec![
+                    Value::ScalarInt(100),
+                    Value::ScalarInt(200),
+                    Value::ScalarInt(300),
+                ],
+            )
+            .unwrap();
+
+        let fields = bulk.list_data_fields();
+        assert_eq!(fields.len(), 2);
+        assert!(fields.contains(&"age".to_string()));
+        assert!(fields.contains(&"height".to_string()));
+    }
+
+    #[test]
+    fn test_at_proxy() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("age".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let bulk = Bulk::new(3).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "age",
+                vec![
+                    Value::ScalarInt(10),
+                    Value::ScalarInt(20),
+                    Value::ScalarInt(30),
+                ],
+            )
+            .unwrap();
+
+        let proxy = bulk.at(1).unwrap();
+        assert_eq!(proxy.index(), 1);
+    }
+
+    #[test]
+    fn test_at_out_of_bounds() {
+        let bulk = Bulk::new(3).unwrap();
+        let result = bulk.at(10);
+        assert!(result.is_err());
+        assert!(matches!(
+            result.unwrap_err(),
+            SoAKitError::IndexOutOfBounds { .. }
+        ));
+    }
+
+    #[test]
+    fn test_single_element_bulk() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("age".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let bulk = Bulk::new(1).unwrap();
+        let bulk = bulk
+            .set(&registry, "age", vec![Value::ScalarInt(42)])
+            .unwrap();
+
+        if let Value::VectorInt(v) = bulk.get(&registry, "age").unwrap() {
+            assert_eq!(v, vec![42]);
+        } else {
+            panic!("Expected VectorInt");
+        }
+    }
+
+    #[test]
+    fn test_large_bulk() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("value".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let count = 1000;
+        let bulk = Bulk::new(count).unwrap();
+        let values: Vec<Value> = (0..count).map(|i| Value::ScalarInt(i as i64)).collect();
+        let bulk = bulk.set(&registry, "value", values).unwrap();
+
+        assert_eq!(bulk.count(), count);
+        if let Value::VectorInt(v) = bulk.get(&registry, "value").unwrap() {
+            assert_eq!(v.len(), count);
+            assert_eq!(v[0], 0);
+            assert_eq!(v[count - 1], (count - 1) as i64);
+        } else {
+            panic!("Expected VectorInt");
+        }
+    }
+
+    #[test]
+    fn test_meta_new() {
+        let meta = Meta::new(5).unwrap();
+        assert_eq!(meta.count, 5);
+        assert_eq!(meta.id, vec![0, 1, 2, 3, 4]);
+        assert!(meta.versions.is_empty());
+    }
+
+    #[test]
+    fn test_meta_new_zero() {
+        let result = Meta::new(0);
+        assert!(result.is_err());
+        assert!(matches!(
+            result.unwrap_err(),
+            SoAKitError::InvalidArgument(_)
+        ));
+    }
+
+    #[test]
+    fn test_bulk_clone() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("age".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let bulk1 = Bulk::new(3).unwrap();
+        let bulk1 = bulk1
+            .set(
+                &registry,
+                "age",
+                vec![
+                    Value::ScalarInt(10),
+                    Value::ScalarInt(20),
+                    Value::ScalarInt(30),
+                ],
+            )
+            .unwrap();
+
+        let bulk2 = bulk1.clone();
+        assert_eq!(bulk1.count(), bulk2.count());
+        assert_eq!(
+            bulk1.get(&registry, "age").unwrap(),
+            bulk2.get(&registry, "age").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_to_table_string_truncates() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("score".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let bulk = Bulk::new(10).unwrap();
+        let values = (0..10).map(|i| Value::ScalarInt(i * 100)).collect();
+        let bulk = bulk.set(&registry, "score", values).unwrap();
+
+        let table = bulk.to_table_string(&registry, 4).unwrap();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[0], " id | score");
+        assert_eq!(lines[1], "----+------");
+        assert_eq!(lines[2], "  0 |     0");
+        assert_eq!(lines[3], "  1 |   100");
+        assert_eq!(lines[4], "... |   ...");
+        assert_eq!(lines[5], "  8 |   800");
+        assert_eq!(lines[6], "  9 |   900");
+        assert_eq!(lines[7], "[10 rows x 1 columns]");
+    }
+
+    #[test]
+    fn test_to_table_string_includes_derived_fields() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("a".to_string(), validator.clone(), false, vec![], None)
+            .unwrap();
+        registry
+            .register(
+                "double".to_string(),
+                validator.clone(),
+                true,
+                vec!["a".to_string()],
+                Some(Box::new(|args: &[Value]| &args[0] * &Value::ScalarInt(2))),
+            )
+            .unwrap();
+        registry
+            .register(
+                "missing_dep".to_string(),
+                validator,
+                true,
+                vec!["nope".to_string()],
+                Some(Box::new(|args: &[Value]| Ok(args[0].clone()))),
+            )
+            .unwrap();
+
+        let bulk = Bulk::new(2).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "a",
+                vec![Value::ScalarInt(1), Value::ScalarInt(2)],
+            )
+            .unwrap();
+
+        let table = bulk.to_table_string(&registry, 10).unwrap();
+        assert!(table.starts_with("id | a | double"));
+        assert!(table.contains(" 1 | 2 |      4"));
+        assert!(!table.contains("missing_dep"));
+    }
+
+    #[test]
+    fn test_tensor_field_across_chunks_and_serialization() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| v.is_tensor() && v.shape() == vec![2, 2]);
+        registry
+            .register("pose".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let count = CHUNK_SIZE + 3;
+        let values: Vec<Value> = (0..count)
+            .map(|i| {
+                let x = i as f64;
+                Value::tensor(vec![x, x + 1.0, x + 2.0, x + 3.0], vec![2, 2]).unwrap()
+            })
+            .collect();
+        let bulk = Bulk::new(count).unwrap();
+        let bulk = bulk.set(&registry, "pose", values.clone()).unwrap();
+
+        let all = bulk.get(&registry, "pose").unwrap();
+        assert_eq!(all.shape(), vec![count, 2, 2]);
+        assert_eq!(
+            all.get_element(CHUNK_SIZE + 1).unwrap(),
+            values[CHUNK_SIZE + 1]
+        );
+
+        let proxy = bulk.at(CHUNK_SIZE).unwrap();
+        assert_eq!(
+            proxy.get_field(&registry, "pose").unwrap(),
+            values[CHUNK_SIZE]
+        );
+
+        let binary = bulk.to_records_binary().unwrap();
+        let restored = Bulk::from_records_binary(&binary, &registry).unwrap();
+        assert_eq!(restored.get(&registry, "pose").unwrap(), all);
+
+        let mut mask = vec![false; count];
+        mask[1] = true;
+        let view = crate::view::View::new(Value::ScalarInt(0), mask, Arc::new(bulk)).unwrap();
+        let selected = view.get_field(&registry, "pose").unwrap();
+        assert_eq!(selected.shape(), vec![1, 2, 2]);
+        assert_eq!(selected.get_element(0).unwrap(), values[1]);
+    }
+
+    #[test]
+    fn test_set_broadcast() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarBool(_)));
+        registry
+            .register("flag".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let count = CHUNK_SIZE * 2 + 5;
+        let bulk = Bulk::new(count).unwrap();
+        let bulk = bulk
+            .set_broadcast(&registry, "flag", Value::ScalarBool(true))
+            .unwrap();
+        assert_eq!(bulk.chunks.len(), 3);
+        assert_eq!(bulk.meta.versions.get("flag"), Some(&1));
+        assert_eq!(
+            bulk.get(&registry, "flag").unwrap(),
+            Value::VectorBool(vec![true; count])
+        );
+
+        let bulk = bulk
+            .set_broadcast(&registry, "flag", Value::ScalarBool(false))
+            .unwrap();
+        assert_eq!(bulk.meta.versions.get("flag"), Some(&2));
+        assert_eq!(
+            bulk.get(&registry, "flag").unwrap(),
+            Value::VectorBool(vec![false; count])
+        );
+
+        assert!(matches!(
+            bulk.set_broadcast(&registry, "flag", Value::ScalarInt(1)),
+            Err(SoAKitError::ValidationFailed(_))
+        ));
+        assert!(matches!(
+            bulk.set_broadcast(&registry, "missing", Value::ScalarBool(true)),
+            Err(SoAKitError::FieldNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_records_json_tagged_preserves_variants() {
+        let mut registry = Registry::new();
+        let float_validator = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
+        let vec_validator = Box::new(|v: &Value| matches!(v, Value::VectorFloat(_)));
+        let tensor_validator = Box::new(|v: &Value| v.is_tensor());
+        registry
+            .register("weight".to_string(), float_validator, false, vec![], None)
+            .unwrap();
+        registry
+            .register("coords".to_string(), vec_validator, false, vec![], None)
+            .unwrap();
+        registry
+            .register("pose".to_string(), tensor_validator, false, vec![], None)
+            .unwrap();
+
+        let bulk = Bulk::new(2).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "weight",
+                vec![Value::ScalarFloat(1.0), Value::ScalarFloat(2.5)],
+            )
+            .unwrap()
+            .set(
+                &registry,
+                "coords",
+                vec![
+                    Value::VectorFloat(vec![0.0, 1.0]),
+                    Value::VectorFloat(vec![2.0, 3.0]),
+                ],
+            )
+            .unwrap()
+            .set(
+                &registry,
+                "pose",
+                vec![
+                    Value::tensor(vec![1.0, 0.0], vec![2]).unwrap(),
+                    Value::tensor(vec![0.0, 1.0], vec![2]).unwrap(),
+                ],
+            )
+            .unwrap();
+
+        // The untagged format cannot tell a tensor from nested vectors
+        let untagged = bulk.to_records_json().unwrap();
+        assert!(Bulk::from_records_json(&untagged, &registry).is_err());
+
+        let tagged = bulk.to_records_json_tagged().unwrap();
+        let restored = Bulk::from_records_json_tagged(&tagged, &registry).unwrap();
+        for field in ["weight", "coords", "pose"] {
+            assert_eq!(
+                restored.get(&registry, field).unwrap(),
+                bulk.get(&registry, field).unwrap()
+            );
+        }
+
+        assert!(Bulk::from_records_json_tagged(&untagged, &registry).is_err());
+        assert!(Bulk::from_records_json_tagged("{}", &registry).is_err());
+        assert!(Bulk::from_records_json_tagged("[1]", &registry).is_err());
+    }
+
+    #[test]
+    fn test_intern_field_is_transparent() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarString(_)));
+        registry
+            .register("category".to_string(), validator, false, vec![], None)
+            .unwrap();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("score".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let count = CHUNK_SIZE + 3;
+        let categories: Vec<Value> = (0..count)
+            .map(|i| Value::from(if i % 3 == 0 { "A" } else { "B" }))
+            .collect();
+        let bulk = Bulk::new(count).unwrap();
+        let bulk = bulk.set(&registry, "category", categories).unwrap();
+        let bulk = bulk
+            .set_broadcast(&registry, "score", Value::ScalarInt(1))
+            .unwrap();
+        let plain = bulk.get(&registry, "category").unwrap();
+
+        let bulk = bulk.intern_field("category").unwrap();
+        assert!(bulk.is_interned("category"));
+        assert!(!bulk.is_interned("score"));
+        assert_eq!(bulk.get(&registry, "category").unwrap(), plain);
+        assert_eq!(bulk.list_data_fields(), vec!["category", "score"]);
+        assert_eq!(bulk.partition_by(&registry, "category").unwrap().len(), 2);
+        assert_eq!(
+            bulk.at(3)
+                .unwrap()
+                .get_field(&registry, "category")
+                .unwrap(),
+            Value::from("A")
+        );
+
+        let from_json = Bulk::from_json(&bulk.to_json().unwrap()).unwrap();
+        assert!(from_json.is_interned("category"));
+        assert_eq!(from_json.get(&registry, "category").unwrap(), plain);
+        let from_binary = Bulk::from_binary(&bulk.to_binary().unwrap()).unwrap();
+        assert!(from_binary.is_interned("category"));
+        assert_eq!(from_binary.get(&registry, "category").unwrap(), plain);
+
+        assert!(matches!(
+            bulk.intern_field("score"),
+            Err(SoAKitError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            bulk.intern_field("missing"),
+            Err(SoAKitError::FieldNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_interned_field_stays_interned_after_set() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarString(_)));
+        registry
+            .register("tag".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let bulk = Bulk::new(2).unwrap();
+        let bulk = bulk
+            .set_broadcast(&registry, "tag", Value::from("x"))
+            .unwrap();
+        let bulk = bulk.intern_field("tag").unwrap();
+        let bulk = bulk
+            .set(&registry, "tag", vec![Value::from("y"), Value::from("z")])
+            .unwrap();
+        assert!(bulk.is_interned("tag"));
+        assert_eq!(
+            bulk.get(&registry, "tag").unwrap(),
+            Value::from(vec!["y", "z"])
+        );
+
+        let bulk = bulk
+            .apply(&[true, false], |values| {
+                Ok(values.iter().map(|_| Value::from("w")).collect())
+            })
+            .unwrap();
+        assert!(bulk.is_interned("tag"));
+        assert_eq!(
+            bulk.get(&registry, "tag").unwrap(),
+            Value::from(vec!["w", "z"])
+        );
+    }
+
+    #[test]
+    fn test_sparse_field_across_chunks() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_) | Value::Null));
+        registry
+            .register("score".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        // The first chunk is dense, the second has a missing element
+        let count = CHUNK_SIZE + 2;
+        let mut values: Vec<Value> = (0..count).map(|_| Value::ScalarInt(1)).collect();
+        values[CHUNK_SIZE + 1] = Value::Null;
+        let bulk = Bulk::new(count).unwrap();
+        let bulk = bulk.set(&registry, "score", values).unwrap();
+
+        let column = bulk.get(&registry, "score").unwrap();
+        assert!(column.is_sparse());
+        assert_eq!(column.len(), count);
+        assert_eq!(
+            column.count_nonnull().unwrap(),
+            Value::ScalarInt(CHUNK_SIZE as i64 + 1)
+        );
+        assert_eq!(
+            bulk.at(CHUNK_SIZE + 1)
+                .unwrap()
+                .get_field(&registry, "score")
+                .unwrap(),
+            Value::Null
+        );
+
+        // Fill the missing element in place
+        let mask: Vec<bool> = (0..count).map(|i| i == CHUNK_SIZE + 1).collect();
+        let filled = bulk
+            .apply(&mask, |values| Ok(vec![Value::ScalarInt(2); values.len()]))
+            .unwrap();
+        assert!(!filled.get(&registry, "score").unwrap().is_sparse());
+
+        let views = bulk.partition_by(&registry, "score").unwrap();
+        assert_eq!(views.len(), 2);
+        let missing = views.iter().find(|v| v.key().is_null()).unwrap();
+        assert_eq!(
+            missing.get_field(&registry, "score").unwrap(),
+            Value::VectorOptionInt {
+                len: 1,
+                entries: vec![]
+            }
+        );
+
+        let from_json =
+            Bulk::from_records_json(&bulk.to_records_json().unwrap(), &registry).unwrap();
+        assert_eq!(from_json.get(&registry, "score").unwrap(), column);
+        let from_toml =
+            Bulk::from_records_toml(&bulk.to_records_toml().unwrap(), &registry).unwrap();
+        assert_eq!(from_toml.get(&registry, "score").unwrap(), column);
+        let from_binary = Bulk::from_binary(&bulk.to_binary().unwrap()).unwrap();
+        assert_eq!(from_binary.get(&registry, "score").unwrap(), column);
+    }
+
+    #[test]
+    fn test_json_field_round_trips() {
+        let mut registry = Registry::new();
+        let validator = crate::json::schema_validator(
+            serde_json::json!({"type": "object", "required": ["kind"]}),
+        );
+        registry
+            .register("payload".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let values: Vec<Value> = (0..CHUNK_SIZE + 2)
+            .map(|i| Value::ScalarJson(serde_json::json!({"kind": i % 2, "extra": [i]})))
+            .collect();
+        let bulk = Bulk::new(values.len()).unwrap();
+        let bulk = bulk.set(&registry, "payload", values.clone()).unwrap();
+        let column = bulk.get(&registry, "payload").unwrap();
+        assert_eq!(column, Value::from_scalars(values).unwrap());
+        assert_eq!(
+            bulk.at(CHUNK_SIZE + 1)
+                .unwrap()
+                .get_field(&registry, "payload")
+                .unwrap(),
+            Value::ScalarJson(serde_json::json!({"kind": 1, "extra": [CHUNK_SIZE + 1]}))
+        );
+        assert_eq!(
+            bulk.partition_by(&registry, "payload").unwrap().len(),
+            CHUNK_SIZE + 2
+        );
+
+        let from_json =
+            Bulk::from_records_json(&bulk.to_records_json().unwrap(), &registry).unwrap();
+        assert_eq!(from_json.get(&registry, "payload").unwrap(), column);
+        let from_tagged =
+            Bulk::from_records_json_tagged(&bulk.to_records_json_tagged().unwrap(), &registry)
+                .unwrap();
+        assert_eq!(from_tagged.get(&registry, "payload").unwrap(), column);
+        let from_binary = Bulk::from_binary(&bulk.to_binary().unwrap()).unwrap();
+        assert_eq!(from_binary.get(&registry, "payload").unwrap(), column);
+    }
+
+    #[test]
+    fn test_set_mut_and_apply_mut_match_immutable_updates() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("age".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let count = CHUNK_SIZE + 3;
+        let values: Vec<Value> = (0..count).map(|i| Value::ScalarInt(i as i64)).collect();
+        let expected = Bulk::new(count)
+            .unwrap()
+            .set(&registry, "age", values.clone())
+            .unwrap();
+
+        let mut bulk = Bulk::new(count).unwrap();
+        bulk.set_mut(&registry, "age", values).unwrap();
+        assert_eq!(bulk.chunks.len(), 2);
+        assert_eq!(
+            bulk.get(&registry, "age").unwrap(),
+            expected.get(&registry, "age").unwrap()
+        );
+        assert_eq!(bulk.meta.versions.get("age"), Some(&1));
+
+        // A failed update leaves the bulk untouched
+        let err = bulk.set_mut(&registry, "age", vec![Value::ScalarInt(0)]);
+        assert!(matches!(err, Err(SoAKitError::LengthMismatch { .. })));
+        let err = bulk.apply_mut(&[], |subset| Ok(subset[1..].to_vec()));
+        assert!(matches!(err, Err(SoAKitError::LengthMismatch { .. })));
+        assert_eq!(
+            bulk.get(&registry, "age").unwrap(),
+            expected.get(&registry, "age").unwrap()
+        );
+        assert_eq!(bulk.meta.versions.get("age"), Some(&1));
+
+        let double = |subset: &[Value]| {
+            Ok(subset
+                .iter()
+                .map(|v| match v {
+                    Value::ScalarInt(i) => Value::ScalarInt(i * 2),
+                    other => other.clone(),
+                })
+                .collect())
+        };
+        let mask: Vec<bool> = (0..count).map(|i| i % 2 == 0).collect();
+        let expected = expected.apply(&mask, double).unwrap();
+        bulk.apply_mut(&mask, double).unwrap();
+        assert_eq!(
+            bulk.get(&registry, "age").unwrap(),
+            expected.get(&registry, "age").unwrap()
+        );
+        assert_eq!(bulk.meta.versions.get("age"), Some(&2));
+    }
+
+    #[test]
+    fn test_updates_share_untouched_columns() {
+        let mut registry = Registry::new();
+        for name in ["a", "b"] {
+            let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+            registry
+                .register(name.to_string(), validator, false, vec![], None)
+                .unwrap();
+        }
+
+        let count = CHUNK_SIZE + 1;
+        let ones = vec![Value::ScalarInt(1); count];
+        let bulk = Bulk::new(count).unwrap();
+        let bulk = bulk.set(&registry, "a", ones.clone()).unwrap();
+        let bulk = bulk.set(&registry, "b", ones).unwrap();
+
+        let updated = bulk
+            .set_broadcast(&registry, "b", Value::ScalarInt(2))
+            .unwrap();
+        for (old, new) in bulk.chunks.iter().zip(&updated.chunks) {
+            assert!(Arc::ptr_eq(&old.columns["a"], &new.columns["a"]));
+            assert!(!Arc::ptr_eq(&old.columns["b"], &new.columns["b"]));
+        }
+        assert_eq!(
+            bulk.get(&registry, "b").unwrap(),
+            Value::VectorInt(vec![1; count])
+        );
+        assert_eq!(
+            updated.get(&registry, "b").unwrap(),
+            Value::VectorInt(vec![2; count])
+        );
+
+        let copy = bulk.clone();
+        assert!(Arc::ptr_eq(
+            &bulk.chunks[1].columns["b"],
+            &copy.chunks[1].columns["b"]
+        ));
+    }
+
+    #[test]
+    fn test_extend_rows_grows_chunks() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("a".to_string(), validator, false, vec![], None)
+            .unwrap();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarString(_) | Value::Null));
+        registry
+            .register("name".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let count = CHUNK_SIZE - 1;
+        let mut bulk = Bulk::new(count)
+            .unwrap()
+            .set_broadcast(&registry, "name", Value::ScalarString("x".to_string()))
+            .unwrap();
+        bulk.set_mut(
+            &registry,
+            "a",
+            (0..count).map(|i| Value::ScalarInt(i as i64)).collect(),
+        )
+        .unwrap();
+
+        let records: Vec<_> = (count..count + CHUNK_SIZE + 2)
+            .map(|i| BTreeMap::from([("a".to_string(), Value::ScalarInt(i as i64))]))
+            .collect();
+        bulk.extend_rows(&registry, records).unwrap();
+
+        let total = count + CHUNK_SIZE + 2;
+        assert_eq!(bulk.count(), total);
+        assert_eq!(bulk.meta.id, (0..total).collect::<Vec<_>>());
+        assert_eq!(
+            bulk.chunks.iter().map(|c| c.len).collect::<Vec<_>>(),
+            vec![CHUNK_SIZE, CHUNK_SIZE, 1]
+        );
+        assert_eq!(
+            bulk.get(&registry, "a").unwrap(),
+            Value::VectorInt((0..total as i64).collect())
+        );
+        let names = bulk.get(&registry, "name").unwrap();
+        assert!(names.is_sparse());
+        assert_eq!(names.len(), total);
+        assert_eq!(bulk.meta.versions.get("a"), Some(&2));
+
+        // Invalid records leave the bulk unchanged
+        let bad = BTreeMap::from([("a".to_string(), Value::ScalarFloat(1.0))]);
+        assert!(matches!(
+            bulk.push_row(&registry, bad),
+            Err(SoAKitError::ValidationFailed(_))
+        ));
+        let unknown = BTreeMap::from([
+            ("a".to_string(), Value::ScalarInt(1)),
+            ("b".to_string(), Value::ScalarInt(1)),
+        ]);
+        assert!(bulk.push_row(&registry, unknown).is_err());
+        assert!(bulk.push_row(&registry, BTreeMap::new()).is_err());
+        assert_eq!(bulk.count(), total);
+        assert_eq!(bulk.get(&registry, "a").unwrap().len(), total);
+    }
+
+    #[test]
+    fn test_remove_rows_rebuilds_chunks() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("a".to_string(), validator, false, vec![], None)
+            .unwrap();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarString(_)));
+        registry
+            .register("tag".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let count = CHUNK_SIZE + 10;
+        let bulk = Bulk::new(count).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "a",
+                (0..count).map(|i| Value::ScalarInt(i as i64)).collect(),
+            )
+            .unwrap();
+        let bulk = bulk
+            .set_broadcast(&registry, "tag", Value::ScalarString("t".to_string()))
+            .unwrap()
+            .intern_field("tag")
+            .unwrap();
+
+        // Drop every even element
+        let mask: Vec<bool> = (0..count).map(|i| i % 2 == 0).collect();
+        let odd = bulk.remove_where(&mask).unwrap();
+        let kept: Vec<usize> = (0..count).filter(|i| i % 2 == 1).collect();
+        assert_eq!(odd.count(), kept.len());
+        assert_eq!(odd.meta.id, kept);
+        assert_eq!(odd.chunks.len(), 1);
+        assert_eq!(odd.meta.versions, bulk.meta.versions);
+        assert!(odd.is_interned("tag"));
+        assert_eq!(
+            odd.get(&registry, "a").unwrap(),
+            Value::VectorInt(kept.iter().map(|&i| i as i64).collect())
+        );
+
+        let fewer = bulk.remove_indices(&[0, CHUNK_SIZE, 0]).unwrap();
+        assert_eq!(fewer.count(), count - 2);
+        assert_eq!(
+            fewer.get(&registry, "a").unwrap().get_element(0).unwrap(),
+            Value::ScalarInt(1)
+        );
+
+        assert!(matches!(
+            bulk.remove_indices(&[count]),
+            Err(SoAKitError::IndexOutOfBounds { .. })
+        ));
+        assert!(matches!(
+            bulk.remove_where(&[true]),
+            Err(SoAKitError::LengthMismatch { .. })
+        ));
+        assert!(bulk.remove_where(&vec![true; count]).is_err());
+    }
+
+    #[test]
+    fn test_drop_field_invalidates_dependents() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("a".to_string(), validator, false, vec![], None)
+            .unwrap();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarString(_)));
+        registry
+            .register("tag".to_string(), validator, false, vec![], None)
+            .unwrap();
+        let derived = Box::new(|args: &[Value]| Ok(args[0].clone()));
+        registry
+            .register(
+                "copy".to_string(),
+                Box::new(|_: &Value| true),
+                true,
+                vec!["a".to_string()],
+                Some(derived),
+            )
+            .unwrap();
+
+        let bulk = Bulk::new(3).unwrap();
+        let bulk = bulk
+            .set_broadcast(&registry, "a", Value::ScalarInt(1))
+            .unwrap();
+        let bulk = bulk
+            .set_broadcast(&registry, "tag", Value::ScalarString("t".to_string()))
+            .unwrap()
+            .intern_field("tag")
+            .unwrap();
+        let _ = bulk.get(&registry, "copy").unwrap();
+        assert!(bulk.cache.borrow().contains_key("copy"));
+
+        let dropped = bulk.drop_field(&registry, "a").unwrap();
+        assert_eq!(dropped.list_data_fields(), vec!["tag".to_string()]);
+        assert!(!dropped.meta.versions.contains_key("a"));
+        assert!(!dropped.cache.borrow().contains_key("copy"));
+        assert!(dropped.get(&registry, "copy").is_err());
+        assert!(bulk.get(&registry, "a").is_ok());
+
+        let dropped = dropped.drop_field(&registry, "tag").unwrap();
+        assert!(dropped.list_data_fields().is_empty());
+        assert!(matches!(
+            dropped.drop_field(&registry, "tag"),
+            Err(SoAKitError::FieldNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_rename_field_moves_data_and_cache() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("a".to_string(), validator, false, vec![], None)
+            .unwrap();
+        let derived = Box::new(|args: &[Value]| Ok(args[0].clone()));
+        registry
+            .register(
+                "copy".to_string(),
+                Box::new(|_: &Value| true),
+                true,
+                vec!["a".to_string()],
+                Some(derived),
+            )
+            .unwrap();
+
+        let bulk = Bulk::new(CHUNK_SIZE + 1).unwrap();
+        let bulk = bulk
+            .set_broadcast(&registry, "a", Value::ScalarInt(7))
+            .unwrap();
+        let expected = bulk.get(&registry, "copy").unwrap();
+
+        registry.rename_field("a", "b").unwrap();
+        registry.rename_field("copy", "mirror").unwrap();
+        let bulk = bulk.rename_field("a", "b").unwrap();
+        let bulk = bulk.rename_field("copy", "mirror").unwrap();
+
+        assert_eq!(bulk.list_data_fields(), vec!["b".to_string()]);
+        assert_eq!(bulk.meta.versions.get("b"), Some(&1));
+        assert!(bulk.cache.borrow().contains_key("mirror"));
+        assert_eq!(bulk.get(&registry, "mirror").unwrap(), expected);
+
+        assert!(matches!(
+            bulk.rename_field("a", "c"),
+            Err(SoAKitError::FieldNotFound(_))
+        ));
+        assert!(matches!(
+            bulk.rename_field("b", "mirror"),
+            Err(SoAKitError::FieldAlreadyExists(_))
+        ));
+    }
+
+    #[test]
+    fn test_sort_by_is_stable_with_nan_last() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
+        registry
+            .register("score".to_string(), validator, false, vec![], None)
+            .unwrap();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("group".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let scores = [2.0, f64::NAN, 1.0, 2.0, f64::NAN, 3.0];
+        let bulk = Bulk::new(scores.len()).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "score",
+                scores.iter().map(|&x| Value::ScalarFloat(x)).collect(),
+            )
+            .unwrap();
+        let bulk = bulk
+            .set(&registry, "group", vec![Value::ScalarInt(0); scores.len()])
+            .unwrap();
+
+        let asc = bulk
+            .sort_by(&registry, &[("score", SortOrder::Asc)])
+            .unwrap();
+        assert_eq!(asc.meta.id, vec![2, 0, 3, 5, 1, 4]);
+        let desc = bulk
+            .sort_by(&registry, &[("score", SortOrder::Desc)])
+            .unwrap();
+        assert_eq!(desc.meta.id, vec![5, 0, 3, 2, 1, 4]);
+        assert_eq!(desc.meta.versions.get("score"), Some(&2));
+        assert_eq!(
+            desc.get(&registry, "score")
+                .unwrap()
+                .get_element(0)
+                .unwrap(),
+            Value::ScalarFloat(3.0)
+        );
+
+        // Ties on every key keep their original order
+        let tied = bulk
+            .sort_by(&registry, &[("group", SortOrder::Desc)])
+            .unwrap();
+        assert_eq!(tied.meta.id, (0..scores.len()).collect::<Vec<_>>());
+
+        assert!(bulk.sort_by(&registry, &[]).is_err());
+        assert!(
+            bulk.sort_by(&registry, &[("missing", SortOrder::Asc)])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_sort_by_across_chunks() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("a".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let count = CHUNK_SIZE * 2 + 5;
+        let bulk = Bulk::new(count).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "a",
+                (0..count)
+                    .map(|i| Value::ScalarInt((i % 7) as i64))
+                    .collect(),
+            )
+            .unwrap();
+        let sorted = bulk.sort_by(&registry, &[("a", SortOrder::Asc)]).unwrap();
+
+        let Value::VectorInt(values) = sorted.get(&registry, "a").unwrap() else {
+            panic!("expected ints");
+        };
+        assert!(values.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(sorted.chunks.len(), 3);
+        assert_eq!(sorted.meta.id[..3], [0, 7, 14]);
+    }
+
+    #[test]
+    fn test_filter_and_filter_field() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("age".to_string(), validator, false, vec![], None)
+            .unwrap();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarString(_)));
+        registry
+            .register("name".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let count = CHUNK_SIZE + 4;
+        let bulk = Bulk::new(count).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "age",
+                (0..count).map(|i| Value::ScalarInt(i as i64)).collect(),
+            )
+            .unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "name",
+                (0..count)
+                    .map(|i| Value::ScalarString(i.to_string()))
+                    .collect(),
+            )
+            .unwrap();
+
+        let by_row = bulk
+            .filter(|row| {
+                Ok(matches!(row.get_field(&registry, "age")?, Value::ScalarInt(a) if a % 3 == 0))
+            })
+            .unwrap();
+        let by_field = bulk
+            .filter_field(
+                &registry,
+                "age",
+                |v| matches!(v, Value::ScalarInt(a) if a % 3 == 0),
+            )
+            .unwrap();
+        let expected: Vec<usize> = (0..count).filter(|i| i % 3 == 0).collect();
+        assert_eq!(by_row.meta.id, expected);
+        assert_eq!(by_field.meta.id, expected);
+        assert_eq!(
+            by_field.get(&registry, "name").unwrap(),
+            Value::VectorString(expected.iter().map(|i| i.to_string()).collect())
+        );
+
+        assert!(bulk.filter_field(&registry, "age", |_| false).is_err());
+        assert!(bulk.filter_field(&registry, "missing", |_| true).is_err());
+        assert!(
+            bulk.filter(|_| Err(SoAKitError::InvalidArgument("stop".to_string())))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_join_inner_and_left() {
+        let mut registry = Registry::new();
+        for name in ["user_id", "amount", "age"] {
+            let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+            registry
+                .register(name.to_string(), validator, false, vec![], None)
+                .unwrap();
+        }
+
+        let count = CHUNK_SIZE + 6;
+        let orders = Bulk::new(count).unwrap();
+        let orders = orders
+            .set(
+                &registry,
+                "user_id",
+                (0..count)
+                    .map(|i| Value::ScalarInt((i % 4) as i64))
+                    .collect(),
+            )
+            .unwrap();
+        let orders = orders
+            .set(
+                &registry,
+                "amount",
+                (0..count).map(|i| Value::ScalarInt(i as i64)).collect(),
+            )
+            .unwrap();
+        // Users 0 and 2 exist; user 2 appears twice
+        let users = Bulk::new(3).unwrap();
+        let users = users
+            .set(
+                &registry,
+                "user_id",
+                vec![
+                    Value::ScalarInt(0),
+                    Value::ScalarInt(2),
+                    Value::ScalarInt(2),
+                ],
+            )
+            .unwrap();
+        let users = users
+            .set(
+                &registry,
+                "age",
+                vec![
+                    Value::ScalarInt(30),
+                    Value::ScalarInt(40),
+                    Value::ScalarInt(41),
+                ],
+            )
+            .unwrap();
+
+        let inner = orders
+            .join(&users, &registry, "user_id", JoinKind::Inner)
+            .unwrap();
+        let orders_of = |user: usize| (0..count).filter(|i| i % 4 == user).count();
+        assert_eq!(inner.count(), orders_of(0) + 2 * orders_of(2));
+        assert_eq!(inner.list_data_fields(), vec!["age", "amount", "user_id"]);
+        assert_eq!(inner.meta.id, (0..inner.count()).collect::<Vec<_>>());
+        let ages = inner.get(&registry, "age").unwrap();
+        assert_eq!(
+            ages.slice(0..3).unwrap(),
+            Value::VectorInt(vec![30, 40, 41])
+        );
+
+        let left = orders
+            .join(&users, &registry, "user_id", JoinKind::Left)
+            .unwrap();
+        assert_eq!(left.count(), inner.count() + count / 2);
+        let ages = left.get(&registry, "age").unwrap();
+        assert!(ages.is_sparse());
+        assert_eq!(ages.get_element(1).unwrap(), Value::Null);
+        assert_eq!(
+            left.get(&registry, "amount").unwrap().slice(0..4).unwrap(),
+            Value::VectorInt(vec![0, 1, 2, 2])
+        );
+
+        assert!(matches!(
+            orders.join(&orders, &registry, "user_id", JoinKind::Inner),
+            Err(SoAKitError::FieldAlreadyExists(_))
+        ));
+        assert!(matches!(
+            orders.join(&users, &registry, "amount", JoinKind::Inner),
+            Err(SoAKitError::FieldNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_merge_bumps_only_changed_fields() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
+        registry
+            .register("score".to_string(), validator, false, vec![], None)
+            .unwrap();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarString(_) | Value::Null));
+        registry
+            .register("name".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let bulk = Bulk::new(3).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "score",
+                vec![
+                    Value::ScalarFloat(1.0),
+                    Value::ScalarFloat(f64::NAN),
+                    Value::ScalarFloat(3.0),
+                ],
+            )
+            .unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "name",
+                vec![Value::from("a"), Value::from("b"), Value::from("c")],
+            )
+            .unwrap()
+            .intern_field("name")
+            .unwrap();
+
+        // Same names, one changed score; NaN stays NaN
+        let mut update = Bulk::new(2).unwrap();
+        update.meta.id = vec![2, 1];
+        let update = update
+            .set(
+                &registry,
+                "score",
+                vec![Value::ScalarFloat(30.0), Value::ScalarFloat(f64::NAN)],
+            )
+            .unwrap();
+        let update = update
+            .set(&registry, "name", vec![Value::from("c"), Value::from("b")])
+            .unwrap();
+        let merged = bulk.merge_by_id(&update, &registry).unwrap();
+        assert_eq!(merged.meta.versions.get("score"), Some(&2));
+        assert_eq!(merged.meta.versions.get("name"), Some(&1));
+        assert!(
+            merged
+                .get(&registry, "score")
+                .unwrap()
+                .equals_nan_aware(&Value::VectorFloat(vec![1.0, f64::NAN, 30.0]))
+        );
+
+        // Appending rebuilds every field; fields missing from `other` become null
+        let mut extra = Bulk::new(1).unwrap();
+        extra.meta.id = vec![9];
+        let extra = extra
+            .set(&registry, "score", vec![Value::ScalarFloat(9.0)])
+            .unwrap();
+        let named = extra
+            .set(&registry, "name", vec![Value::from("d")])
+            .unwrap();
+        let appended = merged.merge_by_id(&named, &registry).unwrap();
+        assert_eq!(appended.meta.id, vec![0, 1, 2, 9]);
+        assert_eq!(appended.meta.versions.get("name"), Some(&2));
+        assert!(appended.is_interned("name"));
+        let merged = merged.merge_by_id(&extra, &registry).unwrap();
+        assert_eq!(
+            merged.at(3).unwrap().get_field(&registry, "name").unwrap(),
+            Value::Null
+        );
+
+        let by_key = bulk.merge_by_key(&update, &registry, "name").unwrap();
+        assert_eq!(by_key.count(), 3);
+        assert_eq!(by_key.meta.versions.get("score"), Some(&2));
+        let new_name = Bulk::new(1)
+            .unwrap()
+            .set(&registry, "name", vec![Value::from("z")])
+            .unwrap();
+        assert!(matches!(
+            bulk.merge_by_key(&new_name, &registry, "name"),
+            Err(SoAKitError::ValidationFailed(_))
+        ));
+        assert!(bulk.merge_by_key(&extra, &registry, "name").is_err());
+    }
+
+    #[test]
+    fn test_group_agg_summarizes_groups() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("category".to_string(), validator, false, vec![], None)
+            .unwrap();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
+        registry
+            .register("price".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let count = CHUNK_SIZE + 2;
+        let bulk = Bulk::new(count).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "category",
+                (0..count)
+                    .map(|i| Value::ScalarInt((i % 2) as i64))
+                    .collect(),
+            )
+            .unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "price",
+                (0..count).map(|i| Value::ScalarFloat(i as f64)).collect(),
+            )
+            .unwrap();
+
+        let summary = bulk
+            .group_agg(&registry, "category", &[("price", Agg::Max)])
+            .unwrap();
+        assert_eq!(summary.count(), 2);
+        assert_eq!(
+            summary.get(&registry, "category").unwrap(),
+            Value::VectorInt(vec![0, 1])
+        );
+        assert_eq!(
+            summary.get(&registry, "price").unwrap(),
+            Value::VectorFloat(vec![(count - 2) as f64, (count - 1) as f64])
+        );
+
+        assert!(matches!(
+            bulk.group_agg(
+                &registry,
+                "category",
+                &[("price", Agg::Max), ("price", Agg::Min)]
+            ),
+            Err(SoAKitError::FieldAlreadyExists(_))
+        ));
+        assert!(matches!(
+            bulk.group_agg(&registry, "category", &[("category", Agg::Count)]),
+            Err(SoAKitError::FieldAlreadyExists(_))
+        ));
+        assert!(
+            bulk.group_agg(&registry, "category", &[("missing", Agg::Sum)])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_dedup_keep_first_and_last() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
+        registry
+            .register("x".to_string(), validator, false, vec![], None)
+            .unwrap();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("n".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let xs = [f64::NAN, 1.0, f64::NAN, -0.0, 0.0, 1.0];
+        let bulk = Bulk::new(xs.len()).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "x",
+                xs.iter().map(|&x| Value::ScalarFloat(x)).collect(),
+            )
+            .unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "n",
+                vec![
+                    Value::ScalarInt(0),
+                    Value::ScalarInt(1),
+                    Value::ScalarInt(0),
+                    Value::ScalarInt(3),
+                    Value::ScalarInt(4),
+                    Value::ScalarInt(1),
+                ],
+            )
+            .unwrap();
+
+        assert_eq!(
+            bulk.dedup(&registry, &["x"], Keep::First).unwrap().meta.id,
+            vec![0, 1, 3]
+        );
+        assert_eq!(
+            bulk.dedup(&registry, &["x"], Keep::Last).unwrap().meta.id,
+            vec![2, 4, 5]
+        );
+        // All fields: rows 3 and 4 differ in `n`
+        assert_eq!(
+            bulk.dedup(&registry, &[], Keep::First).unwrap().meta.id,
+            vec![0, 1, 3, 4]
+        );
+        assert!(bulk.dedup(&registry, &["missing"], Keep::First).is_err());
+    }
+
+    #[test]
+    fn test_head_tail_and_sample() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("a".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let count = CHUNK_SIZE + 50;
+        let bulk = Bulk::new(count).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "a",
+                (0..count).map(|i| Value::ScalarInt(i as i64)).collect(),
+            )
+            .unwrap();
+
+        let head = bulk.head(3).unwrap();
+        assert_eq!(
+            head.get(&registry, "a").unwrap(),
+            Value::VectorInt(vec![0, 1, 2])
+        );
+        let tail = bulk.tail(2).unwrap();
+        assert_eq!(tail.meta.id, vec![count - 2, count - 1]);
+        assert_eq!(bulk.head(count + 10).unwrap().count(), count);
+        assert!(bulk.head(0).is_err());
+
+        let sample = bulk.sample(100, 7).unwrap();
+        assert_eq!(sample.count(), 100);
+        assert!(sample.meta.id.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(sample.meta.id, bulk.sample(100, 7).unwrap().meta.id);
+        assert_ne!(sample.meta.id, bulk.sample(100, 8).unwrap().meta.id);
+        assert!(sample.meta.id.iter().any(|&id| id >= CHUNK_SIZE));
+        let Value::VectorInt(values) = sample.get(&registry, "a").unwrap() else {
+            panic!("expected ints");
+        };
+        assert!(
+            values
+                .iter()
+                .zip(&sample.meta.id)
+                .all(|(&v, &id)| v == id as i64)
+        );
+        assert_eq!(bulk.sample(count * 2, 1).unwrap().count(), count);
+    }
+
+    #[test]
+    fn test_iter_rows_across_chunks() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("a".to_string(), validator, false, vec![], None)
+            .unwrap();
+        let derived_func = Box::new(|args: &[Value]| -> Result<Value> {
+            match &args[0] {
+                Value::VectorInt(v) => Ok(Value::VectorInt(v.iter().map(|x| x * 2).collect())),
+                _ => Err(SoAKitError::InvalidArgument("expected ints".to_string())),
+            }
+        });
+        registry
+            .register(
+                "double".to_string(),
+                Box::new(|_: &Value| true),
+                true,
+                vec!["a".to_string()],
+                Some(derived_func),
+            )
+            .unwrap();
+        let unused = Box::new(|v: &Value| matches!(v, Value::ScalarBool(_)));
+        registry
+            .register("unused".to_string(), unused, false, vec![], None)
+            .unwrap();
+
+        let count = CHUNK_SIZE + 5;
+        let bulk = Bulk::new(count).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "a",
+                (0..count).map(|i| Value::ScalarInt(i as i64)).collect(),
+            )
+            .unwrap();
+        let bulk = bulk.remove_indices(&[0]).unwrap();
+
+        let rows = bulk.iter_rows(&registry).unwrap();
+        assert_eq!(rows.len(), count - 1);
+        let mut seen = 0;
+        for (i, row) in rows.enumerate() {
+            assert_eq!(row.index(), i);
+            assert_eq!(row.id(), i + 1);
+            assert_eq!(row.get("a"), Some(&Value::ScalarInt(i as i64 + 1)));
+            assert_eq!(
+                row.get("double"),
+                Some(&Value::ScalarInt(2 * (i as i64 + 1)))
+            );
+            assert_eq!(row.get("unused"), None);
+            seen += 1;
+        }
+        assert_eq!(seen, count - 1);
+
+        let empty = Bulk::new(2).unwrap();
+        let rows: Vec<_> = empty.iter_rows(&registry).unwrap().collect();
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0].values().is_empty());
+    }
+
+    #[test]
+    fn test_typed_field_iterators() {
+        let mut registry = Registry::new();
+        let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("n".to_string(), ints, false, vec![], None)
+            .unwrap();
+        let strings = Box::new(|v: &Value| matches!(v, Value::ScalarString(_)));
+        registry
+            .register("s".to_string(), strings, false, vec![], None)
+            .unwrap();
+
+        let count = CHUNK_SIZE + 3;
+        let bulk = Bulk::new(count).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "n",
+                (0..count).map(|i| Value::ScalarInt(i as i64)).collect(),
+            )
+            .unwrap()
+            .set(
+                &registry,
+                "s",
+                (0..count)
+                    .map(|i| Value::ScalarString((i % 3).to_string()))
+                    .collect(),
+            )
+            .unwrap();
+
+        let collected: Vec<i64> = bulk.iter_field_i64("n").unwrap().collect();
+        assert_eq!(collected, (0..count as i64).collect::<Vec<_>>());
+        assert_eq!(
+            bulk.iter_field_str("s").unwrap().nth(CHUNK_SIZE + 1),
+            Some("2")
+        );
+
+        let interned = bulk.intern_field("s").unwrap();
+        let plain: Vec<&str> = bulk.iter_field_str("s").unwrap().collect();
+        let pooled: Vec<&str> = interned.iter_field_str("s").unwrap().collect();
+        assert_eq!(plain, pooled);
+
+        assert!(matches!(
+            bulk.iter_field_f64("n"),
+            Err(SoAKitError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            interned.iter_field_i64("s"),
+            Err(SoAKitError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            bulk.iter_field_bool("missing"),
+            Err(SoAKitError::FieldNotFound(_))
+        ));
+        assert!(Bulk::new(2).unwrap().iter_field_i64("n").is_err());
+    }
+
+    #[test]
+    fn test_set_element_rewrites_one_chunk() {
+        let mut registry = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
+        registry
+            .register("x".to_string(), validator, false, vec![], None)
+            .unwrap();
+
+        let count = CHUNK_SIZE + 2;
+        let bulk = Bulk::new(count).unwrap();
+        let bulk = bulk
+            .set(&registry, "x", vec![Value::ScalarFloat(0.0); count])
+            .unwrap();
+        let version = bulk.meta.versions["x"];
+
+        let updated = bulk
+            .set_element(&registry, "x", CHUNK_SIZE + 1, Value::ScalarFloat(2.5))
+            .unwrap();
+        assert_eq!(updated.meta.versions["x"], version + 1);
+        assert!(Arc::ptr_eq(
+            &bulk.chunks[0].columns["x"],
+            &updated.chunks[0].columns["x"]
+        ));
+        assert!(!Arc::ptr_eq(
+            &bulk.chunks[1].columns["x"],
+            &updated.chunks[1].columns["x"]
+        ));
+        assert_eq!(
+            updated
+                .at(CHUNK_SIZE + 1)
+                .unwrap()
+                .get_field(&registry, "x")
+                .unwrap(),
+            Value::ScalarFloat(2.5)
+        );
+        assert_eq!(bulk.meta.versions["x"], version);
+
+        let mut bulk = bulk;
+        assert!(matches!(
+            bulk.set_element_mut(&registry, "x", count, Value::ScalarFloat(1.0)),
+            Err(SoAKitError::IndexOutOfBounds { .. })
+        ));
+        assert!(matches!(
+            bulk.set_element_mut(&registry, "x", 0, Value::ScalarInt(1)),
+            Err(SoAKitError::ValidationFailed(_))
+        ));
+        assert_eq!(bulk.meta.versions["x"], version);
+        bulk.set_element_mut(&registry, "x", 0, Value::ScalarFloat(1.0))
+            .unwrap();
+        assert_eq!(bulk.iter_field_f64("x").unwrap().next(), Some(1.0));
+    }
+
+    #[test]
+    fn test_set_many_is_all_or_nothing() {
+        let mut registry = Registry::new();
+        let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("a".to_string(), ints, false, vec![], None)
+            .unwrap();
+        let bools = Box::new(|v: &Value| matches!(v, Value::ScalarBool(_)));
+        registry
+            .register("b".to_string(), bools, false, vec![], None)
+            .unwrap();
+
+        let bulk = Bulk::new(2).unwrap();
+        let mut columns = BTreeMap::new();
+        let _ = columns.insert("a", vec![Value::ScalarInt(1), Value::ScalarInt(2)]);
+        let _ = columns.insert("b", vec![Value::ScalarBool(true), Value::ScalarBool(false)]);
+        let set = bulk.set_many(&registry, columns.clone()).unwrap();
+        assert_eq!(
+            set.get(&registry, "a").unwrap(),
+            Value::VectorInt(vec![1, 2])
+        );
+        assert_eq!(
+            set.get(&registry, "b").unwrap(),
+            Value::VectorBool(vec![true, false])
+        );
+        assert_eq!(set.meta.versions["a"], 1);
+        assert_eq!(set.meta.versions["b"], 1);
+
+        let _ = columns.insert("b", vec![Value::ScalarBool(true)]);
+        assert!(matches!(
+            set.set_many(&registry, columns),
+            Err(SoAKitError::LengthMismatch { .. })
+        ));
+        assert!(bulk.list_data_fields().is_empty());
+    }
+
+    #[test]
+    fn test_apply_fields_mixed_types() {
+        let mut registry = Registry::new();
+        let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("n".to_string(), ints, false, vec![], None)
+            .unwrap();
+        let strings = Box::new(|v: &Value| matches!(v, Value::ScalarString(_)));
+        registry
+            .register("s".to_string(), strings, false, vec![], None)
+            .unwrap();
+
+        let bulk = Bulk::new(3).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "n",
+                vec![
+                    Value::ScalarInt(1),
+                    Value::ScalarInt(2),
+                    Value::ScalarInt(3),
+                ],
+            )
+            .unwrap()
+            .set(
+                &registry,
+                "s",
+                vec![Value::ScalarString("x".to_string()); 3],
+            )
+            .unwrap();
+
+        let negate = |subset: &[Value]| -> Result<Vec<Value>> {
+            subset
+                .iter()
+                .map(|v| match v {
+                    Value::ScalarInt(i) => Ok(Value::ScalarInt(-i)),
+                    _ => Err(SoAKitError::InvalidArgument("expected an int".to_string())),
+                })
+                .collect()
+        };
+        let updated = bulk
+            .apply_fields(&registry, &["n"], &[true, false, true], negate)
+            .unwrap();
+        assert_eq!(
+            updated.get(&registry, "n").unwrap(),
+            Value::VectorInt(vec![-1, 2, -3])
+        );
+        assert_eq!(updated.meta.versions["n"], 2);
+        assert_eq!(updated.meta.versions["s"], 1);
+
+        // The same function fails on the string field, through apply as well
+        assert!(bulk.apply(&[], negate).is_err());
+        assert!(
+            bulk.apply_fields(&registry, &["n", "s"], &[], negate)
+                .is_err()
+        );
+        assert!(matches!(
+            bulk.apply_fields(&registry, &["missing"], &[], negate),
+            Err(SoAKitError::FieldNotFound(_))
+        ));
+        let to_float = |subset: &[Value]| -> Result<Vec<Value>> {
+            Ok(subset.iter().map(|_| Value::ScalarFloat(0.0)).collect())
+        };
+        assert!(matches!(
+            bulk.apply_fields(&registry, &["n"], &[], to_float),
+            Err(SoAKitError::ValidationFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_apply_with_ids_sees_ids_not_positions() {
+        let mut registry = Registry::new();
+        let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("n".to_string(), ints, false, vec![], None)
+            .unwrap();
+
+        let bulk = Bulk::new(4).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "n",
+                (0..4).map(|i| Value::ScalarInt(i * 100)).collect(),
+            )
+            .unwrap()
+            .remove_indices(&[0])
+            .unwrap();
+
+        let add_id = |id: usize, v: &Value| match v {
+            Value::ScalarInt(i) => Ok(Value::ScalarInt(i + id as i64)),
+            _ => Err(SoAKitError::InvalidArgument("expected an int".to_string())),
+        };
+        let all = bulk.apply_with_ids(&registry, &["n"], &[], add_id).unwrap();
+        assert_eq!(
+            all.get(&registry, "n").unwrap(),
+            Value::VectorInt(vec![101, 202, 303])
+        );
+        let some = bulk
+            .apply_with_ids(&registry, &["n"], &[false, true, false], add_id)
+            .unwrap();
+        assert_eq!(
+            some.get(&registry, "n").unwrap(),
+            Value::VectorInt(vec![100, 202, 300])
+        );
+        assert!(matches!(
+            bulk.apply_with_ids(&registry, &["n"], &[true], add_id),
+            Err(SoAKitError::LengthMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_apply_chunks_works_on_whole_columns() {
+        let mut registry = Registry::new();
+        let floats = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
+        registry
+            .register("x".to_string(), floats, false, vec![], None)
+            .unwrap();
+
+        let count = CHUNK_SIZE + 10;
+        let bulk = Bulk::new(count).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "x",
+                (0..count).map(|i| Value::ScalarFloat(i as f64)).collect(),
+            )
+            .unwrap();
+
+        let mut calls = 0;
+        let doubled = bulk
+            .apply_chunks(&registry, "x", |column| {
+                calls += 1;
+                if let Value::VectorFloat(xs) = column {
+                    xs.iter_mut().for_each(|x| *x *= 2.0);
+                }
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(calls, 2);
+        assert_eq!(doubled.meta.versions["x"], bulk.meta.versions["x"] + 1);
+        let values: Vec<f64> = doubled.iter_field_f64("x").unwrap().collect();
+        assert_eq!(
+            values,
+            (0..count).map(|i| i as f64 * 2.0).collect::<Vec<_>>()
+        );
+
+        let truncate = |column: &mut Value| {
+            if let Value::VectorFloat(xs) = column {
+                xs.truncate(1);
+            }
+            Ok(())
+        };
+        assert!(matches!(
+            bulk.apply_chunks(&registry, "x", truncate),
+            Err(SoAKitError::LengthMismatch { .. })
+        ));
+        let retype = |column: &mut Value| {
+            *column = Value::VectorInt(vec![0; column.len()]);
+            Ok(())
+        };
+        assert!(matches!(
+            bulk.apply_chunks(&registry, "x", retype),
+            Err(SoAKitError::ValidationFailed(_))
+        ));
+        let mut unset = Bulk::new(2).unwrap();
+        assert!(matches!(
+            unset.apply_chunks_mut(&registry, "x", |_| Ok(())),
+            Err(SoAKitError::FieldNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_chunk_safe_derived_runs_per_chunk() {
+        use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+        let mut registry = Registry::new();
+        let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("a".to_string(), ints, false, vec![], None)
+            .unwrap();
+        let negate = Box::new(|args: &[Value]| match &args[0] {
+            Value::VectorInt(v) => Ok(Value::VectorInt(v.iter().map(|x| -x).collect())),
+            _ => Err(SoAKitError::InvalidArgument("expected ints".to_string())),
+        });
+        registry
+            .register(
+                "neg".to_string(),
+                Box::new(|_: &Value| true),
+                true,
+                vec!["a".to_string()],
+                Some(negate),
+            )
+            .unwrap();
+
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        let add = Box::new(|args: &[Value]| {
+            let _ = CALLS.fetch_add(1, AtomicOrdering::SeqCst);
+            match (&args[0], &args[1]) {
+                (Value::VectorInt(a), Value::VectorInt(b)) => Ok(Value::VectorInt(
+                    a.iter().zip(b).map(|(x, y)| x + 2 * y).collect(),
+                )),
+                _ => Err(SoAKitError::InvalidArgument("expected ints".to_string())),
+            }
+        });
+        registry
+            .register_chunk_safe(
+                "mix".to_string(),
+                Box::new(|_: &Value| true),
+                vec!["a".to_string(), "neg".to_string()],
+                add,
+            )
+            .unwrap();
+        assert!(registry.get_metadata("mix").unwrap().chunk_safe);
+        assert!(!registry.get_metadata("neg").unwrap().chunk_safe);
+
+        let count = 2 * CHUNK_SIZE + 1;
+        let bulk = Bulk::new(count).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "a",
+                (0..count).map(|i| Value::ScalarInt(i as i64)).collect(),
+            )
+            .unwrap();
+        assert_eq!(
+            bulk.get(&registry, "mix").unwrap(),
+            Value::VectorInt((0..count as i64).map(|i| -i).collect())
+        );
+        assert_eq!(CALLS.load(AtomicOrdering::SeqCst), 3);
+
+        let shrink = Box::new(|_: &[Value]| Ok(Value::VectorInt(vec![0])));
+        registry
+            .register_chunk_safe(
+                "bad".to_string(),
+                Box::new(|_: &Value| true),
+                vec!["a".to_string()],
+                shrink,
+            )
+            .unwrap();
+        assert!(matches!(
+            bulk.get(&registry, "bad"),
+            Err(SoAKitError::LengthMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_elementwise_derived_maps_rows_across_chunks() {
+        let mut registry = Registry::new();
+        registry.register_int("a").unwrap();
+        registry.register_int("b").unwrap();
+        registry
+            .register_derived_elementwise("sum", &["a", "b"], |row: &[Value]| match row {
+                [Value::ScalarInt(a), Value::ScalarInt(b)] => Ok(Value::ScalarInt(a + b)),
+                [Value::Null, _] | [_, Value::Null] => Ok(Value::Null),
+                _ => Err(SoAKitError::InvalidArgument("expected ints".to_string())),
+            })
+            .unwrap();
+        assert!(registry.get_metadata("sum").unwrap().chunk_safe);
+        assert_eq!(
+            registry.get_metadata("sum").unwrap().dependencies,
+            vec!["a", "b"]
+        );
+
+        let count = CHUNK_SIZE + 2;
+        let bulk = Bulk::new(count).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "a",
+                (0..count).map(|i| Value::ScalarInt(i as i64)).collect(),
+            )
+            .unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "b",
+                (0..count).map(|i| Value::ScalarInt(2 * i as i64)).collect(),
+            )
+            .unwrap();
+        assert_eq!(
+            bulk.get(&registry, "sum").unwrap(),
+            Value::VectorInt((0..count as i64).map(|i| 3 * i).collect())
+        );
+
+        registry
+            .register_derived_elementwise("fails", &["a"], |_: &[Value]| {
+                Err(SoAKitError::ValidationFailed("no".to_string()))
+            })
+            .unwrap();
+        assert!(matches!(
+            bulk.get(&registry, "fails"),
+            Err(SoAKitError::ValidationFailed(_))
+        ));
+        assert!(matches!(
+            registry.register_derived_elementwise("none", &[], |_: &[Value]| Ok(Value::Null)),
+            Err(SoAKitError::DerivedFieldNoDeps(_))
+        ));
+    }
+
+    #[test]
+    fn test_multi_output_derived_computes_once() {
+        use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        let mut registry = Registry::new();
+        registry.register_int("a").unwrap();
+        registry
+            .register_derived_multi(&["double", "square"], &["a"], |args: &[Value]| {
+                let _ = CALLS.fetch_add(1, AtomicOrdering::SeqCst);
+                match &args[0] {
+                    Value::VectorInt(a) => Ok(vec![
+                        Value::VectorInt(a.iter().map(|x| 2 * x).collect()),
+                        Value::VectorInt(a.iter().map(|x| x * x).collect()),
+                    ]),
+                    _ => Err(SoAKitError::InvalidArgument("expected ints".to_string())),
+                }
+            })
+            .unwrap();
+        let metadata = registry.get_metadata("square").unwrap();
+        assert!(metadata.is_derived);
+        assert_eq!(
+            metadata.multi_output.as_ref().unwrap().names,
+            vec!["double", "square"]
+        );
+
+        let bulk = Bulk::new(3).unwrap();
+        let values = vec![
+            Value::ScalarInt(1),
+            Value::ScalarInt(2),
+            Value::ScalarInt(3),
+        ];
+        let bulk = bulk.set(&registry, "a", values).unwrap();
+        assert_eq!(
+            bulk.get(&registry, "square").unwrap(),
+            Value::VectorInt(vec![1, 4, 9])
+        );
+        assert_eq!(
+            bulk.get(&registry, "double").unwrap(),
+            Value::VectorInt(vec![2, 4, 6])
+        );
+        assert_eq!(CALLS.load(AtomicOrdering::SeqCst), 1);
+
+        let bulk = bulk
+            .set(&registry, "a", vec![Value::ScalarInt(5); 3])
+            .unwrap();
+        assert_eq!(
+            bulk.get(&registry, "double").unwrap(),
+            Value::VectorInt(vec![10; 3])
+        );
+        assert_eq!(
+            bulk.get(&registry, "square").unwrap(),
+            Value::VectorInt(vec![25; 3])
+        );
+        assert_eq!(CALLS.load(AtomicOrdering::SeqCst), 2);
+
+        registry.rename_field("square", "sq").unwrap();
+        assert_eq!(
+            bulk.get(&registry, "sq").unwrap(),
+            Value::VectorInt(vec![25; 3])
+        );
+        assert_eq!(
+            registry
+                .get_metadata("double")
+                .unwrap()
+                .multi_output
+                .as_ref()
+                .unwrap()
+                .names,
+            vec!["double", "sq"]
+        );
+
+        registry
+            .register_derived_multi(&["one", "two"], &["a"], |_: &[Value]| {
+                Ok(vec![Value::VectorInt(vec![0; 3])])
+            })
+            .unwrap();
+        assert!(matches!(
+            bulk.get(&registry, "one"),
+            Err(SoAKitError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_derived_context_sees_ids_and_chunks() {
+        let mut registry = Registry::new();
+        registry.register_int("a").unwrap();
+        registry
+            .register_derived_with_context(
+                "row",
+                &["a"],
+                |ctx: &DerivedContext<'_>, _: &[Value]| {
+                    Ok(Value::VectorInt(
+                        ctx.ids.iter().map(|&id| id as i64).collect(),
+                    ))
+                },
+            )
+            .unwrap();
+        let chunk_number = |ctx: &DerivedContext<'_>, args: &[Value]| {
+            assert_eq!(args[0].len(), ctx.count);
+            let chunk_of = |i: usize| {
+                ctx.chunk_offsets
+                    .iter()
+                    .filter(|&&start| start <= i)
+                    .count()
+            };
+            Ok(Value::VectorInt(
+                (0..ctx.count).map(|i| chunk_of(i) as i64).collect(),
+            ))
+        };
+        registry
+            .register_derived_with_context("chunk", &["a"], chunk_number)
+            .unwrap();
+        assert!(!registry.get_metadata("chunk").unwrap().chunk_safe);
+
+        let count = CHUNK_SIZE + 1;
+        let bulk = Bulk::new(count).unwrap();
+        let bulk = bulk
+            .set(&registry, "a", vec![Value::ScalarInt(0); count])
+            .unwrap();
+        let bulk = bulk.remove_indices(&[0]).unwrap();
+        assert_eq!(
+            bulk.get(&registry, "row").unwrap(),
+            Value::VectorInt((1..count as i64).collect())
+        );
+        let expected: Vec<i64> = (1..=bulk.chunks.len() as i64)
+            .zip(&bulk.chunks)
+            .flat_map(|(i, chunk)| vec![i; chunk.len])
+            .collect();
+        assert_eq!(
+            bulk.get(&registry, "chunk").unwrap(),
+            Value::VectorInt(expected)
+        );
 
-        views
+        // Without a bulk, elements are numbered from 0 in one chunk
+        let derived_func = registry
+            .get_metadata("row")
+            .unwrap()
+            .derived_func
+            .as_ref()
+            .unwrap();
+        assert_eq!(
+            derived_func(&[Value::VectorInt(vec![7, 8])]).unwrap(),
+            Value::VectorInt(vec![0, 1])
+        );
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::value::Value;
 
     #[test]
-    fn test_new_bulk() {
-        let bulk = Bulk::new(5).unwrap();
-        assert_eq!(bulk.count(), 5);
-        assert_eq!(bulk.meta.id, vec![0, 1, 2, 3, 4]);
-    }
+    fn test_derived_outputs_are_validated() {
+        let mut registry = Registry::new();
+        registry.register_int("a").unwrap();
+        let mut derived = |name: &str, whole: bool, func: DerivedFunc| {
+            let validator: Box<dyn Fn(&Value) -> bool + Send + Sync> = if whole {
+                Box::new(|v: &Value| matches!(v, Value::VectorInt(_)))
+            } else {
+                Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)))
+            };
+            registry
+                .register(
+                    name.to_string(),
+                    validator,
+                    true,
+                    vec!["a".to_string()],
+                    Some(func),
+                )
+                .unwrap();
+        };
+        derived(
+            "short",
+            false,
+            Box::new(|_: &[Value]| Ok(Value::VectorInt(vec![0]))),
+        );
+        derived(
+            "floats",
+            false,
+            Box::new(|_: &[Value]| Ok(Value::VectorFloat(vec![0.5, 1.5]))),
+        );
+        derived(
+            "column",
+            true,
+            Box::new(|args: &[Value]| Ok(args[0].clone())),
+        );
 
-    #[test]
-    fn test_new_bulk_zero_count() {
-        let result = Bulk::new(0);
-        assert!(result.is_err());
+        let bulk = Bulk::new(2).unwrap();
+        let values = vec![Value::ScalarInt(1), Value::ScalarInt(2)];
+        let bulk = bulk.set(&registry, "a", values).unwrap();
+        match bulk.get(&registry, "short") {
+            Err(SoAKitError::DerivedOutputInvalid { field, reason }) => {
+                assert_eq!(field, "short");
+                assert_eq!(reason, "expected 2 values, got 1");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(matches!(
+            bulk.get(&registry, "floats"),
+            Err(SoAKitError::DerivedOutputInvalid { .. })
+        ));
+        // Validators written for whole columns still accept the column
+        assert_eq!(
+            bulk.get(&registry, "column").unwrap(),
+            Value::VectorInt(vec![1, 2])
+        );
+
+        registry.set_validate_output("floats", false).unwrap();
+        assert_eq!(
+            bulk.get(&registry, "floats").unwrap(),
+            Value::VectorFloat(vec![0.5, 1.5])
+        );
+        assert!(matches!(
+            registry.set_validate_output("a", false),
+            Err(SoAKitError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            registry.set_validate_output("missing", false),
+            Err(SoAKitError::FieldNotFound(_))
+        ));
     }
 
     #[test]
-    fn test_set_and_get() {
+    fn test_field_groups_get_set_and_select() {
         let mut registry = Registry::new();
-        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        for field in ["x", "y", "mass"] {
+            registry.register_float(field).unwrap();
+        }
+        let copy: DerivedFunc = Box::new(|args: &[Value]| Ok(args[0].clone()));
         registry
-            .register("age".to_string(), validator, false, vec![], None)
+            .register(
+                "x_copy".to_string(),
+                Box::new(|_| true),
+                true,
+                vec!["x".into()],
+                Some(copy),
+            )
+            .unwrap();
+        registry
+            .define_group("position", &["x", "y", "x_copy"])
             .unwrap();
+        registry.define_group("flat", &["x", "y"]).unwrap();
 
+        let floats = |xs: &[f64]| {
+            xs.iter()
+                .copied()
+                .map(Value::ScalarFloat)
+                .collect::<Vec<_>>()
+        };
         let bulk = Bulk::new(3).unwrap();
-        let values = vec![
-            Value::ScalarInt(10),
-            Value::ScalarInt(20),
-            Value::ScalarInt(30),
-        ];
-        let bulk = bulk.set(&registry, "age", values).unwrap();
+        let columns = vec![floats(&[1.0, 2.0, 3.0]), floats(&[4.0, 5.0, 6.0])];
+        let bulk = bulk.set_group(&registry, "flat", columns).unwrap();
+        let bulk = bulk
+            .set_broadcast(&registry, "mass", Value::ScalarFloat(1.0))
+            .unwrap();
 
-        let result = bulk.get(&registry, "age").unwrap();
-        if let Value::VectorInt(v) = result {
-            assert_eq!(v, vec![10, 20, 30]);
-        } else {
-            panic!("Expected VectorInt");
-        }
+        let position = bulk.get_group(&registry, "position").unwrap();
+        assert_eq!(
+            position.keys().collect::<Vec<_>>(),
+            vec!["x", "x_copy", "y"]
+        );
+        assert_eq!(position["x_copy"], Value::VectorFloat(vec![1.0, 2.0, 3.0]));
+
+        let selected = bulk.select_group(&registry, "position").unwrap();
+        assert_eq!(selected.list_data_fields(), vec!["x", "y"]);
+        assert_eq!(selected.count(), 3);
+        assert_eq!(
+            selected.get(&registry, "x_copy").unwrap(),
+            position["x_copy"]
+        );
+        assert!(bulk.get(&registry, "mass").is_ok() && selected.get(&registry, "mass").is_err());
+
+        let invalid = |result: Result<Bulk>| matches!(result, Err(SoAKitError::InvalidArgument(_)));
+        assert!(invalid(bulk.set_group(
+            &registry,
+            "flat",
+            vec![floats(&[1.0, 2.0, 3.0])]
+        )));
+        assert!(invalid(bulk.select_group(&registry, "missing")));
+        assert!(bulk.get_group(&registry, "missing").is_err());
+        // Nothing is set when any column of the group fails
+        let bad = vec![floats(&[7.0, 8.0, 9.0]), vec![Value::ScalarInt(1); 3]];
+        assert!(bulk.set_group(&registry, "flat", bad).is_err());
+        assert_eq!(
+            bulk.get(&registry, "x").unwrap(),
+            Value::VectorFloat(vec![1.0, 2.0, 3.0])
+        );
     }
 
     #[test]
-    fn test_set_length_mismatch() {
+    fn test_virtual_fields_resolve_on_every_access() {
+        use std::sync::atomic::{AtomicI64, Ordering};
+
+        let base = Arc::new(AtomicI64::new(100));
+        let state = Arc::clone(&base);
         let mut registry = Registry::new();
-        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry.register_int("a").unwrap();
         registry
-            .register("age".to_string(), validator, false, vec![], None)
+            .register_virtual("score", move |ctx: &DerivedContext<'_>| {
+                let base = state.load(Ordering::SeqCst);
+                Ok(Value::VectorInt(
+                    ctx.ids.iter().map(|&id| base + id as i64).collect(),
+                ))
+            })
+            .unwrap();
+        let copy: DerivedFunc = Box::new(|args: &[Value]| Ok(args[0].clone()));
+        let deps = vec!["score".to_string()];
+        registry
+            .register(
+                "copy".to_string(),
+                Box::new(|_| true),
+                true,
+                deps,
+                Some(copy),
+            )
+            .unwrap();
+        registry
+            .register_derived_elementwise("sum", &["copy", "a"], |row: &[Value]| match row {
+                [Value::ScalarInt(x), Value::ScalarInt(y)] => Ok(Value::ScalarInt(x + y)),
+                _ => Err(SoAKitError::InvalidArgument("expected ints".to_string())),
+            })
             .unwrap();
+        assert_eq!(registry.virtual_fields(), vec!["score"]);
+        assert_eq!(registry.regular_fields(), vec!["a"]);
 
-        let bulk = Bulk::new(3).unwrap();
-        let values = vec![Value::ScalarInt(10), Value::ScalarInt(20)];
-        let result = bulk.set(&registry, "age", values);
-        assert!(result.is_err());
+        let count = CHUNK_SIZE + 2;
+        let bulk = Bulk::new(count).unwrap();
+        let bulk = bulk
+            .set_broadcast(&registry, "a", Value::ScalarInt(1))
+            .unwrap();
+        let expected = |base: i64| Value::VectorInt((0..count as i64).map(|i| base + i).collect());
+        assert_eq!(bulk.get(&registry, "score").unwrap(), expected(100));
+        assert_eq!(bulk.get(&registry, "sum").unwrap(), expected(101));
+
+        // Derived fields reading virtual ones are not cached either
+        base.store(200, Ordering::SeqCst);
+        assert_eq!(bulk.get(&registry, "score").unwrap(), expected(200));
+        assert_eq!(bulk.get(&registry, "copy").unwrap(), expected(200));
+        assert_eq!(bulk.get(&registry, "sum").unwrap(), expected(201));
+        assert!(bulk.cache.borrow().is_empty());
+        assert!(!bulk.meta.versions.contains_key("score"));
+        assert!(bulk.describe(&registry).unwrap().contains_key("score"));
+
+        let values = vec![Value::ScalarInt(0); count];
+        let refused = bulk.set(&registry, "score", values);
+        assert!(matches!(refused, Err(SoAKitError::InvalidArgument(_))));
+        assert!(
+            bulk.set_broadcast(&registry, "score", Value::ScalarInt(0))
+                .is_err()
+        );
+        assert!(
+            bulk.set_element(&registry, "score", 0, Value::ScalarInt(0))
+                .is_err()
+        );
+
+        let short = |_: &DerivedContext<'_>| Ok(Value::VectorInt(vec![1]));
+        registry.register_virtual("short", short).unwrap();
+        let invalid = bulk.get(&registry, "short");
         assert!(matches!(
-            result.unwrap_err(),
-            SoAKitError::LengthMismatch { .. }
+            invalid,
+            Err(SoAKitError::DerivedOutputInvalid { .. })
         ));
+
+        // The flag follows virtual fields registered or renamed after their readers
+        let reads =
+            |registry: &Registry, name: &str| registry.get_metadata(name).unwrap().reads_virtual;
+        assert!(reads(&registry, "copy") && reads(&registry, "sum") && !reads(&registry, "a"));
+        let echo: DerivedFunc = Box::new(|args: &[Value]| Ok(args[0].clone()));
+        let deps = vec!["later".to_string()];
+        registry
+            .register(
+                "echo".to_string(),
+                Box::new(|_| true),
+                true,
+                deps,
+                Some(echo),
+            )
+            .unwrap();
+        assert!(!reads(&registry, "echo"));
+        registry.register_virtual("feed", short).unwrap();
+        assert!(!reads(&registry, "echo"));
+        registry.rename_field("feed", "later").unwrap();
+        assert!(reads(&registry, "echo"));
+        let wait: DerivedFunc = Box::new(|args: &[Value]| Ok(args[0].clone()));
+        let deps = vec!["soon".to_string()];
+        registry
+            .register(
+                "wait".to_string(),
+                Box::new(|_| true),
+                true,
+                deps,
+                Some(wait),
+            )
+            .unwrap();
+        registry.register_virtual("soon", short).unwrap();
+        assert!(reads(&registry, "wait"));
     }
 
     #[test]
-    fn test_set_all_value_types() {
+    fn test_declared_shapes_are_enforced() {
         let mut registry = Registry::new();
-
-        // Int field
-        let int_validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
         registry
-            .register("age".to_string(), int_validator, false, vec![], None)
+            .register(
+                "position".to_string(),
+                Box::new(|_| true),
+                false,
+                vec![],
+                None,
+            )
             .unwrap();
+        registry.register_int("n").unwrap();
+        registry.set_shape("position", &[3]).unwrap();
+        registry.set_shape("n", &[]).unwrap();
+        let point = |x: f64| Value::VectorFloat(vec![x, x, x]);
+        let flat = Value::VectorFloat(vec![1.0, 2.0]);
+        assert!(registry.validate("position", &point(1.0)));
+        assert!(!registry.validate("position", &flat));
 
-        // Float field
-        let float_validator = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
-        registry
-            .register("height".to_string(), float_validator, false, vec![], None)
+        let bulk = Bulk::new(2).unwrap();
+        let bulk = bulk
+            .set(&registry, "position", vec![point(1.0), point(0.0)])
             .unwrap();
+        let bulk = bulk
+            .set_element(&registry, "position", 1, point(2.0))
+            .unwrap();
+        assert_eq!(bulk.get(&registry, "position").unwrap().shape(), vec![2, 3]);
 
-        // Bool field
-        let bool_validator = Box::new(|v: &Value| matches!(v, Value::ScalarBool(_)));
+        let mismatch = bulk.set(&registry, "position", vec![point(1.0), flat.clone()]);
+        assert_eq!(
+            mismatch.err(),
+            Some(SoAKitError::ShapeMismatch {
+                field: "position".to_string(),
+                expected: vec![3],
+                actual: vec![2],
+            })
+        );
+        let shape_error =
+            |result: Result<Bulk>| matches!(result, Err(SoAKitError::ShapeMismatch { .. }));
+        assert!(shape_error(bulk.set_broadcast(
+            &registry,
+            "position",
+            flat.clone()
+        )));
+        assert!(shape_error(bulk.set_element(
+            &registry,
+            "position",
+            0,
+            flat.clone()
+        )));
+        assert!(shape_error(bulk.set(
+            &registry,
+            "n",
+            vec![Value::VectorInt(vec![1]); 2]
+        )));
+        let mut grown = bulk.clone();
+        let record = BTreeMap::from([("position".to_string(), flat.clone())]);
+        assert!(grown.push_row(&registry, record).is_err());
+
+        // Computed values are checked element by element
+        let copy: DerivedFunc = Box::new(|args: &[Value]| Ok(args[0].clone()));
+        let deps = vec!["position".to_string()];
         registry
-            .register("active".to_string(), bool_validator, false, vec![], None)
+            .register(
+                "copy".to_string(),
+                Box::new(|_| true),
+                true,
+                deps,
+                Some(copy),
+            )
             .unwrap();
+        registry.set_shape("copy", &[2]).unwrap();
+        let invalid = bulk.get(&registry, "copy");
+        assert!(matches!(
+            invalid,
+            Err(SoAKitError::DerivedOutputInvalid { .. })
+        ));
 
-        // String field
-        let str_validator = Box::new(|v: &Value| matches!(v, Value::ScalarString(_)));
+        // Every element of every modified chunk is checked
+        let mut ragged = bulk.clone();
+        let result = ragged.apply_chunks_mut(&registry, "position", |column| {
+            *column = Value::Matrix(vec![point(3.0), flat.clone()]);
+            Ok(())
+        });
+        assert!(shape_error(result.map(|()| bulk.clone())));
+        let positions = bulk.get(&registry, "position").unwrap();
+        assert_eq!(ragged.get(&registry, "position").unwrap(), positions);
+
+        // Staged elements report the shape, not a validation failure
+        let staged = bulk.transaction(&registry, |tx| tx.set_element("position", 0, flat.clone()));
+        assert!(shape_error(staged));
+
+        registry.clear_shape("position").unwrap();
+        assert!(bulk.set_broadcast(&registry, "position", flat).is_ok());
+        assert!(matches!(
+            registry.set_shape("missing", &[1]),
+            Err(SoAKitError::FieldNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_typed_fields_cast_on_import() {
+        let mut registry = Registry::new();
+        registry.register_float("weight").unwrap();
+        registry.register_int("age").unwrap();
+
+        // An untagged float written as `2` is read back as a float
+        let json = r#"[{"weight": 2, "age": 30.0}, {"weight": 2.5, "age": 40}]"#;
+        let bulk = Bulk::from_records_json(json, &registry).unwrap();
+        let weights = bulk.get(&registry, "weight").unwrap();
+        assert_eq!(weights, Value::VectorFloat(vec![2.0, 2.5]));
+        assert_eq!(
+            bulk.get(&registry, "age").unwrap(),
+            Value::VectorInt(vec![30, 40])
+        );
+        let restored = Bulk::from_records_json(&bulk.to_records_json().unwrap(), &registry);
+        assert_eq!(restored.unwrap().get(&registry, "weight").unwrap(), weights);
+
+        // Lossy casts are refused, naming the expected and actual types
+        let error = Bulk::from_records_json(r#"[{"weight": 1.0, "age": 30.5}]"#, &registry);
+        let message = error.unwrap_err().to_string();
+        assert!(message.contains("'age' at index 0"));
+        assert!(message.contains("expected Int, got Float"));
+
+        let error = bulk.set_element(&registry, "age", 0, Value::ScalarString("x".to_string()));
+        assert!(
+            error
+                .unwrap_err()
+                .to_string()
+                .ends_with("(expected Int, got String)")
+        );
+    }
+
+    #[test]
+    fn test_field_reductions_stream_over_chunks() {
+        let mut registry = Registry::new();
+        let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
         registry
-            .register("name".to_string(), str_validator, false, vec![], None)
+            .register("n".to_string(), ints, false, vec![], None)
+            .unwrap();
+        let floats = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
+        registry
+            .register("x".to_string(), floats, false, vec![], None)
             .unwrap();
 
-        let bulk = Bulk::new(2).unwrap();
-
+        let count = 2 * CHUNK_SIZE + 3;
+        let bulk = Bulk::new(count).unwrap();
         let bulk = bulk
             .set(
                 &registry,
-                "age",
-                vec![Value::ScalarInt(25), Value::ScalarInt(30)],
+                "n",
+                (0..count).map(|i| Value::ScalarInt(i as i64)).collect(),
             )
             .unwrap();
+        // The whole first chunk of "x" is NaN
+        let xs: Vec<Value> = (0..count)
+            .map(|i| Value::ScalarFloat(if i < CHUNK_SIZE { f64::NAN } else { i as f64 }))
+            .collect();
+        let bulk = bulk.set(&registry, "x", xs).unwrap();
+
+        for field in ["n", "x"] {
+            let whole = bulk.get(&registry, field).unwrap();
+            assert_eq!(
+                bulk.sum_field(&registry, field).unwrap(),
+                whole.sum().unwrap()
+            );
+            assert_eq!(
+                bulk.min_field(&registry, field).unwrap(),
+                whole.min().unwrap()
+            );
+            assert_eq!(
+                bulk.max_field(&registry, field).unwrap(),
+                whole.max().unwrap()
+            );
+            let (Value::ScalarFloat(a), Value::ScalarFloat(b)) = (
+                bulk.mean_field(&registry, field).unwrap(),
+                whole.mean().unwrap(),
+            ) else {
+                panic!("expected float means");
+            };
+            assert!((a - b).abs() < 1e-9);
+        }
+
+        let odd = bulk
+            .reduce(&registry, "n", 0usize, |acc, v| match v {
+                Value::ScalarInt(i) if i % 2 == 1 => acc + 1,
+                _ => acc,
+            })
+            .unwrap();
+        assert_eq!(odd, count / 2);
+        assert!(matches!(
+            bulk.sum_field(&registry, "missing"),
+            Err(SoAKitError::FieldNotFound(_))
+        ));
+
+        let nans = Bulk::new(2)
+            .unwrap()
+            .set(&registry, "x", vec![Value::ScalarFloat(f64::NAN); 2])
+            .unwrap();
+        assert!(nans.min_field(&registry, "x").is_err());
+        assert!(nans.mean_field(&registry, "x").is_err());
+    }
+
+    #[test]
+    fn test_rolling_carries_window_across_chunks() {
+        let mut registry = Registry::new();
+        let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("n".to_string(), ints, false, vec![], None)
+            .unwrap();
+
+        let count = 2 * CHUNK_SIZE + 7;
+        let bulk = Bulk::new(count).unwrap();
         let bulk = bulk
             .set(
                 &registry,
-                "height",
-                vec![Value::ScalarFloat(1.75), Value::ScalarFloat(1.80)],
+                "n",
+                (0..count)
+                    .map(|i| Value::ScalarInt((i % 13) as i64))
+                    .collect(),
+            )
+            .unwrap();
+        let whole = bulk.get(&registry, "n").unwrap();
+
+        for op in [RollOp::Sum, RollOp::Mean, RollOp::Min, RollOp::Max] {
+            let chunked = bulk
+                .rolling_with(&registry, "n", 5, op, RollEdge::Partial)
+                .unwrap();
+            assert_eq!(
+                chunked,
+                whole.rolling_with(5, op, RollEdge::Partial).unwrap()
+            );
+        }
+        let Value::VectorFloat(sums) = bulk.rolling(&registry, "n", 3, RollOp::Sum).unwrap() else {
+            panic!("expected floats");
+        };
+        assert_eq!(sums.len(), count);
+        assert!(sums[1].is_nan());
+        // The window ending at the first element of the second chunk spans both chunks
+        let expected: i64 = (CHUNK_SIZE - 2..=CHUNK_SIZE).map(|i| (i % 13) as i64).sum();
+        assert_eq!(sums[CHUNK_SIZE], expected as f64);
+        assert!(bulk.rolling(&registry, "n", 0, RollOp::Max).is_err());
+        assert!(bulk.rolling(&registry, "missing", 2, RollOp::Max).is_err());
+    }
+
+    #[test]
+    fn test_describe_summarizes_stored_and_derived_fields() {
+        let mut registry = Registry::new();
+        let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("n".to_string(), ints, false, vec![], None)
+            .unwrap();
+        let strings = Box::new(|v: &Value| matches!(v, Value::ScalarString(_)));
+        registry
+            .register("s".to_string(), strings, false, vec![], None)
+            .unwrap();
+        let double = Box::new(|args: &[Value]| match &args[0] {
+            Value::VectorInt(v) => Ok(Value::VectorInt(v.iter().map(|x| x * 2).collect())),
+            _ => Err(SoAKitError::InvalidArgument("expected ints".to_string())),
+        });
+        registry
+            .register(
+                "double".to_string(),
+                Box::new(|_: &Value| true),
+                true,
+                vec!["n".to_string()],
+                Some(double),
             )
             .unwrap();
+        let floats = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
+        registry
+            .register("unused".to_string(), floats, false, vec![], None)
+            .unwrap();
+
+        let bulk = Bulk::new(4).unwrap();
         let bulk = bulk
             .set(
                 &registry,
-                "active",
-                vec![Value::ScalarBool(true), Value::ScalarBool(false)],
+                "n",
+                vec![
+                    Value::ScalarInt(2),
+                    Value::ScalarInt(4),
+                    Value::ScalarInt(4),
+                    Value::ScalarInt(6),
+                ],
             )
-            .unwrap();
-        let bulk = bulk
+            .unwrap()
             .set(
                 &registry,
-                "name",
+                "s",
                 vec![
-                    Value::ScalarString("Alice".to_string()),
-                    Value::ScalarString("Bob".to_string()),
+                    Value::from("a"),
+                    Value::from("b"),
+                    Value::from("a"),
+                    Value::from("a"),
                 ],
             )
             .unwrap();
 
-        // Verify all fields
-        if let Value::VectorInt(v) = bulk.get(&registry, "age").unwrap() {
-            assert_eq!(v, vec![25, 30]);
-        } else {
-            panic!("Expected VectorInt");
-        }
-
-        if let Value::VectorFloat(v) = bulk.get(&registry, "height").unwrap() {
-            assert_eq!(v, vec![1.75, 1.80]);
-        } else {
-            panic!("Expected VectorFloat");
-        }
+        let summaries = bulk.describe(&registry).unwrap();
+        assert_eq!(
+            summaries.keys().collect::<Vec<_>>(),
+            vec!["double", "n", "s"]
+        );
+        let n = &summaries["n"];
+        assert_eq!((n.count, n.nulls, n.unique), (4, 0, 3));
+        assert_eq!(
+            (n.min.clone(), n.max.clone()),
+            (Some(Value::ScalarInt(2)), Some(Value::ScalarInt(6)))
+        );
+        assert_eq!(n.mean, Some(4.0));
+        assert!(n.std.is_some_and(|std| (std - 2.0f64.sqrt()).abs() < 1e-12));
+        assert_eq!(summaries["double"].max, Some(Value::ScalarInt(12)));
+        let s = &summaries["s"];
+        assert_eq!((s.count, s.nulls, s.unique), (4, 0, 2));
+        assert_eq!((s.mean, s.std), (None, None));
+    }
 
-        if let Value::VectorBool(v) = bulk.get(&registry, "active").unwrap() {
-            assert_eq!(v, vec![true, false]);
-        } else {
-            panic!("Expected VectorBool");
-        }
+    #[test]
+    fn test_from_columns_chunks_whole_columns() {
+        let mut registry = Registry::new();
+        let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("n".to_string(), ints, false, vec![], None)
+            .unwrap();
+        let names = Box::new(|v: &Value| matches!(v, Value::ScalarString(_) | Value::Null));
+        registry
+            .register("name".to_string(), names, false, vec![], None)
+            .unwrap();
 
-        if let Value::VectorString(v) = bulk.get(&registry, "name").unwrap() {
-            assert_eq!(v, vec!["Alice".to_string(), "Bob".to_string()]);
-        } else {
-            panic!("Expected VectorString");
-        }
+        let count = CHUNK_SIZE + 3;
+        let n = Value::VectorInt((0..count as i64).collect());
+        let name = Value::VectorOptionString {
+            len: count,
+            entries: vec![(1, "b".to_string())],
+        };
+        let columns = BTreeMap::from([
+            ("n".to_string(), n.clone()),
+            ("name".to_string(), name.clone()),
+        ]);
+        let bulk = Bulk::from_columns(&registry, columns).unwrap();
+        assert_eq!(bulk.count(), count);
+        assert_eq!(bulk.chunks.len(), 2);
+        assert_eq!(bulk.get(&registry, "n").unwrap(), n);
+        assert_eq!(bulk.get(&registry, "name").unwrap(), name);
+
+        let short = BTreeMap::from([
+            ("n".to_string(), Value::VectorInt(vec![1, 2])),
+            ("name".to_string(), Value::from(vec!["a"])),
+        ]);
+        assert!(matches!(
+            Bulk::from_columns(&registry, short),
+            Err(SoAKitError::LengthMismatch {
+                expected: 2,
+                actual: 1
+            })
+        ));
+        let scalar = BTreeMap::from([("n".to_string(), Value::ScalarInt(1))]);
+        assert!(matches!(
+            Bulk::from_columns(&registry, scalar),
+            Err(SoAKitError::InvalidArgument(_))
+        ));
+        let wrong_type = BTreeMap::from([("n".to_string(), Value::VectorFloat(vec![1.0]))]);
+        assert!(matches!(
+            Bulk::from_columns(&registry, wrong_type),
+            Err(SoAKitError::ValidationFailed(_))
+        ));
+        let unknown = BTreeMap::from([("other".to_string(), Value::VectorInt(vec![1]))]);
+        assert!(matches!(
+            Bulk::from_columns(&registry, unknown),
+            Err(SoAKitError::FieldNotFound(_))
+        ));
+        assert!(Bulk::from_columns(&registry, BTreeMap::new()).is_err());
     }
 
     #[test]
-    fn test_version_tracking() {
+    fn test_sorted_lookups_across_chunks() {
         let mut registry = Registry::new();
-        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
         registry
-            .register("age".to_string(), validator, false, vec![], None)
+            .register("n".to_string(), ints, false, vec![], None)
+            .unwrap();
+        let floats = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
+        registry
+            .register("x".to_string(), floats, false, vec![], None)
             .unwrap();
 
-        let bulk = Bulk::new(3).unwrap();
-        assert_eq!(bulk.meta.versions.get("age"), None);
+        // Every value appears twice: 0, 0, 1, 1, ...
+        let count = 2 * CHUNK_SIZE + 10;
+        let mut bulk = Bulk::new(count).unwrap();
+        bulk.set_mut(
+            &registry,
+            "n",
+            (0..count)
+                .map(|i| Value::ScalarInt((i / 2) as i64))
+                .collect(),
+        )
+        .unwrap();
+        assert!(
+            bulk.binary_search(&registry, "n", &Value::ScalarInt(3))
+                .is_err()
+        );
+        bulk.declare_sorted(&registry, "n", SortOrder::Asc).unwrap();
+        assert_eq!(bulk.sorted_order("n"), Some(SortOrder::Asc));
+        assert!(
+            bulk.declare_sorted(&registry, "n", SortOrder::Desc)
+                .is_err()
+        );
 
-        let bulk = bulk
-            .set(
-                &registry,
-                "age",
-                vec![
-                    Value::ScalarInt(10),
-                    Value::ScalarInt(20),
-                    Value::ScalarInt(30),
-                ],
-            )
-            .unwrap();
-        assert_eq!(bulk.meta.versions.get("age"), Some(&1));
+        let target = (CHUNK_SIZE / 2) as i64;
+        assert_eq!(
+            bulk.binary_search(&registry, "n", &Value::ScalarInt(target))
+                .unwrap(),
+            Some(CHUNK_SIZE)
+        );
+        assert_eq!(
+            bulk.binary_search(&registry, "n", &Value::ScalarInt(-1))
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            bulk.range_lookup("n", Value::ScalarInt(2)..Value::ScalarInt(4))
+                .unwrap(),
+            4..8
+        );
+        assert_eq!(
+            bulk.range_lookup("n", Value::ScalarInt(2)..=Value::ScalarInt(4))
+                .unwrap(),
+            4..10
+        );
+        assert_eq!(bulk.range_lookup("n", ..Value::ScalarInt(1)).unwrap(), 0..2);
+        assert_eq!(
+            bulk.range_lookup("n", Value::ScalarInt(5)..Value::ScalarInt(2))
+                .unwrap()
+                .len(),
+            0
+        );
 
-        let bulk = bulk
-            .set(
-                &registry,
-                "age",
-                vec![
-                    Value::ScalarInt(11),
-                    Value::ScalarInt(21),
-                    Value::ScalarInt(31),
-                ],
-            )
+        // Removing elements keeps the order; updating the field forgets it
+        let mut mask = vec![false; count];
+        mask[0] = true;
+        let trimmed = bulk.remove_where(&mask).unwrap();
+        assert_eq!(
+            trimmed
+                .binary_search(&registry, "n", &Value::ScalarInt(1))
+                .unwrap(),
+            Some(1)
+        );
+        let updated = bulk
+            .set_element(&registry, "n", 0, Value::ScalarInt(0))
             .unwrap();
-        assert_eq!(bulk.meta.versions.get("age"), Some(&2));
-
-        let bulk = bulk
+        assert_eq!(updated.sorted_order("n"), None);
+        let dropped = bulk.drop_field(&registry, "n").unwrap();
+        let reset = dropped
             .set(
                 &registry,
-                "age",
-                vec![
-                    Value::ScalarInt(12),
-                    Value::ScalarInt(22),
-                    Value::ScalarInt(32),
-                ],
+                "n",
+                (0..count).map(|i| Value::ScalarInt(-(i as i64))).collect(),
             )
             .unwrap();
-        assert_eq!(bulk.meta.versions.get("age"), Some(&3));
+        assert_eq!(reset.sorted_order("n"), None);
+
+        // sort_by records descending order, with NaN last
+        let xs = vec![
+            Value::ScalarFloat(1.0),
+            Value::ScalarFloat(f64::NAN),
+            Value::ScalarFloat(3.0),
+        ];
+        let small = Bulk::new(3).unwrap().set(&registry, "x", xs).unwrap();
+        let desc = small.sort_by(&registry, &[("x", SortOrder::Desc)]).unwrap();
+        assert_eq!(desc.sorted_order("x"), Some(SortOrder::Desc));
+        assert_eq!(
+            desc.range_lookup("x", Value::ScalarFloat(0.0)..).unwrap(),
+            0..2
+        );
+        assert_eq!(
+            desc.range_lookup("x", ..Value::ScalarFloat(3.0)).unwrap(),
+            1..2
+        );
+        assert_eq!(
+            desc.binary_search(&registry, "x", &Value::ScalarFloat(f64::NAN))
+                .unwrap(),
+            None
+        );
+        let restored = Bulk::from_binary(&desc.to_binary().unwrap()).unwrap();
+        assert_eq!(restored.sorted_order("x"), Some(SortOrder::Desc));
     }
 
     #[test]
-    fn test_version_tracking_multiple_fields() {
+    fn test_memory_usage_by_field_chunk_and_cache() {
         let mut registry = Registry::new();
-        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
         registry
-            .register("a".to_string(), validator.clone(), false, vec![], None)
+            .register("n".to_string(), ints, false, vec![], None)
             .unwrap();
+        let strings = Box::new(|v: &Value| matches!(v, Value::ScalarString(_)));
         registry
-            .register("b".to_string(), validator, false, vec![], None)
+            .register("s".to_string(), strings, false, vec![], None)
+            .unwrap();
+        let double = Box::new(|args: &[Value]| &args[0] * &Value::ScalarInt(2));
+        registry
+            .register_chunk_safe(
+                "twice".to_string(),
+                Box::new(|_: &Value| true),
+                vec!["n".to_string()],
+                double,
+            )
             .unwrap();
 
-        let bulk = Bulk::new(2).unwrap();
-        let bulk = bulk
+        let count = CHUNK_SIZE + 10;
+        let bulk = Bulk::new(count)
+            .unwrap()
             .set(
                 &registry,
-                "a",
-                vec![Value::ScalarInt(1), Value::ScalarInt(2)],
+                "n",
+                (0..count).map(|i| Value::ScalarInt(i as i64)).collect(),
             )
-            .unwrap();
-        let bulk = bulk
+            .unwrap()
             .set(
                 &registry,
-                "b",
-                vec![Value::ScalarInt(3), Value::ScalarInt(4)],
+                "s",
+                vec![Value::ScalarString("abcd".to_string()); count],
             )
             .unwrap();
 
-        assert_eq!(bulk.meta.versions.get("a"), Some(&1));
-        assert_eq!(bulk.meta.versions.get("b"), Some(&1));
+        let usage = bulk.memory_usage();
+        assert_eq!(usage.fields["n"], count * 8);
+        assert_eq!(usage.fields["s"], count * (size_of::<String>() + 4));
+        assert_eq!(usage.chunks.len(), 2);
+        assert_eq!(usage.chunks[1], 10 * (8 + size_of::<String>() + 4));
+        assert_eq!(
+            usage.chunks.iter().sum::<usize>(),
+            usage.fields.values().sum::<usize>()
+        );
+        assert!(usage.cache.is_empty());
+        assert_eq!(usage.ids, count * size_of::<usize>());
 
-        let bulk = bulk
-            .set(
-                &registry,
-                "a",
-                vec![Value::ScalarInt(10), Value::ScalarInt(20)],
-            )
-            .unwrap();
+        let _ = bulk.get(&registry, "twice").unwrap();
+        let usage = bulk.memory_usage();
+        assert_eq!(usage.cache["twice"], count * 8);
+        assert_eq!(
+            usage.total,
+            usage.ids + usage.fields.values().sum::<usize>() + count * 8
+        );
 
-        assert_eq!(bulk.meta.versions.get("a"), Some(&2));
-        assert_eq!(bulk.meta.versions.get("b"), Some(&1));
+        let interned = bulk.intern_field("s").unwrap().memory_usage();
+        assert!(interned.fields["s"] < usage.fields["s"]);
     }
 
     #[test]
-    fn test_derived_field_caching() {
-        let mut registry = Registry::new();
-        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    fn test_set_element_recomputes_only_changed_chunks() {
+        use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
 
+        let mut registry = Registry::new();
+        let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
         registry
-            .register("a".to_string(), validator.clone(), false, vec![], None)
+            .register("a".to_string(), ints, false, vec![], None)
             .unwrap();
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        let double = Box::new(|args: &[Value]| {
+            let _ = CALLS.fetch_add(1, AtomicOrdering::SeqCst);
+            &args[0] * &Value::ScalarInt(2)
+        });
         registry
-            .register("b".to_string(), validator.clone(), false, vec![], None)
+            .register_chunk_safe(
+                "twice".to_string(),
+                Box::new(|_: &Value| true),
+                vec!["a".to_string()],
+                double,
+            )
             .unwrap();
-
-        let derived_func = Box::new(|args: &[Value]| {
-            if let (Value::VectorInt(a), Value::VectorInt(b)) = (&args[0], &args[1]) {
-                let sum: Vec<i64> = a.iter().zip(b.iter()).map(|(x, y)| x + y).collect();
-                Ok(Value::VectorInt(sum))
-            } else {
-                Err(SoAKitError::InvalidArgument(
-                    "Invalid arguments".to_string(),
-                ))
-            }
-        });
+        let plus_one = Box::new(|args: &[Value]| &args[0] + &Value::ScalarInt(1));
         registry
             .register(
-                "sum".to_string(),
-                validator,
+                "twice_plus_one".to_string(),
+                Box::new(|_: &Value| true),
                 true,
-                vec!["a".to_string(), "b".to_string()],
-                Some(derived_func),
+                vec!["twice".to_string()],
+                Some(plus_one),
             )
             .unwrap();
 
-        let bulk = Bulk::new(3).unwrap();
-        let bulk = bulk
+        let count = 3 * CHUNK_SIZE;
+        let mut bulk = Bulk::new(count)
+            .unwrap()
             .set(
                 &registry,
                 "a",
-                vec![
-                    Value::ScalarInt(10),
-                    Value::ScalarInt(20),
-                    Value::ScalarInt(30),
-                ],
-            )
-            .unwrap();
-        let bulk = bulk
-            .set(
-                &registry,
-                "b",
-                vec![
-                    Value::ScalarInt(5),
-                    Value::ScalarInt(15),
-                    Value::ScalarInt(25),
-                ],
+                (0..count).map(|i| Value::ScalarInt(i as i64)).collect(),
             )
             .unwrap();
+        let expected = |bulk: &Bulk| match bulk.get(&registry, "a").unwrap() {
+            Value::VectorInt(v) => Value::VectorInt(v.iter().map(|x| x * 2).collect()),
+            other => panic!("unexpected {:?}", other),
+        };
+        let _ = bulk.get(&registry, "twice_plus_one").unwrap();
+        assert_eq!(CALLS.load(AtomicOrdering::SeqCst), 3);
+        // Cached derived fields depending on derived fields can be read again
+        let _ = bulk.get(&registry, "twice_plus_one").unwrap();
 
-        // First get should compute
-        let sum1 = bulk.get(&registry, "sum").unwrap();
-        if let Value::VectorInt(v) = sum1 {
-            assert_eq!(v, vec![15, 35, 55]);
-        } else {
-            panic!("Expected VectorInt");
-        }
+        bulk.set_element_mut(&registry, "a", CHUNK_SIZE + 5, Value::ScalarInt(-1))
+            .unwrap();
+        assert_eq!(bulk.chunk_version("a", 0), Some(1));
+        assert_eq!(bulk.chunk_version("a", 1), Some(2));
+        assert_eq!(bulk.chunk_version("a", 2), Some(1));
+        assert_eq!(bulk.chunk_version("a", 3), None);
+        let restored = Bulk::from_binary(&bulk.to_binary().unwrap()).unwrap();
+        let versions = |bulk: &Bulk| {
+            (0..3)
+                .map(|i| bulk.chunk_version("a", i))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(versions(&restored), versions(&bulk));
+        assert_eq!(bulk.get(&registry, "twice").unwrap(), expected(&bulk));
+        assert_eq!(CALLS.load(AtomicOrdering::SeqCst), 4);
+        let plus = bulk.get(&registry, "twice_plus_one").unwrap();
+        assert_eq!(
+            plus.get_element(CHUNK_SIZE + 5).unwrap(),
+            Value::ScalarInt(-1)
+        );
 
-        // Second get should use cache
-        let sum2 = bulk.get(&registry, "sum").unwrap();
-        if let Value::VectorInt(v) = sum2 {
-            assert_eq!(v, vec![15, 35, 55]);
-        } else {
-            panic!("Expected VectorInt");
-        }
+        // Updates to several chunks, on a clone sharing the cache
+        let updated = bulk
+            .set_element(&registry, "a", 0, Value::ScalarInt(7))
+            .unwrap()
+            .set_element(&registry, "a", 2 * CHUNK_SIZE, Value::ScalarInt(8))
+            .unwrap();
+        assert_eq!(updated.get(&registry, "twice").unwrap(), expected(&updated));
+        assert_eq!(CALLS.load(AtomicOrdering::SeqCst), 6);
+        assert_eq!(bulk.get(&registry, "twice").unwrap(), expected(&bulk));
+        assert_eq!(CALLS.load(AtomicOrdering::SeqCst), 6);
+
+        // Whole-field updates reset the chunk versions and recompute every chunk
+        let reset = updated
+            .set(&registry, "a", vec![Value::ScalarInt(1); count])
+            .unwrap();
+        assert_eq!(reset.chunk_version("a", 0), Some(reset.meta.versions["a"]));
+        assert_eq!(reset.get(&registry, "twice").unwrap(), expected(&reset));
+        assert_eq!(CALLS.load(AtomicOrdering::SeqCst), 9);
     }
 
     #[test]
-    fn test_cache_invalidation() {
+    fn test_validate_all_reports_every_violation() {
         let mut registry = Registry::new();
-        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
-
+        let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
         registry
-            .register("a".to_string(), validator.clone(), false, vec![], None)
+            .register("n".to_string(), ints, false, vec![], None)
             .unwrap();
+        let any = Box::new(|_: &Value| true);
         registry
-            .register("b".to_string(), validator.clone(), false, vec![], None)
+            .register("tag".to_string(), any, false, vec![], None)
             .unwrap();
 
-        let derived_func = Box::new(|args: &[Value]| {
-            if let (Value::VectorInt(a), Value::VectorInt(b)) = (&args[0], &args[1]) {
-                let sum: Vec<i64> = a.iter().zip(b.iter()).map(|(x, y)| x + y).collect();
-                Ok(Value::VectorInt(sum))
-            } else {
-                Err(SoAKitError::InvalidArgument(
-                    "Invalid arguments".to_string(),
-                ))
+        let count = CHUNK_SIZE + 3;
+        let mut tags = vec![Value::ScalarString("ok".to_string()); count];
+        tags[1] = Value::ScalarString(String::new());
+        tags[CHUNK_SIZE + 2] = Value::ScalarString(String::new());
+        let bulk = Bulk::new(count)
+            .unwrap()
+            .set(
+                &registry,
+                "n",
+                (0..count).map(|i| Value::ScalarInt(i as i64 % 7)).collect(),
+            )
+            .unwrap()
+            .set(&registry, "tag", tags)
+            .unwrap();
+        let bulk = Bulk::from_binary(&bulk.to_binary().unwrap()).unwrap();
+        assert!(bulk.validate_all(&registry).unwrap().is_empty());
+
+        let mut strict = Registry::new();
+        let small = Box::new(|v: &Value| matches!(v, Value::ScalarInt(i) if *i < 6));
+        strict
+            .register("n".to_string(), small, false, vec![], None)
+            .unwrap();
+        let non_empty = Box::new(|v: &Value| matches!(v, Value::ScalarString(s) if !s.is_empty()));
+        strict
+            .register("tag".to_string(), non_empty, false, vec![], None)
+            .unwrap();
+        let violations = bulk.validate_all(&strict).unwrap();
+        let sixes = (0..count).filter(|i| i % 7 == 6).count();
+        assert_eq!(violations.len(), sixes + 2);
+        assert!(
+            violations[..sixes]
+                .iter()
+                .all(|v| v.field == "n" && v.value == Value::ScalarInt(6))
+        );
+        assert_eq!(violations[sixes].index, 1);
+        assert_eq!(
+            violations[sixes + 1],
+            Violation {
+                field: "tag".to_string(),
+                index: CHUNK_SIZE + 2,
+                value: Value::ScalarString(String::new()),
             }
+        );
+
+        let mut partial = Registry::new();
+        let any = Box::new(|_: &Value| true);
+        partial
+            .register("n".to_string(), any, false, vec![], None)
+            .unwrap();
+        assert!(matches!(
+            bulk.validate_all(&partial),
+            Err(SoAKitError::FieldNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_bulk_equality_ignores_versions_cache_and_layout() {
+        let mut registry = Registry::new();
+        let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("n".to_string(), ints, false, vec![], None)
+            .unwrap();
+        let double: DerivedFunc = Box::new(|args: &[Value]| match args.first() {
+            Some(Value::VectorInt(v)) => Ok(Value::VectorInt(v.iter().map(|x| x * 2).collect())),
+            _ => Err(SoAKitError::InvalidArgument("expected ints".to_string())),
         });
         registry
             .register(
-                "sum".to_string(),
-                validator,
+                "double".to_string(),
+                Box::new(|_| true),
                 true,
-                vec!["a".to_string(), "b".to_string()],
-                Some(derived_func),
+                vec!["n".to_string()],
+                Some(double),
             )
             .unwrap();
 
-        let bulk = Bulk::new(2).unwrap();
-        let bulk = bulk
-            .set(
-                &registry,
-                "a",
-                vec![Value::ScalarInt(10), Value::ScalarInt(20)],
-            )
+        let count = CHUNK_SIZE + 3;
+        let values: Vec<Value> = (0..count)
+            .map(|i| Value::ScalarInt(i64::try_from(i).unwrap()))
+            .collect();
+        let expected = Bulk::new(count)
+            .unwrap()
+            .set(&registry, "n", values.clone())
             .unwrap();
-        let bulk = bulk
-            .set(
-                &registry,
-                "b",
-                vec![Value::ScalarInt(5), Value::ScalarInt(15)],
-            )
+
+        // Built in two steps with a different version and a filled cache
+        let mut actual = Bulk::new(1)
+            .unwrap()
+            .set(&registry, "n", values[..1].to_vec())
+            .unwrap();
+        let records = values[1..]
+            .iter()
+            .map(|value| BTreeMap::from([("n".to_string(), value.clone())]))
+            .collect();
+        actual.extend_rows(&registry, records).unwrap();
+        let _ = actual.get(&registry, "double").unwrap();
+        assert_ne!(actual.meta.versions, expected.meta.versions);
+        assert_eq!(actual, expected);
+        assert_eq!(actual.content_hash(), expected.content_hash());
+
+        let changed = actual
+            .set_element(&registry, "n", CHUNK_SIZE, Value::ScalarInt(-1))
+            .unwrap();
+        assert_ne!(changed, expected);
+        assert_ne!(changed.content_hash(), expected.content_hash());
+        assert_ne!(Bulk::new(2).unwrap(), Bulk::new(3).unwrap());
+
+        // Tombstones and custom identifiers are part of the data
+        let mut mask = vec![false; count];
+        mask[1] = true;
+        let deleted = expected.mark_deleted(&mask).unwrap();
+        assert_ne!(deleted, expected);
+        assert_ne!(deleted.content_hash(), expected.content_hash());
+        assert_eq!(deleted, actual.mark_deleted(&mask).unwrap());
+        let named = expected
+            .set_ids((0..count).map(|i| format!("e{}", i)))
+            .unwrap();
+        let renamed = expected
+            .set_ids((0..count).map(|i| format!("f{}", i)))
             .unwrap();
+        assert_ne!(named, expected);
+        assert_ne!(named, renamed);
+        assert_ne!(named.content_hash(), renamed.content_hash());
+        assert_eq!(
+            named,
+            actual
+                .set_ids((0..count).map(|i| format!("e{}", i)))
+                .unwrap()
+        );
+    }
 
-        // Get sum (should compute and cache)
-        let _sum1 = bulk.get(&registry, "sum").unwrap();
+    #[test]
+    fn test_custom_ids_follow_elements() {
+        let mut registry = Registry::new();
+        let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("n".to_string(), ints, false, vec![], None)
+            .unwrap();
+        let values = vec![
+            Value::ScalarInt(3),
+            Value::ScalarInt(1),
+            Value::ScalarInt(2),
+        ];
+        let bulk = Bulk::from_ids(["c", "a", "b"])
+            .unwrap()
+            .set(&registry, "n", values)
+            .unwrap();
+        assert_eq!(bulk.index_of_id("b"), Some(2));
+        // Elements without a custom id are found by their numeric id
+        assert_eq!(Bulk::new(3).unwrap().index_of_id(2u64), Some(2));
 
-        // Update dependency 'a'
-        let bulk = bulk
+        let sorted = bulk.sort_by(&registry, &[("n", SortOrder::Asc)]).unwrap();
+        assert_eq!(
+            sorted.ids(),
+            vec!["a".into(), "b".into(), ElementId::from("c")]
+        );
+        let tail = sorted.tail(2).unwrap();
+        assert_eq!(tail.ids(), vec![ElementId::from("b"), ElementId::from("c")]);
+        assert_eq!(tail.meta.custom_ids.len(), 2);
+
+        // Appended elements bring their ids; extended ones have none
+        let extra = Bulk::from_ids([ElementId::Int(40)])
+            .unwrap()
+            .set(&registry, "n", vec![Value::ScalarInt(4)])
+            .unwrap();
+        let mut merged = tail.merge_by_key(&extra, &registry, "n").unwrap();
+        assert_eq!(merged.index_of_id(40u64), Some(2));
+        let record = BTreeMap::from([("n".to_string(), Value::ScalarInt(5))]);
+        merged.extend_rows(&registry, vec![record]).unwrap();
+        let last = merged.meta.id[3];
+        assert_eq!(
+            merged.ids()[3],
+            ElementId::Int(u64::try_from(last).unwrap())
+        );
+        // The lookup built before extending was dropped
+        assert_eq!(merged.index_of_id(u64::try_from(last).unwrap()), Some(3));
+        assert_eq!(merged.index_of_id("c"), Some(1));
+        assert_eq!(merged.index_of_id("missing"), None);
+
+        // Appended rows skip the numeric custom ids already in use
+        let mut numbered = Bulk::from_ids([1u64, 2])
+            .unwrap()
             .set(
                 &registry,
-                "a",
-                vec![Value::ScalarInt(100), Value::ScalarInt(200)],
+                "n",
+                vec![Value::ScalarInt(1), Value::ScalarInt(2)],
             )
             .unwrap();
+        numbered
+            .push_row(
+                &registry,
+                BTreeMap::from([("n".to_string(), Value::ScalarInt(3))]),
+            )
+            .unwrap();
+        assert_eq!(
+            numbered.ids(),
+            vec![ElementId::Int(1), ElementId::Int(2), ElementId::Int(3)]
+        );
+        assert_eq!(
+            (numbered.index_of_id(2u64), numbered.index_of_id(3u64)),
+            (Some(1), Some(2))
+        );
 
-        // Get sum again (should recompute due to cache invalidation)
-        let sum2 = bulk.get(&registry, "sum").unwrap();
-        if let Value::VectorInt(v) = sum2 {
-            assert_eq!(v, vec![105, 215]);
-        } else {
-            panic!("Expected VectorInt");
-        }
-    }
-
-    #[test]
-    fn test_get_nonexistent_field() {
-        let registry = Registry::new();
-        let bulk = Bulk::new(3).unwrap();
-        let result = bulk.get(&registry, "nonexistent");
-        assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), SoAKitError::FieldNotFound(_)));
-    }
-
-    #[test]
-    fn test_set_nonexistent_field() {
-        let registry = Registry::new();
-        let bulk = Bulk::new(3).unwrap();
-        let values = vec![
-            Value::ScalarInt(10),
-            Value::ScalarInt(20),
-            Value::ScalarInt(30),
-        ];
-        let result = bulk.set(&registry, "nonexistent", values);
-        assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), SoAKitError::FieldNotFound(_)));
+        // Merges refuse to give two elements the same identifier
+        let clash = Bulk::from_ids(["b"])
+            .unwrap()
+            .set(&registry, "n", vec![Value::ScalarInt(9)]);
+        let clash = tail.merge_by_key(&clash.unwrap(), &registry, "n");
+        assert!(matches!(clash, Err(SoAKitError::InvalidArgument(_))));
+        let twice = Bulk::from_ids([ElementId::Int(40)])
+            .unwrap()
+            .set(&registry, "n", vec![Value::ScalarInt(8)])
+            .unwrap();
+        let clash = merged.merge_by_key(&twice, &registry, "n");
+        assert!(matches!(clash, Err(SoAKitError::InvalidArgument(_))));
+
+        let restored = Bulk::from_json(&bulk.to_json().unwrap()).unwrap();
+        assert_eq!(restored.ids(), bulk.ids());
+        let restored = Bulk::from_binary(&bulk.to_binary().unwrap()).unwrap();
+        assert_eq!(restored.ids(), bulk.ids());
+        assert_eq!(restored.index_of_id("a"), Some(1));
+
+        assert!(matches!(
+            bulk.set_ids(["x", "y"]),
+            Err(SoAKitError::LengthMismatch {
+                expected: 3,
+                actual: 2
+            })
+        ));
+        assert!(matches!(
+            bulk.set_ids(["x", "y", "x"]),
+            Err(SoAKitError::InvalidArgument(_))
+        ));
     }
 
     #[test]
-    fn test_set_validation_failure() {
+    fn test_resize_grows_across_chunks_and_truncates() {
         let mut registry = Registry::new();
-        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
         registry
-            .register("age".to_string(), validator, false, vec![], None)
+            .register("n".to_string(), ints, false, vec![], None)
+            .unwrap();
+        let optional = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_) | Value::Null));
+        registry
+            .register("opt".to_string(), optional, false, vec![], None)
             .unwrap();
 
-        let bulk = Bulk::new(3).unwrap();
-        let values = vec![
-            Value::ScalarFloat(10.0),
-            Value::ScalarFloat(20.0),
-            Value::ScalarFloat(30.0),
-        ];
-        let result = bulk.set(&registry, "age", values);
-        assert!(result.is_err());
+        let bulk = Bulk::new(2)
+            .unwrap()
+            .set(&registry, "n", vec![Value::ScalarInt(1); 2])
+            .unwrap()
+            .set(&registry, "opt", vec![Value::ScalarInt(2); 2])
+            .unwrap();
+        let defaults = BTreeMap::from([("n".to_string(), Value::ScalarInt(7))]);
+        let count = CHUNK_SIZE + 5;
+        let grown = bulk.resize(&registry, count, &defaults).unwrap();
+        assert_eq!(grown.count(), count);
+        assert_eq!(grown.chunks.len(), 2);
+        assert_eq!(grown.meta.id, (0..count).collect::<Vec<_>>());
+        let n = grown.get(&registry, "n").unwrap().to_scalars();
+        assert_eq!(n[1], Value::ScalarInt(1));
+        assert!(n[2..].iter().all(|v| *v == Value::ScalarInt(7)));
+        let opt = grown.get(&registry, "opt").unwrap();
+        assert_eq!(opt.get_element(1).unwrap(), Value::ScalarInt(2));
+        assert!(opt.get_element(count - 1).unwrap().is_null());
+
+        let shrunk = grown.resize(&registry, 3, &defaults).unwrap();
+        assert_eq!(
+            shrunk.get(&registry, "n").unwrap(),
+            Value::VectorInt(vec![1, 1, 7])
+        );
+        assert_eq!(
+            shrunk.resize(&registry, 3, &BTreeMap::new()).unwrap(),
+            shrunk
+        );
+
+        // Growing needs a default for fields that reject missing values
+        assert!(bulk.resize(&registry, 3, &BTreeMap::new()).is_err());
         assert!(matches!(
-            result.unwrap_err(),
-            SoAKitError::ValidationFailed(_)
+            bulk.resize(&registry, 0, &defaults),
+            Err(SoAKitError::InvalidArgument(_))
         ));
     }
 
     #[test]
-    fn test_apply_operation() {
+    fn test_compact_repacks_chunks_and_keeps_current_caches() {
         let mut registry = Registry::new();
-        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
-        registry
-            .register("age".to_string(), validator, false, vec![], None)
-            .unwrap();
+        for name in ["n", "m"] {
+            let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+            registry
+                .register(name.to_string(), ints, false, vec![], None)
+                .unwrap();
+        }
+        for (name, dep) in [("double_n", "n"), ("double_m", "m")] {
+            let double: DerivedFunc = Box::new(|args: &[Value]| match args.first() {
+                Some(Value::VectorInt(v)) => {
+                    Ok(Value::VectorInt(v.iter().map(|x| x * 2).collect()))
+                }
+                _ => Err(SoAKitError::InvalidArgument("expected ints".to_string())),
+            });
+            registry
+                .register(
+                    name.to_string(),
+                    Box::new(|_| true),
+                    true,
+                    vec![dep.to_string()],
+                    Some(double),
+                )
+                .unwrap();
+        }
 
-        let bulk = Bulk::new(5).unwrap();
-        let bulk = bulk
-            .set(
-                &registry,
-                "age",
-                vec![
-                    Value::ScalarInt(10),
-                    Value::ScalarInt(20),
-                    Value::ScalarInt(30),
-                    Value::ScalarInt(40),
-                    Value::ScalarInt(50),
-                ],
-            )
+        let part = |start: i64| {
+            let values: Vec<Value> = (start..start + 3).map(Value::ScalarInt).collect();
+            Bulk::new(3)
+                .unwrap()
+                .set(&registry, "n", values.clone())
+                .unwrap()
+                .set(&registry, "m", values)
+                .unwrap()
+        };
+        // Two partly filled chunks
+        let mut bulk = part(0);
+        bulk.chunks.extend(part(3).chunks);
+        bulk.meta.count = 6;
+        bulk.meta.id = (0..6).collect();
+        let _ = bulk.get(&registry, "double_n").unwrap();
+        let _ = bulk.get(&registry, "double_m").unwrap();
+        bulk.set_mut(&registry, "m", (0..6).map(Value::ScalarInt).collect())
             .unwrap();
+        bulk.build_index(&registry, "n").unwrap();
+        let _ = bulk.on_change(|_, _, _| {});
+
+        let compacted = bulk.compact(&registry).unwrap();
+        assert_eq!(compacted.chunks.len(), 1);
+        assert_eq!(compacted.chunks[0].len, 6);
+        assert_eq!(compacted, bulk);
+        assert_eq!(compacted.observer_count(), 1);
+        assert_eq!(
+            compacted.cache.borrow().keys().collect::<Vec<_>>(),
+            vec!["double_n"]
+        );
+        assert_eq!(
+            compacted.lookup("n", &Value::ScalarInt(4)).unwrap(),
+            vec![4]
+        );
+        assert_eq!(
+            compacted.get(&registry, "double_m").unwrap(),
+            Value::VectorInt(vec![0, 2, 4, 6, 8, 10])
+        );
+    }
 
-        let mask = vec![true, false, true, false, true];
-        let new_bulk = bulk
-            .apply(&mask, |subset| {
-                let new_vals: Vec<Value> = subset
-                    .iter()
-                    .map(|v| {
-                        if let Value::ScalarInt(i) = v {
-                            Value::ScalarInt(i + 1)
-                        } else {
-                            v.clone()
-                        }
-                    })
-                    .collect();
-                Ok(new_vals)
-            })
-            .unwrap();
+    #[test]
+    fn test_bulk_is_shared_across_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Bulk>();
+        assert_send_sync::<crate::view::View>();
+        assert_send_sync::<crate::proxy::Proxy>();
 
-        if let Value::VectorInt(v) = new_bulk.get(&registry, "age").unwrap() {
-            assert_eq!(v, vec![11, 20, 31, 40, 51]);
-        } else {
-            panic!("Expected VectorInt");
-        }
+        let mut registry = Registry::new();
+        let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("n".to_string(), ints, false, vec![], None)
+            .unwrap();
+        let double: DerivedFunc = Box::new(|args: &[Value]| match args.first() {
+            Some(Value::VectorInt(v)) => Ok(Value::VectorInt(v.iter().map(|x| x * 2).collect())),
+            _ => Err(SoAKitError::InvalidArgument("expected ints".to_string())),
+        });
+        registry
+            .register(
+                "double".to_string(),
+                Box::new(|_| true),
+                true,
+                vec!["n".to_string()],
+                Some(double),
+            )
+            .unwrap();
+        let values = (0..100).map(Value::ScalarInt).collect();
+        let bulk = Arc::new(Bulk::new(100).unwrap().set(&registry, "n", values).unwrap());
+
+        let registry = &registry;
+        std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..4)
+                .map(|_| {
+                    let bulk = Arc::clone(&bulk);
+                    scope.spawn(move || bulk.sum_field(registry, "double").unwrap())
+                })
+                .collect();
+            for worker in workers {
+                assert_eq!(worker.join().unwrap(), Value::ScalarInt(9900));
+            }
+        });
+        assert!(bulk.cache.borrow().contains_key("double"));
     }
 
     #[test]
-    fn test_apply_empty_mask() {
+    fn test_tolerant_apply_and_import() {
         let mut registry = Registry::new();
-        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
         registry
-            .register("age".to_string(), validator, false, vec![], None)
+            .register("n".to_string(), ints, false, vec![], None)
+            .unwrap();
+        let strings = Box::new(|v: &Value| matches!(v, Value::ScalarString(_)));
+        registry
+            .register("s".to_string(), strings, false, vec![], None)
             .unwrap();
 
-        let bulk = Bulk::new(3).unwrap();
+        let bulk = Bulk::new(4).unwrap();
         let bulk = bulk
-            .set(
-                &registry,
-                "age",
-                vec![
-                    Value::ScalarInt(10),
-                    Value::ScalarInt(20),
-                    Value::ScalarInt(30),
-                ],
-            )
+            .set(&registry, "n", vec![Value::ScalarInt(1); 4])
             .unwrap();
-
-        // Empty mask should be treated as all true
-        let new_bulk = bulk
-            .apply(&[], |subset| {
-                let new_vals: Vec<Value> = subset
-                    .iter()
-                    .map(|v| {
-                        if let Value::ScalarInt(i) = v {
-                            Value::ScalarInt(i + 1)
-                        } else {
-                            v.clone()
-                        }
-                    })
-                    .collect();
-                Ok(new_vals)
+        let (applied, errors) = bulk
+            .apply_tolerant(&registry, &["n"], &[true, true, false, true], |id, v| {
+                // Strings fail validation
+                Ok(if id == 3 {
+                    Value::ScalarString("x".to_string())
+                } else {
+                    v.clone()
+                })
             })
             .unwrap();
+        assert_eq!(
+            applied.get(&registry, "n").unwrap(),
+            Value::VectorInt(vec![1; 4])
+        );
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].index, 3);
+        assert!(matches!(errors[0].error, SoAKitError::ValidationFailed(_)));
+        assert!(matches!(
+            bulk.apply_tolerant(&registry, &["s"], &[], |_, v| Ok(v.clone())),
+            Err(SoAKitError::FieldNotFound(_))
+        ));
 
-        if let Value::VectorInt(v) = new_bulk.get(&registry, "age").unwrap() {
-            assert_eq!(v, vec![11, 21, 31]);
-        } else {
-            panic!("Expected VectorInt");
-        }
+        // A record failing both fields is reported once per field
+        let json = r#"[{"n": 1, "s": "a"}, {"n": "b"}, {"n": 2, "s": "c"}]"#;
+        let (imported, errors) = Bulk::from_records_json_tolerant(json, &registry).unwrap();
+        assert_eq!(imported.count(), 2);
+        assert_eq!(imported.meta.id, vec![0, 2]);
+        let fields: Vec<_> = errors
+            .iter()
+            .map(|e| (e.index, e.field.as_deref()))
+            .collect();
+        assert_eq!(fields, vec![(1, Some("n")), (1, Some("s"))]);
+        assert!(Bulk::from_records_json_tolerant(r#"[{"n": "b"}]"#, &registry).is_err());
+        assert!(Bulk::from_records_json_tolerant("{", &registry).is_err());
     }
 
     #[test]
-    fn test_apply_mask_length_mismatch() {
+    fn test_partition_by_multi() {
         let mut registry = Registry::new();
-        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        let strings = Box::new(|v: &Value| matches!(v, Value::ScalarString(_)));
         registry
-            .register("age".to_string(), validator, false, vec![], None)
+            .register("region".to_string(), strings, false, vec![], None)
+            .unwrap();
+        let floats = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
+        registry
+            .register("score".to_string(), floats, false, vec![], None)
             .unwrap();
 
-        let bulk = Bulk::new(3).unwrap();
-        let bulk = bulk
-            .set(
-                &registry,
-                "age",
-                vec![
-                    Value::ScalarInt(10),
-                    Value::ScalarInt(20),
-                    Value::ScalarInt(30),
-                ],
-            )
+        let region = |s: &str| Value::ScalarString(s.to_string());
+        let bulk = Bulk::new(5).unwrap();
+        let regions = vec![
+            region("us"),
+            region("eu"),
+            region("us"),
+            region("eu"),
+            region("us"),
+        ];
+        let scores = [f64::NAN, 1.0, f64::NAN, 1.0, 0.0]
+            .map(Value::ScalarFloat)
+            .to_vec();
+        let bulk = bulk.set(&registry, "region", regions).unwrap();
+        let bulk = bulk.set(&registry, "score", scores).unwrap();
+
+        let views = bulk
+            .partition_by_multi(&registry, &["region", "score"])
             .unwrap();
+        let keys: Vec<_> = views
+            .iter()
+            .map(|v| (v.key().clone(), v.mask().to_vec()))
+            .collect();
+        assert_eq!(keys.len(), 3);
+        assert_eq!(
+            keys[0],
+            (
+                Value::Matrix(vec![region("eu"), Value::ScalarFloat(1.0)]),
+                vec![false, true, false, true, false]
+            )
+        );
+        // NaNs share a group, ordered after other floats
+        assert_eq!(views[1].mask(), &[false, false, false, false, true]);
+        assert_eq!(views[2].mask(), &[true, false, true, false, false]);
 
-        let mask = vec![true, false]; // Wrong length
-        let result = bulk.apply(&mask, |subset| Ok(subset.to_vec()));
-        assert!(result.is_err());
+        // A single field groups like partition_by
+        assert_eq!(
+            bulk.partition_by_multi(&registry, &["region"])
+                .unwrap()
+                .len(),
+            2
+        );
         assert!(matches!(
-            result.unwrap_err(),
-            SoAKitError::LengthMismatch { .. }
+            bulk.partition_by_multi(&registry, &[]),
+            Err(SoAKitError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            bulk.partition_by_multi(&registry, &["region", "missing"]),
+            Err(SoAKitError::FieldNotFound(_))
         ));
     }
 
     #[test]
-    fn test_partition_by_int() {
+    fn test_partition_by_bins_and_quantiles() {
         let mut registry = Registry::new();
-        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        let floats = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_) | Value::Null));
         registry
-            .register("category".to_string(), validator, false, vec![], None)
+            .register("x".to_string(), floats, false, vec![], None)
             .unwrap();
 
-        let bulk = Bulk::new(6).unwrap();
-        let bulk = bulk
-            .set(
-                &registry,
-                "category",
-                vec![
-                    Value::ScalarInt(1),
-                    Value::ScalarInt(2),
-                    Value::ScalarInt(1),
-                    Value::ScalarInt(3),
-                    Value::ScalarInt(2),
-                    Value::ScalarInt(1),
-                ],
-            )
-            .unwrap();
+        let values = vec![
+            Value::ScalarFloat(-1.0),
+            Value::ScalarFloat(0.0),
+            Value::ScalarFloat(10.0),
+            Value::Null,
+            Value::ScalarFloat(f64::NAN),
+            Value::ScalarFloat(20.0),
+            Value::ScalarFloat(25.0),
+        ];
+        let bulk = Bulk::new(7).unwrap().set(&registry, "x", values).unwrap();
 
-        let views = bulk.partition_by(&registry, "category").unwrap();
-        assert_eq!(views.len(), 3);
+        let views = bulk
+            .partition_by_bins(&registry, "x", &[0.0, 10.0, 20.0])
+            .unwrap();
+        let masks: Vec<Vec<bool>> = views.iter().map(|v| v.mask().to_vec()).collect();
+        assert_eq!(
+            masks,
+            vec![
+                vec![false, true, false, false, false, false, false],
+                vec![false, false, true, false, false, true, false],
+                vec![true, false, false, true, true, false, true],
+            ]
+        );
+        for edges in [&[0.0][..], &[1.0, 1.0], &[2.0, 1.0], &[0.0, f64::INFINITY]] {
+            assert!(matches!(
+                bulk.partition_by_bins(&registry, "x", edges),
+                Err(SoAKitError::InvalidArgument(_))
+            ));
+        }
 
-        // Find view for category 1
-        let view_1 = views
-            .iter()
-            .find(|v| {
-                if let Value::ScalarInt(i) = v.key() {
-                    *i == 1
-                } else {
-                    false
-                }
-            })
+        // Quantile edges ignore NaNs and missing elements
+        let views = bulk.partition_by_quantiles(&registry, "x", 4).unwrap();
+        let keys: Vec<Value> = views.iter().map(|v| v.key().clone()).collect();
+        assert_eq!(keys.len(), 5);
+        assert_eq!(keys[0], Value::VectorFloat(vec![-1.0, 0.0]));
+        assert_eq!(keys[3], Value::VectorFloat(vec![20.0, 25.0]));
+        assert_eq!(views[4].count(), 2);
+        assert!(bulk.partition_by_quantiles(&registry, "x", 0).is_err());
+
+        // Equal values collapse into one bin
+        let same = Bulk::new(3)
+            .unwrap()
+            .set(&registry, "x", vec![Value::ScalarFloat(2.0); 3]);
+        let views = same
+            .unwrap()
+            .partition_by_quantiles(&registry, "x", 3)
             .unwrap();
-        assert_eq!(view_1.count(), 3);
+        assert_eq!(views.len(), 2);
+        assert_eq!(views[0].count(), 3);
     }
 
     #[test]
-    fn test_partition_by_string() {
+    fn test_partition_by_fn() {
         let mut registry = Registry::new();
-        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarString(_)));
+        let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
         registry
-            .register("category".to_string(), validator, false, vec![], None)
+            .register("n".to_string(), ints, false, vec![], None)
             .unwrap();
+        let values = (0..7).map(Value::ScalarInt).collect();
+        let bulk = Bulk::new(7).unwrap().set(&registry, "n", values).unwrap();
 
-        let bulk = Bulk::new(4).unwrap();
-        let bulk = bulk
-            .set(
-                &registry,
-                "category",
-                vec![
-                    Value::ScalarString("A".to_string()),
-                    Value::ScalarString("B".to_string()),
-                    Value::ScalarString("A".to_string()),
-                    Value::ScalarString("C".to_string()),
-                ],
-            )
+        let views = bulk
+            .partition_by_fn(|row| match row.get_field(&registry, "n")? {
+                Value::ScalarInt(n) => Ok(Value::ScalarInt(n.rem_euclid(3))),
+                other => Ok(other),
+            })
             .unwrap();
+        let counts: Vec<_> = views.iter().map(|v| (v.key().clone(), v.count())).collect();
+        assert_eq!(
+            counts,
+            vec![
+                (Value::ScalarInt(0), 3),
+                (Value::ScalarInt(1), 2),
+                (Value::ScalarInt(2), 2),
+            ]
+        );
+        assert!(
+            views
+                .iter()
+                .all(|v| Arc::ptr_eq(&v.parent, &views[0].parent))
+        );
 
-        let views = bulk.partition_by(&registry, "category").unwrap();
-        assert_eq!(views.len(), 3);
+        let failing = bulk.partition_by_fn(|row| row.get_field(&registry, "missing"));
+        assert!(matches!(failing, Err(SoAKitError::FieldNotFound(_))));
     }
 
     #[test]
-    fn test_partition_by_float() {
+    fn test_top_k() {
         let mut registry = Registry::new();
-        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
+        let floats = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
         registry
-            .register("value".to_string(), validator, false, vec![], None)
+            .register("score".to_string(), floats, false, vec![], None)
+            .unwrap();
+
+        // Spread over several chunks, with ties and a NaN
+        let count = CHUNK_SIZE + 10;
+        let values = (0..count)
+            .map(|i| match i {
+                5 => Value::ScalarFloat(f64::NAN),
+                _ => Value::ScalarFloat(f64::from(u32::try_from(i % 100).unwrap())),
+            })
+            .collect();
+        let bulk = Bulk::new(count)
+            .unwrap()
+            .set(&registry, "score", values)
+            .unwrap();
+
+        let top = bulk
+            .top_k_positions(&registry, "score", 3, SortOrder::Desc)
+            .unwrap();
+        assert_eq!(top, vec![99, 199, 299]);
+        let bottom = bulk
+            .top_k_positions(&registry, "score", 2, SortOrder::Asc)
             .unwrap();
+        assert_eq!(bottom, vec![0, 100]);
 
-        let bulk = Bulk::new(4).unwrap();
-        let bulk = bulk
-            .set(
-                &registry,
-                "value",
-                vec![
-                    Value::ScalarFloat(1.0),
-                    Value::ScalarFloat(2.0),
-                    Value::ScalarFloat(1.0),
-                    Value::ScalarFloat(3.0),
-                ],
-            )
+        // Same elements as a full sort followed by head
+        let sorted = bulk
+            .sort_by(&registry, &[("score", SortOrder::Asc)])
             .unwrap();
+        let expected = sorted.head(4).unwrap();
+        let top = bulk.top_k(&registry, "score", 4, SortOrder::Asc).unwrap();
+        assert_eq!(top.meta.id, expected.meta.id);
+        assert_eq!(
+            top.get(&registry, "score").unwrap(),
+            expected.get(&registry, "score").unwrap()
+        );
 
-        let views = bulk.partition_by(&registry, "value").unwrap();
-        assert_eq!(views.len(), 3);
+        // NaN ranks last, and k beyond the count keeps everything
+        let small = bulk.head(6).unwrap();
+        let all = small
+            .top_k_positions(&registry, "score", 10, SortOrder::Desc)
+            .unwrap();
+        assert_eq!(all, vec![4, 3, 2, 1, 0, 5]);
+        assert!(matches!(
+            bulk.top_k(&registry, "score", 0, SortOrder::Desc),
+            Err(SoAKitError::InvalidArgument(_))
+        ));
+        assert!(
+            bulk.top_k(&registry, "missing", 1, SortOrder::Desc)
+                .is_err()
+        );
     }
 
     #[test]
-    fn test_partition_by_bool() {
+    fn test_value_counts() {
         let mut registry = Registry::new();
-        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarBool(_)));
+        let floats = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
         registry
-            .register("flag".to_string(), validator, false, vec![], None)
+            .register("x".to_string(), floats, false, vec![], None)
             .unwrap();
 
-        let bulk = Bulk::new(4).unwrap();
-        let bulk = bulk
-            .set(
-                &registry,
-                "flag",
-                vec![
-                    Value::ScalarBool(true),
-                    Value::ScalarBool(false),
-                    Value::ScalarBool(true),
-                    Value::ScalarBool(false),
-                ],
-            )
+        let count = CHUNK_SIZE + 3;
+        let values = (0..count)
+            .map(|i| match i % 3 {
+                0 => Value::ScalarFloat(f64::NAN),
+                1 => Value::ScalarFloat(-0.0),
+                _ => Value::ScalarFloat(if i == 2 { 0.0 } else { 1.0 }),
+            })
+            .collect();
+        let bulk = Bulk::new(count)
+            .unwrap()
+            .set(&registry, "x", values)
             .unwrap();
 
-        let views = bulk.partition_by(&registry, "flag").unwrap();
-        assert_eq!(views.len(), 2);
-    }
+        let counts = bulk.value_counts(&registry, "x").unwrap();
+        let third = count / 3;
+        // -0.0 and 0.0 are counted together, as are all NaNs
+        assert_eq!(counts.len(), 3);
+        assert_eq!(counts[0], (Value::ScalarFloat(-0.0), third + 1));
+        // Equal counts are in key order, which puts NaN after the other floats
+        assert!(matches!(counts[1], (Value::ScalarFloat(x), n) if x.is_nan() && n == third + 1));
+        assert_eq!(counts[2], (Value::ScalarFloat(1.0), third - 1));
+        assert_eq!(counts.iter().map(|c| c.1).sum::<usize>(), count);
+        let views = bulk.partition_by(&registry, "x").unwrap();
+        assert_eq!(views.len(), counts.len());
 
-    #[test]
-    fn test_partition_nonexistent_field() {
-        let registry = Registry::new();
-        let bulk = Bulk::new(3).unwrap();
-        let result = bulk.partition_by(&registry, "nonexistent");
-        assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), SoAKitError::FieldNotFound(_)));
+        assert!(matches!(
+            bulk.value_counts(&registry, "missing"),
+            Err(SoAKitError::FieldNotFound(_))
+        ));
     }
 
     #[test]
-    fn test_list_data_fields() {
+    fn test_null_policies() {
         let mut registry = Registry::new();
-        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_) | Value::Null));
         registry
-            .register("age".to_string(), validator.clone(), false, vec![], None)
+            .register("n".to_string(), ints, false, vec![], None)
             .unwrap();
+        let strings = Box::new(|v: &Value| matches!(v, Value::ScalarString(_)));
         registry
-            .register("height".to_string(), validator, false, vec![], None)
+            .register("g".to_string(), strings, false, vec![], None)
             .unwrap();
 
-        let bulk = Bulk::new(3).unwrap();
-        let bulk = bulk
-            .set(
-                &registry,
-                "age",
-                vec![
-                    Value::ScalarInt(10),
-                    Value::ScalarInt(20),
-                    Value::ScalarInt(30),
-                ],
-            )
+        // Nulls in two different chunks
+        let count = CHUNK_SIZE + 2;
+        let values = (0..count)
+            .map(|i| match i {
+                1 | 1025 => Value::Null,
+                _ => Value::ScalarInt(1),
+            })
+            .collect();
+        let groups = (0..count)
+            .map(|i| Value::from(if i < 3 { "a" } else { "b" }))
+            .collect();
+        let bulk = Bulk::new(count)
+            .unwrap()
+            .set(&registry, "n", values)
             .unwrap();
-        let bulk = bulk
-            .set(
-                &registry,
-                "height",
-                vec![
-                    Value::ScalarInt(100),
-                    Value::ScalarInt(200),
-                    Value::ScalarInt(300),
-                ],
-            )
+        let bulk = bulk.set(&registry, "g", groups).unwrap();
+
+        assert_eq!(bulk.null_count_field(&registry, "n").unwrap(), 2);
+        let nonnull = i64::try_from(count - 2).unwrap();
+        let aggregate = |agg, nulls| bulk.aggregate_field(&registry, "n", agg, nulls).unwrap();
+        assert_eq!(
+            aggregate(Agg::Sum, NullPolicy::Skip),
+            Value::ScalarInt(nonnull)
+        );
+        assert_eq!(
+            aggregate(Agg::Std, NullPolicy::Skip),
+            Value::ScalarFloat(0.0)
+        );
+        for agg in [Agg::Sum, Agg::Mean, Agg::Min, Agg::Max, Agg::Std] {
+            assert_eq!(aggregate(agg, NullPolicy::Propagate), Value::Null);
+        }
+        assert_eq!(
+            aggregate(Agg::Count, NullPolicy::Propagate),
+            Value::ScalarInt(nonnull)
+        );
+        assert_eq!(
+            aggregate(Agg::NullCount, NullPolicy::Skip),
+            Value::ScalarInt(2)
+        );
+
+        let summary = bulk
+            .group_agg_with(&registry, "g", &[("n", Agg::NullCount)], NullPolicy::Skip)
             .unwrap();
+        assert_eq!(
+            summary.get(&registry, "n").unwrap(),
+            Value::VectorInt(vec![1, 1])
+        );
 
-        let fields = bulk.list_data_fields();
-        assert_eq!(fields.len(), 2);
-        assert!(fields.contains(&"age".to_string()));
-        assert!(fields.contains(&"height".to_string()));
+        let skipped = &bulk.describe(&registry).unwrap()["n"];
+        assert_eq!(
+            (skipped.nulls, skipped.max.clone()),
+            (2, Some(Value::ScalarInt(1)))
+        );
+        let propagated = &bulk
+            .describe_with(&registry, NullPolicy::Propagate)
+            .unwrap()["n"];
+        assert_eq!((propagated.nulls, propagated.max.clone()), (2, None));
+        assert_eq!(propagated.unique, 1);
     }
 
     #[test]
-    fn test_at_proxy() {
+    fn test_constraints_enforced_on_set() {
+        use crate::constraint::Constraint;
+
         let mut registry = Registry::new();
-        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry.register_int("age").unwrap();
         registry
-            .register("age".to_string(), validator, false, vec![], None)
+            .add_constraint("age", Constraint::Range(0..=150))
             .unwrap();
+        let ages = |ages: &[i64]| {
+            ages.iter()
+                .map(|&a| Value::ScalarInt(a))
+                .collect::<Vec<_>>()
+        };
 
-        let bulk = Bulk::new(3).unwrap();
-        let bulk = bulk
-            .set(
-                &registry,
-                "age",
-                vec![
-                    Value::ScalarInt(10),
-                    Value::ScalarInt(20),
-                    Value::ScalarInt(30),
-                ],
-            )
+        let bulk = Bulk::new(3)
+            .unwrap()
+            .set(&registry, "age", ages(&[1, 2, 3]))
             .unwrap();
+        let err = bulk.set(&registry, "age", ages(&[1, 200, 3])).unwrap_err();
+        assert_eq!(
+            err,
+            SoAKitError::ValidationFailed(
+                "Value at index 1 of field age is not in 0..=150".to_string()
+            )
+        );
+        assert!(
+            bulk.set_element(&registry, "age", 2, Value::ScalarInt(-1))
+                .is_err()
+        );
+        assert!(
+            bulk.set_broadcast(&registry, "age", Value::ScalarInt(151))
+                .is_err()
+        );
 
-        let proxy = bulk.at(1).unwrap();
-        assert_eq!(proxy.index(), 1);
-    }
-
-    #[test]
-    fn test_at_out_of_bounds() {
-        let bulk = Bulk::new(3).unwrap();
-        let result = bulk.at(10);
-        assert!(result.is_err());
+        let columns = BTreeMap::from([("age".to_string(), Value::VectorInt(vec![5, 500]))]);
         assert!(matches!(
-            result.unwrap_err(),
-            SoAKitError::IndexOutOfBounds { .. }
+            Bulk::from_columns(&registry, columns),
+            Err(SoAKitError::ValidationFailed(_))
         ));
+
+        let mut chunked = bulk.clone();
+        let result = chunked.apply_chunks_mut(&registry, "age", |column| {
+            *column = Value::VectorInt(vec![7, 8, 999]);
+            Ok(())
+        });
+        assert!(matches!(result, Err(SoAKitError::ValidationFailed(_))));
+        assert_eq!(
+            chunked.get(&registry, "age").unwrap(),
+            Value::VectorInt(vec![1, 2, 3])
+        );
+
+        // Constraints added later show up in validate_all
+        registry
+            .add_constraint("age", Constraint::OneOf(ages(&[1, 3])))
+            .unwrap();
+        let violations = bulk.validate_all(&registry).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert!(
+            registry
+                .add_constraint("missing", Constraint::Range(0..=1))
+                .is_err()
+        );
     }
 
     #[test]
-    fn test_single_element_bulk() {
+    fn test_defaults_fill_omitted_values() {
         let mut registry = Registry::new();
-        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry.register_int("age").unwrap();
+        registry.register_string("name").unwrap();
+        let nullable = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_) | Value::Null));
         registry
-            .register("age".to_string(), validator, false, vec![], None)
+            .register("score".to_string(), nullable, false, vec![], None)
             .unwrap();
 
-        let bulk = Bulk::new(1).unwrap();
-        let bulk = bulk
-            .set(&registry, "age", vec![Value::ScalarInt(42)])
+        assert!(matches!(
+            registry.set_default("missing", Value::ScalarInt(0)),
+            Err(SoAKitError::FieldNotFound(_))
+        ));
+        assert!(matches!(
+            registry.set_default("age", Value::ScalarFloat(1.0)),
+            Err(SoAKitError::ValidationFailed(_))
+        ));
+        assert!(matches!(
+            registry.set_default("score", Value::Null),
+            Err(SoAKitError::InvalidArgument(_))
+        ));
+        registry.set_default("age", Value::ScalarInt(18)).unwrap();
+        registry.set_default("score", Value::ScalarInt(0)).unwrap();
+
+        let bulk =
+            Bulk::from_records_json(r#"[{"name": "a", "age": 30}, {"name": "b"}]"#, &registry)
+                .unwrap();
+        assert_eq!(
+            bulk.get(&registry, "age").unwrap(),
+            Value::VectorInt(vec![30, 18])
+        );
+
+        let mut extended = bulk.clone();
+        let record = BTreeMap::from([("name".to_string(), Value::from("c".to_string()))]);
+        extended.extend_rows(&registry, vec![record]).unwrap();
+        assert_eq!(
+            extended.get(&registry, "age").unwrap(),
+            Value::VectorInt(vec![30, 18, 18])
+        );
+
+        let resized = bulk
+            .resize(
+                &registry,
+                3,
+                &BTreeMap::from([("name".to_string(), Value::from("z".to_string()))]),
+            )
             .unwrap();
+        assert_eq!(
+            resized.get(&registry, "age").unwrap(),
+            Value::VectorInt(vec![30, 18, 18])
+        );
 
-        if let Value::VectorInt(v) = bulk.get(&registry, "age").unwrap() {
-            assert_eq!(v, vec![42]);
-        } else {
-            panic!("Expected VectorInt");
-        }
+        let scores = vec![Value::Null, Value::ScalarInt(4)];
+        let bulk = bulk.set(&registry, "score", scores).unwrap();
+        let filled = bulk.fill_missing(&registry).unwrap();
+        assert_eq!(
+            filled.get(&registry, "score").unwrap(),
+            Value::VectorInt(vec![0, 4])
+        );
+        let refilled = filled.fill_missing(&registry).unwrap();
+        assert_eq!(
+            refilled.chunk_version("score", 0),
+            filled.chunk_version("score", 0)
+        );
     }
 
     #[test]
-    fn test_large_bulk() {
+    fn test_aliases_resolve_in_get_set_and_records() {
         let mut registry = Registry::new();
-        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
-        registry
-            .register("value".to_string(), validator, false, vec![], None)
-            .unwrap();
+        registry.register_int("date_of_birth").unwrap();
+        registry.register_string("name").unwrap();
+        registry.add_alias("dob", "date_of_birth").unwrap();
 
-        let count = 1000;
-        let bulk = Bulk::new(count).unwrap();
-        let values: Vec<Value> = (0..count).map(|i| Value::ScalarInt(i as i64)).collect();
-        let bulk = bulk.set(&registry, "value", values).unwrap();
+        let bulk = Bulk::new(2).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "dob",
+                vec![Value::ScalarInt(1), Value::ScalarInt(2)],
+            )
+            .unwrap();
+        assert_eq!(bulk.list_data_fields(), vec!["date_of_birth"]);
+        assert_eq!(
+            bulk.get(&registry, "dob").unwrap(),
+            Value::VectorInt(vec![1, 2])
+        );
+        let bulk = bulk
+            .set_element(&registry, "dob", 1, Value::ScalarInt(5))
+            .unwrap();
+        assert_eq!(
+            bulk.get(&registry, "date_of_birth").unwrap(),
+            Value::VectorInt(vec![1, 5])
+        );
 
-        assert_eq!(bulk.count(), count);
-        if let Value::VectorInt(v) = bulk.get(&registry, "value").unwrap() {
-            assert_eq!(v.len(), count);
-            assert_eq!(v[0], 0);
-            assert_eq!(v[count - 1], (count - 1) as i64);
-        } else {
-            panic!("Expected VectorInt");
-        }
-    }
+        let json = r#"[{"dob": 3, "name": "a"}, {"date_of_birth": 4, "name": "b"}]"#;
+        let imported = Bulk::from_records_json(json, &registry).unwrap();
+        assert_eq!(
+            imported.get(&registry, "dob").unwrap(),
+            Value::VectorInt(vec![3, 4])
+        );
+        let both = r#"[{"dob": 3, "date_of_birth": 3, "name": "a"}]"#;
+        assert!(matches!(
+            Bulk::from_records_json(both, &registry),
+            Err(SoAKitError::InvalidArgument(_))
+        ));
 
-    #[test]
-    fn test_meta_new() {
-        let meta = Meta::new(5).unwrap();
-        assert_eq!(meta.count, 5);
-        assert_eq!(meta.id, vec![0, 1, 2, 3, 4]);
-        assert!(meta.versions.is_empty());
+        let mut extended = imported.clone();
+        let record = BTreeMap::from([
+            ("dob".to_string(), Value::ScalarInt(6)),
+            ("name".to_string(), Value::from("c".to_string())),
+        ]);
+        extended.push_row(&registry, record).unwrap();
+        assert_eq!(
+            extended.get(&registry, "dob").unwrap(),
+            Value::VectorInt(vec![3, 4, 6])
+        );
     }
 
     #[test]
-    fn test_meta_new_zero() {
-        let result = Meta::new(0);
-        assert!(result.is_err());
+    fn test_validate_complete() {
+        let mut registry = Registry::new();
+        registry.register_int("a").unwrap();
+        registry.register_int("b").unwrap();
+        registry.register_int("c").unwrap();
+        let any = Box::new(|_: &Value| true);
+        let copy = Box::new(|args: &[Value]| Ok(args[0].clone()));
+        registry
+            .register(
+                "d".to_string(),
+                any,
+                true,
+                vec!["a".to_string()],
+                Some(copy),
+            )
+            .unwrap();
+        registry.set_required("a", true).unwrap();
+        registry.set_required("b", true).unwrap();
         assert!(matches!(
-            result.unwrap_err(),
-            SoAKitError::InvalidArgument(_)
+            registry.set_required("d", true),
+            Err(SoAKitError::InvalidArgument(_))
         ));
+        assert!(matches!(
+            registry.set_required("x", true),
+            Err(SoAKitError::FieldNotFound(_))
+        ));
+        assert_eq!(registry.required_fields(), vec!["a", "b"]);
+
+        let ints = |n: usize| {
+            (0..n)
+                .map(|i| Value::ScalarInt(i as i64))
+                .collect::<Vec<_>>()
+        };
+        let bulk = Bulk::new(CHUNK_SIZE + 1).unwrap();
+        assert_eq!(bulk.validate_complete(&registry), vec!["a", "b"]);
+        let bulk = bulk.set(&registry, "a", ints(CHUNK_SIZE + 1)).unwrap();
+        assert_eq!(bulk.validate_complete(&registry), vec!["b"]);
+
+        // A column missing from one chunk is incomplete
+        let mut partial = bulk.set(&registry, "b", ints(CHUNK_SIZE + 1)).unwrap();
+        assert!(partial.validate_complete(&registry).is_empty());
+        assert!(partial.chunks.last_mut().unwrap().remove_column("b"));
+        assert_eq!(partial.validate_complete(&registry), vec!["b"]);
+
+        registry.set_required("b", false).unwrap();
+        assert!(!registry.is_required("b"));
+        assert!(partial.validate_complete(&registry).is_empty());
     }
 
     #[test]
-    fn test_bulk_clone() {
+    fn test_deprecated_field_access_runs_handler() {
+        use std::sync::{Arc, Mutex};
+
         let mut registry = Registry::new();
-        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry.register_int("age").unwrap();
+        let any = Box::new(|_: &Value| true);
+        let copy = Box::new(|args: &[Value]| Ok(args[0].clone()));
+        let dependencies = vec!["age".to_string()];
         registry
-            .register("age".to_string(), validator, false, vec![], None)
+            .register("age2".to_string(), any, true, dependencies, Some(copy))
             .unwrap();
+        registry.add_alias("years", "age").unwrap();
+        assert!(registry.deprecate("missing", "x").is_err());
 
-        let bulk1 = Bulk::new(3).unwrap();
-        let bulk1 = bulk1
-            .set(
-                &registry,
-                "age",
-                vec![
-                    Value::ScalarInt(10),
-                    Value::ScalarInt(20),
-                    Value::ScalarInt(30),
-                ],
-            )
+        // Without a handler, deprecation only records the hint
+        registry.deprecate("age", "use date_of_birth").unwrap();
+        assert_eq!(registry.deprecation("age"), Some("use date_of_birth"));
+        assert_eq!(registry.deprecation("age2"), None);
+        let bulk = Bulk::new(2).unwrap();
+        let bulk = bulk
+            .set(&registry, "age", vec![Value::ScalarInt(1); 2])
             .unwrap();
 
-        let bulk2 = bulk1.clone();
-        assert_eq!(bulk1.count(), bulk2.count());
+        let accesses = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&accesses);
+        registry.on_deprecated_access(move |field, hint| {
+            log.lock()
+                .unwrap()
+                .push((field.to_string(), hint.to_string()));
+        });
+        let bulk = bulk
+            .set_element(&registry, "years", 0, Value::ScalarInt(2))
+            .unwrap();
         assert_eq!(
-            bulk1.get(&registry, "age").unwrap(),
-            bulk2.get(&registry, "age").unwrap()
+            bulk.get(&registry, "years").unwrap(),
+            Value::VectorInt(vec![2, 1])
+        );
+        assert_eq!(
+            bulk.get(&registry, "age2").unwrap(),
+            Value::VectorInt(vec![2, 1])
+        );
+        let accesses = accesses.lock().unwrap();
+        assert_eq!(accesses.len(), 2);
+        assert!(
+            accesses
+                .iter()
+                .all(|(field, hint)| field == "age" && hint == "use date_of_birth")
         );
     }
 }
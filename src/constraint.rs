@@ -0,0 +1,203 @@
+/// Declarative field constraints.
+///
+/// A [`Constraint`] describes a rule that every value of a field must satisfy,
+/// such as a numeric range or a regular expression. Unlike validator closures,
+/// constraints are plain data: they are shown in error messages and serialized
+/// as part of a [`Schema`](crate::schema::Schema).
+///
+/// Constraints are attached with [`Registry::add_constraint`](crate::Registry::add_constraint)
+/// and enforced, next to the field's validator, on every value a bulk update
+/// stores. Missing (`Null`) values satisfy every constraint.
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::{Bulk, Constraint, Registry, SoAKitError, Value};
+///
+/// let mut registry = Registry::new();
+/// registry.register_int("age").unwrap();
+/// registry.add_constraint("age", Constraint::Range(0..=150)).unwrap();
+///
+/// let bulk = Bulk::new(2).unwrap();
+/// assert!(bulk.set(&registry, "age", vec![Value::ScalarInt(30), Value::ScalarInt(40)]).is_ok());
+/// assert!(matches!(
+///     bulk.set(&registry, "age", vec![Value::ScalarInt(30), Value::ScalarInt(200)]),
+///     Err(SoAKitError::ValidationFailed(_))
+/// ));
+/// ```
+use crate::error::{Result, SoAKitError};
+use crate::value::{Value, int_to_float};
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::ops::RangeInclusive;
+
+/// A rule that every value of a field must satisfy.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Constraint {
+    /// Integers and floats must lie in the range, bounds included; NaN never does
+    Range(RangeInclusive<i64>),
+    /// Strings must match the regular expression somewhere; anchor it with `^`
+    /// and `$` to match whole strings
+    Regex(Pattern),
+    /// Values must equal one of the listed values
+    OneOf(Vec<Value>),
+}
+
+impl Constraint {
+    /// Create a [`Constraint::Regex`] from a regular expression.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if the pattern is not a valid regular expression
+    pub fn regex(pattern: &str) -> Result<Self> {
+        Pattern::new(pattern).map(Constraint::Regex)
+    }
+
+    /// Check whether a value satisfies the constraint.
+    ///
+    /// `Null` satisfies every constraint; other values of the wrong kind (for
+    /// example a string checked against a range) do not.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Constraint, Value};
+    ///
+    /// let range = Constraint::Range(0..=150);
+    /// assert!(range.check(&Value::ScalarInt(150)));
+    /// assert!(range.check(&Value::ScalarFloat(0.5)));
+    /// assert!(!range.check(&Value::ScalarInt(-1)));
+    ///
+    /// let one_of = Constraint::OneOf(vec![Value::from("red".to_string()), Value::from("blue".to_string())]);
+    /// assert!(one_of.check(&Value::ScalarString("red".to_string())));
+    /// assert!(!one_of.check(&Value::ScalarString("green".to_string())));
+    /// ```
+    pub fn check(&self, value: &Value) -> bool {
+        if value.is_null() {
+            return true;
+        }
+        match (self, value) {
+            (Constraint::Range(range), Value::ScalarInt(n)) => range.contains(n),
+            (Constraint::Range(range), Value::ScalarFloat(x)) => {
+                (int_to_float(*range.start())..=int_to_float(*range.end())).contains(x)
+            }
+            (Constraint::Regex(pattern), Value::ScalarString(s)) => pattern.0.is_match(s),
+            (Constraint::OneOf(options), value) => options.contains(value),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Constraint::Range(range) => write!(f, "in {}..={}", range.start(), range.end()),
+            Constraint::Regex(pattern) => write!(f, "matches /{}/", pattern.as_str()),
+            Constraint::OneOf(options) => {
+                let options: Vec<String> = options.iter().map(ToString::to_string).collect();
+                write!(f, "one of [{}]", options.join(", "))
+            }
+        }
+    }
+}
+
+/// A compiled regular expression for [`Constraint::Regex`].
+///
+/// Patterns compare equal when their source text is equal, and serialize as
+/// that text.
+#[derive(Clone, Debug)]
+pub struct Pattern(Regex);
+
+impl Pattern {
+    /// Compile a regular expression.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if the pattern is not a valid regular expression
+    pub fn new(pattern: &str) -> Result<Self> {
+        Regex::new(pattern)
+            .map(Pattern)
+            .map_err(|e| SoAKitError::InvalidArgument(format!("Invalid pattern: {}", e)))
+    }
+
+    /// Get the source text of the pattern.
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Serialize for Pattern {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Pattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        Regex::new(&pattern)
+            .map(Pattern)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constraint_checks() {
+        let range = Constraint::Range(-5..=5);
+        assert!(range.check(&Value::ScalarInt(-5)) && range.check(&Value::ScalarInt(5)));
+        assert!(!range.check(&Value::ScalarInt(6)));
+        assert!(range.check(&Value::ScalarFloat(4.5)));
+        assert!(!range.check(&Value::ScalarFloat(5.5)));
+        assert!(!range.check(&Value::ScalarFloat(f64::NAN)));
+        assert!(!range.check(&Value::ScalarString("1".to_string())));
+        assert!(range.check(&Value::Null));
+
+        let regex = Constraint::regex("^[a-z]+@[a-z]+$").unwrap();
+        assert!(regex.check(&Value::ScalarString("ann@example".to_string())));
+        assert!(!regex.check(&Value::ScalarString("Ann@example".to_string())));
+        assert!(!regex.check(&Value::ScalarInt(1)));
+        assert!(matches!(
+            Constraint::regex("("),
+            Err(SoAKitError::InvalidArgument(_))
+        ));
+
+        let one_of = Constraint::OneOf(vec![Value::ScalarInt(1), Value::ScalarInt(2)]);
+        assert!(one_of.check(&Value::ScalarInt(2)));
+        assert!(!one_of.check(&Value::ScalarInt(3)));
+        assert!(!one_of.check(&Value::ScalarFloat(1.0)));
+    }
+
+    #[test]
+    fn test_constraint_display_and_serde() {
+        let constraints = vec![
+            Constraint::Range(0..=150),
+            Constraint::regex("^a.*$").unwrap(),
+            Constraint::OneOf(vec![
+                Value::ScalarString("x".to_string()),
+                Value::ScalarInt(1),
+            ]),
+        ];
+        let shown: Vec<String> = constraints.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            shown,
+            vec!["in 0..=150", "matches /^a.*$/", "one of [x, 1]"]
+        );
+
+        let json = serde_json::to_string(&constraints).unwrap();
+        assert!(json.contains("\"^a.*$\""));
+        let back: Vec<Constraint> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, constraints);
+        assert!(serde_json::from_str::<Constraint>("{\"Regex\": \"(\"}").is_err());
+    }
+}
@@ -0,0 +1,402 @@
+/// Ready-made derived field functions for numeric columns.
+///
+/// Arithmetic over whole columns runs into the same edge cases every time:
+/// integer overflow, division by zero and NaN. The constructors here build the
+/// usual element-wise computations once, with a single [`Policy`] deciding what
+/// happens to every element whose result is out of range or undefined.
+///
+/// Inputs may be integer or float vectors, dense or sparse. Integer inputs give
+/// integer results unless a float is involved; [`ratio`] always gives floats. An
+/// element missing from any input is missing from the result.
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::derived::{self, Policy};
+/// use soakit::{Bulk, Registry, Value};
+///
+/// let mut registry = Registry::new();
+/// for name in ["hits", "misses"] {
+///     let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+///     registry.register(name.to_string(), validator, false, vec![], None).unwrap();
+/// }
+/// derived::sum_checked(&["hits", "misses"]).register(&mut registry, "total").unwrap();
+/// derived::ratio("hits", "total")
+///     .with_policy(Policy::Null)
+///     .register(&mut registry, "hit_rate")
+///     .unwrap();
+///
+/// let bulk = Bulk::new(2).unwrap();
+/// let bulk = bulk.set(&registry, "hits", vec![Value::ScalarInt(3), Value::ScalarInt(0)]).unwrap();
+/// let bulk = bulk.set(&registry, "misses", vec![Value::ScalarInt(1), Value::ScalarInt(0)]).unwrap();
+/// assert_eq!(bulk.get(&registry, "total").unwrap(), Value::VectorInt(vec![4, 0]));
+///
+/// // 0 / 0 is missing instead of failing the whole column
+/// let rate = bulk.get(&registry, "hit_rate").unwrap();
+/// assert_eq!(rate.get_element(0).unwrap(), Value::ScalarFloat(0.75));
+/// assert_eq!(rate.get_element(1).unwrap(), Value::Null);
+/// ```
+use crate::error::{Result, SoAKitError};
+use crate::meta::{DerivedFunc, Registry};
+use crate::value::{Value, int_to_float};
+
+/// What a derived helper does with an element whose result is out of range
+/// (integer overflow, infinite floats) or undefined (division by zero, NaN).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Policy {
+    /// Fail with [`SoAKitError::InvalidArgument`], so the field cannot be computed
+    #[default]
+    Error,
+    /// Clamp to the nearest representable value: `i64::MIN`/`i64::MAX` for
+    /// integers and `f64::MIN`/`f64::MAX` for floats (so `1 / 0` becomes
+    /// `f64::MAX`); undefined results such as `0 / 0` are NaN
+    Saturate,
+    /// Leave the element missing, giving an option vector
+    Null,
+}
+
+/// The element-wise operation computed by a [`Derived`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Op {
+    /// Sum of all dependencies
+    Sum,
+    /// Product of all dependencies
+    Product,
+    /// First dependency divided by the second
+    Ratio,
+}
+
+impl Op {
+    /// Name of the operation, for error messages.
+    const fn name(self) -> &'static str {
+        match self {
+            Op::Sum => "sum",
+            Op::Product => "product",
+            Op::Ratio => "ratio",
+        }
+    }
+}
+
+/// A derived field computation with its dependencies, ready to register.
+///
+/// Built by [`sum_checked`], [`product_checked`] and [`ratio`]. The computation
+/// is element-wise, so it is registered with
+/// [`Registry::register_chunk_safe`] and computed chunk by chunk.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Derived {
+    /// The operation to compute
+    op: Op,
+    /// The fields the operation reads, in order
+    dependencies: Vec<String>,
+    /// What to do with out-of-range or undefined results
+    policy: Policy,
+}
+
+/// Sum numeric fields element-wise.
+///
+/// With the default [`Policy::Error`], an integer sum that overflows `i64` or a
+/// float sum that is not finite fails instead of wrapping or going unnoticed.
+///
+/// # Arguments
+///
+/// * `fields` - The fields to add up
+pub fn sum_checked(fields: &[&str]) -> Derived {
+    Derived::new(Op::Sum, fields)
+}
+
+/// Multiply numeric fields element-wise, with overflow handled as for [`sum_checked`].
+///
+/// # Arguments
+///
+/// * `fields` - The fields to multiply
+pub fn product_checked(fields: &[&str]) -> Derived {
+    Derived::new(Op::Product, fields)
+}
+
+/// Divide one numeric field by another element-wise, giving floats.
+///
+/// Division by zero is handled by the [`Policy`], like NaN and infinite results.
+///
+/// # Arguments
+///
+/// * `numerator` - The field to divide
+/// * `denominator` - The field to divide by
+pub fn ratio(numerator: &str, denominator: &str) -> Derived {
+    Derived::new(Op::Ratio, &[numerator, denominator])
+}
+
+impl Derived {
+    /// Create a computation of `op` over `fields` with the default policy.
+    fn new(op: Op, fields: &[&str]) -> Self {
+        Self {
+            op,
+            dependencies: fields.iter().map(|field| (*field).to_string()).collect(),
+            policy: Policy::default(),
+        }
+    }
+
+    /// Set what happens to out-of-range or undefined results.
+    #[must_use]
+    pub fn with_policy(self, policy: Policy) -> Self {
+        Self { policy, ..self }
+    }
+
+    /// Get the policy for out-of-range or undefined results.
+    pub const fn policy(&self) -> Policy {
+        self.policy
+    }
+
+    /// Get the fields the computation reads, in order.
+    pub fn dependencies(&self) -> &[String] {
+        &self.dependencies
+    }
+
+    /// Turn the computation into a function for [`Registry::register`].
+    pub fn into_func(self) -> DerivedFunc {
+        let Self { op, policy, .. } = self;
+        Box::new(move |args: &[Value]| compute(op, policy, args))
+    }
+
+    /// Register the computation as a derived field.
+    ///
+    /// The field accepts any value; its validator is not consulted for derived
+    /// results.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry to add the field to
+    /// * `name` - The name of the derived field
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Registry::register`].
+    pub fn register(self, registry: &mut Registry, name: &str) -> Result<()> {
+        let dependencies = self.dependencies.clone();
+        registry.register_chunk_safe(
+            name.to_string(),
+            Box::new(|_: &Value| true),
+            dependencies,
+            self.into_func(),
+        )
+    }
+}
+
+/// Compute `op` element-wise over the dependency columns.
+fn compute(op: Op, policy: Policy, args: &[Value]) -> Result<Value> {
+    let columns: Vec<Vec<Value>> = args.iter().map(Value::to_scalars).collect();
+    let len = columns.first().map_or(0, Vec::len);
+    if let Some(column) = columns.iter().find(|column| column.len() != len) {
+        return Err(SoAKitError::LengthMismatch {
+            expected: len,
+            actual: column.len(),
+        });
+    }
+
+    let results = (0..len)
+        .map(|i| {
+            let operands: Vec<&Value> = columns.iter().filter_map(|column| column.get(i)).collect();
+            element(op, policy, &operands)
+        })
+        .collect::<Result<Vec<Value>>>()?;
+    Value::from_scalars(results)
+}
+
+/// Compute `op` for one element.
+fn element(op: Op, policy: Policy, operands: &[&Value]) -> Result<Value> {
+    if operands.iter().any(|v| v.is_null()) {
+        return Ok(Value::Null);
+    }
+    let ints: Option<Vec<i64>> = operands
+        .iter()
+        .map(|v| match v {
+            Value::ScalarInt(x) => Some(*x),
+            _ => None,
+        })
+        .collect();
+
+    match (op, ints) {
+        (Op::Sum, Some(ints)) => {
+            // An i128 cannot overflow summing any realistic number of i64s
+            let exact = ints
+                .iter()
+                .map(|&x| i128::from(x))
+                .fold(0, i128::saturating_add);
+            int_result(op, policy, exact)
+        }
+        (Op::Product, Some(ints)) => {
+            let magnitude = ints.iter().fold(1u128, |acc, x| {
+                acc.saturating_mul(u128::from(x.unsigned_abs()))
+            });
+            let magnitude = i128::try_from(magnitude).unwrap_or(i128::MAX);
+            let negative = ints.iter().filter(|&&x| x < 0).count() & 1 == 1;
+            let exact = if negative {
+                0i128.saturating_sub(magnitude)
+            } else {
+                magnitude
+            };
+            int_result(op, policy, exact)
+        }
+        _ => {
+            let floats = operands
+                .iter()
+                .map(|v| match v {
+                    Value::ScalarInt(x) => Ok(int_to_float(*x)),
+                    Value::ScalarFloat(x) => Ok(*x),
+                    other => Err(SoAKitError::InvalidArgument(format!(
+                        "Expected numeric values for {}, got {:?}",
+                        op.name(),
+                        other
+                    ))),
+                })
+                .collect::<Result<Vec<f64>>>()?;
+            float_result(op, policy, &floats)
+        }
+    }
+}
+
+/// Apply `policy` to an exact integer result.
+fn int_result(op: Op, policy: Policy, exact: i128) -> Result<Value> {
+    if let Ok(value) = i64::try_from(exact) {
+        return Ok(Value::ScalarInt(value));
+    }
+    match policy {
+        Policy::Error => Err(SoAKitError::InvalidArgument(format!(
+            "Integer overflow in {}",
+            op.name()
+        ))),
+        Policy::Saturate => Ok(Value::ScalarInt(if exact < 0 {
+            i64::MIN
+        } else {
+            i64::MAX
+        })),
+        Policy::Null => Ok(Value::Null),
+    }
+}
+
+/// Compute a float result and apply `policy` to it.
+fn float_result(op: Op, policy: Policy, floats: &[f64]) -> Result<Value> {
+    let result = match op {
+        Op::Sum => floats.iter().sum(),
+        Op::Product => floats.iter().product(),
+        Op::Ratio => {
+            let (numerator, denominator) = match floats {
+                [numerator, denominator] => (*numerator, *denominator),
+                _ => {
+                    return Err(SoAKitError::InvalidArgument(
+                        "A ratio needs exactly two fields".to_string(),
+                    ));
+                }
+            };
+            if denominator == 0.0 && policy != Policy::Saturate {
+                return match policy {
+                    Policy::Null => Ok(Value::Null),
+                    _ => Err(SoAKitError::InvalidArgument(
+                        "Division by zero in ratio".to_string(),
+                    )),
+                };
+            }
+            numerator / denominator
+        }
+    };
+
+    if result.is_finite() {
+        return Ok(Value::ScalarFloat(result));
+    }
+    match policy {
+        Policy::Error => Err(SoAKitError::InvalidArgument(format!(
+            "Result of {} is not finite: {}",
+            op.name(),
+            result
+        ))),
+        // NaN stays NaN: there is no nearest value to clamp it to
+        Policy::Saturate => Ok(Value::ScalarFloat(result.clamp(f64::MIN, f64::MAX))),
+        Policy::Null => Ok(Value::Null),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bulk::Bulk;
+
+    #[test]
+    fn test_integer_overflow_policies() {
+        let mut registry = Registry::new();
+        for name in ["a", "b"] {
+            let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+            registry
+                .register(name.to_string(), ints, false, vec![], None)
+                .unwrap();
+        }
+        sum_checked(&["a", "b"])
+            .register(&mut registry, "sum")
+            .unwrap();
+        let saturating = sum_checked(&["a", "b"]).with_policy(Policy::Saturate);
+        saturating.register(&mut registry, "sum_sat").unwrap();
+        let product = product_checked(&["a", "b"]).with_policy(Policy::Null);
+        assert_eq!(product.dependencies(), ["a".to_string(), "b".to_string()]);
+        product.register(&mut registry, "product").unwrap();
+
+        let bulk = Bulk::new(3).unwrap();
+        let a = vec![
+            Value::ScalarInt(i64::MAX),
+            Value::ScalarInt(-3),
+            Value::ScalarInt(i64::MIN),
+        ];
+        let b = vec![
+            Value::ScalarInt(1),
+            Value::ScalarInt(4),
+            Value::ScalarInt(-1),
+        ];
+        let bulk = bulk
+            .set(&registry, "a", a)
+            .unwrap()
+            .set(&registry, "b", b)
+            .unwrap();
+
+        assert!(matches!(
+            bulk.get(&registry, "sum"),
+            Err(SoAKitError::InvalidArgument(_))
+        ));
+        assert_eq!(
+            bulk.get(&registry, "sum_sat").unwrap(),
+            Value::VectorInt(vec![i64::MAX, 1, i64::MIN])
+        );
+        let product = bulk.get(&registry, "product").unwrap();
+        assert_eq!(product.get_element(0).unwrap(), Value::ScalarInt(i64::MAX));
+        assert_eq!(product.get_element(1).unwrap(), Value::ScalarInt(-12));
+        assert_eq!(product.get_element(2).unwrap(), Value::Null);
+    }
+
+    #[test]
+    fn test_ratio_and_nan_policies() {
+        let num = Value::VectorFloat(vec![1.0, -1.0, 0.0, f64::NAN]);
+        let den = Value::VectorFloat(vec![2.0, 0.0, 0.0, 1.0]);
+        let args = [num, den];
+
+        let checked = ratio("num", "den").into_func();
+        assert!(checked(&args).is_err());
+        assert!(checked(&[Value::VectorFloat(vec![1.0]), Value::VectorFloat(vec![4.0])]).is_ok());
+
+        let saturating = ratio("num", "den")
+            .with_policy(Policy::Saturate)
+            .into_func();
+        let Value::VectorFloat(saturated) = saturating(&args).unwrap() else {
+            panic!("expected floats");
+        };
+        assert_eq!(&saturated[..2], &[0.5, f64::MIN]);
+        assert!(saturated[2].is_nan() && saturated[3].is_nan());
+
+        let nulls = ratio("num", "den").with_policy(Policy::Null).into_func();
+        let result = nulls(&args).unwrap();
+        assert_eq!(result.get_element(0).unwrap(), Value::ScalarFloat(0.5));
+        assert_eq!(result.get_element(1).unwrap(), Value::Null);
+        assert_eq!(result.get_element(3).unwrap(), Value::Null);
+
+        // Columns of different lengths are rejected
+        assert!(matches!(
+            nulls(&[Value::VectorFloat(vec![1.0]), Value::VectorFloat(vec![])]),
+            Err(SoAKitError::LengthMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,729 @@
+/// Out-of-core bulks stored on disk.
+///
+/// A [`DiskBulk`] keeps every chunk of a bulk in its own file and only loads
+/// the chunks an operation touches. Loaded chunks are kept in memory up to a
+/// fixed capacity; when it is exceeded, the least recently used chunk is
+/// evicted. Only the metadata (element IDs and versions) stays in memory, so a
+/// dataset larger than RAM can be processed one chunk at a time.
+///
+/// Chunk files are memory-mapped when a chunk is loaded, so the operating system
+/// pages in only the file being decoded and needs no read buffer for it. The
+/// columns are decoded into owned values, which is the one copy a load makes.
+/// Updates are written through to disk immediately, each file to a temporary
+/// file first that is then renamed over the old one, so a file is never
+/// changed in place while it is mapped. Other processes must not modify the
+/// files of an open disk bulk.
+///
+/// Every chunk file records the version of each of its columns, so
+/// [`Bulk::chunk_version`] on a chunk from [`DiskBulk::chunk`] tells which
+/// chunks an update touched, as it does for an in-memory bulk.
+///
+/// Every file starts with the bytes `SOAKITD\0` and the layout version as a
+/// little-endian `u32`, followed by the bincode encoding of the chunk or
+/// metadata. The version is the one of [`Bulk::to_binary`], since both encode
+/// the same [`Meta`] and [`Chunk`] structs; files of another version are
+/// rejected rather than misread.
+///
+/// Whole-field reads and writes such as [`DiskBulk::get`] still build the full
+/// column in memory; use [`DiskBulk::chunk`] or [`DiskBulk::for_each_chunk`] to
+/// work on one chunk at a time, with the full [`Bulk`] API.
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::disk::DiskBulk;
+/// use soakit::{Bulk, Registry, Value};
+///
+/// let mut registry = Registry::new();
+/// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+/// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+///
+/// let bulk = Bulk::new(3).unwrap();
+/// let bulk = bulk.set(&registry, "age", vec![Value::ScalarInt(25); 3]).unwrap();
+///
+/// let dir = std::env::temp_dir().join(format!("soakit-disk-doc-{}", std::process::id()));
+/// let mut disk = DiskBulk::create(&dir, &bulk, 4).unwrap();
+/// disk.set_element(&registry, "age", 1, Value::ScalarInt(30)).unwrap();
+///
+/// // Reopening reads the updated chunks back
+/// let disk = DiskBulk::open(&dir, 4).unwrap();
+/// assert_eq!(disk.get(&registry, "age").unwrap(), Value::VectorInt(vec![25, 30, 25]));
+/// std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+use crate::bulk::{BINARY_VERSION, Bulk, Chunk, Meta, check_column, chunk_of};
+use crate::error::{Result, SoAKitError};
+use crate::meta::{DerivedContext, Registry, chunk_offsets};
+use crate::util::filter_system_fields;
+use crate::value::Value;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Name of the file holding a disk bulk's metadata.
+const META_FILE: &str = "meta.bin";
+
+/// Leading bytes of every chunk and metadata file, followed by the layout
+/// version as a little-endian `u32`.
+const DISK_MAGIC: [u8; 8] = *b"SOAKITD\0";
+
+/// The metadata file of a disk bulk.
+#[derive(Serialize, Deserialize)]
+struct DiskMeta {
+    /// Metadata of the bulk
+    meta: Meta,
+    /// Number of elements in each chunk
+    chunk_lens: Vec<usize>,
+}
+
+/// Chunks held in memory, with their order of use.
+#[derive(Default)]
+struct LoadedChunks {
+    /// Loaded chunks by chunk number
+    chunks: BTreeMap<usize, Arc<Chunk>>,
+    /// Chunk numbers from least to most recently used
+    recent: VecDeque<usize>,
+}
+
+/// A bulk whose chunks live in files and are loaded on demand.
+pub struct DiskBulk {
+    /// Directory holding the chunk and metadata files
+    dir: PathBuf,
+    /// Metadata of the bulk
+    meta: Meta,
+    /// Number of elements in each chunk
+    chunk_lens: Vec<usize>,
+    /// Maximum number of chunks kept in memory
+    capacity: usize,
+    /// Chunks currently in memory
+    loaded: Mutex<LoadedChunks>,
+}
+
+impl DiskBulk {
+    /// Write a bulk to a directory and open it as a disk bulk.
+    ///
+    /// The directory is created if needed; files of a previous disk bulk in it
+    /// are overwritten. Derived field caches are not written.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory to write the chunk files to
+    /// * `bulk` - The bulk to write
+    /// * `capacity` - The maximum number of chunks kept in memory
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(DiskBulk)` with no chunk loaded.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if `capacity` is 0, or if a file cannot
+    ///   be written or serialized
+    pub fn create(dir: impl AsRef<Path>, bulk: &Bulk, capacity: usize) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).map_err(io_error)?;
+        let disk = Self::with_parts(
+            dir,
+            bulk.meta.clone(),
+            bulk.chunks.iter().map(|chunk| chunk.len).collect(),
+            capacity,
+        )?;
+        for (i, chunk) in bulk.chunks.iter().enumerate() {
+            let mut chunk = chunk.clone();
+            disk.pin_versions(&mut chunk);
+            disk.write_chunk(i, &chunk)?;
+        }
+        disk.write_meta()?;
+        Ok(disk)
+    }
+
+    /// Open a disk bulk written by [`DiskBulk::create`].
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory holding the chunk files
+    /// * `capacity` - The maximum number of chunks kept in memory
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(DiskBulk)` with no chunk loaded.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if `capacity` is 0, or if the metadata
+    ///   file cannot be read or deserialized, or was written in another layout
+    ///   version
+    pub fn open(dir: impl AsRef<Path>, capacity: usize) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        let bytes = fs::read(dir.join(META_FILE)).map_err(io_error)?;
+        let disk_meta: DiskMeta = decode(&bytes)?;
+        Self::with_parts(dir, disk_meta.meta, disk_meta.chunk_lens, capacity)
+    }
+
+    /// Build a disk bulk with nothing loaded.
+    fn with_parts(
+        dir: PathBuf,
+        meta: Meta,
+        chunk_lens: Vec<usize>,
+        capacity: usize,
+    ) -> Result<Self> {
+        if capacity == 0 {
+            return Err(SoAKitError::InvalidArgument(
+                "Disk bulk capacity must be greater than 0".to_string(),
+            ));
+        }
+        Ok(Self {
+            dir,
+            meta,
+            chunk_lens,
+            capacity,
+            loaded: Mutex::new(LoadedChunks::default()),
+        })
+    }
+
+    /// Get the metadata of the bulk.
+    pub const fn meta(&self) -> &Meta {
+        &self.meta
+    }
+
+    /// Get the number of elements.
+    pub const fn count(&self) -> usize {
+        self.meta.count
+    }
+
+    /// Get the number of chunks.
+    pub const fn num_chunks(&self) -> usize {
+        self.chunk_lens.len()
+    }
+
+    /// Get the number of chunks currently held in memory.
+    pub fn loaded_chunks(&self) -> usize {
+        self.lock().chunks.len()
+    }
+
+    /// List the stored fields (excluding system fields).
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if the first chunk cannot be loaded
+    pub fn list_data_fields(&self) -> Result<Vec<String>> {
+        if self.chunk_lens.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(filter_system_fields(&self.load(0)?.field_names()))
+    }
+
+    /// Load one chunk as a bulk of its elements.
+    ///
+    /// The returned bulk holds the chunk's elements with their IDs, custom
+    /// identifiers, tombstones and the field versions, so every [`Bulk`]
+    /// operation can be used on it. Changes to it are not written back.
+    ///
+    /// # Arguments
+    ///
+    /// * `i` - The chunk number
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::IndexOutOfBounds`] if there is no chunk `i`
+    /// - [`SoAKitError::InvalidArgument`] if the chunk file cannot be read
+    pub fn chunk(&self, i: usize) -> Result<Bulk> {
+        let chunk = self.load(i)?;
+        let (start, end) = self.chunk_range(i)?;
+        let ids = self
+            .meta
+            .id
+            .get(start..end)
+            .ok_or(SoAKitError::IndexOutOfBounds {
+                index: end,
+                max: self.meta.count,
+            })?;
+
+        let mut bulk = Bulk::new(chunk.len)?;
+        bulk.meta.id = ids.to_vec();
+        bulk.meta.versions = self.meta.versions.clone();
+        bulk.meta.custom_ids = ids
+            .iter()
+            .filter_map(|id| Some((*id, self.meta.custom_ids.get(id)?.clone())))
+            .collect();
+        bulk.meta.deleted = ids
+            .iter()
+            .filter(|id| self.meta.deleted.contains(id))
+            .copied()
+            .collect();
+        bulk.chunks = vec![Arc::unwrap_or_clone(chunk)];
+        Ok(bulk)
+    }
+
+    /// Run `func` on every chunk in turn, loaded as by [`DiskBulk::chunk`].
+    ///
+    /// # Arguments
+    ///
+    /// * `func` - Function called with each chunk number and chunk bulk
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`DiskBulk::chunk`] and the first error of `func`.
+    pub fn for_each_chunk<F>(&self, mut func: F) -> Result<()>
+    where
+        F: FnMut(usize, &Bulk) -> Result<()>,
+    {
+        for i in 0..self.chunk_lens.len() {
+            func(i, &self.chunk(i)?)?;
+        }
+        Ok(())
+    }
+
+    /// Get the values of a field, as by [`Bulk::get`].
+    ///
+    /// Stored fields and chunk-safe derived fields are read chunk by chunk;
+    /// other derived fields are computed from their full dependencies, and
+    /// virtual fields are resolved for the whole bulk. Derived values are not
+    /// cached.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Bulk::get`], and
+    /// [`SoAKitError::InvalidArgument`] if a chunk file cannot be read.
+    pub fn get(&self, registry: &Registry, field: &str) -> Result<Value> {
+        let metadata = registry
+            .get_metadata(field)
+            .ok_or_else(|| SoAKitError::FieldNotFound(field.to_string()))?;
+        if metadata.is_virtual() || (metadata.is_derived && !metadata.chunk_safe) {
+            let dependencies = metadata
+                .dependencies
+                .iter()
+                .map(|dep| self.get(registry, dep))
+                .collect::<Result<Vec<_>>>()?;
+            let chunk_offsets = chunk_offsets(self.chunk_lens.iter().copied());
+            let context = DerivedContext {
+                ids: &self.meta.id,
+                count: self.meta.count,
+                chunk_offsets: &chunk_offsets,
+            };
+            let value = match &metadata.resolver {
+                Some(resolver) => resolver(&context)?,
+                None => metadata.derive(&context, &dependencies)?,
+            };
+            metadata.check_output(field, &value, self.meta.count)?;
+            return Ok(value);
+        }
+        let parts = (0..self.chunk_lens.len())
+            .map(|i| self.chunk(i)?.get(registry, field))
+            .collect::<Result<Vec<_>>>()?;
+        Value::concat(&parts)
+    }
+
+    /// Get the value of one element of a stored field.
+    ///
+    /// Only the chunk holding the element is loaded.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::IndexOutOfBounds`] if `idx` is out of bounds
+    /// - [`SoAKitError::FieldNotFound`] if the field is not stored
+    /// - [`SoAKitError::InvalidArgument`] if the chunk file cannot be read
+    pub fn get_element(&self, field: &str, idx: usize) -> Result<Value> {
+        let (i, offset) = self.locate(idx)?;
+        let chunk = self.load(i)?;
+        let column = chunk
+            .column(field)
+            .ok_or_else(|| SoAKitError::FieldNotFound(field.to_string()))?;
+        column.get_element(offset)
+    }
+
+    /// Set the values of a field, as by [`Bulk::set_mut`].
+    ///
+    /// Every chunk file is rewritten with the new column at the new version, and
+    /// the field version is incremented. If writing a file fails, the chunks written before it
+    /// keep their new values.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Bulk::set`], and
+    /// [`SoAKitError::InvalidArgument`] if a file cannot be read or written.
+    pub fn set(&mut self, registry: &Registry, field: &str, values: Vec<Value>) -> Result<()> {
+        check_column(registry, field, self.meta.count, &values)?;
+        let new_ver = self.next_version(field)?;
+        for i in 0..self.chunk_lens.len() {
+            let (start, end) = self.chunk_range(i)?;
+            let mut chunk = Arc::unwrap_or_clone(self.load(i)?);
+            chunk.insert_column(field, chunk_of(&values, start, end)?);
+            let _ = chunk.versions.insert(field.to_string(), new_ver);
+            self.store_chunk(i, chunk)?;
+        }
+        self.store_version(field, new_ver)
+    }
+
+    /// Set one element of a stored field, as by [`Bulk::set_element_mut`].
+    ///
+    /// Only the chunk holding the element is loaded and rewritten, and only its
+    /// column moves to the new version (see [`Bulk::chunk_version`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Bulk::set_element`], and
+    /// [`SoAKitError::InvalidArgument`] if a file cannot be read or written.
+    pub fn set_element(
+        &mut self,
+        registry: &Registry,
+        field: &str,
+        idx: usize,
+        value: Value,
+    ) -> Result<()> {
+        if !registry.has_field(field) {
+            return Err(SoAKitError::FieldNotFound(field.to_string()));
+        }
+        registry.check_value(field, &value)?;
+        let (i, offset) = self.locate(idx)?;
+        let mut chunk = Arc::unwrap_or_clone(self.load(i)?);
+        let mut scalars = chunk
+            .column(field)
+            .ok_or_else(|| SoAKitError::FieldNotFound(field.to_string()))?
+            .to_scalars();
+        let slot = scalars
+            .get_mut(offset)
+            .ok_or(SoAKitError::IndexOutOfBounds {
+                index: idx,
+                max: self.meta.count,
+            })?;
+        if !slot.is_null() && !value.is_null() && slot.len() != value.len() {
+            return Err(SoAKitError::InvalidArgument(format!(
+                "Value at index {} has different length",
+                idx
+            )));
+        }
+        *slot = value;
+        let new_ver = self.next_version(field)?;
+        chunk.insert_column(field, Value::from_scalars(scalars)?);
+        let _ = chunk.versions.insert(field.to_string(), new_ver);
+        self.store_chunk(i, chunk)?;
+        self.store_version(field, new_ver)
+    }
+
+    /// Load every chunk into an in-memory bulk.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if a chunk file cannot be read
+    pub fn to_bulk(&self) -> Result<Bulk> {
+        let mut bulk = Bulk::new(self.meta.count)?;
+        bulk.meta = self.meta.clone();
+        bulk.chunks = (0..self.chunk_lens.len())
+            .map(|i| Ok(Arc::unwrap_or_clone(self.load(i)?)))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(bulk)
+    }
+
+    /// Get chunk `i`, reading it from disk if it is not loaded.
+    fn load(&self, i: usize) -> Result<Arc<Chunk>> {
+        if i >= self.chunk_lens.len() {
+            return Err(SoAKitError::IndexOutOfBounds {
+                index: i,
+                max: self.chunk_lens.len(),
+            });
+        }
+        if let Some(chunk) = self.touch(i) {
+            return Ok(chunk);
+        }
+        let file = fs::File::open(self.chunk_path(i)).map_err(io_error)?;
+        // SAFETY: the mapping is only read while decoding and dropped right after.
+        // Chunk files are never changed in place: `write_file` replaces them by
+        // renaming a new file over them, which leaves this mapping on the old
+        // file, and other processes must not modify them (see the module docs).
+        let bytes = unsafe { memmap2::Mmap::map(&file) }.map_err(io_error)?;
+        let chunk = Arc::new(decode::<Chunk>(&bytes)?);
+        self.insert_loaded(i, Arc::clone(&chunk));
+        Ok(chunk)
+    }
+
+    /// Get chunk `i` if it is loaded, marking it as most recently used.
+    fn touch(&self, i: usize) -> Option<Arc<Chunk>> {
+        let mut loaded = self.lock();
+        let chunk = loaded.chunks.get(&i).cloned()?;
+        loaded.recent.retain(|&used| used != i);
+        loaded.recent.push_back(i);
+        Some(chunk)
+    }
+
+    /// Keep chunk `i` in memory, evicting the least recently used chunks.
+    fn insert_loaded(&self, i: usize, chunk: Arc<Chunk>) {
+        let mut loaded = self.lock();
+        let _ = loaded.chunks.insert(i, chunk);
+        loaded.recent.retain(|&used| used != i);
+        loaded.recent.push_back(i);
+        while loaded.chunks.len() > self.capacity {
+            let Some(evicted) = loaded.recent.pop_front() else {
+                break;
+            };
+            let _ = loaded.chunks.remove(&evicted);
+        }
+    }
+
+    /// Write chunk `i` to disk and keep the new version in memory.
+    fn store_chunk(&self, i: usize, chunk: Chunk) -> Result<()> {
+        self.write_chunk(i, &chunk)?;
+        self.insert_loaded(i, Arc::new(chunk));
+        Ok(())
+    }
+
+    /// Get the version a field moves to on its next update.
+    fn next_version(&self, field: &str) -> Result<u64> {
+        self.meta
+            .versions
+            .get(field)
+            .copied()
+            .unwrap_or(0)
+            .checked_add(1)
+            .ok_or_else(|| SoAKitError::InvalidArgument("Version overflow".to_string()))
+    }
+
+    /// Record the new version of a field and write the metadata.
+    fn store_version(&mut self, field: &str, new_ver: u64) -> Result<()> {
+        let _ = self.meta.versions.insert(field.to_string(), new_ver);
+        self.write_meta()
+    }
+
+    /// Record the version of every column of a chunk in the chunk itself.
+    ///
+    /// Columns without a version of their own are at their field's version, and
+    /// keep it when a later update moves only some chunks to a new version.
+    fn pin_versions(&self, chunk: &mut Chunk) {
+        for field in chunk.field_names() {
+            if let Some(&version) = self.meta.versions.get(&field) {
+                let _ = chunk.versions.entry(field).or_insert(version);
+            }
+        }
+    }
+
+    /// Get the element range `start..end` of chunk `i`.
+    fn chunk_range(&self, i: usize) -> Result<(usize, usize)> {
+        let start = self
+            .chunk_lens
+            .iter()
+            .take(i)
+            .try_fold(0_usize, |start, &len| start.checked_add(len))
+            .ok_or_else(|| SoAKitError::InvalidArgument("Arithmetic overflow".to_string()))?;
+        let len = self
+            .chunk_lens
+            .get(i)
+            .ok_or(SoAKitError::IndexOutOfBounds {
+                index: i,
+                max: self.chunk_lens.len(),
+            })?;
+        let end = start
+            .checked_add(*len)
+            .ok_or_else(|| SoAKitError::InvalidArgument("Arithmetic overflow".to_string()))?;
+        Ok((start, end))
+    }
+
+    /// Get the chunk number and offset within it of element `idx`.
+    fn locate(&self, idx: usize) -> Result<(usize, usize)> {
+        let mut offset = idx;
+        for (i, &len) in self.chunk_lens.iter().enumerate() {
+            if offset < len {
+                return Ok((i, offset));
+            }
+            offset = offset.saturating_sub(len);
+        }
+        Err(SoAKitError::IndexOutOfBounds {
+            index: idx,
+            max: self.meta.count,
+        })
+    }
+
+    /// Write chunk `i` to its file.
+    fn write_chunk(&self, i: usize, chunk: &Chunk) -> Result<()> {
+        write_file(&self.chunk_path(i), &encode(chunk)?)
+    }
+
+    /// Write the metadata file.
+    fn write_meta(&self) -> Result<()> {
+        let disk_meta = DiskMeta {
+            meta: self.meta.clone(),
+            chunk_lens: self.chunk_lens.clone(),
+        };
+        write_file(&self.dir.join(META_FILE), &encode(&disk_meta)?)
+    }
+
+    /// Get the path of the file of chunk `i`.
+    fn chunk_path(&self, i: usize) -> PathBuf {
+        self.dir.join(format!("chunk-{}.bin", i))
+    }
+
+    /// Lock the loaded chunks, recovering from a poisoned lock.
+    fn lock(&self) -> std::sync::MutexGuard<'_, LoadedChunks> {
+        self.loaded.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Replace the contents of a file by writing a temporary file and renaming it.
+///
+/// Readers that mapped the old file keep seeing its old contents.
+fn write_file(path: &Path, bytes: &[u8]) -> Result<()> {
+    let temp = path.with_extension("tmp");
+    fs::write(&temp, bytes).map_err(io_error)?;
+    fs::rename(&temp, path).map_err(io_error)
+}
+
+/// Encode a chunk or metadata file: the header, then the bincode encoding.
+fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+    let mut data = DISK_MAGIC.to_vec();
+    data.extend_from_slice(&BINARY_VERSION.to_le_bytes());
+    bincode::serialize_into(&mut data, value)
+        .map_err(|e| SoAKitError::InvalidArgument(e.to_string()))?;
+    Ok(data)
+}
+
+/// Decode a file written by [`encode`], checking its header.
+fn decode<T: DeserializeOwned>(data: &[u8]) -> Result<T> {
+    let header = data
+        .strip_prefix(&DISK_MAGIC)
+        .and_then(<[u8]>::split_first_chunk::<4>)
+        .ok_or_else(|| SoAKitError::InvalidArgument("Not a disk bulk file".to_string()))?;
+    match header {
+        (version, payload) if u32::from_le_bytes(*version) == BINARY_VERSION => {
+            bincode::deserialize(payload).map_err(|e| SoAKitError::InvalidArgument(e.to_string()))
+        }
+        (version, _) => Err(SoAKitError::InvalidArgument(format!(
+            "Unsupported disk bulk layout version {} (latest is {})",
+            u32::from_le_bytes(*version),
+            BINARY_VERSION
+        ))),
+    }
+}
+
+/// Convert an I/O error into a [`SoAKitError`].
+fn io_error(error: std::io::Error) -> SoAKitError {
+    SoAKitError::InvalidArgument(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bulk::CHUNK_SIZE;
+    use crate::meta::DerivedFunc;
+
+    #[test]
+    fn test_disk_bulk_loads_on_demand_and_evicts() {
+        let mut registry = Registry::new();
+        let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("n".to_string(), ints, false, vec![], None)
+            .unwrap();
+        let double: DerivedFunc = Box::new(|args: &[Value]| match args.first() {
+            Some(Value::VectorInt(v)) => Ok(Value::VectorInt(v.iter().map(|x| x * 2).collect())),
+            _ => Err(SoAKitError::InvalidArgument("expected ints".to_string())),
+        });
+        registry
+            .register(
+                "double".to_string(),
+                Box::new(|_| true),
+                true,
+                vec!["n".to_string()],
+                Some(double),
+            )
+            .unwrap();
+
+        let count = CHUNK_SIZE * 2 + 10;
+        let values: Vec<Value> = (0..count)
+            .map(|i| Value::ScalarInt(i64::try_from(i).unwrap()))
+            .collect();
+        let bulk = Bulk::new(count)
+            .unwrap()
+            .set(&registry, "n", values.clone())
+            .unwrap();
+        let bulk = bulk
+            .set_ids((0..count).map(|i| u64::try_from(i).unwrap() + 100))
+            .unwrap();
+
+        let dir = std::env::temp_dir().join(format!("soakit-disk-test-{}", std::process::id()));
+        let mut disk = DiskBulk::create(&dir, &bulk, 1).unwrap();
+        assert_eq!((disk.num_chunks(), disk.loaded_chunks()), (3, 0));
+
+        assert_eq!(
+            disk.get_element("n", CHUNK_SIZE + 1).unwrap(),
+            values[CHUNK_SIZE + 1]
+        );
+        assert_eq!(disk.loaded_chunks(), 1);
+        assert_eq!(
+            disk.get(&registry, "n").unwrap(),
+            bulk.get(&registry, "n").unwrap()
+        );
+        assert_eq!(
+            disk.get(&registry, "double").unwrap(),
+            bulk.get(&registry, "double").unwrap()
+        );
+        assert_eq!(disk.loaded_chunks(), 1);
+
+        let last = disk.chunk(2).unwrap();
+        assert_eq!(last.count(), 10);
+        assert_eq!(
+            last.index_of_id(u64::try_from(CHUNK_SIZE * 2).unwrap() + 100),
+            Some(0)
+        );
+
+        disk.set_element(&registry, "n", count - 1, Value::ScalarInt(-1))
+            .unwrap();
+        // Only the rewritten chunk moves to the new version
+        assert_eq!(disk.chunk(2).unwrap().chunk_version("n", 0), Some(2));
+        assert_eq!(disk.chunk(0).unwrap().chunk_version("n", 0), Some(1));
+        let mut seen = 0;
+        disk.for_each_chunk(|_, chunk| {
+            seen += chunk.count();
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(seen, count);
+
+        let reopened = DiskBulk::open(&dir, 2).unwrap();
+        assert_eq!(reopened.meta().versions["n"], 2);
+        assert_eq!(
+            reopened.get_element("n", count - 1).unwrap(),
+            Value::ScalarInt(-1)
+        );
+        assert_eq!(
+            reopened.to_bulk().unwrap(),
+            bulk.set_element(&registry, "n", count - 1, Value::ScalarInt(-1))
+                .unwrap()
+        );
+
+        assert!(matches!(
+            disk.set(&registry, "n", vec![Value::ScalarInt(0)]),
+            Err(SoAKitError::LengthMismatch { .. })
+        ));
+        assert!(matches!(
+            disk.get_element("n", count),
+            Err(SoAKitError::IndexOutOfBounds { .. })
+        ));
+        assert!(DiskBulk::open(dir.join("missing"), 1).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_disk_bulk_files_carry_layout_version() {
+        let dir = std::env::temp_dir().join(format!("soakit-disk-version-{}", std::process::id()));
+        let disk = DiskBulk::create(&dir, &Bulk::new(2).unwrap(), 1).unwrap();
+        let meta = fs::read(dir.join(META_FILE)).unwrap();
+        assert_eq!(meta.get(..8), Some(&DISK_MAGIC[..]));
+        assert_eq!(meta.get(8..12), Some(&BINARY_VERSION.to_le_bytes()[..]));
+
+        let mut newer = meta.clone();
+        let _ = newer.splice(8..12, (BINARY_VERSION + 1).to_le_bytes());
+        fs::write(dir.join(META_FILE), &newer).unwrap();
+        let err = DiskBulk::open(&dir, 1).err().unwrap();
+        assert!(
+            err.to_string()
+                .contains("Unsupported disk bulk layout version")
+        );
+
+        // Files without the header, as written before it existed, are rejected
+        fs::write(dir.join(META_FILE), meta.get(12..).unwrap()).unwrap();
+        assert!(DiskBulk::open(&dir, 1).is_err());
+        fs::write(disk.chunk_path(0), b"SOAKITD").unwrap();
+        assert!(disk.chunk(0).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
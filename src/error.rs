@@ -119,6 +119,67 @@ pub enum SoAKitError {
     /// - Registering "age" twice
     /// - Attempting to overwrite an existing field
     FieldAlreadyExists(String),
+    /// Derived fields depend on each other in a cycle.
+    ///
+    /// This error occurs when registering or renaming a field would make a derived
+    /// field depend, directly or indirectly, on itself, so it could never be computed.
+    ///
+    /// # Fields
+    ///
+    /// The fields on the cycle, in dependency order, starting and ending with the
+    /// same field (e.g. `["a", "b", "a"]` when `a` depends on `b` and `b` on `a`).
+    ///
+    /// # Examples
+    ///
+    /// - Registering `b` depending on `a` when `a` already depends on `b`
+    /// - Registering a derived field that depends on itself
+    DependencyCycle(Vec<String>),
+    /// A derived function produced an invalid value.
+    ///
+    /// This error occurs when a derived field is computed and the result does not
+    /// have one element per bulk element, or is rejected by the field's validator.
+    /// The check can be turned off per field with
+    /// [`Registry::set_validate_output`](crate::Registry::set_validate_output).
+    ///
+    /// # Fields
+    ///
+    /// * `field` - The derived field
+    /// * `reason` - What is wrong with the value
+    ///
+    /// # Examples
+    ///
+    /// - A derived function returning 3 values for a bulk of 4 elements
+    /// - A derived function returning strings for a field validating integers
+    DerivedOutputInvalid {
+        /// The derived field
+        field: String,
+        /// What is wrong with the value
+        reason: String,
+    },
+    /// A value does not have the shape declared for its field.
+    ///
+    /// This error occurs when a bulk update stores an element value whose shape
+    /// (see [`Value::shape`](crate::Value::shape)) differs from the shape
+    /// declared with [`Registry::set_shape`](crate::Registry::set_shape).
+    ///
+    /// # Fields
+    ///
+    /// * `field` - The field
+    /// * `expected` - The declared shape
+    /// * `actual` - The shape of the value
+    ///
+    /// # Examples
+    ///
+    /// - Setting a 2-element vector in a field declared as vectors of 3
+    /// - Setting a scalar in a field declared as 2x2 matrices
+    ShapeMismatch {
+        /// The field
+        field: String,
+        /// The declared shape
+        expected: Vec<usize>,
+        /// The shape of the value
+        actual: Vec<usize>,
+    },
 }
 
 impl fmt::Display for SoAKitError {
@@ -137,11 +198,7 @@ impl fmt::Display for SoAKitError {
                 write!(f, "Index {} out of bounds (max: {})", index, max)
             }
             SoAKitError::LengthMismatch { expected, actual } => {
-                write!(
-                    f,
-                    "Length mismatch: expected {}, got {}",
-                    expected, actual
-                )
+                write!(f, "Length mismatch: expected {}, got {}", expected, actual)
             }
             SoAKitError::DerivedFieldNoDeps(field) => {
                 write!(f, "Derived field '{}' has no dependencies", field)
@@ -149,6 +206,27 @@ impl fmt::Display for SoAKitError {
             SoAKitError::FieldAlreadyExists(field) => {
                 write!(f, "Field '{}' already exists", field)
             }
+            SoAKitError::DependencyCycle(fields) => {
+                write!(f, "Dependency cycle: {}", fields.join(" -> "))
+            }
+            SoAKitError::DerivedOutputInvalid { field, reason } => {
+                write!(
+                    f,
+                    "Derived field '{}' produced an invalid value: {}",
+                    field, reason
+                )
+            }
+            SoAKitError::ShapeMismatch {
+                field,
+                expected,
+                actual,
+            } => {
+                write!(
+                    f,
+                    "Shape mismatch for field '{}': expected {:?}, got {:?}",
+                    field, expected, actual
+                )
+            }
         }
     }
 }
@@ -215,6 +293,38 @@ mod tests {
         assert_eq!(display_str, "Field 'age' already exists");
     }
 
+    #[test]
+    fn test_dependency_cycle_display() {
+        let cycle = ["a", "b", "a"].map(String::from).to_vec();
+        let err = SoAKitError::DependencyCycle(cycle);
+        assert_eq!(format!("{}", err), "Dependency cycle: a -> b -> a");
+    }
+
+    #[test]
+    fn test_derived_output_invalid_display() {
+        let err = SoAKitError::DerivedOutputInvalid {
+            field: "total".to_string(),
+            reason: "expected 4 values, got 3".to_string(),
+        };
+        assert_eq!(
+            format!("{}", err),
+            "Derived field 'total' produced an invalid value: expected 4 values, got 3"
+        );
+    }
+
+    #[test]
+    fn test_shape_mismatch_display() {
+        let err = SoAKitError::ShapeMismatch {
+            field: "position".to_string(),
+            expected: vec![3],
+            actual: vec![2],
+        };
+        assert_eq!(
+            format!("{}", err),
+            "Shape mismatch for field 'position': expected [3], got [2]"
+        );
+    }
+
     #[test]
     fn test_error_equality() {
         let err1 = SoAKitError::InvalidArgument("test".to_string());
@@ -255,6 +365,16 @@ mod tests {
             },
             SoAKitError::DerivedFieldNoDeps("field".to_string()),
             SoAKitError::FieldAlreadyExists("field".to_string()),
+            SoAKitError::DependencyCycle(vec!["field".to_string()]),
+            SoAKitError::DerivedOutputInvalid {
+                field: "field".to_string(),
+                reason: "msg".to_string(),
+            },
+            SoAKitError::ShapeMismatch {
+                field: "field".to_string(),
+                expected: vec![],
+                actual: vec![0],
+            },
         ];
 
         for err in errors {
@@ -344,4 +464,3 @@ mod tests {
         }
     }
 }
-
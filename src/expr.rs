@@ -0,0 +1,1028 @@
+/// Expressions over bulk fields.
+///
+/// An [`Expr`] combines field references and literals with arithmetic,
+/// comparison and boolean operators. Expressions can be built in Rust with
+/// [`col`] and [`lit`], or parsed from text with [`Expr::parse`]:
+///
+/// ```text
+/// age > 30 && active == true
+/// (price * qty >= 100 || vip) && !(region == "north")
+/// ```
+///
+/// [`Bulk::eval`] evaluates an expression one chunk at a time into a column,
+/// and [`Bulk::query`] and [`Bulk::filter_expr`] keep the elements for which it
+/// is `true`. [`Registry::register_expr`] turns an expression into a derived
+/// field, so simple filters and derived fields don't need Rust closures.
+///
+/// Arithmetic follows the `Value` operators (`+` on strings concatenates).
+/// Comparisons promote integers to floats when mixed with floats; comparing
+/// with a missing or NaN element is `false`, except that `== null` and
+/// `!= null` test for missing elements. Boolean operators treat missing
+/// elements as `false`.
+use crate::bulk::Bulk;
+use crate::error::{Result, SoAKitError};
+use crate::meta::Registry;
+use crate::value::Value;
+use std::cmp::Ordering;
+use std::ops::{Add, Div, Mul, Not, Sub};
+
+/// A binary operator of an [`Expr`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinaryOp {
+    /// `+`
+    Add,
+    /// `-`
+    Sub,
+    /// `*`
+    Mul,
+    /// `/`
+    Div,
+    /// `==`
+    Eq,
+    /// `!=`
+    Ne,
+    /// `<`
+    Lt,
+    /// `<=`
+    Le,
+    /// `>`
+    Gt,
+    /// `>=`
+    Ge,
+    /// `&&`
+    And,
+    /// `||`
+    Or,
+}
+
+/// An expression over the fields of a bulk.
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::expr::{col, Expr};
+///
+/// let built = col("age").gt(30).and(col("active").eq(true));
+/// assert_eq!(Expr::parse("age > 30 && active == true").unwrap(), built);
+/// assert_eq!(built.columns(), vec!["age", "active"]);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    /// The value of a field
+    Column(String),
+    /// A constant
+    Literal(Value),
+    /// Boolean negation
+    Not(Box<Expr>),
+    /// A binary operation
+    Binary {
+        /// The operator
+        op: BinaryOp,
+        /// The left operand
+        left: Box<Expr>,
+        /// The right operand
+        right: Box<Expr>,
+    },
+}
+
+/// Refer to a field by name.
+pub fn col(name: &str) -> Expr {
+    Expr::Column(name.to_string())
+}
+
+/// Wrap a constant.
+pub fn lit(value: impl Into<Value>) -> Expr {
+    Expr::Literal(value.into())
+}
+
+impl Expr {
+    /// Parse an expression from text.
+    ///
+    /// The grammar, from lowest to highest precedence:
+    ///
+    /// - `a || b`, `a && b`
+    /// - `!a`
+    /// - `a == b`, `a != b`, `a < b`, `a <= b`, `a > b`, `a >= b`
+    /// - `a + b`, `a - b`, then `a * b`, `a / b`
+    /// - unary `-a`
+    /// - field names, integers (a `-` directly before digits, where no operand
+    ///   precedes it, is part of the literal, so `-9223372036854775808` parses), floats, `'strings'` or `"strings"`, `true`,
+    ///   `false`, `null` and parenthesized expressions
+    ///
+    /// Parentheses, `!` and unary `-` nest at most 64 levels deep, and the parsed
+    /// expression may be at most 256 levels deep (so a chain such as `a + b + ...`
+    /// has at most 255 operators), so that no text can exhaust the stack while
+    /// parsing or evaluating.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The expression to parse
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Expr)` with the parsed expression.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if the text is not a valid expression or
+    ///   is nested too deeply
+    pub fn parse(text: &str) -> Result<Expr> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            depth: 0,
+            end: text.len(),
+        };
+        let expr = parser.or()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(expr),
+            Some((offset, token)) => Err(syntax_error(*offset, &format!("unexpected {:?}", token))),
+        }
+    }
+
+    /// Number of nodes on the longest path from the expression to a field or literal.
+    fn height(&self) -> usize {
+        match self {
+            Expr::Column(_) | Expr::Literal(_) => 1,
+            Expr::Not(inner) => inner.height().saturating_add(1),
+            Expr::Binary { left, right, .. } => left.height().max(right.height()).saturating_add(1),
+        }
+    }
+
+    /// The names of the fields the expression reads, in order of first use.
+    pub fn columns(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        self.collect_columns(&mut names);
+        names
+    }
+
+    /// Add the names of the fields the expression reads to `names`.
+    fn collect_columns(&self, names: &mut Vec<String>) {
+        match self {
+            Expr::Column(name) => {
+                if !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+            Expr::Literal(_) => {}
+            Expr::Not(inner) => inner.collect_columns(names),
+            Expr::Binary { left, right, .. } => {
+                left.collect_columns(names);
+                right.collect_columns(names);
+            }
+        }
+    }
+
+    /// Combine two expressions with a binary operator.
+    pub fn binary(self, op: BinaryOp, rhs: impl Into<Expr>) -> Expr {
+        Expr::Binary {
+            op,
+            left: Box::new(self),
+            right: Box::new(rhs.into()),
+        }
+    }
+
+    /// `self == rhs`
+    pub fn eq(self, rhs: impl Into<Expr>) -> Expr {
+        self.binary(BinaryOp::Eq, rhs)
+    }
+
+    /// `self != rhs`
+    pub fn ne(self, rhs: impl Into<Expr>) -> Expr {
+        self.binary(BinaryOp::Ne, rhs)
+    }
+
+    /// `self < rhs`
+    pub fn lt(self, rhs: impl Into<Expr>) -> Expr {
+        self.binary(BinaryOp::Lt, rhs)
+    }
+
+    /// `self <= rhs`
+    pub fn le(self, rhs: impl Into<Expr>) -> Expr {
+        self.binary(BinaryOp::Le, rhs)
+    }
+
+    /// `self > rhs`
+    pub fn gt(self, rhs: impl Into<Expr>) -> Expr {
+        self.binary(BinaryOp::Gt, rhs)
+    }
+
+    /// `self >= rhs`
+    pub fn ge(self, rhs: impl Into<Expr>) -> Expr {
+        self.binary(BinaryOp::Ge, rhs)
+    }
+
+    /// `self && rhs`
+    pub fn and(self, rhs: impl Into<Expr>) -> Expr {
+        self.binary(BinaryOp::And, rhs)
+    }
+
+    /// `self || rhs`
+    pub fn or(self, rhs: impl Into<Expr>) -> Expr {
+        self.binary(BinaryOp::Or, rhs)
+    }
+
+    /// Evaluate the expression over `len` elements.
+    ///
+    /// `lookup` returns the values of a field for those elements. The result is
+    /// a vector of `len` elements, or a scalar if the expression reads no field.
+    fn evaluate(&self, lookup: &dyn Fn(&str) -> Result<Value>, len: usize) -> Result<Value> {
+        match self {
+            Expr::Column(name) => lookup(name),
+            Expr::Literal(value) => Ok(value.clone()),
+            Expr::Not(inner) => {
+                let values = truth_values(&inner.evaluate(lookup, len)?, len)?;
+                Ok(Value::VectorBool(values.into_iter().map(|b| !b).collect()))
+            }
+            Expr::Binary { op, left, right } => {
+                let left = left.evaluate(lookup, len)?;
+                let right = right.evaluate(lookup, len)?;
+                match op {
+                    BinaryOp::Add => Add::add(&left, &right),
+                    BinaryOp::Sub => Sub::sub(&left, &right),
+                    BinaryOp::Mul => Mul::mul(&left, &right),
+                    BinaryOp::Div => Div::div(&left, &right),
+                    BinaryOp::And | BinaryOp::Or => {
+                        let pairs = truth_values(&left, len)?
+                            .into_iter()
+                            .zip(truth_values(&right, len)?);
+                        Ok(Value::VectorBool(if *op == BinaryOp::And {
+                            pairs.map(|(a, b)| a && b).collect()
+                        } else {
+                            pairs.map(|(a, b)| a || b).collect()
+                        }))
+                    }
+                    _ => {
+                        let pairs = elements(&left, len)?
+                            .into_iter()
+                            .zip(elements(&right, len)?);
+                        let results = pairs
+                            .map(|(a, b)| compare(*op, &a, &b))
+                            .collect::<Result<Vec<bool>>>()?;
+                        Ok(Value::VectorBool(results))
+                    }
+                }
+            }
+        }
+    }
+
+    /// Evaluate the expression over `len` elements into a column of `len` elements.
+    fn evaluate_column(&self, lookup: &dyn Fn(&str) -> Result<Value>, len: usize) -> Result<Value> {
+        let value = self.evaluate(lookup, len)?;
+        if value.is_scalar() || value.is_null() {
+            Value::from_scalars(vec![value; len])
+        } else if value.len() == len {
+            Ok(value)
+        } else {
+            Err(SoAKitError::LengthMismatch {
+                expected: len,
+                actual: value.len(),
+            })
+        }
+    }
+}
+
+impl From<Value> for Expr {
+    fn from(value: Value) -> Self {
+        Expr::Literal(value)
+    }
+}
+
+/// Implement `From<$ty> for Expr` as a literal.
+macro_rules! impl_literal_conversion {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for Expr {
+                fn from(value: $ty) -> Self {
+                    Expr::Literal(Value::from(value))
+                }
+            }
+        )*
+    };
+}
+
+impl_literal_conversion!(i64, i32, f64, bool, &str, String);
+
+/// Implement an arithmetic operator trait for `Expr`.
+macro_rules! impl_expr_op {
+    ($trait:ident, $method:ident, $op:expr) => {
+        impl<T: Into<Expr>> $trait<T> for Expr {
+            type Output = Expr;
+
+            fn $method(self, rhs: T) -> Expr {
+                self.binary($op, rhs)
+            }
+        }
+    };
+}
+
+impl_expr_op!(Add, add, BinaryOp::Add);
+impl_expr_op!(Sub, sub, BinaryOp::Sub);
+impl_expr_op!(Mul, mul, BinaryOp::Mul);
+impl_expr_op!(Div, div, BinaryOp::Div);
+
+impl Not for Expr {
+    type Output = Expr;
+
+    fn not(self) -> Expr {
+        Expr::Not(Box::new(self))
+    }
+}
+
+impl Bulk {
+    /// Evaluate an expression into a column, one chunk at a time.
+    ///
+    /// Stored fields are read from each chunk; derived fields are computed once
+    /// and sliced per chunk.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `expr` - The expression to evaluate
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Value)` with one element per element of the bulk.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the expression reads a missing field
+    /// - [`SoAKitError::InvalidArgument`] if an operator is applied to unsupported
+    ///   types, such as ordering a string against a number
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::expr::col;
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("price".to_string(), validator.clone(), false, vec![], None).unwrap();
+    /// registry.register("qty".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(2).unwrap()
+    ///     .set(&registry, "price", vec![Value::ScalarInt(3), Value::ScalarInt(5)]).unwrap()
+    ///     .set(&registry, "qty", vec![Value::ScalarInt(2), Value::ScalarInt(4)]).unwrap();
+    ///
+    /// let total = bulk.eval(&registry, &(col("price") * col("qty"))).unwrap();
+    /// assert_eq!(total, Value::VectorInt(vec![6, 20]));
+    /// ```
+    pub fn eval(&self, registry: &Registry, expr: &Expr) -> Result<Value> {
+        let mut derived = Vec::new();
+        for name in expr.columns() {
+            if !registry.has_field(&name) {
+                return Err(SoAKitError::FieldNotFound(name));
+            }
+            if registry
+                .get_metadata(&name)
+                .is_some_and(|m| m.is_derived || m.is_virtual())
+            {
+                let value = self.get(registry, &name)?;
+                derived.push((name, value));
+            }
+        }
+
+        if self.chunks.is_empty() {
+            let lookup = |name: &str| match derived.iter().find(|(n, _)| n == name) {
+                Some((_, value)) => Ok(value.clone()),
+                None => Err(SoAKitError::FieldNotFound(name.to_string())),
+            };
+            return expr.evaluate_column(&lookup, self.meta.count);
+        }
+
+        let mut pieces = Vec::with_capacity(self.chunks.len());
+        for (chunk, (start, end)) in self.chunks.iter().zip(self.all_chunk_bounds()?) {
+            let lookup = |name: &str| match derived.iter().find(|(n, _)| n == name) {
+                Some((_, value)) => value.slice(start..end),
+                None => chunk
+                    .column(name)
+                    .map(std::borrow::Cow::into_owned)
+                    .ok_or_else(|| SoAKitError::FieldNotFound(name.to_string())),
+            };
+            pieces.push(expr.evaluate_column(&lookup, chunk.len)?);
+        }
+        Value::concat(&pieces)
+    }
+
+    /// Evaluate a boolean expression for every element.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Vec<bool>)` with the expression's value for each element;
+    /// missing results count as `false`.
+    ///
+    /// # Errors
+    ///
+    /// - The errors of [`Bulk::eval`]
+    /// - [`SoAKitError::InvalidArgument`] if the expression is not boolean
+    pub fn eval_mask(&self, registry: &Registry, expr: &Expr) -> Result<Vec<bool>> {
+        truth_values(&self.eval(registry, expr)?, self.meta.count)
+    }
+
+    /// Keep the elements for which a boolean expression is `true`.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with the matching elements.
+    ///
+    /// # Errors
+    ///
+    /// - The errors of [`Bulk::eval_mask`] and [`Bulk::remove_where`]
+    pub fn filter_expr(&self, registry: &Registry, expr: &Expr) -> Result<Self> {
+        let remove: Vec<bool> = self
+            .eval_mask(registry, expr)?
+            .into_iter()
+            .map(|keep| !keep)
+            .collect();
+        self.remove_where(&remove)
+    }
+
+    /// Keep the elements matching a textual query.
+    ///
+    /// This parses `query` with [`Expr::parse`] and calls [`Bulk::filter_expr`].
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `query` - A boolean expression, such as `"age > 30 && active == true"`
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with the matching elements.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if the query does not parse
+    /// - The errors of [`Bulk::filter_expr`]
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("age".to_string(), ints, false, vec![], None).unwrap();
+    /// let bools = Box::new(|v: &Value| matches!(v, Value::ScalarBool(_)));
+    /// registry.register("active".to_string(), bools, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(3).unwrap()
+    ///     .set(&registry, "age", vec![Value::ScalarInt(25), Value::ScalarInt(40), Value::ScalarInt(35)])
+    ///     .unwrap()
+    ///     .set(&registry, "active", vec![Value::ScalarBool(true), Value::ScalarBool(true), Value::ScalarBool(false)])
+    ///     .unwrap();
+    ///
+    /// let matched = bulk.query(&registry, "age > 30 && active == true").unwrap();
+    /// assert_eq!(matched.get(&registry, "age").unwrap(), Value::VectorInt(vec![40]));
+    /// ```
+    pub fn query(&self, registry: &Registry, query: &str) -> Result<Self> {
+        self.filter_expr(registry, &Expr::parse(query)?)
+    }
+}
+
+impl Registry {
+    /// Register a derived field computed by an expression.
+    ///
+    /// The field depends on the fields the expression reads and is registered
+    /// with [`Registry::register_chunk_safe`], since expressions are evaluated
+    /// element by element. Computed values are not validated.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the field (must be valid and unique)
+    /// * `expr` - The expression computing the field
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Registry::register`]; an expression that
+    /// reads no field fails with [`SoAKitError::DerivedFieldNoDeps`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::expr::Expr;
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
+    /// registry.register("celsius".to_string(), validator, false, vec![], None).unwrap();
+    /// let fahrenheit = Expr::parse("celsius * 1.8 + 32").unwrap();
+    /// registry.register_expr("fahrenheit".to_string(), fahrenheit).unwrap();
+    ///
+    /// let bulk = Bulk::new(2).unwrap();
+    /// let bulk = bulk.set(&registry, "celsius", vec![Value::ScalarFloat(0.0), Value::ScalarFloat(100.0)]).unwrap();
+    /// assert_eq!(bulk.get(&registry, "fahrenheit").unwrap(), Value::VectorFloat(vec![32.0, 212.0]));
+    /// ```
+    pub fn register_expr(&mut self, name: String, expr: Expr) -> Result<()> {
+        let dependencies = expr.columns();
+        let names = dependencies.clone();
+        let derived_func = Box::new(move |args: &[Value]| {
+            let len = args.first().map_or(0, Value::len);
+            let lookup = |field: &str| {
+                names
+                    .iter()
+                    .position(|name| name == field)
+                    .and_then(|i| args.get(i))
+                    .cloned()
+                    .ok_or_else(|| SoAKitError::FieldNotFound(field.to_string()))
+            };
+            expr.evaluate_column(&lookup, len)
+        });
+        self.register_chunk_safe(name, Box::new(|_: &Value| true), dependencies, derived_func)
+    }
+}
+
+/// The elements of an operand over `len` elements, broadcasting scalars.
+fn elements(value: &Value, len: usize) -> Result<Vec<Value>> {
+    if value.is_scalar() || value.is_null() {
+        return Ok(vec![value.clone(); len]);
+    }
+    if value.len() != len {
+        return Err(SoAKitError::LengthMismatch {
+            expected: len,
+            actual: value.len(),
+        });
+    }
+    Ok(value.to_scalars())
+}
+
+/// The elements of a boolean operand over `len` elements; missing elements are `false`.
+fn truth_values(value: &Value, len: usize) -> Result<Vec<bool>> {
+    elements(value, len)?
+        .iter()
+        .map(|element| match element {
+            Value::ScalarBool(b) => Ok(*b),
+            Value::Null => Ok(false),
+            other => Err(SoAKitError::InvalidArgument(format!(
+                "Expected a boolean in expression, got {}",
+                other
+            ))),
+        })
+        .collect()
+}
+
+/// Apply a comparison operator to two scalars.
+fn compare(op: BinaryOp, a: &Value, b: &Value) -> Result<bool> {
+    if a.is_null() || b.is_null() {
+        let both = a.is_null() && b.is_null();
+        return Ok(match op {
+            BinaryOp::Eq => both,
+            BinaryOp::Ne => !both,
+            _ => false,
+        });
+    }
+    let ordering = match (a, b) {
+        (Value::ScalarInt(x), Value::ScalarInt(y)) => Some(x.cmp(y)),
+        (Value::ScalarFloat(x), Value::ScalarFloat(y)) => x.partial_cmp(y),
+        (Value::ScalarInt(_), Value::ScalarFloat(y)) => as_float(a).and_then(|x| x.partial_cmp(y)),
+        (Value::ScalarFloat(x), Value::ScalarInt(_)) => as_float(b).and_then(|y| x.partial_cmp(&y)),
+        (Value::ScalarBool(x), Value::ScalarBool(y)) => Some(x.cmp(y)),
+        (Value::ScalarString(x), Value::ScalarString(y)) => Some(x.cmp(y)),
+        _ if matches!(op, BinaryOp::Eq | BinaryOp::Ne) => {
+            return Ok((op == BinaryOp::Ne) != (a.total_cmp(b) == Ordering::Equal));
+        }
+        _ => {
+            return Err(SoAKitError::InvalidArgument(format!(
+                "Cannot compare {} with {}",
+                a, b
+            )));
+        }
+    };
+    // NaN compares unequal to everything
+    let Some(ordering) = ordering else {
+        return Ok(op == BinaryOp::Ne);
+    };
+    Ok(match op {
+        BinaryOp::Eq => ordering.is_eq(),
+        BinaryOp::Ne => ordering.is_ne(),
+        BinaryOp::Lt => ordering.is_lt(),
+        BinaryOp::Le => ordering.is_le(),
+        BinaryOp::Gt => ordering.is_gt(),
+        _ => ordering.is_ge(),
+    })
+}
+
+/// Promote an integer scalar to a float.
+fn as_float(value: &Value) -> Option<f64> {
+    match value.cast(crate::value::ValueKind::Float) {
+        Ok(Value::ScalarFloat(x)) => Some(x),
+        _ => None,
+    }
+}
+
+/// Build the error for malformed expression text.
+fn syntax_error(offset: usize, message: &str) -> SoAKitError {
+    SoAKitError::InvalidArgument(format!(
+        "Invalid expression at position {}: {}",
+        offset, message
+    ))
+}
+
+/// A lexical token of expression text.
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    /// A field name or keyword
+    Ident(String),
+    /// A literal number or string
+    Literal(Value),
+    /// An operator or parenthesis
+    Symbol(&'static str),
+}
+
+/// Deepest nesting of parentheses, `!` and unary `-` accepted by [`Expr::parse`].
+///
+/// Each level costs several parser frames, which are large in debug builds, so
+/// this stays well within the 2 MiB stack of a spawned thread.
+const MAX_NESTING: usize = 64;
+
+/// Deepest expression [`Expr::parse`] builds, bounding the recursion of evaluation.
+const MAX_HEIGHT: usize = 256;
+
+/// Operators and parentheses, longest first so that `<=` wins over `<`.
+const SYMBOLS: [&str; 16] = [
+    "&&", "||", "==", "!=", "<=", ">=", "<", ">", "!", "+", "-", "*", "/", "(", ")", "=",
+];
+
+/// Split expression text into tokens, each with its byte offset.
+fn tokenize(text: &str) -> Result<Vec<(usize, Token)>> {
+    let mut tokens = Vec::new();
+    let mut rest = text.char_indices().peekable();
+    while let Some(&(offset, c)) = rest.peek() {
+        let tail = text.get(offset..).unwrap_or_default();
+        if c.is_whitespace() {
+            let _ = rest.next();
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let len = tail
+                .find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_'))
+                .unwrap_or(tail.len());
+            let word = tail.get(..len).unwrap_or_default();
+            tokens.push((offset, Token::Ident(word.to_string())));
+            let _ = rest.nth(len.saturating_sub(1));
+        } else if c.is_ascii_digit() || c == '.' || starts_negative_literal(tail, &tokens) {
+            let digits = tail.strip_prefix('-').unwrap_or(tail);
+            let len = digits
+                .find(|ch: char| !(ch.is_ascii_digit() || ch == '.'))
+                .unwrap_or(digits.len())
+                .saturating_add(tail.len().saturating_sub(digits.len()));
+            let number = tail.get(..len).unwrap_or_default();
+            let value = if number.contains('.') {
+                number.parse().map(Value::ScalarFloat).ok()
+            } else {
+                number.parse().map(Value::ScalarInt).ok()
+            };
+            let value = value.ok_or_else(|| syntax_error(offset, "invalid number"))?;
+            tokens.push((offset, Token::Literal(value)));
+            let _ = rest.nth(len.saturating_sub(1));
+        } else if c == '\'' || c == '"' {
+            let body = tail.get(1..).unwrap_or_default();
+            let len = body
+                .find(c)
+                .ok_or_else(|| syntax_error(offset, "unterminated string"))?;
+            let string = body.get(..len).unwrap_or_default();
+            tokens.push((
+                offset,
+                Token::Literal(Value::ScalarString(string.to_string())),
+            ));
+            // Skip the string and both quotes
+            let _ = rest.nth(string.chars().count().saturating_add(1));
+        } else if let Some(symbol) = SYMBOLS.iter().find(|symbol| tail.starts_with(**symbol)) {
+            if *symbol == "=" {
+                return Err(syntax_error(offset, "use == to compare"));
+            }
+            tokens.push((offset, Token::Symbol(symbol)));
+            let _ = rest.nth(symbol.len().saturating_sub(1));
+        } else {
+            return Err(syntax_error(
+                offset,
+                &format!("unexpected character {:?}", c),
+            ));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Check whether `tail` starts with a `-` that belongs to a number literal.
+///
+/// That is a `-` directly followed by a digit where no operand precedes it, so
+/// that the literal `-9223372036854775808` is parsed whole instead of negating
+/// a positive literal that does not fit; `a -1` still subtracts.
+fn starts_negative_literal(tail: &str, tokens: &[(usize, Token)]) -> bool {
+    let follows_operand = matches!(
+        tokens.last(),
+        Some((_, Token::Ident(_) | Token::Literal(_) | Token::Symbol(")")))
+    );
+    tail.strip_prefix('-')
+        .is_some_and(|digits| digits.starts_with(|ch: char| ch.is_ascii_digit()))
+        && !follows_operand
+}
+
+/// Recursive-descent parser over the tokens of an expression.
+struct Parser {
+    /// Tokens with their byte offsets
+    tokens: Vec<(usize, Token)>,
+    /// Index of the next token
+    pos: usize,
+    /// Number of parentheses, `!` and unary `-` enclosing the next token
+    depth: usize,
+    /// Byte length of the expression text
+    end: usize,
+}
+
+impl Parser {
+    /// Get the byte offset of the next token, or the length of the text at the end.
+    fn offset(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .map_or(self.end, |(offset, _)| *offset)
+    }
+
+    /// Run `parse` one nesting level deeper, failing past [`MAX_NESTING`].
+    fn nested(&mut self, offset: usize, parse: fn(&mut Self) -> Result<Expr>) -> Result<Expr> {
+        if self.depth >= MAX_NESTING {
+            return Err(syntax_error(offset, "expression nested too deeply"));
+        }
+        self.depth = self.depth.saturating_add(1);
+        let result = parse(self);
+        self.depth = self.depth.saturating_sub(1);
+        result
+    }
+
+    /// Check that a newly built expression is not deeper than [`MAX_HEIGHT`].
+    fn checked(&self, expr: Expr) -> Result<Expr> {
+        if expr.height() > MAX_HEIGHT {
+            return Err(syntax_error(self.offset(), "expression nested too deeply"));
+        }
+        Ok(expr)
+    }
+
+    /// Consume the next token if it is the given symbol.
+    fn eat(&mut self, symbol: &str) -> bool {
+        let matched = matches!(
+            self.tokens.get(self.pos),
+            Some((_, Token::Symbol(s))) if *s == symbol
+        );
+        if matched {
+            self.pos = self.pos.saturating_add(1);
+        }
+        matched
+    }
+
+    /// Parse `a || b || ...`.
+    fn or(&mut self) -> Result<Expr> {
+        let mut expr = self.and()?;
+        while self.eat("||") {
+            let rhs = self.and()?;
+            expr = self.checked(expr.or(rhs))?;
+        }
+        Ok(expr)
+    }
+
+    /// Parse `a && b && ...`.
+    fn and(&mut self) -> Result<Expr> {
+        let mut expr = self.not()?;
+        while self.eat("&&") {
+            let rhs = self.not()?;
+            expr = self.checked(expr.and(rhs))?;
+        }
+        Ok(expr)
+    }
+
+    /// Parse `!a`.
+    fn not(&mut self) -> Result<Expr> {
+        let offset = self.offset();
+        if self.eat("!") {
+            let inner = self.nested(offset, Self::not)?;
+            self.checked(!inner)
+        } else {
+            self.comparison()
+        }
+    }
+
+    /// Parse `a <op> b` for a comparison operator.
+    fn comparison(&mut self) -> Result<Expr> {
+        let left = self.additive()?;
+        let ops = [
+            ("==", BinaryOp::Eq),
+            ("!=", BinaryOp::Ne),
+            ("<=", BinaryOp::Le),
+            (">=", BinaryOp::Ge),
+            ("<", BinaryOp::Lt),
+            (">", BinaryOp::Gt),
+        ];
+        for (symbol, op) in ops {
+            if self.eat(symbol) {
+                let right = self.additive()?;
+                return self.checked(left.binary(op, right));
+            }
+        }
+        Ok(left)
+    }
+
+    /// Parse `a + b - c ...`.
+    fn additive(&mut self) -> Result<Expr> {
+        let mut expr = self.multiplicative()?;
+        loop {
+            let op = if self.eat("+") {
+                BinaryOp::Add
+            } else if self.eat("-") {
+                BinaryOp::Sub
+            } else {
+                return Ok(expr);
+            };
+            let rhs = self.multiplicative()?;
+            expr = self.checked(expr.binary(op, rhs))?;
+        }
+    }
+
+    /// Parse `a * b / c ...`.
+    fn multiplicative(&mut self) -> Result<Expr> {
+        let mut expr = self.unary()?;
+        loop {
+            let op = if self.eat("*") {
+                BinaryOp::Mul
+            } else if self.eat("/") {
+                BinaryOp::Div
+            } else {
+                return Ok(expr);
+            };
+            let rhs = self.unary()?;
+            expr = self.checked(expr.binary(op, rhs))?;
+        }
+    }
+
+    /// Parse `-a`, folding negative number literals.
+    fn unary(&mut self) -> Result<Expr> {
+        let offset = self.offset();
+        if !self.eat("-") {
+            return self.primary();
+        }
+        match self.nested(offset, Self::unary)? {
+            Expr::Literal(Value::ScalarInt(x)) => x
+                .checked_neg()
+                .map(lit)
+                .ok_or_else(|| SoAKitError::InvalidArgument("Integer overflow".to_string())),
+            Expr::Literal(Value::ScalarFloat(x)) => Ok(lit(-x)),
+            expr => self.checked(lit(0).binary(BinaryOp::Sub, expr)),
+        }
+    }
+
+    /// Parse a literal, field name or parenthesized expression.
+    fn primary(&mut self) -> Result<Expr> {
+        let offset = self.offset();
+        let token = self
+            .tokens
+            .get(self.pos)
+            .map(|(_, token)| token.clone())
+            .ok_or_else(|| syntax_error(offset, "unexpected end of expression"))?;
+        self.pos = self.pos.saturating_add(1);
+        match token {
+            Token::Literal(value) => Ok(Expr::Literal(value)),
+            Token::Ident(word) => Ok(match word.as_str() {
+                "true" => lit(true),
+                "false" => lit(false),
+                "null" => Expr::Literal(Value::Null),
+                _ => Expr::Column(word),
+            }),
+            Token::Symbol("(") => {
+                let expr = self.nested(offset, Self::or)?;
+                if self.eat(")") {
+                    Ok(expr)
+                } else {
+                    Err(syntax_error(offset, "unclosed parenthesis"))
+                }
+            }
+            Token::Symbol(symbol) => Err(syntax_error(offset, &format!("unexpected {:?}", symbol))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bulk::CHUNK_SIZE;
+
+    #[test]
+    fn test_parse_precedence_and_errors() {
+        assert_eq!(
+            Expr::parse("a + 2 * b > -3 || !flag && name == 'x y'").unwrap(),
+            (col("a") + lit(2) * col("b"))
+                .gt(-3)
+                .or((!col("flag")).and(col("name").eq("x y")))
+        );
+        assert_eq!(Expr::parse("(a - 1.5) / 2").unwrap(), (col("a") - 1.5) / 2);
+        assert_eq!(Expr::parse("x != null").unwrap(), col("x").ne(Value::Null));
+        for bad in ["", "a >", "(a", "a = 1", "a # b", "'open", "1.2.3", "a b"] {
+            assert!(
+                matches!(Expr::parse(bad), Err(SoAKitError::InvalidArgument(_))),
+                "{:?} should not parse",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_end_offset_and_negative_literals() {
+        let message = |text: &str| Expr::parse(text).unwrap_err().to_string();
+        assert!(message("n > 1 &&").contains("position 8"));
+        assert!(message("(a").contains("position 0"));
+
+        assert_eq!(Expr::parse("-9223372036854775808").unwrap(), lit(i64::MIN));
+        assert_eq!(
+            Expr::parse("n > -9223372036854775808").unwrap(),
+            col("n").gt(i64::MIN)
+        );
+        assert_eq!(Expr::parse("(-2.5)").unwrap(), lit(-2.5));
+        assert_eq!(Expr::parse("--1").unwrap(), lit(1));
+        // After an operand, `-` still subtracts
+        assert_eq!(Expr::parse("a -1").unwrap(), col("a") - 1);
+        assert_eq!(Expr::parse("(a)-1").unwrap(), col("a") - 1);
+        assert!(Expr::parse("9223372036854775808").is_err());
+        assert!(Expr::parse("--9223372036854775808").is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_deep_nesting() {
+        let too_deep = |text: &str| matches!(Expr::parse(text), Err(SoAKitError::InvalidArgument(m)) if m.contains("deeply"));
+        let n = 200_000;
+        assert!(too_deep(&format!("{}a{}", "(".repeat(n), ")".repeat(n))));
+        assert!(too_deep(&format!("{}a", "!".repeat(n))));
+        assert!(too_deep(&format!("{}a", "-".repeat(n))));
+        assert!(too_deep(&format!("a{}", " + a".repeat(n))));
+        assert!(too_deep(&format!("{}a", "!(".repeat(n))));
+
+        assert!(too_deep(&format!("{}a{}", "(".repeat(65), ")".repeat(65))));
+
+        // Nesting up to the limits still parses
+        let nested = Expr::parse(&format!("{}a{}", "(".repeat(64), ")".repeat(64))).unwrap();
+        assert_eq!(nested, col("a"));
+        let negated = Expr::parse(&format!("{}a", "!!".repeat(32))).unwrap();
+        assert_eq!(negated.height(), 65);
+        assert!(Expr::parse(&format!("a{}", " || a".repeat(255))).is_ok());
+        assert!(too_deep(&format!("a{}", " || a".repeat(256))));
+    }
+
+    #[test]
+    fn test_query_filters_over_chunks() {
+        let mut registry = Registry::new();
+        let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("n".to_string(), ints, false, vec![], None)
+            .unwrap();
+        let floats = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
+        registry
+            .register("x".to_string(), floats, false, vec![], None)
+            .unwrap();
+        registry
+            .register_expr("half".to_string(), col("x") / 2)
+            .unwrap();
+        assert!(registry.get_metadata("half").unwrap().chunk_safe);
+
+        let count = CHUNK_SIZE + 100;
+        let bulk = Bulk::new(count).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "n",
+                (0..count).map(|i| Value::ScalarInt(i as i64)).collect(),
+            )
+            .unwrap()
+            .set(
+                &registry,
+                "x",
+                (0..count)
+                    .map(|i| Value::ScalarFloat(if i % 5 == 0 { f64::NAN } else { i as f64 }))
+                    .collect(),
+            )
+            .unwrap();
+
+        let filtered = bulk.query(&registry, "n >= 1000 && half > 550").unwrap();
+        let expected = bulk
+            .filter_field(
+                &registry,
+                "n",
+                |v| matches!(v, Value::ScalarInt(i) if *i > 1100 && i % 5 != 0),
+            )
+            .unwrap();
+        assert_eq!(filtered.meta.id, expected.meta.id);
+
+        let mask = bulk.eval_mask(&registry, &col("x").ne(col("x"))).unwrap();
+        assert_eq!(mask.iter().filter(|&&b| b).count(), count.div_ceil(5));
+        assert_eq!(
+            bulk.eval(&registry, &lit(1)).unwrap(),
+            Value::VectorInt(vec![1; count])
+        );
+        assert!(matches!(
+            bulk.query(&registry, "missing > 1"),
+            Err(SoAKitError::FieldNotFound(_))
+        ));
+        assert!(bulk.query(&registry, "n + 1").is_err());
+        assert!(bulk.query(&registry, "n < 'text'").is_err());
+        assert!(
+            registry
+                .register_expr("constant".to_string(), lit(1))
+                .is_err()
+        );
+    }
+}
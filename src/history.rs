@@ -0,0 +1,249 @@
+/// Undo and redo over successive bulk states.
+///
+/// Bulk updates return new bulks and leave the old ones untouched, and cloning a
+/// bulk shares every column with the original. A [`BulkHistory`] keeps each state
+/// it is given, so undo and redo never recompute anything, and a state costs only
+/// the columns that changed since the previous one.
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::history::BulkHistory;
+/// use soakit::{Bulk, Registry, Value};
+///
+/// let mut registry = Registry::new();
+/// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+/// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+///
+/// let bulk = Bulk::new(2).unwrap();
+/// let bulk = bulk.set(&registry, "age", vec![Value::ScalarInt(25); 2]).unwrap();
+/// let mut history = BulkHistory::new(bulk);
+/// history.checkpoint("loaded");
+///
+/// history.update(|b| b.set_element(&registry, "age", 0, Value::ScalarInt(40))).unwrap();
+/// history.undo().unwrap();
+/// assert_eq!(history.current().get(&registry, "age").unwrap(), Value::VectorInt(vec![25, 25]));
+/// history.redo().unwrap();
+/// assert_eq!(history.current().get(&registry, "age").unwrap(), Value::VectorInt(vec![40, 25]));
+///
+/// history.restore("loaded").unwrap();
+/// assert_eq!(history.current().get(&registry, "age").unwrap(), Value::VectorInt(vec![25, 25]));
+/// ```
+use crate::bulk::Bulk;
+use crate::error::{Result, SoAKitError};
+use std::collections::BTreeMap;
+
+/// A linear history of bulk states with undo, redo and named checkpoints.
+///
+/// Recording a state after undoing discards the undone states, as in an editor.
+#[derive(Clone, Debug)]
+pub struct BulkHistory {
+    /// States before the current one, oldest first
+    past: Vec<Bulk>,
+    /// The current state
+    current: Bulk,
+    /// Undone states, most recently undone last
+    future: Vec<Bulk>,
+    /// States saved by name with [`BulkHistory::checkpoint`]
+    checkpoints: BTreeMap<String, Bulk>,
+}
+
+impl BulkHistory {
+    /// Start a history whose only state is `bulk`.
+    pub const fn new(bulk: Bulk) -> Self {
+        Self {
+            past: Vec::new(),
+            current: bulk,
+            future: Vec::new(),
+            checkpoints: BTreeMap::new(),
+        }
+    }
+
+    /// Get the current state.
+    pub const fn current(&self) -> &Bulk {
+        &self.current
+    }
+
+    /// Record a new current state, discarding any undone states.
+    pub fn push(&mut self, bulk: Bulk) {
+        self.future.clear();
+        self.past.push(std::mem::replace(&mut self.current, bulk));
+    }
+
+    /// Compute a new state from the current one and record it.
+    ///
+    /// # Arguments
+    ///
+    /// * `func` - Function building the new state, such as a call to [`Bulk::set`]
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(&Bulk)` with the new current state.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error returned by `func`; the history is then unchanged.
+    pub fn update<F>(&mut self, func: F) -> Result<&Bulk>
+    where
+        F: FnOnce(&Bulk) -> Result<Bulk>,
+    {
+        let bulk = func(self.current())?;
+        self.push(bulk);
+        Ok(self.current())
+    }
+
+    /// Step back to the previous state.
+    ///
+    /// # Returns
+    ///
+    /// The new current state, or `None` if there is nothing to undo.
+    pub fn undo(&mut self) -> Option<&Bulk> {
+        let previous = self.past.pop()?;
+        self.future
+            .push(std::mem::replace(&mut self.current, previous));
+        Some(&self.current)
+    }
+
+    /// Step forward to the next undone state.
+    ///
+    /// # Returns
+    ///
+    /// The new current state, or `None` if there is nothing to redo.
+    pub fn redo(&mut self) -> Option<&Bulk> {
+        let next = self.future.pop()?;
+        self.past.push(std::mem::replace(&mut self.current, next));
+        Some(&self.current)
+    }
+
+    /// Get the number of states [`BulkHistory::undo`] can step back through.
+    pub const fn undo_depth(&self) -> usize {
+        self.past.len()
+    }
+
+    /// Get the number of states [`BulkHistory::redo`] can step forward through.
+    pub const fn redo_depth(&self) -> usize {
+        self.future.len()
+    }
+
+    /// Save the current state under a name, replacing any checkpoint of that name.
+    ///
+    /// Checkpoints outlive undo and redo: a checkpointed state stays available even
+    /// after the states recorded around it are discarded.
+    pub fn checkpoint(&mut self, name: &str) {
+        let bulk = self.current().clone();
+        let _ = self.checkpoints.insert(name.to_string(), bulk);
+    }
+
+    /// Make a checkpointed state current again.
+    ///
+    /// The state is recorded as a new state, so restoring can itself be undone.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name given to [`BulkHistory::checkpoint`]
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(&Bulk)` with the new current state.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if there is no checkpoint with that name
+    pub fn restore(&mut self, name: &str) -> Result<&Bulk> {
+        let bulk =
+            self.checkpoints.get(name).cloned().ok_or_else(|| {
+                SoAKitError::InvalidArgument(format!("Unknown checkpoint: {}", name))
+            })?;
+        self.push(bulk);
+        Ok(self.current())
+    }
+
+    /// Get the names of the saved checkpoints, in sorted order.
+    pub fn checkpoints(&self) -> Vec<String> {
+        self.checkpoints.keys().cloned().collect()
+    }
+
+    /// Remove a checkpoint.
+    ///
+    /// Returns `true` if a checkpoint with that name existed.
+    pub fn remove_checkpoint(&mut self, name: &str) -> bool {
+        self.checkpoints.remove(name).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meta::Registry;
+    use crate::value::Value;
+
+    #[test]
+    fn test_undo_redo_and_checkpoints() {
+        let mut registry = Registry::new();
+        let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("n".to_string(), ints, false, vec![], None)
+            .unwrap();
+        let values = |bulk: &Bulk| bulk.get(&registry, "n").unwrap();
+
+        let bulk = Bulk::new(3)
+            .unwrap()
+            .set(&registry, "n", vec![Value::ScalarInt(0); 3])
+            .unwrap();
+        let mut history = BulkHistory::new(bulk);
+        assert!(history.undo().is_none());
+        assert!(history.redo().is_none());
+        history.checkpoint("start");
+
+        for i in 1..=3 {
+            let _ = history
+                .update(|b| b.set_element(&registry, "n", 0, Value::ScalarInt(i)))
+                .unwrap();
+        }
+        assert_eq!((history.undo_depth(), history.redo_depth()), (3, 0));
+        assert_eq!(
+            values(history.undo().unwrap()),
+            Value::VectorInt(vec![2, 0, 0])
+        );
+        assert_eq!(
+            values(history.undo().unwrap()),
+            Value::VectorInt(vec![1, 0, 0])
+        );
+        assert_eq!(
+            values(history.redo().unwrap()),
+            Value::VectorInt(vec![2, 0, 0])
+        );
+        assert_eq!((history.undo_depth(), history.redo_depth()), (2, 1));
+
+        // A failed update leaves the history unchanged
+        assert!(
+            history
+                .update(|b| b.set_element(&registry, "n", 9, Value::ScalarInt(9)))
+                .is_err()
+        );
+        assert_eq!(history.redo_depth(), 1);
+
+        // Recording after undo discards the undone state
+        history.push(
+            history
+                .current()
+                .set_element(&registry, "n", 1, Value::ScalarInt(5))
+                .unwrap(),
+        );
+        assert_eq!(values(history.current()), Value::VectorInt(vec![2, 5, 0]));
+        assert!(history.redo().is_none());
+
+        let _ = history.restore("start").unwrap();
+        assert_eq!(values(history.current()), Value::VectorInt(vec![0, 0, 0]));
+        assert_eq!(
+            values(history.undo().unwrap()),
+            Value::VectorInt(vec![2, 5, 0])
+        );
+        assert_eq!(history.checkpoints(), vec!["start".to_string()]);
+        assert!(history.remove_checkpoint("start"));
+        assert!(matches!(
+            history.restore("start"),
+            Err(SoAKitError::InvalidArgument(_))
+        ));
+    }
+}
@@ -0,0 +1,413 @@
+/// Secondary indices over bulk fields.
+///
+/// [`Bulk::build_index`] maps every distinct value of a stored field to the
+/// positions of the elements holding it, so [`Bulk::lookup`] finds matching
+/// elements without scanning. A [`IndexKind::BTree`] index keeps its keys
+/// sorted and also answers [`Bulk::lookup_range`].
+///
+/// Indices are kept inside the bulk, like derived field caches, and are tied to
+/// the version of the field they were built from: once the field is updated the
+/// index is ignored until it is rebuilt. Operations that create a new bulk with
+/// different elements, such as [`Bulk::remove_where`], start without indices.
+///
+/// [`Bulk::join`] uses a current index over the right bulk's key field instead
+/// of hashing the key column, and [`Bulk::partition_by`] uses a current index to
+/// group elements.
+use crate::bulk::Bulk;
+use crate::error::{Result, SoAKitError};
+use crate::meta::Registry;
+use crate::value::{HashableValue, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::ops::{Bound, RangeBounds};
+
+/// The data structure behind an index.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IndexKind {
+    /// Hash map: point lookups only
+    #[default]
+    Hash,
+    /// B-tree: point lookups and range lookups, keys in sorted order
+    BTree,
+}
+
+/// Positions of the elements holding each value, keyed by value.
+#[derive(Clone, Debug)]
+enum Entries {
+    /// Entries of an [`IndexKind::Hash`] index
+    Hash(HashMap<HashableValue, Vec<usize>>),
+    /// Entries of an [`IndexKind::BTree`] index
+    BTree(BTreeMap<HashableValue, Vec<usize>>),
+}
+
+/// An index over one field of a bulk.
+#[derive(Clone, Debug)]
+pub(crate) struct FieldIndex {
+    /// Version of the field the index was built from
+    version: u64,
+    /// Positions of the elements holding each value
+    entries: Entries,
+}
+
+impl FieldIndex {
+    /// Index the given column of scalars, built from field version `version`.
+    pub(crate) fn new(values: Vec<Value>, version: u64, kind: IndexKind) -> Self {
+        let keyed = values.into_iter().map(HashableValue::new).enumerate();
+        let entries = match kind {
+            IndexKind::Hash => {
+                let mut map: HashMap<HashableValue, Vec<usize>> = HashMap::new();
+                for (idx, key) in keyed {
+                    map.entry(key).or_default().push(idx);
+                }
+                Entries::Hash(map)
+            }
+            IndexKind::BTree => {
+                let mut map: BTreeMap<HashableValue, Vec<usize>> = BTreeMap::new();
+                for (idx, key) in keyed {
+                    map.entry(key).or_default().push(idx);
+                }
+                Entries::BTree(map)
+            }
+        };
+        Self { version, entries }
+    }
+
+    /// Positions of the elements equal to `key`, in order.
+    pub(crate) fn get(&self, key: &Value) -> &[usize] {
+        let key = HashableValue::new(key.clone());
+        let positions = match &self.entries {
+            Entries::Hash(map) => map.get(&key),
+            Entries::BTree(map) => map.get(&key),
+        };
+        positions.map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Every distinct value with the positions of the elements holding it,
+    /// sorted by value.
+    pub(crate) fn sorted_groups(&self) -> BTreeMap<HashableValue, Vec<usize>> {
+        match &self.entries {
+            Entries::Hash(map) => map.clone().into_iter().collect(),
+            Entries::BTree(map) => map.clone(),
+        }
+    }
+}
+
+impl Bulk {
+    /// Build a hash index over a stored field.
+    ///
+    /// This is [`Bulk::build_index_with`] with [`IndexKind::Hash`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Bulk::build_index_with`].
+    pub fn build_index(&self, registry: &Registry, field: &str) -> Result<()> {
+        self.build_index_with(registry, field, IndexKind::Hash)
+    }
+
+    /// Build an index over a stored field, replacing any existing one.
+    ///
+    /// The index is stored in the bulk and used by [`Bulk::lookup`],
+    /// [`Bulk::lookup_range`], [`Bulk::join`] and [`Bulk::partition_by`] for as
+    /// long as the field is not updated.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `field` - The name of a stored field
+    /// * `kind` - The data structure to build
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field is not stored in the bulk
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, IndexKind, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("user_id".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(4).unwrap();
+    /// let bulk = bulk.set(&registry, "user_id", vec![
+    ///     Value::ScalarInt(7),
+    ///     Value::ScalarInt(3),
+    ///     Value::ScalarInt(7),
+    ///     Value::ScalarInt(5),
+    /// ]).unwrap();
+    ///
+    /// bulk.build_index_with(&registry, "user_id", IndexKind::BTree).unwrap();
+    /// assert_eq!(bulk.lookup("user_id", &Value::ScalarInt(7)).unwrap(), vec![0, 2]);
+    /// assert_eq!(
+    ///     bulk.lookup_range("user_id", Value::ScalarInt(4)..).unwrap(),
+    ///     vec![3, 0, 2]
+    /// );
+    /// ```
+    pub fn build_index_with(
+        &self,
+        registry: &Registry,
+        field: &str,
+        kind: IndexKind,
+    ) -> Result<()> {
+        let version = self
+            .meta
+            .versions
+            .get(field)
+            .copied()
+            .ok_or_else(|| SoAKitError::FieldNotFound(field.to_string()))?;
+        let values = self.get(registry, field)?.to_scalars();
+        let index = FieldIndex::new(values, version, kind);
+        let _ = self.indices.borrow_mut().insert(field.to_string(), index);
+        Ok(())
+    }
+
+    /// Check whether a field has an index that is still current.
+    pub fn has_index(&self, field: &str) -> bool {
+        self.with_current_index(field, |_| ()).is_some()
+    }
+
+    /// Remove the index over a field, if any.
+    ///
+    /// # Returns
+    ///
+    /// `true` if an index was removed.
+    pub fn drop_index(&self, field: &str) -> bool {
+        self.indices.borrow_mut().remove(field).is_some()
+    }
+
+    /// Find the elements whose value of an indexed field equals `key`.
+    ///
+    /// # Arguments
+    ///
+    /// * `field` - The name of a field with a current index
+    /// * `key` - The scalar value to look for
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Vec<usize>)` with the positions of the matching elements, in order.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if the field has no current index
+    pub fn lookup(&self, field: &str, key: &Value) -> Result<Vec<usize>> {
+        self.with_current_index(field, |index| index.get(key).to_vec())
+            .ok_or_else(|| missing_index(field))
+    }
+
+    /// Find the elements whose value of a B-tree indexed field falls in a range.
+    ///
+    /// NaN and missing elements never match.
+    ///
+    /// # Arguments
+    ///
+    /// * `field` - The name of a field with a current [`IndexKind::BTree`] index
+    /// * `range` - The range of values to look for, such as `lo..hi` or `lo..=hi`
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Vec<usize>)` with the positions of the matching elements,
+    /// ordered by value and then by position.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if the field has no current index or
+    ///   its index is a hash index
+    pub fn lookup_range(&self, field: &str, range: impl RangeBounds<Value>) -> Result<Vec<usize>> {
+        let bound = |b: Bound<&Value>| match b {
+            Bound::Included(v) => Bound::Included(HashableValue::new(v.clone())),
+            Bound::Excluded(v) => Bound::Excluded(HashableValue::new(v.clone())),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        let (start, end) = (bound(range.start_bound()), bound(range.end_bound()));
+        // `BTreeMap::range` rejects ranges that end before they start
+        let empty = match (&start, &end) {
+            (Bound::Included(lo), Bound::Included(hi)) => lo > hi,
+            (Bound::Included(lo) | Bound::Excluded(lo), Bound::Excluded(hi))
+            | (Bound::Excluded(lo), Bound::Included(hi)) => lo >= hi,
+            _ => false,
+        };
+
+        self.with_current_index(field, |index| match &index.entries {
+            Entries::BTree(_) if empty => Ok(Vec::new()),
+            Entries::BTree(map) => Ok(map
+                .range((start, end))
+                .filter(|(key, _)| !is_missing(key.value()))
+                .flat_map(|(_, positions)| positions.iter().copied())
+                .collect()),
+            Entries::Hash(_) => Err(SoAKitError::InvalidArgument(format!(
+                "Index over '{}' is a hash index; range lookups need IndexKind::BTree",
+                field
+            ))),
+        })
+        .ok_or_else(|| missing_index(field))?
+    }
+
+    /// Call `f` with the index over `field` if it is still current.
+    pub(crate) fn with_current_index<R, F>(&self, field: &str, f: F) -> Option<R>
+    where
+        F: FnOnce(&FieldIndex) -> R,
+    {
+        let indices = self.indices.borrow();
+        indices
+            .get(field)
+            .filter(|index| self.meta.versions.get(field) == Some(&index.version))
+            .map(f)
+    }
+
+    /// Get a copy of every index that is still current.
+    pub(crate) fn current_indices(&self) -> BTreeMap<String, FieldIndex> {
+        self.indices
+            .borrow()
+            .iter()
+            .filter(|(field, index)| self.meta.versions.get(*field) == Some(&index.version))
+            .map(|(field, index)| (field.clone(), index.clone()))
+            .collect()
+    }
+}
+
+/// Error for a lookup on a field without a current index.
+fn missing_index(field: &str) -> SoAKitError {
+    SoAKitError::InvalidArgument(format!(
+        "Field '{}' has no current index; call build_index first",
+        field
+    ))
+}
+
+/// Check whether an index key is a NaN float or missing.
+const fn is_missing(value: &Value) -> bool {
+    match value {
+        Value::ScalarFloat(x) => x.is_nan(),
+        Value::Null => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bulk::CHUNK_SIZE;
+
+    #[test]
+    fn test_index_lookups_follow_field_versions() {
+        let mut registry = Registry::new();
+        let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("k".to_string(), ints, false, vec![], None)
+            .unwrap();
+
+        let count = CHUNK_SIZE + 20;
+        let bulk = Bulk::new(count).unwrap();
+        let bulk = bulk
+            .set(
+                &registry,
+                "k",
+                (0..count)
+                    .map(|i| Value::ScalarInt((i % 10) as i64))
+                    .collect(),
+            )
+            .unwrap();
+        assert!(bulk.lookup("k", &Value::ScalarInt(3)).is_err());
+        assert!(bulk.build_index(&registry, "missing").is_err());
+
+        bulk.build_index(&registry, "k").unwrap();
+        assert!(bulk.has_index("k"));
+        let threes = bulk.lookup("k", &Value::ScalarInt(3)).unwrap();
+        assert_eq!(threes.len(), count.div_ceil(10));
+        assert!(threes.iter().all(|&i| i % 10 == 3));
+        assert!(bulk.lookup("k", &Value::ScalarInt(42)).unwrap().is_empty());
+        assert!(bulk.lookup_range("k", Value::ScalarInt(1)..).is_err());
+
+        // A clone shares the index until the field changes
+        let copy = bulk.clone();
+        assert!(copy.has_index("k"));
+        let updated = bulk
+            .set_element(&registry, "k", 0, Value::ScalarInt(9))
+            .unwrap();
+        assert!(!updated.has_index("k"));
+        let dropped = bulk.drop_field(&registry, "k").unwrap();
+        let reset = dropped
+            .set(
+                &registry,
+                "k",
+                (0..count).map(|_| Value::ScalarInt(0)).collect(),
+            )
+            .unwrap();
+        assert!(!reset.has_index("k"));
+        assert!(!bulk.head(5).unwrap().has_index("k"));
+
+        bulk.build_index_with(&registry, "k", IndexKind::BTree)
+            .unwrap();
+        let range = bulk
+            .lookup_range("k", Value::ScalarInt(8)..=Value::ScalarInt(9))
+            .unwrap();
+        assert_eq!(range.len(), 2 * (count / 10));
+        assert!(
+            bulk.lookup_range("k", Value::ScalarInt(5)..Value::ScalarInt(5))
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            bulk.lookup_range("k", Value::ScalarInt(6)..Value::ScalarInt(2))
+                .unwrap()
+                .is_empty()
+        );
+        assert!(bulk.drop_index("k"));
+        assert!(!bulk.has_index("k"));
+    }
+
+    #[test]
+    fn test_join_and_partition_use_current_index() {
+        let mut registry = Registry::new();
+        let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("id".to_string(), ints.clone(), false, vec![], None)
+            .unwrap();
+        registry
+            .register("amount".to_string(), ints, false, vec![], None)
+            .unwrap();
+        let strings = Box::new(|v: &Value| matches!(v, Value::ScalarString(_)));
+        registry
+            .register("name".to_string(), strings, false, vec![], None)
+            .unwrap();
+
+        let ids = |v: &[i64]| v.iter().map(|&i| Value::ScalarInt(i)).collect::<Vec<_>>();
+        let orders = Bulk::new(4)
+            .unwrap()
+            .set(&registry, "id", ids(&[2, 1, 2, 3]))
+            .unwrap()
+            .set(&registry, "amount", ids(&[10, 20, 30, 40]))
+            .unwrap();
+        let users = Bulk::new(2)
+            .unwrap()
+            .set(&registry, "id", ids(&[1, 2]))
+            .unwrap()
+            .set(
+                &registry,
+                "name",
+                vec![Value::from("ann"), Value::from("bob")],
+            )
+            .unwrap();
+
+        let plain = orders
+            .join(&users, &registry, "id", crate::JoinKind::Left)
+            .unwrap();
+        users.build_index(&registry, "id").unwrap();
+        let indexed = orders
+            .join(&users, &registry, "id", crate::JoinKind::Left)
+            .unwrap();
+        for field in ["id", "amount", "name"] {
+            assert_eq!(
+                indexed.get(&registry, field).unwrap(),
+                plain.get(&registry, field).unwrap()
+            );
+        }
+
+        let plain = orders.partition_by(&registry, "id").unwrap();
+        orders.build_index(&registry, "id").unwrap();
+        let indexed = orders.partition_by(&registry, "id").unwrap();
+        assert_eq!(plain.len(), indexed.len());
+        for (a, b) in plain.iter().zip(&indexed) {
+            assert_eq!((a.key(), a.mask()), (b.key(), b.mask()));
+        }
+    }
+}
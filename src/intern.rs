@@ -0,0 +1,186 @@
+/// String interning for string columns.
+///
+/// This module provides a crate-level pool of shared strings. String columns that
+/// are interned (see [`Bulk::intern_field`](crate::Bulk::intern_field)) store one
+/// `Arc<str>` per element instead of one `String`, so repeated strings share a
+/// single allocation across every chunk and every bulk.
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Global string pool
+static POOL: OnceLock<Mutex<HashSet<Arc<str>>>> = OnceLock::new();
+
+/// Get or initialize the global string pool.
+fn pool() -> &'static Mutex<HashSet<Arc<str>>> {
+    POOL.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+/// Intern a string in the global pool.
+///
+/// Returns the pooled `Arc<str>` equal to `s`, adding it to the pool first if
+/// needed. Interning the same string twice returns pointers to the same allocation.
+///
+/// # Arguments
+///
+/// * `s` - The string to intern
+///
+/// # Returns
+///
+/// A shared handle to the pooled string.
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::intern::intern;
+/// use std::sync::Arc;
+///
+/// let a = intern("red");
+/// let b = intern("red");
+/// assert!(Arc::ptr_eq(&a, &b));
+/// ```
+pub fn intern(s: &str) -> Arc<str> {
+    // A poisoned pool still holds valid strings, so keep using it
+    let mut pool = pool()
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    if let Some(existing) = pool.get(s) {
+        return Arc::clone(existing);
+    }
+    let interned: Arc<str> = Arc::from(s);
+    let _ = pool.insert(Arc::clone(&interned));
+    interned
+}
+
+/// Get the number of distinct strings currently in the global pool.
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::intern::{intern, pool_size};
+///
+/// let _keep = intern("pool_size example");
+/// assert!(pool_size() >= 1);
+/// ```
+pub fn pool_size() -> usize {
+    pool()
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .len()
+}
+
+/// Remove strings that are no longer used outside the pool.
+///
+/// Interned strings stay in the pool after the columns that used them are
+/// dropped. Call this periodically in long-running programs to release them.
+///
+/// # Returns
+///
+/// The number of strings removed.
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::intern::{intern, purge_unused};
+///
+/// drop(intern("temporary"));
+/// let _ = purge_unused();
+/// ```
+pub fn purge_unused() -> usize {
+    let mut pool = pool()
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let before = pool.len();
+    pool.retain(|s| Arc::strong_count(s) > 1);
+    before.saturating_sub(pool.len())
+}
+
+/// A column of interned strings.
+///
+/// Serializes as a plain sequence of strings and re-interns every string when
+/// deserialized, so interned columns survive a round trip through any format.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InternedStrings(pub Vec<Arc<str>>);
+
+impl InternedStrings {
+    /// Intern every string of a slice.
+    pub fn from_strings(strings: &[String]) -> Self {
+        Self(strings.iter().map(|s| intern(s)).collect())
+    }
+
+    /// Copy the strings out into owned `String`s.
+    pub fn to_strings(&self) -> Vec<String> {
+        self.0.iter().map(|s| s.to_string()).collect()
+    }
+
+    /// Get the number of strings.
+    pub const fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Check if there are no strings.
+    pub const fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Estimate the heap memory of the column's string handles, in bytes.
+    ///
+    /// The strings themselves live in the shared pool and are not counted.
+    pub const fn heap_size(&self) -> usize {
+        size_of_val(self.0.as_slice())
+    }
+}
+
+impl Serialize for InternedStrings {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().map(|s| &**s))
+    }
+}
+
+impl<'de> Deserialize<'de> for InternedStrings {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let strings = Vec::<String>::deserialize(deserializer)?;
+        Ok(Self::from_strings(&strings))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern_shares_allocations() {
+        let a = intern("intern_test_shared");
+        let b = intern(&String::from("intern_test_shared"));
+        assert!(Arc::ptr_eq(&a, &b));
+        assert_eq!(&*a, "intern_test_shared");
+
+        let c = intern("intern_test_other");
+        assert!(!Arc::ptr_eq(&a, &c));
+    }
+
+    #[test]
+    fn test_purge_unused_keeps_live_strings() {
+        let live = intern("intern_test_live");
+        drop(intern("intern_test_dead"));
+        let _ = purge_unused();
+
+        let again = intern("intern_test_live");
+        assert!(Arc::ptr_eq(&live, &again));
+    }
+
+    #[test]
+    fn test_interned_strings_round_trip() {
+        let column =
+            InternedStrings::from_strings(&["x".to_string(), "y".to_string(), "x".to_string()]);
+        assert_eq!(column.len(), 3);
+        assert!(Arc::ptr_eq(&column.0[0], &column.0[2]));
+
+        let json = serde_json::to_string(&column).unwrap();
+        assert_eq!(json, r#"["x","y","x"]"#);
+        let restored: InternedStrings = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, column);
+        assert!(Arc::ptr_eq(&restored.0[0], &column.0[0]));
+        assert_eq!(restored.to_strings(), vec!["x", "y", "x"]);
+    }
+}
@@ -0,0 +1,288 @@
+/// JSON cell helpers for SoAKit.
+///
+/// [`Value::ScalarJson`] cells hold arbitrary JSON documents. This module checks
+/// documents against a JSON Schema, so that JSON fields can be validated when they
+/// are set like any other field.
+///
+/// The supported keywords are a commonly used subset of JSON Schema: `type`,
+/// `enum`, `const`, `properties`, `required`, `additionalProperties`, `items`,
+/// `minItems`, `maxItems`, `minimum`, `maximum`, `minLength`, `maxLength`,
+/// `allOf` and `anyOf`. The boolean schemas `true` and `false` are supported too.
+/// Other keywords are ignored.
+use crate::value::Value;
+use serde_json::{Map, Value as Json};
+
+/// Check whether a JSON document matches a JSON Schema.
+///
+/// # Arguments
+///
+/// * `document` - The JSON document to check
+/// * `schema` - The JSON Schema to check against
+///
+/// # Returns
+///
+/// `true` if the document satisfies every supported keyword of the schema.
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::json::matches_schema;
+/// use serde_json::json;
+///
+/// let schema = json!({
+///     "type": "object",
+///     "properties": { "name": { "type": "string" }, "age": { "type": "integer", "minimum": 0 } },
+///     "required": ["name"]
+/// });
+/// assert!(matches_schema(&json!({"name": "Alice", "age": 30}), &schema));
+/// assert!(!matches_schema(&json!({"age": 30}), &schema));
+/// assert!(!matches_schema(&json!({"name": "Bob", "age": -1}), &schema));
+/// ```
+pub fn matches_schema(document: &Json, schema: &Json) -> bool {
+    let keywords = match schema {
+        Json::Bool(accept) => return *accept,
+        Json::Object(keywords) => keywords,
+        _ => return false,
+    };
+
+    if let Some(expected) = keywords.get("type")
+        && !type_matches(document, expected)
+    {
+        return false;
+    }
+    if let Some(Json::Array(options)) = keywords.get("enum")
+        && !options.contains(document)
+    {
+        return false;
+    }
+    if let Some(constant) = keywords.get("const")
+        && constant != document
+    {
+        return false;
+    }
+    if let Some(Json::Array(schemas)) = keywords.get("allOf")
+        && !schemas.iter().all(|s| matches_schema(document, s))
+    {
+        return false;
+    }
+    if let Some(Json::Array(schemas)) = keywords.get("anyOf")
+        && !schemas.iter().any(|s| matches_schema(document, s))
+    {
+        return false;
+    }
+
+    match document {
+        Json::Object(object) => object_matches(object, keywords),
+        Json::Array(items) => array_matches(items, keywords),
+        Json::Number(n) => n.as_f64().is_some_and(|x| number_matches(x, keywords)),
+        Json::String(s) => string_matches(s, keywords),
+        Json::Null | Json::Bool(_) => true,
+    }
+}
+
+/// Create a field validator that accepts JSON cells matching a JSON Schema.
+///
+/// The validator accepts [`Value::ScalarJson`] cells whose document matches
+/// `schema` (see [`matches_schema`]) and rejects every other value.
+///
+/// # Arguments
+///
+/// * `schema` - The JSON Schema that documents must match
+///
+/// # Returns
+///
+/// A validator suitable for [`Registry::register`](crate::Registry::register).
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::json::schema_validator;
+/// use soakit::{Bulk, Registry, Value};
+/// use serde_json::json;
+///
+/// let mut registry = Registry::new();
+/// let validator = schema_validator(json!({"type": "object", "required": ["id"]}));
+/// registry.register("payload".to_string(), validator, false, vec![], None).unwrap();
+///
+/// let bulk = Bulk::new(2).unwrap();
+/// let ok = vec![
+///     Value::ScalarJson(json!({"id": 1, "tags": ["a"]})),
+///     Value::ScalarJson(json!({"id": 2})),
+/// ];
+/// assert!(bulk.set(&registry, "payload", ok).is_ok());
+///
+/// let bad = vec![Value::ScalarJson(json!([])), Value::ScalarJson(json!({"id": 1}))];
+/// assert!(bulk.set(&registry, "payload", bad).is_err());
+/// ```
+pub fn schema_validator(schema: Json) -> Box<dyn Fn(&Value) -> bool + Send + Sync> {
+    Box::new(move |value: &Value| match value {
+        Value::ScalarJson(document) => matches_schema(document, &schema),
+        _ => false,
+    })
+}
+
+/// Check the `type` keyword, which names one type or lists several.
+fn type_matches(document: &Json, expected: &Json) -> bool {
+    match expected {
+        Json::String(name) => is_type(document, name),
+        Json::Array(names) => names
+            .iter()
+            .filter_map(Json::as_str)
+            .any(|name| is_type(document, name)),
+        _ => false,
+    }
+}
+
+/// Check a document against one JSON Schema type name.
+fn is_type(document: &Json, name: &str) -> bool {
+    match name {
+        "null" => document.is_null(),
+        "boolean" => document.is_boolean(),
+        "object" => document.is_object(),
+        "array" => document.is_array(),
+        "string" => document.is_string(),
+        "number" => document.is_number(),
+        "integer" => {
+            document.is_i64()
+                || document.is_u64()
+                || document.as_f64().is_some_and(|x| x.fract() == 0.0)
+        }
+        _ => false,
+    }
+}
+
+/// Check the object keywords `properties`, `required` and `additionalProperties`.
+fn object_matches(object: &Map<String, Json>, keywords: &Map<String, Json>) -> bool {
+    let properties = keywords.get("properties").and_then(Json::as_object);
+
+    if let Some(Json::Array(required)) = keywords.get("required")
+        && !required
+            .iter()
+            .filter_map(Json::as_str)
+            .all(|name| object.contains_key(name))
+    {
+        return false;
+    }
+
+    object.iter().all(
+        |(name, member)| match properties.and_then(|p| p.get(name)) {
+            Some(schema) => matches_schema(member, schema),
+            None => keywords
+                .get("additionalProperties")
+                .is_none_or(|schema| matches_schema(member, schema)),
+        },
+    )
+}
+
+/// Check the array keywords `items`, `minItems` and `maxItems`.
+fn array_matches(items: &[Json], keywords: &Map<String, Json>) -> bool {
+    let len = u64::try_from(items.len()).unwrap_or(u64::MAX);
+    if keywords
+        .get("minItems")
+        .and_then(Json::as_u64)
+        .is_some_and(|min| len < min)
+        || keywords
+            .get("maxItems")
+            .and_then(Json::as_u64)
+            .is_some_and(|max| len > max)
+    {
+        return false;
+    }
+    keywords
+        .get("items")
+        .is_none_or(|schema| items.iter().all(|item| matches_schema(item, schema)))
+}
+
+/// Check the number keywords `minimum` and `maximum`.
+fn number_matches(x: f64, keywords: &Map<String, Json>) -> bool {
+    let below_minimum = keywords
+        .get("minimum")
+        .and_then(Json::as_f64)
+        .is_some_and(|min| x < min);
+    let above_maximum = keywords
+        .get("maximum")
+        .and_then(Json::as_f64)
+        .is_some_and(|max| x > max);
+    !below_minimum && !above_maximum
+}
+
+/// Check the string keywords `minLength` and `maxLength`, counted in characters.
+fn string_matches(s: &str, keywords: &Map<String, Json>) -> bool {
+    let len = u64::try_from(s.chars().count()).unwrap_or(u64::MAX);
+    let too_short = keywords
+        .get("minLength")
+        .and_then(Json::as_u64)
+        .is_some_and(|min| len < min);
+    let too_long = keywords
+        .get("maxLength")
+        .and_then(Json::as_u64)
+        .is_some_and(|max| len > max);
+    !too_short && !too_long
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_matches_schema_keywords() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "name": { "type": "string", "minLength": 1, "maxLength": 5 },
+                "tags": { "type": "array", "items": { "enum": ["a", "b"] }, "maxItems": 2 },
+                "score": { "type": ["number", "null"], "minimum": 0, "maximum": 1 }
+            },
+            "required": ["name"],
+            "additionalProperties": false
+        });
+        assert!(matches_schema(&json!({"name": "x"}), &schema));
+        assert!(matches_schema(
+            &json!({"name": "x", "tags": ["a", "b"], "score": 0.5}),
+            &schema
+        ));
+        assert!(matches_schema(
+            &json!({"name": "x", "score": null}),
+            &schema
+        ));
+        assert!(!matches_schema(&json!({"name": ""}), &schema));
+        assert!(!matches_schema(&json!({"name": "toolong"}), &schema));
+        assert!(!matches_schema(
+            &json!({"name": "x", "tags": ["c"]}),
+            &schema
+        ));
+        assert!(!matches_schema(
+            &json!({"name": "x", "tags": ["a", "a", "a"]}),
+            &schema
+        ));
+        assert!(!matches_schema(&json!({"name": "x", "score": 2}), &schema));
+        assert!(!matches_schema(&json!({"name": "x", "extra": 1}), &schema));
+        assert!(!matches_schema(&json!("x"), &schema));
+    }
+
+    #[test]
+    fn test_matches_schema_combinators() {
+        assert!(matches_schema(&json!(1), &json!(true)));
+        assert!(!matches_schema(&json!(1), &json!(false)));
+        assert!(matches_schema(&json!(3), &json!({"type": "integer"})));
+        assert!(matches_schema(&json!(3.0), &json!({"type": "integer"})));
+        assert!(!matches_schema(&json!(3.5), &json!({"type": "integer"})));
+        assert!(matches_schema(&json!(2), &json!({"const": 2})));
+        let any_of = json!({"anyOf": [{"type": "string"}, {"type": "integer", "minimum": 10}]});
+        assert!(matches_schema(&json!("s"), &any_of));
+        assert!(matches_schema(&json!(11), &any_of));
+        assert!(!matches_schema(&json!(9), &any_of));
+        let all_of = json!({"allOf": [{"minimum": 0}, {"maximum": 5}]});
+        assert!(matches_schema(&json!(5), &all_of));
+        assert!(!matches_schema(&json!(6), &all_of));
+    }
+
+    #[test]
+    fn test_schema_validator_rejects_non_json_values() {
+        let validator = schema_validator(json!({"type": "string"}));
+        assert!(validator(&Value::ScalarJson(json!("s"))));
+        assert!(!validator(&Value::ScalarJson(json!(1))));
+        assert!(!validator(&Value::ScalarString("s".to_string())));
+    }
+}
@@ -0,0 +1,460 @@
+/// Lazy evaluation of bulk pipelines.
+///
+/// Each chained [`Bulk`] operation builds a complete new bulk, so a pipeline such
+/// as filter, filter, select copies every field once per step. A [`LazyBulk`],
+/// created with [`Bulk::lazy`], instead records the operations as a plan and runs
+/// it when [`LazyBulk::collect`] is called:
+///
+/// - consecutive filters only combine masks, and the kept elements of the
+///   selected fields are gathered once, when the next step needs the data;
+/// - selected-away fields are never copied;
+/// - [`LazyBulk::apply`] transforms the gathered bulk in place.
+///
+/// The result is the same as running the operations one by one.
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::expr::col;
+/// use soakit::{Bulk, Registry, SortOrder, Value};
+///
+/// let mut registry = Registry::new();
+/// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+/// registry.register("age".to_string(), validator.clone(), false, vec![], None).unwrap();
+/// registry.register("score".to_string(), validator, false, vec![], None).unwrap();
+///
+/// let bulk = Bulk::new(4).unwrap()
+///     .set(&registry, "age", (0..4).map(|i| Value::ScalarInt(20 + i * 10)).collect()).unwrap()
+///     .set(&registry, "score", (0..4).map(Value::ScalarInt).collect()).unwrap();
+///
+/// let result = bulk
+///     .lazy(&registry)
+///     .filter(col("age").gt(25))
+///     .select(&["score"])
+///     .sort_by(&[("score", SortOrder::Desc)])
+///     .collect()
+///     .unwrap();
+/// assert_eq!(result.get(&registry, "score").unwrap(), Value::VectorInt(vec![3, 2, 1]));
+/// assert!(result.get(&registry, "age").is_err());
+/// ```
+use crate::bulk::{Bulk, JoinKind, SortOrder, kept_columns};
+use crate::error::{Result, SoAKitError};
+use crate::expr::Expr;
+use crate::meta::Registry;
+use crate::value::Value;
+use std::borrow::Cow;
+
+/// A function transforming the values of one field, as taken by [`Bulk::apply_fields`].
+type ApplyFunc<'a> = Box<dyn Fn(&[Value]) -> Result<Vec<Value>> + 'a>;
+
+/// One recorded operation of a [`LazyBulk`].
+enum Step<'a> {
+    /// Keep only the listed stored fields
+    Select(Vec<String>),
+    /// Keep the elements for which an expression is `true`
+    FilterExpr(Expr),
+    /// Keep the elements whose value of a field matches a predicate
+    FilterField(String, Box<dyn Fn(&Value) -> bool + 'a>),
+    /// Transform the listed fields
+    Apply(Vec<String>, ApplyFunc<'a>),
+    /// Sort by the listed keys
+    Sort(Vec<(String, SortOrder)>),
+    /// Join with another bulk
+    Join(&'a Bulk, String, JoinKind),
+}
+
+/// A recorded pipeline of bulk operations, run by [`LazyBulk::collect`].
+///
+/// Every method only records its operation; errors such as a missing field are
+/// reported by `collect`.
+pub struct LazyBulk<'a> {
+    /// The bulk the pipeline starts from
+    source: &'a Bulk,
+    /// The registry containing field metadata
+    registry: &'a Registry,
+    /// The recorded operations, in order
+    plan: Vec<Step<'a>>,
+}
+
+impl Bulk {
+    /// Start a lazy pipeline over this bulk.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    ///
+    /// # Returns
+    ///
+    /// A [`LazyBulk`] with an empty plan.
+    pub const fn lazy<'a>(&'a self, registry: &'a Registry) -> LazyBulk<'a> {
+        LazyBulk {
+            source: self,
+            registry,
+            plan: Vec::new(),
+        }
+    }
+}
+
+impl<'a> LazyBulk<'a> {
+    /// Keep only the listed stored fields.
+    ///
+    /// Derived fields stay readable as long as their dependencies are kept.
+    /// Later steps cannot read the fields that were not selected.
+    pub fn select(mut self, fields: &[&str]) -> Self {
+        let fields = fields.iter().map(|field| (*field).to_string()).collect();
+        self.plan.push(Step::Select(fields));
+        self
+    }
+
+    /// Keep the elements for which a boolean expression is `true`, as by
+    /// [`Bulk::filter_expr`].
+    pub fn filter(mut self, expr: Expr) -> Self {
+        self.plan.push(Step::FilterExpr(expr));
+        self
+    }
+
+    /// Keep the elements whose value of `field` matches a predicate, as by
+    /// [`Bulk::filter_field`].
+    pub fn filter_field<F>(mut self, field: &str, predicate: F) -> Self
+    where
+        F: Fn(&Value) -> bool + 'a,
+    {
+        self.plan
+            .push(Step::FilterField(field.to_string(), Box::new(predicate)));
+        self
+    }
+
+    /// Transform every element of the listed fields, as by [`Bulk::apply_fields`]
+    /// with an empty mask.
+    pub fn apply<F>(mut self, fields: &[&str], func: F) -> Self
+    where
+        F: Fn(&[Value]) -> Result<Vec<Value>> + 'a,
+    {
+        let fields = fields.iter().map(|field| (*field).to_string()).collect();
+        self.plan.push(Step::Apply(fields, Box::new(func)));
+        self
+    }
+
+    /// Sort the elements, as by [`Bulk::sort_by`].
+    pub fn sort_by(mut self, keys: &[(&str, SortOrder)]) -> Self {
+        let keys = keys
+            .iter()
+            .map(|(field, order)| ((*field).to_string(), *order))
+            .collect();
+        self.plan.push(Step::Sort(keys));
+        self
+    }
+
+    /// Join with another bulk, as by [`Bulk::join`].
+    pub fn join(mut self, other: &'a Bulk, on: &str, kind: JoinKind) -> Self {
+        self.plan.push(Step::Join(other, on.to_string(), kind));
+        self
+    }
+
+    /// Run the recorded operations.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` equal to the result of running the operations one by one.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if a step reads a field that is missing or
+    ///   was not selected
+    /// - The errors of the operation each step records
+    pub fn collect(self) -> Result<Bulk> {
+        let registry = self.registry;
+        let mut state = Pending {
+            current: Cow::Borrowed(self.source),
+            remove: None,
+            selected: None,
+        };
+        for step in self.plan {
+            match step {
+                Step::Select(fields) => state.select(fields)?,
+                Step::FilterExpr(expr) => {
+                    state.check_readable(registry, &expr.columns())?;
+                    let mask = state.current.eval_mask(registry, &expr)?;
+                    state.keep(&mask);
+                }
+                Step::FilterField(field, predicate) => {
+                    state.check_readable(registry, std::slice::from_ref(&field))?;
+                    let mask: Vec<bool> = state
+                        .current
+                        .get(registry, &field)?
+                        .to_scalars()
+                        .iter()
+                        .map(predicate)
+                        .collect();
+                    state.keep(&mask);
+                }
+                Step::Apply(fields, func) => {
+                    state.flush()?;
+                    let fields: Vec<&str> = fields.iter().map(String::as_str).collect();
+                    state
+                        .current
+                        .to_mut()
+                        .apply_fields_mut(registry, &fields, &[], func)?;
+                }
+                Step::Sort(keys) => {
+                    state.flush()?;
+                    let keys: Vec<(&str, SortOrder)> = keys
+                        .iter()
+                        .map(|(field, order)| (field.as_str(), *order))
+                        .collect();
+                    state.current = Cow::Owned(state.current.sort_by(registry, &keys)?);
+                }
+                Step::Join(other, on, kind) => {
+                    state.flush()?;
+                    state.current = Cow::Owned(state.current.join(other, registry, &on, kind)?);
+                }
+            }
+        }
+        state.flush()?;
+        Ok(state.current.into_owned())
+    }
+}
+
+/// The state of a running [`LazyBulk`] plan: a bulk plus the filters and
+/// selection not yet applied to it.
+struct Pending<'a> {
+    /// The bulk as of the last gather
+    current: Cow<'a, Bulk>,
+    /// `true` for each element of `current` removed by a pending filter
+    remove: Option<Vec<bool>>,
+    /// The stored fields kept by a pending selection
+    selected: Option<Vec<String>>,
+}
+
+impl Pending<'_> {
+    /// Record a selection, narrowing any pending one.
+    fn select(&mut self, fields: Vec<String>) -> Result<()> {
+        let available = self.current.list_data_fields();
+        for field in &fields {
+            let kept = self
+                .selected
+                .as_ref()
+                .map_or(&available, |selected| selected);
+            if !kept.contains(field) {
+                return Err(SoAKitError::FieldNotFound(field.clone()));
+            }
+        }
+        self.selected = Some(fields);
+        Ok(())
+    }
+
+    /// Combine a filter's keep mask with the pending removals.
+    fn keep(&mut self, mask: &[bool]) {
+        match &mut self.remove {
+            Some(remove) => {
+                for (remove, &keep) in remove.iter_mut().zip(mask) {
+                    *remove = *remove || !keep;
+                }
+            }
+            None => self.remove = Some(mask.iter().map(|&keep| !keep).collect()),
+        }
+    }
+
+    /// Check that the stored fields read by `fields` survive the pending selection.
+    ///
+    /// Derived fields are followed to the stored fields they depend on.
+    fn check_readable(&self, registry: &Registry, fields: &[String]) -> Result<()> {
+        let Some(selected) = &self.selected else {
+            return Ok(());
+        };
+        let mut queue = fields.to_vec();
+        while let Some(field) = queue.pop() {
+            match registry.get_metadata(&field) {
+                Some(meta) if meta.is_derived => queue.extend(meta.dependencies.iter().cloned()),
+                Some(meta) if meta.is_virtual() => {}
+                _ if !selected.contains(&field) => return Err(SoAKitError::FieldNotFound(field)),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Gather the kept elements of the selected fields into a new bulk, once.
+    fn flush(&mut self) -> Result<()> {
+        if self.remove.is_none() && self.selected.is_none() {
+            return Ok(());
+        }
+        let bulk = &self.current;
+        let remove = self
+            .remove
+            .take()
+            .unwrap_or_else(|| vec![false; bulk.meta.count]);
+        let fields = self
+            .selected
+            .take()
+            .unwrap_or_else(|| bulk.list_data_fields());
+
+        let (mut new_bulk, bounds) = bulk.kept_skeleton(&remove)?;
+        new_bulk
+            .meta
+            .versions
+            .retain(|field, _| fields.contains(field));
+        new_bulk
+            .meta
+            .sorted
+            .retain(|field, _| fields.contains(field));
+        for field in &fields {
+            let columns = kept_columns(&bulk.chunks, field, &remove, &bounds)?;
+            new_bulk.insert_kept(field, columns, bulk.is_interned(field));
+        }
+        self.current = Cow::Owned(new_bulk);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bulk::CHUNK_SIZE;
+    use crate::expr::col;
+
+    fn sample(registry: &mut Registry, count: usize) -> Bulk {
+        let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("n".to_string(), ints.clone(), false, vec![], None)
+            .unwrap();
+        registry
+            .register("k".to_string(), ints, false, vec![], None)
+            .unwrap();
+        let strings = Box::new(|v: &Value| matches!(v, Value::ScalarString(_)));
+        registry
+            .register("s".to_string(), strings, false, vec![], None)
+            .unwrap();
+        registry
+            .register_expr("twice".to_string(), col("n") * 2)
+            .unwrap();
+
+        Bulk::new(count)
+            .unwrap()
+            .set(
+                registry,
+                "n",
+                (0..count).map(|i| Value::ScalarInt(i as i64)).collect(),
+            )
+            .unwrap()
+            .set(
+                registry,
+                "k",
+                (0..count)
+                    .map(|i| Value::ScalarInt((i % 10) as i64))
+                    .collect(),
+            )
+            .unwrap()
+            .set(
+                registry,
+                "s",
+                (0..count)
+                    .map(|i| Value::ScalarString((i % 3).to_string()))
+                    .collect(),
+            )
+            .unwrap()
+            .intern_field("s")
+            .unwrap()
+    }
+
+    #[test]
+    fn test_lazy_matches_eager() {
+        let mut registry = Registry::new();
+        let bulk = sample(&mut registry, 2 * CHUNK_SIZE + 33);
+        let lookup = Bulk::new(5)
+            .unwrap()
+            .set(&registry, "k", (0..5).map(Value::ScalarInt).collect())
+            .unwrap()
+            .set(
+                &registry,
+                "s",
+                (0..5).map(|i| Value::ScalarString(i.to_string())).collect(),
+            )
+            .unwrap();
+        let negate = |values: &[Value]| -> Result<Vec<Value>> {
+            Ok(values
+                .iter()
+                .map(|v| match v {
+                    Value::ScalarInt(i) => Value::ScalarInt(-i),
+                    other => other.clone(),
+                })
+                .collect())
+        };
+        let is_odd = |v: &Value| matches!(v, Value::ScalarInt(i) if i % 2 == 1);
+
+        let lazy = bulk
+            .lazy(&registry)
+            .filter(col("twice").gt(100))
+            .select(&["n", "k"])
+            .filter_field("n", is_odd)
+            .apply(&["n"], negate)
+            .sort_by(&[("k", SortOrder::Asc), ("n", SortOrder::Desc)])
+            .join(&lookup, "k", JoinKind::Inner)
+            .collect()
+            .unwrap();
+
+        let eager = bulk
+            .filter_expr(&registry, &col("twice").gt(100))
+            .unwrap()
+            .drop_field(&registry, "s")
+            .unwrap()
+            .filter_field(&registry, "n", is_odd)
+            .unwrap()
+            .apply_fields(&registry, &["n"], &[], negate)
+            .unwrap()
+            .sort_by(&registry, &[("k", SortOrder::Asc), ("n", SortOrder::Desc)])
+            .unwrap()
+            .join(&lookup, &registry, "k", JoinKind::Inner)
+            .unwrap();
+
+        assert_eq!(lazy.meta.id, eager.meta.id);
+        assert_eq!(lazy.list_data_fields(), eager.list_data_fields());
+        for field in lazy.list_data_fields() {
+            assert_eq!(
+                lazy.get(&registry, &field).unwrap(),
+                eager.get(&registry, &field).unwrap()
+            );
+        }
+
+        let plain = bulk.lazy(&registry).collect().unwrap();
+        assert_eq!(
+            plain.get(&registry, "s").unwrap(),
+            bulk.get(&registry, "s").unwrap()
+        );
+        assert!(plain.is_interned("s"));
+    }
+
+    #[test]
+    fn test_lazy_errors_on_unselected_fields() {
+        let mut registry = Registry::new();
+        let bulk = sample(&mut registry, 10);
+
+        let narrowed = bulk
+            .lazy(&registry)
+            .select(&["k"])
+            .filter(col("twice").gt(4));
+        assert!(matches!(
+            narrowed.collect(),
+            Err(SoAKitError::FieldNotFound(_))
+        ));
+        let unknown = bulk.lazy(&registry).select(&["missing"]);
+        assert!(matches!(
+            unknown.collect(),
+            Err(SoAKitError::FieldNotFound(_))
+        ));
+        let empty = bulk.lazy(&registry).filter(col("n").lt(0));
+        assert!(matches!(
+            empty.collect(),
+            Err(SoAKitError::InvalidArgument(_))
+        ));
+
+        let selected = bulk
+            .lazy(&registry)
+            .select(&["n"])
+            .filter(col("twice").ge(10))
+            .collect()
+            .unwrap();
+        assert_eq!(selected.list_data_fields(), vec!["n".to_string()]);
+        assert_eq!(selected.count(), 5);
+    }
+}
@@ -49,45 +49,130 @@
 //! ## Modules
 //!
 //! - [`bulk`]: Core Bulk data structure for SoA operations
+//! - [`bound`]: Bulks bound to their registry
+//! - [`builder`]: Fluent builders for registries and bulks
 //! - [`value`]: Value types (scalars, vectors, matrices)
 //! - [`meta`]: Field metadata and registry
+//! - [`constraint`]: Declarative field constraints
+//! - [`schema`]: Serializable registry schemas
+//! - [`migration`]: Schema versions and migration of older data
 //! - [`view`]: Partitioned data views
 //! - [`proxy`]: Single element access
+//! - [`row`]: Row-by-row iteration
+//! - [`derived`]: Ready-made derived field functions with overflow policies
+//! - [`disk`]: Out-of-core bulks stored on disk
+//! - [`transaction`]: Transactional batch updates
+//! - [`tombstone`]: Soft deletion with tombstones
 //! - [`error`]: Error types
 //! - [`util`]: Utility functions
+//! - [`validators`]: Ready-made field validators
+//! - [`expr`]: Expressions for queries and derived fields
+//! - [`history`]: Undo and redo over bulk states
+//! - [`index`]: Secondary indices over fields
+//! - [`lazy`]: Lazily evaluated bulk pipelines
+//! - [`observer`]: Change observers for fields
+//! - [`intern`]: String interning for string columns
+//! - [`json`]: JSON cell helpers and schema validation
+//! - `parallel`: Parallel apply and filter (requires the `rayon` feature)
+//! - `testing`: Property-testing generators (requires the `testing` feature)
+//!
+//! With the `derive` feature, `#[derive(SoaSchema)]` implements [`SoaSchema`] for
+//! plain structs, giving typed accessors over the dynamic [`Value`] API.
 
+pub mod bound;
+pub mod builder;
 pub mod bulk;
+pub mod constraint;
+pub mod derived;
+pub mod disk;
 pub mod error;
+pub mod expr;
+pub mod history;
+pub mod index;
+pub mod intern;
+pub mod json;
+pub mod lazy;
 pub mod meta;
+pub mod migration;
+pub mod observer;
+#[cfg(feature = "rayon")]
+pub mod parallel;
 pub mod proxy;
+pub mod row;
+pub mod schema;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod tombstone;
+pub mod transaction;
 pub mod util;
+pub mod validators;
 pub mod value;
 pub mod view;
 
 // Re-export public API
-pub use bulk::{Bulk, CacheEntry, Meta};
+pub use bound::BoundBulk;
+pub use builder::{BulkBuilder, RegistryBuilder};
+pub use bulk::{
+    Bulk, CacheCell, CacheEntry, ElementId, FieldSummary, JoinKind, Keep, MemoryUsage, Meta,
+    RowError, SortOrder, Violation,
+};
+pub use constraint::{Constraint, Pattern};
+pub use disk::DiskBulk;
 pub use error::{Result, SoAKitError};
-pub use meta::{DerivedFunc, FieldMetadata, Registry};
+pub use expr::Expr;
+pub use history::BulkHistory;
+pub use index::IndexKind;
+pub use lazy::LazyBulk;
+pub use meta::{
+    ConflictPolicy, ContextDerivedFunc, DeprecationHandler, DerivedContext, DerivedFunc,
+    FieldMetadata, FieldType, MultiDerivedFunc, MultiOutput, Registry, ScalarType,
+    SharedDerivedFunc, VirtualFunc,
+};
+pub use migration::Migrations;
+pub use observer::ObserverId;
 pub use proxy::Proxy;
-pub use util::{filter_system_fields, is_matrix, is_scalar, is_valid_field_name, is_vector};
-pub use value::Value;
+pub use row::{Row, Rows};
+pub use schema::{FieldSchema, Resolver, Schema, SoaSchema};
+#[cfg(feature = "derive")]
+pub use soakit_derive::SoaSchema;
+pub use transaction::Transaction;
+pub use util::{
+    filter_system_fields, float_eq_nan_aware, float_key, is_matrix, is_scalar, is_valid_field_name,
+    is_vector,
+};
+pub use value::{
+    Agg, CastOverflow, CumOp, HashableValue, NullPolicy, RollEdge, RollOp, Value, ValueKind,
+};
 pub use view::View;
 
 // Global registry instance using OnceLock for thread-safe singleton
-use std::sync::OnceLock;
+use std::cell::RefCell;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
 
 /// Global registry instance
 static GLOBAL_REGISTRY: OnceLock<std::sync::Mutex<Registry>> = OnceLock::new();
 
+thread_local! {
+    /// Registries installed by [`with_registry`] on this thread, innermost last
+    static SCOPED_REGISTRIES: RefCell<Vec<Arc<Mutex<Registry>>>> =
+        const { RefCell::new(Vec::new()) };
+    /// This thread's registry, used by [`with_thread_registry`]
+    static THREAD_REGISTRY: RefCell<Registry> = const { RefCell::new(Registry::new()) };
+}
+
 /// Get or initialize the global registry.
 ///
 /// The global registry is a thread-safe singleton that stores field metadata
 /// for the entire application. All fields registered via [`register_field`] are
 /// stored in this registry.
 ///
+/// This is always the process-wide registry, even inside [`with_registry`]; use
+/// [`current_registry`] to get the scoped registry there.
+///
 /// # Returns
 ///
-/// A reference to the global registry wrapped in a `Mutex` for thread-safe access.
+/// A static reference to the registry wrapped in a `Mutex` for thread-safe access.
 ///
 /// # Examples
 ///
@@ -102,6 +187,225 @@ pub fn get_registry() -> &'static std::sync::Mutex<Registry> {
     GLOBAL_REGISTRY.get_or_init(|| std::sync::Mutex::new(Registry::new()))
 }
 
+/// Get the registry [`register_field`] uses on this thread.
+///
+/// Inside [`with_registry`], this is the scoped registry, on the thread running
+/// the scope; otherwise it is the global registry of [`get_registry`].
+///
+/// # Returns
+///
+/// A [`RegistryHandle`] dereferencing to the registry wrapped in a `Mutex`.
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::{current_registry, register_field, with_registry, Registry, Value};
+///
+/// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+/// let (found, _) = with_registry(Registry::new(), || {
+///     register_field("current_age".to_string(), validator, false, vec![], None).unwrap();
+///     current_registry().lock().unwrap().has_field("current_age")
+/// });
+/// assert!(found);
+/// ```
+pub fn current_registry() -> RegistryHandle {
+    let scoped = SCOPED_REGISTRIES.with_borrow(|scoped| scoped.last().cloned());
+    RegistryHandle(match scoped {
+        Some(registry) => RegistrySource::Scoped(registry),
+        None => RegistrySource::Global(get_registry()),
+    })
+}
+
+/// The registry returned by [`current_registry`].
+///
+/// The handle dereferences to the `Mutex` of the global registry or, if it was
+/// taken inside [`with_registry`], of that scope's registry. A handle kept after
+/// its scope ended still refers to the scope's registry, which is then empty,
+/// and never to the registry of a later scope; call [`current_registry`] again
+/// instead of keeping it.
+#[derive(Clone)]
+pub struct RegistryHandle(RegistrySource);
+
+/// Where a [`RegistryHandle`] points.
+#[derive(Clone)]
+enum RegistrySource {
+    /// The process-wide registry
+    Global(&'static Mutex<Registry>),
+    /// A registry installed by [`with_registry`]
+    Scoped(Arc<Mutex<Registry>>),
+}
+
+impl Deref for RegistryHandle {
+    type Target = Mutex<Registry>;
+
+    fn deref(&self) -> &Mutex<Registry> {
+        match &self.0 {
+            RegistrySource::Global(registry) => registry,
+            RegistrySource::Scoped(registry) => registry,
+        }
+    }
+}
+
+/// Run a function with a local registry standing in for the global one.
+///
+/// While `f` runs, [`current_registry`] and [`register_field`] on the calling
+/// thread use `local` instead of the process-wide registry, so tests registering fields
+/// through them do not affect, or conflict with, other tests. Scopes nest; other
+/// threads still see the global registry. The registry, with everything `f`
+/// registered, is returned next to the result of `f`.
+///
+/// A [`RegistryHandle`] taken inside the scope should not be kept after it
+/// ends: the registry it refers to has been moved out and returned.
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::{current_registry, get_registry, register_field, with_registry};
+/// use soakit::{Registry, Value};
+///
+/// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+/// let (found, local) = with_registry(Registry::new(), || {
+///     register_field("scoped_age".to_string(), validator, false, vec![], None).unwrap();
+///     current_registry().lock().unwrap().has_field("scoped_age")
+/// });
+/// assert!(found);
+/// assert!(local.has_field("scoped_age"));
+/// assert!(!get_registry().lock().unwrap().has_field("scoped_age"));
+/// ```
+pub fn with_registry<R>(local: Registry, f: impl FnOnce() -> R) -> (R, Registry) {
+    /// Uninstalls the scoped registry, even if `f` panics.
+    struct Scope;
+
+    impl Drop for Scope {
+        fn drop(&mut self) {
+            let _ = SCOPED_REGISTRIES.with_borrow_mut(Vec::pop);
+        }
+    }
+
+    let scoped = Arc::new(Mutex::new(local));
+    SCOPED_REGISTRIES.with_borrow_mut(|registries| registries.push(Arc::clone(&scoped)));
+    let scope = Scope;
+
+    let result = f();
+    drop(scope);
+    let local = std::mem::take(&mut *scoped.lock().unwrap_or_else(PoisonError::into_inner));
+    (result, local)
+}
+
+/// Run a function with this thread's registry.
+///
+/// Each thread has a registry of its own, empty until fields are registered in
+/// it or it is filled with [`sync_from_global`]. Unlike the global registry it is
+/// reached without locking a `Mutex`, so applications that register their
+/// fields once and then read them on every call can copy the global registry
+/// into each worker thread and use this instead.
+///
+/// # Errors
+///
+/// - [`SoAKitError::InvalidArgument`] if called from within `f` itself
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::{init, with_thread_registry, Value};
+///
+/// with_thread_registry(|registry| registry.register_int("thread_age")).unwrap().unwrap();
+///
+/// let bulk = init(1).unwrap();
+/// let bulk = with_thread_registry(|registry| {
+///     bulk.set(registry, "thread_age", vec![Value::ScalarInt(30)])
+/// })
+/// .unwrap()
+/// .unwrap();
+/// let ages = with_thread_registry(|registry| bulk.get(registry, "thread_age")).unwrap();
+/// assert_eq!(ages.unwrap(), Value::VectorInt(vec![30]));
+/// ```
+pub fn with_thread_registry<R>(f: impl FnOnce(&mut Registry) -> R) -> Result<R> {
+    THREAD_REGISTRY.with(|registry| {
+        let mut registry = registry.try_borrow_mut().map_err(|e| {
+            SoAKitError::InvalidArgument(format!("Thread registry is already in use: {}", e))
+        })?;
+        Ok(f(&mut registry))
+    })
+}
+
+/// Replace this thread's registry with a copy of the global registry.
+///
+/// The copy shares field metadata with the global registry (see
+/// [`Registry`]), so it is cheap. Later changes to either registry are not seen
+/// by the other until the next sync or [`publish_to_global`]. Inside
+/// [`with_registry`], the scoped registry is copied instead.
+///
+/// # Errors
+///
+/// - [`SoAKitError::InvalidArgument`] if the global registry cannot be locked or
+///   this thread's registry is in use
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::{register_field, sync_from_global, with_thread_registry, Value};
+///
+/// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+/// register_field("synced_age".to_string(), validator, false, vec![], None).unwrap();
+///
+/// let found = std::thread::spawn(|| {
+///     sync_from_global().unwrap();
+///     with_thread_registry(|registry| registry.has_field("synced_age")).unwrap()
+/// });
+/// assert!(found.join().unwrap());
+/// ```
+pub fn sync_from_global() -> Result<()> {
+    let global = current_registry()
+        .lock()
+        .map_err(|e| {
+            SoAKitError::InvalidArgument(format!("Failed to lock global registry: {}", e))
+        })?
+        .clone();
+    with_thread_registry(|registry| *registry = global)
+}
+
+/// Merge a copy of this thread's registry into the global registry.
+///
+/// This is [`Registry::merge`] into the global registry, so fields other threads
+/// registered since [`sync_from_global`] are kept. Fields synced from the global
+/// registry are defined in both, so `policy` is usually
+/// [`ConflictPolicy::Skip`] (keep the global definitions) or
+/// [`ConflictPolicy::Overwrite`] (publish this thread's definitions). Fields
+/// removed from this thread's registry are not removed from the global one.
+///
+/// Other threads see the published fields through [`get_registry`], or in
+/// their own thread registry after [`sync_from_global`]. Inside
+/// [`with_registry`], the scoped registry is merged into instead.
+///
+/// # Errors
+///
+/// - [`SoAKitError::InvalidArgument`] if the global registry cannot be locked or
+///   this thread's registry is in use
+/// - The errors of [`Registry::merge`], in which case the global registry is
+///   unchanged
+pub fn publish_to_global(policy: ConflictPolicy) -> Result<()> {
+    let local = with_thread_registry(|registry| registry.clone())?;
+    current_registry()
+        .lock()
+        .map_err(|e| {
+            SoAKitError::InvalidArgument(format!("Failed to lock global registry: {}", e))
+        })?
+        .merge(local, policy)
+}
+
+/// Clear the process-wide registry, removing every field registered through
+/// [`register_field`] outside of [`with_registry`], or through [`get_registry`].
+///
+/// A registry poisoned by a panicking test is recovered. Available in tests of
+/// this crate and with the `testing` feature.
+#[cfg(any(test, feature = "testing"))]
+pub fn reset_global_registry() {
+    let global = get_registry();
+    *global.lock().unwrap_or_else(PoisonError::into_inner) = Registry::new();
+    global.clear_poison();
+}
+
 /// Register a field in the global registry.
 ///
 /// Fields must be registered before they can be used in a [`Bulk`] structure.
@@ -132,6 +436,7 @@ pub fn get_registry() -> &'static std::sync::Mutex<Registry> {
 /// - [`SoAKitError::InvalidArgument`] if the field name is invalid or arguments are inconsistent
 /// - [`SoAKitError::FieldAlreadyExists`] if a field with the same name is already registered
 /// - [`SoAKitError::DerivedFieldNoDeps`] if a derived field has no dependencies
+/// - [`SoAKitError::DependencyCycle`] if the field would be part of a dependency cycle
 ///
 /// # Examples
 ///
@@ -173,7 +478,7 @@ pub fn register_field(
     dependencies: Vec<String>,
     derived_func: Option<Box<dyn Fn(&[Value]) -> Result<Value> + Send + Sync>>,
 ) -> Result<()> {
-    let registry = get_registry();
+    let registry = current_registry();
     let mut reg = registry
         .lock()
         .map_err(|_| SoAKitError::InvalidArgument("Failed to lock global registry".to_string()))?;
@@ -376,4 +681,92 @@ mod tests {
             panic!("Expected VectorInt");
         }
     }
+
+    #[test]
+    fn test_with_registry_nests_and_survives_panics() {
+        let validator = || Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        let (inner, outer) = with_registry(Registry::new(), || {
+            register_field("scope_outer".to_string(), validator(), false, vec![], None).unwrap();
+            let ((), inner) = with_registry(Registry::new(), || {
+                register_field("scope_inner".to_string(), validator(), false, vec![], None)
+                    .unwrap();
+            });
+            assert!(current_registry().lock().unwrap().has_field("scope_outer"));
+            assert!(!get_registry().lock().unwrap().has_field("scope_outer"));
+            inner
+        });
+        assert_eq!(outer.list_fields(), vec!["scope_outer"]);
+        assert_eq!(inner.list_fields(), vec!["scope_inner"]);
+
+        // A panic inside the scope, even with the registry locked, uninstalls it
+        let panicked = std::panic::catch_unwind(|| {
+            with_registry(Registry::new(), || {
+                let registry = current_registry();
+                let _reg = registry.lock().unwrap();
+                panic!("test panic");
+            })
+        });
+        assert!(panicked.is_err());
+        let ((), reused) = with_registry(Registry::new(), || {
+            register_field("scope_again".to_string(), validator(), false, vec![], None).unwrap();
+        });
+        assert_eq!(reused.list_fields(), vec!["scope_again"]);
+        let registry = get_registry();
+        let global = registry.lock().unwrap();
+        assert!(
+            !["scope_outer", "scope_inner", "scope_again"]
+                .iter()
+                .any(|f| global.has_field(f))
+        );
+
+        // A handle kept past its scope never sees a later scope's registry
+        let (kept, _) = with_registry(Registry::new(), current_registry);
+        let ((), _) = with_registry(Registry::new(), || {
+            register_field("scope_kept".to_string(), validator(), false, vec![], None).unwrap();
+        });
+        assert!(kept.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_thread_registry_sync_and_publish() {
+        std::thread::spawn(|| {
+            let ((), published) = with_registry(Registry::new(), || {
+                let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+                register_field("shared".to_string(), validator, false, vec![], None).unwrap();
+
+                sync_from_global().unwrap();
+                with_thread_registry(|reg| {
+                    assert!(reg.has_field("shared"));
+                    reg.register_float("local_only").unwrap();
+                })
+                .unwrap();
+                assert!(!current_registry().lock().unwrap().has_field("local_only"));
+
+                // Re-entering the thread registry fails instead of panicking
+                let nested = with_thread_registry(|_| with_thread_registry(|_| ()));
+                assert!(matches!(nested, Ok(Err(SoAKitError::InvalidArgument(_)))));
+
+                // Fields registered globally after the sync survive the publish
+                register_field("late".to_string(), Box::new(|_| true), false, vec![], None)
+                    .unwrap();
+                let conflict = publish_to_global(ConflictPolicy::Error);
+                assert!(matches!(conflict, Err(SoAKitError::FieldAlreadyExists(_))));
+                assert!(!current_registry().lock().unwrap().has_field("local_only"));
+                publish_to_global(ConflictPolicy::Skip).unwrap();
+            });
+            assert_eq!(
+                published.list_fields(),
+                vec!["late", "local_only", "shared"]
+            );
+        })
+        .join()
+        .unwrap();
+
+        // Every thread starts with an empty registry of its own
+        assert!(
+            std::thread::spawn(|| with_thread_registry(|reg| reg.is_empty()).unwrap())
+                .join()
+                .unwrap()
+        );
+    }
 }
@@ -2,10 +2,14 @@
 ///
 /// This module provides the [`Registry`] and [`FieldMetadata`] structures for
 /// managing field definitions, validation, and derived field computation.
+use crate::constraint::Constraint;
 use crate::error::{Result, SoAKitError};
 use crate::util::is_valid_field_name;
-use crate::value::Value;
-use std::collections::BTreeMap;
+use crate::value::{Value, ValueKind, variant_name};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
 
 /// Type alias for derived field computation functions.
 ///
@@ -13,6 +17,267 @@ use std::collections::BTreeMap;
 /// and returns a `Result<Value>` (the computed value).
 pub type DerivedFunc = Box<dyn Fn(&[Value]) -> Result<Value> + Send + Sync>;
 
+/// A [`DerivedFunc`] shared between clones of a [`FieldMetadata`].
+pub type SharedDerivedFunc = Arc<dyn Fn(&[Value]) -> Result<Value> + Send + Sync>;
+
+/// A derived function computing several fields at once, returning one value
+/// per output in the order the outputs were registered.
+pub type MultiDerivedFunc = dyn Fn(&[Value]) -> Result<Vec<Value>> + Send + Sync;
+
+/// The derived fields computed together by one [`MultiDerivedFunc`], registered
+/// with [`Registry::register_derived_multi`].
+#[derive(Clone)]
+pub struct MultiOutput {
+    /// Names of the fields computed, in the order the function returns them
+    pub names: Vec<String>,
+    /// The function computing every output from the shared dependencies
+    pub func: Arc<MultiDerivedFunc>,
+}
+
+impl MultiOutput {
+    /// Compute every output from the values of the dependencies.
+    ///
+    /// # Errors
+    ///
+    /// - The error of the function, if it fails
+    /// - [`SoAKitError::InvalidArgument`] if it returns the wrong number of values
+    pub fn compute(&self, args: &[Value]) -> Result<Vec<Value>> {
+        let values = (self.func)(args)?;
+        if values.len() != self.names.len() {
+            return Err(SoAKitError::InvalidArgument(format!(
+                "Derived function of {} returned {} values",
+                self.names.join(", "),
+                values.len()
+            )));
+        }
+        Ok(values)
+    }
+}
+
+/// What a derived function registered with
+/// [`Registry::register_derived_with_context`] knows about the bulk it is
+/// computed for, next to the values of its dependencies. Virtual fields
+/// registered with [`Registry::register_virtual`] are resolved from it alone.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DerivedContext<'a> {
+    /// IDs of the elements, in order (see [`Meta::id`](crate::bulk::Meta::id))
+    pub ids: &'a [usize],
+    /// Number of elements
+    pub count: usize,
+    /// Index of the first element of each chunk, in order
+    pub chunk_offsets: &'a [usize],
+}
+
+/// Get the index of the first element of each chunk from the chunk lengths.
+pub(crate) fn chunk_offsets(lens: impl IntoIterator<Item = usize>) -> Vec<usize> {
+    lens.into_iter()
+        .scan(0usize, |start, len| {
+            let offset = *start;
+            *start = start.saturating_add(len);
+            Some(offset)
+        })
+        .collect()
+}
+
+/// A derived function that also receives the [`DerivedContext`] of the bulk.
+pub type ContextDerivedFunc = dyn Fn(&DerivedContext<'_>, &[Value]) -> Result<Value> + Send + Sync;
+
+/// A function resolving the values of a virtual field from the
+/// [`DerivedContext`] of the bulk, registered with [`Registry::register_virtual`].
+pub type VirtualFunc = dyn Fn(&DerivedContext<'_>) -> Result<Value> + Send + Sync;
+
+/// A callback run with the name and replacement hint of a deprecated field
+/// when it is accessed.
+pub type DeprecationHandler = dyn Fn(&str, &str) + Send + Sync;
+
+/// How [`Registry::merge`] handles a name defined in both registries.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ConflictPolicy {
+    /// Fail with [`SoAKitError::FieldAlreadyExists`] and merge nothing
+    #[default]
+    Error,
+    /// Keep the existing definition and drop the merged one
+    Skip,
+    /// Replace the existing definition with the merged one
+    Overwrite,
+}
+
+/// Declared type of a field's values.
+///
+/// Fields registered with [`Registry::register_typed`] or
+/// [`Registry::register_with_type`] record their type, and their validator
+/// accepts exactly the values of that type. The type is written to schemas,
+/// used to cast values read back from untagged records (see
+/// [`FieldType::coerce`]), and named in validation errors, e.g.
+/// "expected Int, got Float".
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FieldType {
+    /// `ScalarInt` values
+    Int,
+    /// `ScalarFloat` values
+    Float,
+    /// `ScalarBool` values
+    Bool,
+    /// `ScalarString` values
+    String,
+    /// Vector values of any element kind, one vector per element
+    Vector,
+    /// `Matrix` values, one matrix per element
+    Matrix,
+    /// `ScalarJson` values
+    Json,
+}
+
+impl FieldType {
+    /// Check whether a value is a scalar of this type.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{FieldType, Value};
+    ///
+    /// assert!(FieldType::Int.matches(&Value::ScalarInt(1)));
+    /// assert!(!FieldType::Int.matches(&Value::ScalarFloat(1.0)));
+    /// assert!(!FieldType::Int.matches(&Value::VectorInt(vec![1])));
+    /// ```
+    pub const fn matches(self, value: &Value) -> bool {
+        matches!(
+            (self, value),
+            (FieldType::Int, Value::ScalarInt(_))
+                | (FieldType::Float, Value::ScalarFloat(_))
+                | (FieldType::Bool, Value::ScalarBool(_))
+                | (FieldType::String, Value::ScalarString(_))
+                | (FieldType::Matrix, Value::Matrix(_))
+                | (FieldType::Json, Value::ScalarJson(_))
+        ) || matches!(self, FieldType::Vector) && value.is_vector()
+    }
+
+    /// Get the type of a value, or `None` for `Null` and tensors.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{FieldType, Value};
+    ///
+    /// assert_eq!(FieldType::of(&Value::ScalarFloat(1.0)), Some(FieldType::Float));
+    /// assert_eq!(FieldType::of(&Value::VectorInt(vec![1])), Some(FieldType::Vector));
+    /// assert_eq!(FieldType::of(&Value::Null), None);
+    /// ```
+    pub const fn of(value: &Value) -> Option<FieldType> {
+        match value {
+            Value::ScalarInt(_) => Some(FieldType::Int),
+            Value::ScalarFloat(_) => Some(FieldType::Float),
+            Value::ScalarBool(_) => Some(FieldType::Bool),
+            Value::ScalarString(_) => Some(FieldType::String),
+            Value::Matrix(_) => Some(FieldType::Matrix),
+            Value::ScalarJson(_) => Some(FieldType::Json),
+            Value::Tensor { .. } | Value::Null => None,
+            _ => Some(FieldType::Vector),
+        }
+    }
+
+    /// Get the name of the type, as shown in error messages.
+    pub const fn name(self) -> &'static str {
+        match self {
+            FieldType::Int => "Int",
+            FieldType::Float => "Float",
+            FieldType::Bool => "Bool",
+            FieldType::String => "String",
+            FieldType::Vector => "Vector",
+            FieldType::Matrix => "Matrix",
+            FieldType::Json => "Json",
+        }
+    }
+
+    /// Get the element kind of a scalar type, or `None` for the other types.
+    pub const fn kind(self) -> Option<ValueKind> {
+        match self {
+            FieldType::Int => Some(ValueKind::Int),
+            FieldType::Float => Some(ValueKind::Float),
+            FieldType::Bool => Some(ValueKind::Bool),
+            FieldType::String => Some(ValueKind::String),
+            FieldType::Vector | FieldType::Matrix | FieldType::Json => None,
+        }
+    }
+
+    /// Convert a value to this type where no information is lost.
+    ///
+    /// Values of this type are returned unchanged. An integer becomes a float
+    /// (which may round above 2^53), and a float with no fractional part that
+    /// fits in an `i64` becomes an integer. Other values are not converted.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Some(Value)` of this type, or `None` if the value cannot be converted.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{FieldType, Value};
+    ///
+    /// assert_eq!(FieldType::Float.coerce(&Value::ScalarInt(2)), Some(Value::ScalarFloat(2.0)));
+    /// assert_eq!(FieldType::Int.coerce(&Value::ScalarFloat(2.0)), Some(Value::ScalarInt(2)));
+    /// assert_eq!(FieldType::Int.coerce(&Value::ScalarFloat(2.5)), None);
+    /// assert_eq!(FieldType::Int.coerce(&Value::ScalarString("2".to_string())), None);
+    /// ```
+    pub fn coerce(self, value: &Value) -> Option<Value> {
+        match (self, value) {
+            _ if self.matches(value) => Some(value.clone()),
+            (FieldType::Float, Value::ScalarInt(_)) => value.cast(ValueKind::Float).ok(),
+            (FieldType::Int, Value::ScalarFloat(x)) if x.fract() == 0.0 => {
+                value.cast(ValueKind::Int).ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// Describe why a value is not of this type, as "expected Int, got Float".
+    ///
+    /// # Returns
+    ///
+    /// Returns `None` if the value is of this type.
+    pub fn mismatch(self, value: &Value) -> Option<String> {
+        if self.matches(value) {
+            return None;
+        }
+        let actual = FieldType::of(value).map_or(variant_name(value), FieldType::name);
+        Some(format!("expected {}, got {}", self, actual))
+    }
+
+    /// Get a validator accepting exactly the values of this type.
+    pub fn validator(self) -> Box<dyn Fn(&Value) -> bool + Send + Sync> {
+        Box::new(move |value: &Value| self.matches(value))
+    }
+}
+
+impl fmt::Display for FieldType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Rust types stored in fields of a [`FieldType`], for [`Registry::register_typed`].
+pub trait ScalarType {
+    /// The field type holding values of this Rust type
+    const FIELD_TYPE: FieldType;
+}
+
+impl ScalarType for i64 {
+    const FIELD_TYPE: FieldType = FieldType::Int;
+}
+
+impl ScalarType for f64 {
+    const FIELD_TYPE: FieldType = FieldType::Float;
+}
+
+impl ScalarType for bool {
+    const FIELD_TYPE: FieldType = FieldType::Bool;
+}
+
+impl ScalarType for String {
+    const FIELD_TYPE: FieldType = FieldType::String;
+}
+
 /// Metadata for a field in the registry.
 ///
 /// Contains all information needed to validate and compute field values,
@@ -25,15 +290,68 @@ pub type DerivedFunc = Box<dyn Fn(&[Value]) -> Result<Value> + Send + Sync>;
 /// * `is_derived` - Whether this field is computed from other fields
 /// * `dependencies` - For derived fields, the names of fields this depends on
 /// * `derived_func` - For derived fields, the function that computes the value
+/// * `chunk_safe` - For derived fields, whether the function can run chunk by chunk
+/// * `field_type` - The declared type of the field's values, if any
+/// * `constraints` - Declarative rules every value of the field must satisfy
+/// * `default` - The value used where an element of the field is missing
+/// * `tags` - Application metadata such as units, a description or a PII flag
+/// * `required` - Whether every bulk must hold data for the field
+/// * `deprecated` - For deprecated fields, a hint naming the replacement
+/// * `multi_output` - For fields computed together with others, the shared computation
+/// * `context_func` - For derived fields reading the bulk's context, the computation
+/// * `validate_output` - For derived fields, whether computed values are checked
+/// * `resolver` - For virtual fields, the function resolving their values
+/// * `shape` - The shape every element value of the field must have, if declared
+///
+/// The validator and functions are shared, so cloning metadata is cheap.
+#[derive(Clone)]
 pub struct FieldMetadata {
     /// Validator function that checks if a value is valid for this field
-    pub validator: Box<dyn Fn(&Value) -> bool + Send + Sync>,
+    pub validator: Arc<dyn Fn(&Value) -> bool + Send + Sync>,
     /// Whether this field is derived (computed from other fields)
     pub is_derived: bool,
     /// Dependencies for derived fields (field names this field depends on)
     pub dependencies: Vec<String>,
     /// Function to compute derived field value from dependencies
-    pub derived_func: Option<DerivedFunc>,
+    pub derived_func: Option<SharedDerivedFunc>,
+    /// Whether the derived function computes each element from the same element
+    /// of its dependencies, so it can be evaluated one chunk at a time
+    pub chunk_safe: bool,
+    /// Declared type of the field's values, recorded by typed registration
+    pub field_type: Option<FieldType>,
+    /// Constraints checked next to the validator, added with
+    /// [`Registry::add_constraint`]
+    pub constraints: Vec<Constraint>,
+    /// Value used for new elements and omitted record fields, set with
+    /// [`Registry::set_default`]
+    pub default: Option<Value>,
+    /// Free-form key/value metadata, set with [`Registry::set_tag`]
+    pub tags: BTreeMap<String, String>,
+    /// Whether the field is required, checked by
+    /// [`Bulk::validate_complete`](crate::Bulk::validate_complete)
+    pub required: bool,
+    /// Replacement hint of a deprecated field, set with [`Registry::deprecate`]
+    pub deprecated: Option<String>,
+    /// The computation shared with the other outputs of a
+    /// [`Registry::register_derived_multi`] field; `derived_func` then computes
+    /// this output alone
+    pub multi_output: Option<MultiOutput>,
+    /// Function computing the field from the bulk's context and its dependencies,
+    /// set by [`Registry::register_derived_with_context`]; `derived_func` then
+    /// computes it as if the dependencies were a whole bulk in one chunk
+    pub context_func: Option<Arc<ContextDerivedFunc>>,
+    /// Whether computed values of a derived field are checked for length and
+    /// against the validator, set with [`Registry::set_validate_output`]
+    pub validate_output: bool,
+    /// Function resolving the values of a virtual field on every access, set by
+    /// [`Registry::register_virtual`]
+    pub resolver: Option<Arc<VirtualFunc>>,
+    /// Whether the field reads a virtual field, directly or through other derived
+    /// fields, so that its values are never cached; kept up to date by the registry
+    pub reads_virtual: bool,
+    /// Shape of every element value, as by [`Value::shape`], declared with
+    /// [`Registry::set_shape`]
+    pub shape: Option<Vec<usize>>,
 }
 
 impl FieldMetadata {
@@ -61,10 +379,23 @@ impl FieldMetadata {
     /// ```
     pub fn new(validator: Box<dyn Fn(&Value) -> bool + Send + Sync>) -> Self {
         Self {
-            validator,
+            validator: validator.into(),
             is_derived: false,
             dependencies: Vec::new(),
             derived_func: None,
+            chunk_safe: false,
+            field_type: None,
+            constraints: Vec::new(),
+            default: None,
+            tags: BTreeMap::new(),
+            required: false,
+            deprecated: None,
+            multi_output: None,
+            context_func: None,
+            validate_output: true,
+            resolver: None,
+            reads_virtual: false,
+            shape: None,
         }
     }
 
@@ -120,12 +451,115 @@ impl FieldMetadata {
             ));
         }
         Ok(Self {
-            validator,
+            validator: validator.into(),
             is_derived: true,
             dependencies,
-            derived_func: Some(derived_func),
+            derived_func: Some(derived_func.into()),
+            chunk_safe: false,
+            field_type: None,
+            constraints: Vec::new(),
+            default: None,
+            tags: BTreeMap::new(),
+            required: false,
+            deprecated: None,
+            multi_output: None,
+            context_func: None,
+            validate_output: true,
+            resolver: None,
+            reads_virtual: false,
+            shape: None,
         })
     }
+
+    /// Check whether the field is virtual, resolved by a [`VirtualFunc`] rather
+    /// than stored or derived.
+    pub const fn is_virtual(&self) -> bool {
+        self.resolver.is_some()
+    }
+
+    /// Check whether an element value has the declared shape of the field.
+    ///
+    /// Values of fields without a declared shape, and `Null`, always match.
+    pub fn shape_matches(&self, value: &Value) -> bool {
+        self.shape
+            .as_ref()
+            .is_none_or(|shape| value.is_null() || value.shape() == *shape)
+    }
+}
+
+impl FieldMetadata {
+    /// Compute a derived field from the values of its dependencies, passing the
+    /// context to a [`ContextDerivedFunc`].
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if the field is not derived
+    /// - The error of the derived function, if it fails
+    pub fn derive(&self, context: &DerivedContext<'_>, args: &[Value]) -> Result<Value> {
+        if let Some(func) = &self.context_func {
+            return func(context, args);
+        }
+        let derived_func = self.derived_func.as_ref().ok_or_else(|| {
+            SoAKitError::InvalidArgument("Derived field missing function".to_string())
+        })?;
+        derived_func(args)
+    }
+
+    /// Check a computed value of the derived field `field` for a bulk of `count`
+    /// elements, unless checks are turned off with `validate_output`.
+    ///
+    /// The value must have `count` elements, each of the declared shape if any,
+    /// and the validator must accept either the value as a whole or each of its
+    /// elements that is not missing, so validators written for columns and for
+    /// scalars both work.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::DerivedOutputInvalid`] if the value has the wrong length,
+    ///   an element of the wrong shape or is rejected by the validator
+    pub fn check_output(&self, field: &str, value: &Value, count: usize) -> Result<()> {
+        if !self.validate_output {
+            return Ok(());
+        }
+        let invalid = |reason: String| SoAKitError::DerivedOutputInvalid {
+            field: field.to_string(),
+            reason,
+        };
+        if value.len() != count {
+            return Err(invalid(format!(
+                "expected {} values, got {}",
+                count,
+                value.len()
+            )));
+        }
+        if let Some(shape) = &self.shape {
+            for (i, element) in value.to_scalars().into_iter().enumerate() {
+                if !self.shape_matches(&element) {
+                    return Err(invalid(format!(
+                        "element {} has shape {:?}, expected {:?}",
+                        i,
+                        element.shape(),
+                        shape
+                    )));
+                }
+            }
+        }
+        if (self.validator)(value) {
+            return Ok(());
+        }
+        let rejected = value
+            .to_scalars()
+            .into_iter()
+            .enumerate()
+            .find(|(_, element)| !element.is_null() && !(self.validator)(element));
+        match rejected {
+            Some((i, element)) => Err(invalid(format!(
+                "element {} ({:?}) rejected by the validator",
+                i, element
+            ))),
+            None => Ok(()),
+        }
+    }
 }
 
 /// Registry for field metadata.
@@ -135,9 +569,41 @@ impl FieldMetadata {
 ///
 /// Fields can be either regular (storing data directly) or derived (computed from
 /// other fields). Derived fields automatically cache their computed values and
-/// invalidate the cache when dependencies change.
+/// invalidate the cache when dependencies change. Virtual fields, registered with
+/// [`Registry::register_virtual`], are resolved from outside state on every access.
+///
+/// A registry created with [`Registry::with_parent`] layers its own fields over
+/// those of a shared parent registry; see there for details.
+///
+/// Cloning a registry is cheap: field metadata is shared between the clones and
+/// copied only when one of them modifies the field, so a registry can be
+/// snapshotted or sent to worker threads without rebuilding its closures.
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::{Registry, Value};
+///
+/// let mut registry = Registry::new();
+/// registry.register_int("age").unwrap();
+/// let snapshot = registry.clone();
+///
+/// registry.set_tag("age", "unit", "years").unwrap();
+/// assert_eq!(registry.tag("age", "unit"), Some("years"));
+/// assert_eq!(snapshot.tag("age", "unit"), None);
+/// assert!(snapshot.validate("age", &Value::ScalarInt(42)));
+///
+/// let worker = std::thread::spawn(move || snapshot.list_fields());
+/// assert_eq!(worker.join().unwrap(), vec!["age"]);
+/// ```
+#[derive(Clone)]
 pub struct Registry {
-    fields: BTreeMap<String, FieldMetadata>,
+    fields: BTreeMap<String, Arc<FieldMetadata>>,
+    aliases: BTreeMap<String, String>,
+    groups: BTreeMap<String, Vec<String>>,
+    deprecation_handler: Option<Arc<DeprecationHandler>>,
+    parent: Option<Arc<Registry>>,
+    schema_version: u32,
 }
 
 impl Registry {
@@ -158,7 +624,149 @@ impl Registry {
     pub const fn new() -> Self {
         Self {
             fields: BTreeMap::new(),
+            aliases: BTreeMap::new(),
+            groups: BTreeMap::new(),
+            deprecation_handler: None,
+            parent: None,
+            schema_version: 0,
+        }
+    }
+
+    /// Create an empty registry layered over a parent registry.
+    ///
+    /// Lookups that miss in the new registry fall back to the parent (and its own
+    /// parent, if any): fields, aliases, groups and the deprecation handler of the parent
+    /// are visible through the child, and listings include them. Fields are
+    /// registered in the child only, and a name already used by a field or an
+    /// alias in any layer cannot be registered again. The parent is shared and
+    /// never modified through the child, so methods changing a field (such as
+    /// [`Registry::set_default`] or [`Registry::rename_field`]) refuse parent fields.
+    ///
+    /// This lets applications keep a core schema in one registry and add
+    /// module-specific fields, including derived fields over core fields, in
+    /// separate layers. The new registry starts at the schema version of the parent.
+    ///
+    /// # Arguments
+    ///
+    /// * `parent` - The registry to fall back to
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, SoAKitError, Value};
+    /// use std::sync::Arc;
+    ///
+    /// let mut core = Registry::new();
+    /// core.register_string("name").unwrap();
+    /// let core = Arc::new(core);
+    ///
+    /// let mut billing = Registry::with_parent(&core);
+    /// billing.register_float("balance").unwrap();
+    /// assert_eq!(billing.list_fields(), vec!["balance", "name"]);
+    /// assert!(matches!(billing.register_int("name"), Err(SoAKitError::FieldAlreadyExists(_))));
+    ///
+    /// let bulk = Bulk::new(1).unwrap();
+    /// let bulk = bulk.set(&billing, "name", vec![Value::from("Ann".to_string())]).unwrap();
+    /// let bulk = bulk.set(&billing, "balance", vec![Value::ScalarFloat(1.5)]).unwrap();
+    /// assert_eq!(bulk.list_data_fields(), vec!["balance", "name"]);
+    /// ```
+    pub fn with_parent(parent: &Arc<Registry>) -> Self {
+        Self {
+            parent: Some(Arc::clone(parent)),
+            schema_version: parent.schema_version,
+            ..Self::new()
+        }
+    }
+
+    /// Get the schema version of the registry.
+    ///
+    /// The version starts at 0 and is set with [`Registry::set_schema_version`].
+    /// Bulks record the version of the registry their first field was stored
+    /// with, so [`Migrations`](crate::migration::Migrations) can upgrade data
+    /// written with an older schema.
+    pub const fn schema_version(&self) -> u32 {
+        self.schema_version
+    }
+
+    /// Set the schema version of the registry.
+    ///
+    /// Bump the version whenever a change of the fields makes existing data
+    /// incompatible, and register the matching migration steps.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// registry.register_int("age").unwrap();
+    /// registry.set_schema_version(2);
+    ///
+    /// let bulk = Bulk::new(1).unwrap().set(&registry, "age", vec![Value::ScalarInt(3)]).unwrap();
+    /// assert_eq!(bulk.meta.schema_version, 2);
+    /// ```
+    pub const fn set_schema_version(&mut self, version: u32) {
+        self.schema_version = version;
+    }
+
+    /// Get the parent registry, if this registry was created with
+    /// [`Registry::with_parent`].
+    pub fn parent(&self) -> Option<&Registry> {
+        self.parent.as_deref()
+    }
+
+    /// Look up a field in this registry or its ancestors.
+    fn field(&self, name: &str) -> Option<&FieldMetadata> {
+        self.fields
+            .get(name)
+            .map(AsRef::as_ref)
+            .or_else(|| self.parent.as_ref()?.field(name))
+    }
+
+    /// Look up a field of this registry, not an ancestor, for modification.
+    fn own_field_mut(&mut self, name: &str) -> Result<&mut FieldMetadata> {
+        let in_parent = self
+            .parent
+            .as_ref()
+            .is_some_and(|parent| parent.has_field(name));
+        if in_parent && !self.fields.contains_key(name) {
+            return Err(SoAKitError::InvalidArgument(format!(
+                "Field {} belongs to a parent registry",
+                name
+            )));
         }
+        self.fields
+            .get_mut(name)
+            .map(Arc::make_mut)
+            .ok_or_else(|| SoAKitError::FieldNotFound(name.to_string()))
+    }
+
+    /// Collect the fields of this registry and its ancestors, by name.
+    fn all_fields(&self) -> BTreeMap<&str, &FieldMetadata> {
+        let mut fields = self
+            .parent
+            .as_ref()
+            .map(|parent| parent.all_fields())
+            .unwrap_or_default();
+        fields.extend(
+            self.fields
+                .iter()
+                .map(|(name, metadata)| (name.as_str(), &**metadata)),
+        );
+        fields
+    }
+
+    /// Look up the target of an alias in this registry or its ancestors.
+    fn alias_target(&self, alias: &str) -> Option<&str> {
+        self.aliases
+            .get(alias)
+            .map(String::as_str)
+            .or_else(|| self.parent.as_ref()?.alias_target(alias))
+    }
+
+    /// Check whether a field or an alias of any layer has this name.
+    fn name_taken(&self, name: &str) -> bool {
+        self.field(name).is_some() || self.alias_target(name).is_some()
     }
 
     /// Register a new field.
@@ -184,12 +792,14 @@ impl Registry {
     /// - The field name is invalid
     /// - The field already exists
     /// - Arguments are inconsistent (e.g., derived field without dependencies)
+    /// - The field would depend on itself through its dependencies
     ///
     /// # Errors
     ///
     /// - [`SoAKitError::InvalidArgument`] if the name is invalid or arguments are inconsistent
-    /// - [`SoAKitError::FieldAlreadyExists`] if the field already exists
+    /// - [`SoAKitError::FieldAlreadyExists`] if the field or an alias of that name already exists
     /// - [`SoAKitError::DerivedFieldNoDeps`] if a derived field has no dependencies
+    /// - [`SoAKitError::DependencyCycle`] if the field would be part of a dependency cycle
     ///
     /// # Examples
     ///
@@ -243,32 +853,1746 @@ impl Registry {
             )));
         }
 
-        if self.fields.contains_key(&name) {
-            return Err(SoAKitError::FieldAlreadyExists(name));
+        if self.name_taken(&name) {
+            return Err(SoAKitError::FieldAlreadyExists(name));
+        }
+
+        if is_derived {
+            if dependencies.is_empty() {
+                return Err(SoAKitError::DerivedFieldNoDeps(name));
+            }
+            let derived_func = derived_func.ok_or_else(|| {
+                SoAKitError::InvalidArgument(
+                    "Derived field must have a derived function".to_string(),
+                )
+            })?;
+            let cycle = find_cycle([name.as_str()], &|field: &str| {
+                if field == name {
+                    dependencies.clone()
+                } else {
+                    self.dependencies_of(field)
+                }
+            });
+            if let Some(cycle) = cycle {
+                return Err(SoAKitError::DependencyCycle(cycle));
+            }
+            let mut metadata = FieldMetadata::new_derived(validator, dependencies, derived_func)?;
+            metadata.reads_virtual = self.any_reads_virtual(&metadata.dependencies);
+            let refresh = metadata.reads_virtual;
+            let _ = self.fields.insert(name, Arc::new(metadata));
+            if refresh {
+                // Fields registered earlier may depend on this one
+                self.refresh_reads_virtual();
+            }
+        } else {
+            if !dependencies.is_empty() || derived_func.is_some() {
+                return Err(SoAKitError::InvalidArgument(
+                    "Non-derived field cannot have dependencies or derived function".to_string(),
+                ));
+            }
+            let metadata = FieldMetadata::new(validator);
+            let _ = self.fields.insert(name, Arc::new(metadata));
+        }
+
+        Ok(())
+    }
+
+    /// Register a chunk-safe derived field.
+    ///
+    /// This is [`Registry::register`] for a derived field whose function is
+    /// element-wise: element `i` of its output depends only on element `i` of each
+    /// dependency, and the output has one element per input element. Such a field
+    /// is computed one chunk at a time from the chunk columns of its dependencies
+    /// and the results are concatenated, instead of materializing whole dependency
+    /// columns for one call. With the `rayon` feature, chunks are computed in
+    /// parallel.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the field (must be valid and unique)
+    /// * `validator` - Function that validates values for this field
+    /// * `dependencies` - The names of the fields this field depends on
+    /// * `derived_func` - The element-wise computation function
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if successful.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Registry::register`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, SoAKitError, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
+    /// registry.register("x".to_string(), validator, false, vec![], None).unwrap();
+    /// let derived_func = Box::new(|args: &[Value]| match &args[0] {
+    ///     Value::VectorFloat(xs) => Ok(Value::VectorFloat(xs.iter().map(|x| x * x).collect())),
+    ///     _ => Err(SoAKitError::InvalidArgument("expected floats".to_string())),
+    /// });
+    /// registry
+    ///     .register_chunk_safe("x2".to_string(), Box::new(|_| true), vec!["x".to_string()], derived_func)
+    ///     .unwrap();
+    ///
+    /// let bulk = Bulk::new(2).unwrap();
+    /// let bulk = bulk.set(&registry, "x", vec![Value::ScalarFloat(2.0), Value::ScalarFloat(3.0)]).unwrap();
+    /// assert_eq!(bulk.get(&registry, "x2").unwrap(), Value::VectorFloat(vec![4.0, 9.0]));
+    /// ```
+    pub fn register_chunk_safe(
+        &mut self,
+        name: String,
+        validator: Box<dyn Fn(&Value) -> bool + Send + Sync>,
+        dependencies: Vec<String>,
+        derived_func: DerivedFunc,
+    ) -> Result<()> {
+        self.register(
+            name.clone(),
+            validator,
+            true,
+            dependencies,
+            Some(derived_func),
+        )?;
+        if let Some(metadata) = self.fields.get_mut(&name) {
+            Arc::make_mut(metadata).chunk_safe = true;
+        }
+        Ok(())
+    }
+
+    /// Register a derived field computed one element at a time.
+    ///
+    /// `func` receives the scalars of every dependency at one row, in the order
+    /// of `dependencies` (`Null` where a sparse column has no value), and returns
+    /// that row's scalar. The engine maps it across each chunk and rebuilds the
+    /// column, so the field is chunk-safe as with [`Registry::register_chunk_safe`].
+    /// The field accepts any value; its validator is not consulted for derived
+    /// results.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the field (must be valid and unique)
+    /// * `dependencies` - The names of the fields this field depends on
+    /// * `func` - The computation for one element
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Registry::register`]. Computing the field
+    /// fails with the first error `func` returns, and with
+    /// [`SoAKitError::InvalidArgument`] if its results cannot form one column.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, SoAKitError, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// registry.register_float("price").unwrap();
+    /// registry.register_int("quantity").unwrap();
+    /// registry
+    ///     .register_derived_elementwise("total", &["price", "quantity"], |row: &[Value]| {
+    ///         match row {
+    ///             [Value::ScalarFloat(p), Value::ScalarInt(q)] => Ok(Value::ScalarFloat(p * *q as f64)),
+    ///             _ => Err(SoAKitError::InvalidArgument("expected price and quantity".to_string())),
+    ///         }
+    ///     })
+    ///     .unwrap();
+    ///
+    /// let bulk = Bulk::new(2).unwrap();
+    /// let bulk = bulk.set(&registry, "price", vec![Value::ScalarFloat(1.5), Value::ScalarFloat(2.0)]).unwrap();
+    /// let bulk = bulk.set(&registry, "quantity", vec![Value::ScalarInt(2), Value::ScalarInt(3)]).unwrap();
+    /// assert_eq!(bulk.get(&registry, "total").unwrap(), Value::VectorFloat(vec![3.0, 6.0]));
+    /// ```
+    pub fn register_derived_elementwise<F>(
+        &mut self,
+        name: &str,
+        dependencies: &[&str],
+        func: F,
+    ) -> Result<()>
+    where
+        F: Fn(&[Value]) -> Result<Value> + Send + Sync + 'static,
+    {
+        let derived_func: DerivedFunc = Box::new(move |args: &[Value]| {
+            let columns: Vec<Vec<Value>> = args.iter().map(Value::to_scalars).collect();
+            let len = columns.first().map_or(0, Vec::len);
+            if let Some(column) = columns.iter().find(|column| column.len() != len) {
+                return Err(SoAKitError::LengthMismatch {
+                    expected: len,
+                    actual: column.len(),
+                });
+            }
+            let results = (0..len)
+                .map(|i| {
+                    let row: Vec<Value> = columns
+                        .iter()
+                        .filter_map(|column| column.get(i).cloned())
+                        .collect();
+                    func(&row)
+                })
+                .collect::<Result<Vec<Value>>>()?;
+            Value::from_scalars(results)
+        });
+        self.register_chunk_safe(
+            name.to_string(),
+            Box::new(|_: &Value| true),
+            dependencies.iter().map(ToString::to_string).collect(),
+            derived_func,
+        )
+    }
+
+    /// Register derived fields computed together by one function.
+    ///
+    /// `func` receives the dependency columns, as for [`Registry::register`], and
+    /// returns one column per name in `names`, in order. Every output is a derived
+    /// field of its own, but getting any of them from a bulk computes all of them
+    /// in one call and caches them together, so the work is not repeated for the
+    /// others. The fields accept any value; their validators are not consulted
+    /// for derived results.
+    ///
+    /// # Arguments
+    ///
+    /// * `names` - The names of the outputs (must be valid, distinct and unused)
+    /// * `dependencies` - The names of the fields the outputs depend on
+    /// * `func` - The computation of every output
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if `names` is empty, repeats a name or holds an invalid name
+    /// - [`SoAKitError::FieldAlreadyExists`] if a field or an alias is already named like an output
+    /// - [`SoAKitError::DerivedFieldNoDeps`] if `dependencies` is empty
+    /// - [`SoAKitError::DependencyCycle`] if an output would depend on itself
+    ///
+    /// Nothing is registered if any check fails. Computing the outputs fails with
+    /// [`SoAKitError::InvalidArgument`] if `func` returns the wrong number of columns.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, SoAKitError, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// registry.register_float("r").unwrap();
+    /// registry.register_float("theta").unwrap();
+    /// registry
+    ///     .register_derived_multi(&["x", "y"], &["r", "theta"], |args: &[Value]| match args {
+    ///         [Value::VectorFloat(r), Value::VectorFloat(theta)] => {
+    ///             let x = r.iter().zip(theta).map(|(r, t)| r * t.cos()).collect();
+    ///             let y = r.iter().zip(theta).map(|(r, t)| r * t.sin()).collect();
+    ///             Ok(vec![Value::VectorFloat(x), Value::VectorFloat(y)])
+    ///         }
+    ///         _ => Err(SoAKitError::InvalidArgument("expected floats".to_string())),
+    ///     })
+    ///     .unwrap();
+    ///
+    /// let bulk = Bulk::new(1).unwrap();
+    /// let bulk = bulk.set(&registry, "r", vec![Value::ScalarFloat(2.0)]).unwrap();
+    /// let bulk = bulk.set(&registry, "theta", vec![Value::ScalarFloat(0.0)]).unwrap();
+    /// assert_eq!(bulk.get(&registry, "x").unwrap(), Value::VectorFloat(vec![2.0]));
+    /// assert_eq!(bulk.get(&registry, "y").unwrap(), Value::VectorFloat(vec![0.0]));
+    /// ```
+    pub fn register_derived_multi<F>(
+        &mut self,
+        names: &[&str],
+        dependencies: &[&str],
+        func: F,
+    ) -> Result<()>
+    where
+        F: Fn(&[Value]) -> Result<Vec<Value>> + Send + Sync + 'static,
+    {
+        if names.is_empty() {
+            return Err(SoAKitError::InvalidArgument(
+                "Derived function must have at least one output".to_string(),
+            ));
+        }
+        for (i, name) in names.iter().enumerate() {
+            if !is_valid_field_name(name) {
+                return Err(SoAKitError::InvalidArgument(format!(
+                    "Invalid field name: {}",
+                    name
+                )));
+            }
+            if names.iter().take(i).any(|earlier| earlier == name) {
+                return Err(SoAKitError::InvalidArgument(format!(
+                    "Output {} is listed twice",
+                    name
+                )));
+            }
+            if self.name_taken(name) {
+                return Err(SoAKitError::FieldAlreadyExists(name.to_string()));
+            }
+        }
+        let dependencies: Vec<String> = dependencies.iter().map(ToString::to_string).collect();
+        if dependencies.is_empty() {
+            return Err(SoAKitError::DerivedFieldNoDeps(names.join(", ")));
+        }
+        let cycle = find_cycle(names.iter().copied(), &|field: &str| {
+            if names.contains(&field) {
+                dependencies.clone()
+            } else {
+                self.dependencies_of(field)
+            }
+        });
+        if let Some(cycle) = cycle {
+            return Err(SoAKitError::DependencyCycle(cycle));
+        }
+
+        let multi_output = MultiOutput {
+            names: names.iter().map(ToString::to_string).collect(),
+            func: Arc::new(func),
+        };
+        for (i, name) in names.iter().enumerate() {
+            let shared = multi_output.clone();
+            let derived_func: DerivedFunc = Box::new(move |args: &[Value]| {
+                shared.compute(args)?.into_iter().nth(i).ok_or_else(|| {
+                    SoAKitError::InvalidArgument("Derived function missing output".to_string())
+                })
+            });
+            let mut metadata = FieldMetadata::new_derived(
+                Box::new(|_: &Value| true),
+                dependencies.clone(),
+                derived_func,
+            )?;
+            metadata.multi_output = Some(multi_output.clone());
+            metadata.reads_virtual = self.any_reads_virtual(&metadata.dependencies);
+            let _ = self.fields.insert(name.to_string(), Arc::new(metadata));
+        }
+        if self.any_reads_virtual(&dependencies) {
+            self.refresh_reads_virtual();
+        }
+        Ok(())
+    }
+
+    /// Register a derived field whose function also receives the bulk's context.
+    ///
+    /// `func` gets a [`DerivedContext`] with the element IDs, the element count
+    /// and the chunk offsets of the bulk, next to the dependency columns, so
+    /// positional computations such as row numbers, ranks or lags can be derived
+    /// fields. The field is computed from whole columns, never chunk by chunk,
+    /// and accepts any value; its validator is not consulted for derived results.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the field (must be valid and unique)
+    /// * `dependencies` - The names of the fields this field depends on
+    /// * `func` - The computation from the context and the dependency columns
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Registry::register`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, DerivedContext, Registry, SoAKitError, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// registry.register_int("price").unwrap();
+    /// // The previous element's price, missing for the first element
+    /// let lag = |ctx: &DerivedContext<'_>, args: &[Value]| {
+    ///     let mut lagged = vec![Value::Null];
+    ///     lagged.extend((1..ctx.count).map(|i| args[0].get_element(i - 1).unwrap()));
+    ///     Value::from_scalars(lagged)
+    /// };
+    /// registry.register_derived_with_context("prev_price", &["price"], lag).unwrap();
+    ///
+    /// let bulk = Bulk::new(3).unwrap();
+    /// let prices = vec![Value::ScalarInt(5), Value::ScalarInt(7), Value::ScalarInt(6)];
+    /// let bulk = bulk.set(&registry, "price", prices).unwrap();
+    /// let prev_price = bulk.get(&registry, "prev_price").unwrap();
+    /// assert_eq!(prev_price.get_element(0).unwrap(), Value::Null);
+    /// assert_eq!(prev_price.get_element(2).unwrap(), Value::ScalarInt(7));
+    /// ```
+    pub fn register_derived_with_context<F>(
+        &mut self,
+        name: &str,
+        dependencies: &[&str],
+        func: F,
+    ) -> Result<()>
+    where
+        F: Fn(&DerivedContext<'_>, &[Value]) -> Result<Value> + Send + Sync + 'static,
+    {
+        let context_func: Arc<ContextDerivedFunc> = Arc::new(func);
+        let standalone = Arc::clone(&context_func);
+        let derived_func: DerivedFunc = Box::new(move |args: &[Value]| {
+            let count = args.first().map_or(0, Value::len);
+            let ids: Vec<usize> = (0..count).collect();
+            let context = DerivedContext {
+                ids: &ids,
+                count,
+                chunk_offsets: &[0],
+            };
+            standalone(&context, args)
+        });
+        self.register(
+            name.to_string(),
+            Box::new(|_: &Value| true),
+            true,
+            dependencies.iter().map(ToString::to_string).collect(),
+            Some(derived_func),
+        )?;
+        if let Some(metadata) = self.fields.get_mut(name) {
+            Arc::make_mut(metadata).context_func = Some(context_func);
+        }
+        Ok(())
+    }
+
+    /// Register a virtual field, whose values come from outside the bulk.
+    ///
+    /// The resolver is called with the [`DerivedContext`] of the bulk on every
+    /// [`Bulk::get`](crate::Bulk::get) of the field, typically to look each
+    /// element's id up in external state such as a table of names. Its values
+    /// are never cached, since the bulk cannot tell when that state changes, and
+    /// they must have one element per element of the bulk.
+    ///
+    /// A virtual field stores no data and has no version: bulk updates refuse
+    /// it, and [`Meta::versions`](crate::bulk::Meta::versions) has no entry for
+    /// it. Derived fields may depend on virtual fields; they are then
+    /// recomputed on every access instead of being cached.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the field
+    /// * `resolver` - The function resolving the field's values
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Registry::register`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, DerivedContext, Registry, Value};
+    /// use std::collections::HashMap;
+    /// use std::sync::{Arc, RwLock};
+    ///
+    /// let names = Arc::new(RwLock::new(HashMap::from([(0, "ann"), (1, "bob")])));
+    /// let table = Arc::clone(&names);
+    /// let mut registry = Registry::new();
+    /// registry
+    ///     .register_virtual("name", move |ctx: &DerivedContext<'_>| {
+    ///         let table = table.read().unwrap();
+    ///         let names = ctx.ids.iter().map(|id| table.get(id).copied().unwrap_or(""));
+    ///         Ok(Value::VectorString(names.map(str::to_string).collect()))
+    ///     })
+    ///     .unwrap();
+    ///
+    /// let bulk = Bulk::new(2).unwrap();
+    /// let first = |bulk: &Bulk| bulk.get(&registry, "name").unwrap().get_element(0).unwrap();
+    /// assert_eq!(first(&bulk), Value::ScalarString("ann".to_string()));
+    ///
+    /// names.write().unwrap().insert(0, "amy");
+    /// assert_eq!(first(&bulk), Value::ScalarString("amy".to_string()));
+    /// assert!(bulk.set(&registry, "name", vec![Value::Null, Value::Null]).is_err());
+    /// ```
+    pub fn register_virtual<F>(&mut self, name: &str, resolver: F) -> Result<()>
+    where
+        F: Fn(&DerivedContext<'_>) -> Result<Value> + Send + Sync + 'static,
+    {
+        self.register(
+            name.to_string(),
+            Box::new(|_: &Value| true),
+            false,
+            vec![],
+            None,
+        )?;
+        if let Some(metadata) = self.fields.get_mut(name) {
+            Arc::make_mut(metadata).resolver = Some(Arc::new(resolver));
+        }
+        // Derived fields may have been registered before the field they read
+        self.refresh_reads_virtual();
+        Ok(())
+    }
+
+    /// Check whether any of `dependencies` is virtual or reads a virtual field,
+    /// trusting the [`FieldMetadata::reads_virtual`] flags of registered fields.
+    fn any_reads_virtual(&self, dependencies: &[String]) -> bool {
+        dependencies.iter().any(|dependency| {
+            self.field(dependency)
+                .is_some_and(|metadata| metadata.is_virtual() || metadata.reads_virtual)
+        })
+    }
+
+    /// Recompute [`FieldMetadata::reads_virtual`] for every field of this layer
+    /// from the full dependency closures, after fields were added or renamed.
+    fn refresh_reads_virtual(&mut self) {
+        let updates: Vec<(String, bool)> = self
+            .fields
+            .iter()
+            .filter(|(_, metadata)| metadata.is_derived)
+            .filter_map(|(name, metadata)| {
+                let reads = self.dependencies_closure(name).iter().any(|dependency| {
+                    self.field(dependency)
+                        .is_some_and(FieldMetadata::is_virtual)
+                });
+                (reads != metadata.reads_virtual).then(|| (name.clone(), reads))
+            })
+            .collect();
+        for (name, reads) in updates {
+            if let Some(metadata) = self.fields.get_mut(&name) {
+                // Only unshare metadata whose flag actually changes
+                Arc::make_mut(metadata).reads_virtual = reads;
+            }
+        }
+    }
+
+    /// Register a stored field holding scalars of a Rust type.
+    ///
+    /// The field's validator accepts exactly the scalars of the matching
+    /// [`FieldType`], which is recorded in the field's metadata.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the field
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Registry::register`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{FieldType, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// registry.register_typed::<i64>("age").unwrap();
+    /// registry.register_float("height").unwrap();
+    ///
+    /// assert!(registry.validate("age", &Value::ScalarInt(30)));
+    /// assert!(!registry.validate("age", &Value::ScalarFloat(30.0)));
+    /// assert_eq!(registry.get_metadata("height").unwrap().field_type, Some(FieldType::Float));
+    /// ```
+    pub fn register_typed<T: ScalarType>(&mut self, name: &str) -> Result<()> {
+        self.register_with_type(name, T::FIELD_TYPE)
+    }
+
+    /// Register a stored field of a given [`FieldType`].
+    ///
+    /// This is [`Registry::register_typed`] with the type chosen at run time.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Registry::register`].
+    pub fn register_with_type(&mut self, name: &str, field_type: FieldType) -> Result<()> {
+        self.register(
+            name.to_string(),
+            field_type.validator(),
+            false,
+            vec![],
+            None,
+        )?;
+        self.set_field_type(name, field_type);
+        Ok(())
+    }
+
+    /// Register a stored field holding `ScalarInt` values.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Registry::register`].
+    pub fn register_int(&mut self, name: &str) -> Result<()> {
+        self.register_typed::<i64>(name)
+    }
+
+    /// Register a stored field holding `ScalarFloat` values.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Registry::register`].
+    pub fn register_float(&mut self, name: &str) -> Result<()> {
+        self.register_typed::<f64>(name)
+    }
+
+    /// Register a stored field holding `ScalarBool` values.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Registry::register`].
+    pub fn register_bool(&mut self, name: &str) -> Result<()> {
+        self.register_typed::<bool>(name)
+    }
+
+    /// Register a stored field holding `ScalarString` values.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Registry::register`].
+    pub fn register_string(&mut self, name: &str) -> Result<()> {
+        self.register_typed::<String>(name)
+    }
+
+    /// Add a constraint to a stored field.
+    ///
+    /// The constraint is checked by [`Registry::validate`] and on every value
+    /// stored by bulk updates; values already stored in bulks are not rechecked
+    /// (use [`Bulk::validate_all`](crate::Bulk::validate_all) for that).
+    ///
+    /// # Arguments
+    ///
+    /// * `field` - The name of the field
+    /// * `constraint` - The constraint to add
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field is not registered
+    /// - [`SoAKitError::InvalidArgument`] if the field is derived
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Constraint, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// registry.register_string("email").unwrap();
+    /// registry.add_constraint("email", Constraint::regex("^[^@]+@[^@]+$").unwrap()).unwrap();
+    ///
+    /// assert!(registry.validate("email", &Value::ScalarString("a@b".to_string())));
+    /// assert!(!registry.validate("email", &Value::ScalarString("ab".to_string())));
+    /// ```
+    pub fn add_constraint(&mut self, field: &str, constraint: Constraint) -> Result<()> {
+        let metadata = self.own_field_mut(field)?;
+        if metadata.is_derived {
+            return Err(SoAKitError::InvalidArgument(format!(
+                "Cannot constrain derived field: {}",
+                field
+            )));
+        }
+        metadata.constraints.push(constraint);
+        Ok(())
+    }
+
+    /// Declare the shape of a field's element values.
+    ///
+    /// The shape is as returned by [`Value::shape`]: `[]` for scalars, `[n]` for
+    /// vectors of `n` elements and `[rows, columns]` for matrices. Bulk updates
+    /// then reject element values of any other shape with
+    /// [`SoAKitError::ShapeMismatch`], before running the validator, and
+    /// [`Registry::validate`] refuses them. `Null` elements are always accepted.
+    /// Computed values of derived and virtual fields are checked as by
+    /// [`Registry::set_validate_output`].
+    ///
+    /// # Arguments
+    ///
+    /// * `field` - The name of the field
+    /// * `shape` - The shape of every element value
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field is not registered
+    /// - [`SoAKitError::InvalidArgument`] if the field belongs to a parent registry
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, SoAKitError, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let any = Box::new(|_: &Value| true);
+    /// registry.register("position".to_string(), any, false, vec![], None).unwrap();
+    /// registry.set_shape("position", &[3]).unwrap();
+    ///
+    /// let bulk = Bulk::new(1).unwrap();
+    /// let point = Value::VectorFloat(vec![1.0, 2.0, 3.0]);
+    /// assert!(bulk.set(&registry, "position", vec![point]).is_ok());
+    /// assert!(matches!(
+    ///     bulk.set(&registry, "position", vec![Value::VectorFloat(vec![1.0, 2.0])]),
+    ///     Err(SoAKitError::ShapeMismatch { .. })
+    /// ));
+    /// ```
+    pub fn set_shape(&mut self, field: &str, shape: &[usize]) -> Result<()> {
+        self.own_field_mut(field)?.shape = Some(shape.to_vec());
+        Ok(())
+    }
+
+    /// Remove the declared shape of a field, accepting values of any shape again.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Registry::set_shape`].
+    pub fn clear_shape(&mut self, field: &str) -> Result<()> {
+        self.own_field_mut(field)?.shape = None;
+        Ok(())
+    }
+
+    /// Check an element value against the declared shape of a field.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::ShapeMismatch`] if the value has another shape
+    pub(crate) fn check_shape(&self, field: &str, value: &Value) -> Result<()> {
+        match self.field(field) {
+            Some(metadata) if !metadata.shape_matches(value) => Err(SoAKitError::ShapeMismatch {
+                field: field.to_string(),
+                expected: metadata.shape.clone().unwrap_or_default(),
+                actual: value.shape(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check a value against a field, as by [`Registry::validate`].
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::ShapeMismatch`] if the value does not have the field's
+    ///   declared shape
+    /// - [`SoAKitError::ValidationFailed`] if the value fails validation, naming
+    ///   the expected and actual types if the field has a [`FieldType`] the value
+    ///   is not of
+    pub(crate) fn check_value(&self, field: &str, value: &Value) -> Result<()> {
+        self.check_shape(field, value)?;
+        if self.validate(field, value) {
+            return Ok(());
+        }
+        let mismatch = self
+            .field(field)
+            .and_then(|metadata| metadata.field_type)
+            .and_then(|field_type| field_type.mismatch(value));
+        let message = format!("Value validation failed for field: {}", field);
+        Err(SoAKitError::ValidationFailed(match mismatch {
+            Some(mismatch) => format!("{} ({})", message, mismatch),
+            None => message,
+        }))
+    }
+
+    /// Set the default value of a stored field.
+    ///
+    /// The default is used where no value is given for the field: for elements
+    /// appended by [`Bulk::extend_rows`](crate::Bulk::extend_rows) and
+    /// [`Bulk::resize`](crate::Bulk::resize), for records that omit the field in
+    /// [`Bulk::from_records_json`](crate::Bulk::from_records_json), and for missing
+    /// elements replaced by [`Bulk::fill_missing`](crate::Bulk::fill_missing).
+    ///
+    /// # Arguments
+    ///
+    /// * `field` - The name of the field
+    /// * `value` - The default value, which must pass the field's validation
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field is not registered
+    /// - [`SoAKitError::InvalidArgument`] if the field is derived or the value is `Null`
+    /// - [`SoAKitError::ValidationFailed`] if the value fails validation
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// registry.register_int("age").unwrap();
+    /// registry.register_string("name").unwrap();
+    /// registry.set_default("age", Value::ScalarInt(18)).unwrap();
+    ///
+    /// let bulk = Bulk::from_records_json(r#"[{"name": "Ann", "age": 30}, {"name": "Bob"}]"#, &registry).unwrap();
+    /// assert_eq!(bulk.get(&registry, "age").unwrap(), Value::VectorInt(vec![30, 18]));
+    /// ```
+    pub fn set_default(&mut self, field: &str, value: Value) -> Result<()> {
+        if value.is_null() {
+            return Err(SoAKitError::InvalidArgument(format!(
+                "Default of field {} cannot be Null",
+                field
+            )));
+        }
+        if !self.validate(field, &value) {
+            return Err(match self.field(field) {
+                None => SoAKitError::FieldNotFound(field.to_string()),
+                Some(_) => SoAKitError::ValidationFailed(format!(
+                    "Default value validation failed for field: {}",
+                    field
+                )),
+            });
+        }
+        let metadata = self.own_field_mut(field)?;
+        if metadata.is_derived {
+            return Err(SoAKitError::InvalidArgument(format!(
+                "Cannot set a default for derived field: {}",
+                field
+            )));
+        }
+        metadata.default = Some(value);
+        Ok(())
+    }
+
+    /// Mark a stored field as required or optional.
+    ///
+    /// Fields are optional when registered. A required field must hold data in
+    /// every bulk, as checked by [`Bulk::validate_complete`](crate::Bulk::validate_complete).
+    ///
+    /// # Arguments
+    ///
+    /// * `field` - The name of the field
+    /// * `required` - Whether the field is required
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field is not registered
+    /// - [`SoAKitError::InvalidArgument`] if the field is derived
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// registry.register_int("id_number").unwrap();
+    /// registry.register_string("nickname").unwrap();
+    /// registry.set_required("id_number", true).unwrap();
+    ///
+    /// let bulk = Bulk::new(1).unwrap();
+    /// let bulk = bulk.set(&registry, "nickname", vec![Value::from("x".to_string())]).unwrap();
+    /// assert_eq!(bulk.validate_complete(&registry), vec!["id_number"]);
+    /// ```
+    pub fn set_required(&mut self, field: &str, required: bool) -> Result<()> {
+        let metadata = self.own_field_mut(field)?;
+        if metadata.is_derived {
+            return Err(SoAKitError::InvalidArgument(format!(
+                "Derived field {} cannot be required",
+                field
+            )));
+        }
+        metadata.required = required;
+        Ok(())
+    }
+
+    /// Turn checks of a derived field's computed values on or off.
+    ///
+    /// Checks are on when a field is registered: every value computed by
+    /// [`Bulk::get`](crate::Bulk::get) must have one element per bulk element and
+    /// pass the field's validator, or the get fails with
+    /// [`SoAKitError::DerivedOutputInvalid`]. Turn them off for derived functions
+    /// that are known to be correct and whose outputs are too large to check.
+    ///
+    /// # Arguments
+    ///
+    /// * `field` - The name of the derived field
+    /// * `enabled` - Whether computed values are checked
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field is not registered
+    /// - [`SoAKitError::InvalidArgument`] if the field is not derived
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, SoAKitError, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// registry.register_int("n").unwrap();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// let first_only = Box::new(|args: &[Value]| args[0].get_element(0));
+    /// registry
+    ///     .register("first".to_string(), validator, true, vec!["n".to_string()], Some(first_only))
+    ///     .unwrap();
+    ///
+    /// let bulk = Bulk::new(2).unwrap();
+    /// let bulk = bulk.set(&registry, "n", vec![Value::ScalarInt(1), Value::ScalarInt(2)]).unwrap();
+    /// assert!(matches!(
+    ///     bulk.get(&registry, "first"),
+    ///     Err(SoAKitError::DerivedOutputInvalid { .. })
+    /// ));
+    ///
+    /// registry.set_validate_output("first", false).unwrap();
+    /// assert_eq!(bulk.get(&registry, "first").unwrap(), Value::ScalarInt(1));
+    /// ```
+    pub fn set_validate_output(&mut self, field: &str, enabled: bool) -> Result<()> {
+        let metadata = self.own_field_mut(field)?;
+        if !metadata.is_derived {
+            return Err(SoAKitError::InvalidArgument(format!(
+                "Field {} is not derived",
+                field
+            )));
+        }
+        metadata.validate_output = enabled;
+        Ok(())
+    }
+
+    /// Check whether a field is registered and required.
+    pub fn is_required(&self, field: &str) -> bool {
+        self.field(field).is_some_and(|metadata| metadata.required)
+    }
+
+    /// List the required fields, in sorted order.
+    pub fn required_fields(&self) -> Vec<String> {
+        self.all_fields()
+            .into_iter()
+            .filter(|(_, metadata)| metadata.required)
+            .map(|(name, _)| name.to_string())
+            .collect()
+    }
+
+    /// Mark a field as deprecated.
+    ///
+    /// Deprecated fields keep working; the hint tells users what to use instead.
+    /// When a handler is registered with [`Registry::on_deprecated_access`], it
+    /// runs on every [`Bulk::get`](crate::Bulk::get) and
+    /// [`Bulk::set`](crate::Bulk::set) of the field. [`Schema`](crate::Schema)
+    /// exports record the hint.
+    ///
+    /// # Arguments
+    ///
+    /// * `field` - The name of the field
+    /// * `hint` - What to use instead, e.g. `"use date_of_birth"`
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field is not registered
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let mut registry = Registry::new();
+    /// registry.register_int("age").unwrap();
+    /// registry.deprecate("age", "use date_of_birth").unwrap();
+    ///
+    /// let warnings = Arc::new(Mutex::new(Vec::new()));
+    /// let log = Arc::clone(&warnings);
+    /// registry.on_deprecated_access(move |field, hint| {
+    ///     log.lock().unwrap().push(format!("{} is deprecated: {}", field, hint));
+    /// });
+    ///
+    /// let bulk = Bulk::new(1).unwrap().set(&registry, "age", vec![Value::ScalarInt(30)]).unwrap();
+    /// assert_eq!(bulk.get(&registry, "age").unwrap(), Value::VectorInt(vec![30]));
+    /// assert_eq!(warnings.lock().unwrap().len(), 2);
+    /// assert_eq!(warnings.lock().unwrap()[0], "age is deprecated: use date_of_birth");
+    /// ```
+    pub fn deprecate(&mut self, field: &str, hint: &str) -> Result<()> {
+        let metadata = self.own_field_mut(field)?;
+        metadata.deprecated = Some(hint.to_string());
+        Ok(())
+    }
+
+    /// Get the replacement hint of a deprecated field.
+    ///
+    /// # Returns
+    ///
+    /// The hint, or `None` if the field is not registered or not deprecated.
+    pub fn deprecation(&self, field: &str) -> Option<&str> {
+        self.field(field)?.deprecated.as_deref()
+    }
+
+    /// Register a callback run when a deprecated field is accessed, replacing any
+    /// previous one.
+    ///
+    /// The callback receives the field name and its replacement hint. It runs on
+    /// [`Bulk::get`](crate::Bulk::get) and on the `set` family of
+    /// [`Bulk`](crate::Bulk) methods, after aliases are resolved; fields read to
+    /// compute a derived field do not trigger it.
+    pub fn on_deprecated_access<F>(&mut self, handler: F)
+    where
+        F: Fn(&str, &str) + Send + Sync + 'static,
+    {
+        self.deprecation_handler = Some(Arc::new(handler));
+    }
+
+    /// Run the deprecation handler if `field` is deprecated.
+    pub(crate) fn notify_access(&self, field: &str) {
+        if let Some(handler) = self.deprecation_handler()
+            && let Some(hint) = self.deprecation(field)
+        {
+            handler(field, hint);
+        }
+    }
+
+    /// Get the deprecation handler of this registry or its nearest ancestor.
+    fn deprecation_handler(&self) -> Option<&DeprecationHandler> {
+        self.deprecation_handler
+            .as_deref()
+            .or_else(|| self.parent.as_ref()?.deprecation_handler())
+    }
+
+    /// Attach a tag to a field, replacing any tag with the same key.
+    ///
+    /// Tags are free-form key/value metadata for applications, such as the units
+    /// of a field, a description, its source or a PII flag. SoAKit stores them
+    /// but does not interpret them.
+    ///
+    /// # Arguments
+    ///
+    /// * `field` - The name of the field
+    /// * `key` - The tag key
+    /// * `value` - The tag value
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field is not registered
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Registry;
+    ///
+    /// let mut registry = Registry::new();
+    /// registry.register_float("height").unwrap();
+    /// registry.register_string("email").unwrap();
+    /// registry.set_tag("height", "units", "m").unwrap();
+    /// registry.set_tag("email", "pii", "true").unwrap();
+    ///
+    /// assert_eq!(registry.tag("height", "units"), Some("m"));
+    /// assert_eq!(registry.fields_with_tag("pii"), vec!["email"]);
+    /// ```
+    pub fn set_tag(&mut self, field: &str, key: &str, value: &str) -> Result<()> {
+        let metadata = self.own_field_mut(field)?;
+        let _ = metadata.tags.insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    /// Remove a tag from a field.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Some(value))` with the removed tag value, or `Ok(None)` if the
+    /// field had no tag with that key.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field is not registered
+    pub fn remove_tag(&mut self, field: &str, key: &str) -> Result<Option<String>> {
+        let metadata = self.own_field_mut(field)?;
+        Ok(metadata.tags.remove(key))
+    }
+
+    /// Get the value of a field's tag.
+    ///
+    /// # Returns
+    ///
+    /// The tag value, or `None` if the field is not registered or has no tag with
+    /// that key.
+    pub fn tag(&self, field: &str, key: &str) -> Option<&str> {
+        self.field(field)?.tags.get(key).map(String::as_str)
+    }
+
+    /// List the fields that have a tag with the given key, in sorted order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Registry;
+    ///
+    /// let mut registry = Registry::new();
+    /// registry.register_string("email").unwrap();
+    /// registry.register_int("age").unwrap();
+    /// registry.set_tag("email", "pii", "true").unwrap();
+    ///
+    /// assert_eq!(registry.fields_with_tag("pii"), vec!["email"]);
+    /// ```
+    pub fn fields_with_tag(&self, key: &str) -> Vec<String> {
+        self.iter()
+            .filter(|(_, metadata)| metadata.tags.contains_key(key))
+            .map(|(name, _)| name.to_string())
+            .collect()
+    }
+
+    /// List the fields whose tag `key` equals `value`, in sorted order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Registry;
+    ///
+    /// let mut registry = Registry::new();
+    /// for field in ["height", "width", "count"] {
+    ///     registry.register_float(field).unwrap();
+    /// }
+    /// registry.set_tag("height", "units", "m").unwrap();
+    /// registry.set_tag("width", "units", "m").unwrap();
+    /// registry.set_tag("count", "units", "1").unwrap();
+    ///
+    /// assert_eq!(registry.fields_with_tag_value("units", "m"), vec!["height", "width"]);
+    /// ```
+    pub fn fields_with_tag_value(&self, key: &str, value: &str) -> Vec<String> {
+        self.all_fields()
+            .into_iter()
+            .filter(|(_, metadata)| metadata.tags.get(key).is_some_and(|v| v == value))
+            .map(|(name, _)| name.to_string())
+            .collect()
+    }
+
+    /// Check values against the constraints of a field.
+    ///
+    /// Unlike [`Registry::validate`], this does not run the field's validator,
+    /// which bulk updates apply to the first value only.
+    ///
+    /// # Arguments
+    ///
+    /// * `field` - The name of the field
+    /// * `values` - The values to check
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::ValidationFailed`] naming the first value that violates a
+    ///   constraint, and the constraint
+    pub fn check_constraints(&self, field: &str, values: &[Value]) -> Result<()> {
+        let Some(metadata) = self.field(field) else {
+            return Ok(());
+        };
+        for constraint in &metadata.constraints {
+            if let Some(idx) = values.iter().position(|value| !constraint.check(value)) {
+                return Err(SoAKitError::ValidationFailed(format!(
+                    "Value at index {} of field {} is not {}",
+                    idx, field, constraint
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check whether a field has any constraints.
+    pub(crate) fn has_constraints(&self, field: &str) -> bool {
+        self.field(field)
+            .is_some_and(|metadata| !metadata.constraints.is_empty())
+    }
+
+    /// Record the declared type of a registered field.
+    pub(crate) fn set_field_type(&mut self, name: &str, field_type: FieldType) {
+        if let Some(metadata) = self.fields.get_mut(name) {
+            Arc::make_mut(metadata).field_type = Some(field_type);
+        }
+    }
+
+    /// Rename a field.
+    ///
+    /// The field keeps its metadata under the new name, and the dependency lists
+    /// of derived fields that reference the old name are rewritten to the new name,
+    /// as are aliases of the field and field groups listing it. Use
+    /// [`Bulk::rename_field`](crate::Bulk::rename_field) to rename the field's data in existing bulks.
+    ///
+    /// # Arguments
+    ///
+    /// * `old` - The current name of the field
+    /// * `new` - The new name of the field (must be valid and unused)
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if successful.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if the new name is invalid or `old` belongs
+    ///   to a parent registry
+    /// - [`SoAKitError::FieldNotFound`] if no field is named `old`
+    /// - [`SoAKitError::FieldAlreadyExists`] if a field or an alias is already named `new`
+    /// - [`SoAKitError::DependencyCycle`] if dependencies naming `new` would form a cycle
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::meta::Registry;
+    /// use soakit::Value;
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// registry.rename_field("age", "years").unwrap();
+    /// assert!(registry.has_field("years"));
+    /// assert!(!registry.has_field("age"));
+    /// ```
+    pub fn rename_field(&mut self, old: &str, new: &str) -> Result<()> {
+        if !is_valid_field_name(new) {
+            return Err(SoAKitError::InvalidArgument(format!(
+                "Invalid field name: {}",
+                new
+            )));
+        }
+        if self.name_taken(new) {
+            return Err(SoAKitError::FieldAlreadyExists(new.to_string()));
+        }
+        let _ = self.own_field_mut(old)?;
+        // Dependencies already naming `new` would resolve to the renamed field
+        let cycle = find_cycle([new], &|field: &str| {
+            let source = if field == new { old } else { field };
+            let rename = |dep: &String| {
+                if dep == old {
+                    new.to_string()
+                } else {
+                    dep.clone()
+                }
+            };
+            self.dependencies_of(source).iter().map(rename).collect()
+        });
+        if let Some(cycle) = cycle {
+            return Err(SoAKitError::DependencyCycle(cycle));
+        }
+        let metadata = self
+            .fields
+            .remove(old)
+            .ok_or_else(|| SoAKitError::FieldNotFound(old.to_string()))?;
+        let _ = self.fields.insert(new.to_string(), metadata);
+
+        for metadata in self.fields.values_mut() {
+            let names_old = |names: &[String]| names.iter().any(|name| name == old);
+            let outputs = metadata
+                .multi_output
+                .as_ref()
+                .map(|multi| multi.names.as_slice());
+            if !names_old(&metadata.dependencies) && !outputs.is_some_and(names_old) {
+                // Leave metadata shared with clones of the registry untouched
+                continue;
+            }
+            let metadata = Arc::make_mut(metadata);
+            let outputs = metadata.multi_output.as_mut().map(|multi| &mut multi.names);
+            for name in metadata
+                .dependencies
+                .iter_mut()
+                .chain(outputs.into_iter().flatten())
+            {
+                if name == old {
+                    *name = new.to_string();
+                }
+            }
+        }
+        for target in self
+            .aliases
+            .values_mut()
+            .chain(self.groups.values_mut().flatten())
+        {
+            if target == old {
+                *target = new.to_string();
+            }
+        }
+        // Dependencies already naming `new` now reach the renamed field
+        self.refresh_reads_virtual();
+        Ok(())
+    }
+
+    /// Register an alias for a field.
+    ///
+    /// An alias is a second name for a field. [`Bulk::get`](crate::Bulk::get),
+    /// [`Bulk::set`](crate::Bulk::set) and the record importers (such as
+    /// [`Bulk::from_records_json`](crate::Bulk::from_records_json)) resolve it to
+    /// the field, so call sites and data files using an old name keep working
+    /// after [`Registry::rename_field`]. Other methods take only field names; use
+    /// [`Registry::resolve`] to look up the field behind a name.
+    ///
+    /// # Arguments
+    ///
+    /// * `alias` - The alias (must be a valid field name and unused)
+    /// * `field` - The field, or another alias of it
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if successful.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if the alias is not a valid field name
+    /// - [`SoAKitError::FieldNotFound`] if `field` is neither a field nor an alias
+    /// - [`SoAKitError::FieldAlreadyExists`] if a field or an alias is already named `alias`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// registry.register_int("date_of_birth").unwrap();
+    /// registry.add_alias("dob", "date_of_birth").unwrap();
+    ///
+    /// let bulk = Bulk::new(1).unwrap();
+    /// let bulk = bulk.set(&registry, "dob", vec![Value::ScalarInt(19700101)]).unwrap();
+    /// assert_eq!(bulk.get(&registry, "date_of_birth").unwrap(), Value::VectorInt(vec![19700101]));
+    /// assert_eq!(registry.resolve("dob"), "date_of_birth");
+    /// ```
+    pub fn add_alias(&mut self, alias: &str, field: &str) -> Result<()> {
+        if !is_valid_field_name(alias) {
+            return Err(SoAKitError::InvalidArgument(format!(
+                "Invalid alias: {}",
+                alias
+            )));
+        }
+        if self.name_taken(alias) {
+            return Err(SoAKitError::FieldAlreadyExists(alias.to_string()));
+        }
+        let target = self.resolve(field).to_string();
+        if self.field(&target).is_none() {
+            return Err(SoAKitError::FieldNotFound(field.to_string()));
+        }
+        let _ = self.aliases.insert(alias.to_string(), target);
+        Ok(())
+    }
+
+    /// Remove an alias.
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if `alias` was an alias, `false` otherwise.
+    pub fn remove_alias(&mut self, alias: &str) -> bool {
+        self.aliases.remove(alias).is_some()
+    }
+
+    /// Resolve a name to a field name.
+    ///
+    /// # Returns
+    ///
+    /// The field `name` is an alias of, or `name` itself if it is not an alias.
+    pub fn resolve<'a>(&'a self, name: &'a str) -> &'a str {
+        self.alias_target(name).unwrap_or(name)
+    }
+
+    /// List the aliases of a field, in sorted order.
+    pub fn aliases_of(&self, field: &str) -> Vec<String> {
+        let mut aliases = self
+            .parent
+            .as_ref()
+            .map(|parent| parent.aliases_of(field))
+            .unwrap_or_default();
+        aliases.extend(
+            self.aliases
+                .iter()
+                .filter(|(_, target)| *target == field)
+                .map(|(alias, _)| alias.clone()),
+        );
+        aliases.sort();
+        aliases
+    }
+
+    /// Define a named group of fields.
+    ///
+    /// A group names related fields, such as the coordinates `x`, `y` and `z` of
+    /// a position, so that [`Bulk::get_group`](crate::Bulk::get_group),
+    /// [`Bulk::set_group`](crate::Bulk::set_group) and
+    /// [`Bulk::select_group`](crate::Bulk::select_group) handle them together.
+    /// Groups have names of their own, separate from field names. Defining a
+    /// group that already exists replaces it; a group of a parent registry is
+    /// hidden by a group of the same name in the child.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the group (must be a valid field name)
+    /// * `fields` - The fields of the group, in order; aliases are resolved
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if the name is invalid, or `fields` is
+    ///   empty or lists a field twice
+    /// - [`SoAKitError::FieldNotFound`] if a field is not registered
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Registry;
+    ///
+    /// let mut registry = Registry::new();
+    /// for field in ["x", "y", "z"] {
+    ///     registry.register_float(field).unwrap();
+    /// }
+    /// registry.define_group("position", &["x", "y", "z"]).unwrap();
+    ///
+    /// assert_eq!(registry.group("position").unwrap(), ["x", "y", "z"]);
+    /// assert!(registry.define_group("broken", &["x", "w"]).is_err());
+    /// ```
+    pub fn define_group(&mut self, name: &str, fields: &[&str]) -> Result<()> {
+        if !is_valid_field_name(name) {
+            return Err(SoAKitError::InvalidArgument(format!(
+                "Invalid group name: {}",
+                name
+            )));
+        }
+        if fields.is_empty() {
+            return Err(SoAKitError::InvalidArgument(format!(
+                "Group {} has no fields",
+                name
+            )));
+        }
+        let mut members: Vec<String> = Vec::with_capacity(fields.len());
+        for field in fields {
+            let target = self.resolve(field);
+            if self.field(target).is_none() {
+                return Err(SoAKitError::FieldNotFound(field.to_string()));
+            }
+            if members.iter().any(|member| member == target) {
+                return Err(SoAKitError::InvalidArgument(format!(
+                    "Group {} lists field {} twice",
+                    name, target
+                )));
+            }
+            members.push(target.to_string());
+        }
+        let _ = self.groups.insert(name.to_string(), members);
+        Ok(())
+    }
+
+    /// Remove a group of this registry. Its fields stay registered.
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the group was defined in this registry, `false` otherwise.
+    pub fn remove_group(&mut self, name: &str) -> bool {
+        self.groups.remove(name).is_some()
+    }
+
+    /// Get the fields of a group, in the order they were listed.
+    ///
+    /// # Returns
+    ///
+    /// The fields, or `None` if no group of this registry or its ancestors has
+    /// that name.
+    pub fn group(&self, name: &str) -> Option<&[String]> {
+        self.groups
+            .get(name)
+            .map(Vec::as_slice)
+            .or_else(|| self.parent.as_ref()?.group(name))
+    }
+
+    /// Get the fields of a group, or an error naming the missing group.
+    pub(crate) fn group_fields(&self, name: &str) -> Result<&[String]> {
+        self.group(name)
+            .ok_or_else(|| SoAKitError::InvalidArgument(format!("Unknown field group: {}", name)))
+    }
+
+    /// List the groups of this registry and its ancestors, in sorted order.
+    pub fn group_names(&self) -> Vec<String> {
+        let mut names = self
+            .parent
+            .as_ref()
+            .map(|parent| parent.group_names())
+            .unwrap_or_default();
+        names.extend(self.groups.keys().cloned());
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Move the fields, aliases and groups of another registry into this one.
+    ///
+    /// This composes field definitions from several sources, such as plugins that
+    /// each build a registry of their own. A name defined in both registries is
+    /// handled according to `policy`. Only the fields, aliases and groups defined in
+    /// `other` itself are merged, not those of its parent (see
+    /// [`Registry::with_parent`]). The deprecation handler of `other` is kept only
+    /// if this registry has none.
+    ///
+    /// Everything is checked before anything is merged, so on error this registry
+    /// is unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The registry to merge in
+    /// * `policy` - How to handle names defined in both registries
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldAlreadyExists`] if a name is defined in both registries
+    ///   and `policy` is [`ConflictPolicy::Error`], or a field and an alias share a
+    ///   name and `policy` is [`ConflictPolicy::Overwrite`]
+    /// - [`SoAKitError::InvalidArgument`] if [`ConflictPolicy::Overwrite`] would
+    ///   replace a definition of a parent registry
+    /// - [`SoAKitError::FieldNotFound`] if an alias or a group of `other` names a
+    ///   field of its parent that this registry does not have
+    /// - [`SoAKitError::DependencyCycle`] if the merged derived fields would form a cycle
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{ConflictPolicy, Registry, SoAKitError, Value};
+    ///
+    /// let mut core = Registry::new();
+    /// core.register_int("id_number").unwrap();
+    ///
+    /// let mut plugin = Registry::new();
+    /// plugin.register_string("id_number").unwrap();
+    /// plugin.register_float("score").unwrap();
+    ///
+    /// let mut strict = Registry::new();
+    /// strict.register_string("id_number").unwrap();
+    /// assert!(matches!(
+    ///     core.merge(strict, ConflictPolicy::Error),
+    ///     Err(SoAKitError::FieldAlreadyExists(_))
+    /// ));
+    ///
+    /// core.merge(plugin, ConflictPolicy::Skip).unwrap();
+    /// assert_eq!(core.list_fields(), vec!["id_number", "score"]);
+    /// assert!(core.validate("id_number", &Value::ScalarInt(1)));
+    /// ```
+    pub fn merge(&mut self, other: Registry, policy: ConflictPolicy) -> Result<()> {
+        let Registry {
+            fields,
+            aliases,
+            groups,
+            deprecation_handler,
+            ..
+        } = other;
+
+        let mut incoming = BTreeMap::new();
+        for (name, metadata) in fields {
+            if self.merge_conflict(&name, false, policy)? {
+                let _ = incoming.insert(name, metadata);
+            }
+        }
+        let mut incoming_aliases = BTreeMap::new();
+        for (alias, target) in aliases {
+            if !self.merge_conflict(&alias, true, policy)? {
+                continue;
+            }
+            if !incoming.contains_key(&target) && self.field(&target).is_none() {
+                return Err(SoAKitError::FieldNotFound(target));
+            }
+            let _ = incoming_aliases.insert(alias, target);
+        }
+        let mut incoming_groups = BTreeMap::new();
+        for (name, members) in groups {
+            if self.group(&name).is_some() {
+                match policy {
+                    ConflictPolicy::Error => return Err(SoAKitError::FieldAlreadyExists(name)),
+                    ConflictPolicy::Skip => continue,
+                    // Groups of a parent are hidden rather than replaced
+                    ConflictPolicy::Overwrite => {}
+                }
+            }
+            let missing = members
+                .iter()
+                .find(|field| !incoming.contains_key(*field) && self.field(field).is_none());
+            if let Some(field) = missing {
+                return Err(SoAKitError::FieldNotFound(field.clone()));
+            }
+            let _ = incoming_groups.insert(name, members);
+        }
+        let cycle = find_cycle(
+            incoming.keys().map(String::as_str),
+            &|field: &str| match incoming.get(field) {
+                Some(metadata) => metadata.dependencies.clone(),
+                None => self.dependencies_of(field),
+            },
+        );
+        if let Some(cycle) = cycle {
+            return Err(SoAKitError::DependencyCycle(cycle));
+        }
+
+        self.fields.extend(incoming);
+        self.aliases.extend(incoming_aliases);
+        self.groups.extend(incoming_groups);
+        self.refresh_reads_virtual();
+        if self.deprecation_handler.is_none() {
+            self.deprecation_handler = deprecation_handler;
+        }
+        Ok(())
+    }
+
+    /// Decide whether [`Registry::merge`] takes the definition of a field or an
+    /// alias named `name`.
+    fn merge_conflict(&self, name: &str, is_alias: bool, policy: ConflictPolicy) -> Result<bool> {
+        if !self.name_taken(name) {
+            return Ok(true);
+        }
+        let (own, same_kind) = if is_alias {
+            (
+                self.aliases.contains_key(name),
+                self.alias_target(name).is_some(),
+            )
+        } else {
+            (self.fields.contains_key(name), self.field(name).is_some())
+        };
+        match policy {
+            ConflictPolicy::Error => Err(SoAKitError::FieldAlreadyExists(name.to_string())),
+            ConflictPolicy::Skip => Ok(false),
+            ConflictPolicy::Overwrite if own => Ok(true),
+            ConflictPolicy::Overwrite if same_kind => Err(SoAKitError::InvalidArgument(format!(
+                "{} belongs to a parent registry",
+                name
+            ))),
+            // A field and an alias cannot replace each other
+            ConflictPolicy::Overwrite => Err(SoAKitError::FieldAlreadyExists(name.to_string())),
+        }
+    }
+
+    /// Check that no derived field depends on itself.
+    ///
+    /// [`Registry::register`] and [`Registry::rename_field`] already refuse to
+    /// create dependency cycles, so this only fails for registries assembled some
+    /// other way; it is cheap enough to run as a sanity check.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::DependencyCycle`] with the fields on the first cycle found
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Registry, SoAKitError, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let any = || Box::new(|_: &Value| true);
+    /// let copy = || Box::new(|args: &[Value]| Ok(args[0].clone()));
+    /// registry.register("a".to_string(), any(), true, vec!["b".to_string()], Some(copy())).unwrap();
+    /// assert!(registry.check_cycles().is_ok());
+    ///
+    /// let err = registry.register("b".to_string(), any(), true, vec!["a".to_string()], Some(copy()));
+    /// assert_eq!(
+    ///     err,
+    ///     Err(SoAKitError::DependencyCycle(vec!["b".to_string(), "a".to_string(), "b".to_string()]))
+    /// );
+    /// ```
+    pub fn check_cycles(&self) -> Result<()> {
+        match find_cycle(self.all_fields().into_keys(), &|field: &str| {
+            self.dependencies_of(field)
+        }) {
+            Some(cycle) => Err(SoAKitError::DependencyCycle(cycle)),
+            None => Ok(()),
+        }
+    }
+
+    /// Get the derived fields that depend directly on a field.
+    ///
+    /// # Arguments
+    ///
+    /// * `field` - The name of the field
+    ///
+    /// # Returns
+    ///
+    /// The names of the derived fields listing `field` among their dependencies,
+    /// in sorted order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// registry.register("a".to_string(), Box::new(|_: &Value| true), false, vec![], None).unwrap();
+    /// let copy = Box::new(|args: &[Value]| Ok(args[0].clone()));
+    /// let validator = Box::new(|_: &Value| true);
+    /// registry.register("b".to_string(), validator, true, vec!["a".to_string()], Some(copy)).unwrap();
+    ///
+    /// assert_eq!(registry.dependents_of("a"), vec!["b".to_string()]);
+    /// assert!(registry.dependents_of("b").is_empty());
+    /// ```
+    pub fn dependents_of(&self, field: &str) -> Vec<String> {
+        self.iter()
+            .filter(|(_, metadata)| {
+                metadata.is_derived && metadata.dependencies.iter().any(|dep| dep == field)
+            })
+            .map(|(name, _)| name.to_string())
+            .collect()
+    }
+
+    /// Check whether field `a` is needed to compute field `b`.
+    ///
+    /// # Returns
+    ///
+    /// `true` if `a` is among the transitive dependencies of `b`, as listed by
+    /// [`Registry::dependencies_closure`]. A field is not a dependency of itself.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let any = || Box::new(|_: &Value| true);
+    /// let copy = || Box::new(|args: &[Value]| Ok(args[0].clone()));
+    /// registry.register("a".to_string(), any(), false, vec![], None).unwrap();
+    /// registry.register("b".to_string(), any(), true, vec!["a".to_string()], Some(copy())).unwrap();
+    /// registry.register("c".to_string(), any(), true, vec!["b".to_string()], Some(copy())).unwrap();
+    ///
+    /// assert!(registry.is_dependency_of("a", "c"));
+    /// assert!(!registry.is_dependency_of("c", "a"));
+    /// ```
+    pub fn is_dependency_of(&self, a: &str, b: &str) -> bool {
+        self.dependencies_closure(b)
+            .iter()
+            .any(|dependency| dependency == a)
+    }
+
+    /// Get every field a field depends on, directly or through other derived fields.
+    ///
+    /// Dependencies that are not registered are included by name, since they are
+    /// still needed to compute the field.
+    ///
+    /// # Arguments
+    ///
+    /// * `field` - The name of the field
+    ///
+    /// # Returns
+    ///
+    /// The transitive dependencies of `field`, not including `field` itself, with
+    /// each field listed after its own dependencies. Stored and unregistered fields
+    /// have no dependencies.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let any = || Box::new(|_: &Value| true);
+    /// let copy = || Box::new(|args: &[Value]| Ok(args[0].clone()));
+    /// registry.register("a".to_string(), any(), false, vec![], None).unwrap();
+    /// registry.register("b".to_string(), any(), true, vec!["a".to_string()], Some(copy())).unwrap();
+    /// registry.register("c".to_string(), any(), true, vec!["b".to_string()], Some(copy())).unwrap();
+    ///
+    /// assert_eq!(registry.dependencies_closure("c"), vec!["a".to_string(), "b".to_string()]);
+    /// ```
+    pub fn dependencies_closure(&self, field: &str) -> Vec<String> {
+        let mut order = Vec::new();
+        let mut seen = HashSet::from([field.to_string()]);
+        for dependency in self.dependencies_of(field) {
+            self.post_order(&dependency, &mut seen, &mut order);
+        }
+        order
+    }
+
+    /// Get every registered field, each listed after its dependencies.
+    ///
+    /// Computing derived fields in this order only ever needs fields that are
+    /// stored or already computed. Ties are broken by name, so the order is stable.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Vec<String>)` with the names of all registered fields.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::DependencyCycle`] if the dependencies form a cycle
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let any = || Box::new(|_: &Value| true);
+    /// let copy = || Box::new(|args: &[Value]| Ok(args[0].clone()));
+    /// registry.register("total".to_string(), any(), true, vec!["price".to_string()], Some(copy())).unwrap();
+    /// registry.register("price".to_string(), any(), false, vec![], None).unwrap();
+    ///
+    /// assert_eq!(
+    ///     registry.topological_order().unwrap(),
+    ///     vec!["price".to_string(), "total".to_string()]
+    /// );
+    /// ```
+    pub fn topological_order(&self) -> Result<Vec<String>> {
+        self.check_cycles()?;
+        let mut order = Vec::new();
+        let mut seen = HashSet::new();
+        for field in self.all_fields().into_keys() {
+            self.post_order(field, &mut seen, &mut order);
         }
+        order.retain(|field| self.field(field).is_some());
+        Ok(order)
+    }
 
-        if is_derived {
-            if dependencies.is_empty() {
-                return Err(SoAKitError::DerivedFieldNoDeps(name));
-            }
-            let derived_func = derived_func.ok_or_else(|| {
-                SoAKitError::InvalidArgument(
-                    "Derived field must have a derived function".to_string(),
-                )
-            })?;
-            let metadata = FieldMetadata::new_derived(validator, dependencies, derived_func)?;
-            let _ = self.fields.insert(name, metadata);
-        } else {
-            if !dependencies.is_empty() || derived_func.is_some() {
-                return Err(SoAKitError::InvalidArgument(
-                    "Non-derived field cannot have dependencies or derived function".to_string(),
-                ));
-            }
-            let metadata = FieldMetadata::new(validator);
-            let _ = self.fields.insert(name, metadata);
+    /// Append `field` and its unseen dependencies to `order`, dependencies first.
+    fn post_order(&self, field: &str, seen: &mut HashSet<String>, order: &mut Vec<String>) {
+        if !seen.insert(field.to_string()) {
+            return;
+        }
+        for dependency in self.dependencies_of(field) {
+            self.post_order(&dependency, seen, order);
         }
+        order.push(field.to_string());
+    }
 
-        Ok(())
+    /// Get the dependencies of a field, or none if it is not registered.
+    fn dependencies_of(&self, field: &str) -> Vec<String> {
+        self.field(field)
+            .map(|metadata| metadata.dependencies.clone())
+            .unwrap_or_default()
     }
 
     /// Validate a value against a field's validator.
@@ -283,8 +2607,10 @@ impl Registry {
     ///
     /// # Returns
     ///
-    /// Returns `true` if the field exists and the value passes validation,
-    /// `false` if the field doesn't exist or validation fails.
+    /// Returns `true` if the field exists and the value passes validation and
+    /// the field's constraints and has its declared shape, `false` if the field
+    /// doesn't exist or a check fails. Virtual fields store no values, so nothing
+    /// is valid for them.
     ///
     /// # Examples
     ///
@@ -301,9 +2627,13 @@ impl Registry {
     /// assert!(!registry.validate("nonexistent", &Value::ScalarInt(25)));
     /// ```
     pub fn validate(&self, field: &str, value: &Value) -> bool {
-        self.fields
-            .get(field)
-            .map(|meta| (meta.validator)(value))
+        self.field(field)
+            .map(|meta| {
+                !meta.is_virtual()
+                    && meta.shape_matches(value)
+                    && (meta.validator)(value)
+                    && meta.constraints.iter().all(|c| c.check(value))
+            })
             .unwrap_or(false)
     }
 
@@ -332,7 +2662,7 @@ impl Registry {
     /// assert!(!metadata.unwrap().is_derived);
     /// ```
     pub fn get_metadata(&self, field: &str) -> Option<&FieldMetadata> {
-        self.fields.get(field)
+        self.field(field)
     }
 
     /// Check if a field exists in the registry.
@@ -359,7 +2689,7 @@ impl Registry {
     /// assert!(!registry.has_field("nonexistent"));
     /// ```
     pub fn has_field(&self, field: &str) -> bool {
-        self.fields.contains_key(field)
+        self.field(field).is_some()
     }
 
     /// List all registered field names (excluding system fields).
@@ -383,7 +2713,105 @@ impl Registry {
     /// assert_eq!(fields.len(), 2);
     /// ```
     pub fn list_fields(&self) -> Vec<String> {
-        self.fields.keys().cloned().collect()
+        self.field_names().map(str::to_string).collect()
+    }
+
+    /// List the derived fields, in sorted order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// registry.register_float("price").unwrap();
+    /// let copy = Box::new(|args: &[Value]| Ok(args[0].clone()));
+    /// let validator = Box::new(|_: &Value| true);
+    /// registry.register("cost".to_string(), validator, true, vec!["price".to_string()], Some(copy)).unwrap();
+    ///
+    /// assert_eq!(registry.derived_fields(), vec!["cost"]);
+    /// assert_eq!(registry.regular_fields(), vec!["price"]);
+    /// ```
+    pub fn derived_fields(&self) -> Vec<String> {
+        self.iter()
+            .filter(|(_, metadata)| metadata.is_derived)
+            .map(|(name, _)| name.to_string())
+            .collect()
+    }
+
+    /// List the regular (stored, neither derived nor virtual) fields, in sorted order.
+    pub fn regular_fields(&self) -> Vec<String> {
+        self.iter()
+            .filter(|(_, metadata)| !metadata.is_derived && !metadata.is_virtual())
+            .map(|(name, _)| name.to_string())
+            .collect()
+    }
+
+    /// List the virtual fields, in sorted order.
+    pub fn virtual_fields(&self) -> Vec<String> {
+        self.iter()
+            .filter(|(_, metadata)| metadata.is_virtual())
+            .map(|(name, _)| name.to_string())
+            .collect()
+    }
+
+    /// Iterate over the registered fields and their metadata, in sorted order.
+    ///
+    /// Fields of a parent registry are included unless this registry defines a
+    /// field of the same name. Unlike [`Registry::list_fields`], no name is cloned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Registry;
+    ///
+    /// let mut registry = Registry::new();
+    /// registry.register_int("b").unwrap();
+    /// registry.register_float("a").unwrap();
+    ///
+    /// let typed: Vec<_> = registry
+    ///     .iter()
+    ///     .map(|(name, metadata)| (name, metadata.field_type.is_some()))
+    ///     .collect();
+    /// assert_eq!(typed, vec![("a", true), ("b", true)]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &FieldMetadata)> {
+        let mut own = self
+            .fields
+            .iter()
+            .map(|(name, metadata)| (name.as_str(), &**metadata))
+            .peekable();
+        // Without a parent the map is empty and nothing is allocated
+        let mut inherited = self
+            .parent
+            .as_ref()
+            .map(|parent| parent.all_fields())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(name, _)| !self.fields.contains_key(*name))
+            .peekable();
+        std::iter::from_fn(move || match (own.peek(), inherited.peek()) {
+            (Some((name, _)), Some((parent_name, _))) if parent_name < name => inherited.next(),
+            (Some(_), _) => own.next(),
+            (None, _) => inherited.next(),
+        })
+    }
+
+    /// Iterate over the registered field names, in sorted order.
+    ///
+    /// This is [`Registry::list_fields`] without cloning the names.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Registry;
+    ///
+    /// let mut registry = Registry::new();
+    /// registry.register_int("age").unwrap();
+    /// assert!(registry.field_names().eq(["age"]));
+    /// ```
+    pub fn field_names(&self) -> impl Iterator<Item = &str> {
+        self.iter().map(|(name, _)| name)
     }
 
     /// Get the number of registered fields.
@@ -406,7 +2834,7 @@ impl Registry {
     /// assert_eq!(registry.len(), 2);
     /// ```
     pub fn len(&self) -> usize {
-        self.fields.len()
+        self.all_fields().len()
     }
 
     /// Check if the registry is empty.
@@ -424,8 +2852,47 @@ impl Registry {
     /// assert!(registry.is_empty());
     /// ```
     pub fn is_empty(&self) -> bool {
-        self.fields.is_empty()
+        self.fields.is_empty() && self.parent.as_ref().is_none_or(|parent| parent.is_empty())
+    }
+}
+
+/// Find a dependency cycle reachable from `roots`.
+///
+/// Returns the fields along the cycle, starting and ending with the same field,
+/// or `None` if every dependency chain ends.
+fn find_cycle<'a>(
+    roots: impl IntoIterator<Item = &'a str>,
+    dependencies: &dyn Fn(&str) -> Vec<String>,
+) -> Option<Vec<String>> {
+    fn visit(
+        field: &str,
+        dependencies: &dyn Fn(&str) -> Vec<String>,
+        path: &mut Vec<String>,
+        done: &mut HashSet<String>,
+    ) -> Option<Vec<String>> {
+        if let Some(start) = path.iter().position(|f| f == field) {
+            let mut cycle = path.split_off(start);
+            cycle.push(field.to_string());
+            return Some(cycle);
+        }
+        if done.contains(field) {
+            return None;
+        }
+        path.push(field.to_string());
+        for dependency in dependencies(field) {
+            if let Some(cycle) = visit(&dependency, dependencies, path, done) {
+                return Some(cycle);
+            }
+        }
+        let _ = path.pop();
+        let _ = done.insert(field.to_string());
+        None
     }
+
+    let mut done = HashSet::new();
+    roots
+        .into_iter()
+        .find_map(|root| visit(root, dependencies, &mut Vec::new(), &mut done))
 }
 
 impl Default for Registry {
@@ -916,4 +3383,634 @@ mod tests {
         assert!(reg.has_field("age"));
         assert!(!reg.has_field("name"));
     }
+
+    #[test]
+    fn test_rename_field_rewrites_dependencies() {
+        let mut reg = Registry::new();
+        let validator = Box::new(|v: &Value| matches!(v, Value::VectorInt(_)));
+        reg.register("a".to_string(), validator.clone(), false, vec![], None)
+            .unwrap();
+        reg.register("b".to_string(), validator.clone(), false, vec![], None)
+            .unwrap();
+        let derived_func = Box::new(|args: &[Value]| Ok(args[0].clone()));
+        reg.register(
+            "copy".to_string(),
+            validator,
+            true,
+            vec!["a".to_string(), "b".to_string()],
+            Some(derived_func),
+        )
+        .unwrap();
+
+        reg.rename_field("a", "z").unwrap();
+        assert!(!reg.has_field("a"));
+        assert!(reg.validate("z", &Value::VectorInt(vec![1])));
+        assert_eq!(
+            reg.get_metadata("copy").unwrap().dependencies,
+            vec!["z".to_string(), "b".to_string()]
+        );
+
+        assert!(matches!(
+            reg.rename_field("z", "b"),
+            Err(SoAKitError::FieldAlreadyExists(_))
+        ));
+        assert!(matches!(
+            reg.rename_field("a", "y"),
+            Err(SoAKitError::FieldNotFound(_))
+        ));
+        assert!(matches!(
+            reg.rename_field("z", "_id"),
+            Err(SoAKitError::InvalidArgument(_))
+        ));
+        assert!(reg.has_field("z"));
+    }
+
+    #[test]
+    fn test_register_rejects_dependency_cycles() {
+        let mut registry = Registry::new();
+        let any = || Box::new(|_: &Value| true);
+        let copy = || -> DerivedFunc { Box::new(|args: &[Value]| Ok(args[0].clone())) };
+        let deps = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        let cycle = |names: &[&str]| Err(SoAKitError::DependencyCycle(deps(names)));
+
+        registry
+            .register("a".to_string(), any(), true, deps(&["b"]), Some(copy()))
+            .unwrap();
+        registry
+            .register("b".to_string(), any(), true, deps(&["c"]), Some(copy()))
+            .unwrap();
+        assert!(registry.check_cycles().is_ok());
+
+        assert_eq!(
+            registry.register("c".to_string(), any(), true, deps(&["a"]), Some(copy())),
+            cycle(&["c", "a", "b", "c"])
+        );
+        assert_eq!(
+            registry.register("d".to_string(), any(), true, deps(&["d"]), Some(copy())),
+            cycle(&["d", "d"])
+        );
+        assert!(!registry.has_field("c") && !registry.has_field("d"));
+
+        // Renaming "x" to the dangling dependency "c" would close the loop
+        registry
+            .register("x".to_string(), any(), true, deps(&["a"]), Some(copy()))
+            .unwrap();
+        assert_eq!(
+            registry.rename_field("x", "c"),
+            cycle(&["c", "a", "b", "c"])
+        );
+        assert!(registry.has_field("x"));
+        registry.rename_field("x", "y").unwrap();
+        assert!(registry.check_cycles().is_ok());
+    }
+
+    #[test]
+    fn test_dependency_graph() {
+        let mut registry = Registry::new();
+        let any = || Box::new(|_: &Value| true);
+        let copy = || -> DerivedFunc { Box::new(|args: &[Value]| Ok(args[0].clone())) };
+        let names = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+
+        // Registered out of order, with "z" depending on an unregistered field
+        registry
+            .register(
+                "d".to_string(),
+                any(),
+                true,
+                names(&["b", "c"]),
+                Some(copy()),
+            )
+            .unwrap();
+        registry
+            .register("b".to_string(), any(), true, names(&["a"]), Some(copy()))
+            .unwrap();
+        registry
+            .register(
+                "c".to_string(),
+                any(),
+                true,
+                names(&["a", "b"]),
+                Some(copy()),
+            )
+            .unwrap();
+        registry
+            .register("a".to_string(), any(), false, vec![], None)
+            .unwrap();
+        registry
+            .register(
+                "z".to_string(),
+                any(),
+                true,
+                names(&["missing"]),
+                Some(copy()),
+            )
+            .unwrap();
+
+        assert_eq!(registry.dependents_of("a"), names(&["b", "c"]));
+        assert_eq!(registry.dependents_of("b"), names(&["c", "d"]));
+        assert!(registry.dependents_of("d").is_empty());
+        assert!(registry.dependents_of("unknown").is_empty());
+
+        assert_eq!(registry.dependencies_closure("d"), names(&["a", "b", "c"]));
+        assert_eq!(registry.dependencies_closure("z"), names(&["missing"]));
+        assert!(registry.dependencies_closure("a").is_empty());
+
+        let order = registry.topological_order().unwrap();
+        assert_eq!(order, names(&["a", "b", "c", "d", "z"]));
+    }
+
+    #[test]
+    fn test_typed_registration() {
+        let mut registry = Registry::new();
+        registry.register_typed::<i64>("age").unwrap();
+        registry.register_float("height").unwrap();
+        registry.register_bool("active").unwrap();
+        registry.register_string("name").unwrap();
+        registry
+            .register_with_type("score", FieldType::Float)
+            .unwrap();
+
+        let cases = [
+            ("age", FieldType::Int, Value::ScalarInt(1)),
+            ("height", FieldType::Float, Value::ScalarFloat(1.5)),
+            ("active", FieldType::Bool, Value::ScalarBool(true)),
+            (
+                "name",
+                FieldType::String,
+                Value::ScalarString("a".to_string()),
+            ),
+            ("score", FieldType::Float, Value::ScalarFloat(0.0)),
+        ];
+        for (field, field_type, value) in cases {
+            let metadata = registry.get_metadata(field).unwrap();
+            assert_eq!(metadata.field_type, Some(field_type));
+            assert!(!metadata.is_derived);
+            assert!(registry.validate(field, &value));
+            assert!(!registry.validate(field, &Value::Null));
+        }
+        assert!(!registry.validate("age", &Value::ScalarFloat(1.0)));
+        assert!(!registry.validate("name", &Value::VectorString(vec![])));
+
+        assert!(matches!(
+            registry.register_int("age"),
+            Err(SoAKitError::FieldAlreadyExists(_))
+        ));
+        assert!(registry.register_int("_id").is_err());
+        assert!(!registry.has_field("_id"));
+
+        // Untyped registration records no type, and renaming keeps it
+        registry
+            .register("any".to_string(), Box::new(|_| true), false, vec![], None)
+            .unwrap();
+        assert_eq!(registry.get_metadata("any").unwrap().field_type, None);
+        registry.rename_field("age", "years").unwrap();
+        assert_eq!(
+            registry.get_metadata("years").unwrap().field_type,
+            Some(FieldType::Int)
+        );
+    }
+
+    #[test]
+    fn test_field_type_descriptors() {
+        let mut registry = Registry::new();
+        registry.register_int("age").unwrap();
+        registry
+            .register_with_type("point", FieldType::Vector)
+            .unwrap();
+        registry
+            .register_with_type("grid", FieldType::Matrix)
+            .unwrap();
+        registry.register_with_type("doc", FieldType::Json).unwrap();
+        registry
+            .register("any".to_string(), Box::new(|_| false), false, vec![], None)
+            .unwrap();
+
+        assert!(registry.validate("point", &Value::VectorFloat(vec![1.0, 2.0])));
+        assert!(!registry.validate("point", &Value::ScalarFloat(1.0)));
+        assert!(registry.validate("grid", &Value::Matrix(vec![Value::VectorInt(vec![1])])));
+        assert!(registry.validate("doc", &Value::ScalarJson(serde_json::json!({"a": 1}))));
+        let sparse = Value::VectorOptionInt {
+            len: 2,
+            entries: vec![(0, 1)],
+        };
+        assert_eq!(FieldType::of(&sparse), Some(FieldType::Vector));
+        assert_eq!(FieldType::Matrix.kind(), None);
+        assert_eq!(FieldType::Bool.kind(), Some(ValueKind::Bool));
+        assert_eq!(FieldType::Json.to_string(), "Json");
+
+        let half = Value::ScalarFloat(0.5);
+        assert_eq!(FieldType::Float.coerce(&half), Some(half));
+        assert_eq!(FieldType::Int.coerce(&Value::ScalarFloat(1e300)), None);
+        assert_eq!(FieldType::Int.coerce(&Value::ScalarFloat(f64::NAN)), None);
+        assert_eq!(FieldType::String.coerce(&Value::ScalarInt(1)), None);
+
+        assert_eq!(FieldType::Int.mismatch(&Value::ScalarInt(1)), None);
+        let mismatch = FieldType::Int.mismatch(&Value::Null);
+        assert_eq!(mismatch.as_deref(), Some("expected Int, got Null"));
+        let error = registry
+            .check_value("age", &Value::ScalarFloat(1.0))
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Validation failed: Value validation failed for field: age (expected Int, got Float)"
+        );
+        let error = registry
+            .check_value("any", &Value::ScalarInt(1))
+            .unwrap_err();
+        assert!(!error.to_string().contains("expected"));
+        assert!(registry.check_value("age", &Value::ScalarInt(1)).is_ok());
+    }
+
+    #[test]
+    fn test_aliases() {
+        let mut registry = Registry::new();
+        registry.register_int("date_of_birth").unwrap();
+        registry.add_alias("dob", "date_of_birth").unwrap();
+        registry.add_alias("birth", "dob").unwrap();
+        assert_eq!(registry.resolve("birth"), "date_of_birth");
+        assert_eq!(registry.resolve("other"), "other");
+        assert_eq!(registry.aliases_of("date_of_birth"), vec!["birth", "dob"]);
+
+        assert!(matches!(
+            registry.add_alias("dob", "date_of_birth"),
+            Err(SoAKitError::FieldAlreadyExists(_))
+        ));
+        assert!(matches!(
+            registry.add_alias("x", "missing"),
+            Err(SoAKitError::FieldNotFound(_))
+        ));
+        assert!(matches!(
+            registry.add_alias("_x", "dob"),
+            Err(SoAKitError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            registry.register_int("dob"),
+            Err(SoAKitError::FieldAlreadyExists(_))
+        ));
+        assert!(matches!(
+            registry.rename_field("date_of_birth", "dob"),
+            Err(SoAKitError::FieldAlreadyExists(_))
+        ));
+
+        registry.rename_field("date_of_birth", "born").unwrap();
+        assert_eq!(registry.resolve("dob"), "born");
+        assert!(registry.remove_alias("dob"));
+        assert!(!registry.remove_alias("dob"));
+        assert_eq!(registry.aliases_of("born"), vec!["birth"]);
+    }
+
+    #[test]
+    fn test_tags() {
+        let mut registry = Registry::new();
+        registry.register_float("height").unwrap();
+        registry.register_string("email").unwrap();
+        let any = Box::new(|_: &Value| true);
+        let copy = Box::new(|args: &[Value]| Ok(args[0].clone()));
+        let dependencies = vec!["height".to_string()];
+        registry
+            .register("h2".to_string(), any, true, dependencies, Some(copy))
+            .unwrap();
+
+        registry.set_tag("height", "units", "m").unwrap();
+        registry.set_tag("h2", "units", "cm").unwrap();
+        registry.set_tag("email", "pii", "true").unwrap();
+        registry.set_tag("height", "units", "mm").unwrap();
+        assert!(matches!(
+            registry.set_tag("missing", "a", "b"),
+            Err(SoAKitError::FieldNotFound(_))
+        ));
+
+        assert_eq!(registry.tag("height", "units"), Some("mm"));
+        assert_eq!(registry.tag("height", "pii"), None);
+        assert_eq!(registry.tag("missing", "units"), None);
+        assert_eq!(registry.fields_with_tag("units"), vec!["h2", "height"]);
+        assert_eq!(registry.fields_with_tag_value("units", "cm"), vec!["h2"]);
+        assert_eq!(registry.get_metadata("email").unwrap().tags.len(), 1);
+
+        assert_eq!(
+            registry.remove_tag("email", "pii").unwrap(),
+            Some("true".to_string())
+        );
+        assert_eq!(registry.remove_tag("email", "pii").unwrap(), None);
+        assert!(registry.fields_with_tag("pii").is_empty());
+        assert!(registry.remove_tag("missing", "pii").is_err());
+    }
+
+    #[test]
+    fn test_parent_registry_fallback() {
+        let mut core = Registry::new();
+        core.register_int("a").unwrap();
+        core.add_alias("alpha", "a").unwrap();
+        core.set_tag("a", "units", "m").unwrap();
+        let core = Arc::new(core);
+
+        let mut child = Registry::with_parent(&core);
+        assert!(!child.is_empty());
+        assert!(child.parent().is_some_and(|parent| parent.has_field("a")));
+        let any = || Box::new(|_: &Value| true);
+        let copy = Box::new(|args: &[Value]| Ok(args[0].clone()));
+        child
+            .register(
+                "b".to_string(),
+                any(),
+                true,
+                vec!["a".to_string()],
+                Some(copy),
+            )
+            .unwrap();
+
+        assert_eq!(child.list_fields(), vec!["a", "b"]);
+        assert_eq!(child.len(), 2);
+        assert!(child.has_field("a") && child.validate("a", &Value::ScalarInt(1)));
+        assert_eq!(child.resolve("alpha"), "a");
+        assert_eq!(child.fields_with_tag("units"), vec!["a"]);
+        assert_eq!(child.dependents_of("a"), vec!["b"]);
+        assert_eq!(child.topological_order().unwrap(), vec!["a", "b"]);
+        assert!(core.dependents_of("a").is_empty());
+
+        // Names are unique across layers
+        for name in ["a", "alpha"] {
+            assert!(matches!(
+                child.register_int(name),
+                Err(SoAKitError::FieldAlreadyExists(_))
+            ));
+            assert!(matches!(
+                child.add_alias(name, "b"),
+                Err(SoAKitError::FieldAlreadyExists(_))
+            ));
+        }
+        child.add_alias("beta", "alpha").unwrap();
+        assert_eq!(child.aliases_of("a"), vec!["alpha", "beta"]);
+
+        // Parent fields are read-only through the child
+        assert!(matches!(
+            child.set_tag("a", "k", "v"),
+            Err(SoAKitError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            child.rename_field("a", "c"),
+            Err(SoAKitError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            child.set_tag("zzz", "k", "v"),
+            Err(SoAKitError::FieldNotFound(_))
+        ));
+        assert_eq!(core.tag("a", "k"), None);
+
+        // A grandchild sees every layer
+        let child = Arc::new(child);
+        let grandchild = Registry::with_parent(&child);
+        assert_eq!(grandchild.list_fields(), vec!["a", "b"]);
+        assert_eq!(grandchild.resolve("beta"), "a");
+        assert!(Registry::with_parent(&Arc::new(Registry::new())).is_empty());
+    }
+
+    #[test]
+    fn test_register_derived_multi_checks_everything_first() {
+        let mut registry = Registry::new();
+        registry.register_int("a").unwrap();
+        let outputs = |_: &[Value]| Ok(vec![Value::Null, Value::Null]);
+
+        assert!(matches!(
+            registry.register_derived_multi(&[], &["a"], outputs),
+            Err(SoAKitError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            registry.register_derived_multi(&["x", "x"], &["a"], outputs),
+            Err(SoAKitError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            registry.register_derived_multi(&["x", "a"], &["a"], outputs),
+            Err(SoAKitError::FieldAlreadyExists(name)) if name == "a"
+        ));
+        assert!(matches!(
+            registry.register_derived_multi(&["x", "y"], &[], outputs),
+            Err(SoAKitError::DerivedFieldNoDeps(_))
+        ));
+        assert!(matches!(
+            registry.register_derived_multi(&["x", "y"], &["a", "y"], outputs),
+            Err(SoAKitError::DependencyCycle(_))
+        ));
+        assert_eq!(registry.list_fields(), vec!["a"]);
+
+        registry
+            .register_derived_multi(&["x", "y"], &["a"], outputs)
+            .unwrap();
+        assert_eq!(registry.dependents_of("a"), vec!["x", "y"]);
+        assert_eq!(registry.topological_order().unwrap(), vec!["a", "x", "y"]);
+    }
+
+    #[test]
+    fn test_iter_merges_parent_fields_in_order() {
+        let mut base = Registry::new();
+        base.register_int("b").unwrap();
+        base.register_int("d").unwrap();
+        let base = Arc::new(base);
+        let mut child = Registry::with_parent(&base);
+        child.register_float("a").unwrap();
+        child.register_float("c").unwrap();
+        child.register_float("e").unwrap();
+
+        assert!(child.field_names().eq(["a", "b", "c", "d", "e"]));
+        assert_eq!(child.list_fields(), vec!["a", "b", "c", "d", "e"]);
+        let types: Vec<_> = child
+            .iter()
+            .map(|(_, metadata)| metadata.field_type)
+            .collect();
+        assert_eq!(
+            types,
+            [
+                FieldType::Float,
+                FieldType::Int,
+                FieldType::Float,
+                FieldType::Int,
+                FieldType::Float
+            ]
+            .map(Some)
+        );
+        assert_eq!(Registry::new().iter().count(), 0);
+    }
+
+    #[test]
+    fn test_clone_shares_metadata_until_modified() {
+        let mut registry = Registry::new();
+        registry.register_int("a").unwrap();
+        registry.register_int("b").unwrap();
+        registry
+            .register_derived_elementwise("c", &["a"], |row: &[Value]| Ok(row[0].clone()))
+            .unwrap();
+        let snapshot = registry.clone();
+        let shared = |r1: &Registry, r2: &Registry, field: &str| {
+            Arc::ptr_eq(&r1.fields[field], &r2.fields[field])
+        };
+        assert!(
+            ["a", "b", "c"]
+                .iter()
+                .all(|f| shared(&registry, &snapshot, f))
+        );
+
+        registry.set_required("a", true).unwrap();
+        assert!(!shared(&registry, &snapshot, "a") && shared(&registry, &snapshot, "b"));
+        assert!(registry.is_required("a") && !snapshot.is_required("a"));
+
+        // Renaming copies only the metadata naming the old field
+        registry.rename_field("a", "x").unwrap();
+        assert_eq!(registry.get_metadata("c").unwrap().dependencies, vec!["x"]);
+        assert_eq!(snapshot.get_metadata("c").unwrap().dependencies, vec!["a"]);
+        assert!(shared(&registry, &snapshot, "b"));
+        assert!(snapshot.has_field("a") && !snapshot.has_field("x"));
+    }
+
+    #[test]
+    fn test_introspection_queries() {
+        let mut parent = Registry::new();
+        parent.register_int("a").unwrap();
+        parent.set_tag("a", "pii", "true").unwrap();
+        let mut registry = Registry::with_parent(&Arc::new(parent));
+        let copy = || Box::new(|args: &[Value]| Ok(args[0].clone()));
+        let any = || Box::new(|_: &Value| true);
+        registry
+            .register(
+                "b".to_string(),
+                any(),
+                true,
+                vec!["a".to_string()],
+                Some(copy()),
+            )
+            .unwrap();
+        registry
+            .register(
+                "c".to_string(),
+                any(),
+                true,
+                vec!["b".to_string()],
+                Some(copy()),
+            )
+            .unwrap();
+        registry.register_string("d").unwrap();
+
+        assert_eq!(registry.derived_fields(), vec!["b", "c"]);
+        assert_eq!(registry.regular_fields(), vec!["a", "d"]);
+        assert_eq!(registry.fields_with_tag("pii"), vec!["a"]);
+        assert!(registry.is_dependency_of("a", "b") && registry.is_dependency_of("a", "c"));
+        assert!(!registry.is_dependency_of("c", "a") && !registry.is_dependency_of("d", "c"));
+        assert!(!registry.is_dependency_of("c", "c"));
+    }
+
+    #[test]
+    fn test_field_groups() {
+        let mut parent = Registry::new();
+        for field in ["x", "y", "z"] {
+            parent.register_float(field).unwrap();
+        }
+        parent.define_group("position", &["x", "y", "z"]).unwrap();
+        let parent = Arc::new(parent);
+
+        let mut registry = Registry::with_parent(&parent);
+        registry.register_float("w").unwrap();
+        registry.add_alias("width", "w").unwrap();
+        assert_eq!(registry.group("position").unwrap(), ["x", "y", "z"]);
+        registry.define_group("plane", &["x", "width"]).unwrap();
+        assert_eq!(registry.group("plane").unwrap(), ["x", "w"]);
+        assert_eq!(registry.group_names(), vec!["plane", "position"]);
+
+        let err = |result: Result<()>| matches!(result, Err(SoAKitError::InvalidArgument(_)));
+        assert!(err(registry.define_group("_hidden", &["x"])));
+        assert!(err(registry.define_group("empty", &[])));
+        assert!(err(registry.define_group("twice", &["w", "width"])));
+        assert!(matches!(
+            registry.define_group("bad", &["x", "missing"]),
+            Err(SoAKitError::FieldNotFound(_))
+        ));
+
+        registry.rename_field("w", "depth").unwrap();
+        assert_eq!(registry.group("plane").unwrap(), ["x", "depth"]);
+        // Parent groups can be hidden but not removed through the child
+        assert!(!registry.remove_group("position"));
+        registry.define_group("position", &["x", "y"]).unwrap();
+        assert_eq!(registry.group("position").unwrap().len(), 2);
+        assert_eq!(parent.group("position").unwrap().len(), 3);
+        assert!(registry.remove_group("plane") && registry.group("plane").is_none());
+
+        let mut other = Registry::new();
+        other.register_int("u").unwrap();
+        other.define_group("position", &["u"]).unwrap();
+        other.define_group("extra", &["u"]).unwrap();
+        assert!(matches!(
+            registry.clone().merge(other.clone(), ConflictPolicy::Error),
+            Err(SoAKitError::FieldAlreadyExists(_))
+        ));
+        registry.merge(other, ConflictPolicy::Skip).unwrap();
+        assert_eq!(registry.group("extra").unwrap(), ["u"]);
+        assert_eq!(registry.group("position").unwrap(), ["x", "y"]);
+    }
+
+    #[test]
+    fn test_merge_registries() {
+        let any = || Box::new(|_: &Value| true);
+        let copy = || Box::new(|args: &[Value]| Ok(args[0].clone()));
+        let derived = |registry: &mut Registry, name: &str, dep: &str| {
+            let dependencies = vec![dep.to_string()];
+            registry
+                .register(name.to_string(), any(), true, dependencies, Some(copy()))
+                .unwrap();
+        };
+
+        let mut base = Registry::new();
+        base.register_int("a").unwrap();
+        base.add_alias("first", "a").unwrap();
+        let plugin = || {
+            let mut plugin = Registry::new();
+            plugin.register_string("a").unwrap();
+            plugin.register_int("b").unwrap();
+            plugin.add_alias("second", "b").unwrap();
+            plugin
+        };
+
+        assert!(matches!(
+            base.merge(plugin(), ConflictPolicy::Error),
+            Err(SoAKitError::FieldAlreadyExists(_))
+        ));
+        assert_eq!(base.list_fields(), vec!["a"]);
+
+        base.merge(plugin(), ConflictPolicy::Skip).unwrap();
+        assert_eq!(base.list_fields(), vec!["a", "b"]);
+        assert_eq!(base.resolve("second"), "b");
+        assert!(base.validate("a", &Value::ScalarInt(1)));
+
+        base.merge(plugin(), ConflictPolicy::Overwrite).unwrap();
+        assert!(base.validate("a", &Value::ScalarString("x".to_string())));
+        assert_eq!(base.aliases_of("a"), vec!["first"]);
+
+        // A field cannot replace an alias
+        let mut shadow = Registry::new();
+        shadow.register_int("first").unwrap();
+        assert!(matches!(
+            base.merge(shadow, ConflictPolicy::Overwrite),
+            Err(SoAKitError::FieldAlreadyExists(_))
+        ));
+
+        // Merged derived fields must not close a cycle
+        derived(&mut base, "c", "d");
+        let mut cyclic = Registry::new();
+        derived(&mut cyclic, "d", "c");
+        assert!(matches!(
+            base.merge(cyclic, ConflictPolicy::Error),
+            Err(SoAKitError::DependencyCycle(_))
+        ));
+        assert!(!base.has_field("d"));
+
+        // Parent definitions cannot be overwritten
+        let mut child = Registry::with_parent(&Arc::new(base));
+        let mut replacement = Registry::new();
+        replacement.register_bool("b").unwrap();
+        assert!(matches!(
+            child.merge(replacement, ConflictPolicy::Overwrite),
+            Err(SoAKitError::InvalidArgument(_))
+        ));
+        let mut replacement = Registry::new();
+        replacement.register_bool("b").unwrap();
+        child.merge(replacement, ConflictPolicy::Skip).unwrap();
+        assert!(child.validate("b", &Value::ScalarInt(1)));
+    }
 }
@@ -0,0 +1,461 @@
+/// Schema versioning and data migration.
+///
+/// A [`Registry`] carries a schema version (see [`Registry::set_schema_version`])
+/// and every bulk records the version of the registry its data was written with
+/// in [`Meta::schema_version`](crate::bulk::Meta::schema_version), which is
+/// serialized with the bulk. When the fields change in an incompatible way,
+/// bump the version and describe how to upgrade older data with [`Migrations`]:
+/// each step is registered for the version it migrates from, and loading a bulk
+/// through [`Migrations::load_json`] (or its binary and TOML counterparts) runs
+/// every step between the data's version and the registry's.
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::{Bulk, Registry, Value};
+/// use soakit::migration::Migrations;
+///
+/// // Version 0 stored ages in a field named "years"
+/// let mut old = Registry::new();
+/// old.register_int("years").unwrap();
+/// let bulk = Bulk::new(2).unwrap();
+/// let bulk = bulk.set(&old, "years", vec![Value::ScalarInt(30), Value::ScalarInt(40)]).unwrap();
+/// let json = bulk.to_json().unwrap();
+///
+/// // Version 1 renamed it to "age"
+/// let mut registry = Registry::new();
+/// registry.register_int("age").unwrap();
+/// registry.set_schema_version(1);
+/// let migrations = Migrations::new().rename(0, "years", "age");
+///
+/// let bulk = migrations.load_json(&json, &registry).unwrap();
+/// assert_eq!(bulk.meta.schema_version, 1);
+/// assert_eq!(bulk.get(&registry, "age").unwrap(), Value::VectorInt(vec![30, 40]));
+/// ```
+use crate::bulk::{Bulk, chunk_of};
+use crate::error::{Result, SoAKitError};
+use crate::meta::{DerivedFunc, Registry};
+use crate::value::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A function converting one value of a field to its new type.
+pub type ConvertFn = dyn Fn(&Value) -> Result<Value> + Send + Sync;
+
+/// A function migrating a whole bulk, given the current registry.
+pub type MigrationFn = dyn Fn(Bulk, &Registry) -> Result<Bulk> + Send + Sync;
+
+/// One step of a migration.
+enum Step {
+    /// Rename a field's data
+    Rename { from: String, to: String },
+    /// Convert every value of a field
+    Retype {
+        field: String,
+        convert: Box<ConvertFn>,
+    },
+    /// Compute a field from others, then drop the others
+    DeriveAndDrop {
+        target: String,
+        sources: Vec<String>,
+        func: DerivedFunc,
+    },
+    /// Run an arbitrary function
+    Custom(Box<MigrationFn>),
+}
+
+/// Migration steps upgrading bulks written with older schema versions.
+///
+/// Steps are registered for the version they migrate from: the steps of version
+/// `v` turn data written with version `v` into data of version `v + 1`, and run
+/// in the order they were added. Versions without steps need no change.
+#[derive(Default)]
+pub struct Migrations {
+    /// Steps by the version they migrate from
+    steps: BTreeMap<u32, Vec<Step>>,
+}
+
+impl Migrations {
+    /// Create an empty set of migrations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a step.
+    fn with_step(mut self, from_version: u32, step: Step) -> Self {
+        self.steps.entry(from_version).or_default().push(step);
+        self
+    }
+
+    /// Rename a field's data, as by [`Bulk::rename_field`].
+    ///
+    /// # Arguments
+    ///
+    /// * `from_version` - The schema version the step migrates from
+    /// * `old` - The name of the field in the old schema
+    /// * `new` - The name of the field in the new schema
+    pub fn rename(self, from_version: u32, old: &str, new: &str) -> Self {
+        self.with_step(
+            from_version,
+            Step::Rename {
+                from: old.to_string(),
+                to: new.to_string(),
+            },
+        )
+    }
+
+    /// Convert every element of a field, for example to a new type.
+    ///
+    /// Missing (`Null`) elements are passed to `convert` too.
+    ///
+    /// # Arguments
+    ///
+    /// * `from_version` - The schema version the step migrates from
+    /// * `field` - The name of the field
+    /// * `convert` - Maps each old value to its new value
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    /// use soakit::migration::Migrations;
+    ///
+    /// let mut old = Registry::new();
+    /// old.register_int("price").unwrap();
+    /// let bulk = Bulk::new(2).unwrap();
+    /// let bulk = bulk.set(&old, "price", vec![Value::ScalarInt(3), Value::ScalarInt(4)]).unwrap();
+    ///
+    /// let mut registry = Registry::new();
+    /// registry.register_float("price").unwrap();
+    /// registry.set_schema_version(1);
+    /// let migrations = Migrations::new().retype(0, "price", |v: &Value| match v {
+    ///     Value::ScalarInt(cents) => Ok(Value::ScalarFloat(*cents as f64 / 100.0)),
+    ///     other => Ok(other.clone()),
+    /// });
+    ///
+    /// let bulk = migrations.migrate(bulk, &registry).unwrap();
+    /// assert_eq!(bulk.get(&registry, "price").unwrap(), Value::VectorFloat(vec![0.03, 0.04]));
+    /// ```
+    pub fn retype<F>(self, from_version: u32, field: &str, convert: F) -> Self
+    where
+        F: Fn(&Value) -> Result<Value> + Send + Sync + 'static,
+    {
+        self.with_step(
+            from_version,
+            Step::Retype {
+                field: field.to_string(),
+                convert: Box::new(convert),
+            },
+        )
+    }
+
+    /// Compute a new field from existing fields, then drop those fields.
+    ///
+    /// `func` receives the whole columns of `sources`, in order, like the
+    /// function of a derived field, and must return one value per element.
+    ///
+    /// # Arguments
+    ///
+    /// * `from_version` - The schema version the step migrates from
+    /// * `target` - The name of the new field
+    /// * `sources` - The fields passed to `func` and dropped afterwards
+    /// * `func` - Computes the new column
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, SoAKitError, Value};
+    /// use soakit::migration::Migrations;
+    ///
+    /// let mut old = Registry::new();
+    /// old.register_string("first").unwrap();
+    /// old.register_string("last").unwrap();
+    /// let bulk = Bulk::new(1).unwrap();
+    /// let bulk = bulk.set(&old, "first", vec![Value::from("Ada".to_string())]).unwrap();
+    /// let bulk = bulk.set(&old, "last", vec![Value::from("Lovelace".to_string())]).unwrap();
+    ///
+    /// let mut registry = Registry::new();
+    /// registry.register_string("name").unwrap();
+    /// registry.set_schema_version(1);
+    /// let full_name = |args: &[Value]| match (&args[0], &args[1]) {
+    ///     (Value::VectorString(first), Value::VectorString(last)) => Ok(Value::VectorString(
+    ///         first.iter().zip(last).map(|(f, l)| format!("{} {}", f, l)).collect(),
+    ///     )),
+    ///     _ => Err(SoAKitError::InvalidArgument("expected strings".to_string())),
+    /// };
+    /// let migrations = Migrations::new().derive_and_drop(0, "name", &["first", "last"], full_name);
+    ///
+    /// let bulk = migrations.migrate(bulk, &registry).unwrap();
+    /// assert_eq!(bulk.list_data_fields(), vec!["name"]);
+    /// ```
+    pub fn derive_and_drop<F>(
+        self,
+        from_version: u32,
+        target: &str,
+        sources: &[&str],
+        func: F,
+    ) -> Self
+    where
+        F: Fn(&[Value]) -> Result<Value> + Send + Sync + 'static,
+    {
+        self.with_step(
+            from_version,
+            Step::DeriveAndDrop {
+                target: target.to_string(),
+                sources: sources.iter().map(|source| source.to_string()).collect(),
+                func: Box::new(func),
+            },
+        )
+    }
+
+    /// Run an arbitrary function on the bulk.
+    ///
+    /// # Arguments
+    ///
+    /// * `from_version` - The schema version the step migrates from
+    /// * `func` - Receives the bulk and the current registry and returns the
+    ///   migrated bulk
+    pub fn custom<F>(self, from_version: u32, func: F) -> Self
+    where
+        F: Fn(Bulk, &Registry) -> Result<Bulk> + Send + Sync + 'static,
+    {
+        self.with_step(from_version, Step::Custom(Box::new(func)))
+    }
+
+    /// Upgrade a bulk to the schema version of a registry.
+    ///
+    /// Runs the steps of every version from the bulk's
+    /// [`schema_version`](crate::bulk::Meta::schema_version) up to, but not
+    /// including, the registry's, then records the registry's version in the
+    /// bulk. A bulk already at the registry's version is returned unchanged.
+    /// The migrated values are not validated; use [`Bulk::validate_all`] for that.
+    ///
+    /// # Arguments
+    ///
+    /// * `bulk` - The bulk to migrate
+    /// * `registry` - The registry of the current schema
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if the bulk was written with a newer
+    ///   schema version than the registry's
+    /// - Any error returned by a step, such as [`SoAKitError::FieldNotFound`] if a
+    ///   step names a field the bulk holds no data for
+    pub fn migrate(&self, mut bulk: Bulk, registry: &Registry) -> Result<Bulk> {
+        let target = registry.schema_version();
+        if bulk.meta.schema_version > target {
+            return Err(SoAKitError::InvalidArgument(format!(
+                "Data has schema version {}, newer than the registry's version {}",
+                bulk.meta.schema_version, target
+            )));
+        }
+        for (_, steps) in self.steps.range(bulk.meta.schema_version..target) {
+            for step in steps {
+                bulk = step.apply(bulk, registry)?;
+            }
+        }
+        bulk.meta.schema_version = target;
+        Ok(bulk)
+    }
+
+    /// Deserialize a bulk from JSON (see [`Bulk::from_json`]) and migrate it.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Bulk::from_json`] and [`Migrations::migrate`].
+    pub fn load_json(&self, json: &str, registry: &Registry) -> Result<Bulk> {
+        self.migrate(Bulk::from_json(json)?, registry)
+    }
+
+    /// Deserialize a bulk from binary data (see [`Bulk::from_binary`]) and migrate it.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Bulk::from_binary`] and [`Migrations::migrate`].
+    pub fn load_binary(&self, data: &[u8], registry: &Registry) -> Result<Bulk> {
+        self.migrate(Bulk::from_binary(data)?, registry)
+    }
+
+    /// Deserialize a bulk from TOML (see [`Bulk::from_toml`]) and migrate it.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Bulk::from_toml`] and [`Migrations::migrate`].
+    pub fn load_toml(&self, toml: &str, registry: &Registry) -> Result<Bulk> {
+        self.migrate(Bulk::from_toml(toml)?, registry)
+    }
+}
+
+impl fmt::Debug for Migrations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counts: BTreeMap<u32, usize> = self
+            .steps
+            .iter()
+            .map(|(version, steps)| (*version, steps.len()))
+            .collect();
+        f.debug_struct("Migrations")
+            .field("steps", &counts)
+            .finish()
+    }
+}
+
+impl Step {
+    /// Run the step on a bulk.
+    fn apply(&self, bulk: Bulk, registry: &Registry) -> Result<Bulk> {
+        match self {
+            Step::Rename { from, to } => bulk.rename_field(from, to),
+            Step::Retype { field, convert } => {
+                let values = stored_values(&bulk, field)?
+                    .iter()
+                    .map(convert.as_ref())
+                    .collect::<Result<Vec<_>>>()?;
+                store_values(bulk, registry, field, &values)
+            }
+            Step::DeriveAndDrop {
+                target,
+                sources,
+                func,
+            } => {
+                let args = sources
+                    .iter()
+                    .map(|source| {
+                        let values = stored_values(&bulk, source)?;
+                        Value::from_scalars(values)
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                let values = func(&args)?.to_scalars();
+                let mut bulk = store_values(bulk, registry, target, &values)?;
+                for source in sources.iter().filter(|source| *source != target) {
+                    bulk = bulk.drop_field(registry, source)?;
+                }
+                Ok(bulk)
+            }
+            Step::Custom(func) => func(bulk, registry),
+        }
+    }
+}
+
+/// Collect the stored values of a field, which need not be registered.
+fn stored_values(bulk: &Bulk, field: &str) -> Result<Vec<Value>> {
+    let mut values = Vec::with_capacity(bulk.meta.count);
+    for chunk in &bulk.chunks {
+        let column = chunk
+            .column(field)
+            .ok_or_else(|| SoAKitError::FieldNotFound(field.to_string()))?;
+        values.extend(column.to_scalars());
+    }
+    if bulk.chunks.is_empty() {
+        return Err(SoAKitError::FieldNotFound(field.to_string()));
+    }
+    Ok(values)
+}
+
+/// Store one value per element as the column of a field, which need not be registered.
+fn store_values(
+    mut bulk: Bulk,
+    registry: &Registry,
+    field: &str,
+    values: &[Value],
+) -> Result<Bulk> {
+    if values.len() != bulk.meta.count {
+        return Err(SoAKitError::LengthMismatch {
+            expected: bulk.meta.count,
+            actual: values.len(),
+        });
+    }
+    bulk.set_chunks(registry, field, |start, end| chunk_of(values, start, end))?;
+    Ok(bulk)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ints(values: &[i64]) -> Vec<Value> {
+        values.iter().map(|&v| Value::ScalarInt(v)).collect()
+    }
+
+    #[test]
+    fn test_migrate_runs_steps_between_versions() {
+        let mut old = Registry::new();
+        old.register_int("a").unwrap();
+        old.register_int("b").unwrap();
+        let bulk = Bulk::new(2).unwrap().set(&old, "a", ints(&[1, 2])).unwrap();
+        let bulk = bulk.set(&old, "b", ints(&[10, 20])).unwrap();
+        assert_eq!(bulk.meta.schema_version, 0);
+
+        let mut registry = Registry::new();
+        registry.register_int("total").unwrap();
+        registry.register_int("c").unwrap();
+        registry.set_schema_version(3);
+        let migrations = Migrations::new()
+            .rename(0, "a", "c")
+            .retype(1, "c", |v: &Value| match v {
+                Value::ScalarInt(n) => Ok(Value::ScalarInt(n * 2)),
+                other => Ok(other.clone()),
+            })
+            .derive_and_drop(1, "total", &["c", "b"], |args: &[Value]| {
+                &args[0] + &args[1]
+            })
+            .custom(5, |_, _| {
+                Err(SoAKitError::InvalidArgument("never run".to_string()))
+            });
+
+        let migrated = migrations
+            .load_binary(&bulk.to_binary().unwrap(), &registry)
+            .unwrap();
+        assert_eq!(migrated.meta.schema_version, 3);
+        assert_eq!(migrated.list_data_fields(), vec!["total"]);
+        assert_eq!(
+            migrated.get(&registry, "total").unwrap(),
+            Value::VectorInt(vec![12, 24])
+        );
+
+        // Data at the current version is left alone
+        let current = migrations.migrate(migrated.clone(), &registry).unwrap();
+        assert_eq!(current.content_hash(), migrated.content_hash());
+
+        // Data from the future cannot be migrated back
+        registry.set_schema_version(2);
+        assert!(matches!(
+            migrations.migrate(migrated, &registry),
+            Err(SoAKitError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_migration_step_errors() {
+        let mut registry = Registry::new();
+        registry.register_int("a").unwrap();
+        let bulk = Bulk::new(2)
+            .unwrap()
+            .set(&registry, "a", ints(&[1, 2]))
+            .unwrap();
+        registry.set_schema_version(1);
+
+        let missing = Migrations::new().rename(0, "nope", "b");
+        assert!(matches!(
+            missing.migrate(bulk.clone(), &registry),
+            Err(SoAKitError::FieldNotFound(_))
+        ));
+        let short =
+            Migrations::new().derive_and_drop(0, "b", &["a"], |_| Ok(Value::VectorInt(vec![1])));
+        assert!(matches!(
+            short.migrate(bulk.clone(), &registry),
+            Err(SoAKitError::LengthMismatch {
+                expected: 2,
+                actual: 1
+            })
+        ));
+        let json = bulk.to_json().unwrap();
+        assert!(json.contains("\"schema_version\":0"));
+        let unversioned = json.replace(",\"schema_version\":0", "");
+        assert_eq!(
+            Bulk::from_json(&unversioned).unwrap().meta.schema_version,
+            0
+        );
+        let migrated = Migrations::new().load_json(&json, &registry).unwrap();
+        assert_eq!(migrated.meta.schema_version, 1);
+        assert_eq!(format!("{:?}", missing), "Migrations { steps: {0: 1} }");
+    }
+}
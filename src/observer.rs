@@ -0,0 +1,176 @@
+/// Change observers for bulk fields.
+///
+/// [`Bulk::on_change`] registers a callback that runs whenever an update bumps
+/// the version of a field, with the field name and its old and new versions.
+/// Downstream systems such as UIs, caches or persistence layers can react to
+/// changes as they happen instead of polling [`Meta::versions`](crate::bulk::Meta::versions).
+///
+/// Observers are shared by clones of a bulk, so they keep firing across
+/// immutable updates such as [`Bulk::set`], which return a new bulk.
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::{Bulk, Registry, Value};
+/// use std::sync::{Arc, Mutex};
+///
+/// let mut registry = Registry::new();
+/// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+/// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+///
+/// let changes = Arc::new(Mutex::new(Vec::new()));
+/// let log = Arc::clone(&changes);
+/// let mut bulk = Bulk::new(2).unwrap();
+/// let _ = bulk.on_change(move |field, old, new| {
+///     log.lock().unwrap().push((field.to_string(), old, new));
+/// });
+///
+/// let bulk = bulk.set(&registry, "age", vec![Value::ScalarInt(25); 2]).unwrap();
+/// let _ = bulk.set_element(&registry, "age", 0, Value::ScalarInt(30)).unwrap();
+/// assert_eq!(
+///     *changes.lock().unwrap(),
+///     vec![("age".to_string(), 0, 1), ("age".to_string(), 1, 2)]
+/// );
+/// ```
+use crate::bulk::Bulk;
+use std::fmt;
+use std::sync::Arc;
+
+/// A callback run with a field name and its old and new versions.
+pub type ChangeObserver = dyn Fn(&str, u64, u64) + Send + Sync;
+
+/// Handle to an observer registered with [`Bulk::on_change`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ObserverId(u64);
+
+/// The observers registered on a bulk.
+#[derive(Clone, Default)]
+pub(crate) struct Observers {
+    /// The id to give the next observer
+    next_id: u64,
+    /// Registered observers, in registration order
+    observers: Vec<(ObserverId, Arc<ChangeObserver>)>,
+}
+
+impl Observers {
+    /// Run every observer for a version bump.
+    pub(crate) fn notify(&self, field: &str, old_version: u64, new_version: u64) {
+        for (_, observer) in &self.observers {
+            observer(field, old_version, new_version);
+        }
+    }
+}
+
+impl fmt::Debug for Observers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Observers")
+            .field("count", &self.observers.len())
+            .finish()
+    }
+}
+
+impl Bulk {
+    /// Register a callback run whenever an update bumps a field version.
+    ///
+    /// The callback receives the field name, the field's previous version (0 if
+    /// it was never set) and its new version. It runs once per bumped field, after
+    /// the new values are stored: [`Bulk::set`] and [`Bulk::set_element`] fire it
+    /// for their field, and updates of several fields, such as [`Bulk::apply`] or
+    /// [`Bulk::transaction`], fire it once for each.
+    ///
+    /// # Arguments
+    ///
+    /// * `observer` - Callback taking the field name, old version and new version
+    ///
+    /// # Returns
+    ///
+    /// An [`ObserverId`] for removing the observer with [`Bulk::remove_observer`].
+    pub fn on_change<F>(&mut self, observer: F) -> ObserverId
+    where
+        F: Fn(&str, u64, u64) + Send + Sync + 'static,
+    {
+        let id = ObserverId(self.observers.next_id);
+        self.observers.next_id = self.observers.next_id.saturating_add(1);
+        self.observers.observers.push((id, Arc::new(observer)));
+        id
+    }
+
+    /// Remove an observer registered with [`Bulk::on_change`].
+    ///
+    /// Clones made before the removal keep the observer.
+    ///
+    /// Returns `true` if the observer was registered on this bulk.
+    pub fn remove_observer(&mut self, id: ObserverId) -> bool {
+        let before = self.observers.observers.len();
+        self.observers
+            .observers
+            .retain(|&(observer_id, _)| observer_id != id);
+        self.observers.observers.len() != before
+    }
+
+    /// Get the number of observers registered on this bulk.
+    pub fn observer_count(&self) -> usize {
+        self.observers.observers.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meta::Registry;
+    use crate::value::Value;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_observers_fire_on_version_bumps() {
+        let mut registry = Registry::new();
+        let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("a".to_string(), ints, false, vec![], None)
+            .unwrap();
+        let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("b".to_string(), ints, false, vec![], None)
+            .unwrap();
+
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&changes);
+        let mut bulk = Bulk::new(3).unwrap();
+        let id = bulk.on_change(move |field, old, new| {
+            log.lock().unwrap().push((field.to_string(), old, new));
+        });
+        assert_eq!(bulk.observer_count(), 1);
+
+        let bulk = bulk
+            .set(&registry, "a", vec![Value::ScalarInt(1); 3])
+            .unwrap();
+        let mut bulk = bulk
+            .set(&registry, "b", vec![Value::ScalarInt(2); 3])
+            .unwrap();
+        bulk.set_element_mut(&registry, "a", 1, Value::ScalarInt(5))
+            .unwrap();
+        let bulk = bulk
+            .apply(&[true, false, true], |values| Ok(values.to_vec()))
+            .unwrap();
+        // A failed update notifies nothing
+        assert!(bulk.set(&registry, "a", vec![Value::ScalarInt(1)]).is_err());
+        assert_eq!(
+            *changes.lock().unwrap(),
+            vec![
+                ("a".to_string(), 0, 1),
+                ("b".to_string(), 0, 1),
+                ("a".to_string(), 1, 2),
+                ("a".to_string(), 2, 3),
+                ("b".to_string(), 1, 2),
+            ]
+        );
+
+        let mut bulk = bulk;
+        assert!(bulk.remove_observer(id));
+        assert!(!bulk.remove_observer(id));
+        let _ = bulk
+            .set(&registry, "a", vec![Value::ScalarInt(0); 3])
+            .unwrap();
+        assert_eq!(changes.lock().unwrap().len(), 5);
+    }
+}
@@ -0,0 +1,453 @@
+/// Parallel bulk operations.
+///
+/// This module is available with the `rayon` feature. It adds `par_` variants of
+/// [`Bulk`] operations that spread their work over the [`rayon`] thread pool one
+/// chunk per task, so even a bulk with a single field uses every thread:
+/// [`Bulk::par_apply`] transforms each chunk of each field separately, and
+/// [`Bulk::par_mask`], [`Bulk::par_filter`], [`Bulk::par_filter_field`] and
+/// [`Bulk::par_remove_where`] test and gather elements chunk by chunk. Results
+/// are identical to the sequential operations, provided the function given to
+/// [`Bulk::par_apply`] transforms each value independently of the others.
+///
+/// Closures passed to these methods must be `Sync`, since they are called from
+/// several threads at once.
+use crate::bulk::{Bulk, applied_columns, chunk_of};
+use crate::error::{Result, SoAKitError};
+use crate::meta::Registry;
+use crate::proxy::Proxy;
+use crate::value::Value;
+use rayon::prelude::*;
+use std::sync::Arc;
+
+impl Bulk {
+    /// Apply a function to a masked subset of data, processing chunks in parallel.
+    ///
+    /// This is [`Bulk::apply`] with every chunk of every data field transformed
+    /// as a separate task. Unlike [`Bulk::apply`], `func` is called once per chunk
+    /// and field with the masked subset of that chunk, so the result only matches
+    /// [`Bulk::apply`] when `func` maps each value on its own; functions that look
+    /// at their neighbours, such as a reversal or a running total, must use
+    /// [`Bulk::apply`].
+    ///
+    /// # Arguments
+    ///
+    /// * `mask` - Boolean array indicating which elements to transform (empty = all true)
+    /// * `func` - Function that takes a slice of values and returns as many
+    ///   transformed values
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with updated values.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::apply`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(3).unwrap();
+    /// let bulk = bulk.set(&registry, "age", vec![Value::ScalarInt(10); 3]).unwrap();
+    /// let bulk = bulk.par_apply(&[true, false, true], |subset| {
+    ///     Ok(subset.iter().map(|_| Value::ScalarInt(0)).collect())
+    /// }).unwrap();
+    /// assert_eq!(bulk.get(&registry, "age").unwrap(), Value::VectorInt(vec![0, 10, 0]));
+    /// ```
+    pub fn par_apply<F>(&self, mask: &[bool], func: F) -> Result<Self>
+    where
+        F: Fn(&[Value]) -> Result<Vec<Value>> + Sync,
+    {
+        let mask = self.normalize_mask(mask)?;
+        let bounds = self.all_chunk_bounds()?;
+        let fields = self.list_data_fields();
+        let tasks: Vec<_> = fields
+            .iter()
+            .flat_map(|field| {
+                self.chunks
+                    .iter()
+                    .zip(&bounds)
+                    .map(move |part| (field, part))
+            })
+            .collect();
+
+        let columns = tasks
+            .into_par_iter()
+            .map(|(field, (chunk, &(start, end)))| {
+                let chunk_mask = mask_slice(&mask, start, end)?;
+                let local = [(0, end.saturating_sub(start))];
+                let chunk = std::slice::from_ref(chunk);
+                applied_columns(chunk, None, field, chunk_mask, &local, &func)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut columns = columns.into_iter().flatten();
+        let updates = fields
+            .into_iter()
+            .map(|field| (field, columns.by_ref().take(self.chunks.len()).collect()))
+            .collect();
+        let mut new_bulk = self.clone();
+        new_bulk.store_columns(None, updates)?;
+        Ok(new_bulk)
+    }
+
+    /// Evaluate a predicate on every value of a field, one chunk per task.
+    ///
+    /// Stored fields are read chunk by chunk in parallel; derived fields are
+    /// computed with [`Bulk::get`] first.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `field` - The name of the field to test
+    /// * `predicate` - Function called with each element's value
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Vec<bool>)` with the predicate's result for each element, in order.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field doesn't exist
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(3).unwrap();
+    /// let bulk = bulk.set(&registry, "age", vec![
+    ///     Value::ScalarInt(15),
+    ///     Value::ScalarInt(30),
+    ///     Value::ScalarInt(45),
+    /// ]).unwrap();
+    /// let adults = bulk
+    ///     .par_mask(&registry, "age", |v| matches!(v, Value::ScalarInt(a) if *a >= 18))
+    ///     .unwrap();
+    /// assert_eq!(adults, vec![false, true, true]);
+    /// ```
+    pub fn par_mask<F>(&self, registry: &Registry, field: &str, predicate: F) -> Result<Vec<bool>>
+    where
+        F: Fn(&Value) -> bool + Sync,
+    {
+        let stored =
+            !self.chunks.is_empty() && self.chunks.iter().all(|chunk| chunk.has_column(field));
+        if !stored {
+            let values = self.get(registry, field)?.to_scalars();
+            return Ok(values.par_iter().map(&predicate).collect());
+        }
+        if !registry.has_field(field) {
+            return Err(SoAKitError::FieldNotFound(field.to_string()));
+        }
+
+        let parts: Vec<Vec<bool>> = self
+            .chunks
+            .par_iter()
+            .map(|chunk| {
+                chunk
+                    .column(field)
+                    .map(|column| column.to_scalars().iter().map(&predicate).collect())
+                    .unwrap_or_default()
+            })
+            .collect();
+        Ok(parts.concat())
+    }
+
+    /// Keep only the elements that match a predicate, testing chunks in parallel.
+    ///
+    /// This is [`Bulk::filter`] with the elements of each chunk tested as a
+    /// separate task and the kept elements gathered by [`Bulk::par_remove_where`].
+    /// Each call of `predicate` gets a [`Proxy`] for one element, so it can read
+    /// any field, including derived ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `predicate` - Function that returns `true` for elements to keep
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with the matching elements.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::filter`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Bulk, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+    /// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+    ///
+    /// let bulk = Bulk::new(3).unwrap();
+    /// let bulk = bulk.set(&registry, "age", vec![
+    ///     Value::ScalarInt(15),
+    ///     Value::ScalarInt(30),
+    ///     Value::ScalarInt(45),
+    /// ]).unwrap();
+    ///
+    /// let adults = bulk
+    ///     .par_filter(|row| {
+    ///         Ok(matches!(row.get_field(&registry, "age")?, Value::ScalarInt(a) if a >= 18))
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(adults.get(&registry, "age").unwrap(), Value::VectorInt(vec![30, 45]));
+    /// ```
+    pub fn par_filter<F>(&self, predicate: F) -> Result<Self>
+    where
+        F: Fn(&Proxy) -> Result<bool> + Sync,
+    {
+        let bounds = if self.chunks.is_empty() {
+            vec![(0, self.meta.count)]
+        } else {
+            self.all_chunk_bounds()?
+        };
+        let shared = Arc::new(self.clone());
+        let parts = bounds
+            .into_par_iter()
+            .map(|(start, end)| {
+                (start..end)
+                    .map(|idx| Ok(!predicate(&Proxy::new(Arc::clone(&shared), idx)?)?))
+                    .collect::<Result<Vec<bool>>>()
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.par_remove_where(&parts.concat())
+    }
+
+    /// Keep the elements whose value of `field` satisfies a predicate, in parallel.
+    ///
+    /// This is [`Bulk::filter_field`] with the mask built by [`Bulk::par_mask`] and
+    /// the kept elements gathered by [`Bulk::par_remove_where`].
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `field` - The name of the field to test
+    /// * `predicate` - Function called with each element's value; `true` keeps it
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with the kept elements.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::filter_field`].
+    pub fn par_filter_field<F>(
+        &self,
+        registry: &Registry,
+        field: &str,
+        predicate: F,
+    ) -> Result<Self>
+    where
+        F: Fn(&Value) -> bool + Sync,
+    {
+        let remove: Vec<bool> = self
+            .par_mask(registry, field, predicate)?
+            .into_iter()
+            .map(|keep| !keep)
+            .collect();
+        self.par_remove_where(&remove)
+    }
+
+    /// Remove the elements where the mask is `true`, gathering chunks in parallel.
+    ///
+    /// This is [`Bulk::remove_where`] with the kept values of every chunk of every
+    /// data field gathered as a separate task, and each new chunk built as a
+    /// separate task.
+    ///
+    /// # Arguments
+    ///
+    /// * `mask` - `true` for each element to remove; must have one entry per element
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with the remaining elements.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::remove_where`].
+    pub fn par_remove_where(&self, mask: &[bool]) -> Result<Self> {
+        let (mut new_bulk, bounds) = self.kept_skeleton(mask)?;
+        let old_bounds = self.all_chunk_bounds()?;
+        for field in self.list_data_fields() {
+            let parts = self
+                .chunks
+                .par_iter()
+                .zip(&old_bounds)
+                .map(|(chunk, &(start, end))| {
+                    let chunk_mask = mask_slice(mask, start, end)?;
+                    Ok(chunk
+                        .column(&field)
+                        .map(|column| column.to_scalars())
+                        .unwrap_or_default()
+                        .into_iter()
+                        .zip(chunk_mask)
+                        .filter(|&(_, &remove)| !remove)
+                        .map(|(value, _)| value)
+                        .collect::<Vec<Value>>())
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let kept = parts.concat();
+            let columns = bounds
+                .par_iter()
+                .map(|&(start, end)| chunk_of(&kept, start, end))
+                .collect::<Result<Vec<_>>>()?;
+            new_bulk.insert_kept(&field, columns, self.is_interned(&field));
+        }
+        Ok(new_bulk)
+    }
+}
+
+/// The entries of a normalized mask that belong to the chunk `start..end`.
+fn mask_slice(mask: &[bool], start: usize, end: usize) -> Result<&[bool]> {
+    mask.get(start..end).ok_or(SoAKitError::IndexOutOfBounds {
+        index: end,
+        max: mask.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bulk::CHUNK_SIZE;
+
+    fn sample_bulk(registry: &mut Registry, count: usize) -> Bulk {
+        let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("n".to_string(), ints, false, vec![], None)
+            .unwrap();
+        let strings = Box::new(|v: &Value| matches!(v, Value::ScalarString(_)));
+        registry
+            .register("s".to_string(), strings, false, vec![], None)
+            .unwrap();
+
+        Bulk::new(count)
+            .unwrap()
+            .set(
+                registry,
+                "n",
+                (0..count).map(|i| Value::ScalarInt(i as i64)).collect(),
+            )
+            .unwrap()
+            .set(
+                registry,
+                "s",
+                (0..count)
+                    .map(|i| Value::ScalarString((i % 7).to_string()))
+                    .collect(),
+            )
+            .unwrap()
+            .intern_field("s")
+            .unwrap()
+    }
+
+    #[test]
+    fn test_par_filter_matches_sequential() {
+        let mut registry = Registry::new();
+        let bulk = sample_bulk(&mut registry, 3 * CHUNK_SIZE + 17);
+        let even = |v: &Value| matches!(v, Value::ScalarInt(i) if i % 2 == 0);
+
+        let parallel = bulk.par_filter_field(&registry, "n", even).unwrap();
+        let sequential = bulk.filter_field(&registry, "n", even).unwrap();
+        assert_eq!(parallel.meta.id, sequential.meta.id);
+        for field in ["n", "s"] {
+            assert_eq!(
+                parallel.get(&registry, field).unwrap(),
+                sequential.get(&registry, field).unwrap()
+            );
+        }
+        assert!(parallel.is_interned("s"));
+        assert!(bulk.par_mask(&registry, "missing", even).is_err());
+    }
+
+    #[test]
+    fn test_par_apply_matches_sequential() {
+        let mut registry = Registry::new();
+        let bulk = sample_bulk(&mut registry, 2 * CHUNK_SIZE + 5);
+        let mask: Vec<bool> = (0..bulk.count()).map(|i| i % 3 == 0).collect();
+        let double = |subset: &[Value]| -> Result<Vec<Value>> {
+            Ok(subset
+                .iter()
+                .map(|v| match v {
+                    Value::ScalarInt(i) => Value::ScalarInt(i * 2),
+                    Value::ScalarString(s) => Value::ScalarString(s.repeat(2)),
+                    other => other.clone(),
+                })
+                .collect())
+        };
+
+        let parallel = bulk.par_apply(&mask, double).unwrap();
+        let sequential = bulk.apply(&mask, double).unwrap();
+        for field in ["n", "s"] {
+            assert_eq!(
+                parallel.get(&registry, field).unwrap(),
+                sequential.get(&registry, field).unwrap()
+            );
+            assert_eq!(
+                parallel.meta.versions[field],
+                sequential.meta.versions[field]
+            );
+        }
+        assert!(matches!(
+            bulk.par_apply(&[true], double),
+            Err(SoAKitError::LengthMismatch { .. })
+        ));
+
+        // Each call sees one chunk of one field
+        let largest = std::sync::Mutex::new(0);
+        let _ = bulk
+            .par_apply(&[], |subset| {
+                let mut largest = largest.lock().unwrap();
+                *largest = (*largest).max(subset.len());
+                Ok(subset.to_vec())
+            })
+            .unwrap();
+        assert_eq!(largest.into_inner().unwrap(), CHUNK_SIZE);
+        let short = |subset: &[Value]| -> Result<Vec<Value>> { Ok(subset[1..].to_vec()) };
+        assert!(matches!(
+            bulk.par_apply(&[], short),
+            Err(SoAKitError::LengthMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_par_filter_with_proxy_matches_sequential() {
+        let mut registry = Registry::new();
+        let bulk = sample_bulk(&mut registry, 3 * CHUNK_SIZE + 17);
+        let keep = |row: &Proxy| -> Result<bool> {
+            let n = row.get_field(&registry, "n")?;
+            let s = row.get_field(&registry, "s")?;
+            Ok(matches!(
+                (n, s),
+                (Value::ScalarInt(i), Value::ScalarString(s)) if i % 5 != 0 && s != "3"
+            ))
+        };
+
+        let parallel = bulk.par_filter(keep).unwrap();
+        let sequential = bulk.filter(keep).unwrap();
+        assert!(parallel.count() > CHUNK_SIZE);
+        assert_eq!(parallel.meta.id, sequential.meta.id);
+        for field in ["n", "s"] {
+            assert_eq!(
+                parallel.get(&registry, field).unwrap(),
+                sequential.get(&registry, field).unwrap()
+            );
+        }
+        assert!(parallel.is_interned("s"));
+
+        let missing = bulk.par_filter(|row| row.get_field(&registry, "missing").map(|_| true));
+        assert!(matches!(missing, Err(SoAKitError::FieldNotFound(_))));
+        assert!(bulk.par_filter(|_| Ok(false)).is_err());
+    }
+}
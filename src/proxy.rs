@@ -7,7 +7,7 @@ use crate::bulk::Bulk;
 use crate::error::{Result, SoAKitError};
 use crate::meta::Registry;
 use crate::value::Value;
-use std::rc::Rc;
+use std::sync::Arc;
 
 /// Proxy for accessing a single element in a Bulk structure.
 ///
@@ -45,7 +45,7 @@ use std::rc::Rc;
 #[derive(Debug)]
 pub struct Proxy {
     /// Reference to the parent Bulk
-    bulk: Rc<Bulk>,
+    bulk: Arc<Bulk>,
     /// Index of the element this proxy represents
     idx: usize,
 }
@@ -70,16 +70,16 @@ impl Proxy {
     ///
     /// ```rust
     /// use soakit::{Bulk, Proxy};
-    /// use std::rc::Rc;
+    /// use std::sync::Arc;
     ///
-    /// let bulk = Rc::new(Bulk::new(5).unwrap());
+    /// let bulk = Arc::new(Bulk::new(5).unwrap());
     /// let proxy = Proxy::new(bulk.clone(), 2).unwrap();
     /// assert_eq!(proxy.index(), 2);
     ///
     /// // Out of bounds
     /// assert!(Proxy::new(bulk, 10).is_err());
     /// ```
-    pub fn new(bulk: Rc<Bulk>, idx: usize) -> Result<Self> {
+    pub fn new(bulk: Arc<Bulk>, idx: usize) -> Result<Self> {
         if idx >= bulk.count() {
             return Err(SoAKitError::IndexOutOfBounds {
                 index: idx,
@@ -162,6 +162,8 @@ impl Proxy {
                     max: v.len(),
                 },
             ),
+            tensor @ Value::Tensor { .. } => tensor.get_element(self.idx),
+            vector if vector.is_vector() => vector.get_element(self.idx),
             _ => Err(SoAKitError::InvalidArgument(
                 "Field value is not a vector".to_string(),
             )),
@@ -214,14 +216,14 @@ mod tests {
 
     #[test]
     fn test_proxy_creation() {
-        let bulk = Rc::new(Bulk::new(5).unwrap());
+        let bulk = Arc::new(Bulk::new(5).unwrap());
         let proxy = Proxy::new(bulk.clone(), 2).unwrap();
         assert_eq!(proxy.index(), 2);
     }
 
     #[test]
     fn test_proxy_out_of_bounds() {
-        let bulk = Rc::new(Bulk::new(5).unwrap());
+        let bulk = Arc::new(Bulk::new(5).unwrap());
         let result = Proxy::new(bulk, 10);
         assert!(result.is_err());
         assert!(matches!(
@@ -238,13 +240,13 @@ mod tests {
             .register("age".to_string(), validator, false, vec![], None)
             .unwrap();
 
-        let bulk = Rc::new(Bulk::new(3).unwrap());
+        let bulk = Arc::new(Bulk::new(3).unwrap());
         let values = vec![
             Value::ScalarInt(10),
             Value::ScalarInt(20),
             Value::ScalarInt(30),
         ];
-        let bulk = Rc::new(bulk.set(&registry, "age", values).unwrap());
+        let bulk = Arc::new(bulk.set(&registry, "age", values).unwrap());
 
         let proxy = Proxy::new(bulk.clone(), 1).unwrap();
         let value = proxy.get_field(&registry, "age").unwrap();
@@ -279,8 +281,8 @@ mod tests {
             .register("name".to_string(), str_validator, false, vec![], None)
             .unwrap();
 
-        let bulk = Rc::new(Bulk::new(2).unwrap());
-        let bulk = Rc::new(
+        let bulk = Arc::new(Bulk::new(2).unwrap());
+        let bulk = Arc::new(
             bulk.set(
                 &registry,
                 "age",
@@ -288,7 +290,7 @@ mod tests {
             )
             .unwrap(),
         );
-        let bulk = Rc::new(
+        let bulk = Arc::new(
             bulk.set(
                 &registry,
                 "height",
@@ -296,7 +298,7 @@ mod tests {
             )
             .unwrap(),
         );
-        let bulk = Rc::new(
+        let bulk = Arc::new(
             bulk.set(
                 &registry,
                 "active",
@@ -304,7 +306,7 @@ mod tests {
             )
             .unwrap(),
         );
-        let bulk = Rc::new(
+        let bulk = Arc::new(
             bulk.set(
                 &registry,
                 "name",
@@ -361,7 +363,7 @@ mod tests {
             .register("value".to_string(), validator, false, vec![], None)
             .unwrap();
 
-        let bulk = Rc::new(Bulk::new(5).unwrap());
+        let bulk = Arc::new(Bulk::new(5).unwrap());
         let values = vec![
             Value::ScalarInt(0),
             Value::ScalarInt(1),
@@ -369,7 +371,7 @@ mod tests {
             Value::ScalarInt(3),
             Value::ScalarInt(4),
         ];
-        let bulk = Rc::new(bulk.set(&registry, "value", values).unwrap());
+        let bulk = Arc::new(bulk.set(&registry, "value", values).unwrap());
 
         // First element
         let proxy = Proxy::new(bulk.clone(), 0).unwrap();
@@ -394,8 +396,8 @@ mod tests {
             .register("value".to_string(), validator, false, vec![], None)
             .unwrap();
 
-        let bulk = Rc::new(Bulk::new(1).unwrap());
-        let bulk = Rc::new(
+        let bulk = Arc::new(Bulk::new(1).unwrap());
+        let bulk = Arc::new(
             bulk.set(&registry, "value", vec![Value::ScalarInt(42)])
                 .unwrap(),
         );
@@ -410,7 +412,7 @@ mod tests {
     #[test]
     fn test_proxy_get_field_nonexistent() {
         let registry = Registry::new();
-        let bulk = Rc::new(Bulk::new(3).unwrap());
+        let bulk = Arc::new(Bulk::new(3).unwrap());
         let proxy = Proxy::new(bulk.clone(), 0).unwrap();
 
         let result = proxy.get_field(&registry, "nonexistent");
@@ -420,7 +422,7 @@ mod tests {
 
     #[test]
     fn test_proxy_index_at_boundary() {
-        let bulk = Rc::new(Bulk::new(5).unwrap());
+        let bulk = Arc::new(Bulk::new(5).unwrap());
 
         // Index at max (should fail)
         let result = Proxy::new(bulk.clone(), 5);
@@ -433,7 +435,7 @@ mod tests {
 
     #[test]
     fn test_proxy_bulk_reference() {
-        let bulk = Rc::new(Bulk::new(3).unwrap());
+        let bulk = Arc::new(Bulk::new(3).unwrap());
         let proxy = Proxy::new(bulk.clone(), 1).unwrap();
 
         let bulk_ref = proxy.bulk();
@@ -472,8 +474,8 @@ mod tests {
             )
             .unwrap();
 
-        let bulk = Rc::new(Bulk::new(3).unwrap());
-        let bulk = Rc::new(
+        let bulk = Arc::new(Bulk::new(3).unwrap());
+        let bulk = Arc::new(
             bulk.set(
                 &registry,
                 "a",
@@ -485,7 +487,7 @@ mod tests {
             )
             .unwrap(),
         );
-        let bulk = Rc::new(
+        let bulk = Arc::new(
             bulk.set(
                 &registry,
                 "b",
@@ -505,7 +507,7 @@ mod tests {
 
     #[test]
     fn test_proxy_zero_index() {
-        let bulk = Rc::new(Bulk::new(3).unwrap());
+        let bulk = Arc::new(Bulk::new(3).unwrap());
         let proxy = Proxy::new(bulk.clone(), 0).unwrap();
         assert_eq!(proxy.index(), 0);
     }
@@ -518,8 +520,8 @@ mod tests {
             .register("name".to_string(), validator, false, vec![], None)
             .unwrap();
 
-        let bulk = Rc::new(Bulk::new(2).unwrap());
-        let bulk = Rc::new(
+        let bulk = Arc::new(Bulk::new(2).unwrap());
+        let bulk = Arc::new(
             bulk.set(
                 &registry,
                 "name",
@@ -546,8 +548,8 @@ mod tests {
             .register("value".to_string(), validator, false, vec![], None)
             .unwrap();
 
-        let bulk = Rc::new(Bulk::new(3).unwrap());
-        let bulk = Rc::new(
+        let bulk = Arc::new(Bulk::new(3).unwrap());
+        let bulk = Arc::new(
             bulk.set(
                 &registry,
                 "value",
@@ -0,0 +1,186 @@
+/// Row-by-row iteration over a Bulk.
+///
+/// This module provides [`Rows`], the iterator returned by [`Bulk::iter_rows`],
+/// and [`Row`], the record it yields for each element. Rows are built one chunk
+/// at a time from the bulk's stored columns, so iterating never clones the bulk
+/// or concatenates whole fields.
+use crate::bulk::{Bulk, column_element};
+use crate::value::Value;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+/// The field values of a single element of a Bulk.
+///
+/// Rows are yielded by [`Bulk::iter_rows`]. Unlike a [`Proxy`](crate::Proxy),
+/// a row owns its values and does not keep the bulk alive.
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::{Bulk, Registry, Value};
+///
+/// let mut registry = Registry::new();
+/// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+/// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+///
+/// let bulk = Bulk::new(2).unwrap();
+/// let bulk = bulk.set(&registry, "age", vec![Value::ScalarInt(25), Value::ScalarInt(30)]).unwrap();
+///
+/// let row = bulk.iter_rows(&registry).unwrap().nth(1).unwrap();
+/// assert_eq!(row.index(), 1);
+/// assert_eq!(row.get("age"), Some(&Value::ScalarInt(30)));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Row {
+    /// Position of the element in the bulk
+    index: usize,
+    /// Identifier of the element
+    id: usize,
+    /// Field values of the element, keyed by field name
+    values: BTreeMap<String, Value>,
+}
+
+impl Row {
+    /// Get the position of the element in the bulk.
+    pub const fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Get the identifier of the element.
+    pub const fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Get the value of a field for this element.
+    ///
+    /// # Arguments
+    ///
+    /// * `field` - The name of the field
+    ///
+    /// # Returns
+    ///
+    /// The scalar value of the field, or `None` if the row has no such field.
+    pub fn get(&self, field: &str) -> Option<&Value> {
+        self.values.get(field)
+    }
+
+    /// Get every field value of this element, keyed by field name.
+    pub const fn values(&self) -> &BTreeMap<String, Value> {
+        &self.values
+    }
+
+    /// Take the field values of this element as a record.
+    pub fn into_values(self) -> BTreeMap<String, Value> {
+        self.values
+    }
+}
+
+/// Iterator over the rows of a Bulk.
+///
+/// Created by [`Bulk::iter_rows`]. Stored columns are borrowed from the bulk
+/// one chunk at a time; derived fields are computed once when the iterator is
+/// created.
+#[derive(Debug)]
+pub struct Rows<'a> {
+    /// The bulk being iterated
+    bulk: &'a Bulk,
+    /// Names of the stored fields included in each row
+    fields: Vec<String>,
+    /// Names and full values of the derived fields included in each row
+    derived: Vec<(String, Value)>,
+    /// Columns of the current chunk, loaded when the chunk is entered
+    columns: Vec<(String, Cow<'a, Value>)>,
+    /// Index of the current chunk
+    chunk_idx: usize,
+    /// Position of the next element within the current chunk
+    offset: usize,
+    /// Position of the next element in the bulk
+    index: usize,
+}
+
+impl<'a> Rows<'a> {
+    /// Create an iterator over `bulk` yielding the given stored and derived fields.
+    pub(crate) const fn new(
+        bulk: &'a Bulk,
+        fields: Vec<String>,
+        derived: Vec<(String, Value)>,
+    ) -> Self {
+        Self {
+            bulk,
+            fields,
+            derived,
+            columns: Vec::new(),
+            chunk_idx: 0,
+            offset: 0,
+            index: 0,
+        }
+    }
+
+    /// Borrow the columns of the current chunk.
+    fn load_chunk(&mut self) {
+        self.columns.clear();
+        let bulk = self.bulk;
+        let Some(chunk) = bulk.chunks.get(self.chunk_idx) else {
+            return;
+        };
+        for field in &self.fields {
+            if let Some(column) = chunk.column(field) {
+                self.columns.push((field.clone(), column));
+            }
+        }
+    }
+}
+
+impl Iterator for Rows<'_> {
+    type Item = Row;
+
+    fn next(&mut self) -> Option<Row> {
+        if self.index >= self.bulk.meta.count {
+            return None;
+        }
+        // A bulk with no field set yet has no chunks, only elements
+        while let Some(chunk) = self.bulk.chunks.get(self.chunk_idx)
+            && self.offset >= chunk.len
+        {
+            self.chunk_idx = self.chunk_idx.saturating_add(1);
+            self.offset = 0;
+        }
+        if self.offset == 0 {
+            self.load_chunk();
+        }
+
+        let mut values = BTreeMap::new();
+        for (field, column) in &self.columns {
+            if let Ok(value) = column_element(column, self.offset) {
+                let _ = values.insert(field.clone(), value);
+            }
+        }
+        for (field, column) in &self.derived {
+            if let Ok(value) = column_element(column, self.index) {
+                let _ = values.insert(field.clone(), value);
+            }
+        }
+
+        let row = Row {
+            index: self.index,
+            id: self
+                .bulk
+                .meta
+                .id
+                .get(self.index)
+                .copied()
+                .unwrap_or(self.index),
+            values,
+        };
+        self.offset = self.offset.saturating_add(1);
+        self.index = self.index.saturating_add(1);
+        Some(row)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.bulk.meta.count.saturating_sub(self.index);
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Rows<'_> {}
@@ -0,0 +1,751 @@
+/// Serializable registry schemas.
+///
+/// Validators and derived functions are closures, so a [`Registry`] itself cannot
+/// be written to a file. A [`Schema`] describes the shape of a registry instead:
+/// the field names, their declared types and constraints, the dependencies of
+/// derived fields and the *names* of their validator and derived functions. Schemas are exported to
+/// and imported from JSON or TOML, so they can live in configuration files, and
+/// are turned back into a registry by a [`Resolver`] that binds each name to a
+/// function.
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::schema::{Resolver, Schema};
+/// use soakit::Value;
+///
+/// let config = r#"
+///     [[fields]]
+///     name = "price"
+///     kind = "Float"
+///     validator = "non_negative"
+///
+///     constraints = [{ Range = { start = 0, end = 1000 } }]
+///
+///     [[fields]]
+///     name = "doubled"
+///     dependencies = ["price"]
+///     derived = "double"
+/// "#;
+/// let schema = Schema::from_toml(config).unwrap();
+///
+/// let resolver = Resolver::new()
+///     .with_validator("non_negative", |v: &Value| matches!(v, Value::ScalarFloat(x) if *x >= 0.0))
+///     .with_derived("double", |args: &[Value]| &args[0] + &args[0]);
+/// let registry = schema.build(&resolver).unwrap();
+///
+/// assert!(registry.validate("price", &Value::ScalarFloat(3.0)));
+/// assert!(!registry.validate("price", &Value::ScalarFloat(-3.0)));
+/// assert!(!registry.validate("price", &Value::ScalarInt(3)));
+/// assert!(!registry.validate("price", &Value::ScalarFloat(3000.0)));
+/// assert!(registry.get_metadata("doubled").unwrap().is_derived);
+/// ```
+use crate::constraint::Constraint;
+use crate::error::{Result, SoAKitError};
+use crate::meta::{FieldType, Registry};
+use crate::value::Value;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// A validator as taken by [`Registry::register`].
+type Validator = Box<dyn Fn(&Value) -> bool + Send + Sync>;
+
+/// A validator bound to a name in a [`Resolver`].
+type SharedValidator = Arc<dyn Fn(&Value) -> bool + Send + Sync>;
+
+/// A derived function bound to a name in a [`Resolver`].
+type SharedDerived = Arc<dyn Fn(&[Value]) -> Result<Value> + Send + Sync>;
+
+/// The serializable shape of a [`Registry`].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Schema {
+    /// The schema version; see [`Registry::schema_version`]
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub version: u32,
+    /// The fields, in the order they are registered
+    #[serde(default)]
+    pub fields: Vec<FieldSchema>,
+}
+
+/// The serializable description of one field of a [`Schema`].
+///
+/// A field with a `derived` function name is a derived field; every other field
+/// is stored. The registered validator accepts a value only if it passes both the
+/// `kind` check and the named `validator`, when they are given. A field with a
+/// `kind` and no `validator` is registered as by [`Registry::register_with_type`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FieldSchema {
+    /// Name of the field
+    pub name: String,
+    /// Declared type: values must be scalars of this type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<FieldType>,
+    /// Name of the validator function, bound by a [`Resolver`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validator: Option<String>,
+    /// Fields a derived field is computed from, in argument order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<String>,
+    /// Name of the derived function, bound by a [`Resolver`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub derived: Option<String>,
+    /// Whether the derived function is element-wise; see
+    /// [`Registry::register_chunk_safe`]
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub chunk_safe: bool,
+    /// Constraints added with [`Registry::add_constraint`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub constraints: Vec<Constraint>,
+    /// Default value set with [`Registry::set_default`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<Value>,
+    /// Aliases added with [`Registry::add_alias`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    /// Tags set with [`Registry::set_tag`]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>,
+    /// Whether the field is required; see [`Registry::set_required`]
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub required: bool,
+    /// Replacement hint of a deprecated field; see [`Registry::deprecate`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<String>,
+    /// Declared shape of element values; see [`Registry::set_shape`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shape: Option<Vec<usize>>,
+}
+
+/// Check whether a flag is unset, to leave it out of serialized schemas.
+const fn is_false(flag: &bool) -> bool {
+    !*flag
+}
+
+/// Check whether a version is the initial one, to leave it out of serialized schemas.
+const fn is_zero(version: &u32) -> bool {
+    *version == 0
+}
+
+impl FieldSchema {
+    /// Describe a stored field that accepts any value.
+    pub fn stored(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            kind: None,
+            validator: None,
+            dependencies: Vec::new(),
+            derived: None,
+            chunk_safe: false,
+            constraints: Vec::new(),
+            default: None,
+            aliases: Vec::new(),
+            tags: BTreeMap::new(),
+            required: false,
+            deprecated: None,
+            shape: None,
+        }
+    }
+
+    /// Describe a derived field.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the field
+    /// * `dependencies` - The fields passed to the derived function, in order
+    /// * `func` - The name of the derived function
+    pub fn derived(name: &str, dependencies: &[&str], func: &str) -> Self {
+        Self {
+            dependencies: dependencies.iter().map(|dep| dep.to_string()).collect(),
+            derived: Some(func.to_string()),
+            ..Self::stored(name)
+        }
+    }
+
+    /// Declare the type of the field's values.
+    pub const fn with_kind(mut self, kind: FieldType) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    /// Name the validator function of the field.
+    pub fn with_validator(mut self, name: &str) -> Self {
+        self.validator = Some(name.to_string());
+        self
+    }
+
+    /// Add a constraint on the field's values.
+    pub fn with_constraint(mut self, constraint: Constraint) -> Self {
+        self.constraints.push(constraint);
+        self
+    }
+
+    /// Declare the shape of the field's element values.
+    pub fn with_shape(mut self, shape: &[usize]) -> Self {
+        self.shape = Some(shape.to_vec());
+        self
+    }
+
+    /// Set the default value of the field.
+    pub fn with_default(mut self, value: Value) -> Self {
+        self.default = Some(value);
+        self
+    }
+
+    /// Add an alias of the field.
+    pub fn with_alias(mut self, alias: &str) -> Self {
+        self.aliases.push(alias.to_string());
+        self
+    }
+
+    /// Tag the field.
+    pub fn with_tag(mut self, key: &str, value: &str) -> Self {
+        let _ = self.tags.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Mark the field as required.
+    pub const fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    /// Mark the field as deprecated, with a hint naming its replacement.
+    pub fn with_deprecated(mut self, hint: &str) -> Self {
+        self.deprecated = Some(hint.to_string());
+        self
+    }
+
+    /// Build the validator registered for this field.
+    fn build_validator(&self, resolver: &Resolver) -> Result<Validator> {
+        let named = self
+            .validator
+            .as_deref()
+            .map(|name| resolver.validator(name))
+            .transpose()?;
+        let kind = self.kind;
+        Ok(Box::new(move |value: &Value| {
+            kind.is_none_or(|kind| kind.matches(value))
+                && named.as_ref().is_none_or(|validator| validator(value))
+        }))
+    }
+}
+
+impl Schema {
+    /// Create an empty schema.
+    pub const fn new() -> Self {
+        Self {
+            version: 0,
+            fields: Vec::new(),
+        }
+    }
+
+    /// Add a field to the schema.
+    pub fn with_field(mut self, field: FieldSchema) -> Self {
+        self.fields.push(field);
+        self
+    }
+
+    /// Describe the fields of a registry.
+    ///
+    /// The functions of a registry cannot be named, so the validator of every
+    /// untyped field and every derived function is named after its field; bind
+    /// those names in the [`Resolver`] used to rebuild the registry. Fields with a
+    /// [`FieldType`] are described by their type alone. Fields are listed after
+    /// their dependencies, in [`Registry::topological_order`]. Virtual fields are
+    /// left out, since their values come from outside any schema.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry to describe
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::schema::{FieldSchema, Schema};
+    /// use soakit::{FieldType, Registry, Value};
+    ///
+    /// let mut registry = Registry::new();
+    /// registry.register("tag".to_string(), Box::new(|_: &Value| true), false, vec![], None).unwrap();
+    /// registry.register_int("age").unwrap();
+    ///
+    /// let schema = Schema::from_registry(&registry);
+    /// assert_eq!(
+    ///     schema.fields,
+    ///     vec![
+    ///         FieldSchema::stored("age").with_kind(FieldType::Int),
+    ///         FieldSchema::stored("tag").with_validator("tag"),
+    ///     ]
+    /// );
+    /// ```
+    pub fn from_registry(registry: &Registry) -> Self {
+        let names = registry
+            .topological_order()
+            .unwrap_or_else(|_| registry.list_fields());
+        let fields = names
+            .into_iter()
+            .filter_map(|name| {
+                let metadata = registry.get_metadata(&name)?;
+                if metadata.is_virtual() {
+                    return None;
+                }
+                Some(FieldSchema {
+                    kind: metadata.field_type,
+                    validator: metadata.field_type.is_none().then(|| name.clone()),
+                    dependencies: metadata.dependencies.clone(),
+                    derived: metadata.is_derived.then(|| name.clone()),
+                    chunk_safe: metadata.chunk_safe,
+                    constraints: metadata.constraints.clone(),
+                    default: metadata.default.clone(),
+                    aliases: registry.aliases_of(&name),
+                    tags: metadata.tags.clone(),
+                    required: metadata.required,
+                    deprecated: metadata.deprecated.clone(),
+                    shape: metadata.shape.clone(),
+                    name,
+                })
+            })
+            .collect();
+        Self {
+            version: registry.schema_version(),
+            fields,
+        }
+    }
+
+    /// Register the schema's fields in a registry.
+    ///
+    /// Every function name is resolved before anything is registered, so an
+    /// unknown name leaves the registry unchanged. Fields registered before a
+    /// field that [`Registry::register`] rejects are kept; use [`Schema::build`]
+    /// to get a registry only if every field can be registered.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry to register the fields in
+    /// * `resolver` - The functions bound to the names used by the schema
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if a validator or derived function name is unknown
+    /// - The errors of [`Registry::register`] for the first field it rejects
+    pub fn register_into(&self, registry: &mut Registry, resolver: &Resolver) -> Result<()> {
+        let resolved = self
+            .fields
+            .iter()
+            .map(|field| {
+                let validator = field.build_validator(resolver)?;
+                let derived_func = field
+                    .derived
+                    .as_deref()
+                    .map(|name| resolver.derived_func(name))
+                    .transpose()?;
+                Ok((field, validator, derived_func))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        for (field, validator, derived_func) in resolved {
+            let name = field.name.clone();
+            let dependencies = field.dependencies.clone();
+            match derived_func {
+                Some(func) if field.chunk_safe => {
+                    registry.register_chunk_safe(name, validator, dependencies, func)?;
+                }
+                func => {
+                    let is_derived = func.is_some();
+                    registry.register(name, validator, is_derived, dependencies, func)?;
+                }
+            }
+            if let Some(kind) = field.kind
+                && field.validator.is_none()
+                && field.derived.is_none()
+            {
+                registry.set_field_type(&field.name, kind);
+            }
+            if let Some(shape) = &field.shape {
+                registry.set_shape(&field.name, shape)?;
+            }
+            for constraint in &field.constraints {
+                registry.add_constraint(&field.name, constraint.clone())?;
+            }
+            if let Some(default) = &field.default {
+                registry.set_default(&field.name, default.clone())?;
+            }
+            for alias in &field.aliases {
+                registry.add_alias(alias, &field.name)?;
+            }
+            for (key, value) in &field.tags {
+                registry.set_tag(&field.name, key, value)?;
+            }
+            if field.required {
+                registry.set_required(&field.name, true)?;
+            }
+            if let Some(hint) = &field.deprecated {
+                registry.deprecate(&field.name, hint)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Build a new registry from the schema.
+    ///
+    /// # Arguments
+    ///
+    /// * `resolver` - The functions bound to the names used by the schema
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Registry)` with every field of the schema registered, at the
+    /// schema's version.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Schema::register_into`].
+    pub fn build(&self, resolver: &Resolver) -> Result<Registry> {
+        let mut registry = Registry::new();
+        registry.set_schema_version(self.version);
+        self.register_into(&mut registry, resolver)?;
+        Ok(registry)
+    }
+
+    /// Serialize the schema to a JSON string.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SoAKitError::InvalidArgument`] if serialization fails.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(|e| SoAKitError::InvalidArgument(e.to_string()))
+    }
+
+    /// Deserialize a schema from a JSON string.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SoAKitError::InvalidArgument`] if the JSON is not a valid schema.
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|e| SoAKitError::InvalidArgument(e.to_string()))
+    }
+
+    /// Serialize the schema to a TOML string, with one `[[fields]]` table per field.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SoAKitError::InvalidArgument`] if serialization fails.
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string(self).map_err(|e| SoAKitError::InvalidArgument(e.to_string()))
+    }
+
+    /// Deserialize a schema from a TOML string.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SoAKitError::InvalidArgument`] if the TOML is not a valid schema.
+    pub fn from_toml(toml: &str) -> Result<Self> {
+        toml::from_str(toml).map_err(|e| SoAKitError::InvalidArgument(e.to_string()))
+    }
+}
+
+/// A Rust struct whose fields map onto typed registry fields.
+///
+/// Implemented by `#[derive(SoaSchema)]` with the `derive` feature, which also
+/// adds typed `get_<field>` and `set_<field>` functions to the struct. Each struct
+/// field becomes a field registered with [`Registry::register_typed`] under the
+/// same name; use [`Bulk::from_structs`](crate::Bulk::from_structs) to store
+/// records as columns.
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::{Bulk, Registry, Result, SoaSchema, Value};
+/// use std::collections::BTreeMap;
+///
+/// struct Person {
+///     age: i64,
+/// }
+///
+/// impl SoaSchema for Person {
+///     fn field_names() -> Vec<&'static str> {
+///         vec!["age"]
+///     }
+///
+///     fn register_fields(registry: &mut Registry) -> Result<()> {
+///         registry.register_typed::<i64>("age")
+///     }
+///
+///     fn into_columns(records: Vec<Self>) -> BTreeMap<String, Value> {
+///         let ages: Vec<i64> = records.into_iter().map(|p| p.age).collect();
+///         BTreeMap::from([("age".to_string(), Value::from(ages))])
+///     }
+/// }
+///
+/// let mut registry = Registry::new();
+/// Person::register_fields(&mut registry).unwrap();
+/// let bulk = Bulk::from_structs(&registry, vec![Person { age: 30 }, Person { age: 40 }]).unwrap();
+/// assert_eq!(bulk.get(&registry, "age").unwrap(), Value::VectorInt(vec![30, 40]));
+/// ```
+pub trait SoaSchema: Sized {
+    /// Get the names of the fields, in declaration order.
+    fn field_names() -> Vec<&'static str>;
+
+    /// Register a typed field for every struct field.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Registry::register`], such as
+    /// [`SoAKitError::FieldAlreadyExists`] if a field is already registered.
+    fn register_fields(registry: &mut Registry) -> Result<()>;
+
+    /// Split records into one column per field, keyed by field name.
+    fn into_columns(records: Vec<Self>) -> std::collections::BTreeMap<String, Value>;
+}
+
+/// Named validator and derived functions used to build a registry from a [`Schema`].
+#[derive(Clone, Default)]
+pub struct Resolver {
+    /// Validators by name
+    validators: HashMap<String, SharedValidator>,
+    /// Derived functions by name
+    derived: HashMap<String, SharedDerived>,
+}
+
+impl std::fmt::Debug for Resolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut validators: Vec<_> = self.validators.keys().collect();
+        let mut derived: Vec<_> = self.derived.keys().collect();
+        validators.sort();
+        derived.sort();
+        f.debug_struct("Resolver")
+            .field("validators", &validators)
+            .field("derived", &derived)
+            .finish()
+    }
+}
+
+impl Resolver {
+    /// Create a resolver with no functions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bind a validator to a name, replacing any validator of that name.
+    pub fn with_validator<F>(mut self, name: &str, func: F) -> Self
+    where
+        F: Fn(&Value) -> bool + Send + Sync + 'static,
+    {
+        let _ = self.validators.insert(name.to_string(), Arc::new(func));
+        self
+    }
+
+    /// Bind a derived function to a name, replacing any function of that name.
+    pub fn with_derived<F>(mut self, name: &str, func: F) -> Self
+    where
+        F: Fn(&[Value]) -> Result<Value> + Send + Sync + 'static,
+    {
+        let _ = self.derived.insert(name.to_string(), Arc::new(func));
+        self
+    }
+
+    /// Look up a validator by name.
+    fn validator(&self, name: &str) -> Result<SharedValidator> {
+        self.validators
+            .get(name)
+            .cloned()
+            .ok_or_else(|| SoAKitError::InvalidArgument(format!("Unknown validator: {}", name)))
+    }
+
+    /// Look up a derived function by name, boxed for registration.
+    fn derived_func(&self, name: &str) -> Result<crate::meta::DerivedFunc> {
+        let func = self.derived.get(name).cloned().ok_or_else(|| {
+            SoAKitError::InvalidArgument(format!("Unknown derived function: {}", name))
+        })?;
+        Ok(Box::new(move |args: &[Value]| func(args)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bulk::Bulk;
+
+    fn resolver() -> Resolver {
+        Resolver::new()
+            .with_validator(
+                "positive",
+                |v: &Value| matches!(v, Value::ScalarInt(n) if *n > 0),
+            )
+            .with_validator("any", |_: &Value| true)
+            .with_derived("double", |args: &[Value]| &args[0] + &args[0])
+    }
+
+    #[test]
+    fn test_schema_round_trips_through_json_and_toml() {
+        let schema = Schema::new()
+            .with_field(
+                FieldSchema::stored("n")
+                    .with_kind(FieldType::Int)
+                    .with_validator("positive")
+                    .with_default(Value::ScalarInt(1)),
+            )
+            .with_field(
+                FieldSchema::stored("name")
+                    .with_kind(FieldType::String)
+                    .with_constraint(Constraint::regex("^[A-Z]").unwrap())
+                    .with_constraint(Constraint::OneOf(vec![Value::from("Ann".to_string())]))
+                    .with_tag("pii", "true"),
+            )
+            .with_field(FieldSchema::stored("position").with_shape(&[3]))
+            .with_field(FieldSchema {
+                chunk_safe: true,
+                ..FieldSchema::derived("n2", &["n"], "double")
+            });
+
+        assert_eq!(
+            Schema::from_json(&schema.to_json().unwrap()).unwrap(),
+            schema
+        );
+        let toml = schema.to_toml().unwrap();
+        assert!(toml.contains("[[fields]]"));
+        assert_eq!(Schema::from_toml(&toml).unwrap(), schema);
+
+        assert!(Schema::from_json("{\"fields\": [{}]}").is_err());
+        assert_eq!(Schema::from_json("{}").unwrap(), Schema::new());
+    }
+
+    #[test]
+    fn test_build_binds_named_functions() {
+        let schema = Schema::new()
+            .with_field(
+                FieldSchema::stored("n")
+                    .with_kind(FieldType::Int)
+                    .with_validator("positive"),
+            )
+            .with_field(
+                FieldSchema::stored("label")
+                    .with_kind(FieldType::String)
+                    .with_alias("tag"),
+            )
+            .with_field(FieldSchema::derived("n2", &["n"], "double"));
+        let registry = schema.build(&resolver()).unwrap();
+        assert_eq!(registry.resolve("tag"), "label");
+        assert_eq!(
+            Schema::from_registry(&registry).fields[0].aliases,
+            vec!["tag"]
+        );
+
+        let field = FieldSchema::stored("x")
+            .with_tag("units", "m")
+            .required()
+            .with_deprecated("use y");
+        let tagged = Schema {
+            version: 4,
+            ..Schema::new().with_field(field)
+        };
+        let tagged_registry = tagged.build(&resolver()).unwrap();
+        assert_eq!(tagged_registry.tag("x", "units"), Some("m"));
+        assert!(tagged_registry.is_required("x"));
+        assert_eq!(tagged_registry.schema_version(), 4);
+        let exported = Schema::from_registry(&tagged_registry);
+        assert_eq!(exported.fields[0].tags, tagged.fields[0].tags);
+        assert!(exported.fields[0].required);
+        assert_eq!(exported.version, 4);
+        assert_eq!(exported.fields[0].deprecated.as_deref(), Some("use y"));
+        assert!(
+            exported
+                .to_json()
+                .unwrap()
+                .contains("\"deprecated\": \"use y\"")
+        );
+        assert_eq!(Schema::from_registry(&registry).fields[0].default, None);
+
+        let defaulted = Schema::new().with_field(
+            FieldSchema::stored("n")
+                .with_validator("positive")
+                .with_default(Value::ScalarInt(-1)),
+        );
+        assert!(matches!(
+            defaulted.build(&resolver()),
+            Err(SoAKitError::ValidationFailed(_))
+        ));
+
+        assert!(registry.validate("n", &Value::ScalarInt(1)));
+        assert!(!registry.validate("n", &Value::ScalarInt(-1)));
+        assert!(registry.validate("label", &Value::ScalarString("a".to_string())));
+        assert!(!registry.validate("label", &Value::VectorString(vec![])));
+
+        let bulk = Bulk::new(2)
+            .unwrap()
+            .set(
+                &registry,
+                "n",
+                vec![Value::ScalarInt(1), Value::ScalarInt(3)],
+            )
+            .unwrap();
+        assert_eq!(
+            bulk.get(&registry, "n2").unwrap(),
+            Value::VectorInt(vec![2, 6])
+        );
+    }
+
+    #[test]
+    fn test_unknown_names_leave_registry_unchanged() {
+        let schema = Schema::new()
+            .with_field(FieldSchema::stored("a"))
+            .with_field(FieldSchema::derived("b", &["a"], "missing"));
+        let mut registry = Registry::new();
+        assert!(matches!(
+            schema.register_into(&mut registry, &resolver()),
+            Err(SoAKitError::InvalidArgument(_))
+        ));
+        assert!(registry.is_empty());
+
+        let schema = Schema::new().with_field(FieldSchema::stored("a").with_validator("missing"));
+        assert!(schema.build(&resolver()).is_err());
+    }
+
+    #[test]
+    fn test_from_registry_names_functions_after_fields() {
+        let original = Schema::new()
+            .with_field(FieldSchema::derived("n2", &["n"], "double"))
+            .with_field(FieldSchema::stored("n").with_validator("positive"))
+            .with_field(
+                FieldSchema::stored("x")
+                    .with_kind(FieldType::Float)
+                    .with_constraint(Constraint::Range(0..=1)),
+            )
+            .with_field(
+                FieldSchema::stored("y")
+                    .with_kind(FieldType::Int)
+                    .with_validator("positive"),
+            );
+        let registry = original.build(&resolver()).unwrap();
+        assert_eq!(
+            registry.get_metadata("x").unwrap().field_type,
+            Some(FieldType::Float)
+        );
+        assert_eq!(registry.get_metadata("y").unwrap().field_type, None);
+        assert!(!registry.validate("x", &Value::ScalarFloat(1.5)));
+
+        let schema = Schema::from_registry(&registry);
+        assert_eq!(
+            schema.fields,
+            vec![
+                FieldSchema::stored("n").with_validator("n"),
+                FieldSchema::derived("n2", &["n"], "n2").with_validator("n2"),
+                FieldSchema::stored("x")
+                    .with_kind(FieldType::Float)
+                    .with_constraint(Constraint::Range(0..=1)),
+                FieldSchema::stored("y").with_validator("y"),
+            ]
+        );
+
+        let resolver = Resolver::new()
+            .with_validator("n", |v: &Value| matches!(v, Value::ScalarInt(_)))
+            .with_validator("n2", |_: &Value| true)
+            .with_validator("y", |v: &Value| matches!(v, Value::ScalarInt(n) if *n > 0))
+            .with_derived("n2", |args: &[Value]| &args[0] + &args[0]);
+        let rebuilt = schema.build(&resolver).unwrap();
+        assert_eq!(
+            rebuilt.topological_order().unwrap(),
+            registry.topological_order().unwrap()
+        );
+    }
+}
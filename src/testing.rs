@@ -0,0 +1,309 @@
+/// Property-testing generators for SoAKit types.
+///
+/// This module is available with the `testing` feature. It provides
+/// [`proptest`] strategies and [`Arbitrary`] implementations for [`Value`] and
+/// [`ValueKind`], plus [`ArbitrarySchema`], a generated set of typed fields that
+/// builds a [`Registry`] and populated [`Bulk`]s matching it.
+///
+/// Generated floats are always finite, so generated values compare equal to
+/// themselves and survive binary round trips unchanged.
+///
+/// # Examples
+///
+/// ```rust
+/// use proptest::prelude::*;
+/// use soakit::testing::ArbitrarySchema;
+/// use soakit::Bulk;
+///
+/// proptest! {
+///     fn binary_round_trip((schema, bulk) in ArbitrarySchema::with_bulk(1..50)) {
+///         let registry = schema.registry().unwrap();
+///         let restored = Bulk::from_binary(&bulk.to_binary().unwrap()).unwrap();
+///         for field in schema.fields.keys() {
+///             prop_assert_eq!(restored.get(&registry, field).unwrap(), bulk.get(&registry, field).unwrap());
+///         }
+///     }
+/// }
+///
+/// binary_round_trip();
+/// ```
+use crate::bulk::Bulk;
+use crate::error::Result;
+use crate::meta::Registry;
+use crate::value::{Value, ValueKind};
+use proptest::arbitrary::{Arbitrary, any};
+use proptest::collection::{SizeRange, btree_map, vec};
+use proptest::prop_oneof;
+use proptest::strategy::{BoxedStrategy, Just, Strategy};
+use std::collections::BTreeMap;
+
+/// Strategy for finite `f64`s of any sign and magnitude, including zero.
+pub fn finite_f64() -> BoxedStrategy<f64> {
+    use proptest::num::f64::{NEGATIVE, NORMAL, POSITIVE, SUBNORMAL, ZERO};
+    (POSITIVE | NEGATIVE | NORMAL | SUBNORMAL | ZERO).boxed()
+}
+
+/// Strategy for short printable strings.
+pub fn short_string() -> BoxedStrategy<String> {
+    "\\PC{0,8}".boxed()
+}
+
+/// Strategy for scalar values of one kind.
+///
+/// # Arguments
+///
+/// * `kind` - The kind of the generated scalars
+///
+/// # Examples
+///
+/// ```rust
+/// use proptest::prelude::*;
+/// use soakit::testing::scalar;
+/// use soakit::ValueKind;
+///
+/// proptest! {
+///     fn ints_are_scalars(value in scalar(ValueKind::Int)) {
+///         prop_assert!(value.is_scalar());
+///         prop_assert_eq!(value.kind(), Some(ValueKind::Int));
+///     }
+/// }
+///
+/// ints_are_scalars();
+/// ```
+pub fn scalar(kind: ValueKind) -> BoxedStrategy<Value> {
+    match kind {
+        ValueKind::Int => any::<i64>().prop_map(Value::ScalarInt).boxed(),
+        ValueKind::Float => finite_f64().prop_map(Value::ScalarFloat).boxed(),
+        ValueKind::Bool => any::<bool>().prop_map(Value::ScalarBool).boxed(),
+        ValueKind::String => short_string().prop_map(Value::ScalarString).boxed(),
+    }
+}
+
+/// Strategy for vector values of one kind.
+///
+/// # Arguments
+///
+/// * `kind` - The kind of the vector elements
+/// * `len` - The range of vector lengths
+pub fn vector(kind: ValueKind, len: impl Into<SizeRange>) -> BoxedStrategy<Value> {
+    let len = len.into();
+    match kind {
+        ValueKind::Int => vec(any::<i64>(), len).prop_map(Value::VectorInt).boxed(),
+        ValueKind::Float => vec(finite_f64(), len).prop_map(Value::VectorFloat).boxed(),
+        ValueKind::Bool => vec(any::<bool>(), len).prop_map(Value::VectorBool).boxed(),
+        ValueKind::String => vec(short_string(), len)
+            .prop_map(Value::VectorString)
+            .boxed(),
+    }
+}
+
+/// Strategy for option vectors of one kind, with about half of the elements missing.
+///
+/// # Arguments
+///
+/// * `kind` - The kind of the present elements
+/// * `len` - The range of vector lengths
+pub fn option_vector(kind: ValueKind, len: impl Into<SizeRange>) -> BoxedStrategy<Value> {
+    vec(prop_oneof![scalar(kind), Just(Value::Null)], len)
+        .prop_filter_map("not an option vector", |scalars| {
+            Value::from_scalars(scalars)
+                .ok()?
+                .to_sparse(&Value::Null)
+                .ok()
+        })
+        .boxed()
+}
+
+/// Strategy for rectangular matrices whose rows are vectors of one kind.
+///
+/// # Arguments
+///
+/// * `kind` - The kind of the matrix elements
+/// * `rows` - The range of row counts
+/// * `cols` - The range of row lengths; every row of one matrix has the same length
+pub fn matrix(
+    kind: ValueKind,
+    rows: impl Into<SizeRange>,
+    cols: impl Into<SizeRange>,
+) -> BoxedStrategy<Value> {
+    let rows = rows.into();
+    let (min_cols, max_cols) = cols.into().start_end_incl();
+    (min_cols..=max_cols)
+        .prop_flat_map(move |cols| vec(vector(kind, cols), rows.clone()))
+        .prop_map(Value::Matrix)
+        .boxed()
+}
+
+/// Strategy for tensors with a shape-consistent data buffer.
+///
+/// # Arguments
+///
+/// * `rank` - The range of tensor ranks
+/// * `dim` - The range of sizes of each dimension
+pub fn tensor(rank: impl Into<SizeRange>, dim: impl Into<SizeRange>) -> BoxedStrategy<Value> {
+    let (min_dim, max_dim) = dim.into().start_end_incl();
+    vec(min_dim..=max_dim, rank)
+        .prop_flat_map(|shape| {
+            let len = shape.iter().try_fold(1_usize, |acc, &d| acc.checked_mul(d));
+            (vec(finite_f64(), len.unwrap_or(0)), Just(shape))
+        })
+        .prop_map(|(data, shape)| Value::Tensor { data, shape })
+        .boxed()
+}
+
+impl Arbitrary for ValueKind {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        prop_oneof![
+            Just(ValueKind::Int),
+            Just(ValueKind::Float),
+            Just(ValueKind::Bool),
+            Just(ValueKind::String),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for Value {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    /// Generates scalars, vectors, option vectors, rectangular matrices and tensors
+    /// of every kind.
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        prop_oneof![
+            any::<ValueKind>().prop_flat_map(scalar),
+            any::<ValueKind>().prop_flat_map(|kind| vector(kind, 0..8)),
+            any::<ValueKind>().prop_flat_map(|kind| option_vector(kind, 0..8)),
+            any::<ValueKind>().prop_flat_map(|kind| matrix(kind, 1..4, 1..4)),
+            tensor(1..=3, 1..4),
+        ]
+        .boxed()
+    }
+}
+
+/// A generated set of scalar fields, each with a fixed [`ValueKind`].
+///
+/// Field names are lowercase identifiers and never start with `_`, so they never
+/// clash with system fields.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArbitrarySchema {
+    /// Field names and the kind of their scalar values
+    pub fields: BTreeMap<String, ValueKind>,
+}
+
+impl ArbitrarySchema {
+    /// Build a registry with one field per schema entry.
+    ///
+    /// Each field's validator accepts only scalars of the field's kind.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a field cannot be registered.
+    pub fn registry(&self) -> Result<Registry> {
+        let mut registry = Registry::new();
+        for (name, &kind) in &self.fields {
+            let validator = Box::new(move |v: &Value| v.is_scalar() && v.kind() == Some(kind));
+            registry.register(name.clone(), validator, false, vec![], None)?;
+        }
+        Ok(registry)
+    }
+
+    /// Strategy for bulks with every field of this schema set.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - The range of element counts; a count of 0 is raised to 1
+    pub fn bulk(&self, count: impl Into<SizeRange>) -> BoxedStrategy<Bulk> {
+        let (min_count, max_count) = count.into().start_end_incl();
+        let schema = self.clone();
+        let fields = self.fields.clone();
+        (min_count.max(1)..=max_count.max(1))
+            .prop_flat_map(move |count| {
+                let columns: Vec<_> = fields
+                    .iter()
+                    .map(|(name, &kind)| (Just(name.clone()), vec(scalar(kind), count)))
+                    .collect();
+                (Just(count), columns)
+            })
+            .prop_filter_map("bulk construction failed", move |(count, columns)| {
+                populate(&schema, count, columns).ok()
+            })
+            .boxed()
+    }
+
+    /// Strategy for a schema together with a bulk populated according to it.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - The range of element counts; a count of 0 is raised to 1
+    pub fn with_bulk(count: impl Into<SizeRange>) -> BoxedStrategy<(Self, Bulk)> {
+        let count = count.into();
+        any::<Self>()
+            .prop_flat_map(move |schema| (Just(schema.clone()), schema.bulk(count.clone())))
+            .boxed()
+    }
+}
+
+impl Arbitrary for ArbitrarySchema {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        btree_map("[a-z][a-z0-9_]{0,7}", any::<ValueKind>(), 1..=4)
+            .prop_map(|fields| Self { fields })
+            .boxed()
+    }
+}
+
+/// Build a bulk of `count` elements from generated columns of `schema`.
+fn populate(
+    schema: &ArbitrarySchema,
+    count: usize,
+    columns: Vec<(String, Vec<Value>)>,
+) -> Result<Bulk> {
+    let registry = schema.registry()?;
+    let mut bulk = Bulk::new(count)?;
+    for (name, values) in columns {
+        bulk = bulk.set(&registry, &name, values)?;
+    }
+    Ok(bulk)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn test_generated_values_are_consistent(value in any::<Value>()) {
+            if let Value::Tensor { data, shape } = &value {
+                prop_assert_eq!(data.len(), shape.iter().product::<usize>());
+            }
+            let bytes = bincode::serialize(&value).unwrap();
+            let restored: Value = bincode::deserialize(&bytes).unwrap();
+            prop_assert_eq!(restored, value);
+        }
+
+        #[test]
+        fn test_generated_values_round_trip_through_json(value in any::<Value>()) {
+            let json = serde_json::to_string(&value).unwrap();
+            let restored: Value = serde_json::from_str(&json).unwrap();
+            prop_assert_eq!(restored, value);
+        }
+
+        #[test]
+        fn test_generated_bulk_matches_schema((schema, bulk) in ArbitrarySchema::with_bulk(1..20)) {
+            let registry = schema.registry().unwrap();
+            prop_assert_eq!(bulk.list_data_fields(), schema.fields.keys().cloned().collect::<Vec<_>>());
+            for (name, kind) in &schema.fields {
+                let column = bulk.get(&registry, name).unwrap();
+                prop_assert_eq!(column.len(), bulk.count());
+                prop_assert_eq!(column.kind(), Some(*kind));
+            }
+        }
+    }
+}
@@ -0,0 +1,226 @@
+/// Soft deletion with tombstones.
+///
+/// [`Bulk::mark_deleted`] flags elements as deleted without moving any data.
+/// Deleted elements keep their place, so every operation sees them until it
+/// opts out: [`Bulk::live`] gives the bulk without them, for use with
+/// [`Bulk::get`], [`Bulk::partition_by`], [`Bulk::iter_rows`], the aggregations
+/// and everything else, and [`Bulk::compact`] drops them for good.
+///
+/// Tombstones are kept by element ID in [`Meta::deleted`](crate::bulk::Meta::deleted),
+/// so they follow their elements through sorting, filtering and merges.
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::{Bulk, Registry, Value};
+///
+/// let mut registry = Registry::new();
+/// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+/// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+///
+/// let bulk = Bulk::new(3).unwrap();
+/// let bulk = bulk.set(&registry, "age", vec![
+///     Value::ScalarInt(10),
+///     Value::ScalarInt(20),
+///     Value::ScalarInt(30),
+/// ]).unwrap();
+/// let bulk = bulk.mark_deleted(&[false, true, false]).unwrap();
+///
+/// // Deleted elements are still stored...
+/// assert_eq!(bulk.count(), 3);
+/// assert_eq!(bulk.deleted_count(), 1);
+/// // ...until an operation skips them
+/// let live = bulk.live().unwrap();
+/// assert_eq!(live.sum_field(&registry, "age").unwrap(), Value::ScalarInt(40));
+/// assert_eq!(bulk.compact(&registry).unwrap().count(), 2);
+/// ```
+use crate::bulk::Bulk;
+use crate::error::{Result, SoAKitError};
+use std::borrow::Cow;
+
+impl Bulk {
+    /// Mark elements as deleted (immutable update).
+    ///
+    /// Elements already marked stay marked. No field data changes, so versions
+    /// and derived caches are unaffected.
+    ///
+    /// # Arguments
+    ///
+    /// * `mask` - Boolean array with one entry per element; `true` marks the element
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with the elements marked.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::LengthMismatch`] if the mask length doesn't match the bulk count
+    pub fn mark_deleted(&self, mask: &[bool]) -> Result<Self> {
+        let mut new_bulk = self.clone();
+        new_bulk.mark_deleted_mut(mask)?;
+        Ok(new_bulk)
+    }
+
+    /// Mark elements as deleted, in place.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::mark_deleted`].
+    pub fn mark_deleted_mut(&mut self, mask: &[bool]) -> Result<()> {
+        let ids = self.masked_ids(mask)?;
+        self.meta.deleted.extend(ids);
+        Ok(())
+    }
+
+    /// Clear the deletion mark of elements (immutable update).
+    ///
+    /// # Arguments
+    ///
+    /// * `mask` - Boolean array with one entry per element; `true` restores the element
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with the elements no longer marked.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::LengthMismatch`] if the mask length doesn't match the bulk count
+    pub fn undelete(&self, mask: &[bool]) -> Result<Self> {
+        let ids = self.masked_ids(mask)?;
+        let mut new_bulk = self.clone();
+        for id in ids {
+            let _ = new_bulk.meta.deleted.remove(&id);
+        }
+        Ok(new_bulk)
+    }
+
+    /// Check whether the element at an index is marked as deleted.
+    ///
+    /// Returns `false` for an index out of bounds.
+    pub fn is_deleted(&self, idx: usize) -> bool {
+        self.meta
+            .id
+            .get(idx)
+            .is_some_and(|id| self.meta.deleted.contains(id))
+    }
+
+    /// Get the number of elements marked as deleted.
+    pub fn deleted_count(&self) -> usize {
+        self.live_mask().iter().filter(|&&live| !live).count()
+    }
+
+    /// Get a mask with `true` for every element not marked as deleted.
+    ///
+    /// The mask can be passed to operations taking one, such as [`Bulk::apply`].
+    pub fn live_mask(&self) -> Vec<bool> {
+        self.meta
+            .id
+            .iter()
+            .map(|id| !self.meta.deleted.contains(id))
+            .collect()
+    }
+
+    /// Get the bulk without the elements marked as deleted.
+    ///
+    /// If no element is marked, the bulk itself is borrowed; otherwise the live
+    /// elements are copied into a new bulk as by [`Bulk::remove_where`].
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Cow<Bulk>)` holding only live elements.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if every element is marked as deleted
+    pub fn live(&self) -> Result<Cow<'_, Self>> {
+        let deleted: Vec<bool> = self.live_mask().iter().map(|&live| !live).collect();
+        if deleted.contains(&true) {
+            self.remove_where(&deleted).map(Cow::Owned)
+        } else {
+            Ok(Cow::Borrowed(self))
+        }
+    }
+
+    /// Get the IDs of the elements selected by a mask.
+    fn masked_ids(&self, mask: &[bool]) -> Result<Vec<usize>> {
+        if mask.len() != self.meta.count {
+            return Err(SoAKitError::LengthMismatch {
+                expected: self.meta.count,
+                actual: mask.len(),
+            });
+        }
+        Ok(self
+            .meta
+            .id
+            .iter()
+            .zip(mask)
+            .filter(|&(_, &selected)| selected)
+            .map(|(&id, _)| id)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bulk::SortOrder;
+    use crate::meta::Registry;
+    use crate::value::Value;
+
+    #[test]
+    fn test_tombstones_follow_elements_until_compacted() {
+        let mut registry = Registry::new();
+        let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("n".to_string(), ints, false, vec![], None)
+            .unwrap();
+        let values = (0..5).map(Value::ScalarInt).collect();
+        let bulk = Bulk::new(5).unwrap().set(&registry, "n", values).unwrap();
+        assert!(matches!(bulk.live().unwrap(), Cow::Borrowed(_)));
+
+        let bulk = bulk
+            .mark_deleted(&[true, false, false, true, false])
+            .unwrap();
+        let bulk = bulk
+            .mark_deleted(&[false, false, false, true, false])
+            .unwrap();
+        assert_eq!(bulk.deleted_count(), 2);
+        assert_eq!(
+            bulk.get(&registry, "n").unwrap(),
+            Value::VectorInt(vec![0, 1, 2, 3, 4])
+        );
+        let live = bulk.live().unwrap();
+        assert_eq!(
+            live.get(&registry, "n").unwrap(),
+            Value::VectorInt(vec![1, 2, 4])
+        );
+        assert_eq!(live.partition_by(&registry, "n").unwrap().len(), 3);
+        assert_eq!(live.iter_rows(&registry).unwrap().count(), 3);
+
+        // Tombstones follow their elements through a sort and a filter
+        let sorted = bulk.sort_by(&registry, &[("n", SortOrder::Desc)]).unwrap();
+        assert_eq!(sorted.live_mask(), vec![true, false, true, true, false]);
+        let head = sorted.head(2).unwrap();
+        assert!(head.is_deleted(1) && !head.is_deleted(0) && !head.is_deleted(2));
+
+        let restored = bulk.undelete(&[true, false, false, false, false]).unwrap();
+        assert_eq!(restored.live_mask(), vec![true, true, true, false, true]);
+        let saved = Bulk::from_binary(&bulk.to_binary().unwrap()).unwrap();
+        assert_eq!(saved.live_mask(), bulk.live_mask());
+
+        let compacted = bulk.compact(&registry).unwrap();
+        assert_eq!(compacted.meta.id, vec![1, 2, 4]);
+        assert_eq!(compacted.deleted_count(), 0);
+        assert!(matches!(
+            bulk.mark_deleted(&[true; 5]).unwrap().compact(&registry),
+            Err(SoAKitError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            bulk.mark_deleted(&[true]),
+            Err(SoAKitError::LengthMismatch {
+                expected: 5,
+                actual: 1
+            })
+        ));
+    }
+}
@@ -0,0 +1,401 @@
+/// Transactional batch updates.
+///
+/// [`Bulk::transaction`] runs a closure that stages updates through a
+/// [`Transaction`]. Each update is validated when it is staged, and later updates
+/// see the values staged by earlier ones. If the closure succeeds, the staged
+/// fields are stored into a single copy of the bulk, each with one version bump;
+/// if it fails, nothing is stored. Fields may be named by an alias (see
+/// [`Registry::add_alias`]), as with [`Bulk::get`] and [`Bulk::set`].
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::{Bulk, Registry, Value};
+///
+/// let mut registry = Registry::new();
+/// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+/// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
+///
+/// let bulk = Bulk::new(2).unwrap();
+/// let bulk = bulk.set(&registry, "age", vec![Value::ScalarInt(25); 2]).unwrap();
+///
+/// let updated = bulk.transaction(&registry, |tx| {
+///     tx.set_element("age", 0, Value::ScalarInt(30))?;
+///     tx.apply(&["age"], &[], |ages| {
+///         Ok(ages.iter().map(|a| match a {
+///             Value::ScalarInt(a) => Value::ScalarInt(a + 1),
+///             other => other.clone(),
+///         }).collect())
+///     })
+/// }).unwrap();
+/// assert_eq!(updated.get(&registry, "age").unwrap(), Value::VectorInt(vec![31, 26]));
+/// assert_eq!(updated.meta.versions["age"], 2);
+///
+/// // A failing transaction leaves the bulk unchanged
+/// let failed = bulk.transaction(&registry, |tx| {
+///     tx.set_element("age", 0, Value::ScalarInt(40))?;
+///     tx.set_element("age", 1, Value::ScalarString("old".to_string()))
+/// });
+/// assert!(failed.is_err());
+/// ```
+use crate::bulk::{Bulk, CHUNK_SIZE, chunk_of};
+use crate::error::{Result, SoAKitError};
+use crate::meta::Registry;
+use crate::value::Value;
+use std::collections::BTreeMap;
+
+/// Updates staged against a bulk by [`Bulk::transaction`].
+pub struct Transaction<'a> {
+    /// The bulk being updated
+    base: &'a Bulk,
+    /// The registry containing field metadata
+    registry: &'a Registry,
+    /// The staged values of each updated field, one per element
+    staged: BTreeMap<String, Vec<Value>>,
+}
+
+impl Transaction<'_> {
+    /// Get the values of a stored field, including staged updates.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::FieldNotFound`] if the field is neither staged nor stored
+    pub fn get(&self, field: &str) -> Result<Value> {
+        let field = self.registry.resolve(field);
+        self.registry.notify_access(field);
+        self.current(field)
+    }
+
+    /// Stage new values for a field, as by [`Bulk::set`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::set`].
+    pub fn set(&mut self, field: &str, values: Vec<Value>) -> Result<()> {
+        let field = self.registry.resolve(field);
+        self.registry.notify_access(field);
+        self.base.check_values(self.registry, field, &values)?;
+        let _ = self.staged.insert(field.to_string(), values);
+        Ok(())
+    }
+
+    /// Stage a new value for one element of a field, as by [`Bulk::set_element`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::set_element`].
+    pub fn set_element(&mut self, field: &str, idx: usize, value: Value) -> Result<()> {
+        let field = self.registry.resolve(field);
+        self.registry.notify_access(field);
+        if !self.registry.has_field(field) {
+            return Err(SoAKitError::FieldNotFound(field.to_string()));
+        }
+        self.registry.check_value(field, &value)?;
+        let count = self.base.count();
+        let values = self.values_mut(field)?;
+        let slot = values.get_mut(idx).ok_or(SoAKitError::IndexOutOfBounds {
+            index: idx,
+            max: count,
+        })?;
+        if !slot.is_null() && !value.is_null() && slot.len() != value.len() {
+            return Err(SoAKitError::InvalidArgument(format!(
+                "Value at index {} has different length",
+                idx
+            )));
+        }
+        *slot = value;
+        Ok(())
+    }
+
+    /// Stage a transformation of a masked subset of chosen fields, as by
+    /// [`Bulk::apply_fields`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::apply_fields`].
+    pub fn apply<F>(&mut self, fields: &[&str], mask: &[bool], func: F) -> Result<()>
+    where
+        F: Fn(&[Value]) -> Result<Vec<Value>>,
+    {
+        let mask = self.base.normalize_mask(mask)?;
+        // Transform every field before staging any
+        let mut updates = Vec::with_capacity(fields.len());
+        for &field in fields {
+            let field = self.registry.resolve(field);
+            self.registry.notify_access(field);
+            if !self.registry.has_field(field) {
+                return Err(SoAKitError::FieldNotFound(field.to_string()));
+            }
+            let mut values = match self.staged.get(field) {
+                Some(values) => values.clone(),
+                None => self.current(field)?.to_scalars(),
+            };
+            let subset: Vec<Value> = values
+                .iter()
+                .zip(&mask)
+                .filter(|&(_, &selected)| selected)
+                .map(|(value, _)| value.clone())
+                .collect();
+            let new_subset = func(&subset)?;
+            if new_subset.len() != subset.len() {
+                return Err(SoAKitError::LengthMismatch {
+                    expected: subset.len(),
+                    actual: new_subset.len(),
+                });
+            }
+            for value in &new_subset {
+                self.registry.check_value(field, value)?;
+            }
+            let selected = values
+                .iter_mut()
+                .zip(&mask)
+                .filter(|&(_, &selected)| selected);
+            for ((slot, _), value) in selected.zip(new_subset) {
+                *slot = value;
+            }
+            updates.push((field.to_string(), values));
+        }
+        self.staged.extend(updates);
+        Ok(())
+    }
+
+    /// Get the names of the fields with staged updates, in sorted order.
+    pub fn staged_fields(&self) -> Vec<String> {
+        self.staged.keys().cloned().collect()
+    }
+
+    /// Get the values of a field named by its field name, not an alias,
+    /// including staged updates.
+    fn current(&self, field: &str) -> Result<Value> {
+        match self.staged.get(field) {
+            Some(values) => Value::from_scalars(values.clone()),
+            None => self
+                .stored(field)
+                .and_then(|_| self.base.get_resolved(self.registry, field)),
+        }
+    }
+
+    /// Check that a field is stored in the bulk.
+    fn stored(&self, field: &str) -> Result<()> {
+        if self
+            .base
+            .list_data_fields()
+            .iter()
+            .any(|name| name == field)
+        {
+            Ok(())
+        } else {
+            Err(SoAKitError::FieldNotFound(field.to_string()))
+        }
+    }
+
+    /// Get the staged values of a field, staging its stored values first.
+    fn values_mut(&mut self, field: &str) -> Result<&mut Vec<Value>> {
+        if !self.staged.contains_key(field) {
+            let values = self.current(field)?.to_scalars();
+            let _ = self.staged.insert(field.to_string(), values);
+        }
+        self.staged
+            .get_mut(field)
+            .ok_or_else(|| SoAKitError::FieldNotFound(field.to_string()))
+    }
+}
+
+impl Bulk {
+    /// Stage several updates and store them together (immutable update).
+    ///
+    /// `func` stages updates through the [`Transaction`] it is given. If it
+    /// returns `Ok`, every staged field is stored into a single copy of the bulk,
+    /// with one version bump per field and dependent derived caches invalidated.
+    /// If it returns an error, nothing is stored.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry containing field metadata
+    /// * `func` - Function staging the updates
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Bulk)` with every staged update stored.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error returned by `func`, such as a failed validation.
+    pub fn transaction<F>(&self, registry: &Registry, func: F) -> Result<Self>
+    where
+        F: FnOnce(&mut Transaction<'_>) -> Result<()>,
+    {
+        let staged = self.stage(registry, func)?;
+        let mut new_bulk = self.clone();
+        new_bulk.commit(registry, staged)?;
+        Ok(new_bulk)
+    }
+
+    /// Stage several updates and store them together, in place.
+    ///
+    /// This is the mutating counterpart of [`Bulk::transaction`]. If an error is
+    /// returned, `self` is left unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Bulk::transaction`].
+    pub fn transaction_mut<F>(&mut self, registry: &Registry, func: F) -> Result<()>
+    where
+        F: FnOnce(&mut Transaction<'_>) -> Result<()>,
+    {
+        let staged = self.stage(registry, func)?;
+        self.commit(registry, staged)
+    }
+
+    /// Run `func` on a new transaction and return its staged values.
+    fn stage<F>(&self, registry: &Registry, func: F) -> Result<BTreeMap<String, Vec<Value>>>
+    where
+        F: FnOnce(&mut Transaction<'_>) -> Result<()>,
+    {
+        let mut tx = Transaction {
+            base: self,
+            registry,
+            staged: BTreeMap::new(),
+        };
+        func(&mut tx)?;
+        Ok(tx.staged)
+    }
+
+    /// Store staged values, one version bump per field.
+    fn commit(&mut self, registry: &Registry, staged: BTreeMap<String, Vec<Value>>) -> Result<()> {
+        // Build every column before storing any, so an error leaves `self` unchanged
+        let bounds = (0..self.meta.count.div_ceil(CHUNK_SIZE))
+            .map(|i| self.chunk_bounds(i))
+            .collect::<Result<Vec<_>>>()?;
+        let columns = staged
+            .iter()
+            .map(|(field, values)| {
+                let columns = bounds
+                    .iter()
+                    .map(|&(start, end)| chunk_of(values, start, end))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok((field, columns))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        for (field, columns) in columns {
+            let mut columns = columns.into_iter();
+            self.set_chunks(registry, field, |_, _| {
+                columns
+                    .next()
+                    .ok_or_else(|| SoAKitError::FieldNotFound(field.clone()))
+            })?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transaction_commits_or_rolls_back() {
+        let mut registry = Registry::new();
+        let ints = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
+        registry
+            .register("a".to_string(), ints.clone(), false, vec![], None)
+            .unwrap();
+        registry
+            .register("b".to_string(), ints, false, vec![], None)
+            .unwrap();
+
+        let count = CHUNK_SIZE + 2;
+        let mut bulk = Bulk::new(count)
+            .unwrap()
+            .set(&registry, "a", vec![Value::ScalarInt(1); count])
+            .unwrap();
+        let increment = |values: &[Value]| -> Result<Vec<Value>> {
+            values.iter().map(|v| v + &Value::ScalarInt(1)).collect()
+        };
+
+        bulk.transaction_mut(&registry, |tx| {
+            tx.set("b", vec![Value::ScalarInt(0); count])?;
+            tx.set_element("a", CHUNK_SIZE, Value::ScalarInt(10))?;
+            tx.set_element("a", 0, Value::ScalarInt(5))?;
+            tx.apply(&["a", "b"], &[], increment)?;
+            assert_eq!(tx.get("a")?.get_element(0)?, Value::ScalarInt(6));
+            assert_eq!(tx.staged_fields(), vec!["a".to_string(), "b".to_string()]);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(bulk.meta.versions["a"], 2);
+        assert_eq!(bulk.meta.versions["b"], 1);
+        let a = bulk.get(&registry, "a").unwrap();
+        assert_eq!(a.get_element(0).unwrap(), Value::ScalarInt(6));
+        assert_eq!(a.get_element(1).unwrap(), Value::ScalarInt(2));
+        assert_eq!(a.get_element(CHUNK_SIZE).unwrap(), Value::ScalarInt(11));
+        assert_eq!(
+            bulk.get(&registry, "b").unwrap(),
+            Value::VectorInt(vec![1; count])
+        );
+
+        // Every failure rolls the whole transaction back
+        let before = bulk.clone();
+        let failures: Vec<Box<dyn Fn(&mut Transaction<'_>) -> Result<()>>> = vec![
+            Box::new(|tx| tx.set_element("a", 0, Value::ScalarString("x".to_string()))),
+            Box::new(|tx| tx.set_element("a", count, Value::ScalarInt(0))),
+            Box::new(|tx| tx.set_element("missing", 0, Value::ScalarInt(0))),
+            Box::new(|tx| tx.set("b", vec![Value::ScalarInt(0)])),
+            Box::new(|tx| tx.apply(&["a"], &[true], increment)),
+            Box::new(|tx| tx.apply(&["a"], &[], |_| Ok(vec![]))),
+            Box::new(|tx| {
+                tx.set_element("b", 0, Value::ScalarInt(7))?;
+                Err(SoAKitError::InvalidArgument("abort".to_string()))
+            }),
+        ];
+        for failure in failures {
+            assert!(bulk.transaction_mut(&registry, |tx| failure(tx)).is_err());
+            assert!(bulk.transaction(&registry, |tx| failure(tx)).is_err());
+        }
+        assert_eq!(bulk.meta.versions, before.meta.versions);
+        assert_eq!(
+            bulk.get(&registry, "b").unwrap(),
+            before.get(&registry, "b").unwrap()
+        );
+
+        let copy = bulk.transaction(&registry, |_| Ok(())).unwrap();
+        assert_eq!(copy.meta.versions, bulk.meta.versions);
+    }
+
+    #[test]
+    fn test_transaction_resolves_aliases() {
+        use std::sync::{Arc, Mutex};
+
+        let mut registry = Registry::new();
+        registry.register_int("date_of_birth").unwrap();
+        registry.add_alias("dob", "date_of_birth").unwrap();
+        registry
+            .deprecate("date_of_birth", "use birth_year")
+            .unwrap();
+        let accesses = Arc::new(Mutex::new(0));
+        let log = Arc::clone(&accesses);
+        registry.on_deprecated_access(move |_, _| *log.lock().unwrap() += 1);
+
+        let bulk = Bulk::new(2).unwrap();
+        let bulk = bulk
+            .transaction(&registry, |tx| {
+                tx.set("dob", vec![Value::ScalarInt(1970); 2])?;
+                tx.set_element("dob", 1, Value::ScalarInt(1980))?;
+                tx.apply(&["dob"], &[true, false], |years| {
+                    years.iter().map(|y| y + &Value::ScalarInt(1)).collect()
+                })?;
+                assert_eq!(tx.get("dob")?, tx.get("date_of_birth")?);
+                assert_eq!(tx.staged_fields(), vec!["date_of_birth".to_string()]);
+                Ok(())
+            })
+            .unwrap();
+        // One notice per staged access: set, set_element, apply and both gets
+        assert_eq!(*accesses.lock().unwrap(), 5);
+        assert_eq!(
+            bulk.get(&registry, "date_of_birth").unwrap(),
+            Value::VectorInt(vec![1971, 1980])
+        );
+        assert_eq!(bulk.list_data_fields(), vec!["date_of_birth".to_string()]);
+    }
+}
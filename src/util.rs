@@ -1,7 +1,7 @@
 /// Utility functions for SoAKit.
 ///
 /// This module provides utility functions for type checking, field name validation,
-/// and filtering system fields.
+/// filtering system fields, and NaN-aware float comparison.
 use crate::value::Value;
 
 /// Check if a value is a scalar (rank 0).
@@ -144,6 +144,71 @@ pub fn filter_system_fields(names: &[String]) -> Vec<String> {
         .collect()
 }
 
+/// Normalize a float so that all NaNs share one bit pattern and `-0.0` becomes `0.0`.
+pub(crate) fn normalize_float(v: f64) -> f64 {
+    if v.is_nan() {
+        f64::NAN
+    } else if v == 0.0 {
+        0.0
+    } else {
+        v
+    }
+}
+
+/// Get a hashable key for a float that matches the crate's float semantics.
+///
+/// All NaN values map to the same key, and `-0.0` maps to the same key as `0.0`;
+/// every other float maps to its own bit pattern. This is how SoAKit groups and
+/// hashes floats (for example in [`Bulk::partition_by`](crate::Bulk::partition_by)),
+/// so using it in validators and derived functions gives consistent results.
+///
+/// # Arguments
+///
+/// * `v` - The float to convert
+///
+/// # Returns
+///
+/// A `u64` key, equal for two floats exactly when [`float_eq_nan_aware`] is `true`.
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::float_key;
+///
+/// assert_eq!(float_key(f64::NAN), float_key(-f64::NAN));
+/// assert_eq!(float_key(-0.0), float_key(0.0));
+/// assert_ne!(float_key(1.0), float_key(2.0));
+/// ```
+pub fn float_key(v: f64) -> u64 {
+    normalize_float(v).to_bits()
+}
+
+/// Compare two floats for equality, treating NaN as equal to NaN.
+///
+/// This also treats `-0.0` and `0.0` as equal (as `==` does).
+///
+/// # Arguments
+///
+/// * `a` - The first float
+/// * `b` - The second float
+///
+/// # Returns
+///
+/// Returns `true` if both are NaN, or if `a == b`.
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::float_eq_nan_aware;
+///
+/// assert!(float_eq_nan_aware(f64::NAN, f64::NAN));
+/// assert!(float_eq_nan_aware(-0.0, 0.0));
+/// assert!(!float_eq_nan_aware(f64::NAN, 1.0));
+/// ```
+pub fn float_eq_nan_aware(a: f64, b: f64) -> bool {
+    float_key(a) == float_key(b)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -322,5 +387,18 @@ mod tests {
         assert!(is_vector(&Value::VectorBool(vec![true])));
         assert!(is_vector(&Value::VectorString(vec!["a".to_string()])));
     }
-}
 
+    #[test]
+    fn test_float_key_and_nan_aware_equality() {
+        let other_nan = f64::from_bits(f64::NAN.to_bits() ^ 1);
+        assert!(other_nan.is_nan());
+        assert_eq!(float_key(other_nan), float_key(f64::NAN));
+        assert_eq!(float_key(-0.0), float_key(0.0));
+        assert_ne!(float_key(f64::INFINITY), float_key(f64::NEG_INFINITY));
+
+        assert!(float_eq_nan_aware(other_nan, f64::NAN));
+        assert!(float_eq_nan_aware(1.5, 1.5));
+        assert!(!float_eq_nan_aware(1.5, 2.5));
+        assert!(!float_eq_nan_aware(0.0, f64::NAN));
+    }
+}
@@ -0,0 +1,141 @@
+/// Ready-made field validators.
+///
+/// Most fields only need a type check or a simple bound, and writing the same
+/// `Box::new(|v: &Value| matches!(v, ...))` closure for each of them is noise.
+/// The functions here return boxed validators ready for
+/// [`Registry::register`](crate::Registry::register) or
+/// [`RegistryBuilder::with_field`](crate::RegistryBuilder::with_field).
+///
+/// Validators only see present values: missing (`Null`) elements are never
+/// checked, so none of these need to accept `Null` themselves.
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::{RegistryBuilder, Value, validators};
+///
+/// let registry = RegistryBuilder::new()
+///     .with_field("id", validators::positive_int())
+///     .with_field("weight", validators::positive_float())
+///     .with_field("name", validators::non_empty_string())
+///     .build()
+///     .unwrap();
+///
+/// assert!(registry.validate("id", &Value::ScalarInt(7)));
+/// assert!(!registry.validate("id", &Value::ScalarInt(0)));
+/// assert!(!registry.validate("name", &Value::ScalarString("  ".to_string())));
+/// ```
+use crate::meta::FieldType;
+use crate::value::Value;
+use std::ops::RangeInclusive;
+
+/// A boxed validator, as taken by [`Registry::register`](crate::Registry::register).
+pub type Validator = Box<dyn Fn(&Value) -> bool + Send + Sync>;
+
+/// Accept any value.
+pub fn any() -> Validator {
+    Box::new(|_: &Value| true)
+}
+
+/// Accept `ScalarInt` values.
+pub fn int() -> Validator {
+    FieldType::Int.validator()
+}
+
+/// Accept `ScalarFloat` values, including NaN and infinities.
+pub fn float() -> Validator {
+    FieldType::Float.validator()
+}
+
+/// Accept `ScalarBool` values.
+pub fn boolean() -> Validator {
+    FieldType::Bool.validator()
+}
+
+/// Accept `ScalarString` values, including the empty string.
+pub fn string() -> Validator {
+    FieldType::String.validator()
+}
+
+/// Accept `ScalarInt` values greater than zero.
+pub fn positive_int() -> Validator {
+    Box::new(|value: &Value| matches!(value, Value::ScalarInt(n) if *n > 0))
+}
+
+/// Accept finite `ScalarFloat` values greater than zero.
+pub fn positive_float() -> Validator {
+    Box::new(|value: &Value| matches!(value, Value::ScalarFloat(x) if x.is_finite() && *x > 0.0))
+}
+
+/// Accept `ScalarString` values that are not empty or whitespace only.
+pub fn non_empty_string() -> Validator {
+    Box::new(|value: &Value| matches!(value, Value::ScalarString(s) if !s.trim().is_empty()))
+}
+
+/// Accept `ScalarInt` values in a range, bounds included.
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::{Value, validators};
+///
+/// let percent = validators::int_range(0..=100);
+/// assert!(percent(&Value::ScalarInt(100)));
+/// assert!(!percent(&Value::ScalarInt(101)));
+/// assert!(!percent(&Value::ScalarFloat(50.0)));
+/// ```
+pub fn int_range(range: RangeInclusive<i64>) -> Validator {
+    Box::new(move |value: &Value| matches!(value, Value::ScalarInt(n) if range.contains(n)))
+}
+
+/// Accept values equal to one of the listed values.
+pub fn one_of(options: Vec<Value>) -> Validator {
+    Box::new(move |value: &Value| options.contains(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::RegistryBuilder;
+
+    #[test]
+    fn test_ready_made_validators() {
+        assert!(any()(&Value::VectorInt(vec![1])));
+        assert!(int()(&Value::ScalarInt(-1)) && !int()(&Value::ScalarFloat(1.0)));
+        assert!(float()(&Value::ScalarFloat(f64::NAN)) && !float()(&Value::ScalarInt(1)));
+        assert!(boolean()(&Value::ScalarBool(false)) && !boolean()(&Value::ScalarInt(0)));
+        assert!(string()(&Value::ScalarString(String::new())));
+
+        assert!(positive_int()(&Value::ScalarInt(1)));
+        assert!(!positive_int()(&Value::ScalarInt(0)) && !positive_int()(&Value::ScalarFloat(1.0)));
+        assert!(positive_float()(&Value::ScalarFloat(0.5)));
+        for x in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(!positive_float()(&Value::ScalarFloat(x)));
+        }
+        assert!(non_empty_string()(&Value::ScalarString("a".to_string())));
+        assert!(!non_empty_string()(&Value::ScalarString(" ".to_string())));
+        assert!(!non_empty_string()(&Value::VectorString(vec![
+            "a".to_string()
+        ])));
+
+        let sizes = one_of(vec![
+            Value::ScalarString("S".to_string()),
+            Value::ScalarInt(1),
+        ]);
+        assert!(sizes(&Value::ScalarString("S".to_string())) && sizes(&Value::ScalarInt(1)));
+        assert!(!sizes(&Value::ScalarString("M".to_string())));
+    }
+
+    #[test]
+    fn test_validators_in_builder() {
+        let registry = RegistryBuilder::new()
+            .with_field("id", positive_int())
+            .with_field("score", int_range(0..=10))
+            .build()
+            .unwrap();
+        assert!(registry.validate("id", &Value::ScalarInt(3)));
+        assert!(!registry.validate("id", &Value::ScalarInt(-3)));
+        assert!(registry.validate("score", &Value::ScalarInt(10)));
+        assert!(!registry.validate("score", &Value::ScalarInt(11)));
+    }
+}
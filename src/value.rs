@@ -2,10 +2,15 @@
 ///
 /// This module defines the [`Value`] enum which represents all possible data types
 /// that can be stored in a SoAKit [`Bulk`] structure. Values can be scalars (rank 0),
-/// vectors (rank 1), or matrices (rank 2+).
+/// vectors (rank 1), matrices (rank 2+), or dense N-dimensional float tensors.
 use crate::error::{Result, SoAKitError};
+use crate::util::{float_key, normalize_float};
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::VecDeque;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{Add, Bound, Div, Mul, RangeBounds, Sub};
 
 /// Represents a value in the SoA structure.
 ///
@@ -29,6 +34,18 @@ use std::fmt;
 /// - **Matrices** (rank 2+): Nested structures
 ///   - `Matrix(Vec<Value>)`: Matrix represented as a vector of Value elements
 ///
+/// - **Tensors** (rank N): Dense float arrays with an explicit shape
+///   - `Tensor { data, shape }`: Row-major `f64` data and the size of each dimension
+///
+/// - **Option vectors** (rank 1): Sparse vectors where elements may be missing
+///   - `VectorOptionInt`, `VectorOptionFloat`, `VectorOptionBool`, `VectorOptionString`:
+///     the vector length plus `(index, value)` pairs for the present elements
+///   - `Null`: a missing element, as returned by [`Value::get_element`]
+///
+/// - **JSON cells**: Arbitrary semi-structured payloads
+///   - `ScalarJson(serde_json::Value)`: One JSON document
+///   - `VectorJson(Vec<serde_json::Value>)`: Vector of JSON documents
+///
 /// # Examples
 ///
 /// Creating scalar values:
@@ -63,6 +80,20 @@ use std::fmt;
 ///     Value::VectorInt(vec![4, 5, 6]),
 /// ]);
 /// ```
+///
+/// Arithmetic operators work element-wise and broadcast scalars. They return a
+/// [`Result`] because integer overflow, division by zero, mismatched lengths and
+/// unsupported types are reported as errors:
+///
+/// ```rust
+/// use soakit::Value;
+///
+/// let a = Value::VectorInt(vec![1, 2, 3]);
+/// let b = Value::VectorInt(vec![10, 20, 30]);
+/// assert_eq!((&a + &b).unwrap(), Value::VectorInt(vec![11, 22, 33]));
+/// assert_eq!((&a * &Value::ScalarFloat(0.5)).unwrap(), Value::VectorFloat(vec![0.5, 1.0, 1.5]));
+/// assert!((&a / &Value::ScalarInt(0)).is_err());
+/// ```
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub enum Value {
     /// Scalar integer value (64-bit signed integer)
@@ -86,6 +117,63 @@ pub enum Value {
     /// Each element in the vector represents a row, and each row is itself a Value
     /// (typically a Vector variant).
     Matrix(Vec<Value>),
+    /// Dense N-dimensional array of floats stored in row-major order
+    ///
+    /// Unlike `Matrix`, a tensor cannot be ragged: the product of `shape` always
+    /// equals `data.len()`. Build tensors with [`Value::tensor`] to have the shape
+    /// checked. A tensor of rank `N` indexed along its first axis yields tensors of
+    /// rank `N - 1`, so per-element tensor fields stack into one tensor per chunk.
+    Tensor {
+        /// Elements in row-major order
+        data: Vec<f64>,
+        /// Size of each dimension
+        shape: Vec<usize>,
+    },
+    /// Missing element of an option vector
+    ///
+    /// [`Value::get_element`] returns `Null` for elements of an option vector that
+    /// hold no value, and [`Value::from_scalars`] builds an option vector from
+    /// scalars that include `Null`.
+    Null,
+    /// Sparse vector of optional integers
+    ///
+    /// Holds `len` elements, of which only those listed in `entries` are present.
+    /// `entries` pairs an element index with its value; indices must be strictly
+    /// increasing and below `len`.
+    VectorOptionInt {
+        /// Number of elements, present or missing
+        len: usize,
+        /// Present elements as `(index, value)` pairs
+        entries: Vec<(usize, i64)>,
+    },
+    /// Sparse vector of optional floats, laid out like [`Value::VectorOptionInt`]
+    VectorOptionFloat {
+        /// Number of elements, present or missing
+        len: usize,
+        /// Present elements as `(index, value)` pairs
+        entries: Vec<(usize, f64)>,
+    },
+    /// Sparse vector of optional booleans, laid out like [`Value::VectorOptionInt`]
+    VectorOptionBool {
+        /// Number of elements, present or missing
+        len: usize,
+        /// Present elements as `(index, value)` pairs
+        entries: Vec<(usize, bool)>,
+    },
+    /// Sparse vector of optional strings, laid out like [`Value::VectorOptionInt`]
+    VectorOptionString {
+        /// Number of elements, present or missing
+        len: usize,
+        /// Present elements as `(index, value)` pairs
+        entries: Vec<(usize, String)>,
+    },
+    /// Scalar JSON document
+    ///
+    /// Serializes as the document itself in human-readable formats such as JSON,
+    /// and as JSON text in binary formats.
+    ScalarJson(#[serde(with = "json_text")] serde_json::Value),
+    /// Vector of JSON documents
+    VectorJson(#[serde(with = "json_texts")] Vec<serde_json::Value>),
 }
 
 impl fmt::Debug for Value {
@@ -100,8 +188,100 @@ impl fmt::Debug for Value {
             Value::VectorBool(v) => write!(f, "VectorBool({:?})", v),
             Value::VectorString(v) => write!(f, "VectorString({:?})", v),
             Value::Matrix(v) => write!(f, "Matrix({:?})", v),
+            Value::Tensor { data, shape } => {
+                write!(f, "Tensor {{ data: {:?}, shape: {:?} }}", data, shape)
+            }
+            Value::Null => write!(f, "Null"),
+            Value::VectorOptionInt { len, entries } => {
+                write!(
+                    f,
+                    "VectorOptionInt {{ len: {}, entries: {:?} }}",
+                    len, entries
+                )
+            }
+            Value::VectorOptionFloat { len, entries } => {
+                write!(
+                    f,
+                    "VectorOptionFloat {{ len: {}, entries: {:?} }}",
+                    len, entries
+                )
+            }
+            Value::VectorOptionBool { len, entries } => {
+                write!(
+                    f,
+                    "VectorOptionBool {{ len: {}, entries: {:?} }}",
+                    len, entries
+                )
+            }
+            Value::VectorOptionString { len, entries } => {
+                write!(
+                    f,
+                    "VectorOptionString {{ len: {}, entries: {:?} }}",
+                    len, entries
+                )
+            }
+            Value::ScalarJson(v) => write!(f, "ScalarJson({})", v),
+            Value::VectorJson(v) => write!(f, "VectorJson({:?})", v),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    /// Human readable rendering of a value.
+    ///
+    /// Scalars render bare (`42`, `1.5`, `true`, `Alice`), vectors render as
+    /// bracketed lists with strings quoted (`[1, 2]`, `["a", "b"]`), and matrices
+    /// render as nested lists. Missing elements of option vectors render as `null`,
+    /// and JSON cells render as compact JSON text.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::ScalarInt(v) => write!(f, "{}", v),
+            Value::ScalarFloat(v) => write!(f, "{}", v),
+            Value::ScalarBool(v) => write!(f, "{}", v),
+            Value::ScalarString(v) => write!(f, "{}", v),
+            Value::VectorInt(v) => write_list(f, v.iter().map(|x| x.to_string())),
+            Value::VectorFloat(v) => write_list(f, v.iter().map(|x| x.to_string())),
+            Value::VectorBool(v) => write_list(f, v.iter().map(|x| x.to_string())),
+            Value::VectorString(v) => write_list(f, v.iter().map(|x| format!("{:?}", x))),
+            Value::Matrix(v) => write_list(f, v.iter().map(|x| x.to_string())),
+            Value::Tensor { data, shape } => match shape.split_first() {
+                None => write!(f, "{}", data.first().copied().unwrap_or(f64::NAN)),
+                Some((&n, _)) => write_list(
+                    f,
+                    (0..n).map(|i| {
+                        self.get_element(i)
+                            .map(|row| row.to_string())
+                            .unwrap_or_default()
+                    }),
+                ),
+            },
+            Value::Null => write!(f, "null"),
+            Value::VectorOptionInt { .. }
+            | Value::VectorOptionFloat { .. }
+            | Value::VectorOptionBool { .. }
+            | Value::VectorOptionString { .. } => write_list(
+                f,
+                self.to_scalars().iter().map(|x| match x {
+                    Value::ScalarString(s) => format!("{:?}", s),
+                    other => other.to_string(),
+                }),
+            ),
+            Value::ScalarJson(v) => write!(f, "{}", v),
+            Value::VectorJson(v) => write_list(f, v.iter().map(ToString::to_string)),
+        }
+    }
+}
+
+/// Write items as a comma separated, bracketed list.
+fn write_list(f: &mut fmt::Formatter<'_>, items: impl Iterator<Item = String>) -> fmt::Result {
+    write!(f, "[")?;
+    for (i, item) in items.enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
         }
+        write!(f, "{}", item)?;
     }
+    write!(f, "]")
 }
 
 impl Value {
@@ -111,8 +291,8 @@ impl Value {
     ///
     /// # Returns
     ///
-    /// `true` if the value is a scalar variant (`ScalarInt`, `ScalarFloat`, `ScalarBool`, or `ScalarString`),
-    /// `false` otherwise.
+    /// `true` if the value is a scalar variant (`ScalarInt`, `ScalarFloat`, `ScalarBool`,
+    /// `ScalarString`, `ScalarJson`, or `Null`), `false` otherwise.
     ///
     /// # Examples
     ///
@@ -130,6 +310,8 @@ impl Value {
                 | Value::ScalarFloat(_)
                 | Value::ScalarBool(_)
                 | Value::ScalarString(_)
+                | Value::ScalarJson(_)
+                | Value::Null
         )
     }
 
@@ -139,8 +321,8 @@ impl Value {
     ///
     /// # Returns
     ///
-    /// `true` if the value is a vector variant (`VectorInt`, `VectorFloat`, `VectorBool`, or `VectorString`),
-    /// `false` otherwise.
+    /// `true` if the value is a vector variant (`VectorInt`, `VectorFloat`, `VectorBool`,
+    /// `VectorString`, `VectorJson`, or one of the option vectors), `false` otherwise.
     ///
     /// # Examples
     ///
@@ -158,6 +340,11 @@ impl Value {
                 | Value::VectorFloat(_)
                 | Value::VectorBool(_)
                 | Value::VectorString(_)
+                | Value::VectorJson(_)
+                | Value::VectorOptionInt { .. }
+                | Value::VectorOptionFloat { .. }
+                | Value::VectorOptionBool { .. }
+                | Value::VectorOptionString { .. }
         )
     }
 
@@ -182,6 +369,56 @@ impl Value {
         matches!(self, Value::Matrix(_))
     }
 
+    /// Check if the value is a [`Value::Tensor`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Value;
+    ///
+    /// assert!(Value::tensor(vec![1.0, 2.0], vec![2]).unwrap().is_tensor());
+    /// assert!(!Value::VectorFloat(vec![1.0, 2.0]).is_tensor());
+    /// ```
+    pub const fn is_tensor(&self) -> bool {
+        matches!(self, Value::Tensor { .. })
+    }
+
+    /// Check if the value is [`Value::Null`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Value;
+    ///
+    /// assert!(Value::Null.is_null());
+    /// assert!(!Value::ScalarInt(0).is_null());
+    /// ```
+    pub const fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    /// Check if the value is an option vector (`VectorOptionInt`, `VectorOptionFloat`,
+    /// `VectorOptionBool`, or `VectorOptionString`).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Value;
+    ///
+    /// let sparse = Value::from_scalars(vec![Value::Null, Value::ScalarInt(3)]).unwrap();
+    /// assert!(sparse.is_sparse());
+    /// assert!(!Value::VectorInt(vec![3]).is_sparse());
+    /// ```
+    pub const fn is_sparse(&self) -> bool {
+        matches!(
+            self,
+            Value::VectorOptionInt { .. }
+                | Value::VectorOptionFloat { .. }
+                | Value::VectorOptionBool { .. }
+                | Value::VectorOptionString { .. }
+        )
+    }
+
     /// Get the rank (number of dimensions) of the value.
     ///
     /// - Scalars have rank 0
@@ -206,12 +443,20 @@ impl Value {
             Value::ScalarInt(_)
             | Value::ScalarFloat(_)
             | Value::ScalarBool(_)
-            | Value::ScalarString(_) => 0,
+            | Value::ScalarString(_)
+            | Value::ScalarJson(_)
+            | Value::Null => 0,
             Value::VectorInt(_)
             | Value::VectorFloat(_)
             | Value::VectorBool(_)
-            | Value::VectorString(_) => 1,
+            | Value::VectorString(_)
+            | Value::VectorJson(_)
+            | Value::VectorOptionInt { .. }
+            | Value::VectorOptionFloat { .. }
+            | Value::VectorOptionBool { .. }
+            | Value::VectorOptionString { .. } => 1,
             Value::Matrix(_) => 2,
+            Value::Tensor { shape, .. } => shape.len(),
         }
     }
 
@@ -239,12 +484,23 @@ impl Value {
             Value::ScalarInt(_)
             | Value::ScalarFloat(_)
             | Value::ScalarBool(_)
-            | Value::ScalarString(_) => 1,
+            | Value::ScalarString(_)
+            | Value::ScalarJson(_)
+            | Value::Null => 1,
             Value::VectorInt(v) => v.len(),
             Value::VectorFloat(v) => v.len(),
             Value::VectorBool(v) => v.len(),
             Value::VectorString(v) => v.len(),
+            Value::VectorJson(v) => v.len(),
             Value::Matrix(v) => v.len(),
+            Value::Tensor { shape, .. } => match shape.as_slice().first() {
+                Some(&n) => n,
+                None => 1,
+            },
+            Value::VectorOptionInt { len, .. }
+            | Value::VectorOptionFloat { len, .. }
+            | Value::VectorOptionBool { len, .. }
+            | Value::VectorOptionString { len, .. } => *len,
         }
     }
 
@@ -270,6 +526,48 @@ impl Value {
         self.len() == 0
     }
 
+    /// Estimate the heap memory owned by the value, in bytes.
+    ///
+    /// Counts the element buffers of vectors and the bytes of strings, but not
+    /// the `Value` itself, spare vector capacity or allocator overhead.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Value;
+    ///
+    /// assert_eq!(Value::ScalarInt(42).heap_size(), 0);
+    /// assert_eq!(Value::VectorInt(vec![1, 2, 3]).heap_size(), 24);
+    /// assert_eq!(Value::ScalarString("abc".to_string()).heap_size(), 3);
+    /// ```
+    pub fn heap_size(&self) -> usize {
+        let total = |sizes: &mut dyn Iterator<Item = usize>| sizes.fold(0, usize::saturating_add);
+        match self {
+            Value::ScalarInt(_) | Value::ScalarFloat(_) | Value::ScalarBool(_) | Value::Null => 0,
+            Value::ScalarString(s) => s.len(),
+            Value::ScalarJson(json) => json_heap_size(json),
+            Value::VectorInt(v) => size_of_val(v.as_slice()),
+            Value::VectorFloat(v) => size_of_val(v.as_slice()),
+            Value::VectorBool(v) => size_of_val(v.as_slice()),
+            Value::VectorString(v) => {
+                size_of_val(v.as_slice()).saturating_add(total(&mut v.iter().map(String::len)))
+            }
+            Value::VectorJson(v) => {
+                size_of_val(v.as_slice()).saturating_add(total(&mut v.iter().map(json_heap_size)))
+            }
+            Value::Matrix(rows) => size_of_val(rows.as_slice())
+                .saturating_add(total(&mut rows.iter().map(Value::heap_size))),
+            Value::Tensor { data, shape } => {
+                size_of_val(data.as_slice()).saturating_add(size_of_val(shape.as_slice()))
+            }
+            Value::VectorOptionInt { entries, .. } => size_of_val(entries.as_slice()),
+            Value::VectorOptionFloat { entries, .. } => size_of_val(entries.as_slice()),
+            Value::VectorOptionBool { entries, .. } => size_of_val(entries.as_slice()),
+            Value::VectorOptionString { entries, .. } => size_of_val(entries.as_slice())
+                .saturating_add(total(&mut entries.iter().map(|(_, s)| s.len()))),
+        }
+    }
+
     /// Get the shape (dimensions) of the value.
     ///
     /// The shape is a vector representing the size of each dimension.
@@ -299,11 +597,14 @@ impl Value {
             Value::ScalarInt(_)
             | Value::ScalarFloat(_)
             | Value::ScalarBool(_)
-            | Value::ScalarString(_) => Vec::new(),
+            | Value::ScalarString(_)
+            | Value::ScalarJson(_)
+            | Value::Null => Vec::new(),
             Value::VectorInt(v) => vec![v.len()],
             Value::VectorFloat(v) => vec![v.len()],
             Value::VectorBool(v) => vec![v.len()],
             Value::VectorString(v) => vec![v.len()],
+            Value::VectorJson(v) => vec![v.len()],
             Value::Matrix(m) => {
                 if m.is_empty() {
                     vec![0]
@@ -312,13 +613,19 @@ impl Value {
                     vec![m.len(), first_row_len]
                 }
             }
+            Value::Tensor { shape, .. } => shape.clone(),
+            Value::VectorOptionInt { len, .. }
+            | Value::VectorOptionFloat { len, .. }
+            | Value::VectorOptionBool { len, .. }
+            | Value::VectorOptionString { len, .. } => vec![*len],
         }
     }
 
     /// Extract a single element from a vector by index.
     ///
     /// This method extracts the element at the given index from a vector value
-    /// and returns it as a scalar value of the same type.
+    /// and returns it as a scalar value of the same type. Missing elements of an
+    /// option vector are returned as [`Value::Null`].
     ///
     /// # Arguments
     ///
@@ -378,6 +685,28 @@ impl Value {
                     }
                 })
             }
+            Value::VectorJson(v) => {
+                v.get(idx)
+                    .cloned()
+                    .map(Value::ScalarJson)
+                    .ok_or(SoAKitError::IndexOutOfBounds {
+                        index: idx,
+                        max: v.len(),
+                    })
+            }
+            Value::Tensor { data, shape } => tensor_index(data, shape, idx),
+            Value::VectorOptionInt { len, entries } => {
+                option_element(*len, entries, idx, Value::ScalarInt)
+            }
+            Value::VectorOptionFloat { len, entries } => {
+                option_element(*len, entries, idx, Value::ScalarFloat)
+            }
+            Value::VectorOptionBool { len, entries } => {
+                option_element(*len, entries, idx, Value::ScalarBool)
+            }
+            Value::VectorOptionString { len, entries } => {
+                option_element(*len, entries, idx, Value::ScalarString)
+            }
             _ => Err(SoAKitError::InvalidArgument(
                 "get_element only works on vectors".to_string(),
             )),
@@ -423,31 +752,59 @@ impl Value {
             Value::Matrix(v) => {
                 serde_json::Value::Array(v.iter().map(|x| x.to_untagged_json_value()).collect())
             }
+            Value::Tensor { data, shape } => {
+                if shape.is_empty() {
+                    let v = data.first().copied().unwrap_or(f64::NAN);
+                    serde_json::Number::from_f64(v)
+                        .map(serde_json::Value::Number)
+                        .unwrap_or(serde_json::Value::Null)
+                } else {
+                    serde_json::Value::Array(
+                        self.to_scalars()
+                            .iter()
+                            .map(Value::to_untagged_json_value)
+                            .collect(),
+                    )
+                }
+            }
+            Value::Null => serde_json::Value::Null,
+            Value::ScalarJson(v) => v.clone(),
+            Value::VectorJson(v) => serde_json::Value::Array(v.clone()),
+            Value::VectorOptionInt { .. }
+            | Value::VectorOptionFloat { .. }
+            | Value::VectorOptionBool { .. }
+            | Value::VectorOptionString { .. } => serde_json::Value::Array(
+                self.to_scalars()
+                    .iter()
+                    .map(Value::to_untagged_json_value)
+                    .collect(),
+            ),
         }
     }
 
     /// Create Value from an untagged serde_json::Value.
     ///
     /// Infers the type based on the JSON value:
+    /// - Null -> Null
     /// - Number -> ScalarInt (if integer) or ScalarFloat
     /// - Bool -> ScalarBool
     /// - String -> ScalarString
-    /// - Array -> Vector (if all elements same type) or Matrix (if elements are arrays)
+    /// - Object -> ScalarJson
+    /// - Array -> Vector (if all elements same type), option vector (if some
+    ///   elements are null), Matrix (if elements are arrays), or VectorJson (if
+    ///   elements are objects)
     ///
     /// Note: This does best-effort inference. For empty arrays, it defaults to VectorInt.
     ///
     /// # Errors
     ///
     /// Returns an error if:
-    /// - The JSON value is `Null`
     /// - A number cannot be represented as i64 or f64
     /// - An array contains mixed types
     /// - An array element cannot be converted to a Value
     pub fn from_untagged_json_value(json: serde_json::Value) -> Result<Self> {
         match json {
-            serde_json::Value::Null => Err(SoAKitError::InvalidArgument(
-                "Cannot convert Null to Value".to_string(),
-            )),
+            serde_json::Value::Null => Ok(Value::Null),
             serde_json::Value::Bool(b) => Ok(Value::ScalarBool(b)),
             serde_json::Value::Number(n) => {
                 if let Some(i) = n.as_i64() {
@@ -466,6 +823,13 @@ impl Value {
                     // Default to VectorInt for empty array
                     return Ok(Value::VectorInt(Vec::new()));
                 }
+                if arr.iter().any(serde_json::Value::is_null)
+                    && !arr.iter().any(serde_json::Value::is_object)
+                {
+                    // Nulls mark missing elements of an option vector
+                    let scalars = arr.into_iter().map(Self::from_untagged_json_value);
+                    return Value::from_scalars(scalars.collect::<Result<_>>()?);
+                }
 
                 // Check first element to determine type
                 let first = arr.first().ok_or_else(|| {
@@ -532,14 +896,15 @@ impl Value {
                         }
                         Ok(Value::Matrix(vec))
                     }
+                    serde_json::Value::Object(_) | serde_json::Value::Null => {
+                        Ok(Value::VectorJson(arr))
+                    }
                     _ => Err(SoAKitError::InvalidArgument(
                         "Unsupported array element type".to_string(),
                     )),
                 }
             }
-            serde_json::Value::Object(_) => Err(SoAKitError::InvalidArgument(
-                "Objects not supported as Values".to_string(),
-            )),
+            object @ serde_json::Value::Object(_) => Ok(Value::ScalarJson(object)),
         }
     }
 
@@ -547,16 +912,23 @@ impl Value {
     ///
     /// All values must be of the same type. Supports both scalar types (which are
     /// converted to vectors) and vector types (which are converted to Matrix).
+    /// Tensors of identical shape are stacked into one tensor with a new leading axis.
+    /// Scalars mixed with [`Value::Null`] are converted to an option vector, with
+    /// the nulls as missing elements.
     ///
     /// # Errors
     ///
     /// Returns an error if:
     /// - The scalars vector contains mixed types
+    /// - Nulls are mixed with non-scalar values
     /// - An unsupported Value type is encountered
     pub fn from_scalars(scalars: Vec<Value>) -> Result<Self> {
         if scalars.is_empty() {
             return Ok(Value::VectorInt(Vec::new())); // Default to empty int vector
         }
+        if scalars.iter().any(Value::is_null) {
+            return option_from_scalars(scalars);
+        }
 
         let first = scalars
             .first()
@@ -626,16 +998,44 @@ impl Value {
                 // Nested matrix - just wrap in another Matrix
                 Ok(Value::Matrix(scalars))
             }
+            Value::Tensor { shape, .. } => {
+                // Stack tensors along a new leading axis
+                let row_shape = shape.clone();
+                stack_tensors(scalars, &row_shape)
+            }
+            Value::ScalarJson(_) => {
+                let mut vec = Vec::with_capacity(scalars.len());
+                for val in scalars {
+                    if let Value::ScalarJson(j) = val {
+                        vec.push(j);
+                    } else {
+                        return Err(SoAKitError::InvalidArgument(
+                            "Mixed types in scalars (expected JSON)".to_string(),
+                        ));
+                    }
+                }
+                Ok(Value::VectorJson(vec))
+            }
+            Value::VectorJson(_)
+            | Value::VectorOptionInt { .. }
+            | Value::VectorOptionFloat { .. }
+            | Value::VectorOptionBool { .. }
+            | Value::VectorOptionString { .. } => Ok(Value::Matrix(scalars)),
+            Value::Null => option_from_scalars(scalars),
         }
     }
 
     /// Append another vector Value to this one.
     ///
-    /// Both Values must be of the same vector type.
+    /// Both Values must be of the same vector type. Tensors are appended along
+    /// their first axis and must agree on every other dimension. An option vector
+    /// can be combined with a dense vector of the same kind; the result is an
+    /// option vector.
     ///
     /// # Errors
     ///
-    /// Returns an error if the two Values are not of the same vector type.
+    /// Returns an error if the two Values are not of the same vector type, or
+    /// if two tensors have incompatible shapes.
     pub fn append(&mut self, other: Value) -> Result<()> {
         match (self, other) {
             (Value::VectorInt(v1), Value::VectorInt(v2)) => {
@@ -654,409 +1054,4610 @@ impl Value {
                 v1.extend(v2);
                 Ok(())
             }
+            (Value::VectorJson(v1), Value::VectorJson(v2)) => {
+                v1.extend(v2);
+                Ok(())
+            }
             (Value::Matrix(v1), Value::Matrix(v2)) => {
                 v1.extend(v2);
                 Ok(())
             }
+            (
+                Value::Tensor {
+                    data: d1,
+                    shape: s1,
+                },
+                Value::Tensor {
+                    data: d2,
+                    shape: s2,
+                },
+            ) => {
+                let (Some((n1, rest1)), Some((n2, rest2))) =
+                    (s1.split_first_mut(), s2.split_first())
+                else {
+                    return Err(SoAKitError::InvalidArgument(
+                        "Cannot append rank-0 tensors".to_string(),
+                    ));
+                };
+                if rest1 != rest2 {
+                    return Err(SoAKitError::InvalidArgument(format!(
+                        "Cannot append tensors with row shapes {:?} and {:?}",
+                        rest1, rest2
+                    )));
+                }
+                *n1 = n1.checked_add(*n2).ok_or_else(|| {
+                    SoAKitError::InvalidArgument("Arithmetic overflow".to_string())
+                })?;
+                d1.extend(d2);
+                Ok(())
+            }
+            (lhs, other) if lhs.is_sparse() || other.is_sparse() => lhs.append_option(other),
             _ => Err(SoAKitError::InvalidArgument(
                 "Cannot append values of different types".to_string(),
             )),
         }
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_scalar_types() {
-        let v1 = Value::ScalarInt(42);
-        let v2 = Value::ScalarFloat(3.14);
-        let v3 = Value::ScalarBool(true);
-        let v4 = Value::ScalarString("hello".to_string());
 
-        assert!(v1.is_scalar());
-        assert!(v2.is_scalar());
-        assert!(v3.is_scalar());
-        assert!(v4.is_scalar());
-        assert!(!v1.is_vector());
-        assert!(!v1.is_matrix());
-        assert_eq!(v1.rank(), 0);
-        assert_eq!(v1.len(), 1);
+    /// Append for option vectors; either operand may be a dense vector instead.
+    fn append_option(&mut self, other: Value) -> Result<()> {
+        let (Some((len, mut indices, mut values)), Some((other_len, other_indices, other_values))) =
+            (self.option_parts(), other.option_parts())
+        else {
+            return Err(SoAKitError::InvalidArgument(
+                "Cannot append values of different types".to_string(),
+            ));
+        };
+        // Without present elements, the kind of an option vector is not meaningful
+        if indices.is_empty() {
+            values = other_values.slice(..0)?;
+        }
+        if !other_indices.is_empty() {
+            values.append(other_values)?;
+        }
+        for index in other_indices {
+            indices.push(
+                index.checked_add(len).ok_or_else(|| {
+                    SoAKitError::InvalidArgument("Arithmetic overflow".to_string())
+                })?,
+            );
+        }
+        let len = len
+            .checked_add(other_len)
+            .ok_or_else(|| SoAKitError::InvalidArgument("Arithmetic overflow".to_string()))?;
+        *self = Value::from_option_parts(len, indices, values)?;
+        Ok(())
     }
 
-    #[test]
-    fn test_vector_types() {
-        let v1 = Value::VectorInt(vec![1, 2, 3]);
-        let v2 = Value::VectorFloat(vec![1.0, 2.0, 3.0]);
-        let v3 = Value::VectorBool(vec![true, false]);
-        let v4 = Value::VectorString(vec!["a".to_string(), "b".to_string()]);
-
-        assert!(v1.is_vector());
-        assert!(v2.is_vector());
-        assert!(v3.is_vector());
-        assert!(v4.is_vector());
-        assert!(!v1.is_scalar());
-        assert!(!v1.is_matrix());
-        assert_eq!(v1.rank(), 1);
-        assert_eq!(v1.len(), 3);
-        assert_eq!(v3.len(), 2);
+    /// Split an option vector into its length, the indices of its present elements
+    /// and a dense vector of their values.
+    ///
+    /// A dense vector is treated as an option vector with every element present.
+    /// Returns `None` for scalars, matrices and tensors.
+    fn option_parts(&self) -> Option<(usize, Vec<usize>, Value)> {
+        let (len, indices, values) = match self {
+            Value::VectorOptionInt { len, entries } => {
+                let (indices, values) = entries.iter().copied().unzip();
+                (*len, indices, Value::VectorInt(values))
+            }
+            Value::VectorOptionFloat { len, entries } => {
+                let (indices, values) = entries.iter().copied().unzip();
+                (*len, indices, Value::VectorFloat(values))
+            }
+            Value::VectorOptionBool { len, entries } => {
+                let (indices, values) = entries.iter().copied().unzip();
+                (*len, indices, Value::VectorBool(values))
+            }
+            Value::VectorOptionString { len, entries } => {
+                let (indices, values) = entries.iter().cloned().unzip();
+                (*len, indices, Value::VectorString(values))
+            }
+            _ if self.is_vector() => (self.len(), (0..self.len()).collect(), self.clone()),
+            _ => return None,
+        };
+        Some((len, indices, values))
     }
 
-    #[test]
-    fn test_matrix_type() {
-        let m = Value::Matrix(vec![
-            Value::VectorInt(vec![1, 2]),
-            Value::VectorInt(vec![3, 4]),
-        ]);
-        assert!(m.is_matrix());
-        assert!(!m.is_scalar());
-        assert!(!m.is_vector());
-        assert_eq!(m.rank(), 2);
-        assert_eq!(m.len(), 2);
+    /// Build an option vector from the parts returned by [`Value::option_parts`].
+    fn from_option_parts(len: usize, indices: Vec<usize>, values: Value) -> Result<Value> {
+        check_same_len(indices.len(), values.len())?;
+        match values {
+            Value::VectorInt(v) => Ok(Value::VectorOptionInt {
+                len,
+                entries: indices.into_iter().zip(v).collect(),
+            }),
+            Value::VectorFloat(v) => Ok(Value::VectorOptionFloat {
+                len,
+                entries: indices.into_iter().zip(v).collect(),
+            }),
+            Value::VectorBool(v) => Ok(Value::VectorOptionBool {
+                len,
+                entries: indices.into_iter().zip(v).collect(),
+            }),
+            Value::VectorString(v) => Ok(Value::VectorOptionString {
+                len,
+                entries: indices.into_iter().zip(v).collect(),
+            }),
+            other => Err(SoAKitError::InvalidArgument(format!(
+                "Option vectors cannot hold {} values",
+                variant_name(&other)
+            ))),
+        }
     }
 
-    #[test]
-    fn test_get_element() {
-        let v = Value::VectorInt(vec![10, 20, 30]);
-        assert_eq!(v.get_element(0).unwrap(), Value::ScalarInt(10));
-        assert_eq!(v.get_element(1).unwrap(), Value::ScalarInt(20));
-        assert!(v.get_element(10).is_err());
+    /// The present values of an option vector as a dense vector, or `None` if
+    /// the value is not an option vector.
+    fn present_values(&self) -> Option<Value> {
+        if self.is_sparse() {
+            self.option_parts().map(|(_, _, values)| values)
+        } else {
+            None
+        }
     }
 
-    #[test]
-    fn test_scalar_edge_values() {
-        // Test zero, negative, and extreme values
-        let zero_int = Value::ScalarInt(0);
-        let neg_int = Value::ScalarInt(-42);
-        let max_int = Value::ScalarInt(i64::MAX);
-        let min_int = Value::ScalarInt(i64::MIN);
-
-        assert!(zero_int.is_scalar());
-        assert_eq!(zero_int.rank(), 0);
-        assert_eq!(zero_int.len(), 1);
-        assert!(!zero_int.is_empty());
-
-        assert!(neg_int.is_scalar());
-        assert!(max_int.is_scalar());
-        assert!(min_int.is_scalar());
-
-        // Test float edge values
-        let zero_float = Value::ScalarFloat(0.0);
-        let neg_float = Value::ScalarFloat(-3.14);
-        let inf_float = Value::ScalarFloat(f64::INFINITY);
-        let neg_inf_float = Value::ScalarFloat(f64::NEG_INFINITY);
-        let nan_float = Value::ScalarFloat(f64::NAN);
-
-        assert!(zero_float.is_scalar());
-        assert!(neg_float.is_scalar());
-        assert!(inf_float.is_scalar());
-        assert!(neg_inf_float.is_scalar());
-        assert!(nan_float.is_scalar());
-
-        // Test empty string
-        let empty_str = Value::ScalarString(String::new());
-        assert!(empty_str.is_scalar());
-        assert_eq!(empty_str.len(), 1);
-        assert!(!empty_str.is_empty());
+    /// Borrow the elements of a `VectorInt` without copying.
+    ///
+    /// # Returns
+    ///
+    /// `Some(&[i64])` for a `VectorInt`, `None` for every other variant.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Value;
+    ///
+    /// let v = Value::VectorInt(vec![1, 2, 3]);
+    /// let sum: i64 = v.as_int_slice().unwrap().iter().sum();
+    /// assert_eq!(sum, 6);
+    /// assert!(Value::VectorFloat(vec![1.0]).as_int_slice().is_none());
+    /// ```
+    pub fn as_int_slice(&self) -> Option<&[i64]> {
+        match self {
+            Value::VectorInt(v) => Some(v),
+            _ => None,
+        }
     }
 
-    #[test]
-    fn test_empty_vectors() {
-        let empty_int = Value::VectorInt(vec![]);
-        let empty_float = Value::VectorFloat(vec![]);
-        let empty_bool = Value::VectorBool(vec![]);
-        let empty_string = Value::VectorString(vec![]);
-
-        assert!(empty_int.is_vector());
-        assert!(empty_int.is_empty());
-        assert_eq!(empty_int.len(), 0);
-        assert_eq!(empty_int.rank(), 1);
-        assert_eq!(empty_int.shape(), vec![0]);
-
-        assert!(empty_float.is_empty());
-        assert!(empty_bool.is_empty());
-        assert!(empty_string.is_empty());
+    /// Borrow the elements of a `VectorFloat` without copying.
+    ///
+    /// # Returns
+    ///
+    /// `Some(&[f64])` for a `VectorFloat`, `None` for every other variant.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Value;
+    ///
+    /// let v = Value::VectorFloat(vec![0.5, 1.5]);
+    /// assert_eq!(v.as_float_slice(), Some(&[0.5, 1.5][..]));
+    /// ```
+    pub fn as_float_slice(&self) -> Option<&[f64]> {
+        match self {
+            Value::VectorFloat(v) => Some(v),
+            _ => None,
+        }
     }
 
-    #[test]
-    fn test_single_element_vectors() {
+    /// Borrow the elements of a `VectorBool` without copying.
+    ///
+    /// # Returns
+    ///
+    /// `Some(&[bool])` for a `VectorBool`, `None` for every other variant.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Value;
+    ///
+    /// let mask = Value::VectorBool(vec![true, false]);
+    /// assert_eq!(mask.as_bool_slice(), Some(&[true, false][..]));
+    /// ```
+    pub fn as_bool_slice(&self) -> Option<&[bool]> {
+        match self {
+            Value::VectorBool(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Borrow the elements of a `VectorString` without copying.
+    ///
+    /// # Returns
+    ///
+    /// `Some(&[String])` for a `VectorString`, `None` for every other variant.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Value;
+    ///
+    /// let names = Value::VectorString(vec!["a".to_string()]);
+    /// assert_eq!(names.as_str_slice().map(|s| s.len()), Some(1));
+    /// ```
+    pub fn as_str_slice(&self) -> Option<&[String]> {
+        match self {
+            Value::VectorString(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Split the value into its elements.
+    ///
+    /// This is the inverse of [`Value::from_scalars`]: vectors yield one scalar per
+    /// element, matrices yield their rows, tensors yield their sub-tensors along the
+    /// first axis, and a scalar (or rank-0 tensor) yields itself. Missing elements of
+    /// an option vector yield [`Value::Null`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Value;
+    ///
+    /// let v = Value::VectorInt(vec![1, 2]);
+    /// assert_eq!(v.to_scalars(), vec![Value::ScalarInt(1), Value::ScalarInt(2)]);
+    /// assert_eq!(Value::from_scalars(v.to_scalars()).unwrap(), v);
+    /// ```
+    pub fn to_scalars(&self) -> Vec<Value> {
+        match self {
+            Value::VectorInt(v) => v.iter().copied().map(Value::ScalarInt).collect(),
+            Value::VectorFloat(v) => v.iter().copied().map(Value::ScalarFloat).collect(),
+            Value::VectorBool(v) => v.iter().copied().map(Value::ScalarBool).collect(),
+            Value::VectorString(v) => v.iter().cloned().map(Value::ScalarString).collect(),
+            Value::VectorJson(v) => v.iter().cloned().map(Value::ScalarJson).collect(),
+            Value::Matrix(rows) => rows.clone(),
+            Value::Tensor { data, shape } => match shape.split_first() {
+                Some((&n, _)) => (0..n)
+                    .filter_map(|i| tensor_index(data, shape, i).ok())
+                    .collect(),
+                None => vec![self.clone()],
+            },
+            Value::VectorOptionInt { .. }
+            | Value::VectorOptionFloat { .. }
+            | Value::VectorOptionBool { .. }
+            | Value::VectorOptionString { .. } => {
+                let Some((len, indices, values)) = self.option_parts() else {
+                    return Vec::new();
+                };
+                let mut scalars = vec![Value::Null; len];
+                for (index, value) in indices.into_iter().zip(values.to_scalars()) {
+                    if let Some(slot) = scalars.get_mut(index) {
+                        *slot = value;
+                    }
+                }
+                scalars
+            }
+            Value::ScalarInt(_)
+            | Value::ScalarFloat(_)
+            | Value::ScalarBool(_)
+            | Value::ScalarString(_)
+            | Value::ScalarJson(_)
+            | Value::Null => vec![self.clone()],
+        }
+    }
+
+    /// Apply a function to every scalar element.
+    ///
+    /// Scalars are passed to `f` directly, vectors element by element, and
+    /// matrices row by row (recursively). Tensor elements are passed as
+    /// `ScalarFloat` and must map to `ScalarFloat`. The results of a vector are collected
+    /// back into a vector, so they must all be scalars of the same type; the
+    /// result type may differ from the input type. An empty vector is returned
+    /// unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - Function applied to each scalar element
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Value)` with the same shape as `self`.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error produced by `f`, or [`SoAKitError::InvalidArgument`] if
+    /// the results of one vector have mixed types.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Value;
+    ///
+    /// let v = Value::VectorInt(vec![1, 2, 3]);
+    /// let even = v.map_elements(|x| match x {
+    ///     Value::ScalarInt(i) => Ok(Value::ScalarBool(i % 2 == 0)),
+    ///     other => Ok(other),
+    /// }).unwrap();
+    /// assert_eq!(even, Value::VectorBool(vec![false, true, false]));
+    /// ```
+    pub fn map_elements<F>(&self, mut f: F) -> Result<Value>
+    where
+        F: FnMut(Value) -> Result<Value>,
+    {
+        self.map_elements_with(&mut f)
+    }
+
+    /// Recursive implementation of [`Value::map_elements`].
+    fn map_elements_with<F>(&self, f: &mut F) -> Result<Value>
+    where
+        F: FnMut(Value) -> Result<Value>,
+    {
+        match self {
+            _ if self.is_scalar() => f(self.clone()),
+            Value::Tensor { data, shape } => {
+                let data = data
+                    .iter()
+                    .map(|&x| match f(Value::ScalarFloat(x))? {
+                        Value::ScalarFloat(y) => Ok(y),
+                        other => Err(SoAKitError::InvalidArgument(format!(
+                            "Tensor elements must map to ScalarFloat, got {}",
+                            variant_name(&other)
+                        ))),
+                    })
+                    .collect::<Result<_>>()?;
+                Ok(Value::Tensor {
+                    data,
+                    shape: shape.clone(),
+                })
+            }
+            Value::Matrix(rows) => Ok(Value::Matrix(
+                rows.iter()
+                    .map(|row| row.map_elements_with(f))
+                    .collect::<Result<_>>()?,
+            )),
+            _ if self.is_empty() => Ok(self.clone()),
+            _ => Value::from_scalars(
+                self.to_scalars()
+                    .into_iter()
+                    .map(f)
+                    .collect::<Result<_>>()?,
+            ),
+        }
+    }
+
+    /// Combine two values element by element.
+    ///
+    /// Both values must have the same shape: two scalars are passed to `f`
+    /// directly, two vectors pairwise, and two matrices row by row. As with
+    /// [`Value::map_elements`], the per-element results of a vector must be
+    /// scalars of one type, and an empty pair of vectors yields `self` unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The value to combine with
+    /// * `f` - Function applied to each pair of scalar elements
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Value)` with the same shape as the inputs.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::LengthMismatch`] if the values have different lengths
+    /// - [`SoAKitError::InvalidArgument`] if the values have different ranks or the
+    ///   results have mixed types
+    /// - Any error produced by `f`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Value;
+    ///
+    /// let a = Value::VectorInt(vec![1, 5]);
+    /// let b = Value::VectorInt(vec![4, 2]);
+    /// let max = a.zip_with(&b, |x, y| {
+    ///     Ok(if x.total_cmp(&y).is_ge() { x } else { y })
+    /// }).unwrap();
+    /// assert_eq!(max, Value::VectorInt(vec![4, 5]));
+    /// ```
+    pub fn zip_with<F>(&self, other: &Value, mut f: F) -> Result<Value>
+    where
+        F: FnMut(Value, Value) -> Result<Value>,
+    {
+        self.zip_with_impl(other, &mut f)
+    }
+
+    /// Recursive implementation of [`Value::zip_with`].
+    fn zip_with_impl<F>(&self, other: &Value, f: &mut F) -> Result<Value>
+    where
+        F: FnMut(Value, Value) -> Result<Value>,
+    {
+        if let (
+            Value::Tensor { data: a, shape },
+            Value::Tensor {
+                data: b,
+                shape: other_shape,
+            },
+        ) = (self, other)
+        {
+            check_same_shape(shape, other_shape)?;
+            let data = a
+                .iter()
+                .zip(b.iter())
+                .map(
+                    |(&x, &y)| match f(Value::ScalarFloat(x), Value::ScalarFloat(y))? {
+                        Value::ScalarFloat(z) => Ok(z),
+                        result => Err(SoAKitError::InvalidArgument(format!(
+                            "Tensor elements must map to ScalarFloat, got {}",
+                            variant_name(&result)
+                        ))),
+                    },
+                )
+                .collect::<Result<_>>()?;
+            return Ok(Value::Tensor {
+                data,
+                shape: shape.clone(),
+            });
+        }
+        if self.rank() != other.rank() {
+            return Err(SoAKitError::InvalidArgument(format!(
+                "Cannot zip {} with {}",
+                variant_name(self),
+                variant_name(other)
+            )));
+        }
+        if self.is_scalar() {
+            return f(self.clone(), other.clone());
+        }
+        check_same_len(self.len(), other.len())?;
+        match (self, other) {
+            (Value::Matrix(a), Value::Matrix(b)) => Ok(Value::Matrix(
+                a.iter()
+                    .zip(b.iter())
+                    .map(|(x, y)| x.zip_with_impl(y, f))
+                    .collect::<Result<_>>()?,
+            )),
+            _ if self.is_empty() => Ok(self.clone()),
+            _ => Value::from_scalars(
+                self.to_scalars()
+                    .into_iter()
+                    .zip(other.to_scalars())
+                    .map(|(x, y)| f(x, y))
+                    .collect::<Result<_>>()?,
+            ),
+        }
+    }
+
+    /// Copy a contiguous range of elements out of a vector or matrix.
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - Element range to copy (any `usize` range, e.g. `1..3` or `2..`)
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Value)` of the same variant containing only the selected elements.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if the value is a scalar or the range is reversed
+    /// - [`SoAKitError::IndexOutOfBounds`] if the range extends past the end
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Value;
+    ///
+    /// let v = Value::VectorInt(vec![10, 20, 30, 40]);
+    /// assert_eq!(v.slice(1..3).unwrap(), Value::VectorInt(vec![20, 30]));
+    /// assert_eq!(v.slice(3..).unwrap(), Value::VectorInt(vec![40]));
+    /// assert!(v.slice(2..5).is_err());
+    /// ```
+    pub fn slice(&self, range: impl RangeBounds<usize>) -> Result<Value> {
+        match self {
+            Value::VectorInt(v) => Ok(Value::VectorInt(slice_range(v, &range)?)),
+            Value::VectorFloat(v) => Ok(Value::VectorFloat(slice_range(v, &range)?)),
+            Value::VectorBool(v) => Ok(Value::VectorBool(slice_range(v, &range)?)),
+            Value::VectorString(v) => Ok(Value::VectorString(slice_range(v, &range)?)),
+            Value::VectorJson(v) => Ok(Value::VectorJson(slice_range(v, &range)?)),
+            Value::Matrix(v) => Ok(Value::Matrix(slice_range(v, &range)?)),
+            Value::Tensor { data, shape } => {
+                let Some((&n, rest)) = shape.split_first() else {
+                    return Err(SoAKitError::InvalidArgument(
+                        "Cannot slice a rank-0 tensor".to_string(),
+                    ));
+                };
+                // Slice row indices, then map them onto the flat data
+                let rows = slice_range(&(0..n).collect::<Vec<usize>>(), &range)?;
+                let stride = shape_product(rest).unwrap_or(0);
+                let start = rows.first().copied().unwrap_or(0).saturating_mul(stride);
+                let len = rows.len().saturating_mul(stride);
+                let data = data
+                    .get(start..start.saturating_add(len))
+                    .map(<[f64]>::to_vec)
+                    .unwrap_or_default();
+                let mut shape = shape.clone();
+                if let Some(first) = shape.first_mut() {
+                    *first = rows.len();
+                }
+                Ok(Value::Tensor { data, shape })
+            }
+            Value::VectorOptionInt { .. }
+            | Value::VectorOptionFloat { .. }
+            | Value::VectorOptionBool { .. }
+            | Value::VectorOptionString { .. } => {
+                let Some((len, indices, values)) = self.option_parts() else {
+                    return Err(SoAKitError::InvalidArgument(
+                        "Cannot slice a scalar value".to_string(),
+                    ));
+                };
+                let (start, end) = resolve_range(len, &range)?;
+                // Indices are sorted, so the present elements in range are contiguous
+                let lo = indices.partition_point(|&i| i < start);
+                let hi = indices.partition_point(|&i| i < end);
+                let kept = indices
+                    .get(lo..hi)
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|&i| i.saturating_sub(start))
+                    .collect();
+                Value::from_option_parts(end.saturating_sub(start), kept, values.slice(lo..hi)?)
+            }
+            Value::ScalarInt(_)
+            | Value::ScalarFloat(_)
+            | Value::ScalarBool(_)
+            | Value::ScalarString(_)
+            | Value::ScalarJson(_)
+            | Value::Null => Err(SoAKitError::InvalidArgument(
+                "Cannot slice a scalar value".to_string(),
+            )),
+        }
+    }
+
+    /// Concatenate several vectors (or matrices) of the same variant.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The values to join, in order
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Value)` holding every element of `values`, in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SoAKitError::InvalidArgument`] if `values` is empty, contains a
+    /// scalar, or mixes variants.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Value;
+    ///
+    /// let joined = Value::concat(&[
+    ///     Value::VectorFloat(vec![1.0]),
+    ///     Value::VectorFloat(vec![2.0, 3.0]),
+    /// ]).unwrap();
+    /// assert_eq!(joined, Value::VectorFloat(vec![1.0, 2.0, 3.0]));
+    /// ```
+    pub fn concat(values: &[Value]) -> Result<Value> {
+        let (first, rest) = values.split_first().ok_or_else(|| {
+            SoAKitError::InvalidArgument("Cannot concatenate an empty list of values".to_string())
+        })?;
+        if first.is_scalar() {
+            return Err(SoAKitError::InvalidArgument(
+                "Cannot concatenate scalar values".to_string(),
+            ));
+        }
+        let mut result = first.clone();
+        for value in rest {
+            result.append(value.clone())?;
+        }
+        Ok(result)
+    }
+
+    /// Split a vector or matrix into two at an element index.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - Number of elements in the first half
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok((head, tail))` where `head` holds elements `0..n` and `tail`
+    /// holds the rest.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::InvalidArgument`] if the value is a scalar
+    /// - [`SoAKitError::IndexOutOfBounds`] if `n` is greater than the length
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Value;
+    ///
+    /// let v = Value::VectorBool(vec![true, false, true]);
+    /// let (head, tail) = v.split_at(1).unwrap();
+    /// assert_eq!(head, Value::VectorBool(vec![true]));
+    /// assert_eq!(tail, Value::VectorBool(vec![false, true]));
+    /// ```
+    pub fn split_at(&self, n: usize) -> Result<(Value, Value)> {
+        Ok((self.slice(..n)?, self.slice(n..)?))
+    }
+
+    /// Convert a vector to an option vector, treating elements equal to `missing`
+    /// as missing.
+    ///
+    /// Elements are compared with [`Value::equals_nan_aware`], so `missing` may be
+    /// NaN. Elements already missing in an option vector stay missing; pass
+    /// [`Value::Null`] to keep every present element.
+    ///
+    /// # Arguments
+    ///
+    /// * `missing` - The element value that marks a missing element
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Value)` holding an option vector of the same kind and length.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SoAKitError::InvalidArgument`] if the value is not a vector.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Value;
+    ///
+    /// let dense = Value::VectorInt(vec![0, 0, 7, 0]);
+    /// let sparse = dense.to_sparse(&Value::ScalarInt(0)).unwrap();
+    /// assert_eq!(sparse, Value::VectorOptionInt { len: 4, entries: vec![(2, 7)] });
+    /// assert_eq!(sparse.get_element(1).unwrap(), Value::Null);
+    /// assert_eq!(sparse.to_dense(&Value::ScalarInt(0)).unwrap(), dense);
+    /// ```
+    pub fn to_sparse(&self, missing: &Value) -> Result<Value> {
+        let (len, indices, values) = self.option_parts().ok_or_else(|| {
+            SoAKitError::InvalidArgument(format!(
+                "Cannot convert {} to an option vector",
+                variant_name(self)
+            ))
+        })?;
+        let (kept_indices, kept): (Vec<usize>, Vec<Value>) = indices
+            .into_iter()
+            .zip(values.to_scalars())
+            .filter(|(_, v)| !v.equals_nan_aware(missing))
+            .unzip();
+        let kept = if kept.is_empty() {
+            values.slice(..0)?
+        } else {
+            Value::from_scalars(kept)?
+        };
+        Value::from_option_parts(len, kept_indices, kept)
+    }
+
+    /// Convert an option vector to a dense vector, replacing missing elements with
+    /// `fill`.
+    ///
+    /// Dense vectors are returned unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `fill` - Scalar stored in place of each missing element
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Value)` holding a dense vector of the same length.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SoAKitError::InvalidArgument`] if the value is not a vector, or if
+    /// `fill` is not a non-null scalar of the vector's type.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Value;
+    ///
+    /// let sparse = Value::VectorOptionFloat { len: 3, entries: vec![(1, 2.5)] };
+    /// assert_eq!(
+    ///     sparse.to_dense(&Value::ScalarFloat(0.0)).unwrap(),
+    ///     Value::VectorFloat(vec![0.0, 2.5, 0.0])
+    /// );
+    /// assert!(sparse.to_dense(&Value::ScalarInt(0)).is_err());
+    /// ```
+    pub fn to_dense(&self, fill: &Value) -> Result<Value> {
+        if !self.is_vector() {
+            return Err(SoAKitError::InvalidArgument(format!(
+                "Cannot convert {} to a dense vector",
+                variant_name(self)
+            )));
+        }
+        if !self.is_sparse() {
+            return Ok(self.clone());
+        }
+        if !fill.is_scalar() || fill.is_null() {
+            return Err(SoAKitError::InvalidArgument(
+                "Fill value must be a non-null scalar".to_string(),
+            ));
+        }
+        let scalars = self
+            .to_scalars()
+            .into_iter()
+            .map(|v| if v.is_null() { fill.clone() } else { v })
+            .collect();
+        Value::from_scalars(scalars)
+    }
+
+    /// Create a tensor from row-major data and a shape.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Elements in row-major order
+    /// * `shape` - Size of each dimension; an empty shape makes a rank-0 tensor
+    ///   holding exactly one element
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Value::Tensor)` if the shape matches the data.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::LengthMismatch`] if the product of `shape` differs from `data.len()`
+    /// - [`SoAKitError::InvalidArgument`] if the product of `shape` overflows
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Value;
+    ///
+    /// let t = Value::tensor(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], vec![2, 3]).unwrap();
+    /// assert_eq!(t.shape(), vec![2, 3]);
+    /// assert_eq!(t.rank(), 2);
+    /// assert!(Value::tensor(vec![1.0, 2.0], vec![3]).is_err());
+    /// ```
+    pub fn tensor(data: Vec<f64>, shape: Vec<usize>) -> Result<Value> {
+        let expected = shape_product(&shape)?;
+        check_same_len(expected, data.len())?;
+        Ok(Value::Tensor { data, shape })
+    }
+
+    /// Give a tensor (or numeric vector) a new shape.
+    ///
+    /// The data is reused in row-major order, so the new shape must describe the
+    /// same number of elements. `VectorInt` and `VectorFloat` values are converted
+    /// to tensors first, which makes this a convenient way to build tensors.
+    ///
+    /// # Arguments
+    ///
+    /// * `shape` - The new size of each dimension
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Value::Tensor)` with the new shape.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::LengthMismatch`] if the element counts differ
+    /// - [`SoAKitError::InvalidArgument`] if the value is not a tensor or numeric vector
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Value;
+    ///
+    /// let t = Value::VectorInt(vec![1, 2, 3, 4]).reshape(vec![2, 2]).unwrap();
+    /// assert_eq!(t.row(1).unwrap(), Value::tensor(vec![3.0, 4.0], vec![2]).unwrap());
+    /// assert_eq!(t.reshape(vec![4]).unwrap().shape(), vec![4]);
+    /// ```
+    pub fn reshape(&self, shape: Vec<usize>) -> Result<Value> {
+        let data = match self {
+            Value::Tensor { data, .. } | Value::VectorFloat(data) => data.clone(),
+            Value::VectorInt(v) => v.iter().map(|&x| int_to_float(x)).collect(),
+            _ => {
+                return Err(SoAKitError::InvalidArgument(format!(
+                    "Cannot reshape {}",
+                    variant_name(self)
+                )));
+            }
+        };
+        Value::tensor(data, shape)
+    }
+
+    /// Get row `i` of a matrix or tensor.
+    ///
+    /// For a `Matrix` this is the row vector at index `i`. For a tensor it is the
+    /// sub-tensor at index `i` of the first axis, so a tensor of shape `[n, a, b]`
+    /// yields a tensor of shape `[a, b]`.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::IndexOutOfBounds`] if `i` is past the first dimension
+    /// - [`SoAKitError::InvalidArgument`] if the value is not a matrix or a tensor
+    ///   of rank 1 or more
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Value;
+    ///
+    /// let t = Value::tensor(vec![1.0, 2.0, 3.0, 4.0], vec![2, 2]).unwrap();
+    /// assert_eq!(t.row(0).unwrap(), Value::tensor(vec![1.0, 2.0], vec![2]).unwrap());
+    ///
+    /// let m = Value::Matrix(vec![Value::VectorInt(vec![1, 2]), Value::VectorInt(vec![3, 4])]);
+    /// assert_eq!(m.row(1).unwrap(), Value::VectorInt(vec![3, 4]));
+    /// ```
+    pub fn row(&self, i: usize) -> Result<Value> {
+        match self {
+            Value::Matrix(rows) => rows.get(i).cloned().ok_or(SoAKitError::IndexOutOfBounds {
+                index: i,
+                max: rows.len(),
+            }),
+            Value::Tensor { data, shape } => tensor_index(data, shape, i),
+            _ => Err(SoAKitError::InvalidArgument(format!(
+                "row is not supported on {}",
+                variant_name(self)
+            ))),
+        }
+    }
+
+    /// Get column `j` of a matrix or tensor.
+    ///
+    /// For a `Matrix` this collects element `j` of every row into a vector; the
+    /// matrix must be rectangular. For a tensor it is the sub-tensor at index `j`
+    /// of the second axis, so a tensor of shape `[n, m, b]` yields a tensor of
+    /// shape `[n, b]`.
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::IndexOutOfBounds`] if `j` is past the second dimension
+    /// - [`SoAKitError::InvalidArgument`] if the value is not a rectangular matrix or
+    ///   a tensor of rank 2 or more
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Value;
+    ///
+    /// let t = Value::tensor(vec![1.0, 2.0, 3.0, 4.0], vec![2, 2]).unwrap();
+    /// assert_eq!(t.col(1).unwrap(), Value::tensor(vec![2.0, 4.0], vec![2]).unwrap());
+    ///
+    /// let m = Value::Matrix(vec![Value::VectorInt(vec![1, 2]), Value::VectorInt(vec![3, 4])]);
+    /// assert_eq!(m.col(0).unwrap(), Value::VectorInt(vec![1, 3]));
+    /// ```
+    pub fn col(&self, j: usize) -> Result<Value> {
+        match self {
+            Value::Matrix(rows) => {
+                let (_, cols) = matrix_dims(rows)?;
+                if j >= cols {
+                    return Err(SoAKitError::IndexOutOfBounds {
+                        index: j,
+                        max: cols,
+                    });
+                }
+                let column = rows
+                    .iter()
+                    .map(|row| row.get_element(j))
+                    .collect::<Result<Vec<Value>>>()?;
+                Value::from_scalars(column)
+            }
+            Value::Tensor { data, shape } => tensor_column(data, shape, j),
+            _ => Err(SoAKitError::InvalidArgument(format!(
+                "col is not supported on {}",
+                variant_name(self)
+            ))),
+        }
+    }
+
+    /// Transpose a rectangular matrix or a rank-2 tensor.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Value)` of the same variant with rows and columns swapped.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SoAKitError::InvalidArgument`] if the value is not a rectangular
+    /// matrix (every row a vector of the same type and length) or a rank-2 tensor.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Value;
+    ///
+    /// let m = Value::Matrix(vec![
+    ///     Value::VectorInt(vec![1, 2, 3]),
+    ///     Value::VectorInt(vec![4, 5, 6]),
+    /// ]);
+    /// let t = m.transpose().unwrap();
+    /// assert_eq!(t.shape(), vec![3, 2]);
+    /// assert_eq!(t.row(0).unwrap(), Value::VectorInt(vec![1, 4]));
+    /// ```
+    pub fn transpose(&self) -> Result<Value> {
+        match self {
+            Value::Matrix(rows) => {
+                let (_, cols) = matrix_dims(rows)?;
+                (0..cols)
+                    .map(|j| self.col(j))
+                    .collect::<Result<Vec<Value>>>()
+                    .map(Value::Matrix)
+            }
+            Value::Tensor { data, shape } => match shape.as_slice() {
+                &[rows, cols] => {
+                    let mut out = Vec::with_capacity(data.len());
+                    for j in 0..cols {
+                        out.extend(data.iter().skip(j).step_by(cols.max(1)).take(rows));
+                    }
+                    Value::tensor(out, vec![cols, rows])
+                }
+                _ => Err(SoAKitError::InvalidArgument(format!(
+                    "transpose requires a rank-2 tensor, got shape {:?}",
+                    shape
+                ))),
+            },
+            _ => Err(SoAKitError::InvalidArgument(format!(
+                "transpose is not supported on {}",
+                variant_name(self)
+            ))),
+        }
+    }
+
+    /// Matrix product of two matrices or two rank-2 tensors.
+    ///
+    /// Multiplying an `n x k` operand by a `k x m` operand yields an `n x m`
+    /// result. Two integer matrices produce an integer matrix (with checked
+    /// arithmetic); any float operand produces floats. If either operand is a
+    /// tensor the result is a tensor, otherwise it is a `Matrix`.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The right-hand operand
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Value)` holding the product.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SoAKitError::InvalidArgument`] if either operand is not a
+    /// rectangular numeric matrix or rank-2 tensor, the inner dimensions differ, or
+    /// integer arithmetic overflows.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Value;
+    ///
+    /// let a = Value::Matrix(vec![Value::VectorInt(vec![1, 2]), Value::VectorInt(vec![3, 4])]);
+    /// let identity = Value::Matrix(vec![Value::VectorInt(vec![1, 0]), Value::VectorInt(vec![0, 1])]);
+    /// assert_eq!(a.matmul(&identity).unwrap(), a);
+    ///
+    /// let col = Value::Matrix(vec![Value::VectorFloat(vec![0.5]), Value::VectorFloat(vec![1.0])]);
+    /// assert_eq!(
+    ///     a.matmul(&col).unwrap(),
+    ///     Value::Matrix(vec![Value::VectorFloat(vec![2.5]), Value::VectorFloat(vec![5.5])])
+    /// );
+    /// ```
+    pub fn matmul(&self, other: &Value) -> Result<Value> {
+        let (n, k, lhs) = float_grid(self)?;
+        let (k2, m, rhs) = float_grid(other)?;
+        if k != k2 {
+            return Err(SoAKitError::InvalidArgument(format!(
+                "Cannot multiply {}x{} by {}x{}",
+                n, k, k2, m
+            )));
+        }
+
+        if let (Some(a), Some(b)) = (int_grid(self), int_grid(other)) {
+            let mut rows = Vec::with_capacity(n);
+            for row in a.chunks(k.max(1)).take(n) {
+                let mut out = Vec::with_capacity(m);
+                for j in 0..m {
+                    let column = b.iter().skip(j).step_by(m);
+                    let dot = row.iter().zip(column).try_fold(0i64, |acc, (x, y)| {
+                        x.checked_mul(*y).and_then(|p| acc.checked_add(p))
+                    });
+                    out.push(dot.ok_or_else(|| {
+                        SoAKitError::InvalidArgument("Integer overflow in matmul".to_string())
+                    })?);
+                }
+                rows.push(Value::VectorInt(out));
+            }
+            // k == 0 leaves no chunks; the product is then all zeros
+            rows.resize(n, Value::VectorInt(vec![0; m]));
+            return Ok(Value::Matrix(rows));
+        }
+
+        let mut data = Vec::with_capacity(n.saturating_mul(m));
+        for i in 0..n {
+            let start = i.saturating_mul(k);
+            let row = lhs.get(start..start.saturating_add(k)).unwrap_or_default();
+            for j in 0..m {
+                let column = rhs.iter().skip(j).step_by(m);
+                data.push(row.iter().zip(column).map(|(x, y)| x * y).sum());
+            }
+        }
+        if self.is_tensor() || other.is_tensor() {
+            Value::tensor(data, vec![n, m])
+        } else {
+            Ok(Value::Matrix(
+                data.chunks(m.max(1))
+                    .take(n)
+                    .map(|row| Value::VectorFloat(row.to_vec()))
+                    .collect(),
+            ))
+        }
+    }
+
+    /// Sum the elements of a vector or tensor.
+    ///
+    /// NaN elements and missing elements of option vectors are ignored, so the sum
+    /// of a float vector is the sum of its non-NaN elements. The sum of an empty
+    /// vector is zero.
+    ///
+    /// # Returns
+    ///
+    /// - `ScalarInt` for `VectorInt`, and for `VectorBool` (the number of `true` values)
+    /// - `ScalarFloat` for `VectorFloat` and `Tensor`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SoAKitError::InvalidArgument`] if integer addition overflows or the
+    /// value is not a numeric or boolean vector or a tensor.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Value;
+    ///
+    /// assert_eq!(Value::VectorInt(vec![1, 2, 3]).sum().unwrap(), Value::ScalarInt(6));
+    /// assert_eq!(
+    ///     Value::VectorFloat(vec![1.5, f64::NAN, 2.0]).sum().unwrap(),
+    ///     Value::ScalarFloat(3.5)
+    /// );
+    /// ```
+    pub fn sum(&self) -> Result<Value> {
+        if let Some(values) = self.present_values() {
+            return values.sum();
+        }
+        match self {
+            Value::VectorInt(v) => v
+                .iter()
+                .try_fold(0i64, |acc, &x| acc.checked_add(x))
+                .map(Value::ScalarInt)
+                .ok_or_else(|| SoAKitError::InvalidArgument("Integer overflow in sum".to_string())),
+            Value::VectorBool(v) => count_to_value(v.iter().filter(|&&b| b).count()),
+            Value::VectorFloat(_) | Value::Tensor { .. } => {
+                Ok(Value::ScalarFloat(self.non_nan_floats("sum")?.iter().sum()))
+            }
+            _ => Err(unsupported_aggregate("sum", self)),
+        }
+    }
+
+    /// Arithmetic mean of the elements of a numeric vector or tensor.
+    ///
+    /// NaN elements are ignored. Booleans count as 0 and 1, so the mean of a
+    /// `VectorBool` is the fraction of `true` values.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Value::ScalarFloat)` with the mean.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SoAKitError::InvalidArgument`] if there are no non-NaN elements or
+    /// the value is not a numeric or boolean vector or a tensor.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Value;
+    ///
+    /// assert_eq!(Value::VectorInt(vec![1, 2]).mean().unwrap(), Value::ScalarFloat(1.5));
+    /// assert!(Value::VectorFloat(vec![f64::NAN]).mean().is_err());
+    /// ```
+    pub fn mean(&self) -> Result<Value> {
+        let values = self.non_nan_floats("mean")?;
+        Ok(Value::ScalarFloat(
+            mean_of(&values).ok_or_else(|| empty_aggregate("mean"))?,
+        ))
+    }
+
+    /// Smallest element of a vector or tensor.
+    ///
+    /// Elements are compared with [`Value::total_cmp`]; NaN elements are ignored.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Value)` holding the smallest element as a scalar of the
+    /// vector's type (`ScalarFloat` for tensors).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SoAKitError::InvalidArgument`] if there are no non-NaN elements or
+    /// the value is not a vector or tensor.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Value;
+    ///
+    /// let v = Value::VectorFloat(vec![3.0, f64::NAN, -1.0]);
+    /// assert_eq!(v.min().unwrap(), Value::ScalarFloat(-1.0));
+    /// let names = Value::VectorString(vec!["b".to_string(), "a".to_string()]);
+    /// assert_eq!(names.min().unwrap(), Value::ScalarString("a".to_string()));
+    /// ```
+    pub fn min(&self) -> Result<Value> {
+        self.extreme("min", Ordering::Less)
+    }
+
+    /// Largest element of a vector or tensor.
+    ///
+    /// Elements are compared with [`Value::total_cmp`]; NaN elements are ignored.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Value)` holding the largest element as a scalar of the
+    /// vector's type (`ScalarFloat` for tensors).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SoAKitError::InvalidArgument`] if there are no non-NaN elements or
+    /// the value is not a vector or tensor.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Value;
+    ///
+    /// let v = Value::VectorFloat(vec![3.0, f64::NAN, -1.0]);
+    /// assert_eq!(v.max().unwrap(), Value::ScalarFloat(3.0));
+    /// assert!(Value::VectorInt(vec![]).max().is_err());
+    /// ```
+    pub fn max(&self) -> Result<Value> {
+        self.extreme("max", Ordering::Greater)
+    }
+
+    /// Population standard deviation of a numeric vector or tensor.
+    ///
+    /// NaN elements are ignored. This divides by the number of elements `n`
+    /// (not `n - 1`), so a single element has a standard deviation of zero.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Value::ScalarFloat)` with the standard deviation.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SoAKitError::InvalidArgument`] if there are no non-NaN elements or
+    /// the value is not a numeric or boolean vector or a tensor.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Value;
+    ///
+    /// let v = Value::VectorInt(vec![2, 4, 4, 4, 5, 5, 7, 9]);
+    /// assert_eq!(v.std().unwrap(), Value::ScalarFloat(2.0));
+    /// ```
+    pub fn std(&self) -> Result<Value> {
+        let values = self.non_nan_floats("std")?;
+        let mean = mean_of(&values).ok_or_else(|| empty_aggregate("std"))?;
+        let squared: Vec<f64> = values.iter().map(|x| (x - mean) * (x - mean)).collect();
+        let variance = mean_of(&squared).ok_or_else(|| empty_aggregate("std"))?;
+        Ok(Value::ScalarFloat(variance.sqrt()))
+    }
+
+    /// Count the non-null elements of a vector or tensor.
+    ///
+    /// NaN floats and missing elements of option vectors are treated as null; every
+    /// other element counts.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Value::ScalarInt)` with the count.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SoAKitError::InvalidArgument`] if the value is not a vector or tensor.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Value;
+    ///
+    /// let v = Value::VectorFloat(vec![1.0, f64::NAN, 2.0]);
+    /// assert_eq!(v.count_nonnull().unwrap(), Value::ScalarInt(2));
+    /// ```
+    pub fn count_nonnull(&self) -> Result<Value> {
+        if let Some(values) = self.present_values() {
+            return values.count_nonnull();
+        }
+        match self {
+            Value::VectorFloat(v) | Value::Tensor { data: v, .. } => {
+                count_to_value(v.iter().filter(|x| !x.is_nan()).count())
+            }
+            _ if self.is_vector() => count_to_value(self.len()),
+            _ => Err(unsupported_aggregate("count_nonnull", self)),
+        }
+    }
+
+    /// Count the null elements of a vector or tensor.
+    ///
+    /// This counts the elements [`Value::count_nonnull`] leaves out: NaN floats and
+    /// missing elements of option vectors.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Value::ScalarInt)` with the count.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SoAKitError::InvalidArgument`] if the value is not a vector or tensor.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Value;
+    ///
+    /// let v = Value::from_scalars(vec![Value::ScalarInt(1), Value::Null, Value::Null]).unwrap();
+    /// assert_eq!(v.count_null().unwrap(), Value::ScalarInt(2));
+    /// ```
+    pub fn count_null(&self) -> Result<Value> {
+        count_to_value(self.null_count()?)
+    }
+
+    /// Count the null elements of a vector or tensor, as [`Value::count_null`] does.
+    pub(crate) fn null_count(&self) -> Result<usize> {
+        let total = match self {
+            Value::Tensor { data, .. } => data.len(),
+            _ => self.len(),
+        };
+        match self.count_nonnull()? {
+            Value::ScalarInt(nonnull) => {
+                Ok(total.saturating_sub(usize::try_from(nonnull).unwrap_or(total)))
+            }
+            _ => Err(unsupported_aggregate("count_null", self)),
+        }
+    }
+
+    /// Apply an aggregation chosen at runtime.
+    ///
+    /// This dispatches to [`sum`](Self::sum), [`mean`](Self::mean),
+    /// [`min`](Self::min), [`max`](Self::max), [`std`](Self::std),
+    /// [`count_nonnull`](Self::count_nonnull) or [`count_null`](Self::count_null).
+    /// Null elements are skipped; see [`Value::aggregate_with`] to propagate them.
+    ///
+    /// # Arguments
+    ///
+    /// * `agg` - The aggregation to apply
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of the chosen aggregation.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Agg, Value};
+    ///
+    /// let v = Value::VectorInt(vec![1, 2, 3]);
+    /// assert_eq!(v.aggregate(Agg::Sum).unwrap(), Value::ScalarInt(6));
+    /// assert_eq!(v.aggregate(Agg::Mean).unwrap(), Value::ScalarFloat(2.0));
+    /// ```
+    pub fn aggregate(&self, agg: Agg) -> Result<Value> {
+        match agg {
+            Agg::Sum => self.sum(),
+            Agg::Mean => self.mean(),
+            Agg::Min => self.min(),
+            Agg::Max => self.max(),
+            Agg::Std => self.std(),
+            Agg::Count => self.count_nonnull(),
+            Agg::NullCount => self.count_null(),
+        }
+    }
+
+    /// Apply an aggregation chosen at runtime, with a policy for null elements.
+    ///
+    /// With [`NullPolicy::Skip`] this is [`Value::aggregate`]. With
+    /// [`NullPolicy::Propagate`], any null element (a NaN float or a missing
+    /// element of an option vector) makes the result [`Value::Null`], so missing
+    /// data cannot go unnoticed. [`Agg::Count`] and [`Agg::NullCount`] count nulls
+    /// and are the same under either policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `agg` - The aggregation to apply
+    /// * `nulls` - What to do with null elements
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of the chosen aggregation.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Agg, NullPolicy, Value};
+    ///
+    /// let v = Value::VectorFloat(vec![1.0, f64::NAN, 3.0]);
+    /// assert_eq!(v.aggregate_with(Agg::Sum, NullPolicy::Skip).unwrap(), Value::ScalarFloat(4.0));
+    /// assert_eq!(v.aggregate_with(Agg::Sum, NullPolicy::Propagate).unwrap(), Value::Null);
+    /// assert_eq!(v.aggregate_with(Agg::NullCount, NullPolicy::Propagate).unwrap(), Value::ScalarInt(1));
+    /// ```
+    pub fn aggregate_with(&self, agg: Agg, nulls: NullPolicy) -> Result<Value> {
+        if nulls == NullPolicy::Propagate && agg.skips_nulls() && self.null_count()? > 0 {
+            return Ok(Value::Null);
+        }
+        self.aggregate(agg)
+    }
+
+    /// Running computation over the elements of a numeric vector.
+    ///
+    /// The result has one element per input element:
+    ///
+    /// - [`CumOp::Sum`]: the sum of the elements so far
+    /// - [`CumOp::Max`]: the largest element so far
+    /// - [`CumOp::Diff`]: the difference from the previous element
+    ///
+    /// NaN elements of a float vector are skipped by `Sum` and `Max` and stay NaN
+    /// in the output. The first element has no previous element, so its `Diff` is
+    /// NaN for floats and missing for integers.
+    ///
+    /// # Arguments
+    ///
+    /// * `op` - The running computation to apply
+    ///
+    /// # Returns
+    ///
+    /// - `VectorInt` for `Sum` and `Max` of a `VectorInt`, `VectorOptionInt` for its `Diff`
+    /// - `VectorFloat` for every operation on a `VectorFloat`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SoAKitError::InvalidArgument`] if integer arithmetic overflows or the
+    /// value is not a `VectorInt` or `VectorFloat`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{CumOp, Value};
+    ///
+    /// let v = Value::VectorInt(vec![3, 1, 4, 1, 5]);
+    /// assert_eq!(v.cumulative(CumOp::Sum).unwrap(), Value::VectorInt(vec![3, 4, 8, 9, 14]));
+    /// assert_eq!(v.cumulative(CumOp::Max).unwrap(), Value::VectorInt(vec![3, 3, 4, 4, 5]));
+    ///
+    /// let diff = Value::VectorFloat(vec![1.0, 4.0, 2.0]).cumulative(CumOp::Diff).unwrap();
+    /// assert_eq!(diff.get_element(2).unwrap(), Value::ScalarFloat(-2.0));
+    /// assert!(matches!(diff.get_element(0).unwrap(), Value::ScalarFloat(x) if x.is_nan()));
+    /// ```
+    pub fn cumulative(&self, op: CumOp) -> Result<Value> {
+        match self {
+            Value::VectorInt(v) => cumulative_ints(v, op),
+            Value::VectorFloat(v) => Ok(Value::VectorFloat(cumulative_floats(v, op))),
+            _ => Err(SoAKitError::InvalidArgument(format!(
+                "Cannot compute cumulative {:?} of {}",
+                op,
+                variant_name(self)
+            ))),
+        }
+    }
+
+    /// Aggregate each sliding window of a numeric vector.
+    ///
+    /// This is [`Value::rolling_with`] with the default [`RollEdge::Nan`] policy,
+    /// so the first `window - 1` elements of the result are NaN.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - Number of elements in each window
+    /// * `op` - The aggregation applied to each window
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Value::rolling_with`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{RollOp, Value};
+    ///
+    /// let v = Value::VectorInt(vec![1, 2, 3, 4]);
+    /// let sums = v.rolling(2, RollOp::Sum).unwrap();
+    /// assert_eq!(sums.get_element(3).unwrap(), Value::ScalarFloat(7.0));
+    /// ```
+    pub fn rolling(&self, window: usize, op: RollOp) -> Result<Value> {
+        self.rolling_with(window, op, RollEdge::default())
+    }
+
+    /// Aggregate each sliding window of a numeric vector, with an edge policy.
+    ///
+    /// Element `i` of the result aggregates the input elements `i + 1 - window`
+    /// through `i`. Before the first full window, `edge` decides whether the
+    /// result is NaN or the aggregate of the elements seen so far. NaN elements
+    /// are ignored; a window with no other elements yields NaN. Booleans count as
+    /// 0 and 1.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - Number of elements in each window
+    /// * `op` - The aggregation applied to each window
+    /// * `edge` - What to yield before the first full window
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Value::VectorFloat)` with one element per input element.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SoAKitError::InvalidArgument`] if `window` is zero or the value is
+    /// not a `VectorInt`, `VectorFloat` or `VectorBool`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{RollEdge, RollOp, Value};
+    ///
+    /// let v = Value::VectorFloat(vec![4.0, 2.0, 6.0]);
+    /// assert_eq!(
+    ///     v.rolling_with(2, RollOp::Min, RollEdge::Partial).unwrap(),
+    ///     Value::VectorFloat(vec![4.0, 2.0, 2.0])
+    /// );
+    /// ```
+    pub fn rolling_with(&self, window: usize, op: RollOp, edge: RollEdge) -> Result<Value> {
+        let inputs = self.rolling_inputs()?;
+        let mut state = RollingWindow::new(window, op, edge, inputs.len())?;
+        Ok(Value::VectorFloat(
+            inputs.into_iter().map(|x| state.push(x)).collect(),
+        ))
+    }
+
+    /// The elements of a numeric or boolean vector as floats, for rolling windows.
+    pub(crate) fn rolling_inputs(&self) -> Result<Vec<f64>> {
+        match self {
+            Value::VectorInt(v) => Ok(v.iter().map(|&x| int_to_float(x)).collect()),
+            Value::VectorFloat(v) => Ok(v.clone()),
+            Value::VectorBool(v) => Ok(v.iter().map(|&b| f64::from(u8::from(b))).collect()),
+            _ => Err(SoAKitError::InvalidArgument(format!(
+                "Cannot compute rolling window of {}",
+                variant_name(self)
+            ))),
+        }
+    }
+
+    /// The non-NaN elements of a numeric or boolean vector or a tensor, as floats.
+    fn non_nan_floats(&self, name: &str) -> Result<Vec<f64>> {
+        if let Some(values) = self.present_values() {
+            return values.non_nan_floats(name);
+        }
+        match self {
+            Value::VectorInt(v) => Ok(v.iter().map(|&x| int_to_float(x)).collect()),
+            Value::VectorFloat(v) | Value::Tensor { data: v, .. } => {
+                Ok(v.iter().copied().filter(|x| !x.is_nan()).collect())
+            }
+            Value::VectorBool(v) => Ok(v.iter().map(|&b| f64::from(u8::from(b))).collect()),
+            _ => Err(unsupported_aggregate(name, self)),
+        }
+    }
+
+    /// Shared implementation of [`Value::min`] and [`Value::max`]: keep the element
+    /// that compares as `keep` against the best so far.
+    fn extreme(&self, name: &str, keep: Ordering) -> Result<Value> {
+        if let Some(values) = self.present_values() {
+            return values.extreme(name, keep);
+        }
+        let candidates = match self {
+            Value::VectorFloat(_) | Value::Tensor { .. } => self
+                .non_nan_floats(name)?
+                .into_iter()
+                .map(Value::ScalarFloat)
+                .collect(),
+            _ if self.is_vector() => self.to_scalars(),
+            _ => return Err(unsupported_aggregate(name, self)),
+        };
+        candidates
+            .into_iter()
+            .reduce(|best, x| if x.total_cmp(&best) == keep { x } else { best })
+            .ok_or_else(|| empty_aggregate(name))
+    }
+
+    /// Compare two values for equality using the crate's float semantics.
+    ///
+    /// Unlike `==`, NaN equals NaN and `-0.0` equals `0.0`, recursively through
+    /// vectors, matrices and tensors. This is the equality used by
+    /// [`HashableValue`], [`Bulk::partition_by`](crate::Bulk::partition_by) and
+    /// the other grouping operations.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Value;
+    ///
+    /// let a = Value::VectorFloat(vec![f64::NAN, -0.0]);
+    /// let b = Value::VectorFloat(vec![f64::NAN, 0.0]);
+    /// assert!(a != b);
+    /// assert!(a.equals_nan_aware(&b));
+    /// ```
+    pub fn equals_nan_aware(&self, other: &Value) -> bool {
+        self.total_cmp(other) == Ordering::Equal
+    }
+
+    /// Compare two values using a total order.
+    ///
+    /// Unlike `PartialEq`/`PartialOrd` on floats, this ordering is total, which makes
+    /// it suitable for sorting and for use as a map key via [`HashableValue`]:
+    ///
+    /// - Values of different variants are ordered by variant, in declaration order
+    ///   (`ScalarInt` < `ScalarFloat` < ... < `Matrix`)
+    /// - Floats are compared with `NaN` normalized (all NaNs are equal and sort after
+    ///   every other float) and `-0.0` treated as equal to `0.0`
+    /// - `false` sorts before `true`, strings compare lexicographically
+    /// - Vectors and matrices compare lexicographically element by element, with a
+    ///   shorter prefix sorting first
+    /// - Tensors compare by shape first, then by data
+    /// - Option vectors compare by length, then by the indices of their present
+    ///   elements, then by the present values
+    /// - JSON cells compare by their compact JSON text
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::Value;
+    /// use std::cmp::Ordering;
+    ///
+    /// let nan = Value::ScalarFloat(f64::NAN);
+    /// assert_eq!(nan.total_cmp(&Value::ScalarFloat(f64::NAN)), Ordering::Equal);
+    /// assert_eq!(Value::ScalarFloat(1.0).total_cmp(&nan), Ordering::Less);
+    /// assert_eq!(
+    ///     Value::ScalarFloat(-0.0).total_cmp(&Value::ScalarFloat(0.0)),
+    ///     Ordering::Equal
+    /// );
+    /// ```
+    pub fn total_cmp(&self, other: &Value) -> Ordering {
+        match (self, other) {
+            (Value::ScalarInt(a), Value::ScalarInt(b)) => a.cmp(b),
+            (Value::ScalarFloat(a), Value::ScalarFloat(b)) => float_total_cmp(*a, *b),
+            (Value::ScalarBool(a), Value::ScalarBool(b)) => a.cmp(b),
+            (Value::ScalarString(a), Value::ScalarString(b)) => a.cmp(b),
+            (Value::VectorInt(a), Value::VectorInt(b)) => a.cmp(b),
+            (Value::VectorFloat(a), Value::VectorFloat(b)) => {
+                lexicographic_cmp(a, b, |x, y| float_total_cmp(*x, *y))
+            }
+            (Value::VectorBool(a), Value::VectorBool(b)) => a.cmp(b),
+            (Value::VectorString(a), Value::VectorString(b)) => a.cmp(b),
+            (Value::Matrix(a), Value::Matrix(b)) => lexicographic_cmp(a, b, Value::total_cmp),
+            (Value::ScalarJson(a), Value::ScalarJson(b)) => json_cmp(a, b),
+            (Value::VectorJson(a), Value::VectorJson(b)) => lexicographic_cmp(a, b, json_cmp),
+            (Value::Tensor { data: a, shape: sa }, Value::Tensor { data: b, shape: sb }) => sa
+                .cmp(sb)
+                .then_with(|| lexicographic_cmp(a, b, |x, y| float_total_cmp(*x, *y))),
+            (a, b) if a.is_sparse() && variant_order(a) == variant_order(b) => {
+                match (a.option_parts(), b.option_parts()) {
+                    (Some((la, ia, va)), Some((lb, ib, vb))) => la
+                        .cmp(&lb)
+                        .then_with(|| ia.cmp(&ib))
+                        .then_with(|| va.total_cmp(&vb)),
+                    _ => Ordering::Equal,
+                }
+            }
+            _ => variant_order(self).cmp(&variant_order(other)),
+        }
+    }
+
+    /// Get the element kind of the value, ignoring its rank.
+    ///
+    /// # Returns
+    ///
+    /// The [`ValueKind`] of a scalar, vector or tensor value, or `None` for matrices
+    /// (whose rows may have different kinds), JSON cells and [`Value::Null`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Value, ValueKind};
+    ///
+    /// assert_eq!(Value::ScalarInt(1).kind(), Some(ValueKind::Int));
+    /// assert_eq!(Value::VectorString(vec![]).kind(), Some(ValueKind::String));
+    /// assert_eq!(Value::Matrix(vec![]).kind(), None);
+    /// ```
+    pub const fn kind(&self) -> Option<ValueKind> {
+        match self {
+            Value::ScalarInt(_) | Value::VectorInt(_) | Value::VectorOptionInt { .. } => {
+                Some(ValueKind::Int)
+            }
+            Value::ScalarFloat(_)
+            | Value::VectorFloat(_)
+            | Value::VectorOptionFloat { .. }
+            | Value::Tensor { .. } => Some(ValueKind::Float),
+            Value::ScalarBool(_) | Value::VectorBool(_) | Value::VectorOptionBool { .. } => {
+                Some(ValueKind::Bool)
+            }
+            Value::ScalarString(_) | Value::VectorString(_) | Value::VectorOptionString { .. } => {
+                Some(ValueKind::String)
+            }
+            Value::Matrix(_) | Value::ScalarJson(_) | Value::VectorJson(_) | Value::Null => None,
+        }
+    }
+
+    /// Cast the value to another element kind, failing on overflow.
+    ///
+    /// This is equivalent to [`Value::cast_with`] with [`CastOverflow::Error`].
+    ///
+    /// # Errors
+    ///
+    /// See [`Value::cast_with`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{Value, ValueKind};
+    ///
+    /// let ints = Value::VectorInt(vec![1, 2]);
+    /// assert_eq!(
+    ///     ints.cast(ValueKind::Float).unwrap(),
+    ///     Value::VectorFloat(vec![1.0, 2.0])
+    /// );
+    /// assert!(Value::ScalarFloat(f64::NAN).cast(ValueKind::Int).is_err());
+    /// ```
+    pub fn cast(&self, target: ValueKind) -> Result<Value> {
+        self.cast_with(target, CastOverflow::Error)
+    }
+
+    /// Cast the value to another element kind.
+    ///
+    /// The rank is preserved: scalars stay scalars, vectors are cast element-wise,
+    /// and matrices are cast row by row. Option vectors cast their present elements
+    /// and keep missing elements missing; `Null` casts to `Null`. Supported
+    /// conversions are:
+    ///
+    /// - Any kind to itself (a clone); tensors only support this cast
+    /// - `Int` to `Float` (may lose precision above 2^53)
+    /// - `Float` to `Int`, truncating toward zero
+    /// - `Bool` to `Int` or `Float` (`false` is 0, `true` is 1)
+    /// - `Int`, `Float` or `Bool` to `String`
+    ///
+    /// When a float is NaN or outside the `i64` range, `overflow` decides what
+    /// happens: [`CastOverflow::Error`] fails, [`CastOverflow::Saturate`] clamps
+    /// to `i64::MIN`/`i64::MAX` (NaN becomes 0).
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The element kind to cast to
+    /// * `overflow` - How to handle floats that do not fit in an `i64`
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Value)` of the same rank with elements of kind `target`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SoAKitError::InvalidArgument`] if the conversion is not supported,
+    /// or if a float does not fit in an `i64` and `overflow` is [`CastOverflow::Error`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use soakit::{CastOverflow, Value, ValueKind};
+    ///
+    /// let floats = Value::VectorFloat(vec![1.9, -2.5, 1e300]);
+    /// assert_eq!(
+    ///     floats.cast_with(ValueKind::Int, CastOverflow::Saturate).unwrap(),
+    ///     Value::VectorInt(vec![1, -2, i64::MAX])
+    /// );
+    /// assert_eq!(
+    ///     Value::ScalarBool(true).cast(ValueKind::String).unwrap(),
+    ///     Value::ScalarString("true".to_string())
+    /// );
+    /// ```
+    pub fn cast_with(&self, target: ValueKind, overflow: CastOverflow) -> Result<Value> {
+        let result = if self.is_scalar() {
+            // Cast as a one-element vector, then unwrap the element again
+            Value::from_scalars(vec![self.clone()])?
+                .cast_elements(target, overflow)
+                .map(|r| r.and_then(|v| v.get_element(0)))
+        } else {
+            self.cast_elements(target, overflow)
+        };
+        result.unwrap_or_else(|| {
+            Err(SoAKitError::InvalidArgument(format!(
+                "Cannot cast {} to {:?}",
+                variant_name(self),
+                target
+            )))
+        })
+    }
+
+    /// Cast a vector or matrix element-wise, returning `None` if the
+    /// conversion is not supported.
+    fn cast_elements(&self, target: ValueKind, overflow: CastOverflow) -> Option<Result<Value>> {
+        let result = match (self, target) {
+            (Value::Matrix(rows), _) => {
+                let rows: Result<Vec<Value>> = rows
+                    .iter()
+                    .map(|row| row.cast_with(target, overflow))
+                    .collect();
+                return Some(rows.map(Value::Matrix));
+            }
+            (Value::VectorInt(v), ValueKind::Int) => Value::VectorInt(v.clone()),
+            (Value::VectorInt(v), ValueKind::Float) => {
+                Value::VectorFloat(v.iter().map(|&x| int_to_float(x)).collect())
+            }
+            (Value::VectorInt(v), ValueKind::String) => {
+                Value::VectorString(v.iter().map(ToString::to_string).collect())
+            }
+            (Value::VectorFloat(v), ValueKind::Int) => {
+                let ints: Result<Vec<i64>> = v.iter().map(|&x| float_to_int(x, overflow)).collect();
+                return Some(ints.map(Value::VectorInt));
+            }
+            (Value::VectorFloat(v), ValueKind::Float) => Value::VectorFloat(v.clone()),
+            (Value::VectorFloat(v), ValueKind::String) => {
+                Value::VectorString(v.iter().map(ToString::to_string).collect())
+            }
+            (Value::VectorBool(v), ValueKind::Int) => {
+                Value::VectorInt(v.iter().map(|&b| i64::from(b)).collect())
+            }
+            (Value::VectorBool(v), ValueKind::Float) => {
+                Value::VectorFloat(v.iter().map(|&b| f64::from(u8::from(b))).collect())
+            }
+            (Value::VectorBool(v), ValueKind::Bool) => Value::VectorBool(v.clone()),
+            (Value::VectorBool(v), ValueKind::String) => {
+                Value::VectorString(v.iter().map(ToString::to_string).collect())
+            }
+            (Value::VectorString(v), ValueKind::String) => Value::VectorString(v.clone()),
+            (Value::Tensor { .. }, ValueKind::Float) => self.clone(),
+            _ if self.is_sparse() => {
+                let (len, indices, values) = self.option_parts()?;
+                return Some(
+                    values
+                        .cast_elements(target, overflow)?
+                        .and_then(|values| Value::from_option_parts(len, indices, values)),
+                );
+            }
+            _ => return None,
+        };
+        Some(Ok(result))
+    }
+
+    /// Apply an arithmetic operator to two values.
+    ///
+    /// This is the shared implementation behind the `Add`, `Sub`, `Mul` and `Div`
+    /// operator impls. The rules are:
+    ///
+    /// - Integer operations use checked arithmetic and fail on overflow or division by zero
+    /// - Mixing integers and floats promotes the result to float
+    /// - Vectors are combined element-wise and must have the same length
+    /// - A scalar combined with a vector (or matrix) is broadcast across every element
+    /// - Matrices are combined row by row
+    /// - Tensors are combined element-wise and must have the same shape; numeric
+    ///   scalars are broadcast over them
+    /// - `Add` on strings concatenates them; every other operator on strings or
+    ///   booleans is rejected
+    /// - `Null` and missing elements of option vectors propagate: any operation
+    ///   with a missing operand yields a missing result
+    ///
+    /// # Errors
+    ///
+    /// - [`SoAKitError::LengthMismatch`] if two vectors or matrices differ in length
+    /// - [`SoAKitError::InvalidArgument`] on overflow, division by zero, or unsupported types
+    fn arith(&self, rhs: &Value, op: ArithOp) -> Result<Value> {
+        if self.is_null() || rhs.is_null() || self.is_sparse() || rhs.is_sparse() {
+            return self.arith_nullable(rhs, op);
+        }
+        match (self, rhs) {
+            // Matrices: row by row, or broadcast a scalar over every row
+            (Value::Matrix(a), Value::Matrix(b)) => {
+                check_same_len(a.len(), b.len())?;
+                a.iter()
+                    .zip(b.iter())
+                    .map(|(x, y)| x.arith(y, op))
+                    .collect::<Result<Vec<Value>>>()
+                    .map(Value::Matrix)
+            }
+            (Value::Matrix(a), scalar) if scalar.is_scalar() => a
+                .iter()
+                .map(|row| row.arith(scalar, op))
+                .collect::<Result<Vec<Value>>>()
+                .map(Value::Matrix),
+            (scalar, Value::Matrix(b)) if scalar.is_scalar() => b
+                .iter()
+                .map(|row| scalar.arith(row, op))
+                .collect::<Result<Vec<Value>>>()
+                .map(Value::Matrix),
+            (Value::Tensor { .. }, _) | (_, Value::Tensor { .. }) => self.arith_tensor(rhs, op),
+            (lhs, rhs) => lhs
+                .arith_elementwise(rhs, op)
+                .or_else(|| lhs.arith_broadcast(rhs, op))
+                .unwrap_or_else(|| {
+                    Err(SoAKitError::InvalidArgument(format!(
+                        "Cannot {} {} and {}",
+                        op.verb(),
+                        variant_name(lhs),
+                        variant_name(rhs)
+                    )))
+                }),
+        }
+    }
+
+    /// Arithmetic where either operand is `Null` or an option vector.
+    fn arith_nullable(&self, rhs: &Value, op: ArithOp) -> Result<Value> {
+        let combine = |a: Value, b: Value| {
+            if a.is_null() || b.is_null() {
+                Ok(Value::Null)
+            } else {
+                a.arith(&b, op)
+            }
+        };
+        match (self.is_scalar(), rhs.is_scalar()) {
+            (true, true) => combine(self.clone(), rhs.clone()),
+            (false, true) => self.map_elements(|a| combine(a, rhs.clone())),
+            (true, false) => rhs.map_elements(|b| combine(self.clone(), b)),
+            (false, false) => self.zip_with(rhs, combine),
+        }
+    }
+
+    /// Tensor-tensor and tensor-scalar arithmetic.
+    fn arith_tensor(&self, rhs: &Value, op: ArithOp) -> Result<Value> {
+        let as_float = |v: &Value| match v {
+            Value::ScalarInt(i) => Some(int_to_float(*i)),
+            Value::ScalarFloat(f) => Some(*f),
+            _ => None,
+        };
+        let result = match (self, rhs) {
+            (
+                Value::Tensor { data: a, shape },
+                Value::Tensor {
+                    data: b,
+                    shape: rhs_shape,
+                },
+            ) => {
+                check_same_shape(shape, rhs_shape)?;
+                Some(Value::Tensor {
+                    data: a
+                        .iter()
+                        .zip(b.iter())
+                        .map(|(x, y)| op.float(*x, *y))
+                        .collect(),
+                    shape: shape.clone(),
+                })
+            }
+            (Value::Tensor { data, shape }, scalar) => as_float(scalar).map(|b| Value::Tensor {
+                data: data.iter().map(|x| op.float(*x, b)).collect(),
+                shape: shape.clone(),
+            }),
+            (scalar, Value::Tensor { data, shape }) => as_float(scalar).map(|a| Value::Tensor {
+                data: data.iter().map(|y| op.float(a, *y)).collect(),
+                shape: shape.clone(),
+            }),
+            _ => None,
+        };
+        result.ok_or_else(|| {
+            SoAKitError::InvalidArgument(format!(
+                "Cannot {} {} and {}",
+                op.verb(),
+                variant_name(self),
+                variant_name(rhs)
+            ))
+        })
+    }
+
+    /// Scalar-scalar and vector-vector arithmetic.
+    ///
+    /// Returns `None` if the operand combination is not handled here.
+    fn arith_elementwise(&self, rhs: &Value, op: ArithOp) -> Option<Result<Value>> {
+        let result =
+            match (self, rhs) {
+                (Value::ScalarInt(a), Value::ScalarInt(b)) => op.int(*a, *b).map(Value::ScalarInt),
+                (Value::ScalarInt(a), Value::ScalarFloat(b)) => {
+                    Ok(Value::ScalarFloat(op.float(int_to_float(*a), *b)))
+                }
+                (Value::ScalarFloat(a), Value::ScalarInt(b)) => {
+                    Ok(Value::ScalarFloat(op.float(*a, int_to_float(*b))))
+                }
+                (Value::ScalarFloat(a), Value::ScalarFloat(b)) => {
+                    Ok(Value::ScalarFloat(op.float(*a, *b)))
+                }
+                (Value::ScalarString(a), Value::ScalarString(b)) if op == ArithOp::Add => {
+                    Ok(Value::ScalarString(format!("{}{}", a, b)))
+                }
+                (Value::VectorInt(a), Value::VectorInt(b)) => check_same_len(a.len(), b.len())
+                    .and_then(|()| {
+                        a.iter()
+                            .zip(b.iter())
+                            .map(|(x, y)| op.int(*x, *y))
+                            .collect::<Result<Vec<i64>>>()
+                    })
+                    .map(Value::VectorInt),
+                (Value::VectorInt(a), Value::VectorFloat(b)) => check_same_len(a.len(), b.len())
+                    .map(|()| {
+                        Value::VectorFloat(
+                            a.iter()
+                                .zip(b.iter())
+                                .map(|(x, y)| op.float(int_to_float(*x), *y))
+                                .collect(),
+                        )
+                    }),
+                (Value::VectorFloat(a), Value::VectorInt(b)) => check_same_len(a.len(), b.len())
+                    .map(|()| {
+                        Value::VectorFloat(
+                            a.iter()
+                                .zip(b.iter())
+                                .map(|(x, y)| op.float(*x, int_to_float(*y)))
+                                .collect(),
+                        )
+                    }),
+                (Value::VectorFloat(a), Value::VectorFloat(b)) => check_same_len(a.len(), b.len())
+                    .map(|()| {
+                        Value::VectorFloat(
+                            a.iter()
+                                .zip(b.iter())
+                                .map(|(x, y)| op.float(*x, *y))
+                                .collect(),
+                        )
+                    }),
+                (Value::VectorString(a), Value::VectorString(b)) if op == ArithOp::Add => {
+                    check_same_len(a.len(), b.len()).map(|()| {
+                        Value::VectorString(
+                            a.iter()
+                                .zip(b.iter())
+                                .map(|(x, y)| format!("{}{}", x, y))
+                                .collect(),
+                        )
+                    })
+                }
+                _ => return None,
+            };
+        Some(result)
+    }
+
+    /// Vector-scalar and scalar-vector arithmetic, broadcasting the scalar.
+    ///
+    /// Returns `None` if the operand combination is not handled here.
+    fn arith_broadcast(&self, rhs: &Value, op: ArithOp) -> Option<Result<Value>> {
+        let result = match (self, rhs) {
+            (Value::VectorInt(a), Value::ScalarInt(b)) => a
+                .iter()
+                .map(|x| op.int(*x, *b))
+                .collect::<Result<Vec<i64>>>()
+                .map(Value::VectorInt),
+            (Value::ScalarInt(a), Value::VectorInt(b)) => b
+                .iter()
+                .map(|y| op.int(*a, *y))
+                .collect::<Result<Vec<i64>>>()
+                .map(Value::VectorInt),
+            (Value::VectorInt(a), Value::ScalarFloat(b)) => Ok(Value::VectorFloat(
+                a.iter().map(|x| op.float(int_to_float(*x), *b)).collect(),
+            )),
+            (Value::ScalarFloat(a), Value::VectorInt(b)) => Ok(Value::VectorFloat(
+                b.iter().map(|y| op.float(*a, int_to_float(*y))).collect(),
+            )),
+            (Value::VectorFloat(a), Value::ScalarInt(b)) => {
+                let b = int_to_float(*b);
+                Ok(Value::VectorFloat(
+                    a.iter().map(|x| op.float(*x, b)).collect(),
+                ))
+            }
+            (Value::ScalarInt(a), Value::VectorFloat(b)) => {
+                let a = int_to_float(*a);
+                Ok(Value::VectorFloat(
+                    b.iter().map(|y| op.float(a, *y)).collect(),
+                ))
+            }
+            (Value::VectorFloat(a), Value::ScalarFloat(b)) => Ok(Value::VectorFloat(
+                a.iter().map(|x| op.float(*x, *b)).collect(),
+            )),
+            (Value::ScalarFloat(a), Value::VectorFloat(b)) => Ok(Value::VectorFloat(
+                b.iter().map(|y| op.float(*a, *y)).collect(),
+            )),
+            (Value::VectorString(a), Value::ScalarString(b)) if op == ArithOp::Add => Ok(
+                Value::VectorString(a.iter().map(|x| format!("{}{}", x, b)).collect()),
+            ),
+            (Value::ScalarString(a), Value::VectorString(b)) if op == ArithOp::Add => Ok(
+                Value::VectorString(b.iter().map(|y| format!("{}{}", a, y)).collect()),
+            ),
+            _ => return None,
+        };
+        Some(result)
+    }
+}
+
+/// Copy the elements of `items` selected by `range`, with bounds checking.
+fn slice_range<T: Clone>(items: &[T], range: &impl RangeBounds<usize>) -> Result<Vec<T>> {
+    let (start, end) = resolve_range(items.len(), range)?;
+    items.get(start..end).map(<[T]>::to_vec).ok_or_else(|| {
+        SoAKitError::InvalidArgument(format!("Invalid slice range {}..{}", start, end))
+    })
+}
+
+/// Resolve `range` against a sequence of `len` elements into `(start, end)`.
+fn resolve_range(len: usize, range: &impl RangeBounds<usize>) -> Result<(usize, usize)> {
+    let start = match range.start_bound() {
+        Bound::Included(&s) => s,
+        Bound::Excluded(&s) => s.saturating_add(1),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&e) => e.saturating_add(1),
+        Bound::Excluded(&e) => e,
+        Bound::Unbounded => len,
+    };
+    if end > len {
+        return Err(SoAKitError::IndexOutOfBounds {
+            index: end,
+            max: len,
+        });
+    }
+    if start > end {
+        return Err(SoAKitError::InvalidArgument(format!(
+            "Invalid slice range {}..{}",
+            start, end
+        )));
+    }
+    Ok((start, end))
+}
+
+/// Look up element `idx` of an option vector, returning `Null` if it is missing.
+fn option_element<T: Clone>(
+    len: usize,
+    entries: &[(usize, T)],
+    idx: usize,
+    wrap: impl FnOnce(T) -> Value,
+) -> Result<Value> {
+    if idx >= len {
+        return Err(SoAKitError::IndexOutOfBounds {
+            index: idx,
+            max: len,
+        });
+    }
+    Ok(entries
+        .binary_search_by_key(&idx, |(i, _)| *i)
+        .ok()
+        .and_then(|pos| entries.get(pos))
+        .map_or(Value::Null, |(_, v)| wrap(v.clone())))
+}
+
+/// Build an option vector from scalars, treating `Null` as a missing element.
+///
+/// A list of nulls only becomes a `VectorOptionInt` with no present elements.
+fn option_from_scalars(scalars: Vec<Value>) -> Result<Value> {
+    let len = scalars.len();
+    let (indices, present): (Vec<usize>, Vec<Value>) = scalars
+        .into_iter()
+        .enumerate()
+        .filter(|(_, v)| !v.is_null())
+        .unzip();
+    if present.iter().any(|v| !v.is_scalar()) {
+        return Err(SoAKitError::InvalidArgument(
+            "Null can only be mixed with scalar values".to_string(),
+        ));
+    }
+    Value::from_option_parts(len, indices, Value::from_scalars(present)?)
+}
+
+/// Number of elements described by a tensor shape.
+fn shape_product(shape: &[usize]) -> Result<usize> {
+    shape.iter().try_fold(1usize, |acc, &dim| {
+        acc.checked_mul(dim).ok_or_else(|| {
+            SoAKitError::InvalidArgument(format!("Tensor shape {:?} is too large", shape))
+        })
+    })
+}
+
+/// Fail with `InvalidArgument` unless two tensor shapes are equal.
+fn check_same_shape(a: &[usize], b: &[usize]) -> Result<()> {
+    if a == b {
+        Ok(())
+    } else {
+        Err(SoAKitError::InvalidArgument(format!(
+            "Tensor shape mismatch: {:?} vs {:?}",
+            a, b
+        )))
+    }
+}
+
+/// Extract the sub-tensor at `idx` along the first axis.
+fn tensor_index(data: &[f64], shape: &[usize], idx: usize) -> Result<Value> {
+    let (&n, rest) = shape
+        .split_first()
+        .ok_or_else(|| SoAKitError::InvalidArgument("Cannot index a rank-0 tensor".to_string()))?;
+    if idx >= n {
+        return Err(SoAKitError::IndexOutOfBounds { index: idx, max: n });
+    }
+    let stride = shape_product(rest)?;
+    let start = idx.saturating_mul(stride);
+    let row = data
+        .get(start..start.saturating_add(stride))
+        .ok_or(SoAKitError::IndexOutOfBounds { index: idx, max: n })?;
+    Ok(Value::Tensor {
+        data: row.to_vec(),
+        shape: rest.to_vec(),
+    })
+}
+
+/// Extract the sub-tensor at `idx` along the second axis.
+fn tensor_column(data: &[f64], shape: &[usize], idx: usize) -> Result<Value> {
+    let (&rows, &cols, rest) = match shape {
+        [rows, cols, rest @ ..] => (rows, cols, rest),
+        _ => {
+            return Err(SoAKitError::InvalidArgument(
+                "col requires a tensor of rank 2 or more".to_string(),
+            ));
+        }
+    };
+    if idx >= cols {
+        return Err(SoAKitError::IndexOutOfBounds {
+            index: idx,
+            max: cols,
+        });
+    }
+    let inner = shape_product(rest)?;
+    let row_stride = cols.saturating_mul(inner);
+    let mut out = Vec::with_capacity(rows.saturating_mul(inner));
+    for r in 0..rows {
+        let start = r
+            .saturating_mul(row_stride)
+            .saturating_add(idx.saturating_mul(inner));
+        let block =
+            data.get(start..start.saturating_add(inner))
+                .ok_or(SoAKitError::IndexOutOfBounds {
+                    index: r,
+                    max: rows,
+                })?;
+        out.extend_from_slice(block);
+    }
+    let mut out_shape = vec![rows];
+    out_shape.extend_from_slice(rest);
+    Ok(Value::Tensor {
+        data: out,
+        shape: out_shape,
+    })
+}
+
+/// Dimensions `(rows, cols)` of a rectangular matrix.
+///
+/// Every row must be a vector of the same variant and length.
+fn matrix_dims(rows: &[Value]) -> Result<(usize, usize)> {
+    let Some(first) = rows.first() else {
+        return Ok((0, 0));
+    };
+    let rectangular = first.is_vector()
+        && rows
+            .iter()
+            .all(|row| variant_order(row) == variant_order(first) && row.len() == first.len());
+    if rectangular {
+        Ok((rows.len(), first.len()))
+    } else {
+        Err(SoAKitError::InvalidArgument(
+            "Matrix rows must be vectors of the same type and length".to_string(),
+        ))
+    }
+}
+
+/// Flatten a numeric matrix or rank-2 tensor into `(rows, cols, row-major floats)`.
+fn float_grid(value: &Value) -> Result<(usize, usize, Vec<f64>)> {
+    match value {
+        Value::Matrix(rows) => {
+            let (n, k) = matrix_dims(rows)?;
+            let mut data = Vec::with_capacity(n.saturating_mul(k));
+            for row in rows {
+                match row {
+                    Value::VectorInt(v) => data.extend(v.iter().map(|&x| int_to_float(x))),
+                    Value::VectorFloat(v) => data.extend_from_slice(v),
+                    other => {
+                        return Err(SoAKitError::InvalidArgument(format!(
+                            "Matrix rows must be numeric, got {}",
+                            variant_name(other)
+                        )));
+                    }
+                }
+            }
+            Ok((n, k, data))
+        }
+        Value::Tensor { data, shape } => match shape.as_slice() {
+            &[n, k] => Ok((n, k, data.clone())),
+            _ => Err(SoAKitError::InvalidArgument(format!(
+                "Expected a rank-2 tensor, got shape {:?}",
+                shape
+            ))),
+        },
+        _ => Err(SoAKitError::InvalidArgument(format!(
+            "Expected a matrix or tensor, got {}",
+            variant_name(value)
+        ))),
+    }
+}
+
+/// Flatten a matrix of `VectorInt` rows into row-major integers, if it is one.
+fn int_grid(value: &Value) -> Option<Vec<i64>> {
+    match value {
+        Value::Matrix(rows) => rows
+            .iter()
+            .map(|row| row.as_int_slice())
+            .collect::<Option<Vec<&[i64]>>>()
+            .map(|rows| rows.concat()),
+        _ => None,
+    }
+}
+
+/// Stack tensors of shape `row_shape` into one tensor with a new leading axis.
+pub(crate) fn stack_tensors(rows: Vec<Value>, row_shape: &[usize]) -> Result<Value> {
+    let mut shape = Vec::with_capacity(row_shape.len().saturating_add(1));
+    shape.push(rows.len());
+    shape.extend_from_slice(row_shape);
+    let mut data = Vec::with_capacity(shape_product(&shape)?);
+    for row in rows {
+        match row {
+            Value::Tensor {
+                data: row_data,
+                shape: s,
+            } if s == row_shape => data.extend(row_data),
+            other => {
+                return Err(SoAKitError::InvalidArgument(format!(
+                    "Cannot stack {} with tensors of shape {:?}",
+                    if other.is_tensor() {
+                        format!("tensor of shape {:?}", other.shape())
+                    } else {
+                        variant_name(&other).to_string()
+                    },
+                    row_shape
+                )));
+            }
+        }
+    }
+    Ok(Value::Tensor { data, shape })
+}
+
+/// Element kind of a [`Value`], independent of its rank.
+///
+/// Used as the target of [`Value::cast`]: casting a vector to `ValueKind::Float`
+/// yields a `VectorFloat`, casting a scalar yields a `ScalarFloat`, and so on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ValueKind {
+    /// 64-bit signed integers
+    Int,
+    /// 64-bit floats
+    Float,
+    /// Booleans
+    Bool,
+    /// Strings
+    String,
+}
+
+/// An aggregation that reduces a vector to a scalar, for [`Value::aggregate`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Agg {
+    /// [`Value::sum`]
+    Sum,
+    /// [`Value::mean`]
+    Mean,
+    /// [`Value::min`]
+    Min,
+    /// [`Value::max`]
+    Max,
+    /// [`Value::std`]
+    Std,
+    /// [`Value::count_nonnull`]
+    Count,
+    /// [`Value::count_null`]
+    NullCount,
+}
+
+impl Agg {
+    /// Check whether the aggregation leaves null elements out, rather than counting them.
+    pub(crate) const fn skips_nulls(self) -> bool {
+        !matches!(self, Agg::Count | Agg::NullCount)
+    }
+}
+
+/// What an aggregation does with null elements: NaN floats and missing elements
+/// of option vectors. See [`Value::aggregate_with`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NullPolicy {
+    /// Aggregate the non-null elements only
+    #[default]
+    Skip,
+    /// Give [`Value::Null`] if any element is null
+    Propagate,
+}
+
+/// A running computation over a vector, for [`Value::cumulative`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CumOp {
+    /// Running sum
+    Sum,
+    /// Running maximum
+    Max,
+    /// Difference from the previous element
+    Diff,
+}
+
+/// An aggregation over a sliding window, for [`Value::rolling`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RollOp {
+    /// Sum of the window
+    Sum,
+    /// Mean of the window
+    Mean,
+    /// Smallest element of the window
+    Min,
+    /// Largest element of the window
+    Max,
+}
+
+/// What [`Value::rolling_with`] yields before the first full window.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RollEdge {
+    /// NaN until a full window of elements has been seen
+    #[default]
+    Nan,
+    /// Aggregate the shorter window of elements seen so far
+    Partial,
+}
+
+/// How [`Value::cast_with`] handles floats that do not fit in an `i64`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CastOverflow {
+    /// Fail with [`SoAKitError::InvalidArgument`]
+    #[default]
+    Error,
+    /// Clamp to `i64::MIN`/`i64::MAX`, mapping NaN to 0
+    Saturate,
+}
+
+/// Convert a float to an integer, truncating toward zero.
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+fn float_to_int(v: f64, overflow: CastOverflow) -> Result<i64> {
+    // i64::MIN is exactly representable; i64::MAX rounds up to 2^63
+    let in_range = v.is_finite() && v >= i64::MIN as f64 && v < -(i64::MIN as f64);
+    match (in_range, overflow) {
+        // `as` truncates in range and saturates (NaN to 0) outside it
+        (true, _) | (false, CastOverflow::Saturate) => Ok(v as i64),
+        (false, CastOverflow::Error) => Err(SoAKitError::InvalidArgument(format!(
+            "Float {} does not fit in an integer",
+            v
+        ))),
+    }
+}
+
+/// Arithmetic operator selector used by [`Value`]'s operator impls.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ArithOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl ArithOp {
+    /// Apply the operator to two integers, failing on overflow or division by zero.
+    fn int(self, a: i64, b: i64) -> Result<i64> {
+        let result = match self {
+            ArithOp::Add => a.checked_add(b),
+            ArithOp::Sub => a.checked_sub(b),
+            ArithOp::Mul => a.checked_mul(b),
+            ArithOp::Div => {
+                if b == 0 {
+                    return Err(SoAKitError::InvalidArgument(
+                        "Integer division by zero".to_string(),
+                    ));
+                }
+                a.checked_div(b)
+            }
+        };
+        result.ok_or_else(|| {
+            SoAKitError::InvalidArgument(format!(
+                "Integer overflow in {} of {} and {}",
+                self.verb(),
+                a,
+                b
+            ))
+        })
+    }
+
+    /// Apply the operator to two floats using IEEE 754 semantics.
+    fn float(self, a: f64, b: f64) -> f64 {
+        match self {
+            ArithOp::Add => a + b,
+            ArithOp::Sub => a - b,
+            ArithOp::Mul => a * b,
+            ArithOp::Div => a / b,
+        }
+    }
+
+    /// Human readable name of the operator for error messages.
+    const fn verb(self) -> &'static str {
+        match self {
+            ArithOp::Add => "add",
+            ArithOp::Sub => "subtract",
+            ArithOp::Mul => "multiply",
+            ArithOp::Div => "divide",
+        }
+    }
+}
+
+/// Promote an integer to a float for mixed int/float arithmetic.
+#[allow(clippy::cast_precision_loss)]
+pub(crate) const fn int_to_float(v: i64) -> f64 {
+    v as f64
+}
+
+/// Arithmetic mean of a slice, or `None` if it is empty.
+#[allow(clippy::cast_precision_loss)]
+fn mean_of(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.iter().sum::<f64>() / values.len() as f64)
+    }
+}
+
+/// Convert an element count to a `ScalarInt`.
+fn count_to_value(count: usize) -> Result<Value> {
+    i64::try_from(count).map(Value::ScalarInt).map_err(|e| {
+        SoAKitError::InvalidArgument(format!("Count does not fit in an integer: {}", e))
+    })
+}
+
+/// Error for an aggregation applied to an unsupported variant.
+fn unsupported_aggregate(name: &str, value: &Value) -> SoAKitError {
+    SoAKitError::InvalidArgument(format!(
+        "Cannot compute {} of {}",
+        name,
+        variant_name(value)
+    ))
+}
+
+/// Sliding-window state shared by [`Value::rolling_with`] and
+/// [`Bulk::rolling_with`](crate::Bulk::rolling_with).
+///
+/// Elements are pushed one at a time; the window carries over between pushes,
+/// so a column can be fed in several pieces. Each push costs amortised constant
+/// time: sums and means keep a running compensated sum, and minimums and
+/// maximums keep a monotonic queue of candidates.
+#[derive(Debug)]
+pub(crate) struct RollingWindow {
+    /// Number of elements in a full window
+    window: usize,
+    /// Aggregation applied to each window
+    op: RollOp,
+    /// Output for positions before the first full window
+    edge: RollEdge,
+    /// Number of elements pushed so far
+    pushed: usize,
+    /// For sums and means, the most recent elements, oldest first
+    values: VecDeque<f64>,
+    /// Running sum of the finite elements in `values`
+    sum: f64,
+    /// Low-order bits lost from `sum` to rounding
+    compensation: f64,
+    /// Number of elements in `values` that are not NaN
+    present: usize,
+    /// Numbers of positive and negative infinities in `values`
+    infinities: (usize, usize),
+    /// For minimums and maximums, `(position, value)` candidates for the extreme
+    /// of the window, oldest first; the front is the current extreme
+    extremes: VecDeque<(usize, f64)>,
+}
+
+impl RollingWindow {
+    /// Create an empty window, failing if `window` is zero.
+    ///
+    /// `len` is the number of elements that will be pushed; it only bounds the
+    /// memory reserved up front, so a window far larger than the input is fine.
+    pub(crate) fn new(window: usize, op: RollOp, edge: RollEdge, len: usize) -> Result<Self> {
+        if window == 0 {
+            return Err(SoAKitError::InvalidArgument(
+                "Rolling window must hold at least one element".to_string(),
+            ));
+        }
+        let capacity = window.min(len);
+        let (values, extremes) = match op {
+            RollOp::Sum | RollOp::Mean => (VecDeque::with_capacity(capacity), VecDeque::new()),
+            RollOp::Min | RollOp::Max => (VecDeque::new(), VecDeque::with_capacity(capacity)),
+        };
+        Ok(Self {
+            window,
+            op,
+            edge,
+            pushed: 0,
+            values,
+            sum: 0.0,
+            compensation: 0.0,
+            present: 0,
+            infinities: (0, 0),
+            extremes,
+        })
+    }
+
+    /// Add the next element and return the aggregate of the window ending at it.
+    pub(crate) fn push(&mut self, x: f64) -> f64 {
+        let position = self.pushed;
+        self.pushed = self.pushed.saturating_add(1);
+        match self.op {
+            RollOp::Sum | RollOp::Mean => {
+                if self.values.len() == self.window
+                    && let Some(old) = self.values.pop_front()
+                {
+                    self.count(old, false);
+                }
+                self.values.push_back(x);
+                self.count(x, true);
+            }
+            RollOp::Min | RollOp::Max => {
+                let window = self.window;
+                while self
+                    .extremes
+                    .front()
+                    .is_some_and(|&(p, _)| p.saturating_add(window) <= position)
+                {
+                    let _ = self.extremes.pop_front();
+                }
+                if !x.is_nan() {
+                    let min = self.op == RollOp::Min;
+                    while self
+                        .extremes
+                        .back()
+                        .is_some_and(|&(_, v)| if min { v >= x } else { v <= x })
+                    {
+                        let _ = self.extremes.pop_back();
+                    }
+                    self.extremes.push_back((position, x));
+                }
+            }
+        }
+        if self.pushed < self.window && self.edge == RollEdge::Nan {
+            return f64::NAN;
+        }
+        self.aggregate()
+    }
+
+    /// Add `x` to (or, if `added` is false, remove it from) the running sum.
+    fn count(&mut self, x: f64, added: bool) {
+        let step = |n: usize| {
+            if added {
+                n.saturating_add(1)
+            } else {
+                n.saturating_sub(1)
+            }
+        };
+        if x.is_nan() {
+            return;
+        }
+        self.present = step(self.present);
+        if x == f64::INFINITY {
+            self.infinities.0 = step(self.infinities.0);
+        } else if x == f64::NEG_INFINITY {
+            self.infinities.1 = step(self.infinities.1);
+        } else {
+            self.add_finite(if added { x } else { -x });
+        }
+    }
+
+    /// Add a finite term to `sum`, carrying the rounding error in `compensation`.
+    fn add_finite(&mut self, x: f64) {
+        let total = self.sum + x;
+        if self.sum.abs() >= x.abs() {
+            self.compensation += (self.sum - total) + x;
+        } else {
+            self.compensation += (x - total) + self.sum;
+        }
+        self.sum = total;
+    }
+
+    /// Sum of the finite elements in the window.
+    ///
+    /// Once the running sum has overflowed it can no longer be corrected by
+    /// subtraction, so it is then recomputed from the window.
+    fn finite_sum(&mut self) -> f64 {
+        let total = self.sum + self.compensation;
+        if self.sum.is_finite() && total.is_finite() {
+            return total;
+        }
+        self.sum = 0.0;
+        self.compensation = 0.0;
+        let finite: Vec<f64> = self
+            .values
+            .iter()
+            .copied()
+            .filter(|v| v.is_finite())
+            .collect();
+        for x in finite {
+            self.add_finite(x);
+        }
+        if self.sum.is_finite() {
+            self.sum + self.compensation
+        } else {
+            self.sum
+        }
+    }
+
+    /// Aggregate of the current window, NaN if it holds no non-NaN element.
+    #[allow(clippy::cast_precision_loss)]
+    fn aggregate(&mut self) -> f64 {
+        match self.op {
+            RollOp::Sum | RollOp::Mean => {
+                if self.present == 0 {
+                    return f64::NAN;
+                }
+                let total = match self.infinities {
+                    (0, 0) => self.finite_sum(),
+                    (_, 0) => f64::INFINITY,
+                    (0, _) => f64::NEG_INFINITY,
+                    _ => f64::NAN,
+                };
+                if self.op == RollOp::Sum {
+                    total
+                } else {
+                    total / self.present as f64
+                }
+            }
+            RollOp::Min | RollOp::Max => self.extremes.front().map_or(f64::NAN, |&(_, v)| v),
+        }
+    }
+}
+
+/// [`Value::cumulative`] of a `VectorInt`, failing on overflow.
+fn cumulative_ints(values: &[i64], op: CumOp) -> Result<Value> {
+    let overflow =
+        || SoAKitError::InvalidArgument(format!("Integer overflow in cumulative {:?}", op));
+    match op {
+        CumOp::Sum => {
+            let mut total = 0i64;
+            let mut out = Vec::with_capacity(values.len());
+            for &x in values {
+                total = total.checked_add(x).ok_or_else(overflow)?;
+                out.push(total);
+            }
+            Ok(Value::VectorInt(out))
+        }
+        CumOp::Max => {
+            let mut best = i64::MIN;
+            Ok(Value::VectorInt(
+                values
+                    .iter()
+                    .map(|&x| {
+                        best = best.max(x);
+                        best
+                    })
+                    .collect(),
+            ))
+        }
+        CumOp::Diff => {
+            let mut entries = Vec::with_capacity(values.len().saturating_sub(1));
+            for (i, pair) in values.windows(2).enumerate() {
+                if let [prev, next] = pair {
+                    let delta = next.checked_sub(*prev).ok_or_else(overflow)?;
+                    entries.push((i.saturating_add(1), delta));
+                }
+            }
+            Ok(Value::VectorOptionInt {
+                len: values.len(),
+                entries,
+            })
+        }
+    }
+}
+
+/// [`Value::cumulative`] of a `VectorFloat`; NaN elements stay NaN.
+fn cumulative_floats(values: &[f64], op: CumOp) -> Vec<f64> {
+    match op {
+        CumOp::Sum | CumOp::Max => {
+            let mut acc: Option<f64> = None;
+            values
+                .iter()
+                .map(|&x| {
+                    if x.is_nan() {
+                        return x;
+                    }
+                    let next = match (acc, op) {
+                        (Some(a), CumOp::Sum) => a + x,
+                        (Some(a), _) => a.max(x),
+                        (None, _) => x,
+                    };
+                    acc = Some(next);
+                    next
+                })
+                .collect()
+        }
+        CumOp::Diff => std::iter::once(f64::NAN)
+            .take(values.len())
+            .chain(values.windows(2).map(|pair| match pair {
+                [prev, next] => next - prev,
+                _ => f64::NAN,
+            }))
+            .collect(),
+    }
+}
+
+/// Error for an aggregation that needs at least one element.
+fn empty_aggregate(name: &str) -> SoAKitError {
+    SoAKitError::InvalidArgument(format!("Cannot compute {} of an empty value", name))
+}
+
+/// Ensure two operands of an element-wise operation have the same length.
+const fn check_same_len(expected: usize, actual: usize) -> Result<()> {
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(SoAKitError::LengthMismatch { expected, actual })
+    }
+}
+
+/// Name of a value's variant, used in error messages.
+pub(crate) const fn variant_name(value: &Value) -> &'static str {
+    match value {
+        Value::ScalarInt(_) => "ScalarInt",
+        Value::ScalarFloat(_) => "ScalarFloat",
+        Value::ScalarBool(_) => "ScalarBool",
+        Value::ScalarString(_) => "ScalarString",
+        Value::VectorInt(_) => "VectorInt",
+        Value::VectorFloat(_) => "VectorFloat",
+        Value::VectorBool(_) => "VectorBool",
+        Value::VectorString(_) => "VectorString",
+        Value::Matrix(_) => "Matrix",
+        Value::Tensor { .. } => "Tensor",
+        Value::Null => "Null",
+        Value::VectorOptionInt { .. } => "VectorOptionInt",
+        Value::VectorOptionFloat { .. } => "VectorOptionFloat",
+        Value::VectorOptionBool { .. } => "VectorOptionBool",
+        Value::VectorOptionString { .. } => "VectorOptionString",
+        Value::ScalarJson(_) => "ScalarJson",
+        Value::VectorJson(_) => "VectorJson",
+    }
+}
+
+/// Implements an arithmetic operator for every combination of owned and
+/// borrowed [`Value`] operands, delegating to [`Value::arith`].
+macro_rules! impl_value_op {
+    ($trait:ident, $method:ident, $op:expr) => {
+        impl $trait<&Value> for &Value {
+            type Output = Result<Value>;
+
+            fn $method(self, rhs: &Value) -> Result<Value> {
+                self.arith(rhs, $op)
+            }
+        }
+
+        impl $trait<Value> for Value {
+            type Output = Result<Value>;
+
+            fn $method(self, rhs: Value) -> Result<Value> {
+                self.arith(&rhs, $op)
+            }
+        }
+
+        impl $trait<&Value> for Value {
+            type Output = Result<Value>;
+
+            fn $method(self, rhs: &Value) -> Result<Value> {
+                self.arith(rhs, $op)
+            }
+        }
+
+        impl $trait<Value> for &Value {
+            type Output = Result<Value>;
+
+            fn $method(self, rhs: Value) -> Result<Value> {
+                self.arith(&rhs, $op)
+            }
+        }
+    };
+}
+
+impl_value_op!(Add, add, ArithOp::Add);
+impl_value_op!(Sub, sub, ArithOp::Sub);
+impl_value_op!(Mul, mul, ArithOp::Mul);
+impl_value_op!(Div, div, ArithOp::Div);
+
+/// Implements `From<$ty> for Value` and `TryFrom<Value> for $ty` for a Rust type
+/// that maps one-to-one onto a single [`Value`] variant.
+macro_rules! impl_value_conversion {
+    ($ty:ty, $variant:ident) => {
+        impl From<$ty> for Value {
+            fn from(v: $ty) -> Self {
+                Value::$variant(v)
+            }
+        }
+
+        impl TryFrom<Value> for $ty {
+            type Error = SoAKitError;
+
+            fn try_from(value: Value) -> Result<Self> {
+                match value {
+                    Value::$variant(v) => Ok(v),
+                    other => Err(SoAKitError::InvalidArgument(format!(
+                        "Expected {}, found {}",
+                        stringify!($variant),
+                        variant_name(&other)
+                    ))),
+                }
+            }
+        }
+    };
+}
+
+impl_value_conversion!(i64, ScalarInt);
+impl_value_conversion!(f64, ScalarFloat);
+impl_value_conversion!(bool, ScalarBool);
+impl_value_conversion!(String, ScalarString);
+impl_value_conversion!(Vec<i64>, VectorInt);
+impl_value_conversion!(Vec<f64>, VectorFloat);
+impl_value_conversion!(Vec<bool>, VectorBool);
+impl_value_conversion!(Vec<String>, VectorString);
+
+impl From<i32> for Value {
+    fn from(v: i32) -> Self {
+        Value::ScalarInt(i64::from(v))
+    }
+}
+
+impl From<&str> for Value {
+    fn from(v: &str) -> Self {
+        Value::ScalarString(v.to_string())
+    }
+}
+
+impl From<Vec<&str>> for Value {
+    fn from(v: Vec<&str>) -> Self {
+        Value::VectorString(v.into_iter().map(str::to_string).collect())
+    }
+}
+
+/// Position of a value's variant in declaration order, used to order values of
+/// different variants in [`Value::total_cmp`].
+const fn variant_order(value: &Value) -> u8 {
+    match value {
+        Value::ScalarInt(_) => 0,
+        Value::ScalarFloat(_) => 1,
+        Value::ScalarBool(_) => 2,
+        Value::ScalarString(_) => 3,
+        Value::VectorInt(_) => 4,
+        Value::VectorFloat(_) => 5,
+        Value::VectorBool(_) => 6,
+        Value::VectorString(_) => 7,
+        Value::Matrix(_) => 8,
+        Value::Tensor { .. } => 9,
+        Value::Null => 10,
+        Value::VectorOptionInt { .. } => 11,
+        Value::VectorOptionFloat { .. } => 12,
+        Value::VectorOptionBool { .. } => 13,
+        Value::VectorOptionString { .. } => 14,
+        Value::ScalarJson(_) => 15,
+        Value::VectorJson(_) => 16,
+    }
+}
+
+/// Total order on floats with NaN normalized (sorted last) and `-0.0 == 0.0`.
+fn float_total_cmp(a: f64, b: f64) -> Ordering {
+    normalize_float(a).total_cmp(&normalize_float(b))
+}
+
+/// Total order on JSON documents, by their compact JSON text.
+fn json_cmp(a: &serde_json::Value, b: &serde_json::Value) -> Ordering {
+    a.to_string().cmp(&b.to_string())
+}
+
+/// Estimate the heap memory owned by a JSON document, as [`Value::heap_size`] does.
+fn json_heap_size(json: &serde_json::Value) -> usize {
+    let node = size_of::<serde_json::Value>();
+    match json {
+        serde_json::Value::String(s) => s.len(),
+        serde_json::Value::Array(items) => items
+            .iter()
+            .map(|item| node.saturating_add(json_heap_size(item)))
+            .fold(0, usize::saturating_add),
+        serde_json::Value::Object(map) => map
+            .iter()
+            .map(|(key, item)| {
+                node.saturating_add(key.len())
+                    .saturating_add(json_heap_size(item))
+            })
+            .fold(0, usize::saturating_add),
+        _ => 0,
+    }
+}
+
+/// Serde helpers for [`Value::ScalarJson`].
+///
+/// Human-readable formats embed the document directly. Binary formats such as
+/// bincode cannot deserialize self-describing data, so they store JSON text.
+mod json_text {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(
+        value: &serde_json::Value,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            value.serialize(serializer)
+        } else {
+            serializer.serialize_str(&value.to_string())
+        }
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<serde_json::Value, D::Error> {
+        if deserializer.is_human_readable() {
+            serde_json::Value::deserialize(deserializer)
+        } else {
+            let text = String::deserialize(deserializer)?;
+            serde_json::from_str(&text).map_err(D::Error::custom)
+        }
+    }
+}
+
+/// Serde helpers for [`Value::VectorJson`], see [`json_text`].
+mod json_texts {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(
+        values: &[serde_json::Value],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            values.serialize(serializer)
+        } else {
+            serializer.collect_seq(values.iter().map(ToString::to_string))
+        }
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<serde_json::Value>, D::Error> {
+        if deserializer.is_human_readable() {
+            Vec::<serde_json::Value>::deserialize(deserializer)
+        } else {
+            Vec::<String>::deserialize(deserializer)?
+                .iter()
+                .map(|text| serde_json::from_str(text).map_err(D::Error::custom))
+                .collect()
+        }
+    }
+}
+
+/// Lexicographic comparison of two slices using a custom element comparator.
+fn lexicographic_cmp<T>(a: &[T], b: &[T], cmp: impl Fn(&T, &T) -> Ordering) -> Ordering {
+    a.iter()
+        .zip(b.iter())
+        .map(|(x, y)| cmp(x, y))
+        .find(|ord| *ord != Ordering::Equal)
+        .unwrap_or_else(|| a.len().cmp(&b.len()))
+}
+
+/// Wrapper that gives [`Value`] `Eq`, `Ord` and `Hash` implementations.
+///
+/// `Value` itself only implements `PartialEq`, because floats do. `HashableValue`
+/// uses [`Value::total_cmp`] for equality and ordering, and hashes floats after
+/// normalizing NaN and `-0.0`, so that equal values always hash equally. This makes
+/// it possible to use values as keys in `HashMap`/`BTreeMap`, to sort them, and to
+/// deduplicate them with the same semantics everywhere in the crate.
+///
+/// # Examples
+///
+/// ```rust
+/// use soakit::{HashableValue, Value};
+/// use std::collections::HashSet;
+///
+/// let mut seen = HashSet::new();
+/// assert!(seen.insert(HashableValue::new(Value::ScalarFloat(f64::NAN))));
+/// assert!(!seen.insert(HashableValue::new(Value::ScalarFloat(f64::NAN))));
+/// assert!(seen.insert(HashableValue::new(Value::ScalarFloat(0.0))));
+/// assert!(!seen.insert(HashableValue::new(Value::ScalarFloat(-0.0))));
+/// ```
+#[derive(Clone, Debug)]
+pub struct HashableValue(pub Value);
+
+impl HashableValue {
+    /// Wrap a value.
+    pub const fn new(value: Value) -> Self {
+        Self(value)
+    }
+
+    /// Get a reference to the wrapped value.
+    pub const fn value(&self) -> &Value {
+        &self.0
+    }
+
+    /// Unwrap into the inner value.
+    pub fn into_inner(self) -> Value {
+        self.0
+    }
+}
+
+impl From<Value> for HashableValue {
+    fn from(value: Value) -> Self {
+        Self(value)
+    }
+}
+
+impl PartialEq for HashableValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.total_cmp(&other.0) == Ordering::Equal
+    }
+}
+
+impl Eq for HashableValue {}
+
+impl PartialOrd for HashableValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HashableValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl Hash for HashableValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_value(&self.0, state);
+    }
+}
+
+/// Hash a value consistently with [`Value::total_cmp`].
+fn hash_value<H: Hasher>(value: &Value, state: &mut H) {
+    variant_order(value).hash(state);
+    match value {
+        Value::ScalarInt(v) => v.hash(state),
+        Value::ScalarFloat(v) => float_key(*v).hash(state),
+        Value::ScalarBool(v) => v.hash(state),
+        Value::ScalarString(v) => v.hash(state),
+        Value::VectorInt(v) => v.hash(state),
+        Value::VectorFloat(v) => {
+            v.len().hash(state);
+            for x in v {
+                float_key(*x).hash(state);
+            }
+        }
+        Value::VectorBool(v) => v.hash(state),
+        Value::VectorString(v) => v.hash(state),
+        Value::Matrix(rows) => {
+            rows.len().hash(state);
+            for row in rows {
+                hash_value(row, state);
+            }
+        }
+        Value::Tensor { data, shape } => {
+            shape.hash(state);
+            for x in data {
+                float_key(*x).hash(state);
+            }
+        }
+        Value::Null => {}
+        Value::ScalarJson(v) => v.to_string().hash(state),
+        Value::VectorJson(v) => {
+            v.len().hash(state);
+            for x in v {
+                x.to_string().hash(state);
+            }
+        }
+        Value::VectorOptionInt { .. }
+        | Value::VectorOptionFloat { .. }
+        | Value::VectorOptionBool { .. }
+        | Value::VectorOptionString { .. } => {
+            if let Some((len, indices, values)) = value.option_parts() {
+                len.hash(state);
+                indices.hash(state);
+                hash_value(&values, state);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scalar_types() {
+        let v1 = Value::ScalarInt(42);
+        let v2 = Value::ScalarFloat(3.14);
+        let v3 = Value::ScalarBool(true);
+        let v4 = Value::ScalarString("hello".to_string());
+
+        assert!(v1.is_scalar());
+        assert!(v2.is_scalar());
+        assert!(v3.is_scalar());
+        assert!(v4.is_scalar());
+        assert!(!v1.is_vector());
+        assert!(!v1.is_matrix());
+        assert_eq!(v1.rank(), 0);
+        assert_eq!(v1.len(), 1);
+    }
+
+    #[test]
+    fn test_vector_types() {
+        let v1 = Value::VectorInt(vec![1, 2, 3]);
+        let v2 = Value::VectorFloat(vec![1.0, 2.0, 3.0]);
+        let v3 = Value::VectorBool(vec![true, false]);
+        let v4 = Value::VectorString(vec!["a".to_string(), "b".to_string()]);
+
+        assert!(v1.is_vector());
+        assert!(v2.is_vector());
+        assert!(v3.is_vector());
+        assert!(v4.is_vector());
+        assert!(!v1.is_scalar());
+        assert!(!v1.is_matrix());
+        assert_eq!(v1.rank(), 1);
+        assert_eq!(v1.len(), 3);
+        assert_eq!(v3.len(), 2);
+    }
+
+    #[test]
+    fn test_matrix_type() {
+        let m = Value::Matrix(vec![
+            Value::VectorInt(vec![1, 2]),
+            Value::VectorInt(vec![3, 4]),
+        ]);
+        assert!(m.is_matrix());
+        assert!(!m.is_scalar());
+        assert!(!m.is_vector());
+        assert_eq!(m.rank(), 2);
+        assert_eq!(m.len(), 2);
+    }
+
+    #[test]
+    fn test_get_element() {
+        let v = Value::VectorInt(vec![10, 20, 30]);
+        assert_eq!(v.get_element(0).unwrap(), Value::ScalarInt(10));
+        assert_eq!(v.get_element(1).unwrap(), Value::ScalarInt(20));
+        assert!(v.get_element(10).is_err());
+    }
+
+    #[test]
+    fn test_scalar_edge_values() {
+        // Test zero, negative, and extreme values
+        let zero_int = Value::ScalarInt(0);
+        let neg_int = Value::ScalarInt(-42);
+        let max_int = Value::ScalarInt(i64::MAX);
+        let min_int = Value::ScalarInt(i64::MIN);
+
+        assert!(zero_int.is_scalar());
+        assert_eq!(zero_int.rank(), 0);
+        assert_eq!(zero_int.len(), 1);
+        assert!(!zero_int.is_empty());
+
+        assert!(neg_int.is_scalar());
+        assert!(max_int.is_scalar());
+        assert!(min_int.is_scalar());
+
+        // Test float edge values
+        let zero_float = Value::ScalarFloat(0.0);
+        let neg_float = Value::ScalarFloat(-3.14);
+        let inf_float = Value::ScalarFloat(f64::INFINITY);
+        let neg_inf_float = Value::ScalarFloat(f64::NEG_INFINITY);
+        let nan_float = Value::ScalarFloat(f64::NAN);
+
+        assert!(zero_float.is_scalar());
+        assert!(neg_float.is_scalar());
+        assert!(inf_float.is_scalar());
+        assert!(neg_inf_float.is_scalar());
+        assert!(nan_float.is_scalar());
+
+        // Test empty string
+        let empty_str = Value::ScalarString(String::new());
+        assert!(empty_str.is_scalar());
+        assert_eq!(empty_str.len(), 1);
+        assert!(!empty_str.is_empty());
+    }
+
+    #[test]
+    fn test_empty_vectors() {
+        let empty_int = Value::VectorInt(vec![]);
+        let empty_float = Value::VectorFloat(vec![]);
+        let empty_bool = Value::VectorBool(vec![]);
+        let empty_string = Value::VectorString(vec![]);
+
+        assert!(empty_int.is_vector());
+        assert!(empty_int.is_empty());
+        assert_eq!(empty_int.len(), 0);
+        assert_eq!(empty_int.rank(), 1);
+        assert_eq!(empty_int.shape(), vec![0]);
+
+        assert!(empty_float.is_empty());
+        assert!(empty_bool.is_empty());
+        assert!(empty_string.is_empty());
+    }
+
+    #[test]
+    fn test_single_element_vectors() {
         let single_int = Value::VectorInt(vec![42]);
         let single_float = Value::VectorFloat(vec![3.14]);
         let single_bool = Value::VectorBool(vec![true]);
         let single_string = Value::VectorString(vec!["hello".to_string()]);
 
-        assert!(single_int.is_vector());
-        assert_eq!(single_int.len(), 1);
-        assert!(!single_int.is_empty());
-        assert_eq!(single_int.shape(), vec![1]);
-        assert_eq!(single_int.get_element(0).unwrap(), Value::ScalarInt(42));
+        assert!(single_int.is_vector());
+        assert_eq!(single_int.len(), 1);
+        assert!(!single_int.is_empty());
+        assert_eq!(single_int.shape(), vec![1]);
+        assert_eq!(single_int.get_element(0).unwrap(), Value::ScalarInt(42));
+
+        assert_eq!(single_float.len(), 1);
+        assert_eq!(single_bool.len(), 1);
+        assert_eq!(single_string.len(), 1);
+    }
+
+    #[test]
+    fn test_large_vectors() {
+        let large_int = Value::VectorInt((0..1000).collect());
+        assert_eq!(large_int.len(), 1000);
+        assert_eq!(large_int.get_element(0).unwrap(), Value::ScalarInt(0));
+        assert_eq!(large_int.get_element(999).unwrap(), Value::ScalarInt(999));
+        assert!(large_int.get_element(1000).is_err());
+
+        let large_float = Value::VectorFloat((0..100).map(|i| i as f64).collect());
+        assert_eq!(large_float.len(), 100);
+    }
+
+    #[test]
+    fn test_vector_float_nan_handling() {
+        let vec_with_nan = Value::VectorFloat(vec![1.0, f64::NAN, 3.0]);
+        assert_eq!(vec_with_nan.len(), 3);
+        assert_eq!(
+            vec_with_nan.get_element(0).unwrap(),
+            Value::ScalarFloat(1.0)
+        );
+
+        // NaN comparison - get_element should work
+        let nan_elem = vec_with_nan.get_element(1).unwrap();
+        if let Value::ScalarFloat(f) = nan_elem {
+            assert!(f.is_nan());
+        } else {
+            panic!("Expected ScalarFloat");
+        }
+
+        assert_eq!(
+            vec_with_nan.get_element(2).unwrap(),
+            Value::ScalarFloat(3.0)
+        );
+    }
+
+    #[test]
+    fn test_get_element_all_vector_types() {
+        // Test VectorInt
+        let v_int = Value::VectorInt(vec![1, 2, 3]);
+        assert_eq!(v_int.get_element(0).unwrap(), Value::ScalarInt(1));
+        assert_eq!(v_int.get_element(2).unwrap(), Value::ScalarInt(3));
+        assert!(v_int.get_element(3).is_err());
+
+        // Test VectorFloat
+        let v_float = Value::VectorFloat(vec![1.1, 2.2, 3.3]);
+        assert_eq!(v_float.get_element(0).unwrap(), Value::ScalarFloat(1.1));
+        assert_eq!(v_float.get_element(2).unwrap(), Value::ScalarFloat(3.3));
+
+        // Test VectorBool
+        let v_bool = Value::VectorBool(vec![true, false, true]);
+        assert_eq!(v_bool.get_element(0).unwrap(), Value::ScalarBool(true));
+        assert_eq!(v_bool.get_element(1).unwrap(), Value::ScalarBool(false));
+
+        // Test VectorString
+        let v_string = Value::VectorString(vec!["a".to_string(), "b".to_string(), "c".to_string()]);
+        assert_eq!(
+            v_string.get_element(0).unwrap(),
+            Value::ScalarString("a".to_string())
+        );
+        assert_eq!(
+            v_string.get_element(2).unwrap(),
+            Value::ScalarString("c".to_string())
+        );
+    }
+
+    #[test]
+    fn test_get_element_non_vector() {
+        // Should fail for scalars
+        let scalar = Value::ScalarInt(42);
+        assert!(scalar.get_element(0).is_err());
+        assert!(matches!(
+            scalar.get_element(0).unwrap_err(),
+            SoAKitError::InvalidArgument(_)
+        ));
+
+        // Should fail for matrices
+        let matrix = Value::Matrix(vec![Value::VectorInt(vec![1, 2])]);
+        assert!(matrix.get_element(0).is_err());
+    }
+
+    #[test]
+    fn test_shape_calculations() {
+        // Scalar shapes
+        assert_eq!(Value::ScalarInt(42).shape(), Vec::<usize>::new());
+        assert_eq!(Value::ScalarFloat(3.14).shape(), Vec::<usize>::new());
+        assert_eq!(Value::ScalarBool(true).shape(), Vec::<usize>::new());
+        assert_eq!(
+            Value::ScalarString("test".to_string()).shape(),
+            Vec::<usize>::new()
+        );
+
+        // Vector shapes
+        assert_eq!(Value::VectorInt(vec![1, 2, 3]).shape(), vec![3]);
+        assert_eq!(Value::VectorInt(vec![]).shape(), vec![0]);
+        assert_eq!(Value::VectorFloat(vec![1.0, 2.0]).shape(), vec![2]);
+        assert_eq!(Value::VectorString(vec!["a".to_string()]).shape(), vec![1]);
+
+        // Matrix shapes
+        let matrix = Value::Matrix(vec![
+            Value::VectorInt(vec![1, 2, 3]),
+            Value::VectorInt(vec![4, 5, 6]),
+        ]);
+        assert_eq!(matrix.shape(), vec![2, 3]);
+
+        // Empty matrix
+        let empty_matrix = Value::Matrix(vec![]);
+        assert_eq!(empty_matrix.shape(), vec![0]);
+
+        // Matrix with empty rows
+        let matrix_empty_row = Value::Matrix(vec![Value::VectorInt(vec![])]);
+        assert_eq!(matrix_empty_row.shape(), vec![1, 0]);
+    }
+
+    #[test]
+    fn test_matrix_operations() {
+        // Regular matrix
+        let matrix = Value::Matrix(vec![
+            Value::VectorInt(vec![1, 2]),
+            Value::VectorInt(vec![3, 4]),
+            Value::VectorInt(vec![5, 6]),
+        ]);
+        assert!(matrix.is_matrix());
+        assert_eq!(matrix.rank(), 2);
+        assert_eq!(matrix.len(), 3);
+        assert_eq!(matrix.shape(), vec![3, 2]);
+        assert!(!matrix.is_empty());
+
+        // Empty matrix
+        let empty_matrix = Value::Matrix(vec![]);
+        assert!(empty_matrix.is_matrix());
+        assert_eq!(empty_matrix.rank(), 2);
+        assert_eq!(empty_matrix.len(), 0);
+        assert!(empty_matrix.is_empty());
+
+        // Single row matrix
+        let single_row = Value::Matrix(vec![Value::VectorInt(vec![1, 2, 3])]);
+        assert_eq!(single_row.len(), 1);
+        assert_eq!(single_row.shape(), vec![1, 3]);
+    }
+
+    #[test]
+    fn test_rank_calculations() {
+        // All scalars have rank 0
+        assert_eq!(Value::ScalarInt(0).rank(), 0);
+        assert_eq!(Value::ScalarFloat(0.0).rank(), 0);
+        assert_eq!(Value::ScalarBool(false).rank(), 0);
+        assert_eq!(Value::ScalarString("".to_string()).rank(), 0);
+
+        // All vectors have rank 1
+        assert_eq!(Value::VectorInt(vec![]).rank(), 1);
+        assert_eq!(Value::VectorFloat(vec![1.0]).rank(), 1);
+        assert_eq!(Value::VectorBool(vec![true, false]).rank(), 1);
+        assert_eq!(Value::VectorString(vec!["a".to_string()]).rank(), 1);
+
+        // All matrices have rank 2
+        assert_eq!(Value::Matrix(vec![]).rank(), 2);
+        assert_eq!(Value::Matrix(vec![Value::VectorInt(vec![1])]).rank(), 2);
+    }
+
+    #[test]
+    fn test_is_empty() {
+        // Scalars are never empty
+        assert!(!Value::ScalarInt(0).is_empty());
+        assert!(!Value::ScalarString(String::new()).is_empty());
+
+        // Empty vectors
+        assert!(Value::VectorInt(vec![]).is_empty());
+        assert!(Value::VectorFloat(vec![]).is_empty());
+        assert!(Value::VectorBool(vec![]).is_empty());
+        assert!(Value::VectorString(vec![]).is_empty());
+
+        // Non-empty vectors
+        assert!(!Value::VectorInt(vec![1]).is_empty());
+        assert!(!Value::VectorFloat(vec![1.0, 2.0]).is_empty());
+
+        // Empty matrix
+        assert!(Value::Matrix(vec![]).is_empty());
+
+        // Non-empty matrix
+        assert!(!Value::Matrix(vec![Value::VectorInt(vec![1])]).is_empty());
+    }
+
+    #[test]
+    fn test_string_edge_cases() {
+        // Empty string scalar
+        let empty = Value::ScalarString(String::new());
+        assert_eq!(empty.len(), 1);
+        assert!(!empty.is_empty());
+
+        // String with special characters
+        let special = Value::ScalarString("hello\nworld\t!".to_string());
+        assert_eq!(special.len(), 1);
+
+        // Unicode strings
+        let unicode = Value::ScalarString("你好".to_string());
+        assert_eq!(unicode.len(), 1);
+
+        // Empty string vector
+        let empty_vec = Value::VectorString(vec![]);
+        assert!(empty_vec.is_empty());
+
+        // Vector with empty strings
+        let vec_empty_strs = Value::VectorString(vec![String::new(), "a".to_string()]);
+        assert_eq!(vec_empty_strs.len(), 2);
+        assert_eq!(
+            vec_empty_strs.get_element(0).unwrap(),
+            Value::ScalarString(String::new())
+        );
+    }
+
+    #[test]
+    fn test_boolean_edge_cases() {
+        // All boolean combinations
+        let all_true = Value::VectorBool(vec![true, true, true]);
+        let all_false = Value::VectorBool(vec![false, false, false]);
+        let mixed = Value::VectorBool(vec![true, false, true, false]);
+
+        assert_eq!(all_true.len(), 3);
+        assert_eq!(all_false.len(), 3);
+        assert_eq!(mixed.len(), 4);
+
+        assert_eq!(all_true.get_element(0).unwrap(), Value::ScalarBool(true));
+        assert_eq!(all_false.get_element(0).unwrap(), Value::ScalarBool(false));
+    }
+
+    #[test]
+    fn test_float_special_values() {
+        let vec_special =
+            Value::VectorFloat(vec![f64::INFINITY, f64::NEG_INFINITY, f64::NAN, 0.0, -0.0]);
+
+        assert_eq!(vec_special.len(), 5);
+        let inf_elem = vec_special.get_element(0).unwrap();
+        if let Value::ScalarFloat(f) = inf_elem {
+            assert!(f.is_infinite() && f.is_sign_positive());
+        } else {
+            panic!("Expected ScalarFloat");
+        }
+
+        let neg_inf_elem = vec_special.get_element(1).unwrap();
+        if let Value::ScalarFloat(f) = neg_inf_elem {
+            assert!(f.is_infinite() && f.is_sign_negative());
+        } else {
+            panic!("Expected ScalarFloat");
+        }
+    }
 
-        assert_eq!(single_float.len(), 1);
-        assert_eq!(single_bool.len(), 1);
-        assert_eq!(single_string.len(), 1);
+    #[test]
+    fn test_integer_edge_values() {
+        let vec_extreme = Value::VectorInt(vec![i64::MIN, -1, 0, 1, i64::MAX]);
+
+        assert_eq!(vec_extreme.len(), 5);
+        assert_eq!(
+            vec_extreme.get_element(0).unwrap(),
+            Value::ScalarInt(i64::MIN)
+        );
+        assert_eq!(vec_extreme.get_element(2).unwrap(), Value::ScalarInt(0));
+        assert_eq!(
+            vec_extreme.get_element(4).unwrap(),
+            Value::ScalarInt(i64::MAX)
+        );
     }
 
     #[test]
-    fn test_large_vectors() {
-        let large_int = Value::VectorInt((0..1000).collect());
-        assert_eq!(large_int.len(), 1000);
-        assert_eq!(large_int.get_element(0).unwrap(), Value::ScalarInt(0));
-        assert_eq!(large_int.get_element(999).unwrap(), Value::ScalarInt(999));
-        assert!(large_int.get_element(1000).is_err());
+    fn test_arithmetic_scalars() {
+        let a = Value::ScalarInt(7);
+        let b = Value::ScalarInt(2);
+        assert_eq!((&a + &b).unwrap(), Value::ScalarInt(9));
+        assert_eq!((&a - &b).unwrap(), Value::ScalarInt(5));
+        assert_eq!((&a * &b).unwrap(), Value::ScalarInt(14));
+        assert_eq!((&a / &b).unwrap(), Value::ScalarInt(3));
 
-        let large_float = Value::VectorFloat((0..100).map(|i| i as f64).collect());
-        assert_eq!(large_float.len(), 100);
+        // Mixed int/float promotes to float
+        assert_eq!(
+            (Value::ScalarInt(1) + Value::ScalarFloat(0.5)).unwrap(),
+            Value::ScalarFloat(1.5)
+        );
+
+        // String concatenation
+        assert_eq!(
+            (Value::ScalarString("foo".to_string()) + Value::ScalarString("bar".to_string()))
+                .unwrap(),
+            Value::ScalarString("foobar".to_string())
+        );
     }
 
     #[test]
-    fn test_vector_float_nan_handling() {
-        let vec_with_nan = Value::VectorFloat(vec![1.0, f64::NAN, 3.0]);
-        assert_eq!(vec_with_nan.len(), 3);
+    fn test_arithmetic_vectors_and_broadcasting() {
+        let a = Value::VectorInt(vec![1, 2, 3]);
+        let b = Value::VectorFloat(vec![0.5, 0.5, 0.5]);
+        assert_eq!((&a + &b).unwrap(), Value::VectorFloat(vec![1.5, 2.5, 3.5]));
         assert_eq!(
-            vec_with_nan.get_element(0).unwrap(),
-            Value::ScalarFloat(1.0)
+            (&Value::ScalarInt(10) - &a).unwrap(),
+            Value::VectorInt(vec![9, 8, 7])
+        );
+        assert_eq!(
+            (&a * &Value::ScalarInt(2)).unwrap(),
+            Value::VectorInt(vec![2, 4, 6])
         );
 
-        // NaN comparison - get_element should work
-        let nan_elem = vec_with_nan.get_element(1).unwrap();
-        if let Value::ScalarFloat(f) = nan_elem {
-            assert!(f.is_nan());
-        } else {
-            panic!("Expected ScalarFloat");
+        let m = Value::Matrix(vec![
+            Value::VectorInt(vec![1, 2]),
+            Value::VectorInt(vec![3, 4]),
+        ]);
+        assert_eq!(
+            (&m * &Value::ScalarInt(10)).unwrap(),
+            Value::Matrix(vec![
+                Value::VectorInt(vec![10, 20]),
+                Value::VectorInt(vec![30, 40]),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_arithmetic_errors() {
+        // Length mismatch
+        let result = Value::VectorInt(vec![1, 2]) + Value::VectorInt(vec![1, 2, 3]);
+        assert!(matches!(
+            result.unwrap_err(),
+            SoAKitError::LengthMismatch {
+                expected: 2,
+                actual: 3
+            }
+        ));
+
+        // Overflow and division by zero
+        assert!((Value::ScalarInt(i64::MAX) + Value::ScalarInt(1)).is_err());
+        assert!((Value::ScalarInt(1) / Value::ScalarInt(0)).is_err());
+        assert!((Value::VectorInt(vec![1, 2]) / Value::ScalarInt(0)).is_err());
+
+        // Float division by zero follows IEEE semantics
+        assert_eq!(
+            (Value::ScalarFloat(1.0) / Value::ScalarInt(0)).unwrap(),
+            Value::ScalarFloat(f64::INFINITY)
+        );
+
+        // Unsupported types
+        assert!(matches!(
+            (Value::ScalarBool(true) + Value::ScalarBool(false)).unwrap_err(),
+            SoAKitError::InvalidArgument(_)
+        ));
+        assert!((Value::ScalarString("a".to_string()) * Value::ScalarInt(2)).is_err());
+    }
+
+    #[test]
+    fn test_total_cmp_ordering() {
+        let mut values = vec![
+            Value::ScalarFloat(f64::NAN),
+            Value::ScalarFloat(2.0),
+            Value::ScalarFloat(f64::NEG_INFINITY),
+            Value::ScalarFloat(-1.0),
+        ];
+        values.sort_by(Value::total_cmp);
+        assert_eq!(values[0], Value::ScalarFloat(f64::NEG_INFINITY));
+        assert_eq!(values[1], Value::ScalarFloat(-1.0));
+        assert_eq!(values[2], Value::ScalarFloat(2.0));
+        assert!(matches!(values[3], Value::ScalarFloat(f) if f.is_nan()));
+
+        // Different variants order by declaration order
+        assert_eq!(
+            Value::ScalarInt(100).total_cmp(&Value::ScalarFloat(0.0)),
+            Ordering::Less
+        );
+
+        // Vectors compare lexicographically, shorter prefix first
+        assert_eq!(
+            Value::VectorInt(vec![1, 2]).total_cmp(&Value::VectorInt(vec![1, 2, 0])),
+            Ordering::Less
+        );
+        assert_eq!(
+            Value::VectorFloat(vec![f64::NAN]).total_cmp(&Value::VectorFloat(vec![f64::NAN])),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn test_hashable_value_as_map_key() {
+        use std::collections::HashMap;
+
+        let mut counts: HashMap<HashableValue, usize> = HashMap::new();
+        for v in [f64::NAN, -f64::NAN, 0.0, -0.0, 1.5] {
+            *counts
+                .entry(HashableValue::new(Value::ScalarFloat(v)))
+                .or_insert(0) += 1;
+        }
+        assert_eq!(counts.len(), 3);
+        assert_eq!(counts[&HashableValue::new(Value::ScalarFloat(f64::NAN))], 2);
+        assert_eq!(counts[&HashableValue::new(Value::ScalarFloat(0.0))], 2);
+
+        let a = HashableValue::new(Value::VectorString(vec!["x".to_string()]));
+        let b = HashableValue::from(Value::VectorString(vec!["x".to_string()]));
+        assert_eq!(a, b);
+        assert_eq!(b.into_inner(), Value::VectorString(vec!["x".to_string()]));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Value::ScalarInt(-3).to_string(), "-3");
+        assert_eq!(Value::ScalarFloat(1.5).to_string(), "1.5");
+        assert_eq!(Value::ScalarFloat(f64::NAN).to_string(), "NaN");
+        assert_eq!(Value::ScalarBool(true).to_string(), "true");
+        assert_eq!(
+            Value::ScalarString("Alice".to_string()).to_string(),
+            "Alice"
+        );
+        assert_eq!(Value::VectorInt(vec![1, 2, 3]).to_string(), "[1, 2, 3]");
+        assert_eq!(Value::VectorInt(vec![]).to_string(), "[]");
+        assert_eq!(
+            Value::VectorString(vec!["a".to_string(), "b c".to_string()]).to_string(),
+            r#"["a", "b c"]"#
+        );
+        assert_eq!(
+            Value::Matrix(vec![
+                Value::VectorInt(vec![1, 2]),
+                Value::VectorInt(vec![3, 4]),
+            ])
+            .to_string(),
+            "[[1, 2], [3, 4]]"
+        );
+    }
+
+    #[test]
+    fn test_from_primitives() {
+        assert_eq!(Value::from(42i64), Value::ScalarInt(42));
+        assert_eq!(Value::from(7i32), Value::ScalarInt(7));
+        assert_eq!(Value::from(1.5), Value::ScalarFloat(1.5));
+        assert_eq!(Value::from(true), Value::ScalarBool(true));
+        assert_eq!(Value::from("hi"), Value::ScalarString("hi".to_string()));
+        assert_eq!(
+            Value::from("hi".to_string()),
+            Value::ScalarString("hi".to_string())
+        );
+        assert_eq!(Value::from(vec![1i64, 2]), Value::VectorInt(vec![1, 2]));
+        assert_eq!(Value::from(vec![0.5]), Value::VectorFloat(vec![0.5]));
+        assert_eq!(Value::from(vec![false]), Value::VectorBool(vec![false]));
+        assert_eq!(
+            Value::from(vec!["a", "b"]),
+            Value::VectorString(vec!["a".to_string(), "b".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_try_from_value() {
+        assert_eq!(i64::try_from(Value::ScalarInt(3)).unwrap(), 3);
+        assert_eq!(f64::try_from(Value::ScalarFloat(2.5)).unwrap(), 2.5);
+        assert!(bool::try_from(Value::ScalarBool(true)).unwrap());
+        assert_eq!(
+            String::try_from(Value::ScalarString("x".to_string())).unwrap(),
+            "x"
+        );
+        assert_eq!(
+            Vec::<i64>::try_from(Value::VectorInt(vec![1, 2])).unwrap(),
+            vec![1, 2]
+        );
+        let strings: Vec<String> = Value::from(vec!["a"]).try_into().unwrap();
+        assert_eq!(strings, vec!["a".to_string()]);
+
+        let err = i64::try_from(Value::ScalarFloat(1.0)).unwrap_err();
+        match err {
+            SoAKitError::InvalidArgument(msg) => {
+                assert_eq!(msg, "Expected ScalarInt, found ScalarFloat")
+            }
+            other => panic!("unexpected error: {:?}", other),
         }
+        assert!(Vec::<f64>::try_from(Value::VectorInt(vec![1])).is_err());
+    }
 
+    #[test]
+    fn test_cast() {
         assert_eq!(
-            vec_with_nan.get_element(2).unwrap(),
+            Value::ScalarInt(3).cast(ValueKind::Float).unwrap(),
             Value::ScalarFloat(3.0)
         );
+        assert_eq!(
+            Value::ScalarFloat(-3.7).cast(ValueKind::Int).unwrap(),
+            Value::ScalarInt(-3)
+        );
+        assert_eq!(
+            Value::VectorBool(vec![true, false])
+                .cast(ValueKind::Int)
+                .unwrap(),
+            Value::VectorInt(vec![1, 0])
+        );
+        assert_eq!(
+            Value::VectorBool(vec![true])
+                .cast(ValueKind::Float)
+                .unwrap(),
+            Value::VectorFloat(vec![1.0])
+        );
+        assert_eq!(
+            Value::VectorFloat(vec![1.5, 2.0])
+                .cast(ValueKind::String)
+                .unwrap(),
+            Value::VectorString(vec!["1.5".to_string(), "2".to_string()])
+        );
+        assert_eq!(
+            Value::VectorInt(vec![]).cast(ValueKind::Float).unwrap(),
+            Value::VectorFloat(vec![])
+        );
+
+        let matrix = Value::Matrix(vec![Value::VectorInt(vec![1]), Value::VectorInt(vec![2])]);
+        assert_eq!(
+            matrix.cast(ValueKind::Float).unwrap(),
+            Value::Matrix(vec![
+                Value::VectorFloat(vec![1.0]),
+                Value::VectorFloat(vec![2.0])
+            ])
+        );
+
+        // Unsupported conversions
+        assert!(
+            Value::ScalarString("1".to_string())
+                .cast(ValueKind::Int)
+                .is_err()
+        );
+        assert!(Value::VectorInt(vec![1]).cast(ValueKind::Bool).is_err());
+        match Value::ScalarInt(1).cast(ValueKind::Bool).unwrap_err() {
+            SoAKitError::InvalidArgument(msg) => assert_eq!(msg, "Cannot cast ScalarInt to Bool"),
+            other => panic!("unexpected error: {:?}", other),
+        }
     }
 
     #[test]
-    fn test_get_element_all_vector_types() {
-        // Test VectorInt
-        let v_int = Value::VectorInt(vec![1, 2, 3]);
-        assert_eq!(v_int.get_element(0).unwrap(), Value::ScalarInt(1));
-        assert_eq!(v_int.get_element(2).unwrap(), Value::ScalarInt(3));
-        assert!(v_int.get_element(3).is_err());
+    fn test_cast_overflow() {
+        let big = Value::VectorFloat(vec![1e19, -1e19, f64::NAN, f64::INFINITY]);
+        assert!(big.cast(ValueKind::Int).is_err());
+        assert_eq!(
+            big.cast_with(ValueKind::Int, CastOverflow::Saturate)
+                .unwrap(),
+            Value::VectorInt(vec![i64::MAX, i64::MIN, 0, i64::MAX])
+        );
+        assert_eq!(
+            Value::ScalarFloat(-9_223_372_036_854_775_808.0)
+                .cast(ValueKind::Int)
+                .unwrap(),
+            Value::ScalarInt(i64::MIN)
+        );
+        assert!(
+            Value::ScalarFloat(9_223_372_036_854_775_808.0)
+                .cast(ValueKind::Int)
+                .is_err()
+        );
+    }
 
-        // Test VectorFloat
-        let v_float = Value::VectorFloat(vec![1.1, 2.2, 3.3]);
-        assert_eq!(v_float.get_element(0).unwrap(), Value::ScalarFloat(1.1));
-        assert_eq!(v_float.get_element(2).unwrap(), Value::ScalarFloat(3.3));
+    #[test]
+    fn test_slice_concat_split() {
+        let v = Value::VectorString(vec!["a".to_string(), "b".to_string(), "c".to_string()]);
+        assert_eq!(
+            v.slice(1..=2).unwrap(),
+            Value::VectorString(vec!["b".to_string(), "c".to_string()])
+        );
+        assert_eq!(v.slice(..).unwrap(), v);
+        assert_eq!(v.slice(3..).unwrap(), Value::VectorString(vec![]));
+        assert!(matches!(
+            v.slice(0..4),
+            Err(SoAKitError::IndexOutOfBounds { index: 4, max: 3 })
+        ));
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = v.slice(2..1);
+        assert!(matches!(reversed, Err(SoAKitError::InvalidArgument(_))));
+        assert!(Value::ScalarInt(1).slice(0..1).is_err());
 
-        // Test VectorBool
-        let v_bool = Value::VectorBool(vec![true, false, true]);
-        assert_eq!(v_bool.get_element(0).unwrap(), Value::ScalarBool(true));
-        assert_eq!(v_bool.get_element(1).unwrap(), Value::ScalarBool(false));
+        let m = Value::Matrix(vec![
+            Value::VectorInt(vec![1]),
+            Value::VectorInt(vec![2]),
+            Value::VectorInt(vec![3]),
+        ]);
+        let (head, tail) = m.split_at(2).unwrap();
+        assert_eq!(head.len(), 2);
+        assert_eq!(tail, Value::Matrix(vec![Value::VectorInt(vec![3])]));
+        assert_eq!(Value::concat(&[head, tail]).unwrap(), m);
+        assert!(m.split_at(4).is_err());
+
+        assert!(Value::concat(&[]).is_err());
+        assert!(Value::concat(&[Value::ScalarInt(1)]).is_err());
+        assert!(
+            Value::concat(&[Value::VectorInt(vec![1]), Value::VectorFloat(vec![1.0])]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_map_elements() {
+        let v = Value::VectorFloat(vec![1.0, 4.0]);
+        let doubled = v.map_elements(|x| &x * &Value::ScalarFloat(2.0)).unwrap();
+        assert_eq!(doubled, Value::VectorFloat(vec![2.0, 8.0]));
+
+        let scalar = Value::ScalarInt(3).map_elements(|x| &x + &x).unwrap();
+        assert_eq!(scalar, Value::ScalarInt(6));
+
+        let m = Value::Matrix(vec![
+            Value::VectorInt(vec![1, 2]),
+            Value::VectorInt(vec![3]),
+        ]);
+        let as_strings = m
+            .map_elements(|x| Ok(Value::ScalarString(x.to_string())))
+            .unwrap();
+        assert_eq!(
+            as_strings,
+            Value::Matrix(vec![
+                Value::VectorString(vec!["1".to_string(), "2".to_string()]),
+                Value::VectorString(vec!["3".to_string()]),
+            ])
+        );
+
+        // Empty vectors are returned unchanged and errors propagate
+        let empty = Value::VectorBool(vec![]);
+        assert_eq!(
+            empty.map_elements(|_| Ok(Value::ScalarInt(0))).unwrap(),
+            empty
+        );
+        let err = v.map_elements(|_| Err(SoAKitError::InvalidArgument("boom".to_string())));
+        assert!(err.is_err());
+
+        // Mixed result types cannot be collected into one vector
+        let mut first = true;
+        let mixed = v.map_elements(|x| {
+            let out = if first { x } else { Value::ScalarInt(0) };
+            first = false;
+            Ok(out)
+        });
+        assert!(mixed.is_err());
+    }
+
+    #[test]
+    fn test_zip_with() {
+        let a = Value::VectorString(vec!["a".to_string(), "b".to_string()]);
+        let b = Value::VectorString(vec!["x".to_string(), "y".to_string()]);
+        assert_eq!(
+            a.zip_with(&b, |x, y| x + y).unwrap(),
+            Value::VectorString(vec!["ax".to_string(), "by".to_string()])
+        );
+
+        let eq = Value::VectorInt(vec![1, 2])
+            .zip_with(&Value::VectorInt(vec![1, 3]), |x, y| {
+                Ok(Value::ScalarBool(x == y))
+            })
+            .unwrap();
+        assert_eq!(eq, Value::VectorBool(vec![true, false]));
+
+        let m = Value::Matrix(vec![Value::VectorInt(vec![1, 2])]);
+        assert_eq!(
+            m.zip_with(&m, |x, y| x * y).unwrap(),
+            Value::Matrix(vec![Value::VectorInt(vec![1, 4])])
+        );
+
+        assert!(matches!(
+            a.zip_with(&Value::VectorString(vec![]), |x, _| Ok(x)),
+            Err(SoAKitError::LengthMismatch { .. })
+        ));
+        assert!(a.zip_with(&Value::ScalarInt(1), |x, _| Ok(x)).is_err());
+    }
+
+    #[test]
+    fn test_typed_slice_accessors() {
+        let ints = Value::VectorInt(vec![1, 2]);
+        let floats = Value::VectorFloat(vec![1.0]);
+        let bools = Value::VectorBool(vec![true]);
+        let strings = Value::VectorString(vec!["s".to_string()]);
+
+        assert_eq!(ints.as_int_slice(), Some(&[1, 2][..]));
+        assert_eq!(floats.as_float_slice(), Some(&[1.0][..]));
+        assert_eq!(bools.as_bool_slice(), Some(&[true][..]));
+        assert_eq!(strings.as_str_slice(), Some(&["s".to_string()][..]));
+
+        assert!(floats.as_int_slice().is_none());
+        assert!(ints.as_float_slice().is_none());
+        assert!(Value::ScalarBool(true).as_bool_slice().is_none());
+        assert!(
+            Value::Matrix(vec![strings.clone()])
+                .as_str_slice()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_tensor_construction_and_accessors() {
+        let t = Value::tensor((1..=6).map(f64::from).collect(), vec![2, 3]).unwrap();
+        assert!(t.is_tensor());
+        assert_eq!(t.rank(), 2);
+        assert_eq!(t.len(), 2);
+        assert_eq!(t.shape(), vec![2, 3]);
+        assert_eq!(t.kind(), Some(ValueKind::Float));
+
+        assert_eq!(
+            t.row(1).unwrap(),
+            Value::tensor(vec![4.0, 5.0, 6.0], vec![3]).unwrap()
+        );
+        assert_eq!(
+            t.col(2).unwrap(),
+            Value::tensor(vec![3.0, 6.0], vec![2]).unwrap()
+        );
+        assert!(matches!(
+            t.row(2),
+            Err(SoAKitError::IndexOutOfBounds { .. })
+        ));
+        assert!(matches!(
+            t.col(3),
+            Err(SoAKitError::IndexOutOfBounds { .. })
+        ));
+        assert!(t.row(0).unwrap().col(0).is_err());
+        assert_eq!(
+            t.row(0).unwrap().row(2).unwrap(),
+            Value::tensor(vec![3.0], vec![]).unwrap()
+        );
+
+        let reshaped = t.reshape(vec![3, 2]).unwrap();
+        assert_eq!(
+            reshaped.row(0).unwrap(),
+            Value::tensor(vec![1.0, 2.0], vec![2]).unwrap()
+        );
+        assert!(t.reshape(vec![4]).is_err());
+        assert!(Value::VectorBool(vec![true]).reshape(vec![1]).is_err());
+
+        assert!(matches!(
+            Value::tensor(vec![1.0], vec![2, 2]),
+            Err(SoAKitError::LengthMismatch {
+                expected: 4,
+                actual: 1
+            })
+        ));
+        assert!(Value::tensor(vec![], vec![usize::MAX, 2]).is_err());
+        assert_eq!(Value::tensor(vec![], vec![0, 3]).unwrap().len(), 0);
+
+        assert_eq!(t.to_string(), "[[1, 2, 3], [4, 5, 6]]");
+        assert_eq!(
+            t.to_untagged_json_value(),
+            serde_json::json!([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]])
+        );
+    }
+
+    #[test]
+    fn test_tensor_stack_slice_append() {
+        let a = Value::tensor(vec![1.0, 2.0], vec![2]).unwrap();
+        let b = Value::tensor(vec![3.0, 4.0], vec![2]).unwrap();
+        let stacked = Value::from_scalars(vec![a.clone(), b.clone()]).unwrap();
+        assert_eq!(stacked.shape(), vec![2, 2]);
+        assert_eq!(stacked.get_element(1).unwrap(), b);
+        assert_eq!(stacked.to_scalars(), vec![a.clone(), b.clone()]);
+
+        let wrong = Value::tensor(vec![1.0], vec![1]).unwrap();
+        assert!(Value::from_scalars(vec![a.clone(), wrong.clone()]).is_err());
+        assert!(Value::from_scalars(vec![a.clone(), Value::ScalarFloat(1.0)]).is_err());
+
+        let (head, tail) = stacked.split_at(1).unwrap();
+        assert_eq!(head.shape(), vec![1, 2]);
+        assert_eq!(tail.get_element(0).unwrap(), b);
+        assert_eq!(Value::concat(&[head, tail]).unwrap(), stacked);
+
+        let mut rank1 = a.clone();
+        assert!(rank1.append(b.clone()).is_ok());
+        assert_eq!(rank1.shape(), vec![4]);
+        assert!(stacked.clone().append(wrong).is_err());
+        assert!(
+            Value::tensor(vec![1.0], vec![])
+                .unwrap()
+                .append(a.clone())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_tensor_arithmetic_and_combinators() {
+        let t = Value::tensor(vec![1.0, 2.0, 3.0, 4.0], vec![2, 2]).unwrap();
+        assert_eq!(
+            (&t + &t).unwrap(),
+            Value::tensor(vec![2.0, 4.0, 6.0, 8.0], vec![2, 2]).unwrap()
+        );
+        assert_eq!(
+            (&Value::ScalarInt(10) - &t).unwrap(),
+            Value::tensor(vec![9.0, 8.0, 7.0, 6.0], vec![2, 2]).unwrap()
+        );
+        let other_shape = t.reshape(vec![4]).unwrap();
+        assert!((&t + &other_shape).is_err());
+        assert!((&t + &Value::VectorFloat(vec![1.0; 4])).is_err());
+
+        let squared = t.map_elements(|x| &x * &x).unwrap();
+        assert_eq!(
+            squared,
+            Value::tensor(vec![1.0, 4.0, 9.0, 16.0], vec![2, 2]).unwrap()
+        );
+        assert!(t.map_elements(|_| Ok(Value::ScalarInt(1))).is_err());
+        assert_eq!(
+            t.zip_with(&t, |x, y| x - y).unwrap(),
+            (&t * &Value::ScalarFloat(0.0)).unwrap()
+        );
+        assert!(t.zip_with(&other_shape, |x, _| Ok(x)).is_err());
+
+        assert_eq!(t.cast(ValueKind::Float).unwrap(), t);
+        assert!(t.cast(ValueKind::Int).is_err());
+
+        assert_eq!(t.total_cmp(&t.clone()), Ordering::Equal);
+        assert_eq!(t.total_cmp(&other_shape), Ordering::Less);
+        let mut set = std::collections::HashSet::new();
+        assert!(set.insert(HashableValue::new(t.clone())));
+        assert!(!set.insert(HashableValue::new(t.clone())));
+    }
+
+    #[test]
+    fn test_aggregations() {
+        let ints = Value::VectorInt(vec![4, -2, 7]);
+        assert_eq!(ints.sum().unwrap(), Value::ScalarInt(9));
+        assert_eq!(ints.mean().unwrap(), Value::ScalarFloat(3.0));
+        assert_eq!(ints.min().unwrap(), Value::ScalarInt(-2));
+        assert_eq!(ints.max().unwrap(), Value::ScalarInt(7));
+        assert_eq!(ints.count_nonnull().unwrap(), Value::ScalarInt(3));
+        assert!(Value::VectorInt(vec![i64::MAX, 1]).sum().is_err());
+
+        let floats = Value::VectorFloat(vec![f64::NAN, 1.0, 3.0]);
+        assert_eq!(floats.sum().unwrap(), Value::ScalarFloat(4.0));
+        assert_eq!(floats.mean().unwrap(), Value::ScalarFloat(2.0));
+        assert_eq!(floats.std().unwrap(), Value::ScalarFloat(1.0));
+        assert_eq!(floats.min().unwrap(), Value::ScalarFloat(1.0));
+        assert_eq!(floats.max().unwrap(), Value::ScalarFloat(3.0));
+        assert_eq!(floats.count_nonnull().unwrap(), Value::ScalarInt(2));
+
+        let bools = Value::VectorBool(vec![true, false, true, true]);
+        assert_eq!(bools.sum().unwrap(), Value::ScalarInt(3));
+        assert_eq!(bools.mean().unwrap(), Value::ScalarFloat(0.75));
+        assert_eq!(bools.min().unwrap(), Value::ScalarBool(false));
+
+        let strings = Value::VectorString(vec!["pear".to_string(), "apple".to_string()]);
+        assert_eq!(
+            strings.max().unwrap(),
+            Value::ScalarString("pear".to_string())
+        );
+        assert!(strings.sum().is_err());
+        assert!(strings.mean().is_err());
+
+        let tensor = Value::tensor(vec![1.0, 2.0, 3.0, 6.0], vec![2, 2]).unwrap();
+        assert_eq!(tensor.sum().unwrap(), Value::ScalarFloat(12.0));
+        assert_eq!(tensor.max().unwrap(), Value::ScalarFloat(6.0));
 
-        // Test VectorString
-        let v_string = Value::VectorString(vec!["a".to_string(), "b".to_string(), "c".to_string()]);
+        // Empty and all-NaN inputs
         assert_eq!(
-            v_string.get_element(0).unwrap(),
-            Value::ScalarString("a".to_string())
+            Value::VectorFloat(vec![]).sum().unwrap(),
+            Value::ScalarFloat(0.0)
         );
+        assert!(Value::VectorFloat(vec![f64::NAN]).min().is_err());
+        assert!(Value::VectorFloat(vec![]).std().is_err());
         assert_eq!(
-            v_string.get_element(2).unwrap(),
-            Value::ScalarString("c".to_string())
+            Value::VectorFloat(vec![5.0]).std().unwrap(),
+            Value::ScalarFloat(0.0)
         );
+
+        // Unsupported variants
+        assert!(Value::ScalarInt(1).sum().is_err());
+        assert!(Value::ScalarInt(1).count_nonnull().is_err());
+        assert!(Value::Matrix(vec![]).min().is_err());
     }
 
     #[test]
-    fn test_get_element_non_vector() {
-        // Should fail for scalars
-        let scalar = Value::ScalarInt(42);
-        assert!(scalar.get_element(0).is_err());
-        assert!(matches!(
-            scalar.get_element(0).unwrap_err(),
-            SoAKitError::InvalidArgument(_)
-        ));
-
-        // Should fail for matrices
-        let matrix = Value::Matrix(vec![Value::VectorInt(vec![1, 2])]);
-        assert!(matrix.get_element(0).is_err());
+    fn test_equals_nan_aware() {
+        assert!(Value::ScalarFloat(f64::NAN).equals_nan_aware(&Value::ScalarFloat(-f64::NAN)));
+        assert!(Value::ScalarFloat(-0.0).equals_nan_aware(&Value::ScalarFloat(0.0)));
+        assert!(!Value::ScalarFloat(1.0).equals_nan_aware(&Value::ScalarInt(1)));
+        let m = Value::Matrix(vec![Value::VectorFloat(vec![f64::NAN])]);
+        assert!(m.equals_nan_aware(&m.clone()));
+        assert_ne!(m, m.clone());
     }
 
     #[test]
-    fn test_shape_calculations() {
-        // Scalar shapes
-        assert_eq!(Value::ScalarInt(42).shape(), Vec::<usize>::new());
-        assert_eq!(Value::ScalarFloat(3.14).shape(), Vec::<usize>::new());
-        assert_eq!(Value::ScalarBool(true).shape(), Vec::<usize>::new());
+    fn test_matrix_row_col_transpose() {
+        let m = Value::Matrix(vec![
+            Value::VectorFloat(vec![1.0, 2.0, 3.0]),
+            Value::VectorFloat(vec![4.0, 5.0, 6.0]),
+        ]);
+        assert_eq!(m.row(0).unwrap(), Value::VectorFloat(vec![1.0, 2.0, 3.0]));
+        assert_eq!(m.col(2).unwrap(), Value::VectorFloat(vec![3.0, 6.0]));
+        assert!(matches!(
+            m.row(2),
+            Err(SoAKitError::IndexOutOfBounds { .. })
+        ));
+        assert!(matches!(
+            m.col(3),
+            Err(SoAKitError::IndexOutOfBounds { .. })
+        ));
+
+        let t = m.transpose().unwrap();
         assert_eq!(
-            Value::ScalarString("test".to_string()).shape(),
-            Vec::<usize>::new()
+            t,
+            Value::Matrix(vec![
+                Value::VectorFloat(vec![1.0, 4.0]),
+                Value::VectorFloat(vec![2.0, 5.0]),
+                Value::VectorFloat(vec![3.0, 6.0]),
+            ])
+        );
+        assert_eq!(t.transpose().unwrap(), m);
+        assert_eq!(
+            Value::Matrix(vec![]).transpose().unwrap(),
+            Value::Matrix(vec![])
         );
 
-        // Vector shapes
-        assert_eq!(Value::VectorInt(vec![1, 2, 3]).shape(), vec![3]);
-        assert_eq!(Value::VectorInt(vec![]).shape(), vec![0]);
-        assert_eq!(Value::VectorFloat(vec![1.0, 2.0]).shape(), vec![2]);
-        assert_eq!(Value::VectorString(vec!["a".to_string()]).shape(), vec![1]);
+        let ragged = Value::Matrix(vec![
+            Value::VectorInt(vec![1, 2]),
+            Value::VectorInt(vec![3]),
+        ]);
+        assert!(ragged.transpose().is_err());
+        assert!(ragged.col(0).is_err());
+        let mixed = Value::Matrix(vec![
+            Value::VectorInt(vec![1]),
+            Value::VectorFloat(vec![1.0]),
+        ]);
+        assert!(mixed.transpose().is_err());
+        assert!(Value::VectorInt(vec![1]).transpose().is_err());
 
-        // Matrix shapes
-        let matrix = Value::Matrix(vec![
+        let tensor = Value::tensor((1..=6).map(f64::from).collect(), vec![2, 3]).unwrap();
+        assert_eq!(
+            tensor.transpose().unwrap(),
+            Value::tensor(vec![1.0, 4.0, 2.0, 5.0, 3.0, 6.0], vec![3, 2]).unwrap()
+        );
+        assert!(tensor.reshape(vec![6]).unwrap().transpose().is_err());
+    }
+
+    #[test]
+    fn test_matmul() {
+        let a = Value::Matrix(vec![
             Value::VectorInt(vec![1, 2, 3]),
             Value::VectorInt(vec![4, 5, 6]),
         ]);
-        assert_eq!(matrix.shape(), vec![2, 3]);
+        let b = Value::Matrix(vec![
+            Value::VectorInt(vec![7, 8]),
+            Value::VectorInt(vec![9, 10]),
+            Value::VectorInt(vec![11, 12]),
+        ]);
+        assert_eq!(
+            a.matmul(&b).unwrap(),
+            Value::Matrix(vec![
+                Value::VectorInt(vec![58, 64]),
+                Value::VectorInt(vec![139, 154]),
+            ])
+        );
+        // Inner dimensions must agree
+        assert!(a.matmul(&a).is_err());
 
-        // Empty matrix
-        let empty_matrix = Value::Matrix(vec![]);
-        assert_eq!(empty_matrix.shape(), vec![0]);
+        // Covariance-style product A * A^T with floats
+        let f = a.cast(ValueKind::Float).unwrap();
+        assert_eq!(
+            f.matmul(&f.transpose().unwrap()).unwrap(),
+            Value::Matrix(vec![
+                Value::VectorFloat(vec![14.0, 32.0]),
+                Value::VectorFloat(vec![32.0, 77.0]),
+            ])
+        );
 
-        // Matrix with empty rows
-        let matrix_empty_row = Value::Matrix(vec![Value::VectorInt(vec![])]);
-        assert_eq!(matrix_empty_row.shape(), vec![1, 0]);
+        let ta = a.cast(ValueKind::Float).unwrap();
+        let ta = Value::tensor(
+            ta.to_scalars()
+                .iter()
+                .flat_map(|r| r.as_float_slice().unwrap().to_vec())
+                .collect(),
+            vec![2, 3],
+        )
+        .unwrap();
+        let product = ta.matmul(&b).unwrap();
+        assert_eq!(
+            product,
+            Value::tensor(vec![58.0, 64.0, 139.0, 154.0], vec![2, 2]).unwrap()
+        );
+
+        let big = Value::Matrix(vec![Value::VectorInt(vec![i64::MAX])]);
+        assert!(
+            big.matmul(&Value::Matrix(vec![Value::VectorInt(vec![2])]))
+                .is_err()
+        );
+        let strings = Value::Matrix(vec![Value::VectorString(vec!["a".to_string()])]);
+        assert!(strings.matmul(&strings).is_err());
+
+        // Zero inner dimension yields zeros
+        let empty_rows = Value::Matrix(vec![Value::VectorInt(vec![]), Value::VectorInt(vec![])]);
+        let zero = empty_rows.matmul(&Value::Matrix(vec![])).unwrap();
+        assert_eq!(
+            zero,
+            Value::Matrix(vec![Value::VectorInt(vec![]), Value::VectorInt(vec![])])
+        );
     }
 
     #[test]
-    fn test_matrix_operations() {
-        // Regular matrix
-        let matrix = Value::Matrix(vec![
-            Value::VectorInt(vec![1, 2]),
-            Value::VectorInt(vec![3, 4]),
-            Value::VectorInt(vec![5, 6]),
-        ]);
-        assert!(matrix.is_matrix());
-        assert_eq!(matrix.rank(), 2);
-        assert_eq!(matrix.len(), 3);
-        assert_eq!(matrix.shape(), vec![3, 2]);
-        assert!(!matrix.is_empty());
-
-        // Empty matrix
-        let empty_matrix = Value::Matrix(vec![]);
-        assert!(empty_matrix.is_matrix());
-        assert_eq!(empty_matrix.rank(), 2);
-        assert_eq!(empty_matrix.len(), 0);
-        assert!(empty_matrix.is_empty());
+    fn test_option_vector_basics() {
+        let sparse = Value::from_scalars(vec![
+            Value::Null,
+            Value::ScalarInt(5),
+            Value::Null,
+            Value::ScalarInt(7),
+        ])
+        .unwrap();
+        assert_eq!(
+            sparse,
+            Value::VectorOptionInt {
+                len: 4,
+                entries: vec![(1, 5), (3, 7)]
+            }
+        );
+        assert!(sparse.is_sparse());
+        assert!(sparse.is_vector());
+        assert_eq!(sparse.rank(), 1);
+        assert_eq!(sparse.len(), 4);
+        assert_eq!(sparse.shape(), vec![4]);
+        assert_eq!(sparse.kind(), Some(ValueKind::Int));
+        assert_eq!(sparse.get_element(0).unwrap(), Value::Null);
+        assert_eq!(sparse.get_element(3).unwrap(), Value::ScalarInt(7));
+        assert!(sparse.get_element(4).is_err());
+        assert_eq!(Value::from_scalars(sparse.to_scalars()).unwrap(), sparse);
+        assert_eq!(sparse.to_string(), "[null, 5, null, 7]");
 
-        // Single row matrix
-        let single_row = Value::Matrix(vec![Value::VectorInt(vec![1, 2, 3])]);
-        assert_eq!(single_row.len(), 1);
-        assert_eq!(single_row.shape(), vec![1, 3]);
+        assert!(Value::Null.is_scalar());
+        assert_eq!(Value::Null.kind(), None);
+        assert_eq!(
+            Value::from_scalars(vec![Value::Null, Value::Null]).unwrap(),
+            Value::VectorOptionInt {
+                len: 2,
+                entries: vec![]
+            }
+        );
+        assert!(
+            Value::from_scalars(vec![
+                Value::Null,
+                Value::ScalarFloat(1.0),
+                Value::ScalarInt(1)
+            ])
+            .is_err()
+        );
+        assert!(Value::from_scalars(vec![Value::Null, Value::VectorInt(vec![1])]).is_err());
     }
 
     #[test]
-    fn test_rank_calculations() {
-        // All scalars have rank 0
-        assert_eq!(Value::ScalarInt(0).rank(), 0);
-        assert_eq!(Value::ScalarFloat(0.0).rank(), 0);
-        assert_eq!(Value::ScalarBool(false).rank(), 0);
-        assert_eq!(Value::ScalarString("".to_string()).rank(), 0);
+    fn test_option_vector_append_and_slice() {
+        let mut column = Value::VectorString(vec!["a".to_string()]);
+        column
+            .append(Value::VectorOptionString {
+                len: 3,
+                entries: vec![(2, "b".to_string())],
+            })
+            .unwrap();
+        assert_eq!(
+            column,
+            Value::VectorOptionString {
+                len: 4,
+                entries: vec![(0, "a".to_string()), (3, "b".to_string())]
+            }
+        );
 
-        // All vectors have rank 1
-        assert_eq!(Value::VectorInt(vec![]).rank(), 1);
-        assert_eq!(Value::VectorFloat(vec![1.0]).rank(), 1);
-        assert_eq!(Value::VectorBool(vec![true, false]).rank(), 1);
-        assert_eq!(Value::VectorString(vec!["a".to_string()]).rank(), 1);
+        // A chunk of nulls only adopts the kind of its neighbour
+        let mut nulls = Value::VectorOptionInt {
+            len: 2,
+            entries: vec![],
+        };
+        nulls.append(Value::VectorFloat(vec![1.5])).unwrap();
+        assert_eq!(
+            nulls,
+            Value::VectorOptionFloat {
+                len: 3,
+                entries: vec![(2, 1.5)]
+            }
+        );
+        let mut mismatched = nulls.clone();
+        assert!(mismatched.append(Value::VectorBool(vec![true])).is_err());
 
-        // All matrices have rank 2
-        assert_eq!(Value::Matrix(vec![]).rank(), 2);
-        assert_eq!(Value::Matrix(vec![Value::VectorInt(vec![1])]).rank(), 2);
+        assert_eq!(
+            nulls.slice(1..).unwrap(),
+            Value::VectorOptionFloat {
+                len: 2,
+                entries: vec![(1, 1.5)]
+            }
+        );
+        assert_eq!(
+            nulls.slice(..2).unwrap(),
+            Value::VectorOptionFloat {
+                len: 2,
+                entries: vec![]
+            }
+        );
+        assert!(nulls.slice(2..4).is_err());
+        assert_eq!(
+            Value::concat(&[nulls.clone(), nulls.clone()])
+                .unwrap()
+                .len(),
+            6
+        );
     }
 
     #[test]
-    fn test_is_empty() {
-        // Scalars are never empty
-        assert!(!Value::ScalarInt(0).is_empty());
-        assert!(!Value::ScalarString(String::new()).is_empty());
-
-        // Empty vectors
-        assert!(Value::VectorInt(vec![]).is_empty());
-        assert!(Value::VectorFloat(vec![]).is_empty());
-        assert!(Value::VectorBool(vec![]).is_empty());
-        assert!(Value::VectorString(vec![]).is_empty());
+    fn test_option_vector_dense_conversion() {
+        let dense = Value::VectorFloat(vec![f64::NAN, 2.0, f64::NAN]);
+        let sparse = dense.to_sparse(&Value::ScalarFloat(f64::NAN)).unwrap();
+        assert_eq!(
+            sparse,
+            Value::VectorOptionFloat {
+                len: 3,
+                entries: vec![(1, 2.0)]
+            }
+        );
+        assert_eq!(
+            sparse.to_dense(&Value::ScalarFloat(0.0)).unwrap(),
+            Value::VectorFloat(vec![0.0, 2.0, 0.0])
+        );
+        assert!(sparse.to_dense(&Value::Null).is_err());
+        assert!(
+            sparse
+                .to_dense(&Value::ScalarString("x".to_string()))
+                .is_err()
+        );
+        assert!(Value::ScalarInt(1).to_sparse(&Value::Null).is_err());
+        assert_eq!(
+            Value::VectorBool(vec![true])
+                .to_sparse(&Value::Null)
+                .unwrap(),
+            Value::VectorOptionBool {
+                len: 1,
+                entries: vec![(0, true)]
+            }
+        );
+    }
 
-        // Non-empty vectors
-        assert!(!Value::VectorInt(vec![1]).is_empty());
-        assert!(!Value::VectorFloat(vec![1.0, 2.0]).is_empty());
+    #[test]
+    fn test_option_vector_cast_aggregate_and_arith() {
+        let sparse = Value::VectorOptionInt {
+            len: 4,
+            entries: vec![(0, 2), (3, 4)],
+        };
+        assert_eq!(
+            sparse.cast(ValueKind::Float).unwrap(),
+            Value::VectorOptionFloat {
+                len: 4,
+                entries: vec![(0, 2.0), (3, 4.0)]
+            }
+        );
+        assert_eq!(Value::Null.cast(ValueKind::String).unwrap(), Value::Null);
 
-        // Empty matrix
-        assert!(Value::Matrix(vec![]).is_empty());
+        assert_eq!(sparse.sum().unwrap(), Value::ScalarInt(6));
+        assert_eq!(sparse.mean().unwrap(), Value::ScalarFloat(3.0));
+        assert_eq!(sparse.min().unwrap(), Value::ScalarInt(2));
+        assert_eq!(sparse.max().unwrap(), Value::ScalarInt(4));
+        assert_eq!(sparse.count_nonnull().unwrap(), Value::ScalarInt(2));
 
-        // Non-empty matrix
-        assert!(!Value::Matrix(vec![Value::VectorInt(vec![1])]).is_empty());
+        assert_eq!(
+            (&sparse * &Value::ScalarInt(10)).unwrap(),
+            Value::VectorOptionInt {
+                len: 4,
+                entries: vec![(0, 20), (3, 40)]
+            }
+        );
+        assert_eq!(
+            (&sparse + &Value::VectorInt(vec![1, 1, 1, 1])).unwrap(),
+            Value::VectorOptionInt {
+                len: 4,
+                entries: vec![(0, 3), (3, 5)]
+            }
+        );
+        assert_eq!((&Value::Null + &Value::ScalarInt(1)).unwrap(), Value::Null);
+        assert!((&sparse + &Value::VectorInt(vec![1])).is_err());
     }
 
     #[test]
-    fn test_string_edge_cases() {
-        // Empty string scalar
-        let empty = Value::ScalarString(String::new());
-        assert_eq!(empty.len(), 1);
-        assert!(!empty.is_empty());
-
-        // String with special characters
-        let special = Value::ScalarString("hello\nworld\t!".to_string());
-        assert_eq!(special.len(), 1);
-
-        // Unicode strings
-        let unicode = Value::ScalarString("你好".to_string());
-        assert_eq!(unicode.len(), 1);
+    fn test_option_vector_ordering_hash_and_json() {
+        let a = Value::VectorOptionFloat {
+            len: 2,
+            entries: vec![(0, f64::NAN)],
+        };
+        let b = Value::VectorOptionFloat {
+            len: 2,
+            entries: vec![(0, f64::NAN)],
+        };
+        let c = Value::VectorOptionFloat {
+            len: 2,
+            entries: vec![(1, 0.0)],
+        };
+        assert!(a.equals_nan_aware(&b));
+        assert_eq!(a.total_cmp(&c), Ordering::Less);
+        assert_eq!(Value::ScalarInt(1).total_cmp(&Value::Null), Ordering::Less);
 
-        // Empty string vector
-        let empty_vec = Value::VectorString(vec![]);
-        assert!(empty_vec.is_empty());
+        let mut seen = std::collections::HashSet::new();
+        assert!(seen.insert(HashableValue::new(a)));
+        assert!(!seen.insert(HashableValue::new(b)));
+        assert!(seen.insert(HashableValue::new(Value::Null)));
+        assert!(!seen.insert(HashableValue::new(Value::Null)));
 
-        // Vector with empty strings
-        let vec_empty_strs = Value::VectorString(vec![String::new(), "a".to_string()]);
-        assert_eq!(vec_empty_strs.len(), 2);
+        let sparse = Value::VectorOptionString {
+            len: 3,
+            entries: vec![(1, "x".to_string())],
+        };
+        let json = sparse.to_untagged_json_value();
+        assert_eq!(json, serde_json::json!([null, "x", null]));
+        assert_eq!(Value::from_untagged_json_value(json).unwrap(), sparse);
         assert_eq!(
-            vec_empty_strs.get_element(0).unwrap(),
-            Value::ScalarString(String::new())
+            Value::from_untagged_json_value(serde_json::Value::Null).unwrap(),
+            Value::Null
         );
+
+        let tagged = serde_json::to_string(&sparse).unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&tagged).unwrap(), sparse);
     }
 
     #[test]
-    fn test_boolean_edge_cases() {
-        // All boolean combinations
-        let all_true = Value::VectorBool(vec![true, true, true]);
-        let all_false = Value::VectorBool(vec![false, false, false]);
-        let mixed = Value::VectorBool(vec![true, false, true, false]);
+    fn test_json_cells() {
+        let a = Value::ScalarJson(serde_json::json!({"id": 1, "tags": ["x"]}));
+        let b = Value::ScalarJson(serde_json::json!([1, 2]));
+        assert!(a.is_scalar());
+        assert_eq!(a.kind(), None);
 
-        assert_eq!(all_true.len(), 3);
-        assert_eq!(all_false.len(), 3);
-        assert_eq!(mixed.len(), 4);
+        let column = Value::from_scalars(vec![a.clone(), b.clone()]).unwrap();
+        assert_eq!(
+            column,
+            Value::VectorJson(vec![
+                serde_json::json!({"id": 1, "tags": ["x"]}),
+                serde_json::json!([1, 2])
+            ])
+        );
+        assert!(column.is_vector());
+        assert_eq!(column.len(), 2);
+        assert_eq!(column.get_element(1).unwrap(), b);
+        assert_eq!(
+            column.slice(0..1).unwrap(),
+            Value::VectorJson(vec![serde_json::json!({"id": 1, "tags": ["x"]})])
+        );
+        assert_eq!(column.to_scalars(), vec![a.clone(), b.clone()]);
 
-        assert_eq!(all_true.get_element(0).unwrap(), Value::ScalarBool(true));
-        assert_eq!(all_false.get_element(0).unwrap(), Value::ScalarBool(false));
+        // Untagged JSON passes the documents through unchanged
+        let untagged = column.to_untagged_json_value();
+        assert_eq!(
+            untagged,
+            serde_json::json!([{"id": 1, "tags": ["x"]}, [1, 2]])
+        );
+        assert_eq!(Value::from_untagged_json_value(untagged).unwrap(), column);
+        assert_eq!(
+            Value::from_untagged_json_value(serde_json::json!({"k": null})).unwrap(),
+            Value::ScalarJson(serde_json::json!({"k": null}))
+        );
+
+        let tagged = serde_json::to_string(&column).unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&tagged).unwrap(), column);
+        let bytes = bincode::serialize(&column).unwrap();
+        assert_eq!(bincode::deserialize::<Value>(&bytes).unwrap(), column);
     }
 
     #[test]
-    fn test_float_special_values() {
-        let vec_special =
-            Value::VectorFloat(vec![f64::INFINITY, f64::NEG_INFINITY, f64::NAN, 0.0, -0.0]);
+    fn test_cumulative_ops() {
+        let ints = Value::VectorInt(vec![2, -1, 5, 3]);
+        assert_eq!(
+            ints.cumulative(CumOp::Sum).unwrap(),
+            Value::VectorInt(vec![2, 1, 6, 9])
+        );
+        assert_eq!(
+            ints.cumulative(CumOp::Max).unwrap(),
+            Value::VectorInt(vec![2, 2, 5, 5])
+        );
+        assert_eq!(
+            ints.cumulative(CumOp::Diff).unwrap(),
+            Value::VectorOptionInt {
+                len: 4,
+                entries: vec![(1, -3), (2, 6), (3, -2)]
+            }
+        );
+        assert!(
+            Value::VectorInt(vec![i64::MAX, 1])
+                .cumulative(CumOp::Sum)
+                .is_err()
+        );
+        assert!(
+            Value::VectorInt(vec![i64::MIN, 1])
+                .cumulative(CumOp::Diff)
+                .is_err()
+        );
 
-        assert_eq!(vec_special.len(), 5);
-        let inf_elem = vec_special.get_element(0).unwrap();
-        if let Value::ScalarFloat(f) = inf_elem {
-            assert!(f.is_infinite() && f.is_sign_positive());
-        } else {
-            panic!("Expected ScalarFloat");
-        }
+        let floats = Value::VectorFloat(vec![1.0, f64::NAN, 3.0, 2.0]);
+        let Value::VectorFloat(sums) = floats.cumulative(CumOp::Sum).unwrap() else {
+            panic!("expected floats");
+        };
+        assert_eq!(sums[0], 1.0);
+        assert!(sums[1].is_nan());
+        assert_eq!(&sums[2..], &[4.0, 6.0]);
+        let Value::VectorFloat(maxes) = floats.cumulative(CumOp::Max).unwrap() else {
+            panic!("expected floats");
+        };
+        assert_eq!(&maxes[2..], &[3.0, 3.0]);
+        let Value::VectorFloat(diffs) = floats.cumulative(CumOp::Diff).unwrap() else {
+            panic!("expected floats");
+        };
+        assert_eq!(diffs.len(), 4);
+        assert!(diffs[0].is_nan() && diffs[1].is_nan() && diffs[2].is_nan());
+        assert_eq!(diffs[3], -1.0);
 
-        let neg_inf_elem = vec_special.get_element(1).unwrap();
-        if let Value::ScalarFloat(f) = neg_inf_elem {
-            assert!(f.is_infinite() && f.is_sign_negative());
-        } else {
-            panic!("Expected ScalarFloat");
-        }
+        assert_eq!(
+            Value::VectorFloat(vec![]).cumulative(CumOp::Diff).unwrap(),
+            Value::VectorFloat(vec![])
+        );
+        assert!(Value::VectorString(vec![]).cumulative(CumOp::Sum).is_err());
     }
 
     #[test]
-    fn test_integer_edge_values() {
-        let vec_extreme = Value::VectorInt(vec![i64::MIN, -1, 0, 1, i64::MAX]);
-
-        assert_eq!(vec_extreme.len(), 5);
+    fn test_rolling_windows_and_edges() {
+        let v = Value::VectorInt(vec![1, 5, 2, 8, 3]);
+        let Value::VectorFloat(means) = v.rolling(3, RollOp::Mean).unwrap() else {
+            panic!("expected floats");
+        };
+        assert!(means[0].is_nan() && means[1].is_nan());
+        assert_eq!(&means[2..], &[8.0 / 3.0, 5.0, 13.0 / 3.0]);
         assert_eq!(
-            vec_extreme.get_element(0).unwrap(),
-            Value::ScalarInt(i64::MIN)
+            v.rolling_with(3, RollOp::Max, RollEdge::Partial).unwrap(),
+            Value::VectorFloat(vec![1.0, 5.0, 5.0, 8.0, 8.0])
         );
-        assert_eq!(vec_extreme.get_element(2).unwrap(), Value::ScalarInt(0));
         assert_eq!(
-            vec_extreme.get_element(4).unwrap(),
-            Value::ScalarInt(i64::MAX)
+            v.rolling_with(1, RollOp::Sum, RollEdge::Nan).unwrap(),
+            Value::VectorFloat(vec![1.0, 5.0, 2.0, 8.0, 3.0])
+        );
+
+        let gaps = Value::VectorFloat(vec![f64::NAN, f64::NAN, 4.0, 6.0]);
+        let Value::VectorFloat(sums) = gaps
+            .rolling_with(2, RollOp::Sum, RollEdge::Partial)
+            .unwrap()
+        else {
+            panic!("expected floats");
+        };
+        assert!(sums[0].is_nan() && sums[1].is_nan());
+        assert_eq!(&sums[2..], &[4.0, 10.0]);
+
+        assert!(v.rolling(0, RollOp::Sum).is_err());
+        assert!(Value::VectorString(vec![]).rolling(2, RollOp::Sum).is_err());
+    }
+
+    #[test]
+    fn test_rolling_running_state_matches_recomputation() {
+        let v = Value::VectorFloat(vec![1.0, 2.0]);
+        assert_eq!(
+            v.rolling_with(usize::MAX, RollOp::Sum, RollEdge::Partial)
+                .unwrap(),
+            Value::VectorFloat(vec![1.0, 3.0])
         );
+        let Value::VectorFloat(nans) = v.rolling(usize::MAX, RollOp::Min).unwrap() else {
+            panic!("expected floats");
+        };
+        assert!(nans.iter().all(|x| x.is_nan()));
+
+        // Infinities and overflowing sums leave the window without poisoning it
+        let inputs = vec![
+            f64::INFINITY,
+            1.0,
+            f64::NAN,
+            f64::NEG_INFINITY,
+            2.0,
+            f64::MAX,
+            f64::MAX,
+            3.0,
+            -4.0,
+            0.5,
+            f64::NAN,
+            f64::NAN,
+            f64::NAN,
+            7.0,
+            -1.5,
+            2.25,
+            8.0,
+            -3.0,
+        ];
+        let v = Value::VectorFloat(inputs.clone());
+        for window in 1..=5 {
+            for op in [RollOp::Sum, RollOp::Mean, RollOp::Min, RollOp::Max] {
+                let Value::VectorFloat(got) =
+                    v.rolling_with(window, op, RollEdge::Partial).unwrap()
+                else {
+                    panic!("expected floats");
+                };
+                for (end, &actual) in got.iter().enumerate() {
+                    let start = (end + 1).saturating_sub(window);
+                    let present: Vec<f64> = inputs[start..=end]
+                        .iter()
+                        .copied()
+                        .filter(|x| !x.is_nan())
+                        .collect();
+                    let expected = if present.is_empty() {
+                        f64::NAN
+                    } else {
+                        match op {
+                            RollOp::Sum => present.iter().sum(),
+                            RollOp::Mean => mean_of(&present).unwrap(),
+                            RollOp::Min => present.iter().copied().fold(f64::INFINITY, f64::min),
+                            RollOp::Max => {
+                                present.iter().copied().fold(f64::NEG_INFINITY, f64::max)
+                            }
+                        }
+                    };
+                    assert!(
+                        actual == expected || (actual.is_nan() && expected.is_nan()),
+                        "{:?} of window {} ending at {}: {} != {}",
+                        op,
+                        window,
+                        end,
+                        actual,
+                        expected
+                    );
+                }
+            }
+        }
     }
 }
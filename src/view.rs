@@ -6,7 +6,7 @@
 use crate::bulk::Bulk;
 use crate::error::{Result, SoAKitError};
 use crate::value::Value;
-use std::rc::Rc;
+use std::sync::Arc;
 
 /// View representing a partition of a Bulk structure.
 ///
@@ -27,7 +27,7 @@ use std::rc::Rc;
 ///
 /// ```rust
 /// use soakit::{Bulk, Registry, Value};
-/// use std::rc::Rc;
+/// use std::sync::Arc;
 ///
 /// let mut registry = Registry::new();
 /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
@@ -51,7 +51,7 @@ pub struct View {
     /// Boolean mask indicating which elements belong to this partition
     pub mask: Vec<bool>,
     /// Reference to the parent Bulk
-    pub parent: Rc<Bulk>,
+    pub parent: Arc<Bulk>,
 }
 
 impl View {
@@ -76,14 +76,14 @@ impl View {
     ///
     /// ```rust
     /// use soakit::{Bulk, View, Value};
-    /// use std::rc::Rc;
+    /// use std::sync::Arc;
     ///
-    /// let bulk = Rc::new(Bulk::new(5).unwrap());
+    /// let bulk = Arc::new(Bulk::new(5).unwrap());
     /// let mask = vec![true, false, true, false, true];
     /// let view = View::new(Value::ScalarInt(1), mask, bulk).unwrap();
     /// assert_eq!(view.count(), 3);
     /// ```
-    pub fn new(key: Value, mask: Vec<bool>, parent: Rc<Bulk>) -> Result<Self> {
+    pub fn new(key: Value, mask: Vec<bool>, parent: Arc<Bulk>) -> Result<Self> {
         // Validate mask length matches parent count
         if mask.len() != parent.count() {
             return Err(SoAKitError::LengthMismatch {
@@ -107,9 +107,9 @@ impl View {
     ///
     /// ```rust
     /// use soakit::{Bulk, View, Value};
-    /// use std::rc::Rc;
+    /// use std::sync::Arc;
     ///
-    /// let bulk = Rc::new(Bulk::new(5).unwrap());
+    /// let bulk = Arc::new(Bulk::new(5).unwrap());
     /// let mask = vec![true, false, true, false, true];
     /// let view = View::new(Value::ScalarInt(1), mask, bulk).unwrap();
     /// assert_eq!(view.count(), 3);
@@ -130,9 +130,9 @@ impl View {
     ///
     /// ```rust
     /// use soakit::{Bulk, View, Value};
-    /// use std::rc::Rc;
+    /// use std::sync::Arc;
     ///
-    /// let bulk = Rc::new(Bulk::new(3).unwrap());
+    /// let bulk = Arc::new(Bulk::new(3).unwrap());
     /// let empty_mask = vec![false, false, false];
     /// let view = View::new(Value::ScalarInt(0), empty_mask, bulk).unwrap();
     /// assert!(view.is_empty());
@@ -167,14 +167,14 @@ impl View {
     ///
     /// ```rust
     /// use soakit::{Bulk, Registry, View, Value};
-    /// use std::rc::Rc;
+    /// use std::sync::Arc;
     ///
     /// let mut registry = Registry::new();
     /// let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
     /// registry.register("age".to_string(), validator, false, vec![], None).unwrap();
     ///
-    /// let bulk = Rc::new(Bulk::new(4).unwrap());
-    /// let bulk = Rc::new(bulk.set(&registry, "age", vec![
+    /// let bulk = Arc::new(Bulk::new(4).unwrap());
+    /// let bulk = Arc::new(bulk.set(&registry, "age", vec![
     ///     Value::ScalarInt(10),
     ///     Value::ScalarInt(20),
     ///     Value::ScalarInt(10),
@@ -251,6 +251,43 @@ impl View {
                     .collect();
                 Ok(Value::VectorString(filtered))
             }
+            Value::VectorJson(v) => {
+                let filtered: Vec<serde_json::Value> = v
+                    .into_iter()
+                    .enumerate()
+                    .filter_map(|(idx, val)| {
+                        if self.mask.get(idx).copied().unwrap_or(false) {
+                            Some(val)
+                        } else {
+                            None
+                        }
+                    })
+                    .collect();
+                Ok(Value::VectorJson(filtered))
+            }
+            tensor @ Value::Tensor { .. } => {
+                // Keep the selected rows of the first axis
+                let row_shape = tensor.shape().get(1..).unwrap_or_default().to_vec();
+                let rows = tensor
+                    .to_scalars()
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(idx, _)| self.mask.get(*idx).copied().unwrap_or(false))
+                    .map(|(_, row)| row)
+                    .collect();
+                crate::value::stack_tensors(rows, &row_shape)
+            }
+            sparse if sparse.is_sparse() => {
+                // Keep the selected elements, missing ones included
+                let selected = sparse
+                    .to_scalars()
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(idx, _)| self.mask.get(*idx).copied().unwrap_or(false))
+                    .map(|(_, element)| element)
+                    .collect();
+                Value::from_scalars(selected)?.to_sparse(&Value::Null)
+            }
             _ => Err(SoAKitError::InvalidArgument(
                 "Field value is not a vector".to_string(),
             )),
@@ -270,9 +307,9 @@ impl View {
     ///
     /// ```rust
     /// use soakit::{Bulk, View, Value};
-    /// use std::rc::Rc;
+    /// use std::sync::Arc;
     ///
-    /// let bulk = Rc::new(Bulk::new(3).unwrap());
+    /// let bulk = Arc::new(Bulk::new(3).unwrap());
     /// let key = Value::ScalarString("A".to_string());
     /// let mask = vec![true, false, true];
     /// let view = View::new(key.clone(), mask, bulk).unwrap();
@@ -295,9 +332,9 @@ impl View {
     ///
     /// ```rust
     /// use soakit::{Bulk, View, Value};
-    /// use std::rc::Rc;
+    /// use std::sync::Arc;
     ///
-    /// let bulk = Rc::new(Bulk::new(3).unwrap());
+    /// let bulk = Arc::new(Bulk::new(3).unwrap());
     /// let mask = vec![true, false, true];
     /// let view = View::new(Value::ScalarInt(0), mask.clone(), bulk).unwrap();
     /// assert_eq!(view.mask(), mask.as_slice());
@@ -316,9 +353,9 @@ impl View {
     ///
     /// ```rust
     /// use soakit::{Bulk, View, Value};
-    /// use std::rc::Rc;
+    /// use std::sync::Arc;
     ///
-    /// let bulk = Rc::new(Bulk::new(3).unwrap());
+    /// let bulk = Arc::new(Bulk::new(3).unwrap());
     /// let mask = vec![true, false, true];
     /// let view = View::new(Value::ScalarInt(0), mask, bulk.clone()).unwrap();
     /// assert_eq!(view.parent().count(), bulk.count());
@@ -335,7 +372,7 @@ mod tests {
 
     #[test]
     fn test_view_creation() {
-        let bulk = Rc::new(Bulk::new(5).unwrap());
+        let bulk = Arc::new(Bulk::new(5).unwrap());
         let mask = vec![true, false, true, false, true];
         let view = View::new(Value::ScalarInt(1), mask, bulk).unwrap();
         assert_eq!(view.count(), 3);
@@ -344,7 +381,7 @@ mod tests {
 
     #[test]
     fn test_view_mask_length_mismatch() {
-        let bulk = Rc::new(Bulk::new(5).unwrap());
+        let bulk = Arc::new(Bulk::new(5).unwrap());
         let mask = vec![true, false]; // Wrong length
         let result = View::new(Value::ScalarInt(1), mask, bulk);
         assert!(result.is_err());
@@ -362,14 +399,14 @@ mod tests {
             .register("age".to_string(), validator, false, vec![], None)
             .unwrap();
 
-        let bulk = Rc::new(Bulk::new(4).unwrap());
+        let bulk = Arc::new(Bulk::new(4).unwrap());
         let values = vec![
             Value::ScalarInt(10),
             Value::ScalarInt(20),
             Value::ScalarInt(10),
             Value::ScalarInt(30),
         ];
-        let bulk = Rc::new(bulk.set(&registry, "age", values).unwrap());
+        let bulk = Arc::new(bulk.set(&registry, "age", values).unwrap());
 
         // Create view for elements with age == 10
         let mask = vec![true, false, true, false];
@@ -411,8 +448,8 @@ mod tests {
             .register("name".to_string(), str_validator, false, vec![], None)
             .unwrap();
 
-        let bulk = Rc::new(Bulk::new(4).unwrap());
-        let bulk = Rc::new(
+        let bulk = Arc::new(Bulk::new(4).unwrap());
+        let bulk = Arc::new(
             bulk.set(
                 &registry,
                 "age",
@@ -425,7 +462,7 @@ mod tests {
             )
             .unwrap(),
         );
-        let bulk = Rc::new(
+        let bulk = Arc::new(
             bulk.set(
                 &registry,
                 "height",
@@ -438,7 +475,7 @@ mod tests {
             )
             .unwrap(),
         );
-        let bulk = Rc::new(
+        let bulk = Arc::new(
             bulk.set(
                 &registry,
                 "active",
@@ -451,7 +488,7 @@ mod tests {
             )
             .unwrap(),
         );
-        let bulk = Rc::new(
+        let bulk = Arc::new(
             bulk.set(
                 &registry,
                 "name",
@@ -495,7 +532,7 @@ mod tests {
 
     #[test]
     fn test_view_empty() {
-        let bulk = Rc::new(Bulk::new(5).unwrap());
+        let bulk = Arc::new(Bulk::new(5).unwrap());
         let mask = vec![false, false, false, false, false];
         let view = View::new(Value::ScalarInt(0), mask, bulk).unwrap();
         assert!(view.is_empty());
@@ -504,7 +541,7 @@ mod tests {
 
     #[test]
     fn test_view_all_true_mask() {
-        let bulk = Rc::new(Bulk::new(3).unwrap());
+        let bulk = Arc::new(Bulk::new(3).unwrap());
         let mask = vec![true, true, true];
         let view = View::new(Value::ScalarInt(0), mask, bulk).unwrap();
         assert_eq!(view.count(), 3);
@@ -513,7 +550,7 @@ mod tests {
 
     #[test]
     fn test_view_single_element() {
-        let bulk = Rc::new(Bulk::new(3).unwrap());
+        let bulk = Arc::new(Bulk::new(3).unwrap());
         let mask = vec![false, true, false];
         let view = View::new(Value::ScalarInt(0), mask, bulk).unwrap();
         assert_eq!(view.count(), 1);
@@ -522,7 +559,7 @@ mod tests {
 
     #[test]
     fn test_view_key() {
-        let bulk = Rc::new(Bulk::new(3).unwrap());
+        let bulk = Arc::new(Bulk::new(3).unwrap());
         let key = Value::ScalarString("test".to_string());
         let mask = vec![true, false, true];
         let view = View::new(key.clone(), mask, bulk).unwrap();
@@ -531,7 +568,7 @@ mod tests {
 
     #[test]
     fn test_view_mask() {
-        let bulk = Rc::new(Bulk::new(3).unwrap());
+        let bulk = Arc::new(Bulk::new(3).unwrap());
         let mask = vec![true, false, true];
         let view = View::new(Value::ScalarInt(0), mask.clone(), bulk).unwrap();
         assert_eq!(view.mask(), mask.as_slice());
@@ -539,7 +576,7 @@ mod tests {
 
     #[test]
     fn test_view_parent() {
-        let bulk = Rc::new(Bulk::new(3).unwrap());
+        let bulk = Arc::new(Bulk::new(3).unwrap());
         let mask = vec![true, false, true];
         let view = View::new(Value::ScalarInt(0), mask, bulk.clone()).unwrap();
         assert_eq!(view.parent().count(), bulk.count());
@@ -548,7 +585,7 @@ mod tests {
     #[test]
     fn test_view_get_field_nonexistent() {
         let registry = crate::meta::Registry::new();
-        let bulk = Rc::new(Bulk::new(3).unwrap());
+        let bulk = Arc::new(Bulk::new(3).unwrap());
         let mask = vec![true, false, true];
         let view = View::new(Value::ScalarInt(0), mask, bulk).unwrap();
 
@@ -580,7 +617,9 @@ mod tests {
                 let sum: Vec<i64> = a.iter().zip(b.iter()).map(|(x, y)| x + y).collect();
                 Ok(Value::VectorInt(sum))
             } else {
-                Err(SoAKitError::InvalidArgument("Invalid arguments".to_string()))
+                Err(SoAKitError::InvalidArgument(
+                    "Invalid arguments".to_string(),
+                ))
             }
         });
         registry
@@ -593,8 +632,8 @@ mod tests {
             )
             .unwrap();
 
-        let bulk = Rc::new(Bulk::new(4).unwrap());
-        let bulk = Rc::new(
+        let bulk = Arc::new(Bulk::new(4).unwrap());
+        let bulk = Arc::new(
             bulk.set(
                 &registry,
                 "a",
@@ -607,7 +646,7 @@ mod tests {
             )
             .unwrap(),
         );
-        let bulk = Rc::new(
+        let bulk = Arc::new(
             bulk.set(
                 &registry,
                 "b",
@@ -634,19 +673,19 @@ mod tests {
     #[test]
     fn test_view_mask_edge_cases() {
         // Mask with all false except first
-        let bulk = Rc::new(Bulk::new(3).unwrap());
+        let bulk = Arc::new(Bulk::new(3).unwrap());
         let mask = vec![true, false, false];
         let view = View::new(Value::ScalarInt(0), mask, bulk).unwrap();
         assert_eq!(view.count(), 1);
 
         // Mask with all false except last
-        let bulk = Rc::new(Bulk::new(3).unwrap());
+        let bulk = Arc::new(Bulk::new(3).unwrap());
         let mask = vec![false, false, true];
         let view = View::new(Value::ScalarInt(0), mask, bulk).unwrap();
         assert_eq!(view.count(), 1);
 
         // Alternating mask
-        let bulk = Rc::new(Bulk::new(4).unwrap());
+        let bulk = Arc::new(Bulk::new(4).unwrap());
         let mask = vec![true, false, true, false];
         let view = View::new(Value::ScalarInt(0), mask, bulk).unwrap();
         assert_eq!(view.count(), 2);
@@ -660,8 +699,8 @@ mod tests {
             .register("value".to_string(), validator, false, vec![], None)
             .unwrap();
 
-        let bulk = Rc::new(Bulk::new(4).unwrap());
-        let bulk = Rc::new(
+        let bulk = Arc::new(Bulk::new(4).unwrap());
+        let bulk = Arc::new(
             bulk.set(
                 &registry,
                 "value",
@@ -696,8 +735,8 @@ mod tests {
             .register("name".to_string(), validator, false, vec![], None)
             .unwrap();
 
-        let bulk = Rc::new(Bulk::new(3).unwrap());
-        let bulk = Rc::new(
+        let bulk = Arc::new(Bulk::new(3).unwrap());
+        let bulk = Arc::new(
             bulk.set(
                 &registry,
                 "name",
@@ -722,4 +761,3 @@ mod tests {
         }
     }
 }
-
@@ -2,129 +2,7 @@
 #![allow(dead_code)] // Test helpers may not all be used in every test file
 use soakit::*;
 
-/// Builder for creating test registries with common field types
-pub struct RegistryBuilder {
-    registry: Registry,
-}
-
-impl RegistryBuilder {
-    /// Create a new registry builder
-    pub fn new() -> Self {
-        Self {
-            registry: Registry::new(),
-        }
-    }
-
-    /// Add an integer field
-    pub fn with_int_field(mut self, name: &str) -> Self {
-        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)));
-        self.registry
-            .register(name.to_string(), validator, false, vec![], None)
-            .unwrap();
-        self
-    }
-
-    /// Add a float field
-    pub fn with_float_field(mut self, name: &str) -> Self {
-        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarFloat(_)));
-        self.registry
-            .register(name.to_string(), validator, false, vec![], None)
-            .unwrap();
-        self
-    }
-
-    /// Add a boolean field
-    pub fn with_bool_field(mut self, name: &str) -> Self {
-        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarBool(_)));
-        self.registry
-            .register(name.to_string(), validator, false, vec![], None)
-            .unwrap();
-        self
-    }
-
-    /// Add a string field
-    pub fn with_string_field(mut self, name: &str) -> Self {
-        let validator = Box::new(|v: &Value| matches!(v, Value::ScalarString(_)));
-        self.registry
-            .register(name.to_string(), validator, false, vec![], None)
-            .unwrap();
-        self
-    }
-
-    /// Add a derived field
-    pub fn with_derived_field(
-        mut self,
-        name: &str,
-        dependencies: Vec<String>,
-        func: Box<dyn Fn(&[Value]) -> Result<Value> + Send + Sync>,
-    ) -> Self {
-        let validator = Box::new(|v: &Value| matches!(v, Value::VectorInt(_)));
-        self.registry
-            .register(name.to_string(), validator, true, dependencies, Some(func))
-            .unwrap();
-        self
-    }
-
-    /// Build the registry
-    pub fn build(self) -> Registry {
-        self.registry
-    }
-}
-
-impl Default for RegistryBuilder {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
-/// Builder for creating test bulk structures
-pub struct BulkBuilder {
-    bulk: Bulk,
-    registry: Registry,
-}
-
-impl BulkBuilder {
-    /// Create a new bulk builder
-    pub fn new(count: usize, registry: Registry) -> Self {
-        Self {
-            bulk: Bulk::new(count).unwrap(),
-            registry,
-        }
-    }
-
-    /// Set an integer field
-    pub fn with_int_field(mut self, name: &str, values: Vec<i64>) -> Self {
-        let values: Vec<Value> = values.into_iter().map(Value::ScalarInt).collect();
-        self.bulk = self.bulk.set(&self.registry, name, values).unwrap();
-        self
-    }
-
-    /// Set a float field
-    pub fn with_float_field(mut self, name: &str, values: Vec<f64>) -> Self {
-        let values: Vec<Value> = values.into_iter().map(Value::ScalarFloat).collect();
-        self.bulk = self.bulk.set(&self.registry, name, values).unwrap();
-        self
-    }
-
-    /// Set a boolean field
-    pub fn with_bool_field(mut self, name: &str, values: Vec<bool>) -> Self {
-        let values: Vec<Value> = values.into_iter().map(Value::ScalarBool).collect();
-        self.bulk = self.bulk.set(&self.registry, name, values).unwrap();
-        self
-    }
-
-    /// Set a string field
-    pub fn with_string_field(mut self, name: &str, values: Vec<String>) -> Self {
-        let values: Vec<Value> = values.into_iter().map(Value::ScalarString).collect();
-        self.bulk = self.bulk.set(&self.registry, name, values).unwrap();
-        self
-    }
-
-    /// Build the bulk
-    pub fn build(self) -> (Bulk, Registry) {
-        (self.bulk, self.registry)
-    }
-}
+pub use soakit::builder::{BulkBuilder, RegistryBuilder};
 
 /// Assertion helpers for testing
 pub mod assertions {
@@ -195,15 +73,18 @@ pub mod fixtures {
             .with_int_field("age")
             .with_string_field("name")
             .build()
+            .unwrap()
     }
 
     /// Create a simple bulk with age and name data
     pub fn simple_bulk() -> (Bulk, Registry) {
         let registry = simple_registry();
-        BulkBuilder::new(3, registry)
+        let bulk = BulkBuilder::new()
             .with_int_field("age", vec![25, 30, 35])
-            .with_string_field("name", vec!["Alice".to_string(), "Bob".to_string(), "Charlie".to_string()])
-            .build()
+            .with_string_field("name", vec!["Alice", "Bob", "Charlie"])
+            .build(&registry)
+            .unwrap();
+        (bulk, registry)
     }
 
     /// Create a registry with derived field
@@ -222,6 +103,7 @@ pub mod fixtures {
             .with_int_field("b")
             .with_derived_field("sum", vec!["a".to_string(), "b".to_string()], sum_func)
             .build()
+            .unwrap()
     }
 }
 
@@ -0,0 +1,79 @@
+#![cfg(feature = "derive")]
+/// Tests for `#[derive(SoaSchema)]`
+use soakit::{Bulk, FieldType, Registry, SoAKitError, SoaSchema, Value};
+
+#[derive(SoaSchema)]
+struct Person {
+    name: String,
+    age: i64,
+    height: f64,
+    active: bool,
+}
+
+fn people() -> Vec<Person> {
+    vec![
+        Person {
+            name: "Alice".to_string(),
+            age: 30,
+            height: 1.7,
+            active: true,
+        },
+        Person {
+            name: "Bob".to_string(),
+            age: 25,
+            height: 1.8,
+            active: false,
+        },
+    ]
+}
+
+#[test]
+fn test_derive_registers_typed_fields() {
+    let mut registry = Registry::new();
+    Person::register_fields(&mut registry).unwrap();
+
+    assert_eq!(
+        Person::field_names(),
+        vec!["name", "age", "height", "active"]
+    );
+    let field_type = |field: &str| registry.get_metadata(field).unwrap().field_type;
+    assert_eq!(field_type("name"), Some(FieldType::String));
+    assert_eq!(field_type("age"), Some(FieldType::Int));
+    assert_eq!(field_type("height"), Some(FieldType::Float));
+    assert_eq!(field_type("active"), Some(FieldType::Bool));
+
+    assert!(matches!(
+        Person::register_fields(&mut registry),
+        Err(SoAKitError::FieldAlreadyExists(_))
+    ));
+}
+
+#[test]
+fn test_derive_typed_accessors() {
+    let mut registry = Registry::new();
+    Person::register_fields(&mut registry).unwrap();
+
+    let bulk = Bulk::from_structs(&registry, people()).unwrap();
+    assert_eq!(bulk.count(), 2);
+    assert_eq!(Person::get_age(&bulk, &registry).unwrap(), vec![30, 25]);
+    assert_eq!(
+        Person::get_name(&bulk, &registry).unwrap(),
+        vec!["Alice", "Bob"]
+    );
+    assert_eq!(
+        Person::get_active(&bulk, &registry).unwrap(),
+        vec![true, false]
+    );
+
+    let bulk = Person::set_height(&bulk, &registry, vec![1.6, 1.9]).unwrap();
+    assert_eq!(
+        bulk.get(&registry, "height").unwrap(),
+        Value::VectorFloat(vec![1.6, 1.9])
+    );
+    assert!(matches!(
+        Person::set_age(&bulk, &registry, vec![1]),
+        Err(SoAKitError::LengthMismatch { .. })
+    ));
+
+    assert!(Bulk::from_structs(&registry, Vec::<Person>::new()).is_err());
+}
@@ -262,7 +262,7 @@ fn test_all_true_mask() {
 fn test_empty_view() {
     let bulk = Bulk::new(3).unwrap();
     let mask = vec![false, false, false];
-    let view = View::new(Value::ScalarInt(0), mask, std::rc::Rc::new(bulk)).unwrap();
+    let view = View::new(Value::ScalarInt(0), mask, std::sync::Arc::new(bulk)).unwrap();
     assert!(view.is_empty());
     assert_eq!(view.count(), 0);
 }
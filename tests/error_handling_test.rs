@@ -189,7 +189,7 @@ fn test_view_creation_errors() {
     let bulk = Bulk::new(5).unwrap();
     let mask = vec![true, false]; // Wrong length
 
-    let result = View::new(Value::ScalarInt(0), mask, std::rc::Rc::new(bulk));
+    let result = View::new(Value::ScalarInt(0), mask, std::sync::Arc::new(bulk));
     assert!(result.is_err());
     assert!(matches!(
         result.unwrap_err(),
@@ -202,7 +202,7 @@ fn test_view_get_field_errors() {
     let registry = Registry::new();
     let bulk = Bulk::new(3).unwrap();
     let mask = vec![true, false, true];
-    let view = View::new(Value::ScalarInt(0), mask, std::rc::Rc::new(bulk)).unwrap();
+    let view = View::new(Value::ScalarInt(0), mask, std::sync::Arc::new(bulk)).unwrap();
 
     // Field not found
     let result = view.get_field(&registry, "nonexistent");
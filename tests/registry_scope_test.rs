@@ -0,0 +1,73 @@
+#![cfg(feature = "testing")]
+/// Tests for scoped and resettable global registries
+use soakit::{Bulk, Registry, Value, get_registry, register_field, reset_global_registry};
+use soakit::{current_registry, init, with_registry};
+
+fn int_validator() -> Box<dyn Fn(&Value) -> bool + Send + Sync> {
+    Box::new(|v: &Value| matches!(v, Value::ScalarInt(_)))
+}
+
+#[test]
+fn test_reset_and_scoped_registries() {
+    register_field(
+        "global_only".to_string(),
+        int_validator(),
+        false,
+        vec![],
+        None,
+    )
+    .unwrap();
+
+    // The same name can be registered in a scope without conflicting
+    let (bulk, local) = with_registry(Registry::new(), || {
+        register_field(
+            "global_only".to_string(),
+            int_validator(),
+            false,
+            vec![],
+            None,
+        )
+        .unwrap();
+        let registry = current_registry();
+        let reg = registry.lock().unwrap();
+        let bulk = init(2).unwrap();
+        bulk.set(
+            &reg,
+            "global_only",
+            vec![Value::ScalarInt(1), Value::ScalarInt(2)],
+        )
+        .unwrap()
+    });
+    assert_eq!(local.list_fields(), vec!["global_only"]);
+    assert_eq!(
+        bulk.get(&local, "global_only").unwrap(),
+        Value::VectorInt(vec![1, 2])
+    );
+
+    // Other threads keep seeing the global registry during a scope
+    let ((), _) = with_registry(Registry::new(), || {
+        let seen = std::thread::spawn(|| get_registry().lock().unwrap().has_field("global_only"))
+            .join()
+            .unwrap();
+        assert!(seen);
+        assert!(!current_registry().lock().unwrap().has_field("global_only"));
+        assert!(get_registry().lock().unwrap().has_field("global_only"));
+    });
+
+    reset_global_registry();
+    assert!(get_registry().lock().unwrap().is_empty());
+    register_field(
+        "global_only".to_string(),
+        int_validator(),
+        false,
+        vec![],
+        None,
+    )
+    .unwrap();
+    let bulk = Bulk::new(1).unwrap();
+    let reg = get_registry().lock().unwrap();
+    assert!(
+        bulk.set(&reg, "global_only", vec![Value::ScalarInt(3)])
+            .is_ok()
+    );
+}
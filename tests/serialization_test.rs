@@ -1,6 +1,10 @@
 //! Tests for Bulk serialization and deserialization functionality.
 
-use soakit::{Bulk, Registry, Value};
+use soakit::{Bulk, Migrations, Registry, SortOrder, Value};
+
+/// A bulk written by `Bulk::to_binary` before binaries had a header: fields
+/// `active`, `age` (set twice), `name` and `score` over three elements.
+const PRE_HEADER_BULK: &[u8] = include_bytes!("fixtures/pre_header_bulk.bin");
 
 #[test]
 fn test_json_round_trip() {
@@ -68,6 +72,67 @@ fn test_binary_round_trip() {
     assert_eq!(deserialized_heights, original_heights);
 }
 
+#[test]
+fn test_binary_written_before_header() {
+    let mut registry = Registry::new();
+    registry.register_bool("active").unwrap();
+    registry.register_int("age").unwrap();
+    registry.register_string("name").unwrap();
+    registry.register_float("score").unwrap();
+
+    let bulk = Bulk::from_binary(PRE_HEADER_BULK).unwrap();
+    assert_eq!(bulk.meta.count, 3);
+    assert_eq!(bulk.meta.id, vec![0, 1, 2]);
+    assert_eq!(bulk.meta.versions["age"], 2);
+    assert_eq!(bulk.meta.schema_version, 0);
+    assert_eq!(
+        bulk.get(&registry, "active").unwrap(),
+        Value::VectorBool(vec![true, false, true])
+    );
+    assert_eq!(
+        bulk.get(&registry, "age").unwrap(),
+        Value::VectorInt(vec![26, 31, 36])
+    );
+    let names = ["ann", "bob", ""].map(String::from).to_vec();
+    assert_eq!(
+        bulk.get(&registry, "name").unwrap(),
+        Value::VectorString(names)
+    );
+    // Interned columns, sort orders and column versions did not exist yet
+    assert!(!bulk.is_interned("name"));
+    assert_eq!(bulk.sorted_order("age"), None);
+    assert_eq!(bulk.chunk_version("age", 0), Some(2));
+    // Custom identifiers did not exist either; elements are found by their ID
+    assert!(bulk.meta.custom_ids.is_empty());
+    assert_eq!(bulk.index_of_id(2u64), Some(2));
+    assert_eq!(bulk.deleted_count(), 0);
+    assert_eq!(
+        bulk.get(&registry, "score").unwrap(),
+        Value::VectorFloat(vec![1.5, -0.25, 1e300])
+    );
+
+    let migrated = Migrations::new()
+        .load_binary(PRE_HEADER_BULK, &registry)
+        .unwrap();
+    assert_eq!(migrated, bulk);
+
+    // Saving again writes the current layout, which reads back the same
+    let binary = bulk.to_binary().unwrap();
+    assert!(binary.starts_with(b"SOAKITB\0"));
+    assert_eq!(Bulk::from_binary(&binary).unwrap(), bulk);
+    let sorted = bulk
+        .sort_by(&registry, &[("score", SortOrder::Asc)])
+        .unwrap();
+    let restored = Bulk::from_binary(&sorted.to_binary().unwrap()).unwrap();
+    assert_eq!(restored.sorted_order("score"), Some(SortOrder::Asc));
+
+    // Layouts from a newer version and truncated headers are refused
+    let mut newer = binary.clone();
+    newer[8] = 0xff;
+    assert!(Bulk::from_binary(&newer).is_err());
+    assert!(Bulk::from_binary(&binary[..10]).is_err());
+}
+
 #[test]
 fn test_cache_invalidation_after_deserialization() {
     // Create a registry with a derived field